@@ -2,7 +2,7 @@ use proc_macro::TokenStream;
 use quote::{quote, ToTokens};
 use std::collections::HashSet;
 use syn::{
-    braced,
+    braced, bracketed,
     ext::IdentExt,
     parse::{Parse, ParseStream},
     parse_macro_input,
@@ -21,6 +21,33 @@ pub fn node_output(input: TokenStream) -> TokenStream {
     expand_node_ports(input, PortKind::Output)
 }
 
+/// Emits both `input_ports` and `output_ports`, rejecting any port name used twice
+/// across inputs and outputs. In edgeless graphs output names are data pool keys,
+/// so a shared name would clash at runtime.
+#[proc_macro]
+pub fn node_ports(input: TokenStream) -> TokenStream {
+    let ports = parse_macro_input!(input as NodePorts);
+
+    if let Err(err) = check_unique_names(ports.inputs.iter().chain(&ports.outputs)) {
+        return err.to_compile_error().into();
+    }
+
+    let inputs = match port_list_fn(ports.inputs, PortKind::Input) {
+        Ok(tokens) => tokens,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let outputs = match port_list_fn(ports.outputs, PortKind::Output) {
+        Ok(tokens) => tokens,
+        Err(err) => return err.to_compile_error().into(),
+    };
+
+    quote! {
+        #inputs
+        #outputs
+    }
+    .into()
+}
+
 enum PortKind {
     Input,
     Output,
@@ -29,40 +56,47 @@ enum PortKind {
 fn expand_node_ports(input: TokenStream, kind: PortKind) -> TokenStream {
     let ports = parse_macro_input!(input as PortList);
 
+    if let Err(err) = check_unique_names(&ports.ports) {
+        return err.to_compile_error().into();
+    }
+
+    match port_list_fn(ports.ports, kind) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn check_unique_names<'a>(ports: impl IntoIterator<Item = &'a PortSpec>) -> Result<()> {
     let mut seen_names: HashSet<String> = HashSet::new();
-    for port in &ports.ports {
+    for port in ports {
         if !seen_names.insert(port.name.value()) {
-            return syn::Error::new(
+            return Err(syn::Error::new(
                 port.name.span(),
                 format!("Duplicate port name '{}'", port.name.value()),
-            )
-            .to_compile_error()
-            .into();
+            ));
         }
     }
+    Ok(())
+}
 
-    let mut port_tokens: Vec<proc_macro2::TokenStream> = Vec::new();
-    for port in ports.ports {
-        match port.to_port_tokens() {
-            Ok(tokens) => port_tokens.push(tokens),
-            Err(err) => return err.to_compile_error().into(),
-        }
-    }
+fn port_list_fn(ports: Vec<PortSpec>, kind: PortKind) -> Result<proc_macro2::TokenStream> {
+    let port_tokens = ports
+        .into_iter()
+        .map(PortSpec::to_port_tokens)
+        .collect::<Result<Vec<_>>>()?;
 
     let fn_name = match kind {
         PortKind::Input => quote! { input_ports },
         PortKind::Output => quote! { output_ports },
     };
 
-    let expanded = quote! {
+    Ok(quote! {
         fn #fn_name(&self) -> ::std::vec::Vec<Port> {
             ::std::vec![
                 #(#port_tokens),*
             ]
         }
-    };
-
-    expanded.into()
+    })
 }
 
 struct PortList {
@@ -78,11 +112,44 @@ impl Parse for PortList {
     }
 }
 
+/// `inputs: [...], outputs: [...]`, in that order
+struct NodePorts {
+    inputs: Vec<PortSpec>,
+    outputs: Vec<PortSpec>,
+}
+
+impl Parse for NodePorts {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let inputs = parse_labeled_list(input, "inputs")?;
+        input.parse::<Token![,]>()?;
+        let outputs = parse_labeled_list(input, "outputs")?;
+        if input.peek(Token![,]) {
+            input.parse::<Token![,]>()?;
+        }
+        Ok(Self { inputs, outputs })
+    }
+}
+
+fn parse_labeled_list(input: ParseStream, label: &str) -> Result<Vec<PortSpec>> {
+    let ident: Ident = input.parse()?;
+    if ident != label {
+        return Err(syn::Error::new(ident.span(), format!("Expected '{}'", label)));
+    }
+    input.parse::<Token![:]>()?;
+    let content;
+    bracketed!(content in input);
+    Ok(content.parse::<PortList>()?.ports)
+}
+
 struct PortSpec {
     name: LitStr,
     data_type: Expr,
     description: Option<LitStr>,
     optional: bool,
+    default: Option<Expr>,
+    choices: Option<Vec<LitStr>>,
+    min: Option<Expr>,
+    max: Option<Expr>,
 }
 
 impl PortSpec {
@@ -97,6 +164,18 @@ impl PortSpec {
         if self.optional {
             tokens = quote! { #tokens.optional() };
         }
+        if let Some(default) = self.default {
+            tokens = quote! { #tokens.with_default(#default) };
+        }
+        if let Some(choices) = self.choices {
+            tokens = quote! { #tokens.with_choices([#(#choices),*]) };
+        }
+        if let Some(min) = self.min {
+            tokens = quote! { #tokens.with_min((#min) as f64) };
+        }
+        if let Some(max) = self.max {
+            tokens = quote! { #tokens.with_max((#max) as f64) };
+        }
         Ok(tokens)
     }
 }
@@ -131,6 +210,10 @@ fn parse_port_body(input: ParseStream) -> Result<PortSpec> {
     let mut data_type: Option<Expr> = None;
     let mut description: Option<LitStr> = None;
     let mut optional: Option<bool> = None;
+    let mut default: Option<Expr> = None;
+    let mut choices: Option<Vec<LitStr>> = None;
+    let mut min: Option<Expr> = None;
+    let mut max: Option<Expr> = None;
 
     for item in items {
         match item {
@@ -139,6 +222,10 @@ fn parse_port_body(input: ParseStream) -> Result<PortSpec> {
             PortAttr::Desc(value) => description = Some(value),
             PortAttr::Optional(value) => optional = Some(value),
             PortAttr::Required(value) => optional = Some(!value),
+            PortAttr::Default(value) => default = Some(value),
+            PortAttr::Choices(value) => choices = Some(value),
+            PortAttr::Min(value) => min = Some(value),
+            PortAttr::Max(value) => max = Some(value),
         }
     }
 
@@ -150,6 +237,10 @@ fn parse_port_body(input: ParseStream) -> Result<PortSpec> {
         data_type,
         description,
         optional: optional.unwrap_or(false),
+        default,
+        choices,
+        min,
+        max,
     })
 }
 
@@ -159,6 +250,10 @@ enum PortAttr {
     Desc(LitStr),
     Optional(bool),
     Required(bool),
+    Default(Expr),
+    Choices(Vec<LitStr>),
+    Min(Expr),
+    Max(Expr),
 }
 
 impl Parse for PortAttr {
@@ -175,6 +270,10 @@ impl Parse for PortAttr {
                 "desc" => Ok(PortAttr::Desc(input.parse()?)),
                 "optional" => Ok(PortAttr::Optional(parse_bool(input)?)),
                 "required" => Ok(PortAttr::Required(parse_bool(input)?)),
+                "default" => Ok(PortAttr::Default(input.parse()?)),
+                "choices" => Ok(PortAttr::Choices(parse_choices(input)?)),
+                "min" => Ok(PortAttr::Min(input.parse()?)),
+                "max" => Ok(PortAttr::Max(input.parse()?)),
                 _ => Err(syn::Error::new(ident.span(), "Unknown port attribute")),
             };
         }
@@ -186,6 +285,18 @@ impl Parse for PortAttr {
     }
 }
 
+fn parse_choices(input: ParseStream) -> Result<Vec<LitStr>> {
+    let content;
+    let bracket = bracketed!(content in input);
+    let choices: Vec<LitStr> = Punctuated::<LitStr, Token![,]>::parse_terminated(&content)?
+        .into_iter()
+        .collect();
+    if choices.is_empty() {
+        return Err(syn::Error::new(bracket.span.join(), "choices must not be empty"));
+    }
+    Ok(choices)
+}
+
 fn parse_bool(input: ParseStream) -> Result<bool> {
     if input.peek(LitBool) {
         let value: LitBool = input.parse()?;
@@ -245,6 +356,28 @@ fn datatype_tokens(expr: Expr) -> Result<proc_macro2::TokenStream> {
                 return Ok(quote! { DataType::List(Box::new(#inner_tokens)) });
             }
 
+            if func_name == "Map" {
+                if call.args.len() != 1 {
+                    return Err(syn::Error::new(call.span(), "Map() expects one argument"));
+                }
+                let inner = call.args.first().cloned().unwrap();
+                let inner_tokens = datatype_tokens(inner)?;
+                return Ok(quote! { DataType::Map(Box::new(#inner_tokens)) });
+            }
+
+            if func_name == "Tuple" {
+                if call.args.is_empty() {
+                    return Err(syn::Error::new(call.span(), "Tuple() expects at least one argument"));
+                }
+                let item_tokens = call
+                    .args
+                    .iter()
+                    .cloned()
+                    .map(datatype_tokens)
+                    .collect::<Result<Vec<_>>>()?;
+                return Ok(quote! { DataType::Tuple(::std::vec![#(#item_tokens),*]) });
+            }
+
             if func_name == "Custom" {
                 if call.args.len() != 1 {
                     return Err(syn::Error::new(call.span(), "Custom() expects one argument"));
@@ -0,0 +1,6 @@
+#[test]
+fn port_macro_expansion() {
+    let cases = trybuild::TestCases::new();
+    cases.pass("tests/ui/pass_*.rs");
+    cases.compile_fail("tests/ui/fail_*.rs");
+}
@@ -0,0 +1,11 @@
+use node_macros::node_input;
+
+include!("support/ports.rs");
+
+impl Ports {
+    node_input![
+        port! { name = "names", ty = Map(), desc = "names" },
+    ];
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+use node_macros::node_ports;
+
+include!("support/ports.rs");
+
+impl Ports {
+    node_ports! {
+        inputs: [
+            port! { name = "text", ty = String, desc = "text" },
+        ],
+        outputs: [
+            port! { name = "text", ty = String, desc = "text" },
+        ],
+    }
+}
+
+fn main() {}
@@ -0,0 +1,25 @@
+use node_macros::node_input;
+
+include!("support/ports.rs");
+
+impl Ports {
+    node_input![
+        port! { name = "names", ty = Map(String), desc = "names" },
+        port! { name = "scores", ty = Map(List(Integer)), desc = "scores" },
+        port! { name = "pair", ty = Tuple(String, Map(Integer)), desc = "pair" },
+    ];
+}
+
+fn main() {
+    let ports = Ports.input_ports();
+    assert_eq!(ports[0].data_type, DataType::Map(Box::new(DataType::String)));
+    assert_eq!(
+        ports[1].data_type,
+        DataType::Map(Box::new(DataType::List(Box::new(DataType::Integer))))
+    );
+    assert_eq!(
+        ports[2].data_type,
+        DataType::Tuple(vec![DataType::String, DataType::Map(Box::new(DataType::Integer))])
+    );
+    assert_eq!(ports[2].name, "pair");
+}
@@ -0,0 +1,19 @@
+use node_macros::node_ports;
+
+include!("support/ports.rs");
+
+impl Ports {
+    node_ports! {
+        inputs: [
+            port! { name = "text", ty = String, desc = "text" },
+        ],
+        outputs: [
+            port! { name = "length", ty = Integer, desc = "length" },
+        ],
+    }
+}
+
+fn main() {
+    assert_eq!(Ports.input_ports()[0].name, "text");
+    assert_eq!(Ports.output_ports()[0].name, "length");
+}
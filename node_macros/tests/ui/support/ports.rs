@@ -0,0 +1,26 @@
+// Minimal stand-ins for the types the macros expand to
+#[derive(Debug, PartialEq)]
+pub enum DataType {
+    String,
+    Integer,
+    List(Box<DataType>),
+    Map(Box<DataType>),
+    Tuple(Vec<DataType>),
+}
+
+pub struct Port {
+    pub name: &'static str,
+    pub data_type: DataType,
+}
+
+impl Port {
+    pub fn new(name: &'static str, data_type: DataType) -> Self {
+        Self { name, data_type }
+    }
+
+    pub fn with_description(self, _description: &str) -> Self {
+        self
+    }
+}
+
+pub struct Ports;
@@ -0,0 +1,79 @@
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tokio::task::block_in_place;
+
+/// Default time to wait for the bot server to answer an action
+pub const DEFAULT_ACTION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Tracks actions waiting for their response, keyed by the `echo` id sent with the action.
+/// Responses can arrive in any order, so each caller gets the response carrying its own echo.
+#[derive(Default)]
+pub struct PendingActions {
+    next_id: AtomicU64,
+    waiters: Mutex<HashMap<String, oneshot::Sender<Value>>>,
+}
+
+impl PendingActions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allocate a new echo id and the receiver its response will be delivered to
+    pub fn register(&self, action: &str) -> (String, oneshot::Receiver<Value>) {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let echo = format!("{}:{}", action, id);
+        let (tx, rx) = oneshot::channel();
+        self.waiters.lock().unwrap().insert(echo.clone(), tx);
+        (echo, rx)
+    }
+
+    /// Deliver a response to the caller waiting on `echo`.
+    /// Returns false if nobody is waiting (unknown echo, or the caller gave up).
+    pub fn resolve(&self, echo: &str, response: Value) -> bool {
+        match self.waiters.lock().unwrap().remove(echo) {
+            Some(tx) => tx.send(response).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Drop a waiter without a response, e.g. when sending the action failed
+    pub fn cancel(&self, echo: &str) {
+        self.waiters.lock().unwrap().remove(echo);
+    }
+
+    pub fn len(&self) -> usize {
+        self.waiters.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Block the current (sync) node thread until an action response arrives or `timeout` passes
+pub fn wait_response(
+    rx: oneshot::Receiver<Value>,
+    timeout: Duration,
+) -> std::result::Result<Value, String> {
+    let wait = async move {
+        match tokio::time::timeout(timeout, rx).await {
+            Ok(Ok(response)) => Ok(response),
+            Ok(Err(_)) => Err("Action was cancelled before a response arrived".to_string()),
+            Err(_) => Err(format!("Timed out after {:?} waiting for action response", timeout)),
+        }
+    };
+
+    if let Ok(handle) = tokio::runtime::Handle::try_current() {
+        block_in_place(|| handle.block_on(wait))
+    } else {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .map_err(|e| e.to_string())?;
+        runtime.block_on(wait)
+    }
+}
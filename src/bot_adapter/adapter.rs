@@ -2,11 +2,17 @@ use futures_util::StreamExt;
 use log::{debug, error, info, warn};
 use tokio_tungstenite::{connect_async, tungstenite::Message as WsMessage};
 
+use super::action::PendingActions;
 use super::event;
-use super::models::{MessageEvent, MessageType, Profile, RawMessageEvent};
+use super::health::{ConnectionMonitor, DEFAULT_HEARTBEAT_INTERVAL, DEFAULT_STALE_AFTER};
+use super::models::{BotEvent, MessageType, Profile};
+use super::protocol::{BotProtocol, OneBotV11Protocol};
+use super::send_queue::{write_frames, DelayedFrames, OutgoingFrame, SendRateLimiter};
 use crate::util::url_utils::extract_host;
 use crate::error::Result;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
 use tokio::sync::Mutex as TokioMutex;
 
 /// Trait for brain agents that handle event processing
@@ -24,12 +30,25 @@ impl Clone for AgentBox {
     }
 }
 
+/// Reconnection attempts after the WebSocket connection drops, before giving up
+pub const DEFAULT_WS_RECONNECT_MAX_ATTEMPTS: u32 = 3;
+/// Delay before the first reconnection attempt; doubled for each further attempt
+pub const DEFAULT_WS_RECONNECT_INTERVAL: Duration = Duration::from_secs(5);
+/// Upper bound of the reconnection delay
+const MAX_WS_RECONNECT_DELAY: Duration = Duration::from_secs(300);
+
 /// Configuration for BotAdapter initialization
 pub struct BotAdapterConfig {
     pub url: String,
     pub token: String,
     pub qq_id: String,
     pub brain_agent: Option<AgentBox>,
+    pub ws_reconnect_max_attempts: u32,
+    pub ws_reconnect_interval: Duration,
+    pub send_rate_limit: Option<Duration>,
+    pub target_send_rate_limit: Option<Duration>,
+    pub stale_after: Duration,
+    pub heartbeat_interval: Option<Duration>,
 }
 
 impl BotAdapterConfig {
@@ -43,6 +62,12 @@ impl BotAdapterConfig {
             token: token.into(),
             qq_id: qq_id.into(),
             brain_agent: None,
+            ws_reconnect_max_attempts: DEFAULT_WS_RECONNECT_MAX_ATTEMPTS,
+            ws_reconnect_interval: DEFAULT_WS_RECONNECT_INTERVAL,
+            send_rate_limit: None,
+            target_send_rate_limit: None,
+            stale_after: DEFAULT_STALE_AFTER,
+            heartbeat_interval: Some(DEFAULT_HEARTBEAT_INTERVAL),
         }
     }
 
@@ -50,6 +75,81 @@ impl BotAdapterConfig {
         self.brain_agent = agent;
         self
     }
+
+    /// Reconnect up to `max_attempts` times in a row when the WebSocket connection drops,
+    /// waiting `interval` before the first attempt and twice as long before each further one.
+    /// `max_attempts` of 0 disables reconnection.
+    pub fn with_ws_reconnect(mut self, max_attempts: u32, interval: Duration) -> Self {
+        self.ws_reconnect_max_attempts = max_attempts;
+        self.ws_reconnect_interval = interval;
+        self
+    }
+
+    /// Keep outgoing messages at least `min_interval` apart. Messages sent faster are queued
+    /// and drained at that rate instead of being dropped.
+    pub fn with_send_rate_limit(mut self, min_interval: Duration) -> Self {
+        self.send_rate_limit = Some(min_interval);
+        self
+    }
+
+    /// Additionally keep messages to the same user or group `min_interval` apart
+    pub fn with_target_send_rate_limit(mut self, min_interval: Duration) -> Self {
+        self.target_send_rate_limit = Some(min_interval);
+        self
+    }
+
+    /// Report the connection as stale once no frame has arrived for `stale_after`
+    pub fn with_stale_after(mut self, stale_after: Duration) -> Self {
+        self.stale_after = stale_after;
+        self
+    }
+
+    /// Send the protocol's heartbeat action every `interval` while connected, so an idle but
+    /// healthy connection keeps receiving frames. `None` disables heartbeats.
+    pub fn with_heartbeat_interval(mut self, interval: Option<Duration>) -> Self {
+        self.heartbeat_interval = interval;
+        self
+    }
+}
+
+/// Delay before reconnection attempt `attempt` (1-based)
+fn reconnect_delay(interval: Duration, attempt: u32) -> Duration {
+    let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+    interval.saturating_mul(factor).min(MAX_WS_RECONNECT_DELAY)
+}
+
+/// Periodically flip the connection to stale when frames stop arriving
+async fn watch_staleness(monitor: Arc<ConnectionMonitor>) {
+    let period = (monitor.stale_after() / 4).max(Duration::from_millis(10));
+    let mut ticker = tokio::time::interval(period);
+    loop {
+        ticker.tick().await;
+        monitor.check(Instant::now());
+    }
+}
+
+/// Send the protocol's heartbeat action every `interval`, starting one interval after connecting
+async fn send_heartbeats(adapter: SharedBotAdapter, interval: Duration) {
+    let mut ticker = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+    loop {
+        ticker.tick().await;
+        let guard = adapter.lock().await;
+        let Some((action, params)) = guard.protocol.heartbeat_action() else {
+            return;
+        };
+        if let Err(e) = guard.send_action(&action, params) {
+            debug!("Failed to send heartbeat: {}", e);
+        }
+    }
+}
+
+fn send_limiter(global: Option<Duration>, per_target: Option<Duration>) -> Option<Mutex<SendRateLimiter>> {
+    if global.is_none() && per_target.is_none() {
+        return None;
+    }
+    let limiter = SendRateLimiter::new(global.unwrap_or_default())
+        .with_per_target_interval(per_target.unwrap_or_default());
+    Some(Mutex::new(limiter))
 }
 
 /// BotAdapter connects to the QQ bot server via WebSocket and processes events
@@ -59,6 +159,20 @@ pub struct BotAdapter {
     bot_profile: Option<Profile>,
     brain_agent: Option<AgentBox>,
     event_handlers: Vec<event::EventHandler>,
+    notice_handlers: Vec<event::NoticeEventHandler>,
+    request_handlers: Vec<event::RequestEventHandler>,
+    protocol: Arc<dyn BotProtocol>,
+    /// Outgoing frames to the WebSocket writer task, set while connected
+    action_tx: Option<mpsc::UnboundedSender<OutgoingFrame>>,
+    pending_actions: Arc<PendingActions>,
+    /// Spaces out outgoing messages, `None` when sends are not rate limited
+    send_limiter: Option<Mutex<SendRateLimiter>>,
+    /// Rate limited frames not sent yet when the last connection ended
+    delayed_frames: DelayedFrames,
+    ws_reconnect_max_attempts: u32,
+    ws_reconnect_interval: Duration,
+    connection_monitor: Arc<ConnectionMonitor>,
+    heartbeat_interval: Option<Duration>,
 }
 
 /// Shared handle for BotAdapter that allows mutation inside async tasks
@@ -75,6 +189,17 @@ impl BotAdapter {
             }),
             brain_agent: config.brain_agent,
             event_handlers: Vec::new(),
+            notice_handlers: Vec::new(),
+            request_handlers: Vec::new(),
+            protocol: Arc::new(OneBotV11Protocol),
+            action_tx: None,
+            pending_actions: Arc::new(PendingActions::new()),
+            send_limiter: send_limiter(config.send_rate_limit, config.target_send_rate_limit),
+            delayed_frames: DelayedFrames::default(),
+            ws_reconnect_max_attempts: config.ws_reconnect_max_attempts,
+            ws_reconnect_interval: config.ws_reconnect_interval,
+            connection_monitor: Arc::new(ConnectionMonitor::new(config.stale_after)),
+            heartbeat_interval: config.heartbeat_interval,
         }
     }
 
@@ -103,26 +228,189 @@ impl BotAdapter {
         self.event_handlers.push(handler);
     }
 
+    /// Remove a handler added by `register_event_handler`, returning whether it was registered
+    pub fn unregister_event_handler(&mut self, handler: &event::EventHandler) -> bool {
+        let before = self.event_handlers.len();
+        self.event_handlers.retain(|registered| !Arc::ptr_eq(registered, handler));
+        self.event_handlers.len() != before
+    }
+
     pub fn get_event_handlers(&self) -> Vec<event::EventHandler> {
         self.event_handlers.clone()
     }
 
-    /// Start the WebSocket connection and begin processing events using a shared handle
+    pub fn register_notice_handler(&mut self, handler: event::NoticeEventHandler) {
+        self.notice_handlers.push(handler);
+    }
+
+    pub fn get_notice_handlers(&self) -> Vec<event::NoticeEventHandler> {
+        self.notice_handlers.clone()
+    }
+
+    pub fn register_request_handler(&mut self, handler: event::RequestEventHandler) {
+        self.request_handlers.push(handler);
+    }
+
+    pub fn get_request_handlers(&self) -> Vec<event::RequestEventHandler> {
+        self.request_handlers.clone()
+    }
+
+    pub fn get_protocol(&self) -> Arc<dyn BotProtocol> {
+        self.protocol.clone()
+    }
+
+    /// Health of the connection to the bot server, shared so it can be watched without the adapter lock
+    pub fn connection_monitor(&self) -> Arc<ConnectionMonitor> {
+        self.connection_monitor.clone()
+    }
+
+    /// Send an action (e.g. `set_friend_add_request`) to the bot server without waiting for its response
+    pub fn send_action(&self, action: &str, params: serde_json::Value) -> Result<()> {
+        let frame = serde_json::json!({
+            "action": action,
+            "params": params,
+        });
+        self.send_frame(frame).map(|_| ())
+    }
+
+    /// Send an action and return a receiver for its response.
+    /// The response is matched by the `echo` id, so concurrent calls never receive each other's results.
+    pub fn call_action(
+        &self,
+        action: &str,
+        params: serde_json::Value,
+    ) -> Result<tokio::sync::oneshot::Receiver<serde_json::Value>> {
+        self.call_action_queued(action, params).map(|(rx, _)| rx)
+    }
+
+    /// Like `call_action`, also returning how long the action waits in the send queue.
+    /// Callers waiting for the response should allow for that delay on top of their timeout.
+    pub fn call_action_queued(
+        &self,
+        action: &str,
+        params: serde_json::Value,
+    ) -> Result<(tokio::sync::oneshot::Receiver<serde_json::Value>, Duration)> {
+        let (echo, rx) = self.pending_actions.register(action);
+        let frame = serde_json::json!({
+            "action": action,
+            "params": params,
+            "echo": echo,
+        });
+        match self.send_frame(frame) {
+            Ok(delay) => Ok((rx, delay)),
+            Err(e) => {
+                self.pending_actions.cancel(&echo);
+                Err(e)
+            }
+        }
+    }
+
+    /// Queue a frame for the WebSocket writer, returning how long it waits for its rate limited turn
+    fn send_frame(&self, frame: serde_json::Value) -> Result<Duration> {
+        let action_tx = self.action_tx.as_ref().ok_or_else(|| {
+            crate::error::Error::ValidationError("Bot adapter is not connected".to_string())
+        })?;
+
+        let now = tokio::time::Instant::now();
+        let send_at = match (&self.send_limiter, frame.get("action").and_then(|a| a.as_str())) {
+            (Some(limiter), Some(action)) => self
+                .protocol
+                .message_target(action, &frame["params"])
+                .map(|target| limiter.lock().unwrap().reserve(&target, now)),
+            _ => None,
+        };
+        let delay = send_at.map(|at| at.saturating_duration_since(now)).unwrap_or_default();
+        if delay.is_zero() {
+            debug!("Sending action: {}", frame);
+        } else {
+            debug!("Queued action for {:?}: {}", delay, frame);
+        }
+
+        let frame = OutgoingFrame {
+            text: frame.to_string(),
+            send_at,
+        };
+        action_tx.send(frame).map_err(|_| {
+            crate::error::Error::ValidationError("Bot adapter connection is closed".to_string())
+        })?;
+        Ok(delay)
+    }
+
+    /// Route outgoing actions to a channel instead of the WebSocket, so a test can play the bot server:
+    /// read frames from the receiver and answer them through the returned pending actions
+    #[cfg(test)]
+    pub(crate) fn connect_mock(&mut self) -> (mpsc::UnboundedReceiver<OutgoingFrame>, Arc<PendingActions>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.action_tx = Some(tx);
+        (rx, self.pending_actions.clone())
+    }
+
+    /// Recall (delete) a message. Bots can always recall their own recent messages;
+    /// recalling others' messages needs group admin rights.
+    /// Returns a receiver for the server's response, see `call_action`.
+    pub fn recall_message(&self, message_id: i64) -> Result<tokio::sync::oneshot::Receiver<serde_json::Value>> {
+        let (action, params) = self.protocol.delete_msg_action(message_id);
+        self.call_action(&action, params)
+    }
+
+    /// Send a plain text message to a user (private) or a group
+    pub fn send_text(&self, message_type: MessageType, target_id: i64, text: &str) -> Result<()> {
+        let (action, params) = self.protocol.send_text_action(message_type, target_id, text);
+        self.send_action(&action, params)
+    }
+
+    /// Start the WebSocket connection and begin processing events using a shared handle.
+    ///
+    /// When the connection drops or can't be established, it is re-established with backoff as
+    /// configured by `with_ws_reconnect`. Handlers and the shared handle stay the same across
+    /// reconnects, so consumers keep receiving events. Returns the connection error once the
+    /// attempts are used up; a successful connection resets the count.
     pub async fn start(
         adapter: SharedBotAdapter,
     ) -> Result<()> {
-        let (url, token) = {
+        let (url, token, max_attempts, interval) = {
             let guard = adapter.lock().await;
-            (guard.url.clone(), guard.token.clone())
+            (
+                guard.url.clone(),
+                guard.token.clone(),
+                guard.ws_reconnect_max_attempts,
+                guard.ws_reconnect_interval,
+            )
         };
 
+        let mut attempt = 0;
+        loop {
+            match Self::run_connection(&adapter, &url, &token).await {
+                Ok(()) => {
+                    attempt = 0;
+                    if max_attempts == 0 {
+                        return Ok(());
+                    }
+                    warn!("Bot server connection lost");
+                }
+                Err(e) => {
+                    if attempt >= max_attempts {
+                        return Err(e);
+                    }
+                    warn!("Failed to connect to bot server: {}", e);
+                }
+            }
+            attempt += 1;
+            let delay = reconnect_delay(interval, attempt);
+            info!("Reconnecting to bot server in {:?} (attempt {}/{})", delay, attempt, max_attempts);
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Connect once and process events until the connection closes
+    async fn run_connection(adapter: &SharedBotAdapter, url: &str, token: &str) -> Result<()> {
         info!("Connecting to bot server at {}", url);
 
         // Build the WebSocket request with authorization header
         let request = http::Request::builder()
-            .uri(&url)
+            .uri(url)
             .header("Authorization", format!("Bearer {}", token))
-            .header("Host", extract_host(&url).unwrap_or("localhost"))
+            .header("Host", extract_host(url).unwrap_or("localhost"))
             .header("Connection", "Upgrade")
             .header("Upgrade", "websocket")
             .header("Sec-WebSocket-Version", "13")
@@ -135,10 +423,30 @@ impl BotAdapter {
         let (ws_stream, _) = connect_async(request).await?;
         info!("Connected to the qq bot server successfully.");
 
-        let (mut _write, mut read) = ws_stream.split();
+        let (write, mut read) = ws_stream.split();
+
+        // Forward outgoing actions to the WebSocket
+        let (action_tx, action_rx) = mpsc::unbounded_channel::<OutgoingFrame>();
+        let (monitor, heartbeat_interval, delayed_frames) = {
+            let mut guard = adapter.lock().await;
+            guard.action_tx = Some(action_tx);
+            (
+                guard.connection_monitor.clone(),
+                guard.heartbeat_interval,
+                std::mem::take(&mut guard.delayed_frames),
+            )
+        };
+        let writer = tokio::spawn(write_frames(action_rx, write, delayed_frames));
+
+        monitor.on_connected(Instant::now());
+        let watchdog = tokio::spawn(watch_staleness(monitor.clone()));
+        let heartbeat = heartbeat_interval.map(|interval| tokio::spawn(send_heartbeats(adapter.clone(), interval)));
 
         // Process incoming messages
         while let Some(msg_result) = read.next().await {
+            if msg_result.is_ok() {
+                monitor.on_frame(Instant::now());
+            }
             match msg_result {
                 Ok(WsMessage::Text(text)) => {
                     let adapter_clone = adapter.clone();
@@ -169,6 +477,25 @@ impl BotAdapter {
             }
         }
 
+        // The heartbeat task holds a sender too; stop it so the writer task can finish
+        watchdog.abort();
+        if let Some(heartbeat) = heartbeat {
+            heartbeat.abort();
+        }
+        adapter.lock().await.action_tx = None;
+        monitor.on_disconnected();
+
+        // With every sender gone the writer finishes and hands back what it couldn't send yet
+        match writer.await {
+            Ok(delayed_frames) => {
+                if !delayed_frames.is_empty() {
+                    info!("{} delayed action(s) will be sent after reconnecting", delayed_frames.len());
+                }
+                adapter.lock().await.delayed_frames = delayed_frames;
+            }
+            Err(e) => error!("Action writer task failed: {}", e),
+        }
+
         Ok(())
     }
 
@@ -185,36 +512,153 @@ impl BotAdapter {
             }
         };
 
-        // Check if this is a message event (has message_type field)
-        if message_json.get("message_type").is_none() {
-            debug!("Ignoring non-message event");
+        let (protocol, pending_actions) = {
+            let guard = adapter.lock().await;
+            (guard.get_protocol(), guard.pending_actions.clone())
+        };
+
+        // Responses to our own actions
+        if let Some(echo) = protocol.action_echo(&message_json) {
+            if !pending_actions.resolve(&echo, message_json) {
+                debug!("No pending action for response echo {}", echo);
+            }
             return;
         }
 
-        // Parse as RawMessageEvent
-        let raw_event: RawMessageEvent = match serde_json::from_value(message_json) {
-            Ok(e) => e,
+        let event = match protocol.parse_event(&message_json) {
+            Ok(Some(event)) => event,
+            Ok(None) => {
+                debug!("Ignoring unsupported event");
+                return;
+            }
             Err(e) => {
-                error!("Failed to parse message event: {}", e);
+                error!("Failed to parse event: {}", e);
                 return;
             }
         };
 
-        // Create the MessageEvent (messages are already deserialized in RawMessageEvent)
-        let event = MessageEvent {
-            message_id: raw_event.message_id,
-            message_type: raw_event.message_type,
-            sender: raw_event.sender.clone(),
-            message_list: raw_event.message.clone(),
-            group_id: raw_event.group_id,
-            group_name: raw_event.group_name.clone(),
-            is_group_message: matches!(raw_event.message_type, MessageType::Group),
-        };
-
-        // Dispatch to the unified message handler
+        // Dispatch to the matching handler
         let adapter_clone = adapter.clone();
         tokio::spawn(async move {
-            event::process_message(adapter_clone, event).await;
+            match event {
+                BotEvent::Message(event) => event::process_message(adapter_clone, event).await,
+                BotEvent::Notice(event) => event::process_notice(adapter_clone, event).await,
+                BotEvent::Request(event) => event::process_request(adapter_clone, event).await,
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::SinkExt;
+    use tokio::net::TcpListener;
+    use tokio::time::timeout;
+
+    const WAIT: Duration = Duration::from_secs(5);
+
+    fn message_event(message_id: i64) -> String {
+        serde_json::json!({
+            "post_type": "message",
+            "message_type": "private",
+            "message_id": message_id,
+            "sender": {"user_id": 42, "nickname": "tester"},
+            "message": [{"type": "text", "data": {"text": "hi"}}]
+        })
+        .to_string()
+    }
+
+    /// Play the bot server for one connection: push `event`, then hang up
+    async fn serve_once(listener: &TcpListener, event: String) {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+        ws.send(WsMessage::Text(event)).await.unwrap();
+        ws.close(None).await.unwrap();
+    }
+
+    #[test]
+    fn test_reconnect_delay_doubles_up_to_cap() {
+        let interval = Duration::from_secs(5);
+        assert_eq!(reconnect_delay(interval, 1), Duration::from_secs(5));
+        assert_eq!(reconnect_delay(interval, 3), Duration::from_secs(20));
+        assert_eq!(reconnect_delay(interval, 40), MAX_WS_RECONNECT_DELAY);
+    }
+
+    #[tokio::test]
+    async fn test_reconnects_after_server_restart() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let config = BotAdapterConfig::new(format!("ws://{}", addr), "token", "10000")
+            .with_ws_reconnect(5, Duration::from_millis(50));
+        let mut adapter = BotAdapter::new(config).await;
+        let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+        adapter.register_event_handler(Arc::new(move |event| {
+            let event_tx = event_tx.clone();
+            let message_id = event.message_id;
+            Box::pin(async move {
+                let _ = event_tx.send(message_id);
+            })
+        }));
+        let client = tokio::spawn(BotAdapter::start(adapter.into_shared()));
+
+        serve_once(&listener, message_event(1)).await;
+        assert_eq!(timeout(WAIT, event_rx.recv()).await.unwrap(), Some(1));
+
+        // Kill the server, then bring it back on the same address
+        drop(listener);
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        let listener = TcpListener::bind(addr).await.unwrap();
+        serve_once(&listener, message_event(2)).await;
+        assert_eq!(timeout(WAIT, event_rx.recv()).await.unwrap(), Some(2));
+
+        client.abort();
+    }
+
+    #[tokio::test]
+    async fn test_silent_connection_turns_stale() {
+        use crate::bot_adapter::health::ConnectionHealth;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        // Accept the connection and then say nothing
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            while ws.next().await.is_some() {}
         });
+
+        let config = BotAdapterConfig::new(format!("ws://{}", addr), "token", "10000")
+            .with_stale_after(Duration::from_millis(200))
+            .with_heartbeat_interval(None);
+        let adapter = BotAdapter::new(config).await;
+        let monitor = adapter.connection_monitor();
+        let mut health = monitor.subscribe();
+        assert_eq!(monitor.health(), ConnectionHealth::Disconnected);
+        let client = tokio::spawn(BotAdapter::start(adapter.into_shared()));
+
+        let connected_at = timeout(WAIT, async {
+            health.wait_for(|h| *h == ConnectionHealth::Connected).await.unwrap();
+            Instant::now()
+        })
+        .await
+        .unwrap();
+        timeout(WAIT, health.wait_for(|h| *h == ConnectionHealth::Stale)).await.unwrap().unwrap();
+        assert!(connected_at.elapsed() >= Duration::from_millis(150));
+
+        client.abort();
+        server.abort();
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_attempts() {
+        // Reserve a port, then free it so nothing is listening there
+        let addr = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+        let config = BotAdapterConfig::new(format!("ws://{}", addr), "token", "10000")
+            .with_ws_reconnect(2, Duration::from_millis(10));
+        let adapter = BotAdapter::new(config).await.into_shared();
+
+        let result = timeout(WAIT, BotAdapter::start(adapter)).await.unwrap();
+        assert!(result.is_err());
     }
 }
@@ -3,7 +3,7 @@ use std::sync::Arc;
 use std::future::Future;
 use std::pin::Pin;
 
-use super::models::{MessageEvent, MessageType};
+use super::models::{MessageEvent, MessageType, NoticeEvent, RequestEvent};
 use crate::bot_adapter::adapter::SharedBotAdapter;
 
 /// Process messages (both private and group)
@@ -59,9 +59,63 @@ pub async fn process_message(bot_adapter: SharedBotAdapter, event: MessageEvent)
     }
 }
 
+/// Process notice events (group member changes, recalls, ...)
+pub async fn process_notice(bot_adapter: SharedBotAdapter, event: NoticeEvent) {
+    info!(
+        "[Notice] [Type: {}/{}] [Group: {}] [User: {}]",
+        event.notice_type,
+        event.sub_type.as_deref().unwrap_or_default(),
+        event.group_id.unwrap_or_default(),
+        event.user_id.unwrap_or_default()
+    );
+
+    let handlers = {
+        let bot_adapter_guard = bot_adapter.lock().await;
+        bot_adapter_guard.get_notice_handlers()
+    };
+
+    for handler in handlers {
+        (handler)(&event).await;
+    }
+}
+
+/// Process request events (friend requests, group join requests and invites)
+pub async fn process_request(bot_adapter: SharedBotAdapter, event: RequestEvent) {
+    info!(
+        "[Request] [Type: {}] [User: {}] [Group: {}] Comment: {}",
+        event.request_type,
+        event.user_id,
+        event.group_id.unwrap_or_default(),
+        event.comment
+    );
+
+    let handlers = {
+        let bot_adapter_guard = bot_adapter.lock().await;
+        bot_adapter_guard.get_request_handlers()
+    };
+
+    for handler in handlers {
+        (handler)(&event).await;
+    }
+}
+
 /// Event handler type alias
 pub type EventHandler = Arc<
     dyn for<'a> Fn(&'a MessageEvent) -> Pin<Box<dyn Future<Output = ()> + Send + 'a>>
         + Send
         + Sync,
 >;
+
+/// Notice event handler type alias
+pub type NoticeEventHandler = Arc<
+    dyn for<'a> Fn(&'a NoticeEvent) -> Pin<Box<dyn Future<Output = ()> + Send + 'a>>
+        + Send
+        + Sync,
+>;
+
+/// Request event handler type alias
+pub type RequestEventHandler = Arc<
+    dyn for<'a> Fn(&'a RequestEvent) -> Pin<Box<dyn Future<Output = ()> + Send + 'a>>
+        + Send
+        + Sync,
+>;
@@ -0,0 +1,232 @@
+use crate::bot_adapter::action::{wait_response, DEFAULT_ACTION_TIMEOUT};
+use crate::bot_adapter::adapter::SharedBotAdapter;
+use crate::bot_adapter::models::GroupMemberInfo;
+use crate::error::Result;
+use crate::node::{node_input, node_output, DataType, DataValue, Node, Port};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+const DEFAULT_CACHE_TTL_SECS: i64 = 300;
+
+/// Source of group member information, implemented by the bot adapter
+pub trait GroupMemberLookup {
+    /// Returns `Ok(None)` when the server says the user is not a member of the group, and
+    /// an error for any other failure, so that it isn't mistaken for (and cached as) a miss
+    fn lookup_member(&self, group_id: i64, user_id: i64) -> std::result::Result<Option<GroupMemberInfo>, String>;
+}
+
+impl GroupMemberLookup for SharedBotAdapter {
+    fn lookup_member(&self, group_id: i64, user_id: i64) -> std::result::Result<Option<GroupMemberInfo>, String> {
+        // Only hold the adapter lock while sending; the response is delivered by the reader task
+        let (protocol, rx) = {
+            let guard = self.blocking_lock();
+            let protocol = guard.get_protocol();
+            let (action, params) = protocol.group_member_info_action(group_id, user_id);
+            let rx = guard.call_action(&action, params).map_err(|e| e.to_string())?;
+            (protocol, rx)
+        };
+
+        let response = wait_response(rx, DEFAULT_ACTION_TIMEOUT)?;
+        match protocol.action_result(&response) {
+            Ok(Value::Null) => Ok(None),
+            Ok(data) => serde_json::from_value(data)
+                .map(Some)
+                .map_err(|e| format!("Invalid group member info: {}", e)),
+            // Servers answer with an error status for users that are not in the group
+            Err(_) if protocol.is_member_not_found(&response) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Per-node cache of member lookups, including misses
+struct MemberCache {
+    ttl: Duration,
+    entries: HashMap<(i64, i64), (Instant, Option<GroupMemberInfo>)>,
+}
+
+impl MemberCache {
+    fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: HashMap::new(),
+        }
+    }
+
+    fn get_or_lookup(
+        &mut self,
+        lookup: &dyn GroupMemberLookup,
+        group_id: i64,
+        user_id: i64,
+        now: Instant,
+    ) -> std::result::Result<Option<GroupMemberInfo>, String> {
+        if let Some((fetched_at, info)) = self.entries.get(&(group_id, user_id)) {
+            if now.saturating_duration_since(*fetched_at) < self.ttl {
+                return Ok(info.clone());
+            }
+        }
+
+        let info = lookup.lookup_member(group_id, user_id)?;
+        self.entries.insert((group_id, user_id), (now, info.clone()));
+        Ok(info)
+    }
+}
+
+/// GroupMemberNode - looks up a group member's card, role and title, with caching
+pub struct GroupMemberNode {
+    id: String,
+    name: String,
+    cache: MemberCache,
+}
+
+impl GroupMemberNode {
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            cache: MemberCache::new(Duration::from_secs(DEFAULT_CACHE_TTL_SECS as u64)),
+        }
+    }
+}
+
+impl Node for GroupMemberNode {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> Option<&str> {
+        Some("群成员信息 - 查询群成员的群名片、角色和头衔，结果会缓存")
+    }
+
+    node_input![
+        port! { name = "bot_adapter", ty = BotAdapterRef, desc = "Bot适配器引用" },
+        port! { name = "group_id", ty = Integer, desc = "群号" },
+        port! { name = "user_id", ty = Integer, desc = "成员QQ号" },
+        port! { name = "cache_ttl_secs", ty = Integer, desc = "缓存有效秒数 (默认: 300)", optional },
+    ];
+
+    node_output![
+        port! { name = "card", ty = String, desc = "群名片" },
+        port! { name = "role", ty = String, desc = "角色: owner/admin/member" },
+        port! { name = "title", ty = String, desc = "专属头衔" },
+        port! { name = "found", ty = Boolean, desc = "是否找到该成员" },
+    ];
+
+    fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+        self.validate_inputs(&inputs)?;
+
+        let adapter = match inputs.get("bot_adapter") {
+            Some(DataValue::BotAdapterRef(adapter)) => adapter.clone(),
+            _ => return Err(crate::error::Error::InvalidNodeInput("bot_adapter is required".to_string())),
+        };
+        let integer = |name: &str| match inputs.get(name) {
+            Some(DataValue::Integer(i)) => Some(*i),
+            _ => None,
+        };
+        let (group_id, user_id) = match (integer("group_id"), integer("user_id")) {
+            (Some(group_id), Some(user_id)) => (group_id, user_id),
+            _ => {
+                return Err(crate::error::Error::InvalidNodeInput(
+                    "group_id and user_id are required".to_string(),
+                ))
+            }
+        };
+        let ttl_secs = integer("cache_ttl_secs").unwrap_or(DEFAULT_CACHE_TTL_SECS).max(0);
+        self.cache.ttl = Duration::from_secs(ttl_secs as u64);
+
+        let info = self
+            .cache
+            .get_or_lookup(&adapter, group_id, user_id, Instant::now())
+            .map_err(crate::error::Error::ValidationError)?;
+
+        let mut outputs = HashMap::new();
+        let found = info.is_some();
+        let info = info.unwrap_or_default();
+        outputs.insert("card".to_string(), DataValue::String(info.card));
+        outputs.insert("role".to_string(), DataValue::String(info.role));
+        outputs.insert("title".to_string(), DataValue::String(info.title));
+        outputs.insert("found".to_string(), DataValue::Boolean(found));
+
+        self.validate_outputs(&outputs)?;
+        Ok(outputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    struct MockLookup {
+        calls: Cell<usize>,
+    }
+
+    impl GroupMemberLookup for MockLookup {
+        fn lookup_member(&self, group_id: i64, user_id: i64) -> std::result::Result<Option<GroupMemberInfo>, String> {
+            self.calls.set(self.calls.get() + 1);
+            Ok(Some(GroupMemberInfo {
+                group_id,
+                user_id,
+                nickname: "alice".to_string(),
+                card: "Alice".to_string(),
+                role: "admin".to_string(),
+                title: "Helper".to_string(),
+            }))
+        }
+    }
+
+    #[test]
+    fn test_member_cache_avoids_repeat_queries_within_ttl() {
+        let lookup = MockLookup { calls: Cell::new(0) };
+        let mut cache = MemberCache::new(Duration::from_secs(60));
+        let now = Instant::now();
+
+        let first = cache.get_or_lookup(&lookup, 1, 2, now).unwrap().unwrap();
+        assert_eq!(first.card, "Alice");
+        assert_eq!(first.role, "admin");
+        assert_eq!(first.title, "Helper");
+
+        let second = cache
+            .get_or_lookup(&lookup, 1, 2, now + Duration::from_secs(30))
+            .unwrap();
+        assert_eq!(second, Some(first));
+        assert_eq!(lookup.calls.get(), 1);
+
+        // Expired entries are fetched again
+        cache
+            .get_or_lookup(&lookup, 1, 2, now + Duration::from_secs(61))
+            .unwrap();
+        assert_eq!(lookup.calls.get(), 2);
+    }
+
+    /// Fails its first lookup, as a dropped connection would
+    struct FlakyLookup {
+        calls: Cell<usize>,
+    }
+
+    impl GroupMemberLookup for FlakyLookup {
+        fn lookup_member(&self, _group_id: i64, _user_id: i64) -> std::result::Result<Option<GroupMemberInfo>, String> {
+            self.calls.set(self.calls.get() + 1);
+            if self.calls.get() == 1 {
+                return Err("Action timed out".to_string());
+            }
+            Ok(Some(GroupMemberInfo::default()))
+        }
+    }
+
+    #[test]
+    fn test_member_cache_does_not_cache_failed_lookups() {
+        let lookup = FlakyLookup { calls: Cell::new(0) };
+        let mut cache = MemberCache::new(Duration::from_secs(60));
+        let now = Instant::now();
+
+        assert!(cache.get_or_lookup(&lookup, 1, 2, now).is_err());
+        assert!(cache.get_or_lookup(&lookup, 1, 2, now).unwrap().is_some());
+        assert_eq!(lookup.calls.get(), 2);
+    }
+}
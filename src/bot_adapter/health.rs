@@ -0,0 +1,156 @@
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+/// Time without any frame from the bot server before the connection counts as stale
+pub const DEFAULT_STALE_AFTER: Duration = Duration::from_secs(90);
+/// Interval of heartbeat actions sent to the bot server to keep frames flowing
+pub const DEFAULT_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Health of the WebSocket connection to the bot server
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionHealth {
+    /// Connected and frames arrive regularly
+    Connected,
+    /// Still connected, but nothing has arrived for too long; the connection is probably dead
+    Stale,
+    /// Not connected, or reconnecting
+    Disconnected,
+}
+
+impl ConnectionHealth {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConnectionHealth::Connected => "Connected",
+            ConnectionHealth::Stale => "Stale",
+            ConnectionHealth::Disconnected => "Disconnected",
+        }
+    }
+}
+
+impl fmt::Display for ConnectionHealth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Tracks when the last frame arrived and derives the connection health from it.
+///
+/// The adapter reports connects, frames and disconnects; `check` is called periodically
+/// and flips a silent connection to `Stale`. Subscribers are notified on every change.
+pub struct ConnectionMonitor {
+    stale_after: Duration,
+    last_frame_at: Mutex<Option<Instant>>,
+    health: watch::Sender<ConnectionHealth>,
+}
+
+impl ConnectionMonitor {
+    pub fn new(stale_after: Duration) -> Self {
+        let (health, _) = watch::channel(ConnectionHealth::Disconnected);
+        Self {
+            stale_after,
+            last_frame_at: Mutex::new(None),
+            health,
+        }
+    }
+
+    pub fn stale_after(&self) -> Duration {
+        self.stale_after
+    }
+
+    pub fn health(&self) -> ConnectionHealth {
+        *self.health.borrow()
+    }
+
+    /// Receiver that sees every health change
+    pub fn subscribe(&self) -> watch::Receiver<ConnectionHealth> {
+        self.health.subscribe()
+    }
+
+    /// Time the last frame arrived, `None` before the first connection
+    pub fn last_frame_at(&self) -> Option<Instant> {
+        *self.last_frame_at.lock().unwrap()
+    }
+
+    /// The connection was just established; counts as a received frame
+    pub fn on_connected(&self, now: Instant) {
+        self.on_frame(now);
+    }
+
+    /// A frame (event, action response or heartbeat) arrived
+    pub fn on_frame(&self, now: Instant) {
+        *self.last_frame_at.lock().unwrap() = Some(now);
+        self.set(ConnectionHealth::Connected);
+    }
+
+    pub fn on_disconnected(&self) {
+        self.set(ConnectionHealth::Disconnected);
+    }
+
+    /// Mark the connection stale if no frame arrived within `stale_after` of `now`
+    pub fn check(&self, now: Instant) -> ConnectionHealth {
+        if self.health() == ConnectionHealth::Connected {
+            let silent_for = self
+                .last_frame_at()
+                .map(|at| now.saturating_duration_since(at))
+                .unwrap_or_default();
+            if silent_for >= self.stale_after {
+                self.set(ConnectionHealth::Stale);
+            }
+        }
+        self.health()
+    }
+
+    fn set(&self, health: ConnectionHealth) {
+        self.health.send_if_modified(|current| {
+            let changed = *current != health;
+            *current = health;
+            changed
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_silence_flips_connected_to_stale() {
+        let monitor = ConnectionMonitor::new(Duration::from_secs(30));
+        let start = Instant::now();
+        assert_eq!(monitor.health(), ConnectionHealth::Disconnected);
+        // Nothing to go stale before the first connection
+        assert_eq!(monitor.check(start + Duration::from_secs(60)), ConnectionHealth::Disconnected);
+
+        monitor.on_connected(start);
+        assert_eq!(monitor.check(start + Duration::from_secs(29)), ConnectionHealth::Connected);
+        assert_eq!(monitor.check(start + Duration::from_secs(30)), ConnectionHealth::Stale);
+
+        // Any frame brings it back
+        monitor.on_frame(start + Duration::from_secs(31));
+        assert_eq!(monitor.health(), ConnectionHealth::Connected);
+        assert_eq!(monitor.check(start + Duration::from_secs(40)), ConnectionHealth::Connected);
+
+        monitor.on_disconnected();
+        assert_eq!(monitor.check(start + Duration::from_secs(120)), ConnectionHealth::Disconnected);
+    }
+
+    #[test]
+    fn test_subscribers_only_see_changes() {
+        let monitor = ConnectionMonitor::new(Duration::from_secs(30));
+        let mut rx = monitor.subscribe();
+        let start = Instant::now();
+
+        monitor.on_connected(start);
+        assert!(rx.has_changed().unwrap());
+        assert_eq!(*rx.borrow_and_update(), ConnectionHealth::Connected);
+
+        monitor.on_frame(start + Duration::from_secs(1));
+        assert!(!rx.has_changed().unwrap());
+
+        monitor.check(start + Duration::from_secs(60));
+        assert!(rx.has_changed().unwrap());
+        assert_eq!(*rx.borrow_and_update(), ConnectionHealth::Stale);
+    }
+}
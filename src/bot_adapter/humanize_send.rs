@@ -0,0 +1,208 @@
+use crate::bot_adapter::models::{MessageEvent, MessageType};
+use crate::error::Result;
+use crate::node::{node_input, node_output, DataType, DataValue, Node, Port};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const DEFAULT_CHARS_PER_SEC: f64 = 8.0;
+const DEFAULT_MAX_DELAY_SECS: f64 = 10.0;
+/// Longest chunk sent as a single message
+const MAX_CHUNK_CHARS: usize = 200;
+/// Sleep granularity while waiting, so cancellation is noticed quickly
+const STOP_CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Split content into chunks: one per non-empty line, long lines cut at MAX_CHUNK_CHARS
+pub fn split_chunks(content: &str) -> Vec<String> {
+    let mut chunks = Vec::new();
+    for line in content.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        let chars: Vec<char> = line.chars().collect();
+        for piece in chars.chunks(MAX_CHUNK_CHARS) {
+            chunks.push(piece.iter().collect());
+        }
+    }
+    chunks
+}
+
+/// Typing delay before each chunk, proportional to its length.
+/// If the total exceeds `max_delay_secs` all delays are scaled down to fit.
+pub fn typing_delays(chunks: &[String], chars_per_sec: f64, max_delay_secs: f64) -> Vec<Duration> {
+    if chars_per_sec <= 0.0 {
+        return vec![Duration::ZERO; chunks.len()];
+    }
+
+    let raw: Vec<f64> = chunks
+        .iter()
+        .map(|chunk| chunk.chars().count() as f64 / chars_per_sec)
+        .collect();
+    let total: f64 = raw.iter().sum();
+    let scale = if total > max_delay_secs && total > 0.0 {
+        max_delay_secs.max(0.0) / total
+    } else {
+        1.0
+    };
+
+    raw.into_iter()
+        .map(|secs| Duration::from_secs_f64(secs * scale))
+        .collect()
+}
+
+/// HumanizeSendNode - replies in chunks with a typing delay before each one
+///
+/// Inputs:
+///   - bot_adapter: BotAdapterRef used for sending
+///   - message_event: MessageEvent being replied to (decides the private/group target)
+///   - content: Reply content
+///   - chars_per_sec: Simulated typing speed (default: 8)
+///   - max_delay_secs: Cap on the total delay over all chunks (default: 10)
+///
+/// Outputs:
+///   - sent_count: Number of chunks sent
+pub struct HumanizeSendNode {
+    id: String,
+    name: String,
+    stop_flag: Option<Arc<AtomicBool>>,
+}
+
+impl HumanizeSendNode {
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            stop_flag: None,
+        }
+    }
+
+    fn is_stopped(&self) -> bool {
+        self.stop_flag
+            .as_ref()
+            .map(|flag| flag.load(Ordering::Relaxed))
+            .unwrap_or(false)
+    }
+
+    /// Sleep for `delay`, returning false if the graph was stopped meanwhile
+    fn wait(&self, delay: Duration) -> bool {
+        let deadline = Instant::now() + delay;
+        loop {
+            if self.is_stopped() {
+                return false;
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return true;
+            }
+            std::thread::sleep((deadline - now).min(STOP_CHECK_INTERVAL));
+        }
+    }
+}
+
+fn reply_target(event: &MessageEvent) -> (MessageType, i64) {
+    match (event.message_type, event.group_id) {
+        (MessageType::Group, Some(group_id)) => (MessageType::Group, group_id),
+        _ => (MessageType::Private, event.sender.user_id),
+    }
+}
+
+impl Node for HumanizeSendNode {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> Option<&str> {
+        Some("拟人发送 - 将回复拆分成多条，并按打字速度延迟发送")
+    }
+
+    node_input![
+        port! { name = "bot_adapter", ty = BotAdapterRef, desc = "Bot适配器引用" },
+        port! { name = "message_event", ty = MessageEvent, desc = "要回复的消息事件，决定发送到私聊还是群聊" },
+        port! { name = "content", ty = String, desc = "回复内容" },
+        port! { name = "chars_per_sec", ty = Float, desc = "模拟打字速度，每秒字符数 (默认: 8)", optional },
+        port! { name = "max_delay_secs", ty = Float, desc = "所有分段的总延迟上限秒数 (默认: 10)", optional },
+    ];
+
+    node_output![
+        port! { name = "sent_count", ty = Integer, desc = "已发送的分段数量" },
+    ];
+
+    fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+        self.validate_inputs(&inputs)?;
+
+        let adapter = match inputs.get("bot_adapter") {
+            Some(DataValue::BotAdapterRef(adapter)) => adapter.clone(),
+            _ => return Err(crate::error::Error::InvalidNodeInput("bot_adapter is required".to_string())),
+        };
+        let (message_type, target_id) = match inputs.get("message_event") {
+            Some(DataValue::MessageEvent(event)) => reply_target(event),
+            _ => return Err(crate::error::Error::InvalidNodeInput("message_event is required".to_string())),
+        };
+        let content = match inputs.get("content") {
+            Some(DataValue::String(s)) => s.clone(),
+            _ => return Err(crate::error::Error::InvalidNodeInput("content is required".to_string())),
+        };
+        let float_input = |name: &str, default: f64| match inputs.get(name) {
+            Some(DataValue::Float(f)) => *f,
+            Some(DataValue::Integer(i)) => *i as f64,
+            _ => default,
+        };
+        let chars_per_sec = float_input("chars_per_sec", DEFAULT_CHARS_PER_SEC);
+        let max_delay_secs = float_input("max_delay_secs", DEFAULT_MAX_DELAY_SECS);
+
+        let chunks = split_chunks(&content);
+        let delays = typing_delays(&chunks, chars_per_sec, max_delay_secs);
+
+        let mut sent_count = 0;
+        for (chunk, delay) in chunks.iter().zip(delays) {
+            if !self.wait(delay) {
+                break;
+            }
+            adapter.blocking_lock().send_text(message_type, target_id, chunk)?;
+            sent_count += 1;
+        }
+
+        let mut outputs = HashMap::new();
+        outputs.insert("sent_count".to_string(), DataValue::Integer(sent_count));
+
+        self.validate_outputs(&outputs)?;
+        Ok(outputs)
+    }
+
+    fn set_stop_flag(&mut self, stop_flag: Arc<AtomicBool>) {
+        self.stop_flag = Some(stop_flag);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_typing_delay_proportional_to_length() {
+        let chunks = vec!["a".repeat(20), "b".repeat(10)];
+        let delays = typing_delays(&chunks, 10.0, 60.0);
+        assert_eq!(delays, vec![Duration::from_secs(2), Duration::from_secs(1)]);
+    }
+
+    #[test]
+    fn test_typing_delay_capped_by_max_delay() {
+        let chunks = vec!["a".repeat(300), "b".repeat(100)];
+        let delays = typing_delays(&chunks, 10.0, 8.0);
+        let total: Duration = delays.iter().sum();
+        assert!((total.as_secs_f64() - 8.0).abs() < 1e-6);
+        // Proportions are kept when scaling down
+        assert!((delays[0].as_secs_f64() - 6.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_split_chunks() {
+        let long_line = "x".repeat(MAX_CHUNK_CHARS + 5);
+        let chunks = split_chunks(&format!("first\n\n  second  \n{}", long_line));
+        assert_eq!(chunks.len(), 4);
+        assert_eq!(chunks[1], "second");
+        assert_eq!(chunks[3].len(), 5);
+    }
+}
@@ -0,0 +1,146 @@
+use crate::bot_adapter::models::message::MessageSegment;
+use crate::error::Result;
+use crate::node::{node_input, node_output, DataType, DataValue, Node, Port};
+use std::collections::HashMap;
+
+/// Non-empty trimmed string input
+fn string_input<'a>(inputs: &'a HashMap<String, DataValue>, name: &str) -> Option<&'a str> {
+    match inputs.get(name) {
+        Some(DataValue::String(s)) if !s.trim().is_empty() => Some(s.trim()),
+        _ => None,
+    }
+}
+
+/// Assemble the outgoing segments in the order QQ displays them: the quoted message,
+/// the mention, then text and image
+pub fn build_segments(
+    text: Option<&str>,
+    image_url: Option<&str>,
+    at_user_id: Option<&str>,
+    reply_message_id: Option<&str>,
+) -> Vec<MessageSegment> {
+    let mut segments = Vec::new();
+    if let Some(id) = reply_message_id {
+        segments.push(MessageSegment::reply(id));
+    }
+    if let Some(user_id) = at_user_id {
+        segments.push(MessageSegment::at(user_id));
+    }
+    if let Some(text) = text {
+        segments.push(MessageSegment::text(text));
+    }
+    if let Some(url) = image_url {
+        segments.push(MessageSegment::image(url));
+    }
+    segments
+}
+
+/// MessageBuilderNode - builds a message out of text, image, @ and reply segments
+///
+/// Inputs:
+///   - text: Message text
+///   - image_url: Image to attach (http(s) URL, file:// path or base64:// data)
+///   - at_user_id: QQ number to mention, "all" for everyone
+///   - reply_message_id: Id of the message to quote
+///
+/// Outputs:
+///   - segments: OneBot message array, accepted by the message sender's `segments` input
+pub struct MessageBuilderNode {
+    id: String,
+    name: String,
+}
+
+impl MessageBuilderNode {
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+        }
+    }
+}
+
+impl Node for MessageBuilderNode {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> Option<&str> {
+        Some("构建消息 - 由文本、图片、@和回复组成消息段")
+    }
+
+    node_input![
+        port! { name = "text", ty = String, desc = "消息文本", optional },
+        port! { name = "image_url", ty = String, desc = "图片地址，支持 http(s)、file:// 和 base64://", optional },
+        port! { name = "at_user_id", ty = String, desc = "要@的QQ号，all 表示全体成员", optional },
+        port! { name = "reply_message_id", ty = String, desc = "要回复的消息ID", optional },
+    ];
+
+    node_output![
+        port! { name = "segments", ty = Json, desc = "OneBot消息段数组" },
+    ];
+
+    fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+        self.validate_inputs(&inputs)?;
+
+        let segments = build_segments(
+            string_input(&inputs, "text"),
+            string_input(&inputs, "image_url"),
+            string_input(&inputs, "at_user_id"),
+            string_input(&inputs, "reply_message_id"),
+        );
+        if segments.is_empty() {
+            return Err(crate::error::Error::InvalidNodeInput(
+                "At least one of text, image_url, at_user_id or reply_message_id is required".to_string(),
+            ));
+        }
+
+        let mut outputs = HashMap::new();
+        outputs.insert("segments".to_string(), DataValue::Json(serde_json::to_value(&segments)?));
+
+        self.validate_outputs(&outputs)?;
+        Ok(outputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_builder_orders_segments() {
+        let mut node = MessageBuilderNode::new("builder", "Builder");
+        let inputs = HashMap::from([
+            ("text".to_string(), DataValue::String("看这个".to_string())),
+            ("image_url".to_string(), DataValue::String("https://example.com/a.png".to_string())),
+            ("at_user_id".to_string(), DataValue::String("42".to_string())),
+            ("reply_message_id".to_string(), DataValue::String("1001".to_string())),
+        ]);
+
+        let outputs = node.execute(inputs).unwrap();
+        let segments = match outputs.get("segments") {
+            Some(DataValue::Json(value)) => value.clone(),
+            other => panic!("expected segments json, got {:?}", other),
+        };
+        assert_eq!(
+            segments,
+            json!([
+                {"type": "reply", "data": {"id": "1001"}},
+                {"type": "at", "data": {"qq": "42"}},
+                {"type": "text", "data": {"text": "看这个"}},
+                {"type": "image", "data": {"file": "https://example.com/a.png"}}
+            ])
+        );
+    }
+
+    #[test]
+    fn test_builder_requires_some_content() {
+        let mut node = MessageBuilderNode::new("builder", "Builder");
+        let inputs = HashMap::from([("text".to_string(), DataValue::String("  ".to_string()))]);
+        assert!(node.execute(inputs).is_err());
+    }
+}
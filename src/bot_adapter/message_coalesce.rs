@@ -0,0 +1,447 @@
+use crate::bot_adapter::adapter::SharedBotAdapter;
+use crate::bot_adapter::event;
+use crate::bot_adapter::models::message::MessageProp;
+use crate::bot_adapter::models::MessageEvent;
+use crate::error::Result;
+use crate::node::{node_input, node_output, DataType, DataValue, Node, NodeType, Port};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+const DEFAULT_QUIET_MS: i64 = 1500;
+const DEFAULT_MAX_WINDOW_MS: i64 = 10000;
+/// Upper bound for a single wait so the stop flag is checked regularly
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// Number of recent message ids remembered to drop duplicate deliveries
+const SEEN_ID_CAPACITY: usize = 256;
+
+/// Messages are merged per sender within the same chat (private chat or a specific group)
+type SenderKey = (i64, Option<i64>);
+
+struct PendingBatch {
+    events: Vec<MessageEvent>,
+    first_at: Instant,
+    last_at: Instant,
+}
+
+/// Debounce state for MessageCoalesceNode.
+///
+/// Pure bookkeeping with caller-provided timestamps, so the merge rules can be tested
+/// without real waiting:
+/// - a batch is ready once `quiet` has passed since its last message
+/// - a batch is forced out once `max_window` has passed since its first message
+/// - messages inside a batch keep their arrival order
+pub struct MessageCoalescer {
+    quiet: Duration,
+    max_window: Duration,
+    pending: HashMap<SenderKey, PendingBatch>,
+}
+
+impl MessageCoalescer {
+    pub fn new(quiet: Duration, max_window: Duration) -> Self {
+        Self {
+            quiet,
+            max_window,
+            pending: HashMap::new(),
+        }
+    }
+
+    pub fn set_windows(&mut self, quiet: Duration, max_window: Duration) {
+        self.quiet = quiet;
+        self.max_window = max_window;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    pub fn push(&mut self, event: MessageEvent, now: Instant) {
+        let key = (event.sender.user_id, event.group_id);
+        let batch = self.pending.entry(key).or_insert_with(|| PendingBatch {
+            events: Vec::new(),
+            first_at: now,
+            last_at: now,
+        });
+        batch.events.push(event);
+        batch.last_at = now;
+    }
+
+    fn deadline_of(&self, batch: &PendingBatch) -> Instant {
+        (batch.last_at + self.quiet).min(batch.first_at + self.max_window)
+    }
+
+    /// Earliest instant at which some pending batch becomes ready
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.pending.values().map(|batch| self.deadline_of(batch)).min()
+    }
+
+    /// Remove the batch with the earliest deadline if it is due at `now`,
+    /// returning its events in arrival order
+    pub fn take_ready(&mut self, now: Instant) -> Option<Vec<MessageEvent>> {
+        let key = self
+            .pending
+            .iter()
+            .map(|(key, batch)| (self.deadline_of(batch), *key))
+            .filter(|(deadline, _)| *deadline <= now)
+            .min()
+            .map(|(_, key)| key)?;
+
+        self.pending.remove(&key).map(|batch| batch.events)
+    }
+}
+
+/// Merge events into one: header fields come from the latest event,
+/// message segments are concatenated in arrival order.
+pub fn merge_events(events: Vec<MessageEvent>) -> Option<MessageEvent> {
+    let mut iter = events.into_iter();
+    let mut merged = iter.next()?;
+    for event in iter {
+        merged.message_list.extend(event.message_list);
+        merged.message_id = event.message_id;
+        merged.sender = event.sender;
+        merged.group_name = event.group_name;
+    }
+    Some(merged)
+}
+
+/// MessageCoalesceNode - debounces consecutive messages from the same user into one event
+///
+/// Inputs:
+///   - message_event: MessageEvent that triggered this run
+///   - bot_adapter: BotAdapterRef used to receive follow-up messages while waiting
+///   - quiet_ms: Quiet period after the last message before emitting (default: 1500)
+///   - max_window_ms: Maximum time a batch may stay open (default: 10000)
+///
+/// Outputs:
+///   - merged_event: MessageEvent containing all merged message segments
+///   - content: Merged text, one line per original message
+///   - merged_count: Number of original messages merged
+pub struct MessageCoalesceNode {
+    id: String,
+    name: String,
+    coalescer: MessageCoalescer,
+    event_rx: Option<Mutex<Receiver<MessageEvent>>>,
+    /// Adapter and handler feeding `event_rx`, removed again in `on_cleanup`
+    subscription: Option<(SharedBotAdapter, event::EventHandler)>,
+    seen_ids: HashSet<i64>,
+    seen_order: VecDeque<i64>,
+    stop_flag: Option<Arc<AtomicBool>>,
+}
+
+impl MessageCoalesceNode {
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            coalescer: MessageCoalescer::new(
+                Duration::from_millis(DEFAULT_QUIET_MS as u64),
+                Duration::from_millis(DEFAULT_MAX_WINDOW_MS as u64),
+            ),
+            event_rx: None,
+            subscription: None,
+            seen_ids: HashSet::new(),
+            seen_order: VecDeque::new(),
+            stop_flag: None,
+        }
+    }
+
+    /// Returns false if the message was already buffered (the upstream adapter node
+    /// delivers the same events that our own subscription already received)
+    fn mark_seen(&mut self, message_id: i64) -> bool {
+        if !self.seen_ids.insert(message_id) {
+            return false;
+        }
+        self.seen_order.push_back(message_id);
+        if self.seen_order.len() > SEEN_ID_CAPACITY {
+            if let Some(oldest) = self.seen_order.pop_front() {
+                self.seen_ids.remove(&oldest);
+            }
+        }
+        true
+    }
+
+    fn buffer_event(&mut self, event: MessageEvent) {
+        if self.mark_seen(event.message_id) {
+            self.coalescer.push(event, Instant::now());
+        }
+    }
+
+    fn subscribe(&mut self, adapter: &SharedBotAdapter) {
+        let (event_tx, event_rx) = mpsc::channel::<MessageEvent>();
+        let handler: event::EventHandler = Arc::new(move |event| {
+            let event_tx = event_tx.clone();
+            Box::pin(async move {
+                let _ = event_tx.send(event.clone());
+            })
+        });
+        adapter.blocking_lock().register_event_handler(handler.clone());
+        self.event_rx = Some(Mutex::new(event_rx));
+        self.subscription = Some((adapter.clone(), handler));
+    }
+
+    fn is_stopped(&self) -> bool {
+        self.stop_flag
+            .as_ref()
+            .map(|flag| flag.load(Ordering::Relaxed))
+            .unwrap_or(false)
+    }
+
+    fn build_outputs(events: Vec<MessageEvent>) -> Option<HashMap<String, DataValue>> {
+        let merged_count = events.len() as i64;
+        let content = events
+            .iter()
+            .filter_map(|event| MessageProp::from_messages(&event.message_list, None).content)
+            .collect::<Vec<_>>()
+            .join("\n");
+        let merged = merge_events(events)?;
+
+        let mut outputs = HashMap::new();
+        outputs.insert("merged_event".to_string(), DataValue::MessageEvent(merged));
+        outputs.insert("content".to_string(), DataValue::String(content));
+        outputs.insert("merged_count".to_string(), DataValue::Integer(merged_count));
+        Some(outputs)
+    }
+}
+
+impl Node for MessageCoalesceNode {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn node_type(&self) -> NodeType {
+        NodeType::EventProducer
+    }
+
+    fn description(&self) -> Option<&str> {
+        Some("消息合并 - 等待同一用户停止输入后，将连续发送的多条消息合并为一条")
+    }
+
+    node_input![
+        port! { name = "message_event", ty = MessageEvent, desc = "触发本次运行的消息事件" },
+        port! { name = "bot_adapter", ty = BotAdapterRef, desc = "Bot适配器引用，用于在等待期间接收后续消息" },
+        port! { name = "quiet_ms", ty = Integer, desc = "最后一条消息后的静默等待毫秒数 (默认: 1500)", optional },
+        port! { name = "max_window_ms", ty = Integer, desc = "单次合并的最长等待毫秒数 (默认: 10000)", optional },
+    ];
+
+    node_output![
+        port! { name = "merged_event", ty = MessageEvent, desc = "合并后的消息事件" },
+        port! { name = "content", ty = String, desc = "合并后的消息文本" },
+        port! { name = "merged_count", ty = Integer, desc = "被合并的消息条数" },
+    ];
+
+    fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+        self.on_start(inputs)?;
+        let outputs = self.on_update()?.ok_or_else(|| {
+            crate::error::Error::ValidationError("No merged message produced".to_string())
+        })?;
+        Ok(outputs)
+    }
+
+    fn on_start(&mut self, inputs: HashMap<String, DataValue>) -> Result<()> {
+        self.validate_inputs(&inputs)?;
+
+        let quiet_ms = inputs
+            .get("quiet_ms")
+            .and_then(|v| match v {
+                DataValue::Integer(i) => Some(*i),
+                _ => None,
+            })
+            .unwrap_or(DEFAULT_QUIET_MS)
+            .max(0) as u64;
+        let max_window_ms = inputs
+            .get("max_window_ms")
+            .and_then(|v| match v {
+                DataValue::Integer(i) => Some(*i),
+                _ => None,
+            })
+            .unwrap_or(DEFAULT_MAX_WINDOW_MS)
+            .max(0) as u64;
+        self.coalescer.set_windows(
+            Duration::from_millis(quiet_ms),
+            Duration::from_millis(max_window_ms.max(quiet_ms)),
+        );
+
+        if self.event_rx.is_none() {
+            if let Some(DataValue::BotAdapterRef(adapter)) = inputs.get("bot_adapter") {
+                self.subscribe(adapter);
+            }
+        }
+
+        if let Some(DataValue::MessageEvent(event)) = inputs.get("message_event") {
+            self.buffer_event(event.clone());
+        }
+
+        Ok(())
+    }
+
+    fn on_update(&mut self) -> Result<Option<HashMap<String, DataValue>>> {
+        loop {
+            if self.is_stopped() {
+                return Ok(None);
+            }
+
+            let received: Vec<MessageEvent> = match &self.event_rx {
+                Some(rx) => rx.lock().unwrap().try_iter().collect(),
+                None => Vec::new(),
+            };
+            for event in received {
+                self.buffer_event(event);
+            }
+
+            let now = Instant::now();
+            if let Some(outputs) = self.coalescer.take_ready(now).and_then(Self::build_outputs) {
+                self.validate_outputs(&outputs)?;
+                return Ok(Some(outputs));
+            }
+
+            // Nothing pending: hand control back to the upstream producer
+            let Some(deadline) = self.coalescer.next_deadline() else {
+                return Ok(None);
+            };
+            let wait = deadline.saturating_duration_since(now).min(POLL_INTERVAL);
+
+            let received = match &self.event_rx {
+                Some(rx) => rx.lock().unwrap().recv_timeout(wait),
+                None => {
+                    std::thread::sleep(wait);
+                    Err(RecvTimeoutError::Timeout)
+                }
+            };
+            match received {
+                Ok(event) => self.buffer_event(event),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => self.event_rx = None,
+            }
+        }
+    }
+
+    fn on_cleanup(&mut self) -> Result<()> {
+        // Without this every run would leave a handler behind on the adapter
+        if let Some((adapter, handler)) = self.subscription.take() {
+            adapter.blocking_lock().unregister_event_handler(&handler);
+        }
+        self.event_rx = None;
+        Ok(())
+    }
+
+    fn set_stop_flag(&mut self, stop_flag: Arc<AtomicBool>) {
+        self.stop_flag = Some(stop_flag);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bot_adapter::models::message::{Message, PlainTextMessage};
+    use crate::bot_adapter::models::{MessageType, Sender};
+
+    fn text_event(message_id: i64, user_id: i64, text: &str) -> MessageEvent {
+        MessageEvent {
+            message_id,
+            message_type: MessageType::Private,
+            sender: Sender {
+                user_id,
+                nickname: "tester".to_string(),
+                card: String::new(),
+                role: None,
+            },
+            message_list: vec![Message::PlainText(PlainTextMessage { text: text.to_string() })],
+            group_id: None,
+            group_name: None,
+            is_group_message: false,
+        }
+    }
+
+    #[test]
+    fn test_rapid_messages_merge_into_one_emission() {
+        let start = Instant::now();
+        let mut coalescer =
+            MessageCoalescer::new(Duration::from_millis(500), Duration::from_millis(5000));
+
+        coalescer.push(text_event(1, 42, "first"), start);
+        coalescer.push(text_event(2, 42, "second"), start + Duration::from_millis(100));
+        coalescer.push(text_event(3, 42, "third"), start + Duration::from_millis(200));
+
+        // Still inside the quiet period of the last message
+        assert!(coalescer.take_ready(start + Duration::from_millis(600)).is_none());
+        assert_eq!(
+            coalescer.next_deadline(),
+            Some(start + Duration::from_millis(700))
+        );
+
+        let batch = coalescer
+            .take_ready(start + Duration::from_millis(700))
+            .expect("batch should be ready after the quiet period");
+        assert_eq!(batch.len(), 3);
+        let merged = merge_events(batch).unwrap();
+        let texts: Vec<String> = merged.message_list.iter().map(|m| m.to_string()).collect();
+        assert_eq!(texts, vec!["first", "second", "third"]);
+        assert_eq!(merged.message_id, 3);
+
+        assert!(coalescer.is_empty());
+        assert!(coalescer.take_ready(start + Duration::from_secs(10)).is_none());
+    }
+
+    #[test]
+    fn test_max_window_caps_merge() {
+        let start = Instant::now();
+        let mut coalescer =
+            MessageCoalescer::new(Duration::from_millis(500), Duration::from_millis(1000));
+
+        for i in 0..4 {
+            coalescer.push(text_event(i, 7, "msg"), start + Duration::from_millis(i as u64 * 300));
+        }
+
+        // The user keeps typing, but the batch is forced out once the window closes
+        let batch = coalescer
+            .take_ready(start + Duration::from_millis(1000))
+            .expect("window cap should force emission");
+        assert_eq!(batch.len(), 4);
+    }
+
+    #[test]
+    fn test_cleanup_removes_the_adapter_subscription() {
+        use crate::bot_adapter::adapter::{BotAdapter, BotAdapterConfig};
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let adapter = runtime
+            .block_on(BotAdapter::new(BotAdapterConfig::new("ws://localhost", "", "10000")))
+            .into_shared();
+        let handlers = || adapter.blocking_lock().get_event_handlers().len();
+
+        let mut node = MessageCoalesceNode::new("coalesce", "Coalesce");
+        let inputs = HashMap::from([
+            ("message_event".to_string(), DataValue::MessageEvent(text_event(1, 42, "hi"))),
+            ("bot_adapter".to_string(), DataValue::BotAdapterRef(adapter.clone())),
+        ]);
+        for _ in 0..2 {
+            node.on_start(inputs.clone()).unwrap();
+            assert_eq!(handlers(), 1);
+            node.on_cleanup().unwrap();
+            assert_eq!(handlers(), 0);
+        }
+    }
+
+    #[test]
+    fn test_different_senders_are_not_merged() {
+        let start = Instant::now();
+        let mut coalescer =
+            MessageCoalescer::new(Duration::from_millis(100), Duration::from_millis(1000));
+
+        coalescer.push(text_event(1, 1, "from one"), start);
+        coalescer.push(text_event(2, 2, "from two"), start);
+
+        let later = start + Duration::from_millis(200);
+        let first = coalescer.take_ready(later).unwrap();
+        let second = coalescer.take_ready(later).unwrap();
+        assert_eq!(first.len(), 1);
+        assert_eq!(second.len(), 1);
+        assert_ne!(first[0].sender.user_id, second[0].sender.user_id);
+    }
+}
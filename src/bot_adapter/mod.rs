@@ -1,5 +1,17 @@
+pub mod action;
 pub mod adapter;
 pub mod event;
 pub mod models;
 pub mod node_impl;
-pub mod extract_message_from_event;
\ No newline at end of file
+pub mod protocol;
+pub mod extract_message_from_event;
+pub mod message_coalesce;
+pub mod request_handler;
+pub mod humanize_send;
+pub mod group_member;
+pub mod send_file;
+pub mod message_builder;
+pub mod send_queue;
+pub mod notice;
+pub mod recall;
+pub mod health;
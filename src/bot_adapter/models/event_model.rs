@@ -30,6 +30,15 @@ impl MessageType {
             MessageType::Group => "group",
         }
     }
+
+    /// Parse "private" / "group", ignoring case and surrounding whitespace
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "private" => Some(MessageType::Private),
+            "group" => Some(MessageType::Group),
+            _ => None,
+        }
+    }
 }
 
 /// Sender information
@@ -70,6 +79,136 @@ pub struct RawMessageEvent {
     pub group_name: Option<String>,
 }
 
+impl From<RawMessageEvent> for MessageEvent {
+    fn from(raw: RawMessageEvent) -> Self {
+        Self {
+            message_id: raw.message_id,
+            is_group_message: matches!(raw.message_type, MessageType::Group),
+            message_type: raw.message_type,
+            sender: raw.sender,
+            message_list: raw.message,
+            group_id: raw.group_id,
+            group_name: raw.group_name,
+        }
+    }
+}
+
+/// Request type enum (friend request or group join/invite request)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RequestType {
+    Friend,
+    Group,
+}
+
+impl fmt::Display for RequestType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RequestType::Friend => write!(f, "friend"),
+            RequestType::Group => write!(f, "group"),
+        }
+    }
+}
+
+/// Request event (post_type = "request"), e.g. friend requests and group invites.
+/// `flag` must be echoed back when approving or rejecting the request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestEvent {
+    pub request_type: RequestType,
+    /// "add" or "invite" for group requests, absent for friend requests
+    #[serde(default)]
+    pub sub_type: Option<String>,
+    pub user_id: i64,
+    #[serde(default)]
+    pub group_id: Option<i64>,
+    #[serde(default)]
+    pub comment: String,
+    pub flag: String,
+}
+
+impl RequestEvent {
+    /// Whether this is an invitation of the bot itself into a group
+    pub fn is_group_invite(&self) -> bool {
+        self.request_type == RequestType::Group && self.sub_type.as_deref() == Some("invite")
+    }
+}
+
+/// Notice event (post_type = "notice"), e.g. group member increase/decrease, recalls
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoticeEvent {
+    pub notice_type: String,
+    #[serde(default)]
+    pub sub_type: Option<String>,
+    #[serde(default)]
+    pub group_id: Option<i64>,
+    #[serde(default)]
+    pub user_id: Option<i64>,
+    #[serde(default)]
+    pub operator_id: Option<i64>,
+}
+
+/// Notice or request event, passed between nodes as `DataType::NoticeEvent` so graphs
+/// can react to friend requests, group invites and members joining or leaving
+#[derive(Debug, Clone)]
+pub enum BotNotice {
+    Notice(NoticeEvent),
+    Request(RequestEvent),
+}
+
+impl BotNotice {
+    /// Short name of what happened, e.g. `friend_request`, `group_invite`, `member_join`.
+    /// Notices without a dedicated name keep their OneBot `notice_type`.
+    pub fn kind(&self) -> String {
+        match self {
+            BotNotice::Request(request) => match request.request_type {
+                RequestType::Friend => "friend_request".to_string(),
+                RequestType::Group if request.is_group_invite() => "group_invite".to_string(),
+                RequestType::Group => "group_join_request".to_string(),
+            },
+            BotNotice::Notice(notice) => match notice.notice_type.as_str() {
+                "group_increase" => "member_join".to_string(),
+                "group_decrease" => "member_leave".to_string(),
+                "friend_add" => "friend_added".to_string(),
+                other => other.to_string(),
+            },
+        }
+    }
+
+    pub fn user_id(&self) -> Option<i64> {
+        match self {
+            BotNotice::Notice(notice) => notice.user_id,
+            BotNotice::Request(request) => Some(request.user_id),
+        }
+    }
+
+    pub fn group_id(&self) -> Option<i64> {
+        match self {
+            BotNotice::Notice(notice) => notice.group_id,
+            BotNotice::Request(request) => request.group_id,
+        }
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut value = match self {
+            BotNotice::Notice(notice) => serde_json::to_value(notice),
+            BotNotice::Request(request) => serde_json::to_value(request),
+        }
+        .unwrap_or_default();
+        if let Some(object) = value.as_object_mut() {
+            object.insert("kind".to_string(), serde_json::Value::String(self.kind()));
+        }
+        value
+    }
+}
+
+/// Unified event surfaced by the bot protocol
+#[derive(Debug, Clone)]
+pub enum BotEvent {
+    Message(MessageEvent),
+    Notice(NoticeEvent),
+    Request(RequestEvent),
+}
+
 fn deserialize_message_vec_lenient<'de, D>(deserializer: D) -> Result<Vec<Message>, D::Error>
 where
     D: Deserializer<'de>,
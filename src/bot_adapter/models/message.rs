@@ -163,6 +163,40 @@ impl MessageBase for ReplyMessage {
     }
 }
 
+/// Segment of an outgoing message, serialized in the OneBot v11 message array format
+/// (`{"type": "text", "data": {"text": "..."}}`).
+///
+/// Incoming segments are parsed into [`Message`]; this is the sending side, where ids and
+/// image sources are passed through as the bot server expects them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "lowercase")]
+pub enum MessageSegment {
+    Text { text: String },
+    /// `file` is an http(s) URL, `file://` path or `base64://` data
+    Image { file: String },
+    /// `qq` is a QQ number, or "all" to mention everyone
+    At { qq: String },
+    Reply { id: String },
+}
+
+impl MessageSegment {
+    pub fn text(text: impl Into<String>) -> Self {
+        MessageSegment::Text { text: text.into() }
+    }
+
+    pub fn image(file: impl Into<String>) -> Self {
+        MessageSegment::Image { file: file.into() }
+    }
+
+    pub fn at(user_id: impl Into<String>) -> Self {
+        MessageSegment::At { qq: user_id.into() }
+    }
+
+    pub fn reply(message_id: impl Into<String>) -> Self {
+        MessageSegment::Reply { id: message_id.into() }
+    }
+}
+
 /// Abstracts and encapsulates the raw messages received by the bot, refining them into structured fields convenient for LLM processing:
 /// - `content`: The merged readable body (text/@/reply, etc.), used directly for feeding to the model
 /// - `ref_content`: Contextual summary from reference/reply chains (e.g., replied content), used to supplement context
@@ -268,6 +302,28 @@ mod tests {
         assert!(!prop.is_at_me);
     }
 
+    #[test]
+    fn test_message_segments_serialize_to_onebot_array() {
+        let segments = vec![
+            MessageSegment::reply("123"),
+            MessageSegment::at("42"),
+            MessageSegment::text(" hi"),
+            MessageSegment::image("https://example.com/a.png"),
+        ];
+        let value = serde_json::to_value(&segments).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!([
+                {"type": "reply", "data": {"id": "123"}},
+                {"type": "at", "data": {"qq": "42"}},
+                {"type": "text", "data": {"text": " hi"}},
+                {"type": "image", "data": {"file": "https://example.com/a.png"}}
+            ])
+        );
+        let parsed: Vec<MessageSegment> = serde_json::from_value(value).unwrap();
+        assert_eq!(parsed, segments);
+    }
+
     #[test]
     fn test_message_prop_dedup_at_targets() {
         let msgs = vec![
@@ -3,4 +3,23 @@ pub struct Profile {
     pub qq_id: String,
     pub nickname: String,
     pub age: u8,
-}
\ No newline at end of file
+}
+/// Group member information returned by `get_group_member_info`
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct GroupMemberInfo {
+    #[serde(default)]
+    pub group_id: i64,
+    #[serde(default)]
+    pub user_id: i64,
+    #[serde(default)]
+    pub nickname: String,
+    /// Group card (nickname inside the group), empty if not set
+    #[serde(default)]
+    pub card: String,
+    /// "owner", "admin" or "member"
+    #[serde(default)]
+    pub role: String,
+    /// Special title granted by the group owner
+    #[serde(default)]
+    pub title: String,
+}
@@ -1,23 +1,67 @@
-use crate::bot_adapter::adapter::{BotAdapter, BotAdapterConfig, SharedBotAdapter};
+use crate::bot_adapter::action::{wait_response, DEFAULT_ACTION_TIMEOUT};
+use crate::bot_adapter::adapter::{
+    BotAdapter, BotAdapterConfig, SharedBotAdapter, DEFAULT_WS_RECONNECT_INTERVAL, DEFAULT_WS_RECONNECT_MAX_ATTEMPTS,
+};
 use crate::bot_adapter::event;
-use crate::bot_adapter::models::event_model::MessageEvent;
+use crate::bot_adapter::health::ConnectionHealth;
+use crate::bot_adapter::models::event_model::{BotNotice, MessageEvent};
+use crate::bot_adapter::models::message::{Message, MessageProp, MessageSegment};
+use crate::bot_adapter::models::MessageType;
 use crate::error::Result;
 use crate::node::{node_input, node_output, DataType, DataValue, Node, NodeType, Port};
-use log::{error, info};
+use log::{error, info, warn};
+use serde_json::Value;
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::{mpsc, oneshot};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot, watch};
 use tokio::task::block_in_place;
 use tokio::sync::Mutex as TokioMutex;
 use tokio::select;
 
+/// Event forwarded from the adapter's handlers to the node
+enum IncomingEvent {
+    Message(MessageEvent),
+    Notice(BotNotice),
+    /// The connection health changed
+    Health(ConnectionHealth),
+}
+
 pub struct BotAdapterNode {
     id: String,
     name: String,
-    event_rx: Option<TokioMutex<mpsc::UnboundedReceiver<MessageEvent>>>,
+    event_rx: Option<TokioMutex<mpsc::UnboundedReceiver<IncomingEvent>>>,
     error_rx: Option<TokioMutex<mpsc::UnboundedReceiver<String>>>,
+    health_rx: Option<TokioMutex<watch::Receiver<ConnectionHealth>>>,
     adapter_handle: Option<SharedBotAdapter>,
     runtime: Option<tokio::runtime::Runtime>,
+    /// QQ id the adapter logged in with, to tell whether a message @-mentions the bot
+    bot_id: String,
+}
+
+/// Structured @-mention and reply information of a message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MentionInfo {
+    /// Mentioned user ids, deduplicated in order of appearance
+    pub at_targets: Vec<String>,
+    pub is_at_me: bool,
+    /// Id of the quoted message, if the message is a reply
+    pub reply_to_message_id: Option<i64>,
+}
+
+impl MentionInfo {
+    pub fn from_event(event: &MessageEvent, bot_id: &str) -> Self {
+        let prop = MessageProp::from_messages(&event.message_list, Some(bot_id));
+        let reply_to_message_id = event.message_list.iter().find_map(|message| match message {
+            Message::Reply(reply) => Some(reply.id),
+            _ => None,
+        });
+        Self {
+            at_targets: prop.at_target_list,
+            is_at_me: prop.is_at_me,
+            reply_to_message_id,
+        }
+    }
 }
 
 impl BotAdapterNode {
@@ -27,8 +71,10 @@ impl BotAdapterNode {
             name: name.into(),
             event_rx: None,
             error_rx: None,
+            health_rx: None,
             adapter_handle: None,
             runtime: None,
+            bot_id: String::new(),
         }
     }
 }
@@ -54,10 +100,19 @@ impl Node for BotAdapterNode {
         port! { name = "qq_id", ty = String, desc = "QQ ID to login" },
         port! { name = "bot_server_url", ty = String, desc = "Bot服务器WebSocket地址" },
         port! { name = "bot_server_token", ty = Password, desc = "Bot服务器连接令牌", optional },
+        port! { name = "ws_reconnect_max_attempts", ty = Integer, desc = "断线后最大重连次数 (默认: 3)", optional },
+        port! { name = "ws_reconnect_interval_secs", ty = Integer, desc = "首次重连间隔秒数，之后逐次翻倍 (默认: 5)", optional },
+        port! { name = "send_interval_ms", ty = Integer, desc = "两条发送消息之间的最小间隔毫秒数，超出频率的消息排队发送 (默认: 不限制)", optional },
+        port! { name = "emit_notices", ty = Boolean, desc = "是否输出好友申请、群邀请、成员进出群等通知事件 (默认: false)", optional },
     ];
 
     node_output![
         port! { name = "message_event", ty = MessageEvent, desc = "Raw message event from QQ server" },
+        port! { name = "at_targets", ty = List(String), desc = "QQ ids the message @-mentions" },
+        port! { name = "is_at_me", ty = Boolean, desc = "Whether the message @-mentions the bot" },
+        port! { name = "reply_to_message_id", ty = String, desc = "Id of the quoted message; only set for replies" },
+        port! { name = "connection_status", ty = String, desc = "Connection health: Connected, Stale (no frames for a while) or Disconnected; also emitted on its own whenever it changes" },
+        port! { name = "notice_event", ty = NoticeEvent, desc = "Notice or request event (friend request, group invite, member join/leave); only emitted when emit_notices is on" },
         port! { name = "bot_adapter", ty = BotAdapterRef, desc = "Shared reference to the bot adapter instance" },
     ];
 
@@ -83,6 +138,7 @@ impl Node for BotAdapterNode {
                 _ => None,
             })
             .unwrap_or_else(|| std::env::var("QQ_ID").unwrap_or_default());
+        self.bot_id = qq_id.clone();
 
         let bot_server_url = inputs
             .get("bot_server_url")
@@ -103,28 +159,70 @@ impl Node for BotAdapterNode {
             })
             .unwrap_or_else(|| std::env::var("BOT_SERVER_TOKEN").unwrap_or_default());
 
-        let adapter_config = BotAdapterConfig::new(
+        let max_attempts = inputs.get("ws_reconnect_max_attempts").and_then(|v| match v {
+            DataValue::Integer(i) => Some((*i).max(0) as u32),
+            _ => None,
+        });
+        let interval_secs = inputs.get("ws_reconnect_interval_secs").and_then(|v| match v {
+            DataValue::Integer(i) => Some((*i).max(0) as u64),
+            _ => None,
+        });
+
+        let send_interval_ms = inputs.get("send_interval_ms").and_then(|v| match v {
+            DataValue::Integer(i) if *i > 0 => Some(*i as u64),
+            _ => None,
+        });
+
+        let mut adapter_config = BotAdapterConfig::new(
             bot_server_url,
             bot_server_token,
             qq_id,
         )
-        .with_brain_agent(None);
+        .with_brain_agent(None)
+        .with_ws_reconnect(
+            max_attempts.unwrap_or(DEFAULT_WS_RECONNECT_MAX_ATTEMPTS),
+            interval_secs.map(Duration::from_secs).unwrap_or(DEFAULT_WS_RECONNECT_INTERVAL),
+        );
+        if let Some(ms) = send_interval_ms {
+            adapter_config = adapter_config.with_send_rate_limit(Duration::from_millis(ms));
+        }
+
+        let emit_notices = matches!(inputs.get("emit_notices"), Some(DataValue::Boolean(true)));
 
-        let (event_tx, event_rx) = mpsc::unbounded_channel::<MessageEvent>();
+        let (event_tx, event_rx) = mpsc::unbounded_channel::<IncomingEvent>();
         let (adapter_tx, adapter_rx) = oneshot::channel();
         let (error_tx, error_rx) = mpsc::unbounded_channel::<String>();
+        let message_tx = event_tx.clone();
         let handler: event::EventHandler = Arc::new(move |event| {
+            let message_tx = message_tx.clone();
+            Box::pin(async move {
+                let _ = message_tx.send(IncomingEvent::Message(event.clone()));
+            })
+        });
+        let notice_tx = event_tx.clone();
+        let notice_handler: event::NoticeEventHandler = Arc::new(move |notice| {
+            let notice_tx = notice_tx.clone();
+            Box::pin(async move {
+                let _ = notice_tx.send(IncomingEvent::Notice(BotNotice::Notice(notice.clone())));
+            })
+        });
+        let request_handler: event::RequestEventHandler = Arc::new(move |request| {
             let event_tx = event_tx.clone();
             Box::pin(async move {
-                let _ = event_tx.send(event.clone());
+                let _ = event_tx.send(IncomingEvent::Notice(BotNotice::Request(request.clone())));
             })
         });
 
         let run_adapter = async move {
             let mut adapter = BotAdapter::new(adapter_config).await;
             adapter.register_event_handler(handler);
+            if emit_notices {
+                adapter.register_notice_handler(notice_handler);
+                adapter.register_request_handler(request_handler);
+            }
+            let health_rx = adapter.connection_monitor().subscribe();
             let adapter = adapter.into_shared();
-            let _ = adapter_tx.send(adapter.clone());
+            let _ = adapter_tx.send((adapter.clone(), health_rx));
             info!("Bot adapter initialized, connecting to server...");
             if let Err(e) = BotAdapter::start(adapter).await {
                 error!("Bot adapter error: {}", e);
@@ -143,11 +241,12 @@ impl Node for BotAdapterNode {
             adapter
         };
 
-        let adapter_handle = adapter_handle.ok_or_else(|| {
+        let (adapter_handle, health_rx) = adapter_handle.ok_or_else(|| {
             crate::error::Error::ValidationError("Failed to receive bot adapter handle".to_string())
         })?;
 
         self.adapter_handle = Some(adapter_handle);
+        self.health_rx = Some(TokioMutex::new(health_rx));
         self.event_rx = Some(TokioMutex::new(event_rx));
         self.error_rx = Some(TokioMutex::new(error_rx));
 
@@ -159,60 +258,40 @@ impl Node for BotAdapterNode {
             crate::error::Error::ValidationError("Bot adapter is not initialized".to_string())
         })?;
         let error_rx = self.error_rx.as_ref();
-
-        let result = if let Ok(handle) = tokio::runtime::Handle::try_current() {
-            block_in_place(|| {
-                handle.block_on(async {
-                    if let Some(error_rx) = error_rx {
-                        select! {
-                            error_msg = async {
-                                let mut guard = error_rx.lock().await;
-                                guard.recv().await
-                            } => {
-                                if let Some(msg) = error_msg {
-                                    return Err(crate::error::Error::ValidationError(msg));
-                                }
-                                Ok(None)
-                            }
-                            event = async {
-                                let mut guard = event_rx.lock().await;
-                                guard.recv().await
-                            } => {
-                                Ok(event)
+        let health_rx = self.health_rx.as_ref();
+
+        let next_event = async {
+            select! {
+                error_msg = async {
+                    match error_rx {
+                        Some(error_rx) => error_rx.lock().await.recv().await,
+                        None => std::future::pending().await,
+                    }
+                } => match error_msg {
+                    Some(msg) => Err(crate::error::Error::ValidationError(msg)),
+                    None => Ok(None),
+                },
+                Some(health) = async {
+                    match health_rx {
+                        Some(health_rx) => {
+                            let mut guard = health_rx.lock().await;
+                            match guard.changed().await {
+                                Ok(()) => Some(*guard.borrow_and_update()),
+                                Err(_) => std::future::pending().await,
                             }
                         }
-                    } else {
-                        let mut guard = event_rx.lock().await;
-                        Ok(guard.recv().await)
+                        None => std::future::pending().await,
                     }
-                })
-            })
+                } => Ok(Some(IncomingEvent::Health(health))),
+                event = async { event_rx.lock().await.recv().await } => Ok(event),
+            }
+        };
+
+        let result = if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            block_in_place(|| handle.block_on(next_event))
         } else {
             let runtime = tokio::runtime::Runtime::new()?;
-            runtime.block_on(async {
-                if let Some(error_rx) = error_rx {
-                    select! {
-                        error_msg = async {
-                            let mut guard = error_rx.lock().await;
-                            guard.recv().await
-                        } => {
-                            if let Some(msg) = error_msg {
-                                return Err(crate::error::Error::ValidationError(msg));
-                            }
-                            Ok(None)
-                        }
-                        event = async {
-                            let mut guard = event_rx.lock().await;
-                            guard.recv().await
-                        } => {
-                            Ok(event)
-                        }
-                    }
-                } else {
-                    let mut guard = event_rx.lock().await;
-                    Ok(guard.recv().await)
-                }
-            })
+            runtime.block_on(next_event)
         };
 
         let event = match result? {
@@ -220,23 +299,73 @@ impl Node for BotAdapterNode {
             None => return Ok(None),
         };
 
+        // Only the port of the arrived event is set, so just that branch of the graph runs
         let mut outputs = HashMap::new();
-        outputs.insert("message_event".to_string(), DataValue::MessageEvent(event.clone()));
+        match event {
+            IncomingEvent::Message(event) => {
+                let mentions = MentionInfo::from_event(&event, &self.bot_id);
+                let at_targets = mentions.at_targets.into_iter().map(DataValue::String).collect();
+                outputs.insert("at_targets".to_string(), DataValue::List(at_targets));
+                outputs.insert("is_at_me".to_string(), DataValue::Boolean(mentions.is_at_me));
+                if let Some(reply_id) = mentions.reply_to_message_id {
+                    outputs.insert("reply_to_message_id".to_string(), DataValue::String(reply_id.to_string()));
+                }
+                outputs.insert("message_event".to_string(), DataValue::MessageEvent(event));
+            }
+            IncomingEvent::Notice(notice) => {
+                outputs.insert("notice_event".to_string(), DataValue::NoticeEvent(notice));
+            }
+            IncomingEvent::Health(health) => {
+                outputs.insert("connection_status".to_string(), DataValue::String(health.to_string()));
+            }
+        }
+        if !outputs.contains_key("connection_status") {
+            if let Some(health_rx) = &self.health_rx {
+                // Nothing else holds the receiver between updates
+                if let Ok(health_rx) = health_rx.try_lock() {
+                    let health = *health_rx.borrow();
+                    outputs.insert("connection_status".to_string(), DataValue::String(health.to_string()));
+                }
+            }
+        }
         outputs.insert("bot_adapter".to_string(), DataValue::BotAdapterRef(self.adapter_handle.clone().unwrap()));
         self.validate_outputs(&outputs)?;
 
         Ok(Some(outputs))
     }
 
+    fn start_outputs(&self) -> HashMap<String, DataValue> {
+        self.adapter_handle
+            .iter()
+            .map(|adapter| ("bot_adapter".to_string(), DataValue::BotAdapterRef(adapter.clone())))
+            .collect()
+    }
+
     fn on_cleanup(&mut self) -> Result<()> {
         self.event_rx = None;
         self.error_rx = None;
+        self.health_rx = None;
         self.adapter_handle = None;
         self.runtime = None;
         Ok(())
     }
 }
 
+/// MessageSenderNode - sends a message through the bot adapter
+///
+/// Inputs:
+///   - bot_adapter: BotAdapterRef used for sending
+///   - target_id: QQ number or group number
+///   - content: Message text, appended after `segments` when both are given
+///   - segments: OneBot message array, e.g. from the message builder node
+///   - message_type: "private" or "group"
+///
+/// Outputs:
+///   - success: Whether the bot server accepted the message
+///   - response: The server's raw action response
+///
+/// A rejection by the server is reported through `success`; failing to reach the server
+/// (not connected, no response in time) is an error.
 pub struct MessageSenderNode {
     id: String,
     name: String,
@@ -265,9 +394,11 @@ impl Node for MessageSenderNode {
     }
 
     node_input![
+        port! { name = "bot_adapter", ty = BotAdapterRef, desc = "Bot适配器引用" },
         port! { name = "target_id", ty = String, desc = "Target user or group ID" },
-        port! { name = "content", ty = String, desc = "Message content to send" },
-        port! { name = "message_type", ty = String, desc = "Type of message to send" },
+        port! { name = "content", ty = String, desc = "Message content to send", optional },
+        port! { name = "segments", ty = Json, desc = "Message segments (text, image, at, reply) to send", optional },
+        port! { name = "message_type", ty = String, desc = "Type of message to send: private or group", choices = ["private", "group"] },
     ];
 
     node_output![
@@ -278,21 +409,185 @@ impl Node for MessageSenderNode {
     fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
         self.validate_inputs(&inputs)?;
 
+        let adapter = match inputs.get("bot_adapter") {
+            Some(DataValue::BotAdapterRef(adapter)) => adapter.clone(),
+            _ => return Err(crate::error::Error::InvalidNodeInput("bot_adapter is required".to_string())),
+        };
+        let target_id = match inputs.get("target_id") {
+            Some(DataValue::String(s)) => s.trim().parse::<i64>().map_err(|_| {
+                crate::error::Error::InvalidNodeInput(format!("target_id must be a number, got '{}'", s))
+            })?,
+            _ => return Err(crate::error::Error::InvalidNodeInput("target_id is required".to_string())),
+        };
+        let mut segments: Vec<MessageSegment> = match inputs.get("segments") {
+            Some(DataValue::Json(Value::Null)) | None => Vec::new(),
+            Some(DataValue::Json(value)) => serde_json::from_value(value.clone()).map_err(|e| {
+                crate::error::Error::InvalidNodeInput(format!("segments is not a valid message array: {}", e))
+            })?,
+            Some(other) => {
+                return Err(crate::error::Error::InvalidNodeInput(format!(
+                    "segments must be a JSON array, got {:?}",
+                    other
+                )))
+            }
+        };
+        if let Some(DataValue::String(content)) = inputs.get("content") {
+            if !content.is_empty() {
+                segments.push(MessageSegment::text(content.clone()));
+            }
+        }
+        if segments.is_empty() {
+            return Err(crate::error::Error::InvalidNodeInput("content or segments is required".to_string()));
+        }
+        let message_type = match inputs.get("message_type") {
+            Some(DataValue::String(s)) => MessageType::parse(s).ok_or_else(|| {
+                crate::error::Error::InvalidNodeInput(format!(
+                    "message_type must be 'private' or 'group', got '{}'",
+                    s
+                ))
+            })?,
+            _ => return Err(crate::error::Error::InvalidNodeInput("message_type is required".to_string())),
+        };
+
+        let (protocol, rx, queued_for) = {
+            let guard = adapter.blocking_lock();
+            let protocol = guard.get_protocol();
+            let (action, params) = protocol.send_segments_action(message_type, target_id, &segments);
+            let (rx, queued_for) = guard.call_action_queued(&action, params)?;
+            (protocol, rx, queued_for)
+        };
+
+        let response = wait_response(rx, DEFAULT_ACTION_TIMEOUT + queued_for).map_err(|e| {
+            crate::error::Error::StringError(format!("Failed to send message to {}: {}", target_id, e))
+        })?;
+        let success = match protocol.action_result(&response) {
+            Ok(_) => true,
+            Err(e) => {
+                warn!("[MessageSenderNode] Bot server rejected message to {}: {}", target_id, e);
+                false
+            }
+        };
+
         let mut outputs = HashMap::new();
+        outputs.insert("success".to_string(), DataValue::Boolean(success));
+        outputs.insert("response".to_string(), DataValue::Json(response));
+
+        self.validate_outputs(&outputs)?;
+        Ok(outputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bot_adapter::models::BotEvent;
+    use crate::bot_adapter::protocol::{BotProtocol, OneBotV11Protocol};
+    use serde_json::json;
+
+    /// Adapter whose actions go to an in-process mock server answering every action with `reply`
+    fn adapter_answering(reply: Value) -> (SharedBotAdapter, std::thread::JoinHandle<Value>) {
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let mut adapter = runtime.block_on(BotAdapter::new(BotAdapterConfig::new("ws://localhost", "", "10000")));
+        let (mut frames, pending) = adapter.connect_mock();
+        let server = std::thread::spawn(move || {
+            let frame: Value = serde_json::from_str(&frames.blocking_recv().unwrap().text).unwrap();
+            let mut response = reply;
+            response["echo"] = frame["echo"].clone();
+            assert!(pending.resolve(frame["echo"].as_str().unwrap(), response));
+            frame
+        });
+        (adapter.into_shared(), server)
+    }
 
-        outputs.insert(
-            "success".to_string(),
-            DataValue::Boolean(true),
+    fn inputs(adapter: SharedBotAdapter, message_type: &str) -> HashMap<String, DataValue> {
+        HashMap::from([
+            ("bot_adapter".to_string(), DataValue::BotAdapterRef(adapter)),
+            ("target_id".to_string(), DataValue::String("123456".to_string())),
+            ("content".to_string(), DataValue::String("hello".to_string())),
+            ("message_type".to_string(), DataValue::String(message_type.to_string())),
+        ])
+    }
+
+    #[test]
+    fn test_message_is_sent_through_adapter() {
+        let reply = json!({"status": "ok", "retcode": 0, "data": {"message_id": 42}});
+        let (adapter, server) = adapter_answering(reply);
+        let mut node = MessageSenderNode::new("sender", "Sender");
+
+        let outputs = node.execute(inputs(adapter, "group")).unwrap();
+        let frame = server.join().unwrap();
+        assert_eq!(frame["params"]["group_id"], json!(123456));
+        assert_eq!(frame["params"]["message"][0]["data"]["text"], json!("hello"));
+        assert!(matches!(outputs.get("success"), Some(DataValue::Boolean(true))));
+        assert!(matches!(outputs.get("response"), Some(DataValue::Json(r)) if r["data"]["message_id"] == json!(42)));
+    }
+
+    #[test]
+    fn test_segments_are_sent_before_content() {
+        let (adapter, server) = adapter_answering(json!({"status": "ok", "retcode": 0, "data": {}}));
+        let mut node = MessageSenderNode::new("sender", "Sender");
+        let mut inputs = inputs(adapter, "private");
+        inputs.insert(
+            "segments".to_string(),
+            DataValue::Json(json!([{"type": "at", "data": {"qq": "42"}}])),
         );
-        outputs.insert(
-            "response".to_string(),
-            DataValue::Json(serde_json::json!({
-                "status": "sent",
-                "timestamp": "2025-01-28T00:00:00Z"
-            })),
+
+        node.execute(inputs).unwrap();
+        let frame = server.join().unwrap();
+        assert_eq!(frame["action"], json!("send_private_msg"));
+        assert_eq!(
+            frame["params"]["message"],
+            json!([
+                {"type": "at", "data": {"qq": "42"}},
+                {"type": "text", "data": {"text": "hello"}}
+            ])
         );
+    }
 
-        self.validate_outputs(&outputs)?;
-        Ok(outputs)
+    #[test]
+    fn test_rejected_message_reports_failure() {
+        let reply = json!({"status": "failed", "retcode": 1200, "message": "not a group member"});
+        let (adapter, server) = adapter_answering(reply);
+        let mut node = MessageSenderNode::new("sender", "Sender");
+
+        let outputs = node.execute(inputs(adapter, "group")).unwrap();
+        server.join().unwrap();
+        assert!(matches!(outputs.get("success"), Some(DataValue::Boolean(false))));
+        assert!(matches!(outputs.get("response"), Some(DataValue::Json(r)) if r["retcode"] == json!(1200)));
+    }
+
+    #[test]
+    fn test_mentions_and_reply_are_extracted() {
+        let payload = json!({
+            "post_type": "message",
+            "message_type": "group",
+            "message_id": 2001,
+            "group_id": 987654,
+            "sender": {"user_id": 42, "nickname": "tester"},
+            "message": [
+                {"type": "reply", "data": {"id": "1999"}},
+                {"type": "at", "data": {"qq": "10000"}},
+                {"type": "at", "data": {"qq": 20000}},
+                {"type": "text", "data": {"text": " 你们看这个"}}
+            ]
+        });
+        let event = match OneBotV11Protocol.parse_event(&payload).unwrap() {
+            Some(BotEvent::Message(event)) => event,
+            other => panic!("expected message event, got {:?}", other),
+        };
+
+        let mentions = MentionInfo::from_event(&event, "10000");
+        assert_eq!(mentions.at_targets, vec!["10000".to_string(), "20000".to_string()]);
+        assert!(mentions.is_at_me);
+        assert_eq!(mentions.reply_to_message_id, Some(1999));
+        assert!(!MentionInfo::from_event(&event, "30000").is_at_me);
+    }
+
+    #[test]
+    fn test_disconnected_adapter_is_an_error() {
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let adapter = runtime.block_on(BotAdapter::new(BotAdapterConfig::new("ws://localhost", "", "10000")));
+        let mut node = MessageSenderNode::new("sender", "Sender");
+        assert!(node.execute(inputs(adapter.into_shared(), "private")).is_err());
     }
 }
@@ -0,0 +1,109 @@
+use crate::bot_adapter::models::BotNotice;
+use crate::error::Result;
+use crate::node::{node_input, node_output, DataType, DataValue, Node, Port};
+use std::collections::HashMap;
+
+/// ExtractNoticeNode - splits a notice event into plain fields a graph can branch on
+///
+/// Inputs:
+///   - notice_event: Notice or request event from the bot adapter
+///
+/// Outputs:
+///   - kind: friend_request, group_invite, group_join_request, member_join, member_leave,
+///     friend_added, or the OneBot notice_type for other notices
+///   - user_id: User the event is about, when present
+///   - group_id: Group the event happened in, when present
+///   - comment: Verification message of a request, empty for notices
+///   - flag: Id needed to answer a request, empty for notices
+pub struct ExtractNoticeNode {
+    id: String,
+    name: String,
+}
+
+impl ExtractNoticeNode {
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+        }
+    }
+}
+
+impl Node for ExtractNoticeNode {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> Option<&str> {
+        Some("解析通知 - 从通知事件中取出类型、用户和群号")
+    }
+
+    node_input![
+        port! { name = "notice_event", ty = NoticeEvent, desc = "通知事件" },
+    ];
+
+    node_output![
+        port! { name = "kind", ty = String, desc = "事件类型，如 friend_request、group_invite、member_join、member_leave" },
+        port! { name = "user_id", ty = Integer, desc = "相关用户QQ号" },
+        port! { name = "group_id", ty = Integer, desc = "相关群号" },
+        port! { name = "comment", ty = String, desc = "申请附带的验证信息" },
+        port! { name = "flag", ty = String, desc = "处理申请所需的标识" },
+    ];
+
+    fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+        self.validate_inputs(&inputs)?;
+
+        let notice = match inputs.get("notice_event") {
+            Some(DataValue::NoticeEvent(notice)) => notice,
+            _ => return Err(crate::error::Error::InvalidNodeInput("notice_event is required".to_string())),
+        };
+        let (comment, flag) = match notice {
+            BotNotice::Request(request) => (request.comment.clone(), request.flag.clone()),
+            BotNotice::Notice(_) => (String::new(), String::new()),
+        };
+
+        let mut outputs = HashMap::new();
+        outputs.insert("kind".to_string(), DataValue::String(notice.kind()));
+        if let Some(user_id) = notice.user_id() {
+            outputs.insert("user_id".to_string(), DataValue::Integer(user_id));
+        }
+        if let Some(group_id) = notice.group_id() {
+            outputs.insert("group_id".to_string(), DataValue::Integer(group_id));
+        }
+        outputs.insert("comment".to_string(), DataValue::String(comment));
+        outputs.insert("flag".to_string(), DataValue::String(flag));
+
+        self.validate_outputs(&outputs)?;
+        Ok(outputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bot_adapter::models::NoticeEvent;
+
+    #[test]
+    fn test_member_join_notice_is_extracted() {
+        let notice = BotNotice::Notice(NoticeEvent {
+            notice_type: "group_increase".to_string(),
+            sub_type: Some("approve".to_string()),
+            group_id: Some(987654),
+            user_id: Some(222),
+            operator_id: Some(111),
+        });
+        let mut node = ExtractNoticeNode::new("notice", "Notice");
+        let outputs = node
+            .execute(HashMap::from([("notice_event".to_string(), DataValue::NoticeEvent(notice))]))
+            .unwrap();
+
+        assert!(matches!(outputs.get("kind"), Some(DataValue::String(kind)) if kind == "member_join"));
+        assert!(matches!(outputs.get("user_id"), Some(DataValue::Integer(222))));
+        assert!(matches!(outputs.get("group_id"), Some(DataValue::Integer(987654))));
+        assert!(matches!(outputs.get("flag"), Some(DataValue::String(flag)) if flag.is_empty()));
+    }
+}
@@ -0,0 +1,402 @@
+use base64::Engine;
+use serde_json::{json, Value};
+
+use super::models::message::MessageSegment;
+use super::models::{BotEvent, MessageType, NoticeEvent, RawMessageEvent, RequestEvent, RequestType};
+
+/// Kind of media carried by a media message segment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaKind {
+    Image,
+    File,
+}
+
+/// Wire protocol spoken with the bot server.
+///
+/// Separates payload parsing and action building from the WebSocket handling in
+/// `BotAdapter`, so the adapter only moves text frames around.
+pub trait BotProtocol: Send + Sync {
+    /// Parse an incoming payload. Returns `Ok(None)` for events the bot does not handle
+    /// (heartbeats, lifecycle meta events, action responses).
+    fn parse_event(&self, payload: &Value) -> serde_json::Result<Option<BotEvent>>;
+
+    /// Build the action that approves or rejects a friend/group request,
+    /// returned as `(action, params)`
+    fn request_reply_action(
+        &self,
+        request: &RequestEvent,
+        approve: bool,
+        reason: Option<&str>,
+    ) -> (String, Value);
+
+    /// Build the action that sends a plain text message to a user or group,
+    /// returned as `(action, params)`
+    fn send_text_action(&self, message_type: MessageType, target_id: i64, text: &str) -> (String, Value);
+
+    /// Build the action that sends a message made of segments (text, image, @, reply)
+    /// to a user or group, returned as `(action, params)`
+    fn send_segments_action(
+        &self,
+        message_type: MessageType,
+        target_id: i64,
+        segments: &[MessageSegment],
+    ) -> (String, Value);
+
+    /// Build the action that sends an image or file to a user or group,
+    /// returned as `(action, params)`
+    fn send_media_action(
+        &self,
+        message_type: MessageType,
+        target_id: i64,
+        kind: MediaKind,
+        data: &[u8],
+        filename: &str,
+    ) -> (String, Value);
+
+    /// Build the action that recalls (deletes) a message, returned as `(action, params)`
+    fn delete_msg_action(&self, message_id: i64) -> (String, Value);
+
+    /// Build the action that queries a member of a group, returned as `(action, params)`
+    fn group_member_info_action(&self, group_id: i64, user_id: i64) -> (String, Value);
+
+    /// Cheap action sent periodically to keep frames flowing on an idle connection, returned as
+    /// `(action, params)`. `None` if the protocol has nothing suitable.
+    fn heartbeat_action(&self) -> Option<(String, Value)>;
+
+    /// Target of an action that sends a message, e.g. `group:123`, `None` for other actions.
+    /// Used to rate limit outgoing messages.
+    fn message_target(&self, action: &str, params: &Value) -> Option<String>;
+
+    /// Echo id of an action response, `None` if the payload is not a response
+    fn action_echo(&self, payload: &Value) -> Option<String>;
+
+    /// Extract the result data of an action response, or the server's error message
+    fn action_result(&self, response: &Value) -> std::result::Result<Value, String>;
+
+    /// Whether a failed response to `group_member_info_action` means the user is not in
+    /// the group, rather than any other failure
+    fn is_member_not_found(&self, response: &Value) -> bool;
+}
+
+/// OneBot v11 protocol (NapCat, Lagrange, go-cqhttp, ...)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OneBotV11Protocol;
+
+impl BotProtocol for OneBotV11Protocol {
+    fn parse_event(&self, payload: &Value) -> serde_json::Result<Option<BotEvent>> {
+        let post_type = payload.get("post_type").and_then(Value::as_str);
+
+        // Some implementations omit post_type on message events, so fall back to message_type
+        if post_type == Some("message") || (post_type.is_none() && payload.get("message_type").is_some()) {
+            let raw_event: RawMessageEvent = serde_json::from_value(payload.clone())?;
+            return Ok(Some(BotEvent::Message(raw_event.into())));
+        }
+
+        match post_type {
+            Some("notice") => {
+                let notice: NoticeEvent = serde_json::from_value(payload.clone())?;
+                Ok(Some(BotEvent::Notice(notice)))
+            }
+            Some("request") => {
+                let request: RequestEvent = serde_json::from_value(payload.clone())?;
+                Ok(Some(BotEvent::Request(request)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn request_reply_action(
+        &self,
+        request: &RequestEvent,
+        approve: bool,
+        reason: Option<&str>,
+    ) -> (String, Value) {
+        match request.request_type {
+            RequestType::Friend => (
+                "set_friend_add_request".to_string(),
+                json!({
+                    "flag": request.flag,
+                    "approve": approve,
+                }),
+            ),
+            RequestType::Group => (
+                "set_group_add_request".to_string(),
+                json!({
+                    "flag": request.flag,
+                    "sub_type": request.sub_type.as_deref().unwrap_or("add"),
+                    "approve": approve,
+                    "reason": reason.unwrap_or_default(),
+                }),
+            ),
+        }
+    }
+
+    fn send_text_action(&self, message_type: MessageType, target_id: i64, text: &str) -> (String, Value) {
+        let message = json!([{ "type": "text", "data": { "text": text } }]);
+        send_msg_action(message_type, target_id, message)
+    }
+
+    fn send_segments_action(
+        &self,
+        message_type: MessageType,
+        target_id: i64,
+        segments: &[MessageSegment],
+    ) -> (String, Value) {
+        let message = serde_json::to_value(segments).unwrap_or_else(|_| json!([]));
+        send_msg_action(message_type, target_id, message)
+    }
+
+    fn send_media_action(
+        &self,
+        message_type: MessageType,
+        target_id: i64,
+        kind: MediaKind,
+        data: &[u8],
+        filename: &str,
+    ) -> (String, Value) {
+        let segment_type = match kind {
+            MediaKind::Image => "image",
+            MediaKind::File => "file",
+        };
+        // Media is inlined as base64 so the bot server does not need access to our filesystem
+        let file = format!("base64://{}", base64::engine::general_purpose::STANDARD.encode(data));
+        let message = json!([{ "type": segment_type, "data": { "file": file, "name": filename } }]);
+        send_msg_action(message_type, target_id, message)
+    }
+
+    fn delete_msg_action(&self, message_id: i64) -> (String, Value) {
+        ("delete_msg".to_string(), json!({ "message_id": message_id }))
+    }
+
+    fn group_member_info_action(&self, group_id: i64, user_id: i64) -> (String, Value) {
+        (
+            "get_group_member_info".to_string(),
+            json!({ "group_id": group_id, "user_id": user_id, "no_cache": false }),
+        )
+    }
+
+    fn heartbeat_action(&self) -> Option<(String, Value)> {
+        Some(("get_status".to_string(), json!({})))
+    }
+
+    fn message_target(&self, action: &str, params: &Value) -> Option<String> {
+        let id = |key: &str| params.get(key).filter(|id| !id.is_null()).map(|id| id.to_string());
+        match action {
+            "send_private_msg" => id("user_id").map(|id| format!("private:{}", id)),
+            "send_group_msg" => id("group_id").map(|id| format!("group:{}", id)),
+            "send_msg" => id("group_id")
+                .map(|id| format!("group:{}", id))
+                .or_else(|| id("user_id").map(|id| format!("private:{}", id))),
+            _ => None,
+        }
+    }
+
+    fn action_echo(&self, payload: &Value) -> Option<String> {
+        if payload.get("post_type").is_some() {
+            return None;
+        }
+        match payload.get("echo")? {
+            Value::String(echo) => Some(echo.clone()),
+            Value::Null => None,
+            other => Some(other.to_string()),
+        }
+    }
+
+    fn action_result(&self, response: &Value) -> std::result::Result<Value, String> {
+        let ok = response.get("status").and_then(Value::as_str) == Some("ok")
+            || response.get("retcode").and_then(Value::as_i64) == Some(0);
+        if ok {
+            return Ok(response.get("data").cloned().unwrap_or(Value::Null));
+        }
+        let retcode = response.get("retcode").and_then(Value::as_i64).unwrap_or(-1);
+        Err(format!("Action failed (retcode {}): {}", retcode, failure_message(response)))
+    }
+
+    fn is_member_not_found(&self, response: &Value) -> bool {
+        // Implementations share no retcode for this (go-cqhttp uses the generic 100),
+        // so go by the message
+        if self.action_result(response).is_ok() {
+            return false;
+        }
+        let message = failure_message(response).to_lowercase();
+        ["不存在", "not found", "not_found", "not exist", "not a group member"]
+            .iter()
+            .any(|marker| message.contains(marker))
+    }
+}
+
+/// Error message of a failed action response
+fn failure_message(response: &Value) -> &str {
+    response
+        .get("wording")
+        .or_else(|| response.get("message"))
+        .or_else(|| response.get("msg"))
+        .and_then(Value::as_str)
+        .unwrap_or("unknown error")
+}
+
+fn send_msg_action(message_type: MessageType, target_id: i64, message: Value) -> (String, Value) {
+    match message_type {
+        MessageType::Private => (
+            "send_private_msg".to_string(),
+            json!({ "user_id": target_id, "message": message }),
+        ),
+        MessageType::Group => (
+            "send_group_msg".to_string(),
+            json!({ "group_id": target_id, "message": message }),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bot_adapter::models::BotNotice;
+
+    #[test]
+    fn test_parse_friend_request() {
+        let payload = json!({
+            "time": 1700000000,
+            "self_id": 10000,
+            "post_type": "request",
+            "request_type": "friend",
+            "user_id": 123456,
+            "comment": "hello, add me",
+            "flag": "flag_abc"
+        });
+
+        let event = OneBotV11Protocol.parse_event(&payload).unwrap();
+        let request = match event {
+            Some(BotEvent::Request(request)) => request,
+            other => panic!("expected request event, got {:?}", other),
+        };
+        assert_eq!(request.request_type, RequestType::Friend);
+        assert_eq!(request.user_id, 123456);
+        assert_eq!(request.comment, "hello, add me");
+        assert_eq!(request.flag, "flag_abc");
+        assert!(!request.is_group_invite());
+
+        let (action, params) = OneBotV11Protocol.request_reply_action(&request, true, None);
+        assert_eq!(action, "set_friend_add_request");
+        assert_eq!(params["flag"], "flag_abc");
+        assert_eq!(params["approve"], true);
+    }
+
+    #[test]
+    fn test_parse_group_increase_notice() {
+        let payload = json!({
+            "time": 1700000000,
+            "self_id": 10000,
+            "post_type": "notice",
+            "notice_type": "group_increase",
+            "sub_type": "approve",
+            "group_id": 987654,
+            "operator_id": 111,
+            "user_id": 222
+        });
+
+        let event = OneBotV11Protocol.parse_event(&payload).unwrap();
+        let notice = match event {
+            Some(BotEvent::Notice(notice)) => notice,
+            other => panic!("expected notice event, got {:?}", other),
+        };
+        assert_eq!(notice.notice_type, "group_increase");
+        assert_eq!(notice.sub_type.as_deref(), Some("approve"));
+        assert_eq!(notice.group_id, Some(987654));
+        assert_eq!(notice.user_id, Some(222));
+        assert_eq!(notice.operator_id, Some(111));
+    }
+
+    #[test]
+    fn test_parse_group_invite_request() {
+        let payload = json!({
+            "time": 1700000000,
+            "self_id": 10000,
+            "post_type": "request",
+            "request_type": "group",
+            "sub_type": "invite",
+            "group_id": 987654,
+            "user_id": 123456,
+            "comment": "",
+            "flag": "1700000000123"
+        });
+
+        let request = match OneBotV11Protocol.parse_event(&payload).unwrap() {
+            Some(BotEvent::Request(request)) => request,
+            other => panic!("expected request event, got {:?}", other),
+        };
+        let notice = BotNotice::Request(request);
+        assert_eq!(notice.kind(), "group_invite");
+        assert_eq!(notice.group_id(), Some(987654));
+        assert_eq!(notice.user_id(), Some(123456));
+        assert_eq!(notice.to_json()["flag"], "1700000000123");
+    }
+
+    #[test]
+    fn test_parse_group_decrease_notice() {
+        let payload = json!({
+            "time": 1700000000,
+            "self_id": 10000,
+            "post_type": "notice",
+            "notice_type": "group_decrease",
+            "sub_type": "kick",
+            "group_id": 987654,
+            "operator_id": 111,
+            "user_id": 222
+        });
+
+        let notice = match OneBotV11Protocol.parse_event(&payload).unwrap() {
+            Some(BotEvent::Notice(notice)) => BotNotice::Notice(notice),
+            other => panic!("expected notice event, got {:?}", other),
+        };
+        assert_eq!(notice.kind(), "member_leave");
+        assert_eq!(notice.to_json()["sub_type"], "kick");
+        assert_eq!(notice.to_json()["kind"], "member_leave");
+    }
+
+    #[test]
+    fn test_meta_events_are_ignored() {
+        let payload = json!({
+            "post_type": "meta_event",
+            "meta_event_type": "heartbeat"
+        });
+        assert!(OneBotV11Protocol.parse_event(&payload).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_encode_group_image_segment() {
+        let (action, params) = OneBotV11Protocol.send_media_action(
+            MessageType::Group,
+            987654,
+            MediaKind::Image,
+            b"\x89PNG",
+            "qr.png",
+        );
+        assert_eq!(action, "send_group_msg");
+        assert_eq!(params["group_id"], 987654);
+        let segment = &params["message"][0];
+        assert_eq!(segment["type"], "image");
+        assert_eq!(segment["data"]["file"], "base64://iVBORw==");
+        assert_eq!(segment["data"]["name"], "qr.png");
+    }
+
+    #[test]
+    fn test_message_target_of_send_actions() {
+        let (action, params) = OneBotV11Protocol.send_text_action(MessageType::Group, 987654, "hi");
+        assert_eq!(OneBotV11Protocol.message_target(&action, &params).as_deref(), Some("group:987654"));
+        let (action, params) = OneBotV11Protocol.send_text_action(MessageType::Private, 42, "hi");
+        assert_eq!(OneBotV11Protocol.message_target(&action, &params).as_deref(), Some("private:42"));
+        let (action, params) = OneBotV11Protocol.group_member_info_action(987654, 42);
+        assert_eq!(OneBotV11Protocol.message_target(&action, &params), None);
+    }
+
+    #[test]
+    fn test_member_not_found_is_told_apart_from_other_failures() {
+        let failed = |retcode: i64, wording: &str| json!({"status": "failed", "retcode": retcode, "wording": wording});
+        assert!(OneBotV11Protocol.is_member_not_found(&failed(100, "群员不存在")));
+        assert!(OneBotV11Protocol.is_member_not_found(&failed(200, "GROUP_MEMBER_NOT_FOUND")));
+        assert!(!OneBotV11Protocol.is_member_not_found(&failed(100, "param error")));
+        assert!(!OneBotV11Protocol.is_member_not_found(&failed(1400, "timeout")));
+        assert!(!OneBotV11Protocol.is_member_not_found(&json!({"status": "ok", "retcode": 0, "data": null})));
+    }
+}
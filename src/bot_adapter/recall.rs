@@ -0,0 +1,166 @@
+use crate::bot_adapter::action::{wait_response, DEFAULT_ACTION_TIMEOUT};
+use crate::bot_adapter::adapter::SharedBotAdapter;
+use crate::error::Result;
+use crate::node::{node_input, node_output, DataType, DataValue, Node, Port};
+use log::warn;
+use std::collections::HashMap;
+
+/// Outcome of a recall the bot server answered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecallOutcome {
+    Recalled,
+    /// The server refused, e.g. the message is too old or the bot lacks admin rights
+    Rejected(String),
+}
+
+/// Recall a message and wait for the server's answer.
+/// Errors mean the server could not be reached or did not answer in time.
+pub fn recall_message(adapter: &SharedBotAdapter, message_id: i64) -> std::result::Result<RecallOutcome, String> {
+    // Only hold the adapter lock while sending; the response is delivered by the reader task
+    let (protocol, rx) = {
+        let guard = adapter.blocking_lock();
+        let rx = guard.recall_message(message_id).map_err(|e| e.to_string())?;
+        (guard.get_protocol(), rx)
+    };
+
+    let response = wait_response(rx, DEFAULT_ACTION_TIMEOUT)?;
+    Ok(match protocol.action_result(&response) {
+        Ok(_) => RecallOutcome::Recalled,
+        Err(e) => RecallOutcome::Rejected(e),
+    })
+}
+
+/// MessageRecallNode - recalls (deletes) a message
+///
+/// Inputs:
+///   - bot_adapter: BotAdapterRef used for sending
+///   - message_id: Id of the message to recall
+///
+/// Outputs:
+///   - success: Whether the bot server recalled the message
+pub struct MessageRecallNode {
+    id: String,
+    name: String,
+}
+
+impl MessageRecallNode {
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+        }
+    }
+}
+
+impl Node for MessageRecallNode {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> Option<&str> {
+        Some("撤回消息 - 撤回机器人自己或（需管理员权限）他人的消息")
+    }
+
+    node_input![
+        port! { name = "bot_adapter", ty = BotAdapterRef, desc = "Bot适配器引用" },
+        port! { name = "message_id", ty = String, desc = "要撤回的消息ID" },
+    ];
+
+    node_output![
+        port! { name = "success", ty = Boolean, desc = "是否撤回成功" },
+    ];
+
+    fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+        self.validate_inputs(&inputs)?;
+
+        let adapter = match inputs.get("bot_adapter") {
+            Some(DataValue::BotAdapterRef(adapter)) => adapter.clone(),
+            _ => return Err(crate::error::Error::InvalidNodeInput("bot_adapter is required".to_string())),
+        };
+        let message_id = match inputs.get("message_id") {
+            Some(DataValue::String(s)) => s.trim().parse::<i64>().map_err(|_| {
+                crate::error::Error::InvalidNodeInput(format!("message_id must be a number, got '{}'", s))
+            })?,
+            _ => return Err(crate::error::Error::InvalidNodeInput("message_id is required".to_string())),
+        };
+
+        let outcome = recall_message(&adapter, message_id).map_err(|e| {
+            crate::error::Error::StringError(format!("Failed to recall message {}: {}", message_id, e))
+        })?;
+        let success = match outcome {
+            RecallOutcome::Recalled => true,
+            RecallOutcome::Rejected(reason) => {
+                warn!("[MessageRecallNode] Bot server refused to recall message {}: {}", message_id, reason);
+                false
+            }
+        };
+
+        let mut outputs = HashMap::new();
+        outputs.insert("success".to_string(), DataValue::Boolean(success));
+
+        self.validate_outputs(&outputs)?;
+        Ok(outputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bot_adapter::adapter::{BotAdapter, BotAdapterConfig};
+    use serde_json::{json, Value};
+
+    #[test]
+    fn test_concurrent_recalls_get_their_own_responses() {
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let mut adapter = runtime.block_on(BotAdapter::new(BotAdapterConfig::new("ws://localhost", "", "10000")));
+        let (mut frames, pending) = adapter.connect_mock();
+        let adapter = adapter.into_shared();
+
+        // Mock server: wait for both recalls, then answer them in reverse order.
+        // Message 1 is recalled, message 2 is refused.
+        let server = std::thread::spawn(move || {
+            let mut received: Vec<Value> = (0..2)
+                .map(|_| serde_json::from_str(&frames.blocking_recv().unwrap().text).unwrap())
+                .collect();
+            received.reverse();
+            for frame in received {
+                assert_eq!(frame["action"], "delete_msg");
+                let response = if frame["params"]["message_id"] == 1 {
+                    json!({"status": "ok", "retcode": 0, "data": null, "echo": frame["echo"]})
+                } else {
+                    json!({"status": "failed", "retcode": 200, "wording": "message too old", "echo": frame["echo"]})
+                };
+                assert!(pending.resolve(frame["echo"].as_str().unwrap(), response));
+            }
+        });
+
+        let recalls: Vec<_> = [1, 2]
+            .into_iter()
+            .map(|message_id| {
+                let adapter = adapter.clone();
+                std::thread::spawn(move || recall_message(&adapter, message_id).unwrap())
+            })
+            .collect();
+        let outcomes: Vec<RecallOutcome> = recalls.into_iter().map(|handle| handle.join().unwrap()).collect();
+        server.join().unwrap();
+
+        assert_eq!(outcomes[0], RecallOutcome::Recalled);
+        assert!(matches!(&outcomes[1], RecallOutcome::Rejected(reason) if reason.contains("message too old")));
+    }
+
+    #[test]
+    fn test_invalid_message_id_is_rejected() {
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let adapter = runtime.block_on(BotAdapter::new(BotAdapterConfig::new("ws://localhost", "", "10000")));
+        let mut node = MessageRecallNode::new("recall", "Recall");
+        let inputs = HashMap::from([
+            ("bot_adapter".to_string(), DataValue::BotAdapterRef(adapter.into_shared())),
+            ("message_id".to_string(), DataValue::String("abc".to_string())),
+        ]);
+        assert!(node.execute(inputs).is_err());
+    }
+}
@@ -0,0 +1,188 @@
+use crate::bot_adapter::adapter::SharedBotAdapter;
+use crate::bot_adapter::event;
+use crate::bot_adapter::models::{RequestEvent, RequestType};
+use crate::error::Result;
+use crate::node::{node_input, node_output, DataType, DataValue, Node, Port};
+use log::{error, info};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+
+/// Decides whether incoming friend/group requests are approved
+#[derive(Debug, Clone, Default)]
+pub struct RequestPolicy {
+    pub approve_friend: bool,
+    pub approve_group_invite: bool,
+    pub reject_reason: Option<String>,
+}
+
+impl RequestPolicy {
+    /// Returns `Some(approve)` for requests this policy handles, `None` for requests left
+    /// for a human (group join requests from other users need an admin decision)
+    pub fn decide(&self, request: &RequestEvent) -> Option<bool> {
+        match request.request_type {
+            RequestType::Friend => Some(self.approve_friend),
+            RequestType::Group if request.is_group_invite() => Some(self.approve_group_invite),
+            RequestType::Group => None,
+        }
+    }
+}
+
+/// RequestHandlerNode - approves or rejects friend requests and group invites
+///
+/// Installs a request handler on the bot adapter as soon as the adapter starts, so requests
+/// arriving before the first message are answered too. Every request is then answered in
+/// the background according to the configured policy.
+///
+/// Inputs:
+///   - bot_adapter: BotAdapterRef used to receive requests and send replies
+///   - approve_friend: Approve friend requests (default: false)
+///   - approve_group_invite: Approve invitations of the bot into groups (default: false)
+///   - reject_reason: Reason sent when rejecting group invites
+///
+/// Outputs:
+///   - handled_count: Number of requests answered so far
+pub struct RequestHandlerNode {
+    id: String,
+    name: String,
+    installed: bool,
+    handled_count: Arc<AtomicI64>,
+}
+
+impl RequestHandlerNode {
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            installed: false,
+            handled_count: Arc::new(AtomicI64::new(0)),
+        }
+    }
+
+    /// Install the handler with the policy from `inputs`, unless it already is
+    fn install_from_inputs(&mut self, inputs: &HashMap<String, DataValue>) -> std::result::Result<(), String> {
+        if self.installed {
+            return Ok(());
+        }
+        let adapter = match inputs.get("bot_adapter") {
+            Some(DataValue::BotAdapterRef(adapter)) => adapter.clone(),
+            _ => return Err("bot_adapter input is required".to_string()),
+        };
+        let flag = |name: &str| matches!(inputs.get(name), Some(DataValue::Boolean(true)));
+        let policy = RequestPolicy {
+            approve_friend: flag("approve_friend"),
+            approve_group_invite: flag("approve_group_invite"),
+            reject_reason: match inputs.get("reject_reason") {
+                Some(DataValue::String(s)) if !s.is_empty() => Some(s.clone()),
+                _ => None,
+            },
+        };
+        self.install(&adapter, policy);
+        Ok(())
+    }
+
+    fn install(&mut self, adapter: &SharedBotAdapter, policy: RequestPolicy) {
+        let adapter_handle = adapter.clone();
+        let handled_count = self.handled_count.clone();
+        let handler: event::RequestEventHandler = Arc::new(move |request| {
+            let adapter_handle = adapter_handle.clone();
+            let handled_count = handled_count.clone();
+            let policy = policy.clone();
+            Box::pin(async move {
+                let Some(approve) = policy.decide(request) else {
+                    return;
+                };
+                let guard = adapter_handle.lock().await;
+                let (action, params) = guard.get_protocol().request_reply_action(
+                    request,
+                    approve,
+                    policy.reject_reason.as_deref(),
+                );
+                match guard.send_action(&action, params) {
+                    Ok(()) => {
+                        handled_count.fetch_add(1, Ordering::Relaxed);
+                        info!(
+                            "[RequestHandler] {} {} request from {}",
+                            if approve { "Approved" } else { "Rejected" },
+                            request.request_type,
+                            request.user_id
+                        );
+                    }
+                    Err(e) => error!("[RequestHandler] Failed to answer request: {}", e),
+                }
+            })
+        });
+        adapter.blocking_lock().register_request_handler(handler);
+        self.installed = true;
+    }
+}
+
+impl Node for RequestHandlerNode {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> Option<&str> {
+        Some("请求处理 - 自动同意或拒绝好友申请和群邀请")
+    }
+
+    node_input![
+        port! { name = "bot_adapter", ty = BotAdapterRef, desc = "Bot适配器引用" },
+        port! { name = "approve_friend", ty = Boolean, desc = "是否同意好友申请 (默认: false)", optional },
+        port! { name = "approve_group_invite", ty = Boolean, desc = "是否同意群邀请 (默认: false)", optional },
+        port! { name = "reject_reason", ty = String, desc = "拒绝群邀请时附带的理由", optional },
+    ];
+
+    node_output![
+        port! { name = "handled_count", ty = Integer, desc = "已处理的请求数量" },
+    ];
+
+    fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+        self.validate_inputs(&inputs)?;
+        // Normally done in on_graph_start; covers an adapter that is not an event producer's
+        self.install_from_inputs(&inputs)
+            .map_err(crate::error::Error::InvalidNodeInput)?;
+
+        let mut outputs = HashMap::new();
+        outputs.insert(
+            "handled_count".to_string(),
+            DataValue::Integer(self.handled_count.load(Ordering::Relaxed)),
+        );
+
+        self.validate_outputs(&outputs)?;
+        Ok(outputs)
+    }
+
+    fn on_graph_start(&mut self, inputs: HashMap<String, DataValue>) -> Result<()> {
+        self.validate_inputs(&inputs)?;
+        self.install_from_inputs(&inputs)
+            .map_err(crate::error::Error::InvalidNodeInput)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bot_adapter::adapter::{BotAdapter, BotAdapterConfig};
+
+    #[test]
+    fn test_handler_is_installed_at_graph_start() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let adapter = runtime
+            .block_on(BotAdapter::new(BotAdapterConfig::new("ws://localhost", "", "10000")))
+            .into_shared();
+        let inputs = HashMap::from([("bot_adapter".to_string(), DataValue::BotAdapterRef(adapter.clone()))]);
+
+        let mut node = RequestHandlerNode::new("requests", "Requests");
+        node.on_graph_start(inputs.clone()).unwrap();
+        assert_eq!(adapter.blocking_lock().get_request_handlers().len(), 1);
+
+        // Later events reuse the installed handler
+        node.execute(inputs).unwrap();
+        assert_eq!(adapter.blocking_lock().get_request_handlers().len(), 1);
+    }
+}
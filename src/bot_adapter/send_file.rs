@@ -0,0 +1,196 @@
+use crate::bot_adapter::action::{wait_response, DEFAULT_ACTION_TIMEOUT};
+use crate::bot_adapter::models::MessageType;
+use crate::bot_adapter::protocol::MediaKind;
+use crate::error::Result;
+use crate::node::{node_input, node_output, DataType, DataValue, Node, Port};
+use base64::Engine;
+use log::warn;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Largest image the bot server accepts in a message segment
+const MAX_IMAGE_BYTES: usize = 10 * 1024 * 1024;
+/// Largest file sent inline; bigger files need the server's upload API
+const MAX_FILE_BYTES: usize = 30 * 1024 * 1024;
+const DEFAULT_IMAGE_FILENAME: &str = "image.png";
+const IMAGE_EXTENSIONS: [&str; 6] = ["png", "jpg", "jpeg", "gif", "webp", "bmp"];
+
+/// Raw bytes with an image extension are sent as images, everything else as files
+fn media_kind_for(filename: &str) -> MediaKind {
+    let extension = filename.rsplit_once('.').map(|(_, ext)| ext.to_ascii_lowercase());
+    match extension {
+        Some(ext) if IMAGE_EXTENSIONS.contains(&ext.as_str()) => MediaKind::Image,
+        _ => MediaKind::File,
+    }
+}
+
+/// Reject media the bot server would refuse, before base64 inflates it further
+pub fn check_media_size(kind: MediaKind, len: usize) -> std::result::Result<(), String> {
+    let limit = match kind {
+        MediaKind::Image => MAX_IMAGE_BYTES,
+        MediaKind::File => MAX_FILE_BYTES,
+    };
+    if len > limit {
+        return Err(format!(
+            "{:?} is too large to send: {} bytes, the limit is {} bytes",
+            kind, len, limit
+        ));
+    }
+    Ok(())
+}
+
+fn message_id_of(data: &Value) -> String {
+    match data.get("message_id") {
+        Some(Value::String(id)) => id.clone(),
+        Some(Value::Number(id)) => id.to_string(),
+        _ => String::new(),
+    }
+}
+
+/// SendFileNode - sends an image or file to a private chat or group
+///
+/// Inputs:
+///   - bot_adapter: BotAdapterRef used for sending
+///   - target_id: QQ number or group number
+///   - message_type: "private" or "group"
+///   - image: Base64 encoded image (e.g. from the QR code node)
+///   - bytes: Raw file content, sent as an image when `filename` has an image extension
+///   - filename: File name shown to the receiver
+///
+/// Outputs:
+///   - success: Whether the bot server accepted the message
+///   - message_id: Id of the sent message, empty on failure
+pub struct SendFileNode {
+    id: String,
+    name: String,
+}
+
+impl SendFileNode {
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+        }
+    }
+}
+
+impl Node for SendFileNode {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> Option<&str> {
+        Some("发送文件 - 向私聊或群聊发送图片或文件")
+    }
+
+    node_input![
+        port! { name = "bot_adapter", ty = BotAdapterRef, desc = "Bot适配器引用" },
+        port! { name = "target_id", ty = Integer, desc = "目标QQ号或群号" },
+        port! { name = "message_type", ty = String, desc = "消息类型: private 或 group", choices = ["private", "group"] },
+        port! { name = "image", ty = String, desc = "Base64编码的图片", optional },
+        port! { name = "bytes", ty = Binary, desc = "文件的原始字节", optional },
+        port! { name = "filename", ty = String, desc = "文件名，扩展名为图片格式时按图片发送", optional },
+    ];
+
+    node_output![
+        port! { name = "success", ty = Boolean, desc = "是否发送成功" },
+        port! { name = "message_id", ty = String, desc = "已发送消息的ID，失败时为空" },
+    ];
+
+    fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+        self.validate_inputs(&inputs)?;
+
+        let adapter = match inputs.get("bot_adapter") {
+            Some(DataValue::BotAdapterRef(adapter)) => adapter.clone(),
+            _ => return Err(crate::error::Error::InvalidNodeInput("bot_adapter is required".to_string())),
+        };
+        let target_id = match inputs.get("target_id") {
+            Some(DataValue::Integer(id)) => *id,
+            _ => return Err(crate::error::Error::InvalidNodeInput("target_id is required".to_string())),
+        };
+        let message_type = match inputs.get("message_type") {
+            Some(DataValue::String(s)) => MessageType::parse(s).ok_or_else(|| {
+                crate::error::Error::InvalidNodeInput(format!(
+                    "message_type must be 'private' or 'group', got '{}'",
+                    s
+                ))
+            })?,
+            _ => return Err(crate::error::Error::InvalidNodeInput("message_type is required".to_string())),
+        };
+        let filename = match inputs.get("filename") {
+            Some(DataValue::String(s)) if !s.trim().is_empty() => Some(s.trim().to_string()),
+            _ => None,
+        };
+
+        let (kind, data, filename) = match (inputs.get("image"), inputs.get("bytes")) {
+            (Some(DataValue::String(encoded)), _) if !encoded.is_empty() => {
+                let data = base64::engine::general_purpose::STANDARD
+                    .decode(encoded.trim())
+                    .map_err(|e| crate::error::Error::InvalidNodeInput(format!("image is not valid base64: {}", e)))?;
+                let filename = filename.unwrap_or_else(|| DEFAULT_IMAGE_FILENAME.to_string());
+                (MediaKind::Image, data, filename)
+            }
+            (_, Some(DataValue::Binary(data))) => {
+                let filename = filename.ok_or_else(|| {
+                    crate::error::Error::InvalidNodeInput("filename is required when sending bytes".to_string())
+                })?;
+                (media_kind_for(&filename), data.clone(), filename)
+            }
+            _ => {
+                return Err(crate::error::Error::InvalidNodeInput(
+                    "Either image or bytes is required".to_string(),
+                ))
+            }
+        };
+        check_media_size(kind, data.len()).map_err(crate::error::Error::ValidationError)?;
+
+        let (protocol, rx, queued_for) = {
+            let guard = adapter.blocking_lock();
+            let protocol = guard.get_protocol();
+            let (action, params) = protocol.send_media_action(message_type, target_id, kind, &data, &filename);
+            let (rx, queued_for) = guard.call_action_queued(&action, params)?;
+            (protocol, rx, queued_for)
+        };
+
+        let result = wait_response(rx, DEFAULT_ACTION_TIMEOUT + queued_for)
+            .and_then(|response| protocol.action_result(&response));
+        let (success, message_id) = match result {
+            Ok(data) => (true, message_id_of(&data)),
+            Err(e) => {
+                warn!("[SendFileNode] Failed to send {} to {}: {}", filename, target_id, e);
+                (false, String::new())
+            }
+        };
+
+        let mut outputs = HashMap::new();
+        outputs.insert("success".to_string(), DataValue::Boolean(success));
+        outputs.insert("message_id".to_string(), DataValue::String(message_id));
+
+        self.validate_outputs(&outputs)?;
+        Ok(outputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_oversized_media_is_rejected() {
+        assert!(check_media_size(MediaKind::Image, MAX_IMAGE_BYTES).is_ok());
+        let err = check_media_size(MediaKind::Image, MAX_IMAGE_BYTES + 1).unwrap_err();
+        assert!(err.contains(&(MAX_IMAGE_BYTES + 1).to_string()));
+        assert!(check_media_size(MediaKind::File, MAX_IMAGE_BYTES + 1).is_ok());
+    }
+
+    #[test]
+    fn test_media_kind_from_filename() {
+        assert_eq!(media_kind_for("qr.PNG"), MediaKind::Image);
+        assert_eq!(media_kind_for("report.csv"), MediaKind::File);
+        assert_eq!(media_kind_for("README"), MediaKind::File);
+    }
+}
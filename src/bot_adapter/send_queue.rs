@@ -0,0 +1,238 @@
+use futures_util::{Sink, SinkExt};
+use log::error;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+use tokio_tungstenite::tungstenite::Message as WsMessage;
+
+/// Action frame on its way to the bot server
+#[derive(Debug, Clone)]
+pub struct OutgoingFrame {
+    pub text: String,
+    /// Rate limited messages wait until this time; other actions go out at once
+    pub send_at: Option<Instant>,
+}
+
+/// Spaces out outgoing messages so the bot server doesn't throttle or ban the bot.
+///
+/// Each message reserves a send time at least `min_interval` after the previous message,
+/// and at least `per_target_interval` after the previous message to the same target.
+/// Messages are delayed, never dropped.
+#[derive(Debug, Clone)]
+pub struct SendRateLimiter {
+    min_interval: Duration,
+    per_target_interval: Duration,
+    next_send: Option<Instant>,
+    next_send_per_target: HashMap<String, Instant>,
+}
+
+impl SendRateLimiter {
+    pub fn new(min_interval: Duration) -> Self {
+        Self {
+            min_interval,
+            per_target_interval: Duration::ZERO,
+            next_send: None,
+            next_send_per_target: HashMap::new(),
+        }
+    }
+
+    /// Also keep messages to the same target (user or group) `interval` apart
+    pub fn with_per_target_interval(mut self, interval: Duration) -> Self {
+        self.per_target_interval = interval;
+        self
+    }
+
+    /// Reserve the earliest allowed send time for a message to `target`, given the current time
+    pub fn reserve(&mut self, target: &str, now: Instant) -> Instant {
+        let mut send_at = now;
+        if let Some(next) = self.next_send {
+            send_at = send_at.max(next);
+        }
+        if let Some(next) = self.next_send_per_target.get(target) {
+            send_at = send_at.max(*next);
+        }
+
+        self.next_send = Some(send_at + self.min_interval);
+        if !self.per_target_interval.is_zero() {
+            // Entries in the past no longer delay anything
+            self.next_send_per_target.retain(|_, next| *next > now);
+            self.next_send_per_target
+                .insert(target.to_string(), send_at + self.per_target_interval);
+        }
+        send_at
+    }
+}
+
+/// Frames held back until their send time. They outlive the connection they were queued on,
+/// so a reconnect delays them further instead of dropping them.
+#[derive(Debug, Default)]
+pub struct DelayedFrames {
+    /// Ordered by send time, then by arrival
+    frames: BinaryHeap<Reverse<(Instant, u64, String)>>,
+    arrivals: u64,
+}
+
+impl DelayedFrames {
+    fn push(&mut self, send_at: Instant, text: String) {
+        self.arrivals += 1;
+        self.frames.push(Reverse((send_at, self.arrivals, text)));
+    }
+
+    fn next_due(&self) -> Option<Instant> {
+        self.frames.peek().map(|Reverse((at, _, _))| *at)
+    }
+
+    fn pop(&mut self) -> Option<String> {
+        self.frames.pop().map(|Reverse((_, _, text))| text)
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+}
+
+/// Write queued frames to the WebSocket until the queue closes or a write fails, and return
+/// the frames still waiting, for the next connection. Delayed frames are held back until
+/// their send time without blocking the frames behind them; a frame that fails to write is
+/// kept as well.
+pub async fn write_frames<S>(
+    mut frames: mpsc::UnboundedReceiver<OutgoingFrame>,
+    mut sink: S,
+    mut delayed: DelayedFrames,
+) -> DelayedFrames
+where
+    S: Sink<WsMessage> + Unpin,
+    S::Error: std::fmt::Display,
+{
+    loop {
+        let next_due = delayed.next_due();
+        let text = tokio::select! {
+            frame = frames.recv() => match frame {
+                Some(OutgoingFrame { text, send_at: Some(at) }) if at > Instant::now() => {
+                    delayed.push(at, text);
+                    continue;
+                }
+                Some(frame) => frame.text,
+                None => break,
+            },
+            _ = tokio::time::sleep_until(next_due.unwrap_or_else(Instant::now)), if next_due.is_some() => {
+                match delayed.pop() {
+                    Some(text) => text,
+                    None => continue,
+                }
+            }
+        };
+        if let Err(e) = sink.send(WsMessage::Text(text.clone())).await {
+            error!("Failed to send action: {}", e);
+            delayed.push(Instant::now(), text);
+            break;
+        }
+    }
+    // Frames queued after a failed write are still in the channel
+    frames.close();
+    while let Some(frame) = frames.recv().await {
+        delayed.push(frame.send_at.unwrap_or_else(Instant::now), frame.text);
+    }
+    delayed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_sends_are_spaced_by_min_interval() {
+        let mut limiter = SendRateLimiter::new(Duration::from_millis(100));
+        let now = Instant::now();
+        assert_eq!(limiter.reserve("group:1", now), now);
+        assert_eq!(limiter.reserve("group:2", now), now + Duration::from_millis(100));
+        assert_eq!(limiter.reserve("group:1", now), now + Duration::from_millis(200));
+        // After a quiet period the next message goes out immediately
+        let later = now + Duration::from_secs(5);
+        assert_eq!(limiter.reserve("group:1", later), later);
+    }
+
+    #[test]
+    fn test_per_target_interval_only_delays_the_same_target() {
+        let mut limiter = SendRateLimiter::new(Duration::from_millis(100))
+            .with_per_target_interval(Duration::from_secs(1));
+        let now = Instant::now();
+        assert_eq!(limiter.reserve("group:1", now), now);
+        assert_eq!(limiter.reserve("group:2", now), now + Duration::from_millis(100));
+        assert_eq!(limiter.reserve("group:1", now), now + Duration::from_secs(1));
+    }
+
+    type Sent = Arc<Mutex<Vec<(Instant, String)>>>;
+
+    /// Sink recording when each frame was written, or failing every write
+    fn recording_sink(sent: Sent, fail: bool) -> impl Sink<WsMessage, Error = String> + Unpin {
+        Box::pin(futures_util::sink::unfold(sent, move |sent, message: WsMessage| async move {
+            if fail {
+                return Err("connection reset".to_string());
+            }
+            sent.lock().unwrap().push((Instant::now(), message.to_string()));
+            Ok(sent)
+        }))
+    }
+
+    fn frame(text: &str, send_at: Option<Instant>) -> OutgoingFrame {
+        OutgoingFrame {
+            text: text.to_string(),
+            send_at,
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rate_limited_frames_are_written_spaced() {
+        let min_interval = Duration::from_millis(100);
+        let mut limiter = SendRateLimiter::new(min_interval);
+        let (tx, rx) = mpsc::unbounded_channel();
+        let now = Instant::now();
+        for i in 0..10 {
+            tx.send(frame(&i.to_string(), Some(limiter.reserve("group:1", now)))).unwrap();
+        }
+        // An unlimited action doesn't wait behind them
+        tx.send(frame("status", None)).unwrap();
+
+        let sent = Sent::default();
+        let writer = tokio::spawn(write_frames(rx, recording_sink(sent.clone(), false), DelayedFrames::default()));
+        tokio::time::sleep(min_interval * 10).await;
+        drop(tx);
+        assert!(writer.await.unwrap().is_empty());
+
+        let sent = sent.lock().unwrap();
+        let texts: Vec<&str> = sent.iter().map(|(_, text)| text.as_str()).collect();
+        assert_eq!(texts, ["0", "status", "1", "2", "3", "4", "5", "6", "7", "8", "9"]);
+        let message_times: Vec<Instant> = sent.iter().filter(|(_, text)| text != "status").map(|(at, _)| *at).collect();
+        for pair in message_times.windows(2) {
+            assert_eq!(pair[1] - pair[0], min_interval);
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_unsent_frames_are_kept_for_the_next_connection() {
+        let (tx, rx) = mpsc::unbounded_channel();
+        tx.send(frame("now", None)).unwrap();
+        tx.send(frame("later", Some(Instant::now() + Duration::from_secs(1)))).unwrap();
+        let delayed = write_frames(rx, recording_sink(Sent::default(), true), DelayedFrames::default()).await;
+        assert_eq!(delayed.len(), 2);
+        // The receiver is closed, so further sends on the dead connection fail
+        assert!(tx.send(frame("too late", None)).is_err());
+
+        let (tx, rx) = mpsc::unbounded_channel();
+        let sent = Sent::default();
+        let writer = tokio::spawn(write_frames(rx, recording_sink(sent.clone(), false), delayed));
+        tokio::time::sleep(Duration::from_secs(2)).await;
+        drop(tx);
+        assert!(writer.await.unwrap().is_empty());
+        let texts: Vec<String> = sent.lock().unwrap().iter().map(|(_, text)| text.clone()).collect();
+        assert_eq!(texts, ["now", "later"]);
+    }
+}
@@ -1,8 +1,13 @@
 use std::fs;
+use std::path::Path;
 use serde::Deserialize;
 use log::{info, error};
 
-#[derive(Debug, Deserialize)]
+/// Configuration files looked for in the working directory, in order of preference. The
+/// extension picks the parser.
+pub const CONFIG_PATHS: [&str; 4] = ["config.yaml", "config.yml", "config.toml", "config.json"];
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
 pub struct Config {
     #[serde(rename = "natural_language_model_api")]
     pub natural_language_model_api: Option<String>,
@@ -18,81 +23,248 @@ pub struct Config {
     pub agent_model_name: Option<String>,
 }
 
-/// Load configuration from config.yaml file (LLM settings only)
+/// The first of `CONFIG_PATHS` that exists, `config.yaml` if none does
+pub fn config_path() -> &'static str {
+    CONFIG_PATHS
+        .into_iter()
+        .find(|path| Path::new(path).is_file())
+        .unwrap_or(CONFIG_PATHS[0])
+}
+
+/// Load configuration from the file found by `config_path` (LLM settings only)
 pub fn load_config() -> Config {
-    // Try to load from config.yaml
-    let mut config = match fs::read_to_string("config.yaml") {
-        Ok(content) => {
-            match serde_yaml::from_str(&content) {
-                Ok(config) => {
-                    info!("Loaded configuration from config.yaml");
-                    config
-                }
-                Err(e) => {
-                    error!("Failed to parse config.yaml: {}", e);
-                    Config {
-                        natural_language_model_api: None,
-                        natural_language_model_api_key: None,
-                        natural_language_model_name: None,
-                        agent_model_api: None,
-                        agent_model_api_key: None,
-                        agent_model_name: None,
-                    }
-                }
+    let path = config_path();
+    let mut config = if Path::new(path).is_file() {
+        match read_config_file(Path::new(path)) {
+            Ok(config) => {
+                info!("Loaded configuration from {}", path);
+                config
             }
-        }
-        Err(e) => {
-            info!("Could not read config.yaml ({}), using environment variables", e);
-            Config {
-                natural_language_model_api: None,
-                natural_language_model_api_key: None,
-                natural_language_model_name: None,
-                agent_model_api: None,
-                agent_model_api_key: None,
-                agent_model_name: None,
+            Err(e) => {
+                error!("Failed to load {}: {}", path, e);
+                Config::default()
             }
         }
+    } else {
+        info!("No configuration file found ({}), using environment variables", CONFIG_PATHS.join(", "));
+        Config::default()
     };
+    apply_env_overrides(&mut config);
+    config
+}
 
-    // LLM configs - apply environment variable overrides
-    if config.natural_language_model_api.is_none() {
-        config.natural_language_model_api = std::env::var("natural_language_model_api").ok();
-    }
+fn read_config_file(path: &Path) -> Result<Config, String> {
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    parse_config(&content, path)
+}
 
-    if config.natural_language_model_api_key.is_none() {
-        config.natural_language_model_api_key = std::env::var("natural_language_model_api_key").ok();
+/// Parse `content` in the format given by the extension of `path`
+fn parse_config(content: &str, path: &Path) -> Result<Config, String> {
+    let extension = path.extension().and_then(|ext| ext.to_str()).unwrap_or_default();
+    match extension.to_ascii_lowercase().as_str() {
+        "yaml" | "yml" => serde_yaml::from_str(content).map_err(|e| e.to_string()),
+        "toml" => toml::from_str(content).map_err(|e| e.to_string()),
+        "json" => serde_json::from_str(content).map_err(|e| e.to_string()),
+        _ => Err(format!("Unsupported configuration format '{}', expected yaml, yml, toml or json", extension)),
     }
+}
 
-    if config.natural_language_model_name.is_none() {
-        config.natural_language_model_name = std::env::var("natural_language_model_name").ok();
+/// Fill the settings missing from the file from environment variables of the same name
+fn apply_env_overrides(config: &mut Config) {
+    let fields = [
+        ("natural_language_model_api", &mut config.natural_language_model_api),
+        ("natural_language_model_api_key", &mut config.natural_language_model_api_key),
+        ("natural_language_model_name", &mut config.natural_language_model_name),
+        ("agent_model_api", &mut config.agent_model_api),
+        ("agent_model_api_key", &mut config.agent_model_api_key),
+        ("agent_model_name", &mut config.agent_model_name),
+    ];
+    for (name, value) in fields {
+        if value.is_none() {
+            *value = std::env::var(name).ok();
+        }
     }
+}
 
-    if config.agent_model_api.is_none() {
-        config.agent_model_api = std::env::var("agent_model_api").ok();
-    }
+/// Check that the configuration is consistent, reporting every problem found.
+///
+/// Each model is optional, but once any of its settings is given its api url and model
+/// name are required, and the api url must be http(s).
+pub fn validate(config: &Config) -> Result<(), Vec<String>> {
+    let models = [
+        (
+            "natural_language_model",
+            &config.natural_language_model_api,
+            &config.natural_language_model_api_key,
+            &config.natural_language_model_name,
+        ),
+        ("agent_model", &config.agent_model_api, &config.agent_model_api_key, &config.agent_model_name),
+    ];
 
-    if config.agent_model_api_key.is_none() {
-        config.agent_model_api_key = std::env::var("agent_model_api_key").ok();
+    let mut problems = Vec::new();
+    for (prefix, api, api_key, name) in models {
+        let is_set = |value: &Option<String>| value.as_deref().is_some_and(|v| !v.trim().is_empty());
+        if !is_set(api) && !is_set(api_key) && !is_set(name) {
+            continue;
+        }
+        match api.as_deref().map(str::trim) {
+            Some(url) if !url.is_empty() => {
+                if !url.starts_with("http://") && !url.starts_with("https://") {
+                    problems.push(format!("{prefix}_api must be an http(s) url, got '{url}'"));
+                }
+            }
+            _ => problems.push(format!("{prefix}_api is required when other {prefix} settings are given")),
+        }
+        if !is_set(name) {
+            problems.push(format!("{prefix}_name is required when {prefix}_api is given"));
+        }
     }
 
-    if config.agent_model_name.is_none() {
-        config.agent_model_name = std::env::var("agent_model_name").ok();
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems)
     }
-    
-    config
 }
 
-/// Percent-encode a password for safe inclusion in a URL
+/// Percent-encode a user name or password for the userinfo part of a URL
 pub fn pct_encode(input: &str) -> String {
-    // Encode everything except unreserved characters per RFC 3986: ALPHA / DIGIT / '-' / '.' / '_' / '~'
-    let mut out = String::new();
+    // Encode every UTF-8 byte except unreserved characters per RFC 3986: ALPHA / DIGIT / '-' / '.' / '_' / '~'.
+    // Sub-delims are legal in userinfo too, but some URL parsers choke on them, so they are encoded as well.
+    let mut out = String::with_capacity(input.len());
     for &b in input.as_bytes() {
-        let c = b as char;
-        if c.is_ascii_alphanumeric() || c == '-' || c == '.' || c == '_' || c == '~' {
-            out.push(c);
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~') {
+            out.push(b as char);
         } else {
             out.push_str(&format!("%{:02X}", b));
         }
     }
     out
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn agent_config(api: Option<&str>, api_key: Option<&str>, name: Option<&str>) -> Config {
+        Config {
+            agent_model_api: api.map(str::to_string),
+            agent_model_api_key: api_key.map(str::to_string),
+            agent_model_name: name.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_validate_accepts_complete_and_empty_configs() {
+        assert_eq!(validate(&Config::default()), Ok(()));
+        let config = agent_config(Some("https://api.example.com/v1"), Some("sk-test"), Some("gpt-4o"));
+        assert_eq!(validate(&config), Ok(()));
+        // The api key is optional for local endpoints
+        assert_eq!(validate(&agent_config(Some("http://localhost:8000"), None, Some("qwen"))), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_reports_every_problem() {
+        let problems = validate(&agent_config(None, Some("sk-test"), None)).unwrap_err();
+        assert_eq!(
+            problems,
+            vec![
+                "agent_model_api is required when other agent_model settings are given".to_string(),
+                "agent_model_name is required when agent_model_api is given".to_string(),
+            ]
+        );
+
+        let problems = validate(&agent_config(Some("api.example.com"), None, Some("gpt-4o"))).unwrap_err();
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].contains("http(s) url"), "{:?}", problems);
+
+        let config = Config {
+            natural_language_model_api: Some("https://api.example.com/v1".to_string()),
+            ..agent_config(Some("ftp://api.example.com"), None, Some(" "))
+        };
+        let problems = validate(&config).unwrap_err();
+        assert_eq!(problems.len(), 3, "{:?}", problems);
+        assert!(problems[0].starts_with("natural_language_model_name"));
+        assert!(problems.iter().skip(1).all(|p| p.starts_with("agent_model")));
+    }
+
+    #[test]
+    fn test_same_config_from_yaml_toml_and_json() {
+        let dir = std::env::temp_dir().join(format!("zihuan_config_formats_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let files = [
+            (
+                "config.yaml",
+                "natural_language_model_api: http://localhost:8000/v1\nnatural_language_model_name: qwen\n\
+                 agent_model_api: https://api.example.com/v1\nagent_model_api_key: sk-test\nagent_model_name: \"gpt 4o\"\n",
+            ),
+            (
+                "config.toml",
+                "natural_language_model_api = \"http://localhost:8000/v1\"\nnatural_language_model_name = \"qwen\"\n\
+                 agent_model_api = \"https://api.example.com/v1\"\nagent_model_api_key = \"sk-test\"\n\
+                 agent_model_name = \"gpt 4o\"\n",
+            ),
+            (
+                "config.json",
+                r#"{"natural_language_model_api": "http://localhost:8000/v1", "natural_language_model_name": "qwen",
+                    "agent_model_api": "https://api.example.com/v1", "agent_model_api_key": "sk-test",
+                    "agent_model_name": "gpt 4o"}"#,
+            ),
+        ];
+
+        let expected = Config {
+            natural_language_model_api: Some("http://localhost:8000/v1".to_string()),
+            natural_language_model_name: Some("qwen".to_string()),
+            ..agent_config(Some("https://api.example.com/v1"), Some("sk-test"), Some("gpt 4o"))
+        };
+        for (name, content) in files {
+            let path = dir.join(name);
+            fs::write(&path, content).unwrap();
+            assert_eq!(read_config_file(&path), Ok(expected.clone()), "{}", name);
+        }
+
+        let path = dir.join("config.ini");
+        fs::write(&path, "agent_model_name=gpt").unwrap();
+        assert!(read_config_file(&path).unwrap_err().contains("Unsupported"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    fn pct_decode(input: &str) -> String {
+        let bytes = input.as_bytes();
+        let mut out = Vec::new();
+        let mut i = 0;
+        while i < bytes.len() {
+            if bytes[i] == b'%' {
+                out.push(u8::from_str_radix(&input[i + 1..i + 3], 16).unwrap());
+                i += 3;
+            } else {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_pct_encoded_credentials_round_trip_through_urls() {
+        let password = "p@ss:w/rd?#[]% 密码";
+        let encoded = pct_encode(password);
+        assert!(encoded.bytes().all(|b| b.is_ascii_alphanumeric() || b"-._~%".contains(&b)), "{}", encoded);
+        assert_eq!(pct_encode("Az09-._~"), "Az09-._~");
+
+        let redis_url = format!("redis://:{}@localhost:6379/0", encoded);
+        let info = redis::IntoConnectionInfo::into_connection_info(redis_url.as_str()).unwrap();
+        assert_eq!(info.redis.password.as_deref(), Some(password));
+        assert_eq!(info.redis.db, 0);
+
+        let user = "bot@team";
+        let mysql_url = format!("mysql://{}:{}@127.0.0.1:3306/zihuan", pct_encode(user), encoded);
+        let url = reqwest::Url::parse(&mysql_url).unwrap();
+        assert_eq!(url.host_str(), Some("127.0.0.1"));
+        assert_eq!(url.port(), Some(3306));
+        assert_eq!(url.path(), "/zihuan");
+        assert_eq!(pct_decode(url.username()), user);
+        assert_eq!(pct_decode(url.password().unwrap()), password);
+    }
+}
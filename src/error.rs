@@ -2,6 +2,8 @@ use std::io;
 use std::num::ParseFloatError;
 use redis::RedisError;
 
+use crate::node::graph_io::EdgeDefinition;
+
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
     #[error("IO error: {0}")]
@@ -42,6 +44,44 @@ pub enum Error {
     
     #[error("Invalid node input: {0}")]
     InvalidNodeInput(String),
+
+    /// A node failed while running inside a graph
+    #[error("Validation error: [NODE_ERROR:{node_id}] {source}")]
+    NodeExecution { node_id: String, source: Box<Error> },
+
+    /// Edges of a loaded graph that point at nodes or ports it doesn't have
+    #[error(
+        "Invalid graph: {} edge(s) reference missing nodes or ports: {}",
+        .dangling_edges.len(),
+        describe_edges(.dangling_edges)
+    )]
+    InvalidGraph { dangling_edges: Vec<EdgeDefinition> },
+
+    /// A node did not finish within its time limit
+    #[error("Validation error: [NODE_ERROR:{node_id}] Node '{node_id}' timed out")]
+    Timeout { node_id: String },
+}
+
+impl Error {
+    /// Attach the id of the failing node. Errors that already name a node are kept as is,
+    /// so the innermost node is reported.
+    pub fn node_execution(node_id: impl Into<String>, source: Error) -> Self {
+        match source {
+            Error::NodeExecution { .. } | Error::Timeout { .. } => source,
+            source => Error::NodeExecution {
+                node_id: node_id.into(),
+                source: Box::new(source),
+            },
+        }
+    }
+
+    /// Id of the node this error was raised by, if known
+    pub fn node_id(&self) -> Option<&str> {
+        match self {
+            Error::NodeExecution { node_id, .. } | Error::Timeout { node_id } => Some(node_id),
+            _ => None,
+        }
+    }
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -73,3 +113,11 @@ macro_rules! bail {
         return Err($crate::string_error!($($arg)*))
     };
 }
+
+fn describe_edges(edges: &[EdgeDefinition]) -> String {
+    edges
+        .iter()
+        .map(|edge| format!("{}.{} -> {}.{}", edge.from_node_id, edge.from_port, edge.to_node_id, edge.to_port))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
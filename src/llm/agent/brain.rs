@@ -1,4 +1,5 @@
-use std::sync::Arc;
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
 
 use log::info;
 
@@ -6,20 +7,259 @@ use crate::bot_adapter::adapter::BotAdapter;
 use crate::bot_adapter::models::MessageEvent;
 use crate::bot_adapter::models::message::MessageProp;
 use crate::llm::agent::Agent;
-use crate::llm::{InferenceParam, LLMBase, Message, UserMessage};
+use crate::llm::{InferenceParam, LLMBase, Message, MessageRole, TokenUsage, UserMessage};
 use crate::error::Result;
-use crate::llm::function_tools::FunctionTool;
+use crate::llm::function_tools::{validate_arguments, FunctionTool, ToolCalls};
+use serde_json::{json, Value};
 
 #[derive(Clone)]
 pub struct BrainAgent {
     llm: Arc<dyn LLMBase + Send + Sync>,
     tools: Vec<Arc<dyn FunctionTool>>,
     persona: String,
+    /// Tokens used by every conversation so far, shared between clones
+    total_usage: Arc<Mutex<TokenUsage>>,
+    temperature: Option<f32>,
+    top_p: Option<f32>,
+    max_tokens: Option<u32>,
+    stop: Option<Vec<String>>,
+    /// Trim the conversation to this many estimated tokens before each inference
+    max_context_tokens: Option<usize>,
+    token_estimator: fn(&str) -> usize,
+    tool_timeout: Option<Duration>,
+}
+
+/// Call `tool`, giving up after `timeout` if one is set.
+///
+/// With a timeout the call runs on its own thread, which is why tools must be `Send`
+/// (the `FunctionTool` bound guarantees it). That thread enters the caller's tokio runtime,
+/// if any, since store-backed tools block on it. A call that times out keeps running in
+/// the background and its result is dropped; a panicking tool becomes an error.
+pub fn call_tool_with_timeout(
+    tool: &Arc<dyn FunctionTool>,
+    arguments: Value,
+    timeout: Option<Duration>,
+) -> std::result::Result<Value, String> {
+    let Some(timeout) = timeout else {
+        return tool.call(arguments).map_err(|e| e.to_string());
+    };
+    let (tx, rx) = mpsc::channel();
+    let worker_tool = tool.clone();
+    let runtime = tokio::runtime::Handle::try_current().ok();
+    std::thread::spawn(move || {
+        let _runtime_guard = runtime.as_ref().map(|handle| handle.enter());
+        let _ = tx.send(worker_tool.call(arguments).map_err(|e| e.to_string()));
+    });
+    match rx.recv_timeout(timeout) {
+        Ok(result) => result,
+        Err(mpsc::RecvTimeoutError::Timeout) => Err(format!("tool '{}' timed out after {:?}", tool.name(), timeout)),
+        Err(mpsc::RecvTimeoutError::Disconnected) => Err(format!("tool '{}' panicked", tool.name())),
+    }
+}
+
+fn tool_message(content: String) -> Message {
+    Message {
+        role: MessageRole::Tool,
+        content: Some(content),
+        tool_calls: Vec::new(),
+        usage: None,
+    }
+}
+
+/// Rough token count for when no tokenizer is at hand: about four characters per token
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// Per-message overhead for the role and separators
+const MESSAGE_OVERHEAD_TOKENS: usize = 4;
+
+fn message_tokens(message: &Message, estimate: fn(&str) -> usize) -> usize {
+    let tool_calls: usize = message
+        .tool_calls
+        .iter()
+        .map(|tc| estimate(&tc.function.name) + estimate(&tc.function.arguments.to_string()))
+        .sum();
+    MESSAGE_OVERHEAD_TOKENS + estimate(message.content.as_deref().unwrap_or("")) + tool_calls
+}
+
+/// Drop the oldest messages until `messages` fits in `budget` estimated tokens and return
+/// how many were dropped. Only messages before the latest user message are dropped, never
+/// system messages or the current turn, whose tool calls and results the next request still
+/// needs; if those alone exceed the budget it is an error. An assistant message goes together
+/// with the tool results that answer it, since APIs reject tool results without their call.
+pub fn truncate_to_token_budget(
+    messages: &mut Vec<Message>,
+    budget: usize,
+    estimate: fn(&str) -> usize,
+) -> std::result::Result<usize, String> {
+    let mut total: usize = messages.iter().map(|m| message_tokens(m, estimate)).sum();
+    let mut dropped = 0;
+    while total > budget {
+        let current_turn = messages.iter().rposition(|m| matches!(m.role, MessageRole::User)).unwrap_or(0);
+        let Some(start) = messages[..current_turn]
+            .iter()
+            .position(|m| !matches!(m.role, MessageRole::System))
+        else {
+            return Err(format!(
+                "the current turn needs about {} tokens, more than the context budget of {}",
+                total, budget
+            ));
+        };
+        let mut end = start + 1;
+        if matches!(messages[start].role, MessageRole::Assistant) && !messages[start].tool_calls.is_empty() {
+            while end < current_turn && matches!(messages[end].role, MessageRole::Tool) {
+                end += 1;
+            }
+        }
+        for message in messages.drain(start..end) {
+            total -= message_tokens(&message, estimate);
+            dropped += 1;
+        }
+    }
+    Ok(dropped)
 }
 
 impl BrainAgent {
     pub fn new(llm: Arc<dyn LLMBase + Send + Sync>, tools: Vec<Arc<dyn FunctionTool>>, persona: String) -> Self {
-        Self { llm, tools, persona }
+        Self {
+            llm,
+            tools,
+            persona,
+            total_usage: Arc::new(Mutex::new(TokenUsage::default())),
+            temperature: None,
+            top_p: None,
+            max_tokens: None,
+            stop: None,
+            max_context_tokens: None,
+            token_estimator: estimate_tokens,
+            tool_timeout: None,
+        }
+    }
+
+    /// Stop waiting for a tool after `timeout` and report it to the model as timed out
+    pub fn with_tool_timeout(mut self, timeout: Duration) -> Self {
+        self.tool_timeout = Some(timeout);
+        self
+    }
+
+    /// Keep the conversation within `max_context_tokens`, as counted by `estimate_tokens`
+    pub fn with_max_context_tokens(mut self, max_context_tokens: usize) -> Self {
+        self.max_context_tokens = Some(max_context_tokens);
+        self
+    }
+
+    /// Count tokens with a real tokenizer instead of the character heuristic
+    pub fn with_token_estimator(mut self, estimator: fn(&str) -> usize) -> Self {
+        self.token_estimator = estimator;
+        self
+    }
+
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    pub fn with_top_p(mut self, top_p: f32) -> Self {
+        self.top_p = Some(top_p);
+        self
+    }
+
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    pub fn with_stop(mut self, stop: Vec<String>) -> Self {
+        self.stop = Some(stop);
+        self
+    }
+
+    /// Request for `messages` with this agent's tools and sampling options
+    fn inference_param<'a>(&'a self, messages: &'a Vec<Message>) -> InferenceParam<'a> {
+        InferenceParam {
+            temperature: self.temperature,
+            top_p: self.top_p,
+            max_tokens: self.max_tokens,
+            stop: self.stop.clone(),
+            ..InferenceParam::new(messages).with_tools(&self.tools)
+        }
+    }
+
+    /// Tokens used since this agent was created, as reported by the LLM API
+    pub fn total_usage(&self) -> TokenUsage {
+        *self.total_usage.lock().unwrap()
+    }
+
+    /// Run the tool calls of one response, concurrently when there are several.
+    /// Results come back in call order so the model always sees the same sequence.
+    /// Each call enters the caller's tokio runtime, as in `call_tool_with_timeout`.
+    fn execute_tool_calls(&self, tool_calls: &[ToolCalls]) -> Vec<Message> {
+        if tool_calls.len() <= 1 {
+            return tool_calls.iter().map(|tool_call| self.execute_tool_call(tool_call)).collect();
+        }
+        let runtime = tokio::runtime::Handle::try_current().ok();
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = tool_calls
+                .iter()
+                .map(|tool_call| {
+                    let runtime = runtime.as_ref();
+                    scope.spawn(move || {
+                        let _runtime_guard = runtime.map(|handle| handle.enter());
+                        self.execute_tool_call(tool_call)
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .zip(tool_calls)
+                .map(|(handle, tool_call)| {
+                    handle.join().unwrap_or_else(|_| {
+                        tool_message(format!("Error executing tool: tool '{}' panicked", tool_call.function.name))
+                    })
+                })
+                .collect()
+        })
+    }
+
+    /// Run one tool call and turn its outcome into a tool message
+    fn execute_tool_call(&self, tool_call: &ToolCalls) -> Message {
+        info!("[BrainAgent] executing tool: {}({}) [{}]",
+            tool_call.function.name,
+            tool_call.function.arguments.to_string().as_str(),
+            tool_call.id);
+
+        let Some(tool) = self.tools.iter().find(|t| t.name() == tool_call.function.name) else {
+            info!("[BrainAgent] tool [{}] not found", tool_call.function.name);
+            return tool_message(format!("Tool '{}' not found", tool_call.function.name));
+        };
+        if let Err(reason) = validate_arguments(&tool.parameters(), &tool_call.function.arguments) {
+            info!("[BrainAgent] tool [{}] called with invalid arguments: {}", tool_call.function.name, reason);
+            // Let the model correct the call instead of running the tool
+            return tool_message(json!({"error": "invalid_arguments", "message": reason}).to_string());
+        }
+        match call_tool_with_timeout(tool, tool_call.function.arguments.clone(), self.tool_timeout) {
+            Ok(tool_response) => {
+                info!("[BrainAgent] tool [{}] executed successfully", tool_call.function.name);
+                tool_message(tool_response.to_string())
+            }
+            Err(e) => {
+                info!("[BrainAgent] tool [{}] execution failed: {}", tool_call.function.name, e);
+                tool_message(format!("Error executing tool: {}", e))
+            }
+        }
+    }
+
+    fn record_usage(&self, conversation_usage: TokenUsage) {
+        let mut total = self.total_usage.lock().unwrap();
+        *total += conversation_usage;
+        info!(
+            "[BrainAgent] conversation used {} tokens (prompt {}, completion {}), {} in total",
+            conversation_usage.total_tokens,
+            conversation_usage.prompt_tokens,
+            conversation_usage.completion_tokens,
+            total.total_tokens
+        );
     }
 }
 
@@ -80,6 +320,7 @@ impl Agent for BrainAgent {
         // Tool calling loop: continue until LLM returns a response without tool calls
         let max_iterations = 5;
         let mut iteration = 0;
+        let mut conversation_usage = TokenUsage::default();
         
         loop {
             iteration += 1;
@@ -88,10 +329,23 @@ impl Agent for BrainAgent {
                 break;
             }
 
-            let response = self.llm.inference(&InferenceParam {
-                messages: &brain_message_list,
-                tools: Some(&self.tools),
-            });
+            if let Some(budget) = self.max_context_tokens {
+                let dropped = match truncate_to_token_budget(&mut brain_message_list, budget, self.token_estimator) {
+                    Ok(dropped) => dropped,
+                    Err(e) => {
+                        self.record_usage(conversation_usage);
+                        return Err(crate::error::Error::StringError(e));
+                    }
+                };
+                if dropped > 0 {
+                    info!("[BrainAgent] dropped {} old message(s) to fit {} context tokens", dropped, budget);
+                }
+            }
+
+            let response = self.llm.inference(&self.inference_param(&brain_message_list));
+            if let Some(usage) = response.usage {
+                conversation_usage += usage;
+            }
 
             // If no tool calls, LLM has finished processing
             if response.tool_calls.is_empty() {
@@ -130,56 +384,15 @@ impl Agent for BrainAgent {
             // Clone tool_calls to avoid borrow checker issues when mutating message list
             let tool_calls_to_execute = brain_message_list.last().unwrap().tool_calls.clone();
             
-            // Execute tools and collect their results
-            for tool_call in &tool_calls_to_execute {
-                info!("[BrainAgent] executing tool: {}({}) [{}]", 
-                    tool_call.function.name, 
-                    tool_call.function.arguments.to_string().as_str(),
-                    tool_call.id);
-                
-                if let Some(tool) = self.tools.iter().find(|t| t.name() == tool_call.function.name) {
-                    match tool.call(tool_call.function.arguments.clone()) {
-                        Ok(tool_response) => {
-                            info!("[BrainAgent] tool [{}] executed successfully", tool_call.function.name);
-                            
-                            // Add tool result as a tool message
-                            let tool_msg = Message {
-                                role: crate::llm::MessageRole::Tool,
-                                content: Some(tool_response.to_string()),
-                                tool_calls: Vec::new(),
-                            };
-                            brain_message_list.push(tool_msg);
-                        }
-                        Err(e) => {
-                            info!("[BrainAgent] tool [{}] execution failed: {}", tool_call.function.name, e);
-                            
-                            // Add error message as tool result
-                            let error_msg = Message {
-                                role: crate::llm::MessageRole::Tool,
-                                content: Some(format!("Error executing tool: {}", e)),
-                                tool_calls: Vec::new(),
-                            };
-                            brain_message_list.push(error_msg);
-                        }
-                    }
-                } else {
-                    info!("[BrainAgent] tool [{}] not found", tool_call.function.name);
-                    
-                    // Add error message for missing tool
-                    let error_msg = Message {
-                        role: crate::llm::MessageRole::Tool,
-                        content: Some(format!("Tool '{}' not found", tool_call.function.name)),
-                        tool_calls: Vec::new(),
-                    };
-                    brain_message_list.push(error_msg);
-                }
-            }
-            
+            // Execute tools and collect their results, in call order
+            brain_message_list.extend(self.execute_tool_calls(&tool_calls_to_execute));
+
             // Continue loop to get next LLM response with tool results
             info!("[BrainAgent] iteration {} complete, continuing with {} messages", 
                 iteration, brain_message_list.len());
         }
 
+        self.record_usage(conversation_usage);
         Ok(())
     }
 
@@ -204,4 +417,202 @@ impl crate::bot_adapter::adapter::BrainAgentTrait for BrainAgent {
     fn clone_box(&self) -> crate::bot_adapter::adapter::AgentBox {
         Box::new(self.clone())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::function_tools::ToolCallsFuncSpec;
+    use crate::llm::SystemMessage;
+    use std::time::Instant;
+
+    /// Sleeps before answering, to exercise the tool timeout
+    #[derive(Debug)]
+    struct SlowTool(Duration);
+
+    impl FunctionTool for SlowTool {
+        fn name(&self) -> &str {
+            "slow"
+        }
+
+        fn description(&self) -> &str {
+            "Answers after a delay"
+        }
+
+        fn parameters(&self) -> Value {
+            json!({"type": "object", "properties": {}})
+        }
+
+        fn call(&self, _arguments: Value) -> Result<Value> {
+            std::thread::sleep(self.0);
+            Ok(json!("done"))
+        }
+    }
+
+    /// Needs a tokio runtime, like the tools backed by the message store
+    #[derive(Debug)]
+    struct RuntimeTool;
+
+    impl FunctionTool for RuntimeTool {
+        fn name(&self) -> &str {
+            "runtime"
+        }
+
+        fn description(&self) -> &str {
+            "Answers from inside a tokio runtime"
+        }
+
+        fn parameters(&self) -> Value {
+            json!({"type": "object", "properties": {}})
+        }
+
+        fn call(&self, _arguments: Value) -> Result<Value> {
+            let handle = tokio::runtime::Handle::try_current()
+                .map_err(|e| crate::error::Error::StringError(e.to_string()))?;
+            Ok(json!(handle.block_on(async { "done" })))
+        }
+    }
+
+    /// Never asked anything in these tests
+    #[derive(Debug)]
+    struct UnusedLlm;
+
+    impl LLMBase for UnusedLlm {
+        fn get_model_name(&self) -> &str {
+            "unused"
+        }
+
+        fn inference(&self, _param: &InferenceParam) -> Message {
+            unreachable!("tool tests do not call the model")
+        }
+    }
+
+    fn call(name: &str, id: &str) -> ToolCalls {
+        ToolCalls {
+            id: id.to_string(),
+            type_name: "function".to_string(),
+            function: ToolCallsFuncSpec {
+                name: name.to_string(),
+                arguments: json!({}),
+            },
+        }
+    }
+
+    #[test]
+    fn test_tool_calls_run_concurrently_in_call_order() {
+        let delay = Duration::from_millis(300);
+        let agent = BrainAgent::new(Arc::new(UnusedLlm), vec![Arc::new(SlowTool(delay))], String::new());
+        let calls = [call("slow", "call_1"), call("missing", "call_2"), call("slow", "call_3")];
+
+        let started = Instant::now();
+        let results = agent.execute_tool_calls(&calls);
+        let elapsed = started.elapsed();
+
+        assert!(elapsed >= delay);
+        assert!(elapsed < delay * 2, "tool calls ran sequentially: {:?}", elapsed);
+        let contents: Vec<_> = results.iter().map(|m| m.content.as_deref().unwrap_or("")).collect();
+        assert_eq!(contents, ["\"done\"", "Tool 'missing' not found", "\"done\""]);
+    }
+
+    #[test]
+    fn test_tool_timeout_stops_waiting_for_slow_tool() {
+        let slow: Arc<dyn FunctionTool> = Arc::new(SlowTool(Duration::from_secs(5)));
+        let started = Instant::now();
+        let err = call_tool_with_timeout(&slow, json!({}), Some(Duration::from_millis(50))).unwrap_err();
+        assert!(err.contains("timed out"));
+        assert!(started.elapsed() < Duration::from_secs(2));
+
+        let quick: Arc<dyn FunctionTool> = Arc::new(SlowTool(Duration::from_millis(1)));
+        let result = call_tool_with_timeout(&quick, json!({}), Some(Duration::from_secs(5))).unwrap();
+        assert_eq!(result, json!("done"));
+    }
+
+    #[test]
+    fn test_tool_with_timeout_runs_in_callers_runtime() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let _guard = runtime.enter();
+        let tool: Arc<dyn FunctionTool> = Arc::new(RuntimeTool);
+        let result = call_tool_with_timeout(&tool, json!({}), Some(Duration::from_secs(5))).unwrap();
+        assert_eq!(result, json!("done"));
+    }
+
+    #[test]
+    fn test_parallel_tool_calls_run_in_callers_runtime() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let _guard = runtime.enter();
+        let agent = BrainAgent::new(Arc::new(UnusedLlm), vec![Arc::new(RuntimeTool)], String::new());
+        let results = agent.execute_tool_calls(&[call("runtime", "call_1"), call("runtime", "call_2")]);
+        let contents: Vec<_> = results.iter().map(|m| m.content.as_deref().unwrap_or("")).collect();
+        assert_eq!(contents, ["\"done\"", "\"done\""]);
+    }
+
+    fn message(role: MessageRole, content: &str) -> Message {
+        Message {
+            role,
+            content: Some(content.to_string()),
+            tool_calls: Vec::new(),
+            usage: None,
+        }
+    }
+
+    fn tool_call_turn() -> Vec<Message> {
+        let mut call = message(MessageRole::Assistant, "");
+        call.tool_calls.push(ToolCalls {
+            id: "call_1".to_string(),
+            type_name: "function".to_string(),
+            function: ToolCallsFuncSpec {
+                name: "chat_history".to_string(),
+                arguments: serde_json::json!({"sender_id": "10001"}),
+            },
+        });
+        vec![call, message(MessageRole::Tool, &"history ".repeat(50))]
+    }
+
+    #[test]
+    fn test_truncation_keeps_system_and_latest_user_message() {
+        let mut messages = vec![SystemMessage("persona")];
+        for i in 0..20 {
+            messages.push(UserMessage(format!("old question {} {}", i, "x".repeat(40))));
+            messages.push(message(MessageRole::Assistant, &"y".repeat(40)));
+        }
+        messages.push(UserMessage("latest question"));
+        messages.extend(tool_call_turn());
+        let before = messages.len();
+
+        let dropped = truncate_to_token_budget(&mut messages, 200, estimate_tokens).unwrap();
+        assert!(dropped > 0);
+        assert_eq!(messages.len(), before - dropped);
+        assert!(messages.iter().map(|m| message_tokens(m, estimate_tokens)).sum::<usize>() <= 200);
+        assert!(matches!(messages[0].role, MessageRole::System));
+        // The current turn, including its tool call and result, is kept whole
+        let current_turn = &messages[messages.len() - 3..];
+        assert_eq!(current_turn[0].content.as_deref(), Some("latest question"));
+        assert!(!current_turn[1].tool_calls.is_empty());
+        assert!(matches!(current_turn[2].role, MessageRole::Tool));
+    }
+
+    #[test]
+    fn test_truncation_drops_tool_results_with_their_call() {
+        let mut messages = vec![SystemMessage("persona"), UserMessage("hi")];
+        messages.extend(tool_call_turn());
+        messages.push(message(MessageRole::Assistant, "done"));
+        messages.push(UserMessage("thanks"));
+
+        truncate_to_token_budget(&mut messages, 30, estimate_tokens).unwrap();
+        assert!(messages.iter().all(|m| !matches!(m.role, MessageRole::Tool)));
+        assert_eq!(messages.last().unwrap().content.as_deref(), Some("thanks"));
+    }
+
+    #[test]
+    fn test_truncation_never_drops_the_current_turn() {
+        let mut messages = vec![SystemMessage("persona"), UserMessage("hi")];
+        messages.extend(tool_call_turn());
+        let before = messages.len();
+        assert!(truncate_to_token_budget(&mut messages, 30, estimate_tokens).is_err());
+        assert_eq!(messages.len(), before);
+
+        let mut messages = vec![SystemMessage("x".repeat(400)), UserMessage("hi")];
+        assert!(truncate_to_token_budget(&mut messages, 10, estimate_tokens).is_err());
+        assert_eq!(messages.len(), 2);
+    }
+}
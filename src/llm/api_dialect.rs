@@ -0,0 +1,464 @@
+use super::function_tools::{ToolCalls, ToolCallsFuncSpec};
+use super::{InferenceParam, Message, MessageRole, TokenUsage, ToolChoice, role_to_str, str_to_role};
+use serde_json::{Value, json};
+
+/// Value of the `anthropic-version` header the request shapes below follow
+pub const ANTHROPIC_VERSION: &str = "2023-06-01";
+/// Anthropic rejects requests without `max_tokens`
+const ANTHROPIC_DEFAULT_MAX_TOKENS: u32 = 4096;
+
+/// Request/response shape spoken by the chat API behind an endpoint
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ApiDialect {
+    /// OpenAI chat completions, also used by DeepSeek, vLLM and most proxies
+    #[default]
+    OpenAI,
+    /// Ollama's native `/api/chat`
+    Ollama,
+    /// Anthropic Messages API (`/v1/messages`)
+    Anthropic,
+}
+
+impl ApiDialect {
+    /// Parse "openai" / "ollama" / "anthropic", ignoring case and surrounding whitespace
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "openai" => Some(ApiDialect::OpenAI),
+            "ollama" => Some(ApiDialect::Ollama),
+            "anthropic" => Some(ApiDialect::Anthropic),
+            _ => None,
+        }
+    }
+}
+
+/// Tool call ids are optional in Ollama, so number them when missing
+fn fallback_tool_call_id(index: usize) -> String {
+    format!("call_{}", index)
+}
+
+/// Body for Ollama `/api/chat`. Ollama streams NDJSON by default, so streaming is always off.
+/// Ollama has no `tool_choice`; `ToolChoice::None` leaves the tools out, other choices are ignored.
+/// JSON mode maps to Ollama's `format: "json"`.
+pub fn ollama_request_body(model: &str, param: &InferenceParam) -> Value {
+    let messages: Vec<Value> = param
+        .messages
+        .iter()
+        .map(|msg| {
+            let mut msg_obj = json!({
+                "role": role_to_str(&msg.role),
+                "content": msg.content.as_deref().unwrap_or(""),
+            });
+            if !msg.tool_calls.is_empty() {
+                let tool_calls: Vec<Value> = msg
+                    .tool_calls
+                    .iter()
+                    .map(|tc| {
+                        json!({
+                            "function": {
+                                "name": tc.function.name,
+                                "arguments": tc.function.arguments,
+                            }
+                        })
+                    })
+                    .collect();
+                msg_obj["tool_calls"] = json!(tool_calls);
+            }
+            msg_obj
+        })
+        .collect();
+
+    let mut request_body = json!({
+        "model": model,
+        "messages": messages,
+        "stream": false,
+    });
+    if param.json_mode {
+        request_body["format"] = json!("json");
+    }
+    if let Some(tools) = param.tools.filter(|_| param.tool_choice != Some(ToolChoice::None)) {
+        let tools: Vec<Value> = tools
+            .iter()
+            .map(|tool| json!({"type": "function", "function": tool.get_json()}))
+            .collect();
+        request_body["tools"] = json!(tools);
+    }
+
+    // Sampling options live under `options`, with Ollama's own names
+    let mut options = serde_json::Map::new();
+    if let Some(temperature) = param.temperature {
+        options.insert("temperature".to_string(), json!(temperature));
+    }
+    if let Some(top_p) = param.top_p {
+        options.insert("top_p".to_string(), json!(top_p));
+    }
+    if let Some(max_tokens) = param.max_tokens {
+        options.insert("num_predict".to_string(), json!(max_tokens));
+    }
+    if let Some(stop) = param.stop.as_ref().filter(|stop| !stop.is_empty()) {
+        options.insert("stop".to_string(), json!(stop));
+    }
+    if !options.is_empty() {
+        request_body["options"] = Value::Object(options);
+    }
+    request_body
+}
+
+/// Parse an Ollama `/api/chat` response (`{"message": {...}, "prompt_eval_count": ..}`)
+pub fn parse_ollama_message(api_resp: &Value) -> Option<Message> {
+    let msg = api_resp.get("message")?;
+    let role = str_to_role(msg.get("role").and_then(Value::as_str).unwrap_or("assistant"));
+    let content = msg
+        .get("content")
+        .and_then(Value::as_str)
+        .filter(|content| !content.is_empty())
+        .map(str::to_string);
+    let tool_calls = msg
+        .get("tool_calls")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .enumerate()
+        .filter_map(|(index, tc)| {
+            let func = tc.get("function")?;
+            Some(ToolCalls {
+                id: tc
+                    .get("id")
+                    .and_then(Value::as_str)
+                    .map(str::to_string)
+                    .unwrap_or_else(|| fallback_tool_call_id(index)),
+                type_name: "function".to_string(),
+                function: ToolCallsFuncSpec {
+                    name: func.get("name")?.as_str()?.to_string(),
+                    arguments: func.get("arguments").cloned().unwrap_or(Value::Null),
+                },
+            })
+        })
+        .collect();
+
+    let count = |key: &str| api_resp.get(key).and_then(Value::as_u64);
+    let usage = match (count("prompt_eval_count"), count("eval_count")) {
+        (None, None) => None,
+        (prompt, completion) => {
+            let (prompt_tokens, completion_tokens) = (prompt.unwrap_or(0), completion.unwrap_or(0));
+            Some(TokenUsage {
+                prompt_tokens,
+                completion_tokens,
+                total_tokens: prompt_tokens + completion_tokens,
+            })
+        }
+    };
+
+    Some(Message {
+        role,
+        content,
+        tool_calls,
+        usage,
+    })
+}
+
+/// Body for the Anthropic Messages API.
+///
+/// System messages move to the top-level `system` field, assistant tool calls become
+/// `tool_use` blocks and tool messages become `tool_result` blocks in a user turn.
+/// `Message` does not record which call a tool message answers, so results are matched
+/// to the preceding assistant's calls in order. The Messages API has no JSON mode, so
+/// `json_mode` is left to the prompt.
+pub fn anthropic_request_body(model: &str, param: &InferenceParam) -> Value {
+    let mut system = Vec::new();
+    let mut messages: Vec<Value> = Vec::new();
+    let mut pending_tool_ids: std::collections::VecDeque<String> = Default::default();
+
+    for msg in param.messages {
+        let text = msg.content.as_deref().unwrap_or("");
+        match msg.role {
+            MessageRole::System => system.push(text),
+            MessageRole::User => messages.push(json!({"role": "user", "content": text})),
+            MessageRole::Assistant => {
+                pending_tool_ids = msg.tool_calls.iter().map(|tc| tc.id.clone()).collect();
+                if msg.tool_calls.is_empty() {
+                    messages.push(json!({"role": "assistant", "content": text}));
+                    continue;
+                }
+                let mut blocks = Vec::new();
+                if !text.is_empty() {
+                    blocks.push(json!({"type": "text", "text": text}));
+                }
+                for tc in &msg.tool_calls {
+                    let input = match &tc.function.arguments {
+                        Value::Null => json!({}),
+                        arguments => arguments.clone(),
+                    };
+                    blocks.push(json!({
+                        "type": "tool_use",
+                        "id": tc.id,
+                        "name": tc.function.name,
+                        "input": input,
+                    }));
+                }
+                messages.push(json!({"role": "assistant", "content": blocks}));
+            }
+            MessageRole::Tool => {
+                let block = json!({
+                    "type": "tool_result",
+                    "tool_use_id": pending_tool_ids.pop_front().unwrap_or_default(),
+                    "content": text,
+                });
+                // Results of one assistant turn share a single user turn
+                let previous_results = messages
+                    .last_mut()
+                    .filter(|last| last["role"] == "user")
+                    .and_then(|last| last["content"].as_array_mut())
+                    .filter(|blocks| blocks.iter().all(|b| b["type"] == "tool_result"));
+                match previous_results {
+                    Some(blocks) => blocks.push(block),
+                    None => messages.push(json!({"role": "user", "content": [block]})),
+                }
+            }
+        }
+    }
+
+    let mut request_body = json!({
+        "model": model,
+        "messages": messages,
+        "max_tokens": param.max_tokens.unwrap_or(ANTHROPIC_DEFAULT_MAX_TOKENS),
+    });
+    if !system.is_empty() {
+        request_body["system"] = json!(system.join("\n\n"));
+    }
+    if let Some(tools) = param.tools {
+        let tools: Vec<Value> = tools
+            .iter()
+            .map(|tool| {
+                json!({
+                    "name": tool.name(),
+                    "description": tool.description(),
+                    "input_schema": tool.parameters(),
+                })
+            })
+            .collect();
+        request_body["tools"] = json!(tools);
+        if let Some(tool_choice) = &param.tool_choice {
+            request_body["tool_choice"] = match tool_choice {
+                ToolChoice::Auto => json!({"type": "auto"}),
+                ToolChoice::None => json!({"type": "none"}),
+                ToolChoice::Required => json!({"type": "any"}),
+                ToolChoice::Named(name) => json!({"type": "tool", "name": name}),
+            };
+        }
+    }
+    if let Some(temperature) = param.temperature {
+        request_body["temperature"] = json!(temperature);
+    }
+    if let Some(top_p) = param.top_p {
+        request_body["top_p"] = json!(top_p);
+    }
+    if let Some(stop) = param.stop.as_ref().filter(|stop| !stop.is_empty()) {
+        request_body["stop_sequences"] = json!(stop);
+    }
+    request_body
+}
+
+/// Parse an Anthropic Messages response, joining its text blocks and collecting `tool_use` blocks
+pub fn parse_anthropic_message(api_resp: &Value) -> Option<Message> {
+    let blocks = api_resp.get("content")?.as_array()?;
+    let role = str_to_role(api_resp.get("role").and_then(Value::as_str).unwrap_or("assistant"));
+
+    let mut text = String::new();
+    let mut tool_calls = Vec::new();
+    for block in blocks {
+        match block.get("type").and_then(Value::as_str) {
+            Some("text") => text.push_str(block.get("text").and_then(Value::as_str).unwrap_or("")),
+            Some("tool_use") => {
+                let (Some(id), Some(name)) = (
+                    block.get("id").and_then(Value::as_str),
+                    block.get("name").and_then(Value::as_str),
+                ) else {
+                    continue;
+                };
+                tool_calls.push(ToolCalls {
+                    id: id.to_string(),
+                    type_name: "function".to_string(),
+                    function: ToolCallsFuncSpec {
+                        name: name.to_string(),
+                        arguments: block.get("input").cloned().unwrap_or(Value::Null),
+                    },
+                });
+            }
+            _ => {}
+        }
+    }
+
+    let usage = api_resp.get("usage").map(|usage| {
+        let count = |key: &str| usage.get(key).and_then(Value::as_u64).unwrap_or(0);
+        TokenUsage {
+            prompt_tokens: count("input_tokens"),
+            completion_tokens: count("output_tokens"),
+            total_tokens: count("input_tokens") + count("output_tokens"),
+        }
+    });
+
+    Some(Message {
+        role,
+        content: if text.is_empty() { None } else { Some(text) },
+        tool_calls,
+        usage,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::function_tools::FunctionTool;
+    use std::sync::Arc;
+
+    #[derive(Debug)]
+    struct SearchTool;
+
+    impl FunctionTool for SearchTool {
+        fn name(&self) -> &str {
+            "search"
+        }
+
+        fn description(&self) -> &str {
+            "Search the web"
+        }
+
+        fn parameters(&self) -> Value {
+            json!({"type": "object", "properties": {"q": {"type": "string"}}, "required": ["q"]})
+        }
+
+        fn call(&self, _arguments: Value) -> crate::error::Result<Value> {
+            Ok(json!("no results"))
+        }
+    }
+
+    fn assistant_calling_search() -> Message {
+        Message {
+            role: MessageRole::Assistant,
+            content: None,
+            tool_calls: vec![ToolCalls {
+                id: "toolu_01".to_string(),
+                type_name: "function".to_string(),
+                function: ToolCallsFuncSpec {
+                    name: "search".to_string(),
+                    arguments: json!({"q": "rust"}),
+                },
+            }],
+            usage: None,
+        }
+    }
+
+    fn tool_result(content: &str) -> Message {
+        Message {
+            role: MessageRole::Tool,
+            content: Some(content.to_string()),
+            tool_calls: Vec::new(),
+            usage: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_dialect_name() {
+        assert_eq!(ApiDialect::parse(" Anthropic "), Some(ApiDialect::Anthropic));
+        assert_eq!(ApiDialect::parse("ollama"), Some(ApiDialect::Ollama));
+        assert_eq!(ApiDialect::parse("gemini"), None);
+        assert_eq!(ApiDialect::default(), ApiDialect::OpenAI);
+    }
+
+    #[test]
+    fn test_parse_recorded_ollama_response() {
+        let response = json!({
+            "model": "qwen2.5:7b",
+            "created_at": "2024-11-02T10:21:44.123456Z",
+            "message": {
+                "role": "assistant",
+                "content": "",
+                "tool_calls": [{"function": {"name": "search", "arguments": {"q": "rust"}}}]
+            },
+            "done_reason": "stop",
+            "done": true,
+            "total_duration": 812345678,
+            "prompt_eval_count": 26,
+            "eval_count": 18
+        });
+        let message = parse_ollama_message(&response).unwrap();
+        assert!(matches!(message.role, MessageRole::Assistant));
+        assert_eq!(message.content, None);
+        assert_eq!(message.tool_calls.len(), 1);
+        assert_eq!(message.tool_calls[0].id, "call_0");
+        assert_eq!(message.tool_calls[0].function.arguments, json!({"q": "rust"}));
+        assert_eq!(message.usage.map(|usage| usage.total_tokens), Some(44));
+    }
+
+    #[test]
+    fn test_parse_recorded_anthropic_response() {
+        let response = json!({
+            "id": "msg_01XFDUDYJgAACzvnptvVoYEL",
+            "type": "message",
+            "role": "assistant",
+            "model": "claude-3-5-sonnet-20241022",
+            "content": [
+                {"type": "text", "text": "Let me look that up."},
+                {"type": "tool_use", "id": "toolu_01", "name": "search", "input": {"q": "rust"}}
+            ],
+            "stop_reason": "tool_use",
+            "stop_sequence": null,
+            "usage": {"input_tokens": 120, "output_tokens": 40}
+        });
+        let message = parse_anthropic_message(&response).unwrap();
+        assert_eq!(message.content.as_deref(), Some("Let me look that up."));
+        assert_eq!(message.tool_calls[0].id, "toolu_01");
+        assert_eq!(message.tool_calls[0].function.name, "search");
+        assert_eq!(
+            message.usage,
+            Some(TokenUsage {
+                prompt_tokens: 120,
+                completion_tokens: 40,
+                total_tokens: 160,
+            })
+        );
+    }
+
+    #[test]
+    fn test_anthropic_body_moves_system_and_tool_results() {
+        let messages = vec![
+            Message::system("You are terse."),
+            Message::user("find rust"),
+            assistant_calling_search(),
+            tool_result("no results"),
+        ];
+        let tools: Vec<Arc<dyn FunctionTool>> = vec![Arc::new(SearchTool)];
+        let param = InferenceParam::new(&messages)
+            .with_tools(&tools)
+            .with_tool_choice(ToolChoice::Required);
+        let body = anthropic_request_body("claude", &param);
+
+        assert_eq!(body["system"], json!("You are terse."));
+        assert_eq!(body["max_tokens"], json!(ANTHROPIC_DEFAULT_MAX_TOKENS));
+        assert_eq!(body["tools"][0]["input_schema"]["required"], json!(["q"]));
+        assert_eq!(body["tool_choice"], json!({"type": "any"}));
+        let turns = body["messages"].as_array().unwrap();
+        assert_eq!(turns.len(), 3);
+        assert_eq!(turns[1]["content"][0]["type"], json!("tool_use"));
+        assert_eq!(
+            turns[2],
+            json!({"role": "user", "content": [
+                {"type": "tool_result", "tool_use_id": "toolu_01", "content": "no results"}
+            ]})
+        );
+    }
+
+    #[test]
+    fn test_ollama_body_puts_sampling_under_options() {
+        let messages = vec![Message::user("hi"), assistant_calling_search()];
+        let param = InferenceParam::new(&messages).with_temperature(0.5).with_max_tokens(32);
+        let body = ollama_request_body("qwen2.5:7b", &param);
+
+        assert_eq!(body["stream"], json!(false));
+        assert!(body.get("format").is_none());
+        assert_eq!(ollama_request_body("qwen2.5:7b", &param.with_json_mode())["format"], json!("json"));
+        assert_eq!(body["options"], json!({"temperature": 0.5, "num_predict": 32}));
+        assert_eq!(body["messages"][1]["content"], json!(""));
+        assert_eq!(body["messages"][1]["tool_calls"][0]["function"]["arguments"], json!({"q": "rust"}));
+    }
+}
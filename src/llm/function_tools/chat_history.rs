@@ -1,6 +1,6 @@
 use super::FunctionTool;
 use crate::error::Result;
-use crate::util::message_store::MessageStore;
+use crate::util::message_store::{MessageQuery, MessageStore};
 use serde_json::{json, Value};
 use std::sync::Arc;
 use tokio::sync::Mutex as TokioMutex;
@@ -74,11 +74,19 @@ impl FunctionTool for ChatHistoryTool {
         // Use current runtime handle to block on async operation
         let handle = Handle::current();
         let store = self.message_store.clone();
-        
-        let result = handle.block_on(async move {
-            let store_guard = store.lock().await;
-            store_guard.get_messages_by_sender(sender_id, group_id, limit).await
-        })?;
+        let filter = MessageQuery {
+            sender_id: Some(sender_id.to_string()),
+            group_id: group_id.map(str::to_string),
+            limit: Some(limit),
+            ..Default::default()
+        };
+
+        let result = handle
+            .block_on(async move {
+                let store_guard = store.lock().await;
+                store_guard.query_records(filter).await
+            })
+            .map_err(crate::error::Error::StringError)?;
 
         // Format results as JSON array
         let messages: Vec<Value> = result
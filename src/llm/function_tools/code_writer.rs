@@ -56,10 +56,10 @@ impl FunctionTool for CodeWriterTool {
         };
 
         let messages = vec![
-            Message { role: MessageRole::System, content: Some(system.to_string()), tool_calls: Vec::new() },
-            Message { role: MessageRole::User, content: Some(user_prompt), tool_calls: Vec::new() },
+            Message { role: MessageRole::System, content: Some(system.to_string()), tool_calls: Vec::new(), usage: None },
+            Message { role: MessageRole::User, content: Some(user_prompt), tool_calls: Vec::new(), usage: None },
         ];
-        let param = InferenceParam { messages: &messages, tools: None };
+        let param = InferenceParam::new(&messages);
         let resp = self.llm.inference(&param);
         let content = resp.content.unwrap_or_default();
         Ok(json!({ "code": content }))
@@ -0,0 +1,203 @@
+use super::FunctionTool;
+use crate::error::Result;
+use crate::util::http_client::{HttpRequest, RetryingHttpClient};
+use serde_json::{json, Value};
+
+const METHODS: [&str; 5] = ["GET", "POST", "PUT", "PATCH", "DELETE"];
+/// Longer bodies are cut so one response can't flood the model's context
+const MAX_BODY_CHARS: usize = 8000;
+
+/// HTTP request tool: send a request to one of a fixed set of hosts.
+///
+/// Parameters:
+/// - method (string, required): GET, POST, PUT, PATCH or DELETE
+/// - url (string, required): http(s) URL whose host is on the allowlist
+/// - headers (object, optional): extra request headers
+/// - body (string, optional): request body
+///
+/// Requests to any host not given at construction are refused, so the model can't be
+/// talked into reaching internal services. Redirects are not followed for the same reason.
+pub struct HttpRequestTool {
+    allowed_hosts: Vec<String>,
+    client: Box<dyn HttpRequest>,
+}
+
+impl std::fmt::Debug for HttpRequestTool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HttpRequestTool").field("allowed_hosts", &self.allowed_hosts).finish_non_exhaustive()
+    }
+}
+
+impl HttpRequestTool {
+    pub fn new(allowed_hosts: Vec<String>) -> Self {
+        Self::with_client(allowed_hosts, Box::new(RetryingHttpClient::without_redirects()))
+    }
+
+    /// Use a custom HTTP client instead of the default retrying one
+    pub fn with_client(allowed_hosts: Vec<String>, client: Box<dyn HttpRequest>) -> Self {
+        Self {
+            allowed_hosts: allowed_hosts.into_iter().map(|host| host.trim().to_ascii_lowercase()).collect(),
+            client,
+        }
+    }
+
+    /// Only exact host matches pass, subdomains have to be listed separately
+    fn check_url(&self, url: &str) -> std::result::Result<(), String> {
+        let parsed = reqwest::Url::parse(url).map_err(|e| format!("invalid url '{}': {}", url, e))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(format!("unsupported url scheme '{}'", parsed.scheme()));
+        }
+        let host = parsed.host_str().unwrap_or("").to_ascii_lowercase();
+        if !self.allowed_hosts.contains(&host) {
+            return Err(format!("host '{}' is not in the allowlist", host));
+        }
+        Ok(())
+    }
+}
+
+impl FunctionTool for HttpRequestTool {
+    fn name(&self) -> &str { "http_request" }
+
+    fn description(&self) -> &str {
+        "Send an HTTP request to an allowed host. Returns the status code and response body."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "method": { "type": "string", "enum": METHODS, "description": "HTTP method" },
+                "url": { "type": "string", "description": "Request URL; only allowlisted hosts are reachable" },
+                "headers": {
+                    "type": "object",
+                    "description": "Extra request headers",
+                    "additionalProperties": { "type": "string" }
+                },
+                "body": { "type": "string", "description": "Request body" }
+            },
+            "required": ["method", "url"],
+            "additionalProperties": false
+        })
+    }
+
+    fn call(&self, arguments: Value) -> Result<Value> {
+        let method = arguments
+            .get("method")
+            .and_then(|v| v.as_str())
+            .map(str::to_ascii_uppercase)
+            .ok_or_else(|| crate::string_error!("missing required parameter: method"))?;
+        if !METHODS.contains(&method.as_str()) {
+            return Err(crate::string_error!("unsupported method: {}", method));
+        }
+        let url = arguments
+            .get("url")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| crate::string_error!("missing required parameter: url"))?;
+        self.check_url(url).map_err(crate::error::Error::StringError)?;
+
+        let headers: Vec<(String, String)> = arguments
+            .get("headers")
+            .and_then(|v| v.as_object())
+            .into_iter()
+            .flatten()
+            .map(|(name, value)| {
+                let value = match value {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                (name.clone(), value)
+            })
+            .collect();
+        let body = arguments.get("body").and_then(|v| v.as_str()).unwrap_or("");
+
+        let response = self
+            .client
+            .request(&method, url, &headers, body.as_bytes())
+            .map_err(|e| crate::string_error!("{} {} failed: {}", method, url, e))?;
+        let truncated = response.body.chars().count() > MAX_BODY_CHARS;
+        let body: String = response.body.chars().take(MAX_BODY_CHARS).collect();
+        Ok(json!({
+            "status": response.status,
+            "body": body,
+            "truncated": truncated,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::http_client::HttpResponse;
+    use std::sync::{Arc, Mutex};
+
+    /// (method, url, body)
+    type Recorded = Arc<Mutex<Vec<(String, String, String)>>>;
+
+    /// Records each request and answers 200
+    struct MockClient {
+        requests: Recorded,
+    }
+
+    impl HttpRequest for MockClient {
+        fn request(
+            &self,
+            method: &str,
+            url: &str,
+            _headers: &[(String, String)],
+            body: &[u8],
+        ) -> std::result::Result<HttpResponse, String> {
+            let body = String::from_utf8_lossy(body).to_string();
+            self.requests.lock().unwrap().push((method.to_string(), url.to_string(), body));
+            Ok(HttpResponse {
+                status: 200,
+                body: "{\"ok\":true}".to_string(),
+            })
+        }
+    }
+
+    fn tool() -> (HttpRequestTool, Recorded) {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let client = MockClient {
+            requests: requests.clone(),
+        };
+        (HttpRequestTool::with_client(vec!["API.example.com".to_string()], Box::new(client)), requests)
+    }
+
+    #[test]
+    fn test_parameters_schema() {
+        let schema = tool().0.parameters();
+        assert_eq!(schema["required"], json!(["method", "url"]));
+        assert_eq!(schema["properties"]["method"]["enum"], json!(METHODS));
+    }
+
+    #[test]
+    fn test_allowlisted_request_returns_status_and_body() {
+        let (tool, requests) = tool();
+        let result = tool
+            .call(json!({"method": "post", "url": "https://api.example.com/v1/items", "body": "{\"name\":\"a\"}"}))
+            .unwrap();
+        assert_eq!(result, json!({"status": 200, "body": "{\"ok\":true}", "truncated": false}));
+        assert_eq!(
+            requests.lock().unwrap()[0],
+            (
+                "POST".to_string(),
+                "https://api.example.com/v1/items".to_string(),
+                "{\"name\":\"a\"}".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_hosts_outside_allowlist_are_refused() {
+        let (tool, requests) = tool();
+        for url in [
+            "http://169.254.169.254/latest/meta-data",
+            "https://evil.example.com/",
+            "https://api.example.com.evil.org/",
+            "file:///etc/passwd",
+        ] {
+            assert!(tool.call(json!({"method": "GET", "url": url})).is_err(), "{} should be refused", url);
+        }
+        assert!(requests.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,135 @@
+use super::FunctionTool;
+use crate::error::Result;
+use crate::util::message_store::MessageStore;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tokio::sync::Mutex as TokioMutex;
+use tokio::runtime::Handle;
+
+const DEFAULT_LIMIT: u64 = 20;
+const MAX_LIMIT: u64 = 100;
+
+/// Search stored messages by content, so the model can recall what was discussed before.
+///
+/// Parameters:
+/// - query (string, required): keyword or phrase to look for
+/// - group_id (string, optional): only search messages of this group
+/// - limit (integer, optional): number of messages to return, 20 by default, at most 100
+///
+/// Matches come from `MessageStore::search_content`, newest first.
+#[derive(Clone, Debug)]
+pub struct MessageSearchTool {
+    message_store: Arc<TokioMutex<MessageStore>>,
+}
+
+impl MessageSearchTool {
+    pub fn new(message_store: Arc<TokioMutex<MessageStore>>) -> Self {
+        Self { message_store }
+    }
+}
+
+impl FunctionTool for MessageSearchTool {
+    fn name(&self) -> &str { "message_search" }
+
+    fn description(&self) -> &str {
+        "Search past chat messages by keyword. Returns matching messages with sender and time, newest first. Use this to remember what was discussed earlier."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "query": { "type": "string", "description": "Keyword or phrase to search for" },
+                "group_id": {
+                    "type": "string",
+                    "description": "Optional group ID to only search messages from that group"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Number of messages to return (default 20, at most 100)",
+                    "minimum": 1,
+                    "maximum": MAX_LIMIT
+                }
+            },
+            "required": ["query"],
+            "additionalProperties": false
+        })
+    }
+
+    fn call(&self, arguments: Value) -> Result<Value> {
+        let query = arguments
+            .get("query")
+            .and_then(|v| v.as_str())
+            .filter(|q| !q.trim().is_empty())
+            .ok_or_else(|| crate::string_error!("missing required parameter: query"))?;
+        let group_id = arguments.get("group_id").and_then(|v| v.as_str());
+        let limit = arguments
+            .get("limit")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_LIMIT)
+            .clamp(1, MAX_LIMIT) as usize;
+
+        let store = self.message_store.clone();
+        let records = Handle::current()
+            .block_on(async move {
+                let store_guard = store.lock().await;
+                store_guard.search_content(group_id, query, limit).await
+            })
+            .map_err(crate::error::Error::StringError)?;
+
+        let messages: Vec<Value> = records
+            .into_iter()
+            .map(|record| {
+                json!({
+                    "message_id": record.message_id,
+                    "sender_id": record.sender_id,
+                    "sender_name": record.sender_name,
+                    "send_time": record.send_time.format("%Y-%m-%d %H:%M:%S").to_string(),
+                    "group_id": record.group_id,
+                    "content": record.content,
+                })
+            })
+            .collect();
+
+        Ok(json!({
+            "query": query,
+            "count": messages.len(),
+            "messages": messages
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::message_store::MessageRecord;
+    use chrono::Local;
+
+    fn record(message_id: &str, content: &str) -> MessageRecord {
+        MessageRecord {
+            message_id: message_id.to_string(),
+            sender_id: "42".to_string(),
+            sender_name: "tester".to_string(),
+            send_time: Local::now().naive_local(),
+            group_id: Some("1001".to_string()),
+            group_name: None,
+            content: content.to_string(),
+            at_target_list: None,
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_search_returns_matching_messages() {
+        let store = MessageStore::new(None, None, None, None, None, None, None).await;
+        store
+            .store_messages(&[record("1", "下周五团建去烧烤"), record("2", "记得交周报")])
+            .await
+            .unwrap();
+        let tool = MessageSearchTool::new(Arc::new(TokioMutex::new(store)));
+
+        let result = tokio::task::block_in_place(|| tool.call(json!({"query": "烧烤", "group_id": "1001"})).unwrap());
+        assert_eq!(result["count"], json!(1));
+        assert_eq!(result["messages"][0]["content"], json!("下周五团建去烧烤"));
+        assert!(tokio::task::block_in_place(|| tool.call(json!({"query": ""})).is_err()));
+    }
+}
@@ -23,6 +23,91 @@ pub trait FunctionTool: Send + Sync + std::fmt::Debug {
     fn call(&self, arguments: Value) -> Result<Value>;
 }
 
+/// JSON type name of `value`, as used by the schema `type` keyword
+fn json_type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn check_against_schema(schema: &Value, value: &Value, path: &str) -> std::result::Result<(), String> {
+    if let Some(expected) = schema.get("type").and_then(Value::as_str) {
+        let actual = json_type_name(value);
+        // Integers are numbers too
+        let matches = actual == expected || (expected == "number" && actual == "integer");
+        if !matches {
+            return Err(format!("'{}' must be {}, got {}", path, expected, actual));
+        }
+    }
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            return Err(format!("'{}' must be one of {}, got {}", path, Value::Array(allowed.clone()), value));
+        }
+    }
+    if let Some(number) = value.as_f64() {
+        if let Some(minimum) = schema.get("minimum").and_then(Value::as_f64).filter(|min| number < *min) {
+            return Err(format!("'{}' must be >= {}, got {}", path, minimum, value));
+        }
+        if let Some(maximum) = schema.get("maximum").and_then(Value::as_f64).filter(|max| number > *max) {
+            return Err(format!("'{}' must be <= {}, got {}", path, maximum, value));
+        }
+    }
+
+    match value {
+        Value::Object(fields) => {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for name in schema.get("required").and_then(Value::as_array).into_iter().flatten().filter_map(Value::as_str) {
+                if !fields.contains_key(name) {
+                    return Err(format!("missing required field '{}'", join_path(path, name)));
+                }
+            }
+            for (name, field) in fields {
+                match properties.and_then(|props| props.get(name)) {
+                    Some(field_schema) => check_against_schema(field_schema, field, &join_path(path, name))?,
+                    None if schema.get("additionalProperties") == Some(&Value::Bool(false)) => {
+                        return Err(format!("unknown field '{}'", join_path(path, name)));
+                    }
+                    None => {}
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    check_against_schema(item_schema, item, &format!("{}[{}]", path, index))?;
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn join_path(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", path, name)
+    }
+}
+
+/// Check tool call `args` against a tool's `parameters()` schema before calling it.
+///
+/// Covers the subset of JSON Schema tools here use: `type`, `required`, `properties`,
+/// `additionalProperties: false`, `enum`, `minimum`/`maximum` and array `items`.
+pub fn validate_arguments(schema: &Value, args: &Value) -> std::result::Result<(), String> {
+    if schema.get("type").and_then(Value::as_str) == Some("object") && !args.is_object() {
+        return Err(format!("arguments must be a JSON object, got {}", json_type_name(args)));
+    }
+    check_against_schema(schema, args, "")
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ToolCallsFuncSpec {
     pub name: String,
@@ -40,6 +125,10 @@ pub mod math;
 pub mod chat_history;
 pub mod nl_reply;
 pub mod code_writer;
+pub mod web_search;
+pub mod weather;
+pub mod http_request;
+pub mod message_search;
 
 #[allow(unused_imports)]
 pub use math::MathTool;
@@ -49,4 +138,56 @@ pub use chat_history::ChatHistoryTool;
 pub use nl_reply::NaturalLanguageReplyTool;
 #[allow(unused_imports)]
 pub use code_writer::CodeWriterTool;
+#[allow(unused_imports)]
+pub use web_search::WebSearchTool;
+#[allow(unused_imports)]
+pub use weather::WeatherTool;
+#[allow(unused_imports)]
+pub use http_request::HttpRequestTool;
+#[allow(unused_imports)]
+pub use message_search::MessageSearchTool;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
+    #[test]
+    fn test_validate_arguments_accepts_matching_call() {
+        let schema = MathTool::new().parameters();
+        assert!(validate_arguments(&schema, &json!({"a": 1, "b": 2.5, "op": "mul"})).is_ok());
+    }
+
+    #[test]
+    fn test_validate_arguments_reports_missing_required_field() {
+        let schema = MathTool::new().parameters();
+        assert_eq!(
+            validate_arguments(&schema, &json!({"a": 1})),
+            Err("missing required field 'b'".to_string())
+        );
+        // Unparseable arguments arrive as null
+        assert!(validate_arguments(&schema, &Value::Null).unwrap_err().contains("must be a JSON object"));
+    }
+
+    #[test]
+    fn test_validate_arguments_reports_wrong_types_and_values() {
+        let schema = MathTool::new().parameters();
+        assert_eq!(
+            validate_arguments(&schema, &json!({"a": "1", "b": 2})),
+            Err("'a' must be number, got string".to_string())
+        );
+        assert!(validate_arguments(&schema, &json!({"a": 1, "b": 2, "op": "pow"})).unwrap_err().contains("must be one of"));
+        assert_eq!(
+            validate_arguments(&schema, &json!({"a": 1, "b": 2, "c": 3})),
+            Err("unknown field 'c'".to_string())
+        );
+
+        let nested = json!({
+            "type": "object",
+            "properties": {"ids": {"type": "array", "items": {"type": "integer", "minimum": 1}}}
+        });
+        assert_eq!(
+            validate_arguments(&nested, &json!({"ids": [3, 0]})),
+            Err("'ids[1]' must be >= 1, got 0".to_string())
+        );
+    }
+}
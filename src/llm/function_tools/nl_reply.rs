@@ -45,10 +45,10 @@ impl FunctionTool for NaturalLanguageReplyTool {
         let system = arguments.get("system").and_then(|v| v.as_str()).unwrap_or("You are a helpful assistant.");
 
         let messages = vec![
-            Message { role: MessageRole::System, content: Some(system.to_string()), tool_calls: Vec::new() },
-            Message { role: MessageRole::User, content: Some(prompt.to_string()), tool_calls: Vec::new() },
+            Message { role: MessageRole::System, content: Some(system.to_string()), tool_calls: Vec::new(), usage: None },
+            Message { role: MessageRole::User, content: Some(prompt.to_string()), tool_calls: Vec::new(), usage: None },
         ];
-        let param = InferenceParam { messages: &messages, tools: None };
+        let param = InferenceParam::new(&messages);
         let resp = self.llm.inference(&param);
         let content = resp.content.unwrap_or_default();
         Ok(json!({ "reply": content }))
@@ -0,0 +1,177 @@
+use super::FunctionTool;
+use crate::error::Result;
+use crate::util::http_client::{HttpGet, RetryingHttpClient};
+use serde_json::{json, Value};
+
+/// Weather tool: look up the current weather of a city.
+///
+/// Parameters:
+/// - city (string, required): city name, e.g. "Beijing" or "London,GB"
+///
+/// Queries an OpenWeatherMap-compatible current weather endpoint as
+/// `GET <endpoint>?q=<city>&appid=<api_key>&units=metric&lang=zh_cn`.
+pub struct WeatherTool {
+    endpoint: String,
+    api_key: String,
+    client: Box<dyn HttpGet>,
+}
+
+impl std::fmt::Debug for WeatherTool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WeatherTool").field("endpoint", &self.endpoint).finish_non_exhaustive()
+    }
+}
+
+impl WeatherTool {
+    pub fn new(endpoint: impl Into<String>, api_key: impl Into<String>) -> Self {
+        Self::with_client(endpoint, api_key, Box::new(RetryingHttpClient::new()))
+    }
+
+    /// Use a custom HTTP client instead of the default retrying one
+    pub fn with_client(endpoint: impl Into<String>, api_key: impl Into<String>, client: Box<dyn HttpGet>) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            api_key: api_key.into(),
+            client,
+        }
+    }
+}
+
+/// Keep the fields worth telling a user about
+fn shape_weather(city: &str, response: &Value) -> Value {
+    json!({
+        "city": response.get("name").and_then(Value::as_str).unwrap_or(city),
+        "temperature_c": response.pointer("/main/temp"),
+        "feels_like_c": response.pointer("/main/feels_like"),
+        "humidity": response.pointer("/main/humidity"),
+        "condition": response.pointer("/weather/0/description").and_then(Value::as_str).unwrap_or(""),
+        "wind_speed_mps": response.pointer("/wind/speed"),
+    })
+}
+
+impl FunctionTool for WeatherTool {
+    fn name(&self) -> &str { "weather" }
+
+    fn description(&self) -> &str {
+        "Get the current weather of a city: temperature (Celsius), condition, humidity and wind speed."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "city": { "type": "string", "description": "City name, optionally with a country code, e.g. \"London,GB\"" }
+            },
+            "required": ["city"],
+            "additionalProperties": false
+        })
+    }
+
+    fn call(&self, arguments: Value) -> Result<Value> {
+        let city = arguments
+            .get("city")
+            .and_then(|v| v.as_str())
+            .map(str::trim)
+            .filter(|city| !city.is_empty())
+            .ok_or_else(|| crate::string_error!("missing required parameter: city"))?;
+
+        let url = reqwest::Url::parse_with_params(
+            &self.endpoint,
+            &[("q", city), ("appid", self.api_key.as_str()), ("units", "metric"), ("lang", "zh_cn")],
+        )
+        .map_err(|e| crate::string_error!("invalid weather endpoint {}: {}", self.endpoint, e))?;
+
+        let response = self
+            .client
+            .get(url.as_str(), &[])
+            .map_err(|e| crate::string_error!("weather request failed: {}", e))?;
+        if response.status == 404 {
+            return Err(crate::string_error!("unknown city: {}", city));
+        }
+        if !(200..300).contains(&response.status) {
+            return Err(crate::string_error!("weather API returned status {}", response.status));
+        }
+        let body: Value = serde_json::from_str(&response.body)?;
+        Ok(shape_weather(city, &body))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::http_client::HttpResponse;
+    use std::sync::{Arc, Mutex};
+
+    /// Records requested urls and answers with a fixed status and body
+    struct MockClient {
+        status: u16,
+        body: String,
+        urls: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl HttpGet for MockClient {
+        fn get(&self, url: &str, _headers: &[(String, String)]) -> std::result::Result<HttpResponse, String> {
+            self.urls.lock().unwrap().push(url.to_string());
+            Ok(HttpResponse {
+                status: self.status,
+                body: self.body.clone(),
+            })
+        }
+    }
+
+    fn tool_answering(status: u16, body: Value) -> (WeatherTool, Arc<Mutex<Vec<String>>>) {
+        let urls = Arc::new(Mutex::new(Vec::new()));
+        let client = MockClient {
+            status,
+            body: body.to_string(),
+            urls: urls.clone(),
+        };
+        let tool = WeatherTool::with_client("https://weather.example.com/data/2.5/weather", "key", Box::new(client));
+        (tool, urls)
+    }
+
+    #[test]
+    fn test_parameters_schema() {
+        let schema = tool_answering(200, json!({})).0.parameters();
+        assert_eq!(schema["required"], json!(["city"]));
+        assert_eq!(schema["properties"]["city"]["type"], json!("string"));
+    }
+
+    #[test]
+    fn test_weather_response_is_shaped() {
+        let (tool, urls) = tool_answering(
+            200,
+            json!({
+                "coord": {"lon": 116.4, "lat": 39.9},
+                "weather": [{"id": 800, "main": "Clear", "description": "晴", "icon": "01d"}],
+                "main": {"temp": 21.5, "feels_like": 20.9, "humidity": 40, "pressure": 1015},
+                "wind": {"speed": 3.1, "deg": 200},
+                "name": "Beijing",
+                "cod": 200
+            }),
+        );
+        let result = tool.call(json!({"city": "Beijing"})).unwrap();
+        assert_eq!(
+            result,
+            json!({
+                "city": "Beijing",
+                "temperature_c": 21.5,
+                "feels_like_c": 20.9,
+                "humidity": 40,
+                "condition": "晴",
+                "wind_speed_mps": 3.1
+            })
+        );
+        assert_eq!(
+            urls.lock().unwrap()[0],
+            "https://weather.example.com/data/2.5/weather?q=Beijing&appid=key&units=metric&lang=zh_cn"
+        );
+    }
+
+    #[test]
+    fn test_unknown_city_is_an_error() {
+        let (tool, _) = tool_answering(404, json!({"cod": "404", "message": "city not found"}));
+        let err = tool.call(json!({"city": "Atlantis"})).unwrap_err();
+        assert!(err.to_string().contains("unknown city"));
+    }
+}
@@ -0,0 +1,208 @@
+use super::FunctionTool;
+use crate::error::Result;
+use crate::util::http_client::{HttpGet, RetryingHttpClient};
+use serde_json::{json, Value};
+
+const DEFAULT_NUM_RESULTS: u64 = 5;
+const MAX_NUM_RESULTS: u64 = 20;
+
+/// Web search tool: query a search endpoint and return the top hits.
+///
+/// Parameters:
+/// - query (string, required): search terms
+/// - num_results (integer, optional): number of hits to return, 5 by default, at most 20
+///
+/// The endpoint is called as `GET <endpoint>?q=<query>&format=json&count=<n>`, which suits
+/// SearxNG and the Bing Web Search API. Both `results[].{title,url,content}` (SearxNG) and
+/// `webPages.value[].{name,url,snippet}` (Bing) responses are understood.
+pub struct WebSearchTool {
+    endpoint: String,
+    api_key: Option<String>,
+    client: Box<dyn HttpGet>,
+}
+
+impl std::fmt::Debug for WebSearchTool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebSearchTool").field("endpoint", &self.endpoint).finish_non_exhaustive()
+    }
+}
+
+impl WebSearchTool {
+    pub fn new(endpoint: impl Into<String>, api_key: Option<String>) -> Self {
+        Self::with_client(endpoint, api_key, Box::new(RetryingHttpClient::new()))
+    }
+
+    /// Use a custom HTTP client instead of the default retrying one
+    pub fn with_client(endpoint: impl Into<String>, api_key: Option<String>, client: Box<dyn HttpGet>) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            api_key,
+            client,
+        }
+    }
+}
+
+/// First string field among `keys`
+fn first_str<'a>(item: &'a Value, keys: &[&str]) -> &'a str {
+    keys.iter().find_map(|key| item.get(*key).and_then(Value::as_str)).unwrap_or("")
+}
+
+/// Reduce a SearxNG or Bing response to `[{title, url, snippet}]`
+fn shape_results(response: &Value, limit: usize) -> Vec<Value> {
+    let hits = response
+        .get("results")
+        .or_else(|| response.pointer("/webPages/value"))
+        .and_then(Value::as_array);
+    hits.into_iter()
+        .flatten()
+        .filter(|item| !first_str(item, &["url", "link"]).is_empty())
+        .take(limit)
+        .map(|item| {
+            json!({
+                "title": first_str(item, &["title", "name"]),
+                "url": first_str(item, &["url", "link"]),
+                "snippet": first_str(item, &["snippet", "content", "description"]),
+            })
+        })
+        .collect()
+}
+
+impl FunctionTool for WebSearchTool {
+    fn name(&self) -> &str { "web_search" }
+
+    fn description(&self) -> &str {
+        "Search the web. Returns a list of results with title, url and snippet."
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "query": { "type": "string", "description": "Search terms" },
+                "num_results": {
+                    "type": "integer",
+                    "description": "Number of results to return (default 5, at most 20)",
+                    "minimum": 1,
+                    "maximum": MAX_NUM_RESULTS
+                }
+            },
+            "required": ["query"],
+            "additionalProperties": false
+        })
+    }
+
+    fn call(&self, arguments: Value) -> Result<Value> {
+        let query = arguments
+            .get("query")
+            .and_then(|v| v.as_str())
+            .filter(|q| !q.trim().is_empty())
+            .ok_or_else(|| crate::string_error!("missing required parameter: query"))?;
+        let num_results = arguments
+            .get("num_results")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(DEFAULT_NUM_RESULTS)
+            .clamp(1, MAX_NUM_RESULTS);
+
+        let count = num_results.to_string();
+        let url = reqwest::Url::parse_with_params(
+            &self.endpoint,
+            &[("q", query.trim()), ("format", "json"), ("count", count.as_str())],
+        )
+        .map_err(|e| crate::string_error!("invalid search endpoint {}: {}", self.endpoint, e))?;
+
+        let mut headers = vec![("Accept".to_string(), "application/json".to_string())];
+        if let Some(key) = self.api_key.as_deref().filter(|key| !key.is_empty()) {
+            // Bing reads the subscription header, self-hosted proxies usually a bearer token
+            headers.push(("Ocp-Apim-Subscription-Key".to_string(), key.to_string()));
+            headers.push(("Authorization".to_string(), format!("Bearer {}", key)));
+        }
+
+        let response = self
+            .client
+            .get(url.as_str(), &headers)
+            .map_err(|e| crate::string_error!("web search request failed: {}", e))?;
+        if !(200..300).contains(&response.status) {
+            return Err(crate::string_error!("web search returned status {}", response.status));
+        }
+        let body: Value = serde_json::from_str(&response.body)?;
+        Ok(Value::Array(shape_results(&body, num_results as usize)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::http_client::HttpResponse;
+    use std::sync::{Arc, Mutex};
+
+    /// Records requested urls and answers with a fixed body
+    struct MockClient {
+        body: String,
+        urls: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl HttpGet for MockClient {
+        fn get(&self, url: &str, _headers: &[(String, String)]) -> std::result::Result<HttpResponse, String> {
+            self.urls.lock().unwrap().push(url.to_string());
+            Ok(HttpResponse {
+                status: 200,
+                body: self.body.clone(),
+            })
+        }
+    }
+
+    fn tool_answering(body: Value) -> (WebSearchTool, Arc<Mutex<Vec<String>>>) {
+        let urls = Arc::new(Mutex::new(Vec::new()));
+        let client = MockClient {
+            body: body.to_string(),
+            urls: urls.clone(),
+        };
+        let tool = WebSearchTool::with_client("https://search.example.com/search", None, Box::new(client));
+        (tool, urls)
+    }
+
+    #[test]
+    fn test_parameters_schema() {
+        let (tool, _) = tool_answering(json!({}));
+        let schema = tool.parameters();
+        assert_eq!(schema["required"], json!(["query"]));
+        assert_eq!(schema["properties"]["query"]["type"], json!("string"));
+        assert_eq!(schema["properties"]["num_results"]["type"], json!("integer"));
+    }
+
+    #[test]
+    fn test_searxng_results_are_shaped_and_limited() {
+        let (tool, urls) = tool_answering(json!({
+            "query": "rust lang",
+            "results": [
+                {"title": "Rust", "url": "https://www.rust-lang.org/", "content": "A language empowering everyone", "engine": "duckduckgo"},
+                {"title": "Rust (programming language)", "url": "https://en.wikipedia.org/wiki/Rust", "content": "Rust is a general-purpose language"},
+                {"title": "The Book", "url": "https://doc.rust-lang.org/book/", "content": "The Rust Programming Language"}
+            ]
+        }));
+
+        let result = tool.call(json!({"query": "rust lang", "num_results": 2})).unwrap();
+        assert_eq!(
+            result,
+            json!([
+                {"title": "Rust", "url": "https://www.rust-lang.org/", "snippet": "A language empowering everyone"},
+                {"title": "Rust (programming language)", "url": "https://en.wikipedia.org/wiki/Rust", "snippet": "Rust is a general-purpose language"}
+            ])
+        );
+        assert_eq!(
+            urls.lock().unwrap()[0],
+            "https://search.example.com/search?q=rust+lang&format=json&count=2"
+        );
+    }
+
+    #[test]
+    fn test_bing_results_are_shaped() {
+        let (tool, _) = tool_answering(json!({
+            "_type": "SearchResponse",
+            "webPages": {"value": [{"name": "Rust", "url": "https://www.rust-lang.org/", "snippet": "Fast and reliable"}]}
+        }));
+        let result = tool.call(json!({"query": "rust"})).unwrap();
+        assert_eq!(result, json!([{"title": "Rust", "url": "https://www.rust-lang.org/", "snippet": "Fast and reliable"}]));
+        assert!(tool.call(json!({"query": " "})).is_err());
+    }
+}
@@ -1,12 +1,17 @@
-use super::{InferenceParam, LLMBase, Message, MessageRole, role_to_str, str_to_role};
+use super::{InferenceParam, LLMBase, Message, MessageRole, TokenUsage, ToolChoice, role_to_str, str_to_role};
+use super::api_dialect::{self, ApiDialect};
 use super::function_tools::{ToolCalls, ToolCallsFuncSpec};
-use reqwest::blocking::Client;
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
 use serde_json::{Value, json};
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::{Arc, Mutex, OnceLock};
 use std::time::Duration;
-use log::{error, debug};
+use log::{error, debug, warn};
 
-#[cfg(test)]
-use log::warn;
+/// Longest `Retry-After` wait honored, so a misbehaving server can't stall a run
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
 
 #[derive(Debug, Clone)]
 pub struct LLMAPI {
@@ -14,6 +19,95 @@ pub struct LLMAPI {
     api_endpoint: String,
     api_key: Option<String>,
     timeout: Duration,
+    max_retries: u32,
+    retry_base_delay: Duration,
+    dialect: ApiDialect,
+    /// Shared between clones, so a cloned client keeps hitting the same cache
+    cache: Option<Arc<Mutex<ResponseCache>>>,
+}
+
+/// Least-recently-used map from request hash to the response it produced
+#[derive(Debug)]
+struct ResponseCache {
+    capacity: usize,
+    entries: HashMap<u64, Message>,
+    /// Keys from least to most recently used
+    order: VecDeque<u64>,
+}
+
+impl ResponseCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    fn touch(&mut self, key: u64) {
+        if let Some(index) = self.order.iter().position(|k| *k == key) {
+            self.order.remove(index);
+        }
+        self.order.push_back(key);
+    }
+
+    fn get(&mut self, key: u64) -> Option<Message> {
+        let message = self.entries.get(&key)?.clone();
+        self.touch(key);
+        Some(message)
+    }
+
+    fn insert(&mut self, key: u64, message: Message) {
+        if !self.entries.contains_key(&key) && self.entries.len() >= self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.entries.insert(key, message);
+        self.touch(key);
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+}
+
+/// Failures are reported as an assistant message starting with "Error:"
+fn is_error_message(message: &Message) -> bool {
+    message.tool_calls.is_empty() && message.content.as_deref().is_some_and(|c| c.starts_with("Error:"))
+}
+
+/// Runtime driving blocking `inference` calls made outside of any tokio runtime
+fn shared_runtime() -> &'static tokio::runtime::Runtime {
+    static RUNTIME: OnceLock<tokio::runtime::Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| {
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("llm-api")
+            .enable_all()
+            .build()
+            .expect("Failed to create LLM API runtime")
+    })
+}
+
+/// Run an LLM request to completion from the sync `Node::execute` path
+fn block_on<F: Future>(future: F) -> F::Output {
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) => tokio::task::block_in_place(|| handle.block_on(future)),
+        Err(_) => shared_runtime().block_on(future),
+    }
+}
+
+/// Rate limits and gateway errors usually clear up on their own
+fn is_retryable_status(status: StatusCode) -> bool {
+    matches!(status.as_u16(), 429 | 500 | 502 | 503 | 504)
+}
+
+/// Delay requested by a `Retry-After: <seconds>` header
+fn retry_after(response: &Response) -> Option<Duration> {
+    let seconds: u64 = response.headers().get("Retry-After")?.to_str().ok()?.trim().parse().ok()?;
+    Some(Duration::from_secs(seconds).min(MAX_RETRY_AFTER))
 }
 
 impl LLMAPI {
@@ -29,21 +123,54 @@ impl LLMAPI {
             api_endpoint,
             api_key,
             timeout,
+            max_retries: 0,
+            retry_base_delay: Duration::ZERO,
+            dialect: ApiDialect::default(),
+            cache: None,
+        }
+    }
+
+    /// Answer repeated identical requests from an in-memory LRU cache of `capacity` responses.
+    /// Only successful responses are cached; a capacity of 0 disables caching.
+    pub fn with_cache(mut self, capacity: usize) -> Self {
+        self.cache = (capacity > 0).then(|| Arc::new(Mutex::new(ResponseCache::new(capacity))));
+        self
+    }
+
+    /// Drop every cached response
+    pub fn clear_cache(&self) {
+        if let Some(cache) = &self.cache {
+            cache.lock().unwrap().clear();
         }
     }
 
+    /// Speak another API's request/response format instead of OpenAI chat completions
+    pub fn with_dialect(mut self, dialect: ApiDialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
     /// Set custom timeout for requests
     pub fn with_timeout(mut self, timeout: Duration) -> Self {
         self.timeout = timeout;
         self
     }
 
+    /// Retry connection errors and HTTP 429/5xx up to `max` times, waiting `base_delay`,
+    /// then twice as long each time, unless the server sends `Retry-After`
+    pub fn with_retries(mut self, max: u32, base_delay: Duration) -> Self {
+        self.max_retries = max;
+        self.retry_base_delay = base_delay;
+        self
+    }
+
     /// Create a system message
     pub fn system_message(content: &str) -> Message {
         Message {
             role: MessageRole::System,
             content: Some(content.to_string()),
             tool_calls: Vec::new(),
+            usage: None,
         }
     }
 
@@ -53,6 +180,7 @@ impl LLMAPI {
             role: MessageRole::User,
             content: Some(content.to_string()),
             tool_calls: Vec::new(),
+            usage: None,
         }
     }
 
@@ -109,21 +237,124 @@ impl LLMAPI {
             role,
             content,
             tool_calls,
+            usage: Self::parse_usage(api_resp),
         })
     }
+
+    /// Parse the `usage` object that accompanies a response (or the last stream chunk)
+    fn parse_usage(api_resp: &Value) -> Option<TokenUsage> {
+        api_resp
+            .get("usage")
+            .filter(|usage| usage.is_object())
+            .and_then(|usage| serde_json::from_value(usage.clone()).ok())
+    }
 }
 
-impl LLMBase for LLMAPI {
-    fn get_model_name(&self) -> &str {
-        &self.model_name
+/// Assembles an OpenAI-style SSE stream (`data: {...}` lines ending with `data: [DONE]`)
+/// into a message. Bytes may arrive split anywhere, including inside a line or a UTF-8 character.
+#[derive(Debug, Default)]
+pub struct StreamAccumulator {
+    pending: Vec<u8>,
+    role: Option<MessageRole>,
+    content: String,
+    /// (id, type, function name, argument fragments) per tool call index
+    tool_calls: Vec<(String, String, String, String)>,
+    usage: Option<TokenUsage>,
+    done: bool,
+}
+
+impl StreamAccumulator {
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    fn inference(&self, param: &InferenceParam) -> Message {
-        let client = Client::builder()
-            .timeout(self.timeout)
-            .build()
-            .expect("Failed to create HTTP client");
+    /// Whether the `[DONE]` sentinel has been seen
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+
+    /// Feed raw bytes from the response body, calling `on_delta` for each content piece
+    pub fn push(&mut self, bytes: &[u8], on_delta: &mut dyn FnMut(&str)) {
+        self.pending.extend_from_slice(bytes);
+        while let Some(end) = self.pending.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=end).collect();
+            self.handle_line(&String::from_utf8_lossy(&line), on_delta);
+        }
+    }
+
+    fn handle_line(&mut self, line: &str, on_delta: &mut dyn FnMut(&str)) {
+        let Some(data) = line.trim().strip_prefix("data:") else {
+            // Blank separators, comments and other SSE fields carry no content
+            return;
+        };
+        let data = data.trim();
+        if data == "[DONE]" {
+            self.done = true;
+            return;
+        }
+        let chunk: Value = match serde_json::from_str(data) {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                error!("Failed to parse stream chunk: {}, chunk: {:?}", e, data);
+                return;
+            }
+        };
+        // Servers that report usage while streaming send it on the last chunk, often without choices
+        if let Some(usage) = LLMAPI::parse_usage(&chunk) {
+            self.usage = Some(usage);
+        }
+        let Some(delta) = chunk.pointer("/choices/0/delta") else {
+            return;
+        };
+
+        if let Some(role) = delta.get("role").and_then(Value::as_str) {
+            self.role = Some(str_to_role(role));
+        }
+        if let Some(content) = delta.get("content").and_then(Value::as_str).filter(|c| !c.is_empty()) {
+            self.content.push_str(content);
+            on_delta(content);
+        }
+        for call in delta.get("tool_calls").and_then(Value::as_array).into_iter().flatten() {
+            let index = call.get("index").and_then(Value::as_u64).unwrap_or(0) as usize;
+            if self.tool_calls.len() <= index {
+                self.tool_calls.resize(index + 1, Default::default());
+            }
+            let entry = &mut self.tool_calls[index];
+            let text = |pointer: &str| call.pointer(pointer).and_then(Value::as_str).unwrap_or("");
+            entry.0.push_str(text("/id"));
+            entry.1.push_str(text("/type"));
+            entry.2.push_str(text("/function/name"));
+            entry.3.push_str(text("/function/arguments"));
+        }
+    }
 
+    /// Build the message from everything received so far
+    pub fn finish(self) -> Message {
+        let tool_calls = self
+            .tool_calls
+            .into_iter()
+            .filter(|(_, _, name, _)| !name.is_empty())
+            .map(|(id, type_name, name, arguments)| ToolCalls {
+                id,
+                type_name: if type_name.is_empty() { "function".to_string() } else { type_name },
+                function: ToolCallsFuncSpec {
+                    name,
+                    arguments: serde_json::from_str(&arguments).unwrap_or(Value::Null),
+                },
+            })
+            .collect();
+        Message {
+            role: self.role.unwrap_or(MessageRole::Assistant),
+            content: if self.content.is_empty() { None } else { Some(self.content) },
+            tool_calls,
+            usage: self.usage,
+        }
+    }
+}
+
+impl LLMAPI {
+    /// Chat completions body; `stream` asks for SSE deltas
+    fn openai_request_body(&self, param: &InferenceParam, stream: bool) -> Value {
         // Convert internal MessageRole enum to string
         let messages: Vec<serde_json::Value> = param
             .messages
@@ -173,13 +404,66 @@ impl LLMBase for LLMAPI {
 
         if let Some(tool_list) = tools {
             request_body["tools"] = json!(tool_list);
-            request_body["tool_choice"] = json!("auto");
+            request_body["tool_choice"] = match param.tool_choice.as_ref().unwrap_or(&ToolChoice::Auto) {
+                ToolChoice::Auto => json!("auto"),
+                ToolChoice::None => json!("none"),
+                ToolChoice::Required => json!("required"),
+                ToolChoice::Named(name) => json!({"type": "function", "function": {"name": name}}),
+            };
+        }
+        if stream {
+            request_body["stream"] = json!(true);
+        }
+        if let Some(temperature) = param.temperature {
+            request_body["temperature"] = json!(temperature);
+        }
+        if let Some(top_p) = param.top_p {
+            request_body["top_p"] = json!(top_p);
         }
+        if let Some(max_tokens) = param.max_tokens {
+            request_body["max_tokens"] = json!(max_tokens);
+        }
+        if let Some(stop) = param.stop.as_ref().filter(|stop| !stop.is_empty()) {
+            request_body["stop"] = json!(stop);
+        }
+        if param.json_mode {
+            request_body["response_format"] = json!({"type": "json_object"});
+        }
+        request_body
+    }
 
+    fn request_body(&self, param: &InferenceParam, stream: bool) -> Value {
+        match self.dialect {
+            ApiDialect::OpenAI => self.openai_request_body(param, stream),
+            ApiDialect::Ollama => api_dialect::ollama_request_body(&self.model_name, param),
+            ApiDialect::Anthropic => api_dialect::anthropic_request_body(&self.model_name, param),
+        }
+    }
+
+    /// The body already holds the model, messages, tools and sampling options
+    fn cache_key(&self, param: &InferenceParam) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.api_endpoint.hash(&mut hasher);
+        self.request_body(param, false).to_string().hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn build_request(&self, param: &InferenceParam, stream: bool) -> RequestBuilder {
+        let client = Client::builder()
+            .timeout(self.timeout)
+            .build()
+            .expect("Failed to create HTTP client");
+
+        let request_body = self.request_body(param, stream);
         let mut request = client.post(&self.api_endpoint).json(&request_body);
 
-        // Add authorization header if API key is provided
-        if let Some(ref api_key) = self.api_key {
+        if self.dialect == ApiDialect::Anthropic {
+            request = request.header("anthropic-version", api_dialect::ANTHROPIC_VERSION);
+            if let Some(ref api_key) = self.api_key {
+                request = request.header("x-api-key", api_key.as_str());
+            }
+        } else if let Some(ref api_key) = self.api_key {
+            // Add authorization header if API key is provided
             // Check if api_key already contains "Bearer " prefix
             let auth_header = if api_key.starts_with("Bearer ") {
                 api_key.to_string()
@@ -188,16 +472,74 @@ impl LLMBase for LLMAPI {
             };
             request = request.header("Authorization", auth_header);
         }
+        request
+    }
+
+    /// Send the request, retrying transient failures. The last response is returned
+    /// even when its status is still an error.
+    async fn send_with_retries(&self, param: &InferenceParam<'_>, stream: bool) -> reqwest::Result<Response> {
+        let mut attempt = 0;
+        loop {
+            let result = self.build_request(param, stream).send().await;
+            if attempt >= self.max_retries {
+                return result;
+            }
+            let backoff = self.retry_base_delay * 2u32.saturating_pow(attempt);
+            let delay = match &result {
+                Ok(response) if is_retryable_status(response.status()) => {
+                    let delay = retry_after(response).unwrap_or(backoff);
+                    warn!(
+                        "[LLMAPI] {} returned {}, retrying in {:?} ({}/{})",
+                        self.api_endpoint, response.status(), delay, attempt + 1, self.max_retries
+                    );
+                    delay
+                }
+                Err(e) if e.is_connect() || e.is_timeout() => {
+                    warn!(
+                        "[LLMAPI] Request to {} failed ({}), retrying in {:?} ({}/{})",
+                        self.api_endpoint, e, backoff, attempt + 1, self.max_retries
+                    );
+                    backoff
+                }
+                _ => return result,
+            };
+            attempt += 1;
+            tokio::time::sleep(delay).await;
+        }
+    }
 
+    /// Non-blocking `inference` for async callers; the blocking one runs this on a runtime
+    pub async fn inference_async(&self, param: &InferenceParam<'_>) -> Message {
+        let Some(cache) = &self.cache else {
+            return self.request_message(param).await;
+        };
+        let key = self.cache_key(param);
+        if let Some(message) = cache.lock().unwrap().get(key) {
+            debug!("[LLMAPI] Answered from response cache");
+            return message;
+        }
+        let message = self.request_message(param).await;
+        if !is_error_message(&message) {
+            cache.lock().unwrap().insert(key, message.clone());
+        }
+        message
+    }
+
+    async fn request_message(&self, param: &InferenceParam<'_>) -> Message {
         // Make the request and handle response
-        match request.send() {
+        match self.send_with_retries(param, false).await {
             Ok(response) => {
                 let status = response.status();
-                let response_text = response.text().unwrap_or_else(|_| "Failed to read response".to_string());
+                let response_text = response.text().await.unwrap_or_else(|_| "Failed to read response".to_string());
                 if status.is_success() {
                     match serde_json::from_str::<Value>(&response_text) {
                         Ok(api_resp) => {
-                            if let Some(msg) = Self::parse_api_message(&api_resp) {
+                            let parsed = match self.dialect {
+                                ApiDialect::OpenAI => Self::parse_api_message(&api_resp),
+                                ApiDialect::Ollama => api_dialect::parse_ollama_message(&api_resp),
+                                ApiDialect::Anthropic => api_dialect::parse_anthropic_message(&api_resp),
+                            };
+                            if let Some(msg) = parsed {
                                 debug!("Successfully parsed API response");
                                 msg
                             } else {
@@ -206,6 +548,7 @@ impl LLMBase for LLMAPI {
                                     role: MessageRole::Assistant,
                                     content: Some("Error: Invalid response structure from API".to_string()),
                                     tool_calls: Vec::new(),
+                                    usage: None,
                                 }
                             }
                         }
@@ -215,6 +558,7 @@ impl LLMBase for LLMAPI {
                                 role: MessageRole::Assistant,
                                 content: Some(format!("Error: Failed to parse response - {}", e)),
                                 tool_calls: Vec::new(),
+                                usage: None,
                             }
                         }
                     }
@@ -224,6 +568,7 @@ impl LLMBase for LLMAPI {
                         role: MessageRole::Assistant,
                         content: Some(format!("Error: API request failed with status {}", status)),
                         tool_calls: Vec::new(),
+                        usage: None,
                     }
                 }
             }
@@ -233,10 +578,80 @@ impl LLMBase for LLMAPI {
                     role: MessageRole::Assistant,
                     content: Some(format!("Error: Failed to send request - {}", e)),
                     tool_calls: Vec::new(),
+                    usage: None,
                 }
             }
         }
     }
+
+    pub async fn inference_streaming_async(
+        &self,
+        param: &InferenceParam<'_>,
+        on_delta: &mut dyn FnMut(&str),
+    ) -> Message {
+        // Only the OpenAI SSE format is parsed incrementally, other dialects reply in one piece
+        if self.dialect != ApiDialect::OpenAI {
+            let message = self.inference_async(param).await;
+            if let Some(content) = message.content.as_deref().filter(|c| !c.is_empty()) {
+                on_delta(content);
+            }
+            return message;
+        }
+        let mut response = match self.send_with_retries(param, true).await {
+            Ok(response) => response,
+            Err(e) => {
+                error!("Failed to send API request: {}", e);
+                return Message {
+                    role: MessageRole::Assistant,
+                    content: Some(format!("Error: Failed to send request - {}", e)),
+                    tool_calls: Vec::new(),
+                    usage: None,
+                };
+            }
+        };
+        let status = response.status();
+        if !status.is_success() {
+            let response_text = response.text().await.unwrap_or_else(|_| "Failed to read response".to_string());
+            error!("API request failed with status {}: {}", status, response_text);
+            return Message {
+                role: MessageRole::Assistant,
+                content: Some(format!("Error: API request failed with status {}", status)),
+                tool_calls: Vec::new(),
+                usage: None,
+            };
+        }
+
+        let mut stream = StreamAccumulator::new();
+        while !stream.is_done() {
+            match response.chunk().await {
+                Ok(None) => break,
+                Ok(Some(bytes)) => stream.push(&bytes, on_delta),
+                Err(e) => {
+                    // Keep what arrived before the connection broke
+                    error!("Failed to read API stream: {}", e);
+                    break;
+                }
+            }
+        }
+        // A final line without a trailing newline is still data
+        stream.push(b"\n", on_delta);
+        debug!("Finished reading API stream");
+        stream.finish()
+    }
+}
+
+impl LLMBase for LLMAPI {
+    fn get_model_name(&self) -> &str {
+        &self.model_name
+    }
+
+    fn inference(&self, param: &InferenceParam) -> Message {
+        block_on(self.inference_async(param))
+    }
+
+    fn inference_streaming(&self, param: &InferenceParam, on_delta: &mut dyn FnMut(&str)) -> Message {
+        block_on(self.inference_streaming_async(param, on_delta))
+    }
 }
 
 #[cfg(test)]
@@ -329,10 +744,7 @@ mod tests {
             LLMAPI::user_message("妈的原神一点都不好玩，为什么这么多人喜欢？请你黑一下原神，并且吹捧一下鸣潮。"),
         ];
         
-        let param = InferenceParam {
-            messages: &messages,
-            tools: None,
-        };
+        let param = InferenceParam::new(&messages);
         let response = api.inference(&param);
         let response_text = response.content.unwrap_or_else(|| "No response".to_string());
         
@@ -406,10 +818,7 @@ mod tests {
             LLMAPI::user_message("妈的原神一点都不好玩，为什么这么多人喜欢？请你黑一下原神，并且吹捧一下鸣潮。"),
         ];
         
-        let param = InferenceParam {
-            messages: &messages,
-            tools: None,
-        };
+        let param = InferenceParam::new(&messages);
         let response = agent_api.inference(&param);
         let response_text = response.content.unwrap_or_else(|| "No response".to_string());
         
@@ -425,13 +834,322 @@ mod tests {
         
         debug!("Agent Model Response: {}", response_text);
     }
+
+    /// Serve one canned HTTP response per connection, in order, and return the server url
+    /// together with the time each request arrived
+    fn serve_responses<S: Into<String>>(responses: Vec<S>) -> (String, std::sync::mpsc::Receiver<std::time::Instant>) {
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+
+        let responses: Vec<String> = responses.into_iter().map(Into::into).collect();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/v1/chat/completions", listener.local_addr().unwrap());
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            for response in responses {
+                let (mut socket, _) = listener.accept().unwrap();
+                let _ = tx.send(std::time::Instant::now());
+                // Requests are small, one read gets headers and body
+                let mut request = [0u8; 8192];
+                let _ = socket.read(&mut request);
+                let _ = socket.write_all(response.as_bytes());
+            }
+        });
+        (url, rx)
+    }
+
+    const UNAVAILABLE: &str = "HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+    const RATE_LIMITED_NOW: &str =
+        "HTTP/1.1 429 Too Many Requests\r\nRetry-After: 0\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+    const OK_REPLY: &str = "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 68\r\nConnection: close\r\n\r\n{\"choices\":[{\"message\":{\"role\":\"assistant\",\"content\":\"recovered\"}}]}";
+
+    /// 200 response whose assistant message has `content`
+    fn reply_with(content: &str) -> String {
+        let body = json!({"choices": [{"message": {"role": "assistant", "content": content}}]}).to_string();
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        )
+    }
+
+    fn json_mode_node_inputs(url: String) -> HashMap<String, DataValue> {
+        HashMap::from([
+            ("messages".to_string(), DataValue::MessageList(vec![LLMAPI::user_message("hi")])),
+            ("model_name".to_string(), DataValue::String("m".to_string())),
+            ("api_endpoint".to_string(), DataValue::String(url)),
+            ("api_key".to_string(), DataValue::Password(String::new())),
+            ("timeout_secs".to_string(), DataValue::Integer(5)),
+            ("json_mode".to_string(), DataValue::Boolean(true)),
+        ])
+    }
+
+    #[test]
+    fn test_json_mode_node_emits_parsed_json_and_rejects_prose() {
+        let (url, _arrivals) = serve_responses(vec![reply_with(r#"{"answer": 42}"#), reply_with("The answer is 42.")]);
+        let mut node = LLMAPINode::new("llm", "LLM");
+
+        let outputs = node.execute(json_mode_node_inputs(url.clone())).unwrap();
+        assert!(matches!(outputs.get("json"), Some(DataValue::Json(value)) if value == &json!({"answer": 42})));
+
+        let err = node.execute(json_mode_node_inputs(url)).unwrap_err();
+        assert!(err.to_string().contains("not valid JSON"));
+    }
+
+    #[test]
+    fn test_json_mode_sets_response_format_for_openai_only() {
+        let api = LLMAPI::new("m".to_string(), "http://localhost/v1".to_string(), None, Duration::from_secs(5));
+        let messages = vec![LLMAPI::user_message("hi")];
+        assert!(request_body(&api, &InferenceParam::new(&messages)).get("response_format").is_none());
+        let body = request_body(&api, &InferenceParam::new(&messages).with_json_mode());
+        assert_eq!(body["response_format"], json!({"type": "json_object"}));
+
+        let api = api.with_dialect(ApiDialect::Anthropic);
+        let body = request_body(&api, &InferenceParam::new(&messages).with_json_mode());
+        assert!(body.get("response_format").is_none());
+    }
+
+    fn ask(api: &LLMAPI) -> Message {
+        let messages = vec![LLMAPI::user_message("hi")];
+        api.inference(&InferenceParam::new(&messages))
+    }
+
+    #[test]
+    fn test_inference_retries_with_exponential_backoff() {
+        let (url, arrivals) = serve_responses(vec![UNAVAILABLE, UNAVAILABLE, OK_REPLY]);
+        let api = LLMAPI::new("m".to_string(), url, None, Duration::from_secs(5))
+            .with_retries(2, Duration::from_millis(50));
+
+        let message = ask(&api);
+        assert_eq!(message.content.as_deref(), Some("recovered"));
+
+        let arrivals: Vec<_> = arrivals.iter().collect();
+        assert_eq!(arrivals.len(), 3);
+        assert!(arrivals[1] - arrivals[0] >= Duration::from_millis(50));
+        assert!(arrivals[2] - arrivals[1] >= Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn test_inference_async_against_mock_endpoint() {
+        let (url, arrivals) = serve_responses(vec![UNAVAILABLE, OK_REPLY]);
+        let api = LLMAPI::new("m".to_string(), url, None, Duration::from_secs(5))
+            .with_retries(1, Duration::from_millis(10));
+        let messages = vec![LLMAPI::user_message("hi")];
+
+        let message = api.inference_async(&InferenceParam::new(&messages)).await;
+        assert_eq!(message.content.as_deref(), Some("recovered"));
+        assert_eq!(arrivals.iter().count(), 2);
+    }
+
+    #[test]
+    fn test_cache_answers_repeated_request_without_network() {
+        let (url, arrivals) = serve_responses(vec![OK_REPLY, OK_REPLY]);
+        let api = LLMAPI::new("m".to_string(), url, None, Duration::from_secs(5)).with_cache(4);
+
+        assert_eq!(ask(&api).content.as_deref(), Some("recovered"));
+        assert_eq!(ask(&api).content.as_deref(), Some("recovered"));
+        assert_eq!(arrivals.try_iter().count(), 1);
+
+        api.clear_cache();
+        assert_eq!(ask(&api).content.as_deref(), Some("recovered"));
+        assert_eq!(arrivals.try_iter().count(), 1);
+    }
+
+    #[test]
+    fn test_cache_skips_errors_and_evicts_least_recently_used() {
+        let (url, arrivals) = serve_responses(vec![UNAVAILABLE, OK_REPLY]);
+        let api = LLMAPI::new("m".to_string(), url, None, Duration::from_secs(5)).with_cache(4);
+        assert!(ask(&api).content.unwrap().starts_with("Error:"));
+        assert_eq!(ask(&api).content.as_deref(), Some("recovered"));
+        assert_eq!(arrivals.iter().count(), 2);
+
+        let mut cache = ResponseCache::new(2);
+        cache.insert(1, LLMAPI::user_message("one"));
+        cache.insert(2, LLMAPI::user_message("two"));
+        assert!(cache.get(1).is_some());
+        cache.insert(3, LLMAPI::user_message("three"));
+        assert!(cache.get(2).is_none());
+        assert!(cache.get(1).is_some() && cache.get(3).is_some());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_blocking_inference_inside_runtime() {
+        let (url, _arrivals) = serve_responses(vec![OK_REPLY]);
+        let api = LLMAPI::new("m".to_string(), url, None, Duration::from_secs(5));
+        assert_eq!(ask(&api).content.as_deref(), Some("recovered"));
+    }
+
+    #[test]
+    fn test_inference_gives_up_after_max_retries_and_honors_retry_after() {
+        let (url, arrivals) = serve_responses(vec![RATE_LIMITED_NOW, UNAVAILABLE]);
+        // Retry-After: 0 overrides the long base delay
+        let api = LLMAPI::new("m".to_string(), url, None, Duration::from_secs(5))
+            .with_retries(1, Duration::from_secs(30));
+
+        let started = std::time::Instant::now();
+        let message = ask(&api);
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert_eq!(message.content.as_deref(), Some("Error: API request failed with status 503 Service Unavailable"));
+        assert_eq!(arrivals.iter().count(), 2);
+    }
+
+    fn request_body(api: &LLMAPI, param: &InferenceParam) -> serde_json::Value {
+        let request = api.build_request(param, false).build().unwrap();
+        serde_json::from_slice(request.body().unwrap().as_bytes().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_sampling_options_are_sent_only_when_set() {
+        let api = LLMAPI::new("m".to_string(), "http://localhost/v1".to_string(), None, Duration::from_secs(5));
+        let messages = vec![LLMAPI::user_message("hi")];
+
+        let body = request_body(&api, &InferenceParam::new(&messages));
+        for key in ["temperature", "top_p", "max_tokens", "stop"] {
+            assert!(body.get(key).is_none(), "{} should be omitted", key);
+        }
+
+        let param = InferenceParam::new(&messages)
+            .with_temperature(0.5)
+            .with_max_tokens(64)
+            .with_stop(vec!["\n\n".to_string()]);
+        let body = request_body(&api, &param);
+        assert_eq!(body["temperature"], json!(0.5));
+        assert_eq!(body["max_tokens"], json!(64));
+        assert_eq!(body["stop"], json!(["\n\n"]));
+        assert!(body.get("top_p").is_none());
+    }
+
+    #[test]
+    fn test_tool_choice_serialization() {
+        let api = LLMAPI::new("m".to_string(), "http://localhost/v1".to_string(), None, Duration::from_secs(5));
+        let messages = vec![LLMAPI::user_message("hi")];
+        let tools: Vec<std::sync::Arc<dyn crate::llm::function_tools::FunctionTool>> =
+            vec![std::sync::Arc::new(crate::llm::function_tools::MathTool::new())];
+
+        let body = request_body(&api, &InferenceParam::new(&messages).with_tools(&tools));
+        assert_eq!(body["tool_choice"], json!("auto"));
+
+        let param = InferenceParam::new(&messages)
+            .with_tools(&tools)
+            .with_tool_choice(ToolChoice::Named("math".to_string()));
+        let body = request_body(&api, &param);
+        assert_eq!(body["tool_choice"], json!({"type": "function", "function": {"name": "math"}}));
+
+        // Without tools there is nothing to choose from
+        let body = request_body(&api, &InferenceParam::new(&messages).with_tool_choice(ToolChoice::Required));
+        assert!(body.get("tool_choice").is_none());
+    }
+
+    #[test]
+    fn test_parse_api_message_attaches_usage() {
+        let response = json!({
+            "choices": [{"message": {"role": "assistant", "content": "hi"}}],
+            "usage": {"prompt_tokens": 12, "completion_tokens": 3, "total_tokens": 15}
+        });
+        let message = LLMAPI::parse_api_message(&response).unwrap();
+        assert_eq!(
+            message.usage,
+            Some(TokenUsage {
+                prompt_tokens: 12,
+                completion_tokens: 3,
+                total_tokens: 15,
+            })
+        );
+
+        let response = json!({"choices": [{"message": {"role": "assistant", "content": "hi"}}]});
+        assert_eq!(LLMAPI::parse_api_message(&response).unwrap().usage, None);
+    }
+
+    #[test]
+    fn test_stream_keeps_usage_from_final_chunk() {
+        let body = "data: {\"choices\":[{\"delta\":{\"content\":\"ok\"}}]}\n\
+                    data: {\"choices\":[],\"usage\":{\"prompt_tokens\":5,\"completion_tokens\":1,\"total_tokens\":6}}\n\
+                    data: [DONE]\n";
+        let (_, stream) = collect_stream(&[body.as_bytes()]);
+        assert_eq!(stream.finish().usage.map(|usage| usage.total_tokens), Some(6));
+    }
+
+    fn collect_stream(chunks: &[&[u8]]) -> (Vec<String>, StreamAccumulator) {
+        let mut deltas = Vec::new();
+        let mut stream = StreamAccumulator::new();
+        for chunk in chunks {
+            stream.push(chunk, &mut |delta| deltas.push(delta.to_string()));
+        }
+        (deltas, stream)
+    }
+
+    #[test]
+    fn test_stream_assembles_split_chunks() {
+        let body = "data: {\"choices\":[{\"delta\":{\"role\":\"assistant\"}}]}\n\n\
+                    data: {\"choices\":[{\"delta\":{\"content\":\"你好\"}}]}\n\n\
+                    : keep-alive\n\
+                    data: {\"choices\":[{\"delta\":{\"content\":\", world\"}}]}\n\n\
+                    data: [DONE]\n\n";
+        // Split inside a line and inside the UTF-8 bytes of "你"
+        let bytes = body.as_bytes();
+        let cut = body.find("你").unwrap() + 1;
+        let (deltas, stream) = collect_stream(&[&bytes[..20], &bytes[20..cut], &bytes[cut..]]);
+
+        assert_eq!(deltas, ["你好", ", world"]);
+        assert!(stream.is_done());
+        let message = stream.finish();
+        assert!(matches!(message.role, MessageRole::Assistant));
+        assert_eq!(message.content.as_deref(), Some("你好, world"));
+    }
+
+    #[test]
+    fn test_stream_assembles_tool_call_fragments() {
+        let body = concat!(
+            "data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,\"id\":\"call_1\",\"type\":\"function\",\"function\":{\"name\":\"search\",\"arguments\":\"{\\\"q\\\":\"}}]}}]}\n",
+            "data: {\"choices\":[{\"delta\":{\"tool_calls\":[{\"index\":0,\"function\":{\"arguments\":\"\\\"rust\\\"}\"}}]}}]}\n",
+            "data: [DONE]\n",
+        );
+        let (deltas, stream) = collect_stream(&[body.as_bytes()]);
+
+        assert!(deltas.is_empty());
+        let message = stream.finish();
+        assert_eq!(message.content, None);
+        assert_eq!(message.tool_calls.len(), 1);
+        assert_eq!(message.tool_calls[0].id, "call_1");
+        assert_eq!(message.tool_calls[0].function.name, "search");
+        assert_eq!(message.tool_calls[0].function.arguments, json!({"q": "rust"}));
+    }
+
+    /// Only implements `inference`, so streaming uses the default
+    #[derive(Debug)]
+    struct FixedLlm;
+
+    impl LLMBase for FixedLlm {
+        fn get_model_name(&self) -> &str {
+            "fixed"
+        }
+
+        fn inference(&self, _param: &InferenceParam) -> Message {
+            LLMAPI::system_message("whole reply")
+        }
+    }
+
+    #[test]
+    fn test_default_streaming_emits_whole_content_once() {
+        let messages = Vec::new();
+        let param = InferenceParam::new(&messages);
+        let mut deltas = Vec::new();
+        let message = FixedLlm.inference_streaming(&param, &mut |delta| deltas.push(delta.to_string()));
+        assert_eq!(deltas, ["whole reply"]);
+        assert_eq!(message.content.as_deref(), Some("whole reply"));
+    }
 }
 
 // ==================== Node Implementation ====================
 
 use crate::node::{node_input, node_output, DataType, DataValue, Node, Port};
 use crate::error::Result;
-use std::collections::HashMap;
+
+/// Parse a reply requested in JSON mode, rejecting prose
+pub fn parse_json_content(message: &Message) -> std::result::Result<Value, String> {
+    let content = message.content.as_deref().unwrap_or("").trim();
+    serde_json::from_str(content).map_err(|e| format!("LLM reply is not valid JSON ({}): {:?}", e, content))
+}
 
 /// LLMAPINode - Node wrapper for LLMAPI that accepts configuration via input ports
 pub struct LLMAPINode {
@@ -467,10 +1185,17 @@ impl Node for LLMAPINode {
         port! { name = "api_endpoint", ty = String, desc = "API端点URL，例如: https://api.openai.com/v1/chat/completions" },
         port! { name = "api_key", ty = Password, desc = "API密钥 (可选，某些本地模型不需要)" },
         port! { name = "timeout_secs", ty = Integer, desc = "超时秒数 (可选，默认120秒)" },
+        port! { name = "temperature", ty = Float, desc = "采样温度 (可选，默认使用服务端设置)", optional, min = 0.0, max = 2.0 },
+        port! { name = "top_p", ty = Float, desc = "核采样概率 (可选)", optional, min = 0.0, max = 1.0 },
+        port! { name = "max_tokens", ty = Integer, desc = "最多生成的token数 (可选)", optional, min = 1 },
+        port! { name = "stop", ty = List(String), desc = "停止序列 (可选)", optional },
+        port! { name = "dialect", ty = String, desc = "API格式 (可选，默认openai)", optional, choices = ["openai", "ollama", "anthropic"] },
+        port! { name = "json_mode", ty = Boolean, desc = "要求模型返回JSON对象，并从json端口输出 (可选)", optional },
     ];
 
     node_output![
         port! { name = "response", ty = MessageList, desc = "LLM返回的消息列表，包含语言模型的回复" },
+        port! { name = "json", ty = Json, desc = "JSON模式下解析后的回复内容" },
     ];
 
     fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
@@ -517,24 +1242,54 @@ impl Node for LLMAPINode {
             })
             .unwrap_or(120);
 
+        let dialect = match inputs.get("dialect") {
+            Some(DataValue::String(s)) => ApiDialect::parse(s).unwrap_or_default(),
+            _ => ApiDialect::default(),
+        };
+
         // Create LLMAPI instance
         let llm_api = LLMAPI::new(
             model_name_str,
             api_endpoint_str,
             api_key_opt,
             Duration::from_secs(timeout_secs),
-        );
+        )
+        .with_dialect(dialect);
 
-        // Call LLM inference
-        let param = super::InferenceParam {
-            messages: &messages,
-            tools: None,  // First version doesn't support tools
-        };
+        // Call LLM inference, first version doesn't support tools
+        let mut param = super::InferenceParam::new(&messages);
+        if let Some(DataValue::Float(temperature)) = inputs.get("temperature") {
+            param = param.with_temperature(*temperature as f32);
+        }
+        if let Some(DataValue::Float(top_p)) = inputs.get("top_p") {
+            param = param.with_top_p(*top_p as f32);
+        }
+        if let Some(DataValue::Integer(max_tokens)) = inputs.get("max_tokens") {
+            param = param.with_max_tokens((*max_tokens).min(u32::MAX as i64) as u32);
+        }
+        if let Some(DataValue::List(stop)) = inputs.get("stop") {
+            let stop = stop
+                .iter()
+                .filter_map(|value| match value {
+                    DataValue::String(s) => Some(s.clone()),
+                    _ => None,
+                })
+                .collect();
+            param = param.with_stop(stop);
+        }
+        let json_mode = matches!(inputs.get("json_mode"), Some(DataValue::Boolean(true)));
+        if json_mode {
+            param = param.with_json_mode();
+        }
 
         let response_message = llm_api.inference(&param);
 
         // Build outputs
         let mut outputs = HashMap::new();
+        if json_mode {
+            let json = parse_json_content(&response_message).map_err(crate::error::Error::ValidationError)?;
+            outputs.insert("json".to_string(), DataValue::Json(json));
+        }
         outputs.insert(
             "response".to_string(),
             DataValue::MessageList(vec![response_message]),
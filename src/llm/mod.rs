@@ -1,4 +1,5 @@
 pub mod agent;
+pub mod api_dialect;
 pub mod llm_api;
 pub mod function_tools;
 pub mod prompt;
@@ -36,11 +37,31 @@ pub fn str_to_role(s: &str) -> MessageRole {
     }
 }
 
+/// Token counts reported by the API for one request, or summed over several
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TokenUsage {
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub total_tokens: u64,
+}
+
+impl std::ops::AddAssign for TokenUsage {
+    fn add_assign(&mut self, other: Self) {
+        self.prompt_tokens += other.prompt_tokens;
+        self.completion_tokens += other.completion_tokens;
+        self.total_tokens += other.total_tokens;
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Message {
     pub role: MessageRole,
     pub content: Option<String>,
     pub tool_calls: Vec<ToolCalls>,
+    /// Set on responses whose API reported token usage
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<TokenUsage>,
 }
 
 impl Message {
@@ -50,6 +71,7 @@ impl Message {
             role: MessageRole::System,
             content: Some(content.into()),
             tool_calls: Vec::new(),
+            usage: None,
         }
     }
 
@@ -59,6 +81,7 @@ impl Message {
             role: MessageRole::User,
             content: Some(content.into()),
             tool_calls: Vec::new(),
+            usage: None,
         }
     }
 }
@@ -73,13 +96,96 @@ pub fn UserMessage<S: Into<String>>(content: S) -> Message {
     Message::user(content)
 }
 
+/// How the model may use the tools of a request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToolChoice {
+    /// The model decides, the default when tools are given
+    Auto,
+    /// Tools stay visible but must not be called
+    None,
+    /// At least one tool must be called
+    Required,
+    /// This tool must be called
+    Named(String),
+}
+
 pub struct InferenceParam<'a> {
     pub messages: &'a Vec<Message>,
     pub tools: Option<&'a Vec<Arc<dyn FunctionTool>>>,
+    /// Sampling options, left to the provider's defaults when `None`
+    pub temperature: Option<f32>,
+    pub top_p: Option<f32>,
+    pub max_tokens: Option<u32>,
+    pub stop: Option<Vec<String>>,
+    /// Ignored without tools; `Auto` when unset
+    pub tool_choice: Option<ToolChoice>,
+    /// Ask for a reply that is a single JSON object, where the API supports it
+    pub json_mode: bool,
+}
+
+impl<'a> InferenceParam<'a> {
+    /// Parameters for `messages` without tools, using the provider's sampling defaults
+    pub fn new(messages: &'a Vec<Message>) -> Self {
+        Self {
+            messages,
+            tools: None,
+            temperature: None,
+            top_p: None,
+            max_tokens: None,
+            stop: None,
+            tool_choice: None,
+            json_mode: false,
+        }
+    }
+
+    pub fn with_tools(mut self, tools: &'a Vec<Arc<dyn FunctionTool>>) -> Self {
+        self.tools = Some(tools);
+        self
+    }
+
+    pub fn with_temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    pub fn with_top_p(mut self, top_p: f32) -> Self {
+        self.top_p = Some(top_p);
+        self
+    }
+
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    pub fn with_stop(mut self, stop: Vec<String>) -> Self {
+        self.stop = Some(stop);
+        self
+    }
+
+    pub fn with_tool_choice(mut self, tool_choice: ToolChoice) -> Self {
+        self.tool_choice = Some(tool_choice);
+        self
+    }
+
+    pub fn with_json_mode(mut self) -> Self {
+        self.json_mode = true;
+        self
+    }
 }
 
 pub trait LLMBase: std::fmt::Debug {
     fn get_model_name(&self) -> &str;
 
     fn inference(&self, param: &InferenceParam) -> Message;
+
+    /// Like `inference`, but calls `on_delta` with each piece of content as it arrives.
+    /// The default emits the whole content once after `inference` returns.
+    fn inference_streaming(&self, param: &InferenceParam, on_delta: &mut dyn FnMut(&str)) -> Message {
+        let message = self.inference(param);
+        if let Some(content) = message.content.as_deref().filter(|c| !c.is_empty()) {
+            on_delta(content);
+        }
+        message
+    }
 }
\ No newline at end of file
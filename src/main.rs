@@ -27,12 +27,26 @@ struct Args {
 
     #[arg(long = "no-gui", help = "以非GUI模式运行节点图（需要--graph-json参数）")]
     no_gui: bool,
+
+    #[arg(long = "param", value_name = "NAME=VALUE", help = "节点图输入参数，可重复（非GUI模式）")]
+    params: Vec<String>,
+
+    #[arg(
+        long = "merge-graph",
+        num_args = 3,
+        value_names = ["BASE", "OURS", "THEIRS"],
+        help = "三方合并节点图，结果写入OURS，有冲突时以非零状态退出（可用作git合并驱动）"
+    )]
+    merge_graph: Option<Vec<String>>,
 }
 
 fn main() {
     // Initialize logging using LogUtil
     LogUtil::init_with_logger(&BASE_LOG).expect("Failed to initialize logger");
 
+    // Record process start time for uptime reporting
+    once_cell::sync::Lazy::force(&node::runtime_nodes::PROCESS_START);
+
     // Initialize node registry
     if let Err(e) = node::registry::init_node_registry() {
         error!("Failed to initialize node registry: {}", e);
@@ -43,6 +57,14 @@ fn main() {
     // Parse command line arguments
     let args = Args::parse();
 
+    if let Some(paths) = args.merge_graph.as_deref() {
+        if let Err(e) = merge_graph_files(&paths[0], &paths[1], &paths[2]) {
+            error!("合并节点图失败: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     // Non-GUI mode: requires graph JSON file
     if args.no_gui {
         let graph_path = match args.graph_json {
@@ -56,7 +78,7 @@ fn main() {
         info!("加载节点图文件: {}", graph_path);
         match node::load_graph_definition_from_json(&graph_path) {
             Ok(definition) => {
-                if let Err(e) = execute_node_graph(definition) {
+                if let Err(e) = execute_node_graph(definition, &graph_path, &args.params) {
                     error!("节点图执行失败: {}", e);
                 }
             }
@@ -69,7 +91,7 @@ fn main() {
 
     // GUI mode: load graph if provided, otherwise start with empty graph
     let mut graph = if let Some(path) = args.graph_json.as_ref() {
-        match node::load_graph_definition_from_json(path) {
+        match node::load_graph_definition_from_json_lenient(path) {
             Ok(graph) => Some(graph),
             Err(err) => {
                 error!("加载节点图失败: {}", err);
@@ -89,20 +111,65 @@ fn main() {
     }
 }
 
-/// Execute a node graph loaded from JSON definition
-fn execute_node_graph(definition: node::NodeGraphDefinition) -> Result<(), Box<dyn std::error::Error>> {
-    info!("构建节点图");
-    let mut graph = node::registry::build_node_graph_from_definition(&definition)?;
-
-    // Load LLM configuration for any LLM nodes that might be in the graph
+/// Execute a node graph loaded from JSON definition. A graph with graph input or output nodes
+/// is fed `params` (`NAME=VALUE`) and its results are printed to stdout as JSON.
+fn execute_node_graph(
+    definition: node::NodeGraphDefinition,
+    graph_path: &str,
+    params: &[String],
+) -> Result<(), Box<dyn std::error::Error>> {
+    // Load LLM configuration for any LLM nodes that might be in the graph, failing before
+    // any node (and with it a bot adapter) is built if it is inconsistent
     let config = load_config();
+    if let Err(problems) = config::validate(&config) {
+        return Err(format!("{} 配置无效:\n  {}", config::config_path(), problems.join("\n  ")).into());
+    }
     if config.agent_model_api.is_none() || config.agent_model_name.is_none() {
         warn!("节点图中的LLM节点可能无法正常工作：缺少 agent_model_api 或 agent_model_name 配置");
     }
 
+    info!("构建节点图");
+    let mut graph = node::registry::build_node_graph_from_definition(&definition)?;
+    graph.set_graph_path(Some(graph_path.into()));
+
     info!("执行节点图");
-    graph.execute()?;
+    if graph.nodes.values().any(|node| node.graph_interface().is_some()) {
+        let parameters = graph.graph_parameters()?;
+        let mut values = std::collections::HashMap::new();
+        for param in params {
+            let (name, raw) = param
+                .split_once('=')
+                .ok_or_else(|| format!("参数格式应为 NAME=VALUE: {}", param))?;
+            let data_type = parameters.get(name).ok_or_else(|| format!("未知的节点图参数: {}", name))?;
+            let value = node::registry::json_to_data_value(&serde_json::Value::String(raw.to_string()), data_type)
+                .ok_or_else(|| format!("参数 {} 的值不是 {}: {}", name, data_type, raw))?;
+            values.insert(name.to_string(), value);
+        }
+
+        let results = graph.execute_with_params(values)?;
+        let results: serde_json::Map<String, serde_json::Value> =
+            results.into_iter().map(|(name, value)| (name, value.to_json())).collect();
+        println!("{}", serde_json::Value::Object(results));
+    } else {
+        if !params.is_empty() {
+            warn!("节点图没有图输入节点，忽略 --param 参数");
+        }
+        graph.execute()?;
+    }
     info!("节点图执行完成");
 
     Ok(())
 }
+
+/// Merge the changes from `base` to `theirs` into `ours` and save the result over `ours`,
+/// the way git calls a merge driver (`%O %A %B`)
+fn merge_graph_files(base: &str, ours: &str, theirs: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let merged = node::graph_io::merge(
+        &node::load_graph_definition_from_json(base)?,
+        &node::load_graph_definition_from_json(ours)?,
+        &node::load_graph_definition_from_json(theirs)?,
+    )?;
+    node::save_graph_definition_to_json(ours, &merged)?;
+    info!("节点图已合并到 {}", ours);
+    Ok(())
+}
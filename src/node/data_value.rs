@@ -1,13 +1,16 @@
+use base64::Engine;
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::HashMap;
 use std::fmt;
 use std::sync::Arc;
 use crate::llm::{Message, function_tools::FunctionTool};
 use crate::bot_adapter::adapter::SharedBotAdapter;
-use crate::bot_adapter::models::event_model::MessageEvent;
+use crate::bot_adapter::models::event_model::{BotNotice, MessageEvent};
 
 /// Redis connection configuration, passed between nodes as a reference
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct RedisConfig {
     pub url: Option<String>,
     pub reconnect_max_attempts: Option<u32>,
@@ -15,13 +18,33 @@ pub struct RedisConfig {
 }
 
 /// MySQL connection configuration, passed between nodes as a reference
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct MySqlConfig {
     pub url: Option<String>,
     pub reconnect_max_attempts: Option<u32>,
     pub reconnect_interval_secs: Option<u64>,
 }
 
+/// Format a local date-time as RFC 3339, e.g. `2024-05-01T12:30:00+08:00`
+pub fn datetime_to_rfc3339(value: &NaiveDateTime) -> String {
+    match Local.from_local_datetime(value).earliest() {
+        Some(local) => local.to_rfc3339(),
+        // Skipped by a DST change, so there is no offset to attach
+        None => value.format("%Y-%m-%dT%H:%M:%S%.f").to_string(),
+    }
+}
+
+/// Parse an ISO-8601 timestamp into local time. Timestamps without an offset are taken as local.
+pub fn parse_iso8601(text: &str) -> Option<NaiveDateTime> {
+    let text = text.trim();
+    if let Ok(value) = DateTime::parse_from_rfc3339(text) {
+        return Some(value.with_timezone(&Local).naive_local());
+    }
+    ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+}
+
 /// Dataflow datatype. Use for checking compatibility between ports.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum DataType {
@@ -31,9 +54,15 @@ pub enum DataType {
     Boolean,
     Json,
     Binary,
+    DateTime,
     List(Box<DataType>),
+    /// String-keyed map whose values all have the inner type
+    Map(Box<DataType>),
+    /// Fixed-length sequence whose elements have the listed types
+    Tuple(Vec<DataType>),
     MessageList,
     MessageEvent,
+    NoticeEvent,
     FunctionTools,
     BotAdapterRef,
     RedisRef,
@@ -42,6 +71,22 @@ pub enum DataType {
     Custom(String),
 }
 
+/// Largest integer magnitude an f64 holds exactly
+const MAX_EXACT_FLOAT_INT: u64 = 1 << 53;
+
+impl DataType {
+    /// Whether `DataValue::coerce_to` may convert values of this type into `target`.
+    /// String to number conversions can still fail at runtime.
+    pub fn can_coerce_to(&self, target: &DataType) -> bool {
+        matches!(
+            (self, target),
+            (DataType::Integer, DataType::Float)
+                | (DataType::Integer | DataType::Float | DataType::Boolean, DataType::String)
+                | (DataType::String, DataType::Integer | DataType::Float)
+        )
+    }
+}
+
 impl fmt::Display for DataType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -51,9 +96,16 @@ impl fmt::Display for DataType {
             DataType::Boolean => write!(f, "Boolean"),
             DataType::Json => write!(f, "Json"),
             DataType::Binary => write!(f, "Binary"),
+            DataType::DateTime => write!(f, "DateTime"),
             DataType::List(inner) => write!(f, "List<{}>", inner),
+            DataType::Map(inner) => write!(f, "Map<{}>", inner),
+            DataType::Tuple(items) => {
+                let items: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+                write!(f, "Tuple<{}>", items.join(", "))
+            }
             DataType::MessageList => write!(f, "MessageList"),
             DataType::MessageEvent => write!(f, "MessageEvent"),
+            DataType::NoticeEvent => write!(f, "NoticeEvent"),
             DataType::FunctionTools => write!(f, "FunctionTools"),
             DataType::BotAdapterRef => write!(f, "BotAdapterRef"),
             DataType::RedisRef => write!(f, "RedisRef"),
@@ -73,9 +125,13 @@ pub enum DataValue {
     Boolean(bool),
     Json(Value),
     Binary(Vec<u8>),
+    DateTime(NaiveDateTime),
     List(Vec<DataValue>),
+    Map(HashMap<String, DataValue>),
+    Tuple(Vec<DataValue>),
     MessageList(Vec<Message>),
     MessageEvent(MessageEvent),
+    NoticeEvent(BotNotice),
     FunctionTools(Vec<Arc<dyn FunctionTool>>),
     BotAdapterRef(SharedBotAdapter),
     RedisRef(Arc<RedisConfig>),
@@ -92,6 +148,7 @@ impl DataValue {
             DataValue::Boolean(_) => DataType::Boolean,
             DataValue::Json(_) => DataType::Json,
             DataValue::Binary(_) => DataType::Binary,
+            DataValue::DateTime(_) => DataType::DateTime,
             DataValue::List(items) => {
                 if let Some(first) = items.first() {
                     DataType::List(Box::new(first.data_type()))
@@ -99,8 +156,14 @@ impl DataValue {
                     DataType::List(Box::new(DataType::String))
                 }
             }
+            DataValue::Map(entries) => match entries.values().next() {
+                Some(value) => DataType::Map(Box::new(value.data_type())),
+                None => DataType::Map(Box::new(DataType::String)),
+            },
+            DataValue::Tuple(items) => DataType::Tuple(items.iter().map(|item| item.data_type()).collect()),
             DataValue::MessageList(_) => DataType::MessageList,
             DataValue::MessageEvent(_) => DataType::MessageEvent,
+            DataValue::NoticeEvent(_) => DataType::NoticeEvent,
             DataValue::FunctionTools(_) => DataType::FunctionTools,
             DataValue::BotAdapterRef(_) => DataType::BotAdapterRef,
             DataValue::RedisRef(_) => DataType::RedisRef,
@@ -109,6 +172,68 @@ impl DataValue {
         }
     }
 
+    /// Check this value against a port type, looking inside lists and maps so every
+    /// element is checked rather than just the one `data_type()` happens to see.
+    /// An empty list or map matches any `List(T)` or `Map(T)`.
+    pub fn check_type(&self, expected: &DataType) -> std::result::Result<(), String> {
+        if let (DataValue::List(items), DataType::List(inner)) = (self, expected) {
+            for (index, item) in items.iter().enumerate() {
+                item.check_type(inner)
+                    .map_err(|reason| format!("{} at index {}", reason, index))?;
+            }
+            return Ok(());
+        }
+        if let (DataValue::Map(entries), DataType::Map(inner)) = (self, expected) {
+            let mut keys: Vec<&String> = entries.keys().collect();
+            keys.sort();
+            for key in keys {
+                entries[key]
+                    .check_type(inner)
+                    .map_err(|reason| format!("{} at key '{}'", reason, key))?;
+            }
+            return Ok(());
+        }
+        if let (DataValue::Tuple(items), DataType::Tuple(types)) = (self, expected) {
+            if items.len() != types.len() {
+                return Err(format!("expects {} tuple elements, got {}", types.len(), items.len()));
+            }
+            for (index, (item, expected)) in items.iter().zip(types).enumerate() {
+                item.check_type(expected)
+                    .map_err(|reason| format!("{} at index {}", reason, index))?;
+            }
+            return Ok(());
+        }
+
+        if self.data_type() != *expected {
+            return Err(format!("expects type {}, got {}", expected, self.data_type()));
+        }
+        Ok(())
+    }
+
+    /// Convert to `target` when no information is lost: Integer to Float, numbers and
+    /// booleans to String, and numeric strings to Integer or Float. Returns `None` otherwise.
+    pub fn coerce_to(&self, target: &DataType) -> Option<DataValue> {
+        if self.data_type() == *target {
+            return Some(self.clone());
+        }
+        match (self, target) {
+            (DataValue::Integer(i), DataType::Float) if i.unsigned_abs() <= MAX_EXACT_FLOAT_INT => {
+                Some(DataValue::Float(*i as f64))
+            }
+            (DataValue::Integer(i), DataType::String) => Some(DataValue::String(i.to_string())),
+            (DataValue::Float(f), DataType::String) => Some(DataValue::String(f.to_string())),
+            (DataValue::Boolean(b), DataType::String) => Some(DataValue::String(b.to_string())),
+            (DataValue::String(s), DataType::Integer) => s.trim().parse().ok().map(DataValue::Integer),
+            (DataValue::String(s), DataType::Float) => s
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|f| f.is_finite())
+                .map(DataValue::Float),
+            _ => None,
+        }
+    }
+
     pub fn to_json(&self) -> Value {
         match self {
             DataValue::String(s) => Value::String(s.clone()),
@@ -116,10 +241,15 @@ impl DataValue {
             DataValue::Float(f) => serde_json::json!(f),
             DataValue::Boolean(b) => Value::Bool(*b),
             DataValue::Json(v) => v.clone(),
-            DataValue::Binary(bytes) => Value::Array(bytes.iter().map(|b| Value::Number((*b).into())).collect()),
+            DataValue::Binary(bytes) => Value::String(base64::engine::general_purpose::STANDARD.encode(bytes)),
+            DataValue::DateTime(value) => Value::String(datetime_to_rfc3339(value)),
             DataValue::List(items) => {
                 Value::Array(items.iter().map(|item| item.to_json()).collect())
             }
+            DataValue::Map(entries) => {
+                Value::Object(entries.iter().map(|(key, value)| (key.clone(), value.to_json())).collect())
+            }
+            DataValue::Tuple(items) => Value::Array(items.iter().map(|item| item.to_json()).collect()),
             DataValue::MessageList(messages) => {
                 let msgs: Vec<Value> = messages.iter().map(|m| {
                     serde_json::json!({
@@ -145,6 +275,7 @@ impl DataValue {
                     "is_group_message": event.is_group_message,
                 })
             }
+            DataValue::NoticeEvent(notice) => notice.to_json(),
             DataValue::FunctionTools(tools) => {
                 let tool_defs: Vec<Value> = tools.iter()
                     .map(|t| t.get_json())
@@ -178,9 +309,13 @@ impl fmt::Debug for DataValue {
             DataValue::Boolean(value) => f.debug_tuple("Boolean").field(value).finish(),
             DataValue::Json(value) => f.debug_tuple("Json").field(value).finish(),
             DataValue::Binary(value) => f.debug_tuple("Binary").field(value).finish(),
+            DataValue::DateTime(value) => f.debug_tuple("DateTime").field(value).finish(),
             DataValue::List(value) => f.debug_tuple("List").field(value).finish(),
+            DataValue::Map(value) => f.debug_tuple("Map").field(value).finish(),
+            DataValue::Tuple(value) => f.debug_tuple("Tuple").field(value).finish(),
             DataValue::MessageList(value) => f.debug_tuple("MessageList").field(value).finish(),
             DataValue::MessageEvent(value) => f.debug_tuple("MessageEvent").field(value).finish(),
+            DataValue::NoticeEvent(value) => f.debug_tuple("NoticeEvent").field(value).finish(),
             DataValue::FunctionTools(value) => f.debug_tuple("FunctionTools").field(value).finish(),
             DataValue::BotAdapterRef(_) => f.debug_tuple("BotAdapterRef").finish(),
             DataValue::RedisRef(config) => f.debug_tuple("RedisRef").field(config).finish(),
@@ -190,6 +325,97 @@ impl fmt::Debug for DataValue {
     }
 }
 
+/// Payloads are compared by value. Message events compare by `message_id`, function
+/// tools by name, and a bot adapter only equals the same shared instance.
+impl PartialEq for DataValue {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (DataValue::String(a), DataValue::String(b)) => a == b,
+            (DataValue::Integer(a), DataValue::Integer(b)) => a == b,
+            (DataValue::Float(a), DataValue::Float(b)) => a == b,
+            (DataValue::Boolean(a), DataValue::Boolean(b)) => a == b,
+            (DataValue::Json(a), DataValue::Json(b)) => a == b,
+            (DataValue::Binary(a), DataValue::Binary(b)) => a == b,
+            (DataValue::DateTime(a), DataValue::DateTime(b)) => a == b,
+            (DataValue::List(a), DataValue::List(b)) => a == b,
+            (DataValue::Map(a), DataValue::Map(b)) => a == b,
+            (DataValue::Tuple(a), DataValue::Tuple(b)) => a == b,
+            (DataValue::MessageList(_), DataValue::MessageList(_)) => self.to_json() == other.to_json(),
+            (DataValue::MessageEvent(a), DataValue::MessageEvent(b)) => a.message_id == b.message_id,
+            (DataValue::NoticeEvent(_), DataValue::NoticeEvent(_)) => self.to_json() == other.to_json(),
+            (DataValue::FunctionTools(a), DataValue::FunctionTools(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.name() == b.name())
+            }
+            (DataValue::BotAdapterRef(a), DataValue::BotAdapterRef(b)) => Arc::ptr_eq(a, b),
+            (DataValue::RedisRef(a), DataValue::RedisRef(b)) => a == b,
+            (DataValue::MySqlRef(a), DataValue::MySqlRef(b)) => a == b,
+            (DataValue::Password(a), DataValue::Password(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+/// Compact human readable form for previews, e.g. `42`, `"hello"`, `[1, 2]`.
+/// The alternate form (`{:#}`) writes a top-level string without quotes.
+/// Connection urls and passwords are never shown, since they may hold credentials.
+impl fmt::Display for DataValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataValue::String(s) if f.alternate() => write!(f, "{}", s),
+            DataValue::String(s) => write!(f, "{:?}", s),
+            DataValue::Integer(i) => write!(f, "{}", i),
+            DataValue::Float(value) => write!(f, "{}", value),
+            DataValue::Boolean(b) => write!(f, "{}", b),
+            DataValue::Json(value) => write!(f, "{}", value),
+            DataValue::Binary(bytes) => write!(f, "<{} bytes>", bytes.len()),
+            DataValue::DateTime(value) => write!(f, "{}", datetime_to_rfc3339(value)),
+            DataValue::List(items) => {
+                write!(f, "[")?;
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+            DataValue::Map(entries) => {
+                let mut keys: Vec<&String> = entries.keys().collect();
+                keys.sort();
+                write!(f, "{{")?;
+                for (index, key) in keys.into_iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{:?}: {}", key, entries[key])?;
+                }
+                write!(f, "}}")
+            }
+            DataValue::Tuple(items) => {
+                write!(f, "(")?;
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, ")")
+            }
+            DataValue::MessageList(messages) => write!(f, "<{} messages>", messages.len()),
+            DataValue::MessageEvent(event) => write!(f, "<message {}>", event.message_id),
+            DataValue::NoticeEvent(notice) => write!(f, "<{}>", notice.kind()),
+            DataValue::FunctionTools(tools) => {
+                let names: Vec<&str> = tools.iter().map(|tool| tool.name()).collect();
+                write!(f, "[{}]", names.join(", "))
+            }
+            DataValue::BotAdapterRef(_) => write!(f, "<bot adapter>"),
+            DataValue::RedisRef(_) => write!(f, "<redis>"),
+            DataValue::MySqlRef(_) => write!(f, "<mysql>"),
+            DataValue::Password(_) => write!(f, "******"),
+        }
+    }
+}
+
 impl Serialize for DataValue {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -198,3 +424,128 @@ impl Serialize for DataValue {
         self.to_json().serialize(serializer)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list_of(inner: DataType) -> DataType {
+        DataType::List(Box::new(inner))
+    }
+
+    #[test]
+    fn test_empty_list_matches_any_list_type() {
+        let empty = DataValue::List(Vec::new());
+        assert!(empty.check_type(&list_of(DataType::String)).is_ok());
+        assert!(empty.check_type(&list_of(DataType::Integer)).is_ok());
+        assert!(empty.check_type(&DataType::String).is_err());
+    }
+
+    #[test]
+    fn test_homogeneous_list_matches_its_element_type() {
+        let list = DataValue::List(vec![DataValue::Integer(1), DataValue::Integer(2)]);
+        assert!(list.check_type(&list_of(DataType::Integer)).is_ok());
+        assert!(list.check_type(&list_of(DataType::String)).is_err());
+    }
+
+    #[test]
+    fn test_mixed_list_names_offending_index() {
+        let list = DataValue::List(vec![
+            DataValue::String("a".to_string()),
+            DataValue::String("b".to_string()),
+            DataValue::Integer(3),
+        ]);
+        let err = list.check_type(&list_of(DataType::String)).unwrap_err();
+        assert_eq!(err, "expects type String, got Integer at index 2");
+
+        // Nested lists report the innermost position
+        let nested = DataValue::List(vec![list]);
+        let err = nested.check_type(&list_of(list_of(DataType::String))).unwrap_err();
+        assert!(err.ends_with("at index 2 at index 0"), "{}", err);
+    }
+
+    #[test]
+    fn test_coerce_safe_widenings() {
+        assert!(matches!(DataValue::Integer(3).coerce_to(&DataType::Float), Some(DataValue::Float(f)) if f == 3.0));
+        assert!(matches!(DataValue::Integer(-7).coerce_to(&DataType::String), Some(DataValue::String(s)) if s == "-7"));
+        assert!(matches!(DataValue::Float(1.5).coerce_to(&DataType::String), Some(DataValue::String(s)) if s == "1.5"));
+        assert!(matches!(DataValue::Boolean(true).coerce_to(&DataType::String), Some(DataValue::String(s)) if s == "true"));
+        assert!(matches!(
+            DataValue::String(" 42 ".to_string()).coerce_to(&DataType::Integer),
+            Some(DataValue::Integer(42))
+        ));
+        assert!(matches!(
+            DataValue::String("2.5".to_string()).coerce_to(&DataType::Float),
+            Some(DataValue::Float(f)) if f == 2.5
+        ));
+    }
+
+    #[test]
+    fn test_coerce_rejects_lossy_and_impossible_conversions() {
+        assert!(DataValue::Float(1.5).coerce_to(&DataType::Integer).is_none());
+        assert!(DataValue::Integer(i64::MAX).coerce_to(&DataType::Float).is_none());
+        assert!(DataValue::Integer(i64::MIN).coerce_to(&DataType::Float).is_none());
+        assert!(DataValue::String("12abc".to_string()).coerce_to(&DataType::Integer).is_none());
+        assert!(DataValue::String("NaN".to_string()).coerce_to(&DataType::Float).is_none());
+        assert!(DataValue::String("true".to_string()).coerce_to(&DataType::Boolean).is_none());
+        assert!(DataValue::Integer(1).coerce_to(&DataType::Boolean).is_none());
+        assert!(DataValue::Json(serde_json::json!(1)).coerce_to(&DataType::Integer).is_none());
+    }
+
+    #[test]
+    fn test_equality_compares_payloads() {
+        let list = |items: &[i64]| DataValue::List(items.iter().map(|i| DataValue::Integer(*i)).collect());
+        assert_eq!(list(&[1, 2]), list(&[1, 2]));
+        assert_ne!(list(&[1, 2]), list(&[2, 1]));
+        assert_ne!(DataValue::Integer(1), DataValue::Float(1.0));
+        assert_ne!(DataValue::String("a".to_string()), DataValue::Password("a".to_string()));
+
+        let config = |url: &str| {
+            DataValue::RedisRef(Arc::new(RedisConfig {
+                url: Some(url.to_string()),
+                reconnect_max_attempts: None,
+                reconnect_interval_secs: None,
+            }))
+        };
+        assert_eq!(config("redis://a"), config("redis://a"));
+        assert_ne!(config("redis://a"), config("redis://b"));
+    }
+
+    #[test]
+    fn test_display_is_compact() {
+        assert_eq!(DataValue::Integer(42).to_string(), "42");
+        assert_eq!(DataValue::String("hello".to_string()).to_string(), "\"hello\"");
+        assert_eq!(format!("{:#}", DataValue::String("hello".to_string())), "hello");
+        let list = DataValue::List(vec![
+            DataValue::String("a".to_string()),
+            DataValue::Boolean(true),
+            DataValue::Float(1.5),
+        ]);
+        assert_eq!(list.to_string(), "[\"a\", true, 1.5]");
+        let map = DataValue::Map(HashMap::from([
+            ("b".to_string(), DataValue::Integer(2)),
+            ("a".to_string(), list),
+        ]));
+        assert_eq!(map.to_string(), "{\"a\": [\"a\", true, 1.5], \"b\": 2}");
+        assert_eq!(DataValue::Json(serde_json::json!({"k": [1]})).to_string(), "{\"k\":[1]}");
+        assert_eq!(DataValue::Binary(vec![0; 3]).to_string(), "<3 bytes>");
+        assert_eq!(DataValue::Password("secret".to_string()).to_string(), "******");
+    }
+
+    #[test]
+    fn test_tuple_checks_arity_and_element_types() {
+        let expected = DataType::Tuple(vec![DataType::String, DataType::Integer]);
+        let pair = DataValue::Tuple(vec![DataValue::String("a".to_string()), DataValue::Integer(1)]);
+        assert!(pair.check_type(&expected).is_ok());
+        assert_eq!(pair.to_string(), "(\"a\", 1)");
+        assert_eq!(expected.to_string(), "Tuple<String, Integer>");
+
+        let swapped = DataValue::Tuple(vec![DataValue::Integer(1), DataValue::String("a".to_string())]);
+        assert_eq!(
+            swapped.check_type(&expected).unwrap_err(),
+            "expects type String, got Integer at index 0"
+        );
+        let short = DataValue::Tuple(vec![DataValue::String("a".to_string())]);
+        assert_eq!(short.check_type(&expected).unwrap_err(), "expects 2 tuple elements, got 1");
+    }
+}
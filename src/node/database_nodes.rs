@@ -35,7 +35,7 @@ impl Node for RedisNode {
 
     node_input![
         port! { name = "redis_host", ty = String, desc = "Redis主机地址" },
-        port! { name = "redis_port", ty = Integer, desc = "Redis端口号" },
+        port! { name = "redis_port", ty = Integer, desc = "Redis端口号", min = 1, max = 65535 },
         port! { name = "redis_db", ty = Integer, desc = "Redis数据库编号 (默认: 0)", optional },
         port! { name = "redis_password", ty = String, desc = "Redis密码", optional },
         port! { name = "reconnect_max_attempts", ty = Integer, desc = "最大重连次数 (默认: 3)", optional },
@@ -47,6 +47,8 @@ impl Node for RedisNode {
     ];
 
     fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+        self.validate_inputs(&inputs)?;
+
         // Extract required parameters
         let host = inputs.get("redis_host").and_then(|v| match v {
             DataValue::String(s) => Some(s.clone()),
@@ -135,7 +137,7 @@ impl Node for MySqlNode {
 
     node_input![
         port! { name = "mysql_host", ty = String, desc = "MySQL主机地址" },
-        port! { name = "mysql_port", ty = Integer, desc = "MySQL端口号" },
+        port! { name = "mysql_port", ty = Integer, desc = "MySQL端口号", min = 1, max = 65535 },
         port! { name = "mysql_user", ty = String, desc = "MySQL用户名" },
         port! { name = "mysql_password", ty = String, desc = "MySQL密码" },
         port! { name = "mysql_database", ty = String, desc = "MySQL数据库名" },
@@ -148,6 +150,8 @@ impl Node for MySqlNode {
     ];
 
     fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+        self.validate_inputs(&inputs)?;
+
         // Extract required parameters
         let host = inputs.get("mysql_host").and_then(|v| match v {
             DataValue::String(s) => Some(s.clone()),
@@ -177,9 +181,9 @@ impl Node for MySqlNode {
         // Build URL from components
         let url = if !password.is_empty() {
             let enc = pct_encode(&password);
-            Some(format!("mysql://{}:{}@{}:{}/{}", user, enc, host, port, database))
+            Some(format!("mysql://{}:{}@{}:{}/{}", pct_encode(&user), enc, host, port, database))
         } else {
-            Some(format!("mysql://{}@{}:{}/{}", user, host, port, database))
+            Some(format!("mysql://{}@{}:{}/{}", pct_encode(&user), host, port, database))
         };
 
         let max_attempts = inputs.get("reconnect_max_attempts").and_then(|v| match v {
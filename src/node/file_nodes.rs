@@ -0,0 +1,231 @@
+use crate::error::Result;
+use crate::node::{node_input, node_output, DataType, DataValue, Node, Port};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Directory (relative to the working directory) that file-writing nodes are confined to
+pub const OUTPUT_ROOT: &str = "output";
+
+/// Resolve a user supplied path inside OUTPUT_ROOT.
+/// Absolute paths and `..` components are rejected so graphs cannot write elsewhere.
+pub fn resolve_output_path(path: &str) -> std::result::Result<PathBuf, String> {
+    let relative = Path::new(path);
+    let is_safe = !path.trim().is_empty()
+        && relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+    if !is_safe {
+        return Err(format!(
+            "Path '{}' must be a relative path inside '{}'",
+            path, OUTPUT_ROOT
+        ));
+    }
+    Ok(Path::new(OUTPUT_ROOT).join(relative))
+}
+
+/// Quote a CSV field per RFC 4180 when it contains a delimiter, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn csv_cell(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(s)) => csv_field(s),
+        Some(other) => csv_field(&other.to_string()),
+    }
+}
+
+/// Render rows as CSV. Columns are `headers` followed by any other keys, in the order
+/// rows first introduce them. Lines end with CRLF as in RFC 4180.
+pub fn render_csv(rows: &[serde_json::Map<String, Value>], headers: &[String]) -> String {
+    let mut columns: Vec<String> = headers.to_vec();
+    for row in rows {
+        for key in row.keys() {
+            if !columns.contains(key) {
+                columns.push(key.clone());
+            }
+        }
+    }
+
+    let mut out = String::new();
+    let header_line: Vec<String> = columns.iter().map(|c| csv_field(c)).collect();
+    out.push_str(&header_line.join(","));
+    out.push_str("\r\n");
+    for row in rows {
+        let line: Vec<String> = columns.iter().map(|c| csv_cell(row.get(c))).collect();
+        out.push_str(&line.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// CSV export node - writes a list of JSON objects to a CSV file under OUTPUT_ROOT
+pub struct CsvExportNode {
+    id: String,
+    name: String,
+}
+
+impl CsvExportNode {
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+        }
+    }
+}
+
+impl Node for CsvExportNode {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> Option<&str> {
+        Some("CSV导出 - 将JSON对象列表写入output目录下的CSV文件")
+    }
+
+    node_input![
+        port! { name = "rows", ty = List(Json), desc = "要导出的JSON对象列表" },
+        port! { name = "path", ty = String, desc = "output目录下的相对文件路径" },
+        port! { name = "headers", ty = List(String), desc = "指定列顺序，其余字段按出现顺序追加", optional },
+        port! { name = "skip_if_empty", ty = Boolean, desc = "没有数据行时不写文件 (默认: false，只写表头)", optional },
+    ];
+
+    node_output![
+        port! { name = "bytes_written", ty = Integer, desc = "写入的字节数" },
+    ];
+
+    fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+        self.validate_inputs(&inputs)?;
+
+        let items = match inputs.get("rows") {
+            Some(DataValue::List(items)) => items,
+            _ => return Err(crate::error::Error::InvalidNodeInput("rows is required".to_string())),
+        };
+        let mut rows = Vec::with_capacity(items.len());
+        for item in items {
+            match item {
+                DataValue::Json(Value::Object(map)) => rows.push(map.clone()),
+                other => {
+                    return Err(crate::error::Error::InvalidNodeInput(format!(
+                        "rows must contain JSON objects, got {:?}",
+                        other
+                    )))
+                }
+            }
+        }
+        let path = match inputs.get("path") {
+            Some(DataValue::String(s)) => {
+                resolve_output_path(s).map_err(crate::error::Error::ValidationError)?
+            }
+            _ => return Err(crate::error::Error::InvalidNodeInput("path is required".to_string())),
+        };
+        let headers: Vec<String> = match inputs.get("headers") {
+            Some(DataValue::List(items)) => items
+                .iter()
+                .filter_map(|item| match item {
+                    DataValue::String(s) => Some(s.clone()),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        let skip_if_empty = matches!(inputs.get("skip_if_empty"), Some(DataValue::Boolean(true)));
+
+        let bytes_written = if rows.is_empty() && skip_if_empty {
+            0
+        } else {
+            let csv = render_csv(&rows, &headers);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&path, &csv)?;
+            csv.len() as i64
+        };
+
+        let mut outputs = HashMap::new();
+        outputs.insert("bytes_written".to_string(), DataValue::Integer(bytes_written));
+
+        self.validate_outputs(&outputs)?;
+        Ok(outputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rows(values: Vec<Value>) -> Vec<serde_json::Map<String, Value>> {
+        values
+            .into_iter()
+            .map(|v| v.as_object().cloned().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_csv_quoting() {
+        let csv = render_csv(
+            &rows(vec![json!({"text": "hello, world", "quote": "say \"hi\"", "multi": "a\nb"})]),
+            &["text".to_string(), "quote".to_string(), "multi".to_string()],
+        );
+        assert_eq!(
+            csv,
+            "text,quote,multi\r\n\"hello, world\",\"say \"\"hi\"\"\",\"a\nb\"\r\n"
+        );
+    }
+
+    #[test]
+    fn test_csv_key_union_across_rows() {
+        let csv = render_csv(
+            &rows(vec![
+                json!({"user": "alice", "count": 3}),
+                json!({"user": "bob", "group": 42}),
+                json!({"note": null}),
+            ]),
+            &[],
+        );
+        let mut lines = csv.split("\r\n");
+        let columns: Vec<&str> = lines.next().unwrap().split(',').collect();
+        // Columns appear in the order rows introduce them; within a row the key order
+        // is up to serde_json, so only the first two are compared as a set
+        let mut first_row_keys = columns[..2].to_vec();
+        first_row_keys.sort();
+        assert_eq!(first_row_keys, ["count", "user"]);
+        assert_eq!(columns[2..], ["group", "note"]);
+
+        let parsed: Vec<HashMap<&str, &str>> = lines
+            .filter(|line| !line.is_empty())
+            .map(|line| columns.iter().copied().zip(line.split(',')).collect())
+            .collect();
+        let expected = [
+            [("count", "3"), ("user", "alice"), ("group", ""), ("note", "")],
+            [("count", ""), ("user", "bob"), ("group", "42"), ("note", "")],
+            [("count", ""), ("user", ""), ("group", ""), ("note", "")],
+        ];
+        assert_eq!(parsed.len(), expected.len());
+        for (row, expected) in parsed.iter().zip(expected) {
+            assert_eq!(*row, HashMap::from(expected));
+        }
+    }
+
+    #[test]
+    fn test_output_path_rejects_escape() {
+        assert!(resolve_output_path("../secret.csv").is_err());
+        assert!(resolve_output_path("/etc/passwd").is_err());
+        assert_eq!(
+            resolve_output_path("reports/run.csv").unwrap(),
+            Path::new(OUTPUT_ROOT).join("reports/run.csv")
+        );
+    }
+}
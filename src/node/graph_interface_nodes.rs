@@ -0,0 +1,239 @@
+use crate::error::Result;
+use crate::node::{DataType, DataValue, GraphInterface, Node, Port};
+use std::collections::HashMap;
+
+/// Inline input naming the graph parameter or result
+pub const PARAM_NAME_PORT: &str = "param_name";
+/// Inline input choosing the type of the value, see `INTERFACE_DATA_TYPES`
+pub const DATA_TYPE_PORT: &str = "data_type";
+/// Input of `GraphInputNode` that `NodeGraph::execute_with_params` fills with the parameter
+pub const PARAM_VALUE_PORT: &str = "param_value";
+
+/// Value types a graph parameter or result can have
+pub const INTERFACE_DATA_TYPES: [&str; 5] = ["String", "Integer", "Float", "Boolean", "Json"];
+
+/// Parse one of `INTERFACE_DATA_TYPES`
+pub fn parse_interface_data_type(name: &str) -> Option<DataType> {
+    match name.trim() {
+        "String" => Some(DataType::String),
+        "Integer" => Some(DataType::Integer),
+        "Float" => Some(DataType::Float),
+        "Boolean" => Some(DataType::Boolean),
+        "Json" => Some(DataType::Json),
+        _ => None,
+    }
+}
+
+fn interface_ports(data_type: &DataType) -> Vec<Port> {
+    vec![
+        Port::new(PARAM_NAME_PORT, DataType::String).with_description("参数名"),
+        Port::new(DATA_TYPE_PORT, DataType::String)
+            .with_description("值的类型")
+            .optional()
+            .with_default(DataValue::String(data_type.to_string()))
+            .with_choices(INTERFACE_DATA_TYPES),
+    ]
+}
+
+/// Graph input node - one parameter of the graph's external interface
+///
+/// Inputs:
+///   - param_name: Name of the parameter (inline value)
+///   - data_type: Type of the parameter (inline value), fixed when the graph is built
+///   - param_value: The parameter, supplied by `NodeGraph::execute_with_params` or inline
+///
+/// Outputs:
+///   - value: The parameter
+pub struct GraphInputNode {
+    id: String,
+    name: String,
+    data_type: DataType,
+}
+
+impl GraphInputNode {
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self::with_data_type(id, name, DataType::String)
+    }
+
+    pub fn with_data_type(id: impl Into<String>, name: impl Into<String>, data_type: DataType) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            data_type,
+        }
+    }
+}
+
+impl Node for GraphInputNode {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> Option<&str> {
+        Some("图输入 - 声明节点图的一个输入参数")
+    }
+
+    fn input_ports(&self) -> Vec<Port> {
+        let mut ports = interface_ports(&self.data_type);
+        ports.push(Port::new(PARAM_VALUE_PORT, self.data_type.clone()).with_description("参数值，运行时由调用方提供"));
+        ports
+    }
+
+    fn output_ports(&self) -> Vec<Port> {
+        vec![Port::new("value", self.data_type.clone()).with_description("参数值")]
+    }
+
+    fn graph_interface(&self) -> Option<GraphInterface> {
+        Some(GraphInterface::Input)
+    }
+
+    fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+        self.validate_inputs(&inputs)?;
+        let value = inputs.get(PARAM_VALUE_PORT).cloned().ok_or_else(|| {
+            crate::error::Error::InvalidNodeInput(format!("{} is required", PARAM_VALUE_PORT))
+        })?;
+        Ok(HashMap::from([("value".to_string(), value)]))
+    }
+}
+
+/// Graph output node - one result of the graph's external interface
+///
+/// Inputs:
+///   - param_name: Name of the result (inline value)
+///   - data_type: Type of the result (inline value), fixed when the graph is built
+///   - value: The result
+///
+/// Outputs:
+///   - result: The recorded result, read by `NodeGraph::execute_with_params`
+pub struct GraphOutputNode {
+    id: String,
+    name: String,
+    data_type: DataType,
+}
+
+impl GraphOutputNode {
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self::with_data_type(id, name, DataType::String)
+    }
+
+    pub fn with_data_type(id: impl Into<String>, name: impl Into<String>, data_type: DataType) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            data_type,
+        }
+    }
+}
+
+impl Node for GraphOutputNode {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> Option<&str> {
+        Some("图输出 - 将值记录为节点图的一个结果")
+    }
+
+    fn input_ports(&self) -> Vec<Port> {
+        let mut ports = interface_ports(&self.data_type);
+        ports.push(Port::new("value", self.data_type.clone()).with_description("要记录的结果"));
+        ports
+    }
+
+    fn output_ports(&self) -> Vec<Port> {
+        vec![Port::new("result", self.data_type.clone()).with_description("记录的结果")]
+    }
+
+    fn graph_interface(&self) -> Option<GraphInterface> {
+        Some(GraphInterface::Output)
+    }
+
+    fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+        self.validate_inputs(&inputs)?;
+        let value = inputs
+            .get("value")
+            .cloned()
+            .ok_or_else(|| crate::error::Error::InvalidNodeInput("value is required".to_string()))?;
+        Ok(HashMap::from([("result".to_string(), value)]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::graph_io::EdgeDefinition;
+    use crate::node::NodeGraph;
+
+    fn edge(from: &str, from_port: &str, to: &str, to_port: &str) -> EdgeDefinition {
+        EdgeDefinition {
+            from_node_id: from.to_string(),
+            from_port: from_port.to_string(),
+            to_node_id: to.to_string(),
+            to_port: to_port.to_string(),
+            condition: None,
+        }
+    }
+
+    fn set_param_name(graph: &mut NodeGraph, node_id: &str, name: &str) {
+        graph
+            .inline_values
+            .entry(node_id.to_string())
+            .or_default()
+            .insert(PARAM_NAME_PORT.to_string(), DataValue::String(name.to_string()));
+    }
+
+    fn passthrough_graph(data_type: DataType) -> NodeGraph {
+        let mut graph = NodeGraph::new();
+        graph
+            .add_node(Box::new(GraphInputNode::with_data_type("in", "In", data_type.clone())))
+            .unwrap();
+        graph
+            .add_node(Box::new(GraphOutputNode::with_data_type("out", "Out", data_type)))
+            .unwrap();
+        set_param_name(&mut graph, "in", "count");
+        set_param_name(&mut graph, "out", "total");
+        graph.set_edges(vec![edge("in", "value", "out", "value")]);
+        graph
+    }
+
+    #[test]
+    fn test_execute_with_params_feeds_inputs_and_collects_outputs() {
+        let mut graph = passthrough_graph(DataType::Integer);
+        for count in [3, 7] {
+            let results = graph
+                .execute_with_params(HashMap::from([("count".to_string(), DataValue::Integer(count))]))
+                .unwrap();
+            assert_eq!(results, HashMap::from([("total".to_string(), DataValue::Integer(count))]));
+        }
+        // The supplied value doesn't stick to the graph
+        assert!(!graph.inline_values["in"].contains_key(PARAM_VALUE_PORT));
+    }
+
+    #[test]
+    fn test_execute_with_params_rejects_missing_and_mistyped_params() {
+        let mut graph = passthrough_graph(DataType::Integer);
+        let missing = graph.execute_with_params(HashMap::new()).unwrap_err();
+        assert!(missing.to_string().contains(PARAM_VALUE_PORT), "{}", missing);
+
+        let mistyped = graph
+            .execute_with_params(HashMap::from([("count".to_string(), DataValue::String("x".to_string()))]))
+            .unwrap_err();
+        assert!(mistyped.to_string().contains("count"), "{}", mistyped);
+    }
+
+    #[test]
+    fn test_parse_interface_data_type() {
+        for name in INTERFACE_DATA_TYPES {
+            assert_eq!(parse_interface_data_type(name).unwrap().to_string(), name);
+        }
+        assert_eq!(parse_interface_data_type("MessageEvent"), None);
+    }
+}
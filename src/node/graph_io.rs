@@ -1,18 +1,40 @@
+use log::warn;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::Path;
 
-use crate::error::Result;
+use crate::error::{Error, Result};
 use crate::node::{DataValue, Node, NodeGraph, Port};
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+/// Version of the graph file format written by `save_graph_definition_to_json`. Files with
+/// an older version are upgraded by `migrate` when loaded.
+pub const GRAPH_FORMAT_VERSION: u32 = 2;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NodeGraphDefinition {
+    /// File format version; files saved before versioning have none and read as 0
+    #[serde(default)]
+    pub version: u32,
     pub nodes: Vec<NodeDefinition>,
     pub edges: Vec<EdgeDefinition>,
     #[serde(skip)]
     pub execution_results: HashMap<String, HashMap<String, DataValue>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub comments: Vec<CommentBox>,
+}
+
+impl Default for NodeGraphDefinition {
+    fn default() -> Self {
+        Self {
+            version: GRAPH_FORMAT_VERSION,
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            execution_results: HashMap::new(),
+            comments: Vec::new(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,41 +53,374 @@ pub struct NodeDefinition {
     pub has_error: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct EdgeDefinition {
     pub from_node_id: String,
     pub from_port: String,
     pub to_node_id: String,
     pub to_port: String,
+    /// Only pass the value along when this boolean output is true
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub condition: Option<EdgeCondition>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Boolean output port that gates an edge
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct EdgeCondition {
+    pub node_id: String,
+    pub port: String,
+}
+
+/// Titled rectangle drawn behind nodes to group them. It has no effect on execution.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommentBox {
+    pub id: String,
+    pub position: GraphPosition,
+    pub size: GraphSize,
+    pub text: String,
+    /// `#rrggbb`
+    pub color: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GraphPosition {
     pub x: f32,
     pub y: f32,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct GraphSize {
     pub width: f32,
     pub height: f32,
 }
 
+/// Load a graph, failing with `Error::InvalidGraph` if any edge points at a node or port the
+/// graph doesn't have
 pub fn load_graph_definition_from_json(path: impl AsRef<Path>) -> Result<NodeGraphDefinition> {
-    let content = fs::read_to_string(path.as_ref())?;
-    let graph: NodeGraphDefinition = serde_json::from_str(&content)?;
+    let graph = read_graph_definition(path.as_ref())?;
+    let dangling_edges = graph.invalid_edges();
+    if !dangling_edges.is_empty() {
+        return Err(Error::InvalidGraph { dangling_edges });
+    }
+    Ok(graph)
+}
+
+/// Load a graph, dropping and logging the edges that point at missing nodes or ports
+pub fn load_graph_definition_from_json_lenient(path: impl AsRef<Path>) -> Result<NodeGraphDefinition> {
+    let mut graph = read_graph_definition(path.as_ref())?;
+    for edge in graph.prune_invalid_edges() {
+        warn!(
+            "Dropped edge {}.{} -> {}.{} from {}: node or port not found",
+            edge.from_node_id,
+            edge.from_port,
+            edge.to_node_id,
+            edge.to_port,
+            path.as_ref().display()
+        );
+    }
     Ok(graph)
 }
 
+fn read_graph_definition(path: &Path) -> Result<NodeGraphDefinition> {
+    let content = fs::read_to_string(path)?;
+    let value: Value = serde_json::from_str(&content)?;
+    // Check the version before parsing, so a newer file reports that instead of whatever
+    // field it no longer matches on
+    let version = match value.get("version") {
+        None => 0,
+        Some(version) => version
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| Error::ValidationError(format!("Invalid graph file version: {}", version)))?,
+    };
+    check_format_version(version)?;
+    migrate(serde_json::from_value(value)?)
+}
+
 pub fn save_graph_definition_to_json(
     path: impl AsRef<Path>,
     graph: &NodeGraphDefinition,
 ) -> Result<()> {
-    let content = serde_json::to_string_pretty(graph)?;
+    let mut value = serde_json::to_value(graph)?;
+    value["version"] = Value::from(GRAPH_FORMAT_VERSION);
+    let content = serde_json::to_string_pretty(&value)?;
     fs::write(path.as_ref(), content)?;
     Ok(())
 }
 
+fn check_format_version(version: u32) -> Result<()> {
+    if version > GRAPH_FORMAT_VERSION {
+        return Err(Error::ValidationError(format!(
+            "Graph file version {} is newer than the supported version {}; please upgrade",
+            version, GRAPH_FORMAT_VERSION
+        )));
+    }
+    Ok(())
+}
+
+/// Upgrade a definition from an older file format version to `GRAPH_FORMAT_VERSION`, one
+/// version at a time. Versions newer than this build understands are an error.
+pub fn migrate(mut graph: NodeGraphDefinition) -> Result<NodeGraphDefinition> {
+    check_format_version(graph.version)?;
+    while graph.version < GRAPH_FORMAT_VERSION {
+        match graph.version {
+            // Files from before versioning share the version 1 layout; nothing to convert.
+            // Later steps rewrite what changed, e.g. a renamed port in nodes and edges.
+            0 => {}
+            1 => rename_input_port(&mut graph, "string_data", "text", "value"),
+            version => {
+                return Err(Error::ValidationError(format!(
+                    "No migration from graph file version {}",
+                    version
+                )))
+            }
+        }
+        graph.version += 1;
+    }
+    Ok(graph)
+}
+
+/// Rename an input port of every node of `node_type`, along with its inline value and
+/// the edges into it
+fn rename_input_port(graph: &mut NodeGraphDefinition, node_type: &str, from: &str, to: &str) {
+    let mut renamed = HashSet::new();
+    for node in graph.nodes.iter_mut().filter(|node| node.node_type == node_type) {
+        for port in node.input_ports.iter_mut().filter(|port| port.name == from) {
+            port.name = to.to_string();
+        }
+        if let Some(value) = node.inline_values.remove(from) {
+            node.inline_values.insert(to.to_string(), value);
+        }
+        renamed.insert(node.id.clone());
+    }
+    for edge in &mut graph.edges {
+        if edge.to_port == from && renamed.contains(&edge.to_node_id) {
+            edge.to_port = to.to_string();
+        }
+    }
+}
+
+/// Structural difference between two graphs, see `diff`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GraphDiff {
+    pub added_nodes: Vec<NodeDefinition>,
+    pub removed_nodes: Vec<String>,
+    pub modified_nodes: Vec<NodeChange>,
+    pub added_edges: Vec<EdgeDefinition>,
+    pub removed_edges: Vec<EdgeDefinition>,
+    pub added_comments: Vec<CommentBox>,
+    pub removed_comments: Vec<String>,
+    /// Comments present in both graphs that differ, as they are in the newer graph
+    pub modified_comments: Vec<CommentBox>,
+}
+
+/// A node present in both graphs whose fields differ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeChange {
+    pub id: String,
+    pub fields: Vec<NodeField>,
+    /// The node as it is in the newer graph
+    pub node: NodeDefinition,
+}
+
+/// Node fields `diff` compares. `has_error` is runtime state and is ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NodeField {
+    Name,
+    Description,
+    NodeType,
+    Position,
+    Size,
+    InlineValues,
+    Ports,
+}
+
+impl NodeField {
+    const ALL: [NodeField; 7] = [
+        NodeField::Name,
+        NodeField::Description,
+        NodeField::NodeType,
+        NodeField::Position,
+        NodeField::Size,
+        NodeField::InlineValues,
+        NodeField::Ports,
+    ];
+
+    fn is_equal(self, a: &NodeDefinition, b: &NodeDefinition) -> bool {
+        match self {
+            NodeField::Name => a.name == b.name,
+            NodeField::Description => a.description == b.description,
+            NodeField::NodeType => a.node_type == b.node_type,
+            NodeField::Position => a.position == b.position,
+            NodeField::Size => a.size == b.size,
+            NodeField::InlineValues => a.inline_values == b.inline_values,
+            // Ports hold default values, which only compare through their JSON form
+            NodeField::Ports => {
+                let ports = |node: &NodeDefinition| serde_json::to_value((&node.input_ports, &node.output_ports)).ok();
+                ports(a) == ports(b)
+            }
+        }
+    }
+
+    fn copy(self, from: &NodeDefinition, to: &mut NodeDefinition) {
+        match self {
+            NodeField::Name => to.name = from.name.clone(),
+            NodeField::Description => to.description = from.description.clone(),
+            NodeField::NodeType => to.node_type = from.node_type.clone(),
+            NodeField::Position => to.position = from.position.clone(),
+            NodeField::Size => to.size = from.size.clone(),
+            NodeField::InlineValues => to.inline_values = from.inline_values.clone(),
+            NodeField::Ports => {
+                to.input_ports = from.input_ports.clone();
+                to.output_ports = from.output_ports.clone();
+            }
+        }
+    }
+}
+
+/// Nodes and comments (by id) and edges (by endpoints and condition) added, removed or changed
+/// from `a` to `b`
+pub fn diff(a: &NodeGraphDefinition, b: &NodeGraphDefinition) -> GraphDiff {
+    let find = |graph: &NodeGraphDefinition, id: &str| graph.nodes.iter().find(|node| node.id == id).cloned();
+
+    let mut graph_diff = GraphDiff::default();
+    for old in &a.nodes {
+        match find(b, &old.id) {
+            None => graph_diff.removed_nodes.push(old.id.clone()),
+            Some(new) => {
+                let fields: Vec<NodeField> =
+                    NodeField::ALL.into_iter().filter(|field| !field.is_equal(old, &new)).collect();
+                if !fields.is_empty() {
+                    graph_diff.modified_nodes.push(NodeChange {
+                        id: old.id.clone(),
+                        fields,
+                        node: new,
+                    });
+                }
+            }
+        }
+    }
+    graph_diff.added_nodes = b.nodes.iter().filter(|node| find(a, &node.id).is_none()).cloned().collect();
+    graph_diff.removed_edges = a.edges.iter().filter(|edge| !b.edges.contains(edge)).cloned().collect();
+    graph_diff.added_edges = b.edges.iter().filter(|edge| !a.edges.contains(edge)).cloned().collect();
+
+    let find_comment = |graph: &NodeGraphDefinition, id: &str| graph.comments.iter().find(|comment| comment.id == id).cloned();
+    for old in &a.comments {
+        match find_comment(b, &old.id) {
+            None => graph_diff.removed_comments.push(old.id.clone()),
+            Some(new) if new != *old => graph_diff.modified_comments.push(new),
+            Some(_) => {}
+        }
+    }
+    graph_diff.added_comments =
+        b.comments.iter().filter(|comment| find_comment(a, &comment.id).is_none()).cloned().collect();
+    graph_diff
+}
+
+/// Apply `graph_diff` to `base`. Changes already present in `base` are accepted; changing a
+/// node or comment `base` doesn't have, adding a different one under an existing id and ending
+/// up with edges to missing nodes or ports are conflicts, all reported in the error.
+pub fn apply_diff(base: &NodeGraphDefinition, graph_diff: &GraphDiff) -> Result<NodeGraphDefinition> {
+    let mut graph = base.clone();
+    let mut conflicts = Vec::new();
+
+    graph.nodes.retain(|node| !graph_diff.removed_nodes.contains(&node.id));
+    for change in &graph_diff.modified_nodes {
+        match graph.nodes.iter_mut().find(|node| node.id == change.id) {
+            Some(node) => change.fields.iter().for_each(|field| field.copy(&change.node, node)),
+            None => conflicts.push(format!("changed node '{}' doesn't exist", change.id)),
+        }
+    }
+    for added in &graph_diff.added_nodes {
+        match graph.nodes.iter().find(|node| node.id == added.id) {
+            Some(existing) if NodeField::ALL.iter().all(|field| field.is_equal(existing, added)) => {}
+            Some(_) => conflicts.push(format!("added node '{}' already exists with different content", added.id)),
+            None => graph.nodes.push(added.clone()),
+        }
+    }
+
+    graph.edges.retain(|edge| !graph_diff.removed_edges.contains(edge));
+    for edge in &graph_diff.added_edges {
+        if !graph.edges.contains(edge) {
+            graph.edges.push(edge.clone());
+        }
+    }
+    for edge in graph.invalid_edges() {
+        conflicts.push(format!(
+            "edge {}.{} -> {}.{} points at a missing node or port",
+            edge.from_node_id, edge.from_port, edge.to_node_id, edge.to_port
+        ));
+    }
+
+    graph.comments.retain(|comment| !graph_diff.removed_comments.contains(&comment.id));
+    for changed in &graph_diff.modified_comments {
+        match graph.comments.iter_mut().find(|comment| comment.id == changed.id) {
+            Some(comment) => *comment = changed.clone(),
+            None => conflicts.push(format!("changed comment '{}' doesn't exist", changed.id)),
+        }
+    }
+    for added in &graph_diff.added_comments {
+        match graph.comments.iter().find(|comment| comment.id == added.id) {
+            Some(existing) if existing == added => {}
+            Some(_) => conflicts.push(format!("added comment '{}' already exists with different content", added.id)),
+            None => graph.comments.push(added.clone()),
+        }
+    }
+
+    if !conflicts.is_empty() {
+        return Err(Error::ValidationError(format!("Graph diff conflicts: {}", conflicts.join("; "))));
+    }
+    Ok(graph)
+}
+
+/// Three-way merge: apply the changes from `base` to `theirs` on top of `ours`. Fields of the
+/// same node changed differently on both sides, comments edited differently on both sides, and
+/// nodes or comments removed on one side but changed on the other, are conflicts.
+pub fn merge(
+    base: &NodeGraphDefinition,
+    ours: &NodeGraphDefinition,
+    theirs: &NodeGraphDefinition,
+) -> Result<NodeGraphDefinition> {
+    let our_diff = diff(base, ours);
+    let their_diff = diff(base, theirs);
+
+    let mut conflicts = Vec::new();
+    for their_change in &their_diff.modified_nodes {
+        let Some(our_change) = our_diff.modified_nodes.iter().find(|change| change.id == their_change.id) else {
+            continue;
+        };
+        for field in &their_change.fields {
+            if our_change.fields.contains(field) && !field.is_equal(&our_change.node, &their_change.node) {
+                conflicts.push(format!("node '{}': both sides changed {:?}", their_change.id, field));
+            }
+        }
+    }
+    for id in &their_diff.removed_nodes {
+        if our_diff.modified_nodes.iter().any(|change| &change.id == id) {
+            conflicts.push(format!("node '{}' was changed on one side and removed on the other", id));
+        }
+    }
+    for their_comment in &their_diff.modified_comments {
+        let ours = our_diff.modified_comments.iter().find(|comment| comment.id == their_comment.id);
+        if ours.is_some_and(|our_comment| our_comment != their_comment) {
+            conflicts.push(format!("comment '{}': both sides changed it", their_comment.id));
+        }
+        if our_diff.removed_comments.contains(&their_comment.id) {
+            conflicts.push(format!("comment '{}' was changed on one side and removed on the other", their_comment.id));
+        }
+    }
+    for id in &their_diff.removed_comments {
+        if our_diff.modified_comments.iter().any(|comment| &comment.id == id) {
+            conflicts.push(format!("comment '{}' was changed on one side and removed on the other", id));
+        }
+    }
+    if !conflicts.is_empty() {
+        return Err(Error::ValidationError(format!("Graph merge conflicts: {}", conflicts.join("; "))));
+    }
+
+    apply_diff(ours, &their_diff)
+}
+
 pub fn ensure_positions(graph: &mut NodeGraphDefinition) {
     let spacing_x = 220.0;
     let spacing_y = 140.0;
@@ -106,16 +461,17 @@ pub fn build_definition_from_graph(graph: &NodeGraph) -> NodeGraphDefinition {
                         from_port: port.name.clone(),
                         to_node_id: node_id.clone(),
                         to_port: port.name.clone(),
+                        condition: None,
                     });
                 }
             }
         }
     }
 
-    NodeGraphDefinition { 
-        nodes, 
+    NodeGraphDefinition {
+        nodes,
         edges,
-        execution_results: HashMap::new(),
+        ..Default::default()
     }
 }
 
@@ -142,4 +498,420 @@ impl NodeGraphDefinition {
     pub fn to_json_value(&self) -> serde_json::Value {
         serde_json::to_value(self).unwrap_or(serde_json::Value::Null)
     }
+
+    /// Copy of the definition with every node id prefixed by `prefix`, so the nodes
+    /// can run inside another graph without clashing with its node ids or node state
+    pub fn namespaced(&self, prefix: &str) -> NodeGraphDefinition {
+        let mut definition = self.clone();
+        for node in &mut definition.nodes {
+            node.id = format!("{}{}", prefix, node.id);
+        }
+        for edge in &mut definition.edges {
+            edge.from_node_id = format!("{}{}", prefix, edge.from_node_id);
+            edge.to_node_id = format!("{}{}", prefix, edge.to_node_id);
+            if let Some(condition) = &mut edge.condition {
+                condition.node_id = format!("{}{}", prefix, condition.node_id);
+            }
+        }
+        definition.execution_results.clear();
+        definition
+    }
+
+    /// Change the id of node `old_id` to `new_id`, rewiring the edges, edge conditions and
+    /// execution results that refer to it. `new_id` must be non-empty, unused and free of
+    /// whitespace and `::`, which separates node id and port name in inline input keys.
+    pub fn rename_node(&mut self, old_id: &str, new_id: &str) -> Result<()> {
+        if new_id.is_empty() || new_id.contains(char::is_whitespace) || new_id.contains("::") {
+            return Err(Error::ValidationError(format!(
+                "Invalid node id '{}': it must be non-empty without whitespace or '::'",
+                new_id
+            )));
+        }
+        if !self.nodes.iter().any(|node| node.id == old_id) {
+            return Err(Error::ValidationError(format!("Node '{}' not found", old_id)));
+        }
+        if old_id == new_id {
+            return Ok(());
+        }
+        if self.nodes.iter().any(|node| node.id == new_id) {
+            return Err(Error::ValidationError(format!("Node id '{}' is already in use", new_id)));
+        }
+
+        let rename = |id: &mut String| {
+            if id == old_id {
+                *id = new_id.to_string();
+            }
+        };
+        self.nodes.iter_mut().for_each(|node| rename(&mut node.id));
+        for edge in &mut self.edges {
+            rename(&mut edge.from_node_id);
+            rename(&mut edge.to_node_id);
+            if let Some(condition) = &mut edge.condition {
+                rename(&mut condition.node_id);
+            }
+        }
+        if let Some(results) = self.execution_results.remove(old_id) {
+            self.execution_results.insert(new_id.to_string(), results);
+        }
+        Ok(())
+    }
+
+    /// Edges whose nodes, ports or condition port don't exist in the graph
+    pub fn invalid_edges(&self) -> Vec<EdgeDefinition> {
+        self.edges.iter().filter(|edge| !self.is_valid_edge(edge)).cloned().collect()
+    }
+
+    /// Remove the edges `invalid_edges` reports, returning them
+    pub fn prune_invalid_edges(&mut self) -> Vec<EdgeDefinition> {
+        let (valid, invalid) = std::mem::take(&mut self.edges)
+            .into_iter()
+            .partition(|edge| self.is_valid_edge(edge));
+        self.edges = valid;
+        invalid
+    }
+
+    fn is_valid_edge(&self, edge: &EdgeDefinition) -> bool {
+        let has_port = |node_id: &str, port: &str, output: bool| {
+            self.nodes.iter().any(|node| {
+                let ports = if output { &node.output_ports } else { &node.input_ports };
+                node.id == node_id && ports.iter().any(|p| p.name == port)
+            })
+        };
+        has_port(&edge.from_node_id, &edge.from_port, true)
+            && has_port(&edge.to_node_id, &edge.to_port, false)
+            && edge
+                .condition
+                .as_ref()
+                .is_none_or(|condition| has_port(&condition.node_id, &condition.port, true))
+    }
+
+    /// Required input ports fed by neither an edge nor an inline value, as `(node_id, port)`.
+    /// Graphs without edges bind ports by name, like `NodeGraph::execute`.
+    pub fn unbound_inputs(&self) -> Vec<(String, Port)> {
+        let mut unbound = Vec::new();
+        for node in &self.nodes {
+            for port in &node.input_ports {
+                if !port.required || node.inline_values.contains_key(&port.name) {
+                    continue;
+                }
+                let bound = if self.edges.is_empty() {
+                    self.nodes
+                        .iter()
+                        .any(|other| other.id != node.id && other.output_ports.iter().any(|p| p.name == port.name))
+                } else {
+                    self.edges
+                        .iter()
+                        .any(|edge| edge.to_node_id == node.id && edge.to_port == port.name)
+                };
+                if !bound {
+                    unbound.push((node.id.clone(), port.clone()));
+                }
+            }
+        }
+        unbound.sort_by(|a, b| a.0.cmp(&b.0));
+        unbound
+    }
+
+    /// Output ports no other node reads from, as `(node_id, port)`
+    pub fn unconsumed_outputs(&self) -> Vec<(String, Port)> {
+        let mut unconsumed = Vec::new();
+        for node in &self.nodes {
+            for port in &node.output_ports {
+                let consumed = if self.edges.is_empty() {
+                    self.nodes
+                        .iter()
+                        .any(|other| other.id != node.id && other.input_ports.iter().any(|p| p.name == port.name))
+                } else {
+                    self.edges
+                        .iter()
+                        .any(|edge| edge.from_node_id == node.id && edge.from_port == port.name)
+                };
+                if !consumed {
+                    unconsumed.push((node.id.clone(), port.clone()));
+                }
+            }
+        }
+        unconsumed.sort_by(|a, b| a.0.cmp(&b.0));
+        unconsumed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::DataType;
+
+    fn node(id: &str, inputs: &[&str], outputs: &[&str]) -> NodeDefinition {
+        NodeDefinition {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: None,
+            node_type: "string_data".to_string(),
+            input_ports: inputs.iter().map(|name| Port::new(*name, DataType::String)).collect(),
+            output_ports: outputs.iter().map(|name| Port::new(*name, DataType::String)).collect(),
+            position: None,
+            size: None,
+            inline_values: HashMap::new(),
+            has_error: false,
+        }
+    }
+
+    fn edge(from: &str, from_port: &str, to: &str, to_port: &str) -> EdgeDefinition {
+        EdgeDefinition {
+            from_node_id: from.to_string(),
+            from_port: from_port.to_string(),
+            to_node_id: to.to_string(),
+            to_port: to_port.to_string(),
+            condition: None,
+        }
+    }
+
+    /// `a.out -> b.in` is fine, the other edges point at a deleted node, a missing port and a
+    /// missing condition port
+    fn graph_with_dangling_edges() -> NodeGraphDefinition {
+        let conditioned = EdgeDefinition {
+            condition: Some(EdgeCondition {
+                node_id: "a".to_string(),
+                port: "flag".to_string(),
+            }),
+            ..edge("a", "out", "b", "in")
+        };
+        NodeGraphDefinition {
+            nodes: vec![node("a", &[], &["out"]), node("b", &["in"], &[])],
+            edges: vec![
+                edge("a", "out", "b", "in"),
+                edge("deleted", "out", "b", "in"),
+                edge("a", "out", "b", "renamed"),
+                conditioned,
+            ],
+            ..Default::default()
+        }
+    }
+
+    fn temp_graph_file(name: &str, content: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("zihuan_graph_io_{}_{}.json", std::process::id(), name));
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_unversioned_file_loads_as_current_version() {
+        let path = temp_graph_file("v0", r#"{"nodes": [], "edges": []}"#);
+        let graph = load_graph_definition_from_json(&path).unwrap();
+        assert_eq!(graph.version, GRAPH_FORMAT_VERSION);
+
+        save_graph_definition_to_json(&path, &NodeGraphDefinition { version: 0, ..graph }).unwrap();
+        let saved: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["version"], GRAPH_FORMAT_VERSION);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_string_data_text_input_is_renamed_on_load() {
+        let mut source = node("source", &["text"], &["text"]);
+        source.inline_values.insert("text".to_string(), Value::from("hello"));
+        let graph = NodeGraphDefinition {
+            version: 1,
+            nodes: vec![source, node("upstream", &[], &["text"])],
+            edges: vec![edge("upstream", "text", "source", "text")],
+            ..Default::default()
+        };
+
+        let graph = migrate(graph).unwrap();
+        let source = &graph.nodes[0];
+        assert_eq!(source.input_ports[0].name, "value");
+        assert_eq!(source.output_ports[0].name, "text");
+        assert_eq!(source.inline_values.get("value"), Some(&Value::from("hello")));
+        assert!(!source.inline_values.contains_key("text"));
+        assert_eq!(graph.edges[0].from_port, "text");
+        assert_eq!(graph.edges[0].to_port, "value");
+    }
+
+    #[test]
+    fn test_newer_file_version_is_rejected() {
+        let content = format!(r#"{{"version": {}, "graph": {{}}}}"#, GRAPH_FORMAT_VERSION + 1);
+        let path = temp_graph_file("future", &content);
+        let err = load_graph_definition_from_json(&path).unwrap_err();
+        assert!(err.to_string().contains("newer than the supported version"), "{}", err);
+        let _ = fs::remove_file(&path);
+
+        let future = NodeGraphDefinition {
+            version: GRAPH_FORMAT_VERSION + 1,
+            ..Default::default()
+        };
+        assert!(migrate(future).is_err());
+    }
+
+    #[test]
+    fn test_prune_invalid_edges_returns_removed_edges() {
+        let mut graph = graph_with_dangling_edges();
+        assert_eq!(graph.invalid_edges().len(), 3);
+
+        let removed = graph.prune_invalid_edges();
+        assert_eq!(removed.len(), 3);
+        assert_eq!(removed[0].from_node_id, "deleted");
+        assert_eq!(removed[1].to_port, "renamed");
+        assert!(removed[2].condition.is_some());
+        assert_eq!(graph.edges.len(), 1);
+        assert!(graph.invalid_edges().is_empty());
+        assert!(graph.prune_invalid_edges().is_empty());
+    }
+
+    #[test]
+    fn test_dangling_edges_fail_strict_load_and_are_pruned_by_lenient_load() {
+        let content = serde_json::to_string(&graph_with_dangling_edges()).unwrap();
+        let path = temp_graph_file("dangling", &content);
+
+        match load_graph_definition_from_json(&path) {
+            Err(Error::InvalidGraph { dangling_edges }) => assert_eq!(dangling_edges.len(), 3),
+            other => panic!("expected InvalidGraph, got {:?}", other.map(|graph| graph.edges)),
+        }
+        let graph = load_graph_definition_from_json_lenient(&path).unwrap();
+        assert_eq!(graph.edges.len(), 1);
+        assert_eq!(graph.edges[0].from_node_id, "a");
+        let _ = fs::remove_file(&path);
+    }
+
+    fn positioned(id: &str, x: f32) -> NodeDefinition {
+        NodeDefinition {
+            position: Some(GraphPosition { x, y: 0.0 }),
+            ..node(id, &["in"], &["out"])
+        }
+    }
+
+    fn chain(ids: &[&str]) -> NodeGraphDefinition {
+        NodeGraphDefinition {
+            nodes: ids.iter().enumerate().map(|(i, id)| positioned(id, i as f32 * 200.0)).collect(),
+            edges: ids.windows(2).map(|pair| edge(pair[0], "out", pair[1], "in")).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn node_ids(graph: &NodeGraphDefinition) -> Vec<&str> {
+        graph.nodes.iter().map(|node| node.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_diff_reports_node_and_edge_changes() {
+        let a = chain(&["a", "b", "c"]);
+        let mut b = chain(&["a", "b", "d"]);
+        b.nodes[0].position = Some(GraphPosition { x: 40.0, y: 60.0 });
+        b.nodes[1].inline_values.insert("in".to_string(), Value::from("hi"));
+
+        let graph_diff = diff(&a, &b);
+        assert_eq!(graph_diff.removed_nodes, vec!["c".to_string()]);
+        let added: Vec<&str> = graph_diff.added_nodes.iter().map(|node| node.id.as_str()).collect();
+        assert_eq!(added, vec!["d"]);
+        let changes: Vec<(&str, &[NodeField])> =
+            graph_diff.modified_nodes.iter().map(|change| (change.id.as_str(), change.fields.as_slice())).collect();
+        assert_eq!(changes, vec![("a", &[NodeField::Position][..]), ("b", &[NodeField::InlineValues][..])]);
+        assert_eq!(graph_diff.removed_edges, vec![edge("b", "out", "c", "in")]);
+        assert_eq!(graph_diff.added_edges, vec![edge("b", "out", "d", "in")]);
+
+        let applied = apply_diff(&a, &graph_diff).unwrap();
+        let round_trip = diff(&applied, &b);
+        assert!(round_trip.added_nodes.is_empty() && round_trip.removed_nodes.is_empty());
+        assert!(round_trip.modified_nodes.is_empty());
+        assert!(round_trip.added_edges.is_empty() && round_trip.removed_edges.is_empty());
+        assert!(diff(&a, &a).modified_nodes.is_empty());
+    }
+
+    #[test]
+    fn test_merge_combines_independent_edits_and_reports_conflicts() {
+        let base = chain(&["a", "b"]);
+        // We move a, they add c after b
+        let mut ours = base.clone();
+        ours.nodes[0].position = Some(GraphPosition { x: 500.0, y: 0.0 });
+        let theirs = chain(&["a", "b", "c"]);
+
+        let merged = merge(&base, &ours, &theirs).unwrap();
+        assert_eq!(node_ids(&merged), vec!["a", "b", "c"]);
+        assert_eq!(merged.nodes[0].position, Some(GraphPosition { x: 500.0, y: 0.0 }));
+        assert_eq!(merged.edges, vec![edge("a", "out", "b", "in"), edge("b", "out", "c", "in")]);
+
+        // Both move a to different places
+        let mut moved = base.clone();
+        moved.nodes[0].position = Some(GraphPosition { x: 0.0, y: 300.0 });
+        let err = merge(&base, &ours, &moved).unwrap_err();
+        assert!(err.to_string().contains("both sides changed Position"), "{}", err);
+
+        // They remove b while we edit it
+        let removed = chain(&["a"]);
+        let mut edited = base.clone();
+        edited.nodes[1].name = "renamed".to_string();
+        let err = merge(&base, &edited, &removed).unwrap_err();
+        assert!(err.to_string().contains("removed on the other"), "{}", err);
+        let err = apply_diff(&chain(&["a"]), &diff(&base, &edited)).unwrap_err();
+        assert!(err.to_string().contains("changed node 'b' doesn't exist"), "{}", err);
+    }
+
+    fn comment(id: &str, text: &str) -> CommentBox {
+        CommentBox {
+            id: id.to_string(),
+            position: GraphPosition { x: 0.0, y: 0.0 },
+            size: GraphSize { width: 200.0, height: 100.0 },
+            text: text.to_string(),
+            color: "#ffcc00".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_merge_carries_comment_changes() {
+        let mut base = chain(&["a", "b"]);
+        base.comments = vec![comment("note", "inputs"), comment("todo", "fix b")];
+        // They add a comment, edit one and remove the other
+        let mut theirs = base.clone();
+        theirs.comments = vec![comment("note", "inputs, checked"), comment("new", "outputs")];
+
+        let merged = merge(&base, &base, &theirs).unwrap();
+        assert_eq!(merged.comments, theirs.comments);
+        let round_trip = diff(&merged, &theirs);
+        assert!(round_trip.added_comments.is_empty() && round_trip.removed_comments.is_empty());
+        assert!(round_trip.modified_comments.is_empty());
+
+        // Both edit the same comment differently
+        let mut ours = base.clone();
+        ours.comments[0].color = "#00ccff".to_string();
+        let err = merge(&base, &ours, &theirs).unwrap_err();
+        assert!(err.to_string().contains("comment 'note': both sides changed it"), "{}", err);
+
+        // We edit a comment they removed
+        let mut ours = base.clone();
+        ours.comments[1].text = "fix b first".to_string();
+        let err = merge(&base, &ours, &theirs).unwrap_err();
+        assert!(err.to_string().contains("comment 'todo' was changed"), "{}", err);
+    }
+
+    #[test]
+    fn test_rename_node_rewires_edges() {
+        let mut graph = chain(&["a", "b", "c"]);
+        graph.edges.push(EdgeDefinition {
+            condition: Some(EdgeCondition {
+                node_id: "b".to_string(),
+                port: "out".to_string(),
+            }),
+            ..edge("a", "out", "c", "in")
+        });
+        graph.execution_results.insert("b".to_string(), HashMap::new());
+
+        graph.rename_node("b", "parser").unwrap();
+        assert_eq!(node_ids(&graph), vec!["a", "parser", "c"]);
+        assert_eq!(graph.edges[0], edge("a", "out", "parser", "in"));
+        assert_eq!(graph.edges[1], edge("parser", "out", "c", "in"));
+        assert_eq!(graph.edges[2].condition.as_ref().unwrap().node_id, "parser");
+        assert!(graph.execution_results.contains_key("parser") && !graph.execution_results.contains_key("b"));
+        assert!(graph.invalid_edges().is_empty());
+        graph.rename_node("parser", "parser").unwrap();
+    }
+
+    #[test]
+    fn test_rename_node_rejects_bad_ids() {
+        let mut graph = chain(&["a", "b"]);
+        for bad in ["", "  ", "my node", "a::b", "b"] {
+            assert!(graph.rename_node("a", bad).is_err(), "{:?}", bad);
+        }
+        assert!(graph.rename_node("missing", "c").is_err());
+        assert_eq!(node_ids(&graph), vec!["a", "b"]);
+        assert_eq!(graph.edges, vec![edge("a", "out", "b", "in")]);
+    }
 }
@@ -0,0 +1,187 @@
+use crate::error::Result;
+use crate::node::{node_ports, DataType, DataValue, Node, Port};
+use crate::util::http_client::{HttpPost, RetryingHttpClient};
+use hmac::{Hmac, Mac};
+use serde_json::Value;
+use sha2::Sha256;
+use std::collections::HashMap;
+
+/// Header carrying the HMAC of the request body
+pub const SIGNATURE_HEADER: &str = "X-Signature";
+
+/// Sign `body` with HMAC-SHA256, formatted as `sha256=<hex>` like GitHub webhooks
+pub fn sign_payload(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    let digest = mac.finalize().into_bytes();
+    let hex: String = digest.iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("sha256={}", hex)
+}
+
+/// Webhook node - POSTs a JSON payload to an external URL
+pub struct WebhookNode {
+    id: String,
+    name: String,
+    /// Built on first use: the registry also creates nodes just to list or validate them,
+    /// and a blocking client must not be created or dropped inside a tokio runtime
+    client: Option<Box<dyn HttpPost>>,
+}
+
+impl WebhookNode {
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            client: None,
+        }
+    }
+
+    /// Use a custom HTTP client instead of the default retrying one
+    pub fn with_client(id: impl Into<String>, name: impl Into<String>, client: Box<dyn HttpPost>) -> Self {
+        Self {
+            client: Some(client),
+            ..Self::new(id, name)
+        }
+    }
+}
+
+impl Node for WebhookNode {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> Option<&str> {
+        Some("Webhook - 将JSON数据POST到外部URL，可选HMAC签名")
+    }
+
+    node_ports! {
+        inputs: [
+            port! { name = "url", ty = String, desc = "Webhook地址" },
+            port! { name = "payload", ty = Json, desc = "要发送的JSON数据" },
+            port! { name = "headers", ty = Json, desc = "额外的请求头，JSON对象", optional },
+            port! { name = "hmac_secret", ty = Password, desc = "签名密钥，提供时添加 X-Signature 请求头", optional },
+        ],
+        outputs: [
+            port! { name = "status", ty = Integer, desc = "HTTP状态码" },
+            port! { name = "response", ty = String, desc = "响应内容" },
+        ],
+    }
+
+    fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+        self.validate_inputs(&inputs)?;
+
+        let url = match inputs.get("url") {
+            Some(DataValue::String(s)) if !s.trim().is_empty() => s.trim().to_string(),
+            _ => return Err(crate::error::Error::InvalidNodeInput("url is required".to_string())),
+        };
+        let payload = match inputs.get("payload") {
+            Some(DataValue::Json(value)) => value,
+            _ => return Err(crate::error::Error::InvalidNodeInput("payload is required".to_string())),
+        };
+        let body = serde_json::to_vec(payload)?;
+
+        let mut headers = vec![("Content-Type".to_string(), "application/json".to_string())];
+        match inputs.get("headers") {
+            Some(DataValue::Json(Value::Object(map))) => {
+                for (name, value) in map {
+                    let value = match value {
+                        Value::String(s) => s.clone(),
+                        other => other.to_string(),
+                    };
+                    headers.push((name.clone(), value));
+                }
+            }
+            Some(DataValue::Json(Value::Null)) | None => {}
+            Some(other) => {
+                return Err(crate::error::Error::InvalidNodeInput(format!(
+                    "headers must be a JSON object, got {:?}",
+                    other
+                )))
+            }
+        }
+        match inputs.get("hmac_secret") {
+            Some(DataValue::Password(secret)) | Some(DataValue::String(secret)) if !secret.is_empty() => {
+                headers.push((SIGNATURE_HEADER.to_string(), sign_payload(secret, &body)));
+            }
+            _ => {}
+        }
+
+        // Non-2xx responses are reported through `status`; only a missing response is an error
+        let client = self.client.get_or_insert_with(|| Box::new(RetryingHttpClient::new()));
+        let response = client
+            .post(&url, &headers, &body)
+            .map_err(|e| crate::error::Error::StringError(format!("Webhook request to {} failed: {}", url, e)))?;
+
+        let mut outputs = HashMap::new();
+        outputs.insert("status".to_string(), DataValue::Integer(response.status as i64));
+        outputs.insert("response".to_string(), DataValue::String(response.body));
+
+        self.validate_outputs(&outputs)?;
+        Ok(outputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::http_client::HttpResponse;
+    use std::sync::{Arc, Mutex};
+
+    type Headers = Vec<(String, String)>;
+
+    /// Records the headers of each request and answers with a fixed status
+    struct MockClient {
+        status: u16,
+        requests: Arc<Mutex<Vec<Headers>>>,
+    }
+
+    impl HttpPost for MockClient {
+        fn post(&self, _url: &str, headers: &[(String, String)], _body: &[u8]) -> std::result::Result<HttpResponse, String> {
+            self.requests.lock().unwrap().push(headers.to_vec());
+            Ok(HttpResponse {
+                status: self.status,
+                body: "rejected".to_string(),
+            })
+        }
+    }
+
+    #[test]
+    fn test_webhook_signs_payload_and_reports_status() {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let client = MockClient {
+            status: 403,
+            requests: requests.clone(),
+        };
+        let mut node = WebhookNode::with_client("webhook_test", "Webhook", Box::new(client));
+        let payload = serde_json::json!({"event": "run_finished"});
+        let inputs = HashMap::from([
+            ("url".to_string(), DataValue::String("https://example.com/hook".to_string())),
+            ("payload".to_string(), DataValue::Json(payload.clone())),
+            ("hmac_secret".to_string(), DataValue::Password("secret".to_string())),
+        ]);
+
+        let outputs = node.execute(inputs).unwrap();
+        // A non-2xx status is an output, not an error
+        assert!(matches!(outputs.get("status"), Some(DataValue::Integer(403))));
+        assert!(matches!(outputs.get("response"), Some(DataValue::String(s)) if s == "rejected"));
+
+        let requests = requests.lock().unwrap();
+        let signature = requests[0]
+            .iter()
+            .find(|(name, _)| name == SIGNATURE_HEADER)
+            .map(|(_, value)| value.clone())
+            .expect("signature header should be present");
+        assert_eq!(signature, sign_payload("secret", &serde_json::to_vec(&payload).unwrap()));
+        assert!(signature.starts_with("sha256="));
+    }
+
+    #[tokio::test]
+    async fn test_new_node_can_be_dropped_inside_runtime() {
+        // As when the registry lists node types from async code
+        drop(WebhookNode::new("webhook_test", "Webhook"));
+    }
+}
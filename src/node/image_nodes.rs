@@ -0,0 +1,184 @@
+use crate::error::Result;
+use crate::node::{node_input, node_output, DataType, DataValue, Node, Port};
+use base64::Engine;
+use qrcode::types::QrError;
+use qrcode::{Color, EcLevel, QrCode};
+use std::collections::HashMap;
+
+/// Pixel size of one QR module in the rendered PNG
+const MODULE_PIXELS: usize = 8;
+/// Quiet zone around the code, in modules, required by most scanners
+const QUIET_ZONE_MODULES: usize = 4;
+
+/// Parse an error correction level name (L/M/Q/H)
+pub fn parse_ec_level(level: &str) -> std::result::Result<EcLevel, String> {
+    match level.trim().to_ascii_uppercase().as_str() {
+        "L" => Ok(EcLevel::L),
+        "M" => Ok(EcLevel::M),
+        "Q" => Ok(EcLevel::Q),
+        "H" => Ok(EcLevel::H),
+        other => Err(format!(
+            "Unknown error correction level '{}', expected one of L, M, Q, H",
+            other
+        )),
+    }
+}
+
+/// Maximum payload of a version 40 QR code in byte mode
+fn max_bytes(level: EcLevel) -> usize {
+    match level {
+        EcLevel::L => 2953,
+        EcLevel::M => 2331,
+        EcLevel::Q => 1663,
+        EcLevel::H => 1273,
+    }
+}
+
+/// Encode `text` as a QR code and render it to a grayscale PNG
+pub fn render_qr_png(text: &str, level: EcLevel) -> std::result::Result<Vec<u8>, String> {
+    let code = QrCode::with_error_correction_level(text.as_bytes(), level).map_err(|e| match e {
+        QrError::DataTooLong => format!(
+            "Text is too long for a QR code: {} bytes, at most {} bytes with error correction {:?}",
+            text.len(),
+            max_bytes(level),
+            level
+        ),
+        other => format!("Failed to encode QR code: {}", other),
+    })?;
+
+    let modules = code.width();
+    let colors = code.to_colors();
+    let size = (modules + QUIET_ZONE_MODULES * 2) * MODULE_PIXELS;
+
+    let mut pixels = vec![255u8; size * size];
+    for (index, color) in colors.iter().enumerate() {
+        if *color != Color::Dark {
+            continue;
+        }
+        let x0 = (index % modules + QUIET_ZONE_MODULES) * MODULE_PIXELS;
+        let y0 = (index / modules + QUIET_ZONE_MODULES) * MODULE_PIXELS;
+        for y in y0..y0 + MODULE_PIXELS {
+            pixels[y * size + x0..y * size + x0 + MODULE_PIXELS].fill(0);
+        }
+    }
+
+    let mut png_bytes = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut png_bytes, size as u32, size as u32);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+        writer.write_image_data(&pixels).map_err(|e| e.to_string())?;
+    }
+    Ok(png_bytes)
+}
+
+/// QR code node - renders text as a QR code PNG
+pub struct QrCodeNode {
+    id: String,
+    name: String,
+}
+
+impl QrCodeNode {
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+        }
+    }
+}
+
+impl Node for QrCodeNode {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> Option<&str> {
+        Some("二维码生成 - 将文本编码为二维码PNG图片")
+    }
+
+    node_input![
+        port! { name = "text", ty = String, desc = "要编码的文本" },
+        port! { name = "error_correction", ty = String, desc = "纠错等级 L/M/Q/H (默认: M)", optional },
+    ];
+
+    node_output![
+        port! { name = "image", ty = String, desc = "Base64编码的PNG图片" },
+        port! { name = "bytes", ty = Binary, desc = "PNG图片的原始字节" },
+    ];
+
+    fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+        self.validate_inputs(&inputs)?;
+
+        let text = match inputs.get("text") {
+            Some(DataValue::String(s)) => s.clone(),
+            _ => return Err(crate::error::Error::InvalidNodeInput("text is required".to_string())),
+        };
+        let level = match inputs.get("error_correction") {
+            Some(DataValue::String(s)) if !s.trim().is_empty() => {
+                parse_ec_level(s).map_err(crate::error::Error::InvalidNodeInput)?
+            }
+            _ => EcLevel::M,
+        };
+
+        let png_bytes = render_qr_png(&text, level).map_err(crate::error::Error::ValidationError)?;
+        let encoded = base64::engine::general_purpose::STANDARD.encode(&png_bytes);
+
+        let mut outputs = HashMap::new();
+        outputs.insert("image".to_string(), DataValue::String(encoded));
+        outputs.insert("bytes".to_string(), DataValue::Binary(png_bytes));
+
+        self.validate_outputs(&outputs)?;
+        Ok(outputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+    #[test]
+    fn test_qr_code_produces_png() {
+        let mut node = QrCodeNode::new("qr_code_test", "QrCode");
+        let inputs = HashMap::from([(
+            "text".to_string(),
+            DataValue::String("https://example.com".to_string()),
+        )]);
+        let outputs = node.execute(inputs).unwrap();
+
+        let bytes = match outputs.get("bytes") {
+            Some(DataValue::Binary(bytes)) => bytes.clone(),
+            _ => panic!("bytes should be Binary"),
+        };
+        assert!(bytes.starts_with(&PNG_SIGNATURE));
+
+        // The image is square and sized by whole modules plus the quiet zone
+        let decoder = png::Decoder::new(bytes.as_slice());
+        let reader = decoder.read_info().unwrap();
+        let info = reader.info();
+        assert_eq!(info.width, info.height);
+        assert_eq!(info.width as usize % MODULE_PIXELS, 0);
+
+        match outputs.get("image") {
+            Some(DataValue::String(encoded)) => {
+                let decoded = base64::engine::general_purpose::STANDARD.decode(encoded).unwrap();
+                assert_eq!(decoded, bytes);
+            }
+            _ => panic!("image should be a base64 String"),
+        }
+    }
+
+    #[test]
+    fn test_qr_code_too_long_reports_size() {
+        let text = "x".repeat(3000);
+        let err = render_qr_png(&text, EcLevel::H).unwrap_err();
+        assert!(err.contains("3000"));
+        assert!(err.contains("1273"));
+    }
+}
@@ -1,7 +1,12 @@
+use crate::bot_adapter::models::MessageEvent;
 use crate::error::Result;
+use crate::node::state_store::{get_node_state, set_node_state, set_persisted_node_state};
+use crate::node::text_nodes::text_similarity;
 use crate::node::{node_input, node_output, DataType, DataValue, Node, Port, NodeType};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::sync::Mutex as TokioMutex;
 
 /// Message MySQL Persistence Node - Stores MessageEvent to MySQL database
@@ -70,6 +75,84 @@ impl Node for MessageMySQLPersistenceNode {
     }
 }
 
+/// Conditional Persist Node - Runs the MySQL persistence only when `should_persist` is true
+pub struct ConditionalPersistNode {
+    id: String,
+    name: String,
+    inner: Box<dyn Node>,
+}
+
+impl ConditionalPersistNode {
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        let id = id.into();
+        let inner = Box::new(MessageMySQLPersistenceNode::new(format!("{}__persist", id), "MessageMySQLPersistence"));
+        Self::with_inner(id, name, inner)
+    }
+
+    /// Wrap a custom persistence node (must accept `message_event`/`mysql_ref` and output `success`)
+    pub fn with_inner(id: impl Into<String>, name: impl Into<String>, inner: Box<dyn Node>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            inner,
+        }
+    }
+}
+
+impl Node for ConditionalPersistNode {
+    fn node_type(&self) -> NodeType {
+        NodeType::Simple
+    }
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> Option<&str> {
+        Some("条件持久化 - 仅当should_persist为true时将MessageEvent存储到MySQL数据库")
+    }
+
+    node_input![
+        port! { name = "message_event", ty = MessageEvent, desc = "消息事件" },
+        port! { name = "mysql_ref", ty = MySqlRef, desc = "MySQL连接配置引用" },
+        port! { name = "should_persist", ty = Boolean, desc = "是否持久化该消息" },
+    ];
+
+    node_output![
+        port! { name = "persisted", ty = Boolean, desc = "消息是否已持久化" },
+        port! { name = "message_event", ty = MessageEvent, desc = "传递输入的消息事件" },
+    ];
+
+    fn execute(&mut self, mut inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+        self.validate_inputs(&inputs)?;
+
+        let message_event = inputs.get("message_event").and_then(|v| match v {
+            DataValue::MessageEvent(e) => Some(e.clone()),
+            _ => None,
+        }).ok_or_else(|| crate::error::Error::InvalidNodeInput("message_event is required".to_string()))?;
+
+        let should_persist = matches!(inputs.remove("should_persist"), Some(DataValue::Boolean(true)));
+
+        let persisted = if should_persist {
+            let inner_outputs = self.inner.execute(inputs)?;
+            matches!(inner_outputs.get("success"), Some(DataValue::Boolean(true)))
+        } else {
+            false
+        };
+
+        let mut outputs = HashMap::new();
+        outputs.insert("persisted".to_string(), DataValue::Boolean(persisted));
+        outputs.insert("message_event".to_string(), DataValue::MessageEvent(message_event));
+
+        self.validate_outputs(&outputs)?;
+        Ok(outputs)
+    }
+}
+
 /// Message Cache Node - Caches MessageEvent in memory or optional Redis
 pub struct MessageCacheNode {
     id: String,
@@ -153,3 +236,392 @@ impl Node for MessageCacheNode {
         Ok(outputs)
     }
 }
+
+/// Conversation key of a message: the group for group chats, the sender for private chats
+pub fn conversation_key(event: &MessageEvent) -> String {
+    match event.group_id {
+        Some(group_id) if event.is_group_message => format!("group:{}", group_id),
+        _ => format!("private:{}", event.sender.user_id),
+    }
+}
+
+const DEFAULT_SUMMARY_THRESHOLD: i64 = 20;
+
+/// Turn Counter Node - Counts messages per conversation and signals when a summary is due
+pub struct TurnCounterNode {
+    id: String,
+    name: String,
+    graph_path: Option<PathBuf>,
+}
+
+impl TurnCounterNode {
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            graph_path: None,
+        }
+    }
+
+    /// Count one turn for `conversation`, returning `(turns, should_summarize)`.
+    /// The counter restarts from zero once the threshold is reached.
+    fn count_turn(&self, conversation: &str, threshold: i64) -> (i64, bool) {
+        let turns = get_node_state(self.graph_path.as_deref(), &self.id, conversation)
+            .and_then(|v| v.as_i64())
+            .unwrap_or(0)
+            + 1;
+        let should_summarize = turns >= threshold;
+        let stored = if should_summarize { 0 } else { turns };
+        set_persisted_node_state(self.graph_path.as_deref(), &self.id, conversation, serde_json::json!(stored));
+        (turns, should_summarize)
+    }
+}
+
+impl Node for TurnCounterNode {
+    fn node_type(&self) -> NodeType {
+        NodeType::Simple
+    }
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> Option<&str> {
+        Some("对话轮数统计 - 按会话（群/私聊）统计消息轮数，达到阈值时提示进行总结并重新计数")
+    }
+
+    node_input![
+        port! { name = "message_event", ty = MessageEvent, desc = "消息事件" },
+        port! { name = "threshold", ty = Integer, desc = "触发总结的轮数阈值 (默认: 20)", optional },
+    ];
+
+    node_output![
+        port! { name = "turns", ty = Integer, desc = "当前会话累计轮数" },
+        port! { name = "should_summarize", ty = Boolean, desc = "是否达到阈值需要总结" },
+    ];
+
+    fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+        self.validate_inputs(&inputs)?;
+
+        let message_event = inputs.get("message_event").and_then(|v| match v {
+            DataValue::MessageEvent(e) => Some(e),
+            _ => None,
+        }).ok_or_else(|| crate::error::Error::InvalidNodeInput("message_event is required".to_string()))?;
+
+        let threshold = match inputs.get("threshold") {
+            Some(DataValue::Integer(i)) => (*i).max(1),
+            _ => DEFAULT_SUMMARY_THRESHOLD,
+        };
+
+        let (turns, should_summarize) = self.count_turn(&conversation_key(message_event), threshold);
+
+        let mut outputs = HashMap::new();
+        outputs.insert("turns".to_string(), DataValue::Integer(turns));
+        outputs.insert("should_summarize".to_string(), DataValue::Boolean(should_summarize));
+
+        self.validate_outputs(&outputs)?;
+        Ok(outputs)
+    }
+    fn set_graph_path(&mut self, graph_path: Option<&Path>) {
+        self.graph_path = graph_path.map(Path::to_path_buf);
+    }
+}
+
+const DEFAULT_FLOOD_THRESHOLD: i64 = 3;
+const DEFAULT_FLOOD_WINDOW_SECS: i64 = 60;
+const DEFAULT_FLOOD_SIMILARITY: f64 = 0.9;
+/// Only the start of each message is remembered and compared
+const FLOOD_SIGNATURE_CHARS: usize = 200;
+/// Upper bound on remembered messages per user
+const FLOOD_HISTORY_LIMIT: usize = 50;
+
+/// Signature of a message: whitespace-collapsed, lowercased and truncated content
+fn flood_signature(content: &str) -> String {
+    content
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+        .chars()
+        .take(FLOOD_SIGNATURE_CHARS)
+        .collect()
+}
+
+/// Flood Detect Node - flags users who repeat the same (or nearly the same) message
+pub struct FloodDetectNode {
+    id: String,
+    name: String,
+    graph_path: Option<PathBuf>,
+}
+
+impl FloodDetectNode {
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            graph_path: None,
+        }
+    }
+
+    /// Record a message from `user_id` at `now_ms` and return how many messages in the
+    /// window, including this one, match it
+    fn record(&self, user_id: i64, content: &str, now_ms: i64, window_ms: i64, min_similarity: f64) -> usize {
+        let key = format!("user:{}", user_id);
+        let signature = flood_signature(content);
+
+        let mut history: Vec<(i64, String)> = get_node_state(self.graph_path.as_deref(), &self.id, &key)
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or_default();
+        history.retain(|(at, _)| now_ms - *at <= window_ms);
+
+        let repeats = 1 + history
+            .iter()
+            .filter(|(_, previous)| text_similarity(previous, &signature) >= min_similarity)
+            .count();
+
+        history.push((now_ms, signature));
+        if history.len() > FLOOD_HISTORY_LIMIT {
+            history.drain(..history.len() - FLOOD_HISTORY_LIMIT);
+        }
+        set_node_state(self.graph_path.as_deref(), &self.id, &key, serde_json::json!(history));
+        repeats
+    }
+}
+
+impl Node for FloodDetectNode {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> Option<&str> {
+        Some("刷屏检测 - 按用户记录时间窗口内的消息，相同或相近内容重复超过阈值时判定为刷屏")
+    }
+
+    node_input![
+        port! { name = "user_id", ty = Integer, desc = "发送者QQ号" },
+        port! { name = "content", ty = String, desc = "消息内容" },
+        port! { name = "threshold", ty = Integer, desc = "允许的重复次数，超过即为刷屏", optional, default = DataValue::Integer(DEFAULT_FLOOD_THRESHOLD) },
+        port! { name = "window_secs", ty = Integer, desc = "统计时间窗口秒数", optional, default = DataValue::Integer(DEFAULT_FLOOD_WINDOW_SECS) },
+        port! { name = "similarity", ty = Float, desc = "视为相同消息的相似度 0-1", optional, default = DataValue::Float(DEFAULT_FLOOD_SIMILARITY) },
+    ];
+
+    node_output![
+        port! { name = "is_flooding", ty = Boolean, desc = "是否判定为刷屏" },
+        port! { name = "repeat_count", ty = Integer, desc = "窗口内相同消息的次数（含本条）" },
+    ];
+
+    fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+        self.validate_inputs(&inputs)?;
+
+        let user_id = match inputs.get("user_id") {
+            Some(DataValue::Integer(id)) => *id,
+            _ => return Err(crate::error::Error::InvalidNodeInput("user_id is required".to_string())),
+        };
+        let content = match inputs.get("content") {
+            Some(DataValue::String(s)) => s.clone(),
+            _ => return Err(crate::error::Error::InvalidNodeInput("content is required".to_string())),
+        };
+        let threshold = match inputs.get("threshold") {
+            Some(DataValue::Integer(i)) => (*i).max(1),
+            _ => DEFAULT_FLOOD_THRESHOLD,
+        };
+        let window_secs = match inputs.get("window_secs") {
+            Some(DataValue::Integer(i)) => (*i).max(1),
+            _ => DEFAULT_FLOOD_WINDOW_SECS,
+        };
+        let similarity = match inputs.get("similarity") {
+            Some(DataValue::Float(f)) => f.clamp(0.0, 1.0),
+            _ => DEFAULT_FLOOD_SIMILARITY,
+        };
+
+        let now_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as i64)
+            .unwrap_or(0);
+        let repeats = self.record(user_id, &content, now_ms, window_secs * 1000, similarity);
+
+        let mut outputs = HashMap::new();
+        outputs.insert("is_flooding".to_string(), DataValue::Boolean(repeats as i64 > threshold));
+        outputs.insert("repeat_count".to_string(), DataValue::Integer(repeats as i64));
+
+        self.validate_outputs(&outputs)?;
+        Ok(outputs)
+    }
+    fn set_graph_path(&mut self, graph_path: Option<&Path>) {
+        self.graph_path = graph_path.map(Path::to_path_buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bot_adapter::models::{MessageType, Sender};
+    use crate::node::data_value::MySqlConfig;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Stands in for the MySQL persistence node and counts writes
+    struct CountingPersistNode {
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl Node for CountingPersistNode {
+        fn id(&self) -> &str {
+            "counting_persist"
+        }
+
+        fn name(&self) -> &str {
+            "CountingPersist"
+        }
+
+        node_input![];
+
+        node_output![
+            port! { name = "success", ty = Boolean, desc = "success" },
+        ];
+
+        fn execute(&mut self, _inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(HashMap::from([("success".to_string(), DataValue::Boolean(true))]))
+        }
+    }
+
+    fn group_event(group_id: i64) -> MessageEvent {
+        MessageEvent {
+            message_id: 1,
+            message_type: MessageType::Group,
+            sender: Sender {
+                user_id: 10,
+                nickname: "tester".to_string(),
+                card: String::new(),
+                role: None,
+            },
+            message_list: Vec::new(),
+            group_id: Some(group_id),
+            group_name: None,
+            is_group_message: true,
+        }
+    }
+
+    fn inputs(should_persist: bool) -> HashMap<String, DataValue> {
+        HashMap::from([
+            ("message_event".to_string(), DataValue::MessageEvent(group_event(100))),
+            (
+                "mysql_ref".to_string(),
+                DataValue::MySqlRef(Arc::new(MySqlConfig {
+                    url: Some("mysql://localhost/test".to_string()),
+                    reconnect_max_attempts: None,
+                    reconnect_interval_secs: None,
+                })),
+            ),
+            ("should_persist".to_string(), DataValue::Boolean(should_persist)),
+        ])
+    }
+
+    #[test]
+    fn test_conditional_persist_skips_store_when_false() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let inner = Box::new(CountingPersistNode { calls: calls.clone() });
+        let mut node = ConditionalPersistNode::with_inner("conditional_persist", "ConditionalPersist", inner);
+
+        let outputs = node.execute(inputs(false)).unwrap();
+        assert!(matches!(outputs.get("persisted"), Some(DataValue::Boolean(false))));
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        let outputs = node.execute(inputs(true)).unwrap();
+        assert!(matches!(outputs.get("persisted"), Some(DataValue::Boolean(true))));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    fn count(node: &mut TurnCounterNode, group_id: i64) -> (i64, bool) {
+        let inputs = HashMap::from([
+            ("message_event".to_string(), DataValue::MessageEvent(group_event(group_id))),
+            ("threshold".to_string(), DataValue::Integer(3)),
+        ]);
+        let outputs = node.execute(inputs).unwrap();
+        match (outputs.get("turns"), outputs.get("should_summarize")) {
+            (Some(DataValue::Integer(turns)), Some(DataValue::Boolean(flag))) => (*turns, *flag),
+            _ => panic!("unexpected outputs"),
+        }
+    }
+
+    #[test]
+    fn test_turn_counter_threshold_and_reset() {
+        let mut node = TurnCounterNode::new("turn_counter_test", "TurnCounter");
+
+        assert_eq!(count(&mut node, 1), (1, false));
+        assert_eq!(count(&mut node, 1), (2, false));
+        assert_eq!(count(&mut node, 1), (3, true));
+        // Counter restarts after signalling
+        assert_eq!(count(&mut node, 1), (1, false));
+
+        // Other conversations are counted separately
+        assert_eq!(count(&mut node, 2), (1, false));
+    }
+
+    #[test]
+    fn test_turn_counter_is_restored_from_state_file() {
+        use crate::node::state_store::state_file_path;
+
+        let dir = std::env::temp_dir().join(format!("zihuan_turn_counter_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let graph = dir.join("bot.json");
+        let mut node = TurnCounterNode::new("turns", "TurnCounter");
+        node.set_graph_path(Some(&graph));
+        assert_eq!(count(&mut node, 1), (1, false));
+        assert_eq!(count(&mut node, 1), (2, false));
+
+        // A graph this process has not loaded yet reads the saved file, like one loaded after a restart
+        let restarted = dir.join("restarted.json");
+        std::fs::copy(state_file_path(&graph), state_file_path(&restarted)).unwrap();
+        let mut node = TurnCounterNode::new("turns", "TurnCounter");
+        node.set_graph_path(Some(&restarted));
+        assert_eq!(count(&mut node, 1), (3, true));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    fn flood(node: &mut FloodDetectNode, user_id: i64, content: &str) -> bool {
+        let inputs = HashMap::from([
+            ("user_id".to_string(), DataValue::Integer(user_id)),
+            ("content".to_string(), DataValue::String(content.to_string())),
+            ("threshold".to_string(), DataValue::Integer(2)),
+        ]);
+        match node.execute(inputs).unwrap().get("is_flooding") {
+            Some(DataValue::Boolean(flag)) => *flag,
+            _ => panic!("is_flooding should be a Boolean"),
+        }
+    }
+
+    #[test]
+    fn test_flood_detect_identical_messages() {
+        let mut node = FloodDetectNode::new("flood_detect_identical", "FloodDetect");
+        assert!(!flood(&mut node, 1, "buy now!!!"));
+        assert!(!flood(&mut node, 1, "Buy  now!!!"));
+        assert!(flood(&mut node, 1, "buy now!!"));
+        // Other users are tracked separately
+        assert!(!flood(&mut node, 2, "buy now!!!"));
+    }
+
+    #[test]
+    fn test_flood_detect_distinct_messages_do_not_trigger() {
+        let mut node = FloodDetectNode::new("flood_detect_distinct", "FloodDetect");
+        for content in ["hello", "how is everyone", "anyone up for lunch?", "see you"] {
+            assert!(!flood(&mut node, 1, content));
+        }
+    }
+
+    #[test]
+    fn test_flood_window_expires_old_messages() {
+        let node = FloodDetectNode::new("flood_detect_window", "FloodDetect");
+        assert_eq!(node.record(1, "spam", 0, 1000, 1.0), 1);
+        assert_eq!(node.record(1, "spam", 500, 1000, 1.0), 2);
+        assert_eq!(node.record(1, "spam", 2000, 1000, 1.0), 1);
+    }
+}
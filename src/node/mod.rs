@@ -2,6 +2,13 @@ use serde_json::{json, Value};
 use std::sync::{Arc, atomic::{AtomicBool, Ordering}};
 use log::info;
 
+/// Side of a graph's external interface a node stands for, see `NodeGraph::execute_with_params`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphInterface {
+    Input,
+    Output,
+}
+
 /// NodeType enum for distinguishing node categories
 #[derive(Debug, Clone, Serialize, PartialEq, Eq)]
 pub enum NodeType {
@@ -9,12 +16,26 @@ pub enum NodeType {
     EventProducer,
 }
 
+/// When the execution callback is called for a node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecutionPhase {
+    /// The node is halted at a breakpoint with its inputs prepared; outputs are empty
+    AboutToExecute,
+    Executed,
+}
+
+type ExecutionCallback =
+    dyn Fn(ExecutionPhase, &str, &HashMap<String, DataValue>, &HashMap<String, DataValue>) + Send + Sync;
 
 #[derive(Debug, Clone)]
 pub struct ExecutionResult {
     pub node_results: HashMap<String, HashMap<String, DataValue>>,
     pub error_node_id: Option<String>,
     pub error_message: Option<String>,
+    /// Wall time of the whole run, set by `execute_and_capture_results`
+    pub duration: Option<std::time::Duration>,
+    /// Time spent inside each node's `execute`, excluding input collection
+    pub timings: HashMap<String, std::time::Duration>,
 }
 
 impl ExecutionResult {
@@ -23,6 +44,8 @@ impl ExecutionResult {
             node_results,
             error_node_id: None,
             error_message: None,
+            duration: None,
+            timings: HashMap::new(),
         }
     }
 
@@ -35,16 +58,118 @@ impl ExecutionResult {
             node_results,
             error_node_id: Some(error_node_id),
             error_message: Some(error_message),
+            duration: None,
+            timings: HashMap::new(),
+        }
+    }
+
+    /// Sum of the per-node execution times
+    pub fn total_duration(&self) -> std::time::Duration {
+        self.timings.values().sum()
+    }
+
+    /// Consolidate the run into one JSON object for logging or posting to a webhook.
+    /// Password values, connection URLs and secret-looking ports are redacted.
+    pub fn run_report(&self) -> Value {
+        let mut nodes = serde_json::Map::new();
+        for (node_id, results) in &self.node_results {
+            let ports: serde_json::Map<String, Value> = results
+                .iter()
+                .map(|(port, value)| (port.clone(), redacted_value(port, value)))
+                .collect();
+            nodes.insert(node_id.clone(), Value::Object(ports));
+        }
+
+        json!({
+            "success": self.error_message.is_none(),
+            "duration_ms": self.duration.map(|d| d.as_millis() as u64),
+            "error": self.error_message.as_ref().map(|message| json!({
+                "node_id": self.error_node_id,
+                "message": message,
+            })),
+            "nodes": nodes,
+        })
+    }
+}
+
+const REDACTED: &str = "***";
+
+/// How often a paused event producer checks whether it was resumed or stopped
+const PAUSE_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(50);
+
+fn is_secret_port(port: &str) -> bool {
+    let port = port.to_ascii_lowercase();
+    ["password", "token", "secret", "api_key", "apikey"]
+        .iter()
+        .any(|needle| port.contains(needle))
+}
+
+fn redacted_value(port: &str, value: &DataValue) -> Value {
+    match value {
+        DataValue::Password(_) => Value::String(REDACTED.to_string()),
+        DataValue::String(_) if is_secret_port(port) => Value::String(REDACTED.to_string()),
+        // Connection URLs usually embed credentials
+        DataValue::RedisRef(_) | DataValue::MySqlRef(_) => {
+            let mut json = value.to_json();
+            if let Some(url) = json.get_mut("url").filter(|url| !url.is_null()) {
+                *url = Value::String(REDACTED.to_string());
+            }
+            json
+        }
+        // Elements share the port's name, map values are judged by their own key
+        DataValue::List(items) | DataValue::Tuple(items) => {
+            Value::Array(items.iter().map(|item| redacted_value(port, item)).collect())
+        }
+        DataValue::Map(entries) => Value::Object(
+            entries
+                .iter()
+                .map(|(key, value)| {
+                    let value = if is_secret_port(key) {
+                        Value::String(REDACTED.to_string())
+                    } else {
+                        redacted_value(key, value)
+                    };
+                    (key.clone(), value)
+                })
+                .collect(),
+        ),
+        DataValue::Json(json) => {
+            let mut json = json.clone();
+            redact_json(&mut json);
+            json
         }
+        other => other.to_json(),
+    }
+}
+
+/// Redact secret-looking keys at any depth of a JSON value
+fn redact_json(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                if is_secret_port(key) && !field.is_null() {
+                    *field = Value::String(REDACTED.to_string());
+                } else {
+                    redact_json(field);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_json),
+        _ => {}
     }
 }
 
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use crate::error::Result;
 
 type OutputPool = HashMap<String, HashMap<String, DataValue>>;
-type InputSourceMap = HashMap<String, HashMap<String, (String, String)>>;
+/// Node id -> ids of the nodes on one side of its dependencies
+type DependencyMap = HashMap<String, Vec<String>>;
+/// Node id -> input port -> upstream `(node_id, port, condition)` sources.
+/// Only `List(T)` ports fed by `T` outputs have more than one source.
+type InputSourceMap = HashMap<String, HashMap<String, Vec<(String, String, Option<EdgeCondition>)>>>;
 
 pub mod data_value;
 pub mod util_nodes;
@@ -52,30 +177,92 @@ pub mod graph_io;
 pub mod registry;
 pub mod database_nodes;
 pub mod message_nodes;
+pub mod state_store;
+pub mod runtime_nodes;
+pub mod text_nodes;
+pub mod file_nodes;
+pub mod image_nodes;
+pub mod http_nodes;
+pub mod subgraph_nodes;
+pub mod graph_interface_nodes;
+pub mod stepping;
 
 #[allow(unused_imports)]
 pub use data_value::{DataType, DataValue};
 #[allow(unused_imports)]
-pub use node_macros::{node_input, node_output};
+pub use node_macros::{node_input, node_output, node_ports};
 #[allow(unused_imports)]
 pub use graph_io::{
     NodeGraphDefinition,
     NodeDefinition,
+    EdgeCondition,
     EdgeDefinition,
     GraphPosition,
     load_graph_definition_from_json,
+    load_graph_definition_from_json_lenient,
     save_graph_definition_to_json,
     ensure_positions,
 };
+#[allow(unused_imports)]
+pub use stepping::{BreakpointControl, GraphStepper, StepRecord};
+use stepping::StepSession;
 
 /// Node input/output ports
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "PortDefinition")]
 pub struct Port {
     pub name: String,
     pub data_type: DataType,
     pub description: Option<String>,
     /// Whether this port is required, only for input ports
     pub required: bool,
+    /// Used when neither an edge nor an inline value feeds this input port
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<DataValue>,
+    /// The only values a String input port accepts, shown as a dropdown in the UI
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed: Option<Vec<String>>,
+    /// Inclusive lower bound for Integer and Float input ports
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    /// Inclusive upper bound for Integer and Float input ports
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+}
+
+/// Serialized form of `Port`; the default is decoded against the port's type
+#[derive(Deserialize)]
+struct PortDefinition {
+    name: String,
+    data_type: DataType,
+    description: Option<String>,
+    required: bool,
+    #[serde(default)]
+    default: Option<Value>,
+    #[serde(default)]
+    allowed: Option<Vec<String>>,
+    #[serde(default)]
+    min: Option<f64>,
+    #[serde(default)]
+    max: Option<f64>,
+}
+
+impl From<PortDefinition> for Port {
+    fn from(definition: PortDefinition) -> Self {
+        let default = definition
+            .default
+            .and_then(|value| registry::json_to_data_value(&value, &definition.data_type));
+        Self {
+            name: definition.name,
+            data_type: definition.data_type,
+            description: definition.description,
+            required: definition.required,
+            default,
+            allowed: definition.allowed,
+            min: definition.min,
+            max: definition.max,
+        }
+    }
 }
 
 impl Port {
@@ -85,6 +272,10 @@ impl Port {
             data_type,
             description: None,
             required: true,
+            default: None,
+            allowed: None,
+            min: None,
+            max: None,
         }
     }
 
@@ -102,6 +293,53 @@ impl Port {
         self.required = false;
         self
     }
+
+    pub fn with_default(mut self, value: DataValue) -> Self {
+        self.default = Some(value);
+        self
+    }
+
+    pub fn with_choices<I, S>(mut self, choices: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.allowed = Some(choices.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn with_min(mut self, min: f64) -> Self {
+        self.min = Some(min);
+        self
+    }
+
+    pub fn with_max(mut self, max: f64) -> Self {
+        self.max = Some(max);
+        self
+    }
+
+    /// Check a value against this port's type, allowed choices and numeric bounds
+    pub fn check_value(&self, value: &DataValue) -> std::result::Result<(), String> {
+        value.check_type(&self.data_type)?;
+        if let (Some(allowed), DataValue::String(s)) = (&self.allowed, value) {
+            if !allowed.contains(s) {
+                return Err(format!("expects one of [{}], got '{}'", allowed.join(", "), s));
+            }
+        }
+
+        let number = match value {
+            DataValue::Integer(i) => *i as f64,
+            DataValue::Float(f) => *f,
+            _ => return Ok(()),
+        };
+        if let Some(min) = self.min.filter(|min| number < *min) {
+            return Err(format!("must be >= {}, got {}", min, value));
+        }
+        if let Some(max) = self.max.filter(|max| number > *max) {
+            return Err(format!("must be <= {}, got {}", max, value));
+        }
+        Ok(())
+    }
 }
 
 /// Node trait
@@ -144,6 +382,28 @@ pub trait Node: Send + Sync {
         Ok(())
     }
 
+    /// Event producer lifecycle: outputs already known when `on_start` returns, before
+    /// the first event, e.g. the bot adapter handle
+    fn start_outputs(&self) -> HashMap<String, DataValue> {
+        HashMap::new()
+    }
+
+    /// Called when an upstream event producer has started, before its first event, with
+    /// inputs filled from the producer's `start_outputs`. Only nodes whose required inputs
+    /// are all available get the call. Lets a node set up work that must not wait for the
+    /// first event, such as an adapter handler.
+    fn on_graph_start(&mut self, _inputs: HashMap<String, DataValue>) -> Result<()> {
+        Ok(())
+    }
+
+    /// Receives the owning graph's stop flag when the node is added, so long-running
+    /// nodes can cancel early. Nodes that never block can ignore it.
+    fn set_stop_flag(&mut self, _stop_flag: Arc<AtomicBool>) {}
+
+    /// Receives the file the owning graph was loaded from, `None` for an unsaved graph.
+    /// Nodes keeping values in `state_store` scope them by it.
+    fn set_graph_path(&mut self, _graph_path: Option<&Path>) {}
+
     fn to_json(&self) -> Value {
         json!({
             "id": self.id(),
@@ -155,6 +415,17 @@ pub trait Node: Send + Sync {
         })
     }
 
+    /// Whether the outputs depend only on the inputs, with no side effects.
+    /// The graph may then skip `execute` and reuse earlier outputs for identical inputs.
+    fn is_pure(&self) -> bool {
+        false
+    }
+
+    /// Whether the node is a parameter or result of the graph, see `graph_interface_nodes`
+    fn graph_interface(&self) -> Option<GraphInterface> {
+        None
+    }
+
     fn validate_inputs(&self, inputs: &HashMap<String, DataValue>) -> Result<()> {
         let input_ports = self.input_ports();
         
@@ -162,12 +433,10 @@ pub trait Node: Send + Sync {
             match inputs.get(&port.name) {
                 Some(value) => {
                     // Validate data type
-                    if value.data_type() != port.data_type {
+                    if let Err(reason) = port.check_value(value) {
                         return Err(crate::error::Error::ValidationError(format!(
-                            "Input port '{}' expects type {}, got {}",
-                            port.name,
-                            port.data_type,
-                            value.data_type()
+                            "Input port '{}' {}",
+                            port.name, reason
                         )));
                     }
                 }
@@ -190,12 +459,10 @@ pub trait Node: Send + Sync {
         
         for port in &output_ports {
             if let Some(value) = outputs.get(&port.name) {
-                if value.data_type() != port.data_type {
+                if let Err(reason) = value.check_type(&port.data_type) {
                     return Err(crate::error::Error::ValidationError(format!(
-                        "Output port '{}' expects type {}, got {}",
-                        port.name,
-                        port.data_type,
-                        value.data_type()
+                        "Output port '{}' {}",
+                        port.name, reason
                     )));
                 }
             }
@@ -205,37 +472,195 @@ pub trait Node: Send + Sync {
     }
 }
 
+/// Hash of a node's inputs, or `None` if an input has no stable content to hash
+/// (shared handles such as adapter or database references)
+fn hash_inputs(inputs: &HashMap<String, DataValue>) -> Option<u64> {
+    use std::hash::{Hash, Hasher};
+
+    let mut entries: Vec<(&String, &DataValue)> = inputs.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    for (name, value) in entries {
+        match value {
+            DataValue::BotAdapterRef(_)
+            | DataValue::RedisRef(_)
+            | DataValue::MySqlRef(_)
+            | DataValue::FunctionTools(_) => return None,
+            _ => {}
+        }
+        name.hash(&mut hasher);
+        value.data_type().to_string().hash(&mut hasher);
+        value.to_json().to_string().hash(&mut hasher);
+    }
+    Some(hasher.finish())
+}
+
+/// Last outputs of pure nodes, keyed by node id and the hash of the inputs they were computed from
+#[derive(Default)]
+struct PureNodeCache {
+    enabled: bool,
+    entries: HashMap<String, (u64, HashMap<String, DataValue>)>,
+}
+
+impl PureNodeCache {
+    fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Run `node`, or return its cached outputs if it is pure and saw the same inputs last time
+    fn execute(
+        &mut self,
+        node_id: &str,
+        node: &mut dyn Node,
+        inputs: HashMap<String, DataValue>,
+    ) -> Result<HashMap<String, DataValue>> {
+        let key = if self.enabled && node.is_pure() { hash_inputs(&inputs) } else { None };
+        let Some(key) = key else {
+            return node.execute(inputs);
+        };
+
+        if let Some((cached_key, outputs)) = self.entries.get(node_id) {
+            if *cached_key == key {
+                return Ok(outputs.clone());
+            }
+        }
+        let outputs = node.execute(inputs)?;
+        self.entries.insert(node_id.to_string(), (key, outputs.clone()));
+        Ok(outputs)
+    }
+}
+
+/// Runs single nodes for the graph: through the pure node cache, halting at breakpoints
+/// and, in step mode, only when the stepper allows it
+struct NodeRunner {
+    cache: PureNodeCache,
+    stepper: Option<GraphStepper>,
+    breakpoints: HashSet<String>,
+    breakpoint_control: BreakpointControl,
+    execution_callback: Option<Box<ExecutionCallback>>,
+}
+
+impl NodeRunner {
+    fn new(stop_flag: Arc<AtomicBool>) -> Self {
+        Self {
+            cache: PureNodeCache::default(),
+            stepper: None,
+            breakpoints: HashSet::new(),
+            breakpoint_control: BreakpointControl::new(stop_flag),
+            execution_callback: None,
+        }
+    }
+
+    fn execute(
+        &mut self,
+        node_id: &str,
+        node: &mut dyn Node,
+        inputs: HashMap<String, DataValue>,
+    ) -> Result<HashMap<String, DataValue>> {
+        if self.breakpoints.contains(node_id) {
+            if let Some(cb) = &self.execution_callback {
+                cb(ExecutionPhase::AboutToExecute, node_id, &inputs, &HashMap::new());
+            }
+            if !self.breakpoint_control.halt(node_id) {
+                return Err(Self::stopped_before(node_id));
+            }
+        }
+        let Some(stepper) = &self.stepper else {
+            return self.cache.execute(node_id, node, inputs);
+        };
+        if !stepper.wait_for_step() {
+            return Err(Self::stopped_before(node_id));
+        }
+        let recorded_inputs = inputs.clone();
+        let outputs = self.cache.execute(node_id, node, inputs)?;
+        stepper.record(StepRecord {
+            node_id: node_id.to_string(),
+            inputs: recorded_inputs,
+            outputs: outputs.clone(),
+        });
+        Ok(outputs)
+    }
+
+    fn stopped_before(node_id: &str) -> crate::error::Error {
+        crate::error::Error::ValidationError(format!("Execution stopped before node '{}'", node_id))
+    }
+}
+
 /// NodeGraph manages multiple nodes
 pub struct NodeGraph {
     pub nodes: HashMap<String, Box<dyn Node>>,
     pub inline_values: HashMap<String, HashMap<String, DataValue>>,
     stop_flag: Arc<AtomicBool>,
-    execution_callback: Option<Box<dyn Fn(&str, &HashMap<String, DataValue>, &HashMap<String, DataValue>) + Send + Sync>>,
+    pause_flag: Arc<AtomicBool>,
     edges: Vec<EdgeDefinition>,
+    node_runner: NodeRunner,
+    event_producer_max_iterations: Option<u64>,
+    auto_coerce: bool,
+    graph_path: Option<PathBuf>,
 }
 
 impl NodeGraph {
     pub fn new() -> Self {
+        let stop_flag = Arc::new(AtomicBool::new(false));
         Self {
             nodes: HashMap::new(),
             inline_values: HashMap::new(),
-            stop_flag: Arc::new(AtomicBool::new(false)),
-            execution_callback: None,
+            node_runner: NodeRunner::new(Arc::clone(&stop_flag)),
+            stop_flag,
+            pause_flag: Arc::new(AtomicBool::new(false)),
             edges: Vec::new(),
+            event_producer_max_iterations: None,
+            auto_coerce: false,
+            graph_path: None,
         }
     }
 
     pub fn set_execution_callback<F>(&mut self, callback: F)
     where
-        F: Fn(&str, &HashMap<String, DataValue>, &HashMap<String, DataValue>) + Send + Sync + 'static,
+        F: Fn(ExecutionPhase, &str, &HashMap<String, DataValue>, &HashMap<String, DataValue>) + Send + Sync + 'static,
     {
-        self.execution_callback = Some(Box::new(callback));
+        self.node_runner.execution_callback = Some(Box::new(callback));
     }
 
     pub fn set_edges(&mut self, edges: Vec<EdgeDefinition>) {
         self.edges = edges;
     }
 
+    /// Reuse the last outputs of pure nodes whose inputs did not change. Off by default.
+    pub fn set_caching_enabled(&mut self, enabled: bool) {
+        self.node_runner.cache.enabled = enabled;
+        if !enabled {
+            self.node_runner.cache.clear();
+        }
+    }
+
+    /// Drop all memoized node outputs
+    pub fn clear_cache(&mut self) {
+        self.node_runner.cache.clear();
+    }
+
+    /// Let edges connect ports whose types `DataValue::coerce_to` can convert between,
+    /// e.g. Integer to Float. Off by default, so edge types must match exactly.
+    pub fn set_auto_coerce(&mut self, enabled: bool) {
+        self.auto_coerce = enabled;
+    }
+
+    /// Stop each event producer after this many `on_update` cycles. `None` runs
+    /// until the stop flag is set or the producer runs out of events.
+    pub fn set_event_producer_max_iterations(&mut self, max_iterations: Option<u64>) {
+        self.event_producer_max_iterations = max_iterations;
+    }
+
+    /// Record the file the graph was loaded from and hand it to every node, so node
+    /// state is kept per graph and can be persisted next to it
+    pub fn set_graph_path(&mut self, graph_path: Option<PathBuf>) {
+        for node in self.nodes.values_mut() {
+            node.set_graph_path(graph_path.as_deref());
+        }
+        self.graph_path = graph_path;
+    }
+
     pub fn get_stop_flag(&self) -> Arc<AtomicBool> {
         Arc::clone(&self.stop_flag)
     }
@@ -248,7 +673,60 @@ impl NodeGraph {
         self.stop_flag.store(false, Ordering::Relaxed);
     }
 
-    pub fn add_node(&mut self, node: Box<dyn Node>) -> Result<()> {
+    /// In step mode the graph waits before each node until `GraphStepper::step` is called
+    /// from another thread. Off by default.
+    pub fn set_step_mode(&mut self, enabled: bool) {
+        self.node_runner.stepper = enabled.then(|| GraphStepper::new(Arc::clone(&self.stop_flag)));
+    }
+
+    /// Stepper of a graph in step mode
+    pub fn get_stepper(&self) -> Option<GraphStepper> {
+        self.node_runner.stepper.clone()
+    }
+
+    /// Halt before running `node_id`, after passing its inputs to the execution callback
+    /// as `ExecutionPhase::AboutToExecute`, until `BreakpointControl::continue_execution`
+    pub fn add_breakpoint(&mut self, node_id: &str) {
+        self.node_runner.breakpoints.insert(node_id.to_string());
+    }
+
+    pub fn remove_breakpoint(&mut self, node_id: &str) {
+        self.node_runner.breakpoints.remove(node_id);
+    }
+
+    pub fn get_breakpoint_control(&self) -> BreakpointControl {
+        self.node_runner.breakpoint_control.clone()
+    }
+
+    pub fn get_pause_flag(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.pause_flag)
+    }
+
+    /// Hold event producers before their next `on_update` until `resume`. Unlike a stop,
+    /// nothing is cleaned up, so producers keep their connections and queued events.
+    pub fn request_pause(&self) {
+        self.pause_flag.store(true, Ordering::Relaxed);
+    }
+
+    pub fn resume(&self) {
+        self.pause_flag.store(false, Ordering::Relaxed);
+    }
+
+    /// Block while paused, returning early if a stop is requested meanwhile
+    fn wait_while_paused(&self, node_id: &str) {
+        if !self.pause_flag.load(Ordering::Relaxed) {
+            return;
+        }
+        info!("Event producer '{}' paused", node_id);
+        while self.pause_flag.load(Ordering::Relaxed) && !self.stop_flag.load(Ordering::Relaxed) {
+            std::thread::sleep(PAUSE_POLL_INTERVAL);
+        }
+        if !self.stop_flag.load(Ordering::Relaxed) {
+            info!("Event producer '{}' resumed", node_id);
+        }
+    }
+
+    pub fn add_node(&mut self, mut node: Box<dyn Node>) -> Result<()> {
         let id = node.id().to_string();
         if self.nodes.contains_key(&id) {
             return Err(crate::error::Error::ValidationError(format!(
@@ -256,91 +734,24 @@ impl NodeGraph {
                 id
             )));
         }
+        node.set_stop_flag(Arc::clone(&self.stop_flag));
+        node.set_graph_path(self.graph_path.as_deref());
         self.nodes.insert(id, node);
         Ok(())
     }
 
     pub fn execute(&mut self) -> Result<()> {
+        let _step_session = self.node_runner.stepper.as_ref().map(StepSession::start);
+        self.execute_graph()
+    }
+
+    fn execute_graph(&mut self) -> Result<()> {
         if !self.edges.is_empty() {
             return self.execute_with_edges();
         }
 
-        let mut output_producers: HashMap<String, String> = HashMap::new();
-        for (node_id, node) in &self.nodes {
-            for port in node.output_ports() {
-                if let Some(existing) = output_producers.insert(port.name.clone(), node_id.clone()) {
-                    return Err(crate::error::Error::ValidationError(format!(
-                        "Output port '{}' is produced by both '{}' and '{}'",
-                        port.name, existing, node_id
-                    )));
-                }
-            }
-        }
-
-        let mut in_degree: HashMap<String, usize> = HashMap::new();
-        let mut dependents: HashMap<String, Vec<String>> = HashMap::new();
-        let mut dependencies: HashMap<String, Vec<String>> = HashMap::new();
-
-        for node_id in self.nodes.keys() {
-            in_degree.insert(node_id.clone(), 0);
-        }
-
-        for (node_id, node) in &self.nodes {
-            for port in node.input_ports() {
-                if let Some(producer) = output_producers.get(&port.name) {
-                    if producer != node_id {
-                        dependencies.entry(node_id.clone()).or_default().push(producer.clone());
-                        dependents.entry(producer.clone()).or_default().push(node_id.clone());
-                        if let Some(count) = in_degree.get_mut(node_id) {
-                            *count += 1;
-                        }
-                    }
-                } else if port.required {
-                    // Check if the port has an inline value
-                    let has_inline = self.inline_values
-                        .get(node_id)
-                        .map(|values| values.contains_key(&port.name))
-                        .unwrap_or(false);
-                    
-                    if !has_inline {
-                        return Err(crate::error::Error::ValidationError(format!(
-                            "Required input port '{}' for node '{}' is not bound",
-                            port.name, node_id
-                        )));
-                    }
-                }
-            }
-        }
-
-        let mut ready: Vec<String> = in_degree
-            .iter()
-            .filter_map(|(id, degree)| if *degree == 0 { Some(id.clone()) } else { None })
-            .collect();
-        ready.sort();
-
-        let mut ordered: Vec<String> = Vec::with_capacity(self.nodes.len());
-        while !ready.is_empty() {
-            let node_id = ready.remove(0);
-            ordered.push(node_id.clone());
-
-            if let Some(next_nodes) = dependents.get(&node_id) {
-                for next_id in next_nodes {
-                    if let Some(count) = in_degree.get_mut(next_id) {
-                        *count = count.saturating_sub(1);
-                        if *count == 0 {
-                            ready.push(next_id.clone());
-                        }
-                    }
-                }
-                ready.sort();
-            }
-        }
-
-        if ordered.len() != self.nodes.len() {
-            return Err(crate::error::Error::ValidationError(
-                "Cycle detected in node dependencies".to_string(),
-            ));
-        }
+        let (dependents, dependencies) = self.edgeless_dependencies()?;
+        let ordered = self.topological_order(&dependents, &dependencies)?;
 
         let event_producer_set: HashSet<String> = self
             .nodes
@@ -365,16 +776,16 @@ impl NodeGraph {
                 })?;
 
                 let inputs = Self::collect_inputs(node.as_ref(), &data_pool, &node_id, self.inline_values.get(&node_id))?;
-                let outputs = node.execute(inputs)?;
-                for (key, value) in outputs {
-                    if data_pool.contains_key(&key) {
-                        return Err(crate::error::Error::ValidationError(format!(
-                            "Output key '{}' from node '{}' conflicts with existing data",
-                            key, node_id
-                        )));
+                let inputs_clone = if self.node_runner.execution_callback.is_some() { Some(inputs.clone()) } else { None };
+                let outputs = self.node_runner.execute(&node_id, node.as_mut(), inputs)?;
+
+                if let Some(cb) = &self.node_runner.execution_callback {
+                    if let Some(inp) = inputs_clone {
+                        cb(ExecutionPhase::Executed, &node_id, &inp, &outputs);
                     }
-                    data_pool.insert(key, value);
                 }
+
+                Self::insert_pool_outputs(&mut data_pool, node.as_ref(), &node_id, outputs)?;
             }
 
             return Ok(());
@@ -415,16 +826,16 @@ impl NodeGraph {
             })?;
 
             let inputs = Self::collect_inputs(node.as_ref(), &base_data_pool, node_id, self.inline_values.get(node_id))?;
-            let outputs = node.execute(inputs)?;
-            for (key, value) in outputs {
-                if base_data_pool.contains_key(&key) {
-                    return Err(crate::error::Error::ValidationError(format!(
-                        "Output key '{}' from node '{}' conflicts with existing data",
-                        key, node_id
-                    )));
+            let inputs_clone = if self.node_runner.execution_callback.is_some() { Some(inputs.clone()) } else { None };
+            let outputs = self.node_runner.execute(node_id, node.as_mut(), inputs)?;
+
+            if let Some(cb) = &self.node_runner.execution_callback {
+                if let Some(inp) = inputs_clone {
+                    cb(ExecutionPhase::Executed, node_id, &inp, &outputs);
                 }
-                base_data_pool.insert(key, value);
             }
+
+            Self::insert_pool_outputs(&mut base_data_pool, node.as_ref(), node_id, outputs)?;
         }
 
         let mut event_producer_roots: Vec<String> = event_producer_set
@@ -455,124 +866,145 @@ impl NodeGraph {
     /// Execute the graph and capture results for each node
     pub fn execute_and_capture_results(&mut self) -> ExecutionResult {
         let mut node_results: HashMap<String, HashMap<String, DataValue>> = HashMap::new();
-        
+        let mut timings: HashMap<String, std::time::Duration> = HashMap::new();
+        let started = std::time::Instant::now();
+        let step_session = self.node_runner.stepper.as_ref().map(StepSession::start);
+
         // Try to execute, if error occurs, return early with error info
-        match self.execute_and_capture_results_internal(&mut node_results) {
+        let mut result = match self.execute_and_capture_results_internal(&mut node_results, &mut timings) {
             Ok(()) => ExecutionResult::success(node_results),
             Err(e) => {
-                // Extract node ID from error if possible
-                let error_msg = e.to_string();
-                let error_node_id = self.extract_error_node_id(&error_msg);
+                let error_node_id = Self::extract_error_node_id(&e);
                 ExecutionResult::with_error(
                     node_results,
                     error_node_id.unwrap_or_else(|| "unknown".to_string()),
-                    error_msg,
+                    e.to_string(),
                 )
             }
+        };
+        drop(step_session);
+        result.duration = Some(started.elapsed());
+        result.timings = timings;
+        result
+    }
+
+    /// Run the graph with every `GraphInputNode` fed the entry of `params` under its parameter
+    /// name, and return what the `GraphOutputNode`s recorded, under their parameter names.
+    /// Results of output nodes that were skipped are left out.
+    pub fn execute_with_params(&mut self, params: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+        use graph_interface_nodes::PARAM_VALUE_PORT;
+
+        let interface = self.interface_nodes()?;
+        let parameters = self.graph_parameters()?;
+        let mut supplied = Vec::new();
+        for (name, value) in &params {
+            let Some(data_type) = parameters.get(name) else {
+                return Err(crate::error::Error::ValidationError(format!("Unknown graph parameter '{}'", name)));
+            };
+            value.check_type(data_type).map_err(|e| {
+                crate::error::Error::ValidationError(format!("Graph parameter '{}': {}", name, e))
+            })?;
+            for (node_id, _, _) in interface
+                .iter()
+                .filter(|(_, kind, param_name)| *kind == GraphInterface::Input && param_name == name)
+            {
+                supplied.push((node_id.clone(), value.clone()));
+            }
+        }
+
+        // Supplied values stand in for inline ones only during this run
+        let mut replaced = Vec::new();
+        for (node_id, value) in supplied {
+            let previous = self
+                .inline_values
+                .entry(node_id.clone())
+                .or_default()
+                .insert(PARAM_VALUE_PORT.to_string(), value);
+            replaced.push((node_id, previous));
+        }
+        let result = self.execute_and_capture_results();
+        for (node_id, previous) in replaced {
+            let values = self.inline_values.entry(node_id).or_default();
+            match previous {
+                Some(value) => values.insert(PARAM_VALUE_PORT.to_string(), value),
+                None => values.remove(PARAM_VALUE_PORT),
+            };
+        }
+
+        if let Some(message) = result.error_message {
+            return Err(crate::error::Error::StringError(message));
+        }
+        let mut outputs = HashMap::new();
+        for (node_id, kind, param_name) in interface {
+            if kind != GraphInterface::Output {
+                continue;
+            }
+            if let Some(value) = result.node_results.get(&node_id).and_then(|values| values.get("result")) {
+                outputs.insert(param_name, value.clone());
+            }
         }
+        Ok(outputs)
     }
 
-    fn extract_error_node_id(&self, error_msg: &str) -> Option<String> {
-        // Try to find node ID in error message like "[NODE_ERROR:xxx]"
-        if let Some(start) = error_msg.find("[NODE_ERROR:") {
-            if let Some(end) = error_msg[start + 12..].find(']') {
-                return Some(error_msg[start + 12..start + 12 + end].to_string());
+    /// Parameters `execute_with_params` takes, by name, with their types
+    pub fn graph_parameters(&self) -> Result<HashMap<String, DataType>> {
+        let mut parameters = HashMap::new();
+        for (node_id, kind, param_name) in self.interface_nodes()? {
+            if kind != GraphInterface::Input {
+                continue;
+            }
+            let port = self.nodes[&node_id]
+                .input_ports()
+                .into_iter()
+                .find(|port| port.name == graph_interface_nodes::PARAM_VALUE_PORT);
+            if let Some(port) = port {
+                parameters.insert(param_name, port.data_type);
             }
         }
+        Ok(parameters)
+    }
 
-        // Try to find node ID in error message like "Node 'xxx' ..."
-        if let Some(start) = error_msg.find("Node '") {
-            if let Some(end) = error_msg[start + 6..].find('\'') {
-                return Some(error_msg[start + 6..start + 6 + end].to_string());
+    /// Graph input and output nodes as `(node_id, side, parameter name)`
+    fn interface_nodes(&self) -> Result<Vec<(String, GraphInterface, String)>> {
+        let mut interface = Vec::new();
+        for (node_id, node) in &self.nodes {
+            let Some(kind) = node.graph_interface() else {
+                continue;
+            };
+            let param_name = self
+                .inline_values
+                .get(node_id)
+                .and_then(|values| values.get(graph_interface_nodes::PARAM_NAME_PORT));
+            match param_name {
+                Some(DataValue::String(name)) if !name.trim().is_empty() => {
+                    interface.push((node_id.clone(), kind, name.trim().to_string()))
+                }
+                _ => {
+                    return Err(crate::error::Error::ValidationError(format!(
+                        "Graph interface node '{}' has no parameter name",
+                        node_id
+                    )))
+                }
             }
         }
-        None
+        Ok(interface)
+    }
+
+    fn extract_error_node_id(error: &crate::error::Error) -> Option<String> {
+        error.node_id().map(str::to_string)
     }
 
     fn execute_and_capture_results_internal(
         &mut self,
         node_results: &mut HashMap<String, HashMap<String, DataValue>>,
+        timings: &mut HashMap<String, std::time::Duration>,
     ) -> Result<()> {
         if !self.edges.is_empty() {
-            return self.execute_and_capture_results_with_edges(node_results);
+            return self.execute_and_capture_results_with_edges(node_results, timings);
         }
         
-        let mut output_producers: HashMap<String, String> = HashMap::new();
-        for (node_id, node) in &self.nodes {
-            for port in node.output_ports() {
-                if let Some(existing) = output_producers.insert(port.name.clone(), node_id.clone()) {
-                    return Err(crate::error::Error::ValidationError(format!(
-                        "Output port '{}' is produced by both '{}' and '{}'",
-                        port.name, existing, node_id
-                    )));
-                }
-            }
-        }
-
-        let mut in_degree: HashMap<String, usize> = HashMap::new();
-        let mut dependents: HashMap<String, Vec<String>> = HashMap::new();
-        let mut dependencies: HashMap<String, Vec<String>> = HashMap::new();
-
-        for node_id in self.nodes.keys() {
-            in_degree.insert(node_id.clone(), 0);
-        }
-
-        for (node_id, node) in &self.nodes {
-            for port in node.input_ports() {
-                if let Some(producer) = output_producers.get(&port.name) {
-                    if producer != node_id {
-                        dependencies.entry(node_id.clone()).or_default().push(producer.clone());
-                        dependents.entry(producer.clone()).or_default().push(node_id.clone());
-                        if let Some(count) = in_degree.get_mut(node_id) {
-                            *count += 1;
-                        }
-                    }
-                } else if port.required {
-                    // Check if the port has an inline value
-                    let has_inline = self.inline_values
-                        .get(node_id)
-                        .map(|values| values.contains_key(&port.name))
-                        .unwrap_or(false);
-                    
-                    if !has_inline {
-                        return Err(crate::error::Error::ValidationError(format!(
-                            "Required input port '{}' for node '{}' is not bound",
-                            port.name, node_id
-                        )));
-                    }
-                }
-            }
-        }
-
-        let mut ready: Vec<String> = in_degree
-            .iter()
-            .filter_map(|(id, degree)| if *degree == 0 { Some(id.clone()) } else { None })
-            .collect();
-        ready.sort();
-
-        let mut ordered: Vec<String> = Vec::with_capacity(self.nodes.len());
-        while !ready.is_empty() {
-            let node_id = ready.remove(0);
-            ordered.push(node_id.clone());
-
-            if let Some(next_nodes) = dependents.get(&node_id) {
-                for next_id in next_nodes {
-                    if let Some(count) = in_degree.get_mut(next_id) {
-                        *count = count.saturating_sub(1);
-                        if *count == 0 {
-                            ready.push(next_id.clone());
-                        }
-                    }
-                }
-                ready.sort();
-            }
-        }
-
-        if ordered.len() != self.nodes.len() {
-            return Err(crate::error::Error::ValidationError(
-                "Cycle detected in node dependencies".to_string(),
-            ));
-        }
+        let (dependents, dependencies) = self.edgeless_dependencies()?;
+        let ordered = self.topological_order(&dependents, &dependencies)?;
 
         let event_producer_set: HashSet<String> = self
             .nodes
@@ -598,13 +1030,16 @@ impl NodeGraph {
 
                 let inputs = Self::collect_inputs(node.as_ref(), &data_pool, &node_id, self.inline_values.get(&node_id))?;
                 
-                let inputs_clone = if self.execution_callback.is_some() { Some(inputs.clone()) } else { None };
+                let inputs_clone = if self.node_runner.execution_callback.is_some() { Some(inputs.clone()) } else { None };
 
-                let outputs = node.execute(inputs.clone())?;
+                let started = std::time::Instant::now();
+                let outcome = self.node_runner.execute(&node_id, node.as_mut(), inputs.clone());
+                timings.insert(node_id.clone(), started.elapsed());
+                let outputs = outcome.map_err(|e| crate::error::Error::node_execution(node_id.as_str(), e))?;
                 
-                if let Some(cb) = &self.execution_callback {
+                if let Some(cb) = &self.node_runner.execution_callback {
                     if let Some(inp) = inputs_clone {
-                        cb(&node_id, &inp, &outputs);
+                        cb(ExecutionPhase::Executed, &node_id, &inp, &outputs);
                     }
                 }
                 
@@ -613,15 +1048,7 @@ impl NodeGraph {
                 result.extend(outputs.iter().map(|(k, v)| (k.clone(), v.clone())));
                 node_results.insert(node_id.clone(), result);
                 
-                for (key, value) in outputs {
-                    if data_pool.contains_key(&key) {
-                        return Err(crate::error::Error::ValidationError(format!(
-                            "Output key '{}' from node '{}' conflicts with existing data",
-                            key, node_id
-                        )));
-                    }
-                    data_pool.insert(key, value);
-                }
+                Self::insert_pool_outputs(&mut data_pool, node.as_ref(), &node_id, outputs)?;
             }
 
             return Ok(());
@@ -640,76 +1067,8 @@ impl NodeGraph {
             return Ok(());
         }
 
-        let mut in_degree: HashMap<String, usize> = HashMap::new();
-        for node_id in self.nodes.keys() {
-            in_degree.insert(node_id.clone(), 0);
-        }
-
-        for (node_id, deps) in &dependencies {
-            if let Some(count) = in_degree.get_mut(node_id) {
-                *count += deps.len();
-            }
-        }
-
-        let mut ready: Vec<String> = in_degree
-            .iter()
-            .filter_map(|(id, degree)| if *degree == 0 { Some(id.clone()) } else { None })
-            .collect();
-        ready.sort();
-
-        let mut ordered: Vec<String> = Vec::with_capacity(self.nodes.len());
-        while !ready.is_empty() {
-            let node_id = ready.remove(0);
-            ordered.push(node_id.clone());
-
-            if let Some(next_nodes) = dependents.get(&node_id) {
-                for next_id in next_nodes {
-                    if let Some(count) = in_degree.get_mut(next_id) {
-                        *count = count.saturating_sub(1);
-                        if *count == 0 {
-                            ready.push(next_id.clone());
-                        }
-                    }
-                }
-                ready.sort();
-            }
-        }
-
-        if ordered.len() != self.nodes.len() {
-            return Err(crate::error::Error::ValidationError(
-                "Cycle detected in node dependencies".to_string(),
-            ));
-        }
-
-        for node_id in &connected_nodes {
-            let node = self.nodes.get(node_id).ok_or_else(|| {
-                crate::error::Error::ValidationError(format!(
-                    "Node '{}' not found during execution",
-                    node_id
-                ))
-            })?;
-
-            let has_inline = self.inline_values.get(node_id);
-            let input_map = input_sources.get(node_id);
-
-            for port in node.input_ports() {
-                if !port.required {
-                    continue;
-                }
-                let has_edge = input_map
-                    .and_then(|m| m.get(&port.name))
-                    .is_some();
-                let has_inline_value = has_inline
-                    .map(|m| m.contains_key(&port.name))
-                    .unwrap_or(false);
-                if !has_edge && !has_inline_value {
-                    return Err(crate::error::Error::ValidationError(format!(
-                        "Required input port '{}' for node '{}' is not bound",
-                        port.name, node_id
-                    )));
-                }
-            }
-        }
+        let ordered = self.topological_order(&dependents, &dependencies)?;
+        self.check_edge_bindings(&connected_nodes, &input_sources)?;
 
         let event_producer_set: HashSet<String> = self
             .nodes
@@ -736,16 +1095,19 @@ impl NodeGraph {
                             node_id
                         ))
                     })?;
-                    self.collect_inputs_with_edges(
+                    match self.collect_inputs_with_edges(
                         node.as_ref(),
                         &data_pool,
                         &input_sources,
                         &node_id,
                         self.inline_values.get(&node_id),
-                    )?
+                    )? {
+                        Some(inputs) => inputs,
+                        None => continue,
+                    }
                 };
 
-                let inputs_clone = if self.execution_callback.is_some() { Some(inputs.clone()) } else { None };
+                let inputs_clone = if self.node_runner.execution_callback.is_some() { Some(inputs.clone()) } else { None };
                 let outputs = {
                     let node = self.nodes.get_mut(&node_id).ok_or_else(|| {
                         crate::error::Error::ValidationError(format!(
@@ -753,12 +1115,12 @@ impl NodeGraph {
                             node_id
                         ))
                     })?;
-                    node.execute(inputs)?
+                    self.node_runner.execute(&node_id, node.as_mut(), inputs)?
                 };
 
-                if let Some(cb) = &self.execution_callback {
+                if let Some(cb) = &self.node_runner.execution_callback {
                     if let Some(inp) = inputs_clone {
-                        cb(&node_id, &inp, &outputs);
+                        cb(ExecutionPhase::Executed, &node_id, &inp, &outputs);
                     }
                 }
 
@@ -805,13 +1167,16 @@ impl NodeGraph {
                         node_id
                     ))
                 })?;
-                self.collect_inputs_with_edges(
+                match self.collect_inputs_with_edges(
                     node.as_ref(),
                     &base_data_pool,
                     &input_sources,
                     node_id,
                     self.inline_values.get(node_id),
-                )?
+                )? {
+                    Some(inputs) => inputs,
+                    None => continue,
+                }
             };
 
             let outputs = {
@@ -821,7 +1186,7 @@ impl NodeGraph {
                         node_id
                     ))
                 })?;
-                node.execute(inputs)?
+                self.node_runner.execute(node_id, node.as_mut(), inputs)?
             };
             self.insert_outputs(&mut base_data_pool, node_id, outputs);
         }
@@ -857,6 +1222,7 @@ impl NodeGraph {
     fn execute_and_capture_results_with_edges(
         &mut self,
         node_results: &mut HashMap<String, HashMap<String, DataValue>>,
+        timings: &mut HashMap<String, std::time::Duration>,
     ) -> Result<()> {
         let (connected_nodes, dependents, dependencies, input_sources) = self.build_edge_maps()?;
 
@@ -864,76 +1230,8 @@ impl NodeGraph {
             return Ok(());
         }
 
-        let mut in_degree: HashMap<String, usize> = HashMap::new();
-        for node_id in self.nodes.keys() {
-            in_degree.insert(node_id.clone(), 0);
-        }
-
-        for (node_id, deps) in &dependencies {
-            if let Some(count) = in_degree.get_mut(node_id) {
-                *count += deps.len();
-            }
-        }
-
-        let mut ready: Vec<String> = in_degree
-            .iter()
-            .filter_map(|(id, degree)| if *degree == 0 { Some(id.clone()) } else { None })
-            .collect();
-        ready.sort();
-
-        let mut ordered: Vec<String> = Vec::with_capacity(self.nodes.len());
-        while !ready.is_empty() {
-            let node_id = ready.remove(0);
-            ordered.push(node_id.clone());
-
-            if let Some(next_nodes) = dependents.get(&node_id) {
-                for next_id in next_nodes {
-                    if let Some(count) = in_degree.get_mut(next_id) {
-                        *count = count.saturating_sub(1);
-                        if *count == 0 {
-                            ready.push(next_id.clone());
-                        }
-                    }
-                }
-                ready.sort();
-            }
-        }
-
-        if ordered.len() != self.nodes.len() {
-            return Err(crate::error::Error::ValidationError(
-                "Cycle detected in node dependencies".to_string(),
-            ));
-        }
-
-        for node_id in &connected_nodes {
-            let node = self.nodes.get(node_id).ok_or_else(|| {
-                crate::error::Error::ValidationError(format!(
-                    "Node '{}' not found during execution",
-                    node_id
-                ))
-            })?;
-
-            let has_inline = self.inline_values.get(node_id);
-            let input_map = input_sources.get(node_id);
-
-            for port in node.input_ports() {
-                if !port.required {
-                    continue;
-                }
-                let has_edge = input_map
-                    .and_then(|m| m.get(&port.name))
-                    .is_some();
-                let has_inline_value = has_inline
-                    .map(|m| m.contains_key(&port.name))
-                    .unwrap_or(false);
-                if !has_edge && !has_inline_value {
-                    return Err(crate::error::Error::ValidationError(format!(
-                        "Required input port '{}' for node '{}' is not bound",
-                        port.name, node_id
-                    )));
-                }
-            }
-        }
+        let ordered = self.topological_order(&dependents, &dependencies)?;
+        self.check_edge_bindings(&connected_nodes, &input_sources)?;
 
         let event_producer_set: HashSet<String> = self
             .nodes
@@ -960,16 +1258,19 @@ impl NodeGraph {
                             node_id
                         ))
                     })?;
-                    self.collect_inputs_with_edges(
+                    match self.collect_inputs_with_edges(
                         node.as_ref(),
                         &data_pool,
                         &input_sources,
                         &node_id,
                         self.inline_values.get(&node_id),
-                    )?
+                    )? {
+                        Some(inputs) => inputs,
+                        None => continue,
+                    }
                 };
 
-                let inputs_clone = if self.execution_callback.is_some() { Some(inputs.clone()) } else { None };
+                let inputs_clone = if self.node_runner.execution_callback.is_some() { Some(inputs.clone()) } else { None };
                 let outputs = {
                     let node = self.nodes.get_mut(&node_id).ok_or_else(|| {
                         crate::error::Error::ValidationError(format!(
@@ -977,12 +1278,15 @@ impl NodeGraph {
                             node_id
                         ))
                     })?;
-                    node.execute(inputs.clone())?
+                    let started = std::time::Instant::now();
+                    let outcome = self.node_runner.execute(&node_id, node.as_mut(), inputs.clone());
+                    timings.insert(node_id.clone(), started.elapsed());
+                    outcome.map_err(|e| crate::error::Error::node_execution(node_id.as_str(), e))?
                 };
 
-                if let Some(cb) = &self.execution_callback {
+                if let Some(cb) = &self.node_runner.execution_callback {
                     if let Some(inp) = inputs_clone {
-                        cb(&node_id, &inp, &outputs);
+                        cb(ExecutionPhase::Executed, &node_id, &inp, &outputs);
                     }
                 }
 
@@ -1000,114 +1304,406 @@ impl NodeGraph {
         Ok(())
     }
 
-    fn build_edge_maps(
-        &self,
-    ) -> Result<(
-        HashSet<String>,
-        HashMap<String, Vec<String>>,
-        HashMap<String, Vec<String>>,
-        InputSourceMap,
-    )> {
-        let mut connected_nodes: HashSet<String> = HashSet::new();
-        let mut dependents: HashMap<String, Vec<String>> = HashMap::new();
-        let mut dependencies: HashMap<String, Vec<String>> = HashMap::new();
-        let mut input_sources: InputSourceMap = HashMap::new();
-
-        for edge in &self.edges {
-            let from_node = self.nodes.get(&edge.from_node_id).ok_or_else(|| {
-                crate::error::Error::ValidationError(format!(
-                    "Node '{}' not found for edge",
-                    edge.from_node_id
-                ))
-            })?;
-            let to_node = self.nodes.get(&edge.to_node_id).ok_or_else(|| {
-                crate::error::Error::ValidationError(format!(
-                    "Node '{}' not found for edge",
-                    edge.to_node_id
-                ))
-            })?;
-
-            let from_port = from_node
-                .output_ports()
-                .into_iter()
-                .find(|p| p.name == edge.from_port)
-                .ok_or_else(|| {
-                    crate::error::Error::ValidationError(format!(
-                        "Output port '{}' not found on node '{}'",
-                        edge.from_port, edge.from_node_id
-                    ))
-                })?;
+    /// Ids of the nodes in the order they would run, without running anything.
+    /// Performs the same checks as `execute`: conflicting producers, unbound
+    /// required inputs, port types and cycles. With edges, only connected nodes are listed.
+    pub fn execution_order(&self) -> Result<Vec<String>> {
+        if self.edges.is_empty() {
+            let (dependents, dependencies) = self.edgeless_dependencies()?;
+            return self.topological_order(&dependents, &dependencies);
+        }
 
-            let to_port = to_node
-                .input_ports()
-                .into_iter()
-                .find(|p| p.name == edge.to_port)
-                .ok_or_else(|| {
-                    crate::error::Error::ValidationError(format!(
-                        "Input port '{}' not found on node '{}'",
-                        edge.to_port, edge.to_node_id
-                    ))
-                })?;
+        let (connected_nodes, dependents, dependencies, input_sources) = self.build_edge_maps()?;
+        let ordered = self.topological_order(&dependents, &dependencies)?;
+        self.check_edge_bindings(&connected_nodes, &input_sources)?;
+        Ok(ordered.into_iter().filter(|id| connected_nodes.contains(id)).collect())
+    }
 
-            if from_port.data_type != to_port.data_type {
-                return Err(crate::error::Error::ValidationError(format!(
-                    "Port type mismatch for edge {}.{} -> {}.{}",
-                    edge.from_node_id, edge.from_port, edge.to_node_id, edge.to_port
-                )));
+    /// In a graph without edges, the node producing each output port name
+    fn edgeless_producers(&self) -> Result<HashMap<String, String>> {
+        let mut output_producers: HashMap<String, String> = HashMap::new();
+        for (node_id, node) in &self.nodes {
+            for port in node.output_ports() {
+                if let Some(existing) = output_producers.insert(port.name.clone(), node_id.clone()) {
+                    return Err(crate::error::Error::ValidationError(format!(
+                        "Output port '{}' is produced by both '{}' and '{}'",
+                        port.name, existing, node_id
+                    )));
+                }
             }
+        }
+        Ok(output_producers)
+    }
 
-            connected_nodes.insert(edge.from_node_id.clone());
-            connected_nodes.insert(edge.to_node_id.clone());
+    /// Dependencies of a graph without edges, where inputs bind to the output of the same name
+    fn edgeless_dependencies(&self) -> Result<(DependencyMap, DependencyMap)> {
+        let output_producers = self.edgeless_producers()?;
+        let unbound = self.unbound_required_inputs(self.nodes.keys(), |_, port| output_producers.contains_key(port));
+        Self::require_bound(unbound)?;
 
-            dependents
-                .entry(edge.from_node_id.clone())
-                .or_default()
-                .push(edge.to_node_id.clone());
-            dependencies
-                .entry(edge.to_node_id.clone())
-                .or_default()
-                .push(edge.from_node_id.clone());
+        let mut dependents: DependencyMap = HashMap::new();
+        let mut dependencies: DependencyMap = HashMap::new();
 
-            let entry = input_sources.entry(edge.to_node_id.clone()).or_default();
-            if entry.contains_key(&edge.to_port) {
-                return Err(crate::error::Error::ValidationError(format!(
-                    "Input port '{}' on node '{}' has multiple connections",
-                    edge.to_port, edge.to_node_id
-                )));
+        for (node_id, node) in &self.nodes {
+            for port in node.input_ports() {
+                if let Some(producer) = output_producers.get(&port.name) {
+                    if producer != node_id {
+                        dependencies.entry(node_id.clone()).or_default().push(producer.clone());
+                        dependents.entry(producer.clone()).or_default().push(node_id.clone());
+                    }
+                }
             }
-            entry.insert(
-                edge.to_port.clone(),
-                (edge.from_node_id.clone(), edge.from_port.clone()),
-            );
         }
 
-        Ok((connected_nodes, dependents, dependencies, input_sources))
+        Ok((dependents, dependencies))
     }
 
-    fn collect_inputs_with_edges(
-        &self,
-        node: &dyn Node,
-        data_pool: &OutputPool,
-        input_sources: &InputSourceMap,
-        node_id: &str,
-        inline_values: Option<&HashMap<String, DataValue>>,
-    ) -> Result<HashMap<String, DataValue>> {
+    /// Kahn's algorithm over all nodes. Ready nodes run in id order, so the order is deterministic.
+    fn topological_order(&self, dependents: &DependencyMap, dependencies: &DependencyMap) -> Result<Vec<String>> {
+        let mut in_degree: HashMap<String, usize> = HashMap::new();
+        for node_id in self.nodes.keys() {
+            in_degree.insert(node_id.clone(), 0);
+        }
+
+        for (node_id, deps) in dependencies {
+            if let Some(count) = in_degree.get_mut(node_id) {
+                *count += deps.len();
+            }
+        }
+
+        let mut ready: Vec<String> = in_degree
+            .iter()
+            .filter_map(|(id, degree)| if *degree == 0 { Some(id.clone()) } else { None })
+            .collect();
+        ready.sort();
+
+        let mut ordered: Vec<String> = Vec::with_capacity(self.nodes.len());
+        while !ready.is_empty() {
+            let node_id = ready.remove(0);
+            ordered.push(node_id.clone());
+
+            if let Some(next_nodes) = dependents.get(&node_id) {
+                for next_id in next_nodes {
+                    if let Some(count) = in_degree.get_mut(next_id) {
+                        *count = count.saturating_sub(1);
+                        if *count == 0 {
+                            ready.push(next_id.clone());
+                        }
+                    }
+                }
+                ready.sort();
+            }
+        }
+
+        if ordered.len() != self.nodes.len() {
+            return Err(crate::error::Error::ValidationError(
+                "Cycle detected in node dependencies".to_string(),
+            ));
+        }
+
+        Ok(ordered)
+    }
+
+    /// Every required input of a connected node needs an edge or an inline value
+    fn check_edge_bindings(&self, connected_nodes: &HashSet<String>, input_sources: &InputSourceMap) -> Result<()> {
+        let unbound = self.unbound_required_inputs(connected_nodes, |node_id, port| {
+            input_sources.get(node_id).is_some_and(|m| m.contains_key(port))
+        });
+        Self::require_bound(unbound)
+    }
+
+    /// Required inputs of `node_ids` without an inline value for which `is_bound(node_id, port)`
+    /// is false, as `(node_id, port_name)` sorted by node id and port
+    fn unbound_required_inputs<'a>(
+        &self,
+        node_ids: impl IntoIterator<Item = &'a String>,
+        is_bound: impl Fn(&str, &str) -> bool,
+    ) -> Vec<(String, String)> {
+        let mut unbound = Vec::new();
+        for node_id in node_ids {
+            let Some(node) = self.nodes.get(node_id) else {
+                continue;
+            };
+            let inline = self.inline_values.get(node_id);
+            for port in node.input_ports() {
+                if !port.required
+                    || inline.is_some_and(|m| m.contains_key(&port.name))
+                    || is_bound(node_id, &port.name)
+                {
+                    continue;
+                }
+                unbound.push((node_id.clone(), port.name));
+            }
+        }
+        unbound.sort();
+        unbound
+    }
+
+    fn require_bound(unbound: Vec<(String, String)>) -> Result<()> {
+        match unbound.into_iter().next() {
+            Some((node_id, port)) => Err(crate::error::Error::ValidationError(format!(
+                "Required input port '{}' for node '{}' is not bound",
+                port, node_id
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Problems that would stop the graph from running, found without executing any node, as
+    /// `(node_id, port_name, reason)`. Problems that don't belong to one port, like a cycle,
+    /// have an empty node id and port name.
+    pub fn validate(&self) -> Vec<(String, String, String)> {
+        let reason = |err: crate::error::Error| match err {
+            crate::error::Error::ValidationError(message) => message,
+            other => other.to_string(),
+        };
+        let mut problems = Vec::new();
+
+        if self.edges.is_empty() {
+            match self.edgeless_producers() {
+                Ok(producers) => {
+                    let unbound = self.unbound_required_inputs(self.nodes.keys(), |_, port| producers.contains_key(port));
+                    for (node_id, port) in unbound {
+                        problems.push((node_id, port, "Required input is not bound".to_string()));
+                    }
+                }
+                Err(err) => problems.push((String::new(), String::new(), reason(err))),
+            }
+        } else {
+            let mut connected_nodes: HashSet<String> = HashSet::new();
+            for edge in &self.edges {
+                if let Err(err) = self.check_edge(edge) {
+                    problems.push((edge.to_node_id.clone(), edge.to_port.clone(), reason(err)));
+                }
+                connected_nodes.insert(edge.from_node_id.clone());
+                connected_nodes.insert(edge.to_node_id.clone());
+                if let Some(condition) = &edge.condition {
+                    connected_nodes.insert(condition.node_id.clone());
+                }
+            }
+            let unbound = self.unbound_required_inputs(&connected_nodes, |node_id, port| {
+                self.edges.iter().any(|e| e.to_node_id == node_id && e.to_port == port)
+            });
+            for (node_id, port) in unbound {
+                problems.push((node_id, port, "Required input is not bound".to_string()));
+            }
+        }
+
+        // Cycles and conflicting connections, once every port is fine on its own
+        if problems.is_empty() {
+            if let Err(err) = self.execution_order() {
+                problems.push((String::new(), String::new(), reason(err)));
+            }
+        }
+        problems
+    }
+
+    /// Check that an edge's nodes and ports exist, its types fit and its condition is a
+    /// Boolean output. Returns whether the edge feeds one element of a `List(T)` input.
+    fn check_edge(&self, edge: &EdgeDefinition) -> Result<bool> {
+        let from_node = self.nodes.get(&edge.from_node_id).ok_or_else(|| {
+            crate::error::Error::ValidationError(format!(
+                "Node '{}' not found for edge",
+                edge.from_node_id
+            ))
+        })?;
+        let to_node = self.nodes.get(&edge.to_node_id).ok_or_else(|| {
+            crate::error::Error::ValidationError(format!(
+                "Node '{}' not found for edge",
+                edge.to_node_id
+            ))
+        })?;
+
+        let from_port = from_node
+            .output_ports()
+            .into_iter()
+            .find(|p| p.name == edge.from_port)
+            .ok_or_else(|| {
+                crate::error::Error::ValidationError(format!(
+                    "Output port '{}' not found on node '{}'",
+                    edge.from_port, edge.from_node_id
+                ))
+            })?;
+
+        let to_port = to_node
+            .input_ports()
+            .into_iter()
+            .find(|p| p.name == edge.to_port)
+            .ok_or_else(|| {
+                crate::error::Error::ValidationError(format!(
+                    "Input port '{}' not found on node '{}'",
+                    edge.to_port, edge.to_node_id
+                ))
+            })?;
+
+        // A List(T) input may also take T outputs, which are merged into the list
+        let auto_coerce = self.auto_coerce;
+        let is_element_edge = matches!(&to_port.data_type, DataType::List(inner)
+            if **inner == from_port.data_type || (auto_coerce && from_port.data_type.can_coerce_to(inner)));
+        let is_coercible = self.auto_coerce && from_port.data_type.can_coerce_to(&to_port.data_type);
+        if from_port.data_type != to_port.data_type && !is_element_edge && !is_coercible {
+            return Err(crate::error::Error::ValidationError(format!(
+                "Port type mismatch for edge {}.{} -> {}.{}",
+                edge.from_node_id, edge.from_port, edge.to_node_id, edge.to_port
+            )));
+        }
+
+        if let Some(condition) = &edge.condition {
+            let condition_node = self.nodes.get(&condition.node_id).ok_or_else(|| {
+                crate::error::Error::ValidationError(format!(
+                    "Node '{}' not found for edge condition",
+                    condition.node_id
+                ))
+            })?;
+            let is_boolean = condition_node
+                .output_ports()
+                .iter()
+                .any(|p| p.name == condition.port && p.data_type == DataType::Boolean);
+            if !is_boolean {
+                return Err(crate::error::Error::ValidationError(format!(
+                    "Edge condition {}.{} is not a Boolean output port",
+                    condition.node_id, condition.port
+                )));
+            }
+        }
+        Ok(is_element_edge)
+    }
+
+    fn build_edge_maps(
+        &self,
+    ) -> Result<(
+        HashSet<String>,
+        HashMap<String, Vec<String>>,
+        HashMap<String, Vec<String>>,
+        InputSourceMap,
+    )> {
+        let mut connected_nodes: HashSet<String> = HashSet::new();
+        let mut dependents: HashMap<String, Vec<String>> = HashMap::new();
+        let mut dependencies: HashMap<String, Vec<String>> = HashMap::new();
+        let mut input_sources: InputSourceMap = HashMap::new();
+        // Input ports whose sources are element edges merged into a list
+        let mut merging_ports: HashSet<(&String, &String)> = HashSet::new();
+
+        for edge in &self.edges {
+            let is_element_edge = self.check_edge(edge)?;
+
+            if let Some(condition) = &edge.condition {
+                // The condition has to be known before the edge's target runs
+                connected_nodes.insert(condition.node_id.clone());
+                dependents
+                    .entry(condition.node_id.clone())
+                    .or_default()
+                    .push(edge.to_node_id.clone());
+                dependencies
+                    .entry(edge.to_node_id.clone())
+                    .or_default()
+                    .push(condition.node_id.clone());
+            }
+
+            connected_nodes.insert(edge.from_node_id.clone());
+            connected_nodes.insert(edge.to_node_id.clone());
+
+            dependents
+                .entry(edge.from_node_id.clone())
+                .or_default()
+                .push(edge.to_node_id.clone());
+            dependencies
+                .entry(edge.to_node_id.clone())
+                .or_default()
+                .push(edge.from_node_id.clone());
+
+            let sources = input_sources
+                .entry(edge.to_node_id.clone())
+                .or_default()
+                .entry(edge.to_port.clone())
+                .or_default();
+            let merges_with_existing = is_element_edge && merging_ports.contains(&(&edge.to_node_id, &edge.to_port));
+            if !sources.is_empty() && !merges_with_existing {
+                return Err(crate::error::Error::ValidationError(format!(
+                    "Input port '{}' on node '{}' has multiple connections",
+                    edge.to_port, edge.to_node_id
+                )));
+            }
+            if is_element_edge {
+                merging_ports.insert((&edge.to_node_id, &edge.to_port));
+            }
+            sources.push((edge.from_node_id.clone(), edge.from_port.clone(), edge.condition.clone()));
+        }
+
+        // Merged values are collected in a deterministic order
+        for ports in input_sources.values_mut() {
+            for sources in ports.values_mut() {
+                sources.sort();
+            }
+        }
+
+        Ok((connected_nodes, dependents, dependencies, input_sources))
+    }
+
+    /// Collect a node's inputs from its incoming edges. Returns `None` when the node
+    /// should be skipped: a required input is only fed by edges whose condition is
+    /// false or whose source node was skipped itself.
+    fn collect_inputs_with_edges(
+        &self,
+        node: &dyn Node,
+        data_pool: &OutputPool,
+        input_sources: &InputSourceMap,
+        node_id: &str,
+        inline_values: Option<&HashMap<String, DataValue>>,
+    ) -> Result<Option<HashMap<String, DataValue>>> {
         let mut inputs: HashMap<String, DataValue> = HashMap::new();
         let sources = input_sources.get(node_id);
 
         for port in node.input_ports() {
-            if let Some(source_map) = sources.and_then(|m| m.get(&port.name)) {
-                let (from_node_id, from_port) = source_map;
-                if let Some(from_outputs) = data_pool.get(from_node_id) {
-                    if let Some(value) = from_outputs.get(from_port) {
-                        inputs.insert(port.name.clone(), value.clone());
-                        continue;
+            let mut deactivated = false;
+            if let Some(port_sources) = sources.and_then(|m| m.get(&port.name)) {
+                let active: Vec<&(String, String, Option<EdgeCondition>)> = port_sources
+                    .iter()
+                    // A source that ran without setting this port (e.g. an event producer that
+                    // emitted a different kind of event) deactivates the edge like a false condition
+                    .filter(|(from_node_id, from_port, condition)| {
+                        data_pool.get(from_node_id).is_some_and(|outputs| outputs.contains_key(from_port))
+                            && Self::edge_condition_holds(data_pool, condition.as_ref())
+                    })
+                    .collect();
+                deactivated = active.is_empty();
+                let values: Vec<DataValue> = active
+                    .into_iter()
+                    .filter_map(|(from_node_id, from_port, _)| data_pool.get(from_node_id)?.get(from_port).cloned())
+                    .collect();
+                // For a list port a list value is passed through, anything else is an element
+                let is_direct = values.len() == 1
+                    && (!matches!(port.data_type, DataType::List(_)) || matches!(values[0], DataValue::List(_)));
+                if is_direct {
+                    for value in values {
+                        let value = self
+                            .coerce_input(value, &port.data_type, &port.name, node_id)
+                            .map_err(crate::error::Error::ValidationError)?;
+                        inputs.insert(port.name.clone(), value);
                     }
+                    continue;
+                }
+                if !values.is_empty() {
+                    let element_type = match &port.data_type {
+                        DataType::List(inner) => inner.as_ref(),
+                        other => other,
+                    };
+                    let values = values
+                        .into_iter()
+                        .map(|value| self.coerce_input(value, element_type, &port.name, node_id))
+                        .collect::<std::result::Result<Vec<_>, String>>()
+                        .map_err(crate::error::Error::ValidationError)?;
+                    inputs.insert(port.name.clone(), DataValue::List(values));
+                    continue;
                 }
             }
 
             if let Some(value) = inline_values.and_then(|m| m.get(&port.name)) {
                 inputs.insert(port.name.clone(), value.clone());
+            } else if port.required && deactivated {
+                return Ok(None);
+            } else if let Some(value) = port.default {
+                inputs.insert(port.name, value);
             } else if port.required {
                 return Err(crate::error::Error::ValidationError(format!(
                     "Required input port '{}' for node '{}' is missing",
@@ -1117,7 +1713,38 @@ impl NodeGraph {
         }
 
         node.validate_inputs(&inputs)?;
-        Ok(inputs)
+        Ok(Some(inputs))
+    }
+
+    /// In auto-coerce mode, convert a value from an edge to the input port's type
+    fn coerce_input(
+        &self,
+        value: DataValue,
+        target: &DataType,
+        port_name: &str,
+        node_id: &str,
+    ) -> std::result::Result<DataValue, String> {
+        if !self.auto_coerce || value.data_type() == *target {
+            return Ok(value);
+        }
+        value.coerce_to(target).ok_or_else(|| {
+            format!(
+                "Input port '{}' for node '{}' cannot convert {:?} to {}",
+                port_name, node_id, value, target
+            )
+        })
+    }
+
+    /// An unconditional edge always holds; a conditional one only once its
+    /// Boolean source has run and produced `true`
+    fn edge_condition_holds(data_pool: &OutputPool, condition: Option<&EdgeCondition>) -> bool {
+        match condition {
+            None => true,
+            Some(condition) => matches!(
+                data_pool.get(&condition.node_id).and_then(|outputs| outputs.get(&condition.port)),
+                Some(DataValue::Boolean(true))
+            ),
+        }
     }
 
     fn insert_outputs(&self, pool: &mut OutputPool, node_id: &str, outputs: HashMap<String, DataValue>) {
@@ -1127,6 +1754,31 @@ impl NodeGraph {
         }
     }
 
+    /// Store a node's declared outputs in an edgeless data pool. Any number of
+    /// downstream inputs may read the same key; only a second producer conflicts.
+    /// Undeclared keys (e.g. a preview node echoing its input) stay out of the pool.
+    fn insert_pool_outputs(
+        pool: &mut HashMap<String, DataValue>,
+        node: &dyn Node,
+        node_id: &str,
+        outputs: HashMap<String, DataValue>,
+    ) -> Result<()> {
+        let declared: HashSet<String> = node.output_ports().into_iter().map(|port| port.name).collect();
+        for (key, value) in outputs {
+            if !declared.contains(&key) {
+                continue;
+            }
+            if pool.contains_key(&key) {
+                return Err(crate::error::Error::ValidationError(format!(
+                    "Output key '{}' from node '{}' conflicts with existing data",
+                    key, node_id
+                )));
+            }
+            pool.insert(key, value);
+        }
+        Ok(())
+    }
+
     fn collect_inputs(
         node: &dyn Node,
         data_pool: &HashMap<String, DataValue>,
@@ -1139,6 +1791,8 @@ impl NodeGraph {
                 inputs.insert(port.name.clone(), value.clone());
             } else if let Some(value) = inline_values.and_then(|m| m.get(&port.name)) {
                 inputs.insert(port.name.clone(), value.clone());
+            } else if let Some(value) = port.default {
+                inputs.insert(port.name, value);
             } else if port.required {
                 return Err(crate::error::Error::ValidationError(format!(
                     "Required input port '{}' for node '{}' is missing",
@@ -1150,6 +1804,20 @@ impl NodeGraph {
         Ok(inputs)
     }
 
+    /// Whether every required input of the node can be filled from the pool, inline values or defaults
+    fn has_required_inputs(
+        node: &dyn Node,
+        data_pool: &HashMap<String, DataValue>,
+        inline_values: Option<&HashMap<String, DataValue>>,
+    ) -> bool {
+        node.input_ports().iter().all(|port| {
+            !port.required
+                || port.default.is_some()
+                || data_pool.contains_key(&port.name)
+                || inline_values.is_some_and(|m| m.contains_key(&port.name))
+        })
+    }
+
     fn run_event_producer_with_edges(
         &mut self,
         node_id: &str,
@@ -1173,13 +1841,17 @@ impl NodeGraph {
                         node_id
                     ))
                 })?;
-                self.collect_inputs_with_edges(
+                match self.collect_inputs_with_edges(
                     node.as_ref(),
                     base_data_pool,
                     input_sources,
                     node_id,
                     self.inline_values.get(node_id),
-                )?
+                )? {
+                    Some(inputs) => inputs,
+                    // Gated off by a false edge condition, the producer never starts
+                    None => return Ok(()),
+                }
             };
 
             let node = self.nodes.get_mut(node_id).ok_or_else(|| {
@@ -1189,16 +1861,50 @@ impl NodeGraph {
                 ))
             })?;
 
-            node.on_start(inputs).map_err(|e| {
-                crate::error::Error::ValidationError(format!("[NODE_ERROR:{}] {}", node_id, e))
-            })?;
+            node.on_start(inputs).map_err(|e| crate::error::Error::node_execution(node_id, e))?;
+        }
+
+        {
+            let start_outputs = self.nodes.get(node_id).map(|node| node.start_outputs()).unwrap_or_default();
+            let mut start_pool = base_data_pool.clone();
+            self.insert_outputs(&mut start_pool, node_id, start_outputs);
+            for ordered_id in Self::started_downstream(node_id, &reachable, reachable_map, event_producer_set, ordered) {
+                if !connected_nodes.contains(ordered_id) {
+                    continue;
+                }
+                let Some(node) = self.nodes.get(ordered_id) else {
+                    continue;
+                };
+                // Inputs from nodes that have not run yet are missing; the node then waits
+                // for the first event, which reports any real problem with its inputs
+                let Ok(Some(inputs)) = self.collect_inputs_with_edges(
+                    node.as_ref(),
+                    &start_pool,
+                    input_sources,
+                    ordered_id,
+                    self.inline_values.get(ordered_id),
+                ) else {
+                    continue;
+                };
+                if let Some(node) = self.nodes.get_mut(ordered_id) {
+                    node.on_graph_start(inputs)
+                        .map_err(|e| crate::error::Error::node_execution(ordered_id, e))?;
+                }
+            }
         }
 
+        let mut iterations: u64 = 0;
         loop {
+            self.wait_while_paused(node_id);
             if self.stop_flag.load(Ordering::Relaxed) {
                 info!("Event producer '{}' stopped by user request", node_id);
                 break;
             }
+            if self.event_producer_max_iterations.is_some_and(|max| iterations >= max) {
+                info!("Event producer '{}' stopped after {} iterations", node_id, iterations);
+                break;
+            }
+            iterations += 1;
 
             let outputs = {
                 let node = self.nodes.get_mut(node_id).ok_or_else(|| {
@@ -1208,9 +1914,7 @@ impl NodeGraph {
                     ))
                 })?;
 
-                match node.on_update().map_err(|e| {
-                    crate::error::Error::ValidationError(format!("[NODE_ERROR:{}] {}", node_id, e))
-                })? {
+                match node.on_update().map_err(|e| crate::error::Error::node_execution(node_id, e))? {
                     Some(outputs) => {
                         node.validate_outputs(&outputs)?;
                         outputs
@@ -1219,8 +1923,8 @@ impl NodeGraph {
                 }
             };
 
-            if let Some(cb) = &self.execution_callback {
-                cb(node_id, &HashMap::new(), &outputs);
+            if let Some(cb) = &self.node_runner.execution_callback {
+                cb(ExecutionPhase::Executed, node_id, &HashMap::new(), &outputs);
             }
 
             let mut event_pool = base_data_pool.clone();
@@ -1264,16 +1968,19 @@ impl NodeGraph {
                             ordered_id
                         ))
                     })?;
-                    self.collect_inputs_with_edges(
+                    match self.collect_inputs_with_edges(
                         node.as_ref(),
                         &event_pool,
                         input_sources,
                         ordered_id,
                         self.inline_values.get(ordered_id),
-                    )?
+                    )? {
+                        Some(inputs) => inputs,
+                        None => continue,
+                    }
                 };
 
-                let inputs_clone = if self.execution_callback.is_some() { Some(inputs.clone()) } else { None };
+                let inputs_clone = if self.node_runner.execution_callback.is_some() { Some(inputs.clone()) } else { None };
                 let outputs = {
                     let node = self.nodes.get_mut(ordered_id).ok_or_else(|| {
                         crate::error::Error::ValidationError(format!(
@@ -1281,14 +1988,12 @@ impl NodeGraph {
                             ordered_id
                         ))
                     })?;
-                    node.execute(inputs).map_err(|e| {
-                        crate::error::Error::ValidationError(format!("[NODE_ERROR:{}] {}", ordered_id, e))
-                    })?
+                    self.node_runner.execute(ordered_id, node.as_mut(), inputs).map_err(|e| crate::error::Error::node_execution(ordered_id, e))?
                 };
 
-                if let Some(cb) = &self.execution_callback {
+                if let Some(cb) = &self.node_runner.execution_callback {
                     if let Some(inp) = inputs_clone {
-                        cb(ordered_id, &inp, &outputs);
+                        cb(ExecutionPhase::Executed, ordered_id, &inp, &outputs);
                     }
                 }
 
@@ -1307,6 +2012,27 @@ impl NodeGraph {
         Ok(())
     }
 
+    /// Nodes an event producer drives, in execution order, leaving out nested event
+    /// producers and what they drive themselves
+    fn started_downstream<'a>(
+        node_id: &str,
+        reachable: &HashSet<String>,
+        reachable_map: &HashMap<String, HashSet<String>>,
+        event_producer_set: &HashSet<String>,
+        ordered: &'a [String],
+    ) -> Vec<&'a String> {
+        let nested: HashSet<&String> = ordered
+            .iter()
+            .filter(|id| id.as_str() != node_id && reachable.contains(*id) && event_producer_set.contains(*id))
+            .flat_map(|id| reachable_map.get(id).into_iter().flatten())
+            .collect();
+        ordered
+            .iter()
+            .filter(|id| id.as_str() != node_id && reachable.contains(*id))
+            .filter(|id| !event_producer_set.contains(*id) && !nested.contains(id))
+            .collect()
+    }
+
     fn run_event_producer(
         &mut self,
         node_id: &str,
@@ -1329,16 +2055,41 @@ impl NodeGraph {
             })?;
 
             let inputs = Self::collect_inputs(node.as_ref(), base_data_pool, node_id, self.inline_values.get(node_id))?;
-            node.on_start(inputs).map_err(|e| {
-                crate::error::Error::ValidationError(format!("[NODE_ERROR:{}] {}", node_id, e))
-            })?;
+            node.on_start(inputs).map_err(|e| crate::error::Error::node_execution(node_id, e))?;
+        }
+
+        {
+            let mut start_pool = base_data_pool.clone();
+            if let Some(node) = self.nodes.get(node_id) {
+                start_pool.extend(node.start_outputs());
+            }
+            for ordered_id in Self::started_downstream(node_id, &reachable, reachable_map, event_producer_set, ordered) {
+                let Some(node) = self.nodes.get_mut(ordered_id) else {
+                    continue;
+                };
+                // Nodes needing outputs of nodes that have not run yet wait for the first event
+                let Ok(inputs) =
+                    Self::collect_inputs(node.as_ref(), &start_pool, ordered_id, self.inline_values.get(ordered_id))
+                else {
+                    continue;
+                };
+                node.on_graph_start(inputs)
+                    .map_err(|e| crate::error::Error::node_execution(ordered_id, e))?;
+            }
         }
 
+        let mut iterations: u64 = 0;
         loop {
+            self.wait_while_paused(node_id);
             if self.stop_flag.load(Ordering::Relaxed) {
                 info!("Event producer '{}' stopped by user request", node_id);
                 break;
             }
+            if self.event_producer_max_iterations.is_some_and(|max| iterations >= max) {
+                info!("Event producer '{}' stopped after {} iterations", node_id, iterations);
+                break;
+            }
+            iterations += 1;
 
             let outputs = {
                 let node = self.nodes.get_mut(node_id).ok_or_else(|| {
@@ -1348,9 +2099,7 @@ impl NodeGraph {
                     ))
                 })?;
 
-                match node.on_update().map_err(|e| {
-                    crate::error::Error::ValidationError(format!("[NODE_ERROR:{}] {}", node_id, e))
-                })? {
+                match node.on_update().map_err(|e| crate::error::Error::node_execution(node_id, e))? {
                     Some(outputs) => {
                         node.validate_outputs(&outputs)?;
                         outputs
@@ -1359,8 +2108,8 @@ impl NodeGraph {
                 }
             };
 
-            if let Some(cb) = &self.execution_callback {
-                cb(node_id, &HashMap::new(), &outputs);
+            if let Some(cb) = &self.node_runner.execution_callback {
+                cb(ExecutionPhase::Executed, node_id, &HashMap::new(), &outputs);
             }
 
             let mut event_pool = base_data_pool.clone();
@@ -1401,29 +2150,24 @@ impl NodeGraph {
                     ))
                 })?;
 
+                // Only the ports of the arrived event are set (a notice has no `message_event`),
+                // so nodes fed by the other ports sit this event out
+                if !Self::has_required_inputs(node.as_ref(), &event_pool, self.inline_values.get(ordered_id)) {
+                    continue;
+                }
                 let inputs = Self::collect_inputs(node.as_ref(), &event_pool, ordered_id, self.inline_values.get(ordered_id))?;
                 
-                let inputs_clone = if self.execution_callback.is_some() { Some(inputs.clone()) } else { None };
+                let inputs_clone = if self.node_runner.execution_callback.is_some() { Some(inputs.clone()) } else { None };
 
-                let outputs = node.execute(inputs).map_err(|e| {
-                    crate::error::Error::ValidationError(format!("[NODE_ERROR:{}] {}", ordered_id, e))
-                })?;
+                let outputs = self.node_runner.execute(ordered_id, node.as_mut(), inputs).map_err(|e| crate::error::Error::node_execution(ordered_id, e))?;
                 
-                if let Some(cb) = &self.execution_callback {
+                if let Some(cb) = &self.node_runner.execution_callback {
                     if let Some(inp) = inputs_clone {
-                        cb(ordered_id, &inp, &outputs);
+                        cb(ExecutionPhase::Executed, ordered_id, &inp, &outputs);
                     }
                 }
 
-                for (key, value) in outputs {
-                    if event_pool.contains_key(&key) {
-                        return Err(crate::error::Error::ValidationError(format!(
-                            "Output key '{}' from node '{}' conflicts with existing data",
-                            key, ordered_id
-                        )));
-                    }
-                    event_pool.insert(key, value);
-                }
+                Self::insert_pool_outputs(&mut event_pool, node.as_ref(), ordered_id, outputs)?;
             }
         }
 
@@ -1458,4 +2202,1370 @@ impl Default for NodeGraph {
     fn default() -> Self {
         Self::new()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_report_includes_outputs_and_redacts_passwords() {
+        let node_results = HashMap::from([
+            (
+                "format".to_string(),
+                HashMap::from([("text".to_string(), DataValue::String("hello".to_string()))]),
+            ),
+            (
+                "login".to_string(),
+                HashMap::from([
+                    ("password".to_string(), DataValue::Password("hunter2".to_string())),
+                    ("api_token".to_string(), DataValue::String("sk-secret".to_string())),
+                ]),
+            ),
+        ]);
+        let result = ExecutionResult::with_error(node_results, "login".to_string(), "boom".to_string());
+
+        let report = result.run_report();
+        assert_eq!(report["nodes"]["format"]["text"], "hello");
+        assert_eq!(report["nodes"]["login"]["password"], REDACTED);
+        assert_eq!(report["nodes"]["login"]["api_token"], REDACTED);
+        assert_eq!(report["success"], false);
+        assert_eq!(report["error"]["node_id"], "login");
+        assert!(!report.to_string().contains("hunter2"));
+    }
+
+    #[test]
+    fn test_run_report_redacts_secrets_nested_in_collections() {
+        let account = HashMap::from([
+            ("user".to_string(), DataValue::String("alice".to_string())),
+            ("login".to_string(), DataValue::Password("hunter2".to_string())),
+        ]);
+        let llm = HashMap::from([
+            ("model".to_string(), DataValue::String("gpt".to_string())),
+            ("api_key".to_string(), DataValue::String("sk-nested".to_string())),
+        ]);
+        let node_results = HashMap::from([(
+            "config".to_string(),
+            HashMap::from([
+                (
+                    "credentials".to_string(),
+                    DataValue::List(vec![
+                        DataValue::String("visible".to_string()),
+                        DataValue::Password("list-secret".to_string()),
+                    ]),
+                ),
+                ("account".to_string(), DataValue::Map(account)),
+                ("llm".to_string(), DataValue::Map(llm)),
+            ]),
+        )]);
+        let result = ExecutionResult::with_error(node_results, "config".to_string(), "boom".to_string());
+
+        let report = result.run_report();
+        let config = &report["nodes"]["config"];
+        assert_eq!(config["credentials"], json!(["visible", REDACTED]));
+        assert_eq!(config["account"]["user"], "alice");
+        assert_eq!(config["account"]["login"], REDACTED);
+        assert_eq!(config["llm"]["model"], "gpt");
+        assert_eq!(config["llm"]["api_key"], REDACTED);
+        let text = report.to_string();
+        assert!(!text.contains("list-secret"));
+        assert!(!text.contains("hunter2"));
+        assert!(!text.contains("sk-nested"));
+    }
+
+    #[test]
+    fn test_run_report_redacts_secrets_nested_in_json() {
+        let config = json!({
+            "name": "bot",
+            "llm": {"api_key": "sk-nested", "model": "gpt"},
+            "accounts": [{"user": "alice", "password": "hunter2"}],
+        });
+        let node_results = HashMap::from([(
+            "config".to_string(),
+            HashMap::from([("config".to_string(), DataValue::Json(config))]),
+        )]);
+        let result = ExecutionResult::with_error(node_results, "config".to_string(), "boom".to_string());
+
+        let report = result.run_report();
+        let config = &report["nodes"]["config"]["config"];
+        assert_eq!(config["name"], "bot");
+        assert_eq!(config["llm"]["model"], "gpt");
+        assert_eq!(config["llm"]["api_key"], REDACTED);
+        assert_eq!(config["accounts"][0]["user"], "alice");
+        assert_eq!(config["accounts"][0]["password"], REDACTED);
+        assert!(!report.to_string().contains("sk-nested"));
+        assert!(!report.to_string().contains("hunter2"));
+    }
+
+    struct FailingNode;
+
+    impl Node for FailingNode {
+        fn id(&self) -> &str {
+            "failing"
+        }
+
+        fn name(&self) -> &str {
+            "Failing"
+        }
+
+        node_input![];
+
+        node_output![];
+
+        fn execute(&mut self, _inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+            Err(crate::error::Error::InvalidNodeInput("boom".to_string()))
+        }
+    }
+
+    #[test]
+    fn test_node_errors_carry_structured_node_id() {
+        let mut graph = NodeGraph::new();
+        graph.add_node(Box::new(FailingNode)).unwrap();
+
+        let result = graph.execute_and_capture_results();
+        assert_eq!(result.error_node_id.as_deref(), Some("failing"));
+        // The message keeps the format logs used before the structured variant
+        assert_eq!(
+            result.error_message.as_deref(),
+            Some("Validation error: [NODE_ERROR:failing] Invalid node input: boom")
+        );
+    }
+
+    #[test]
+    fn test_node_execution_keeps_innermost_node() {
+        let inner = crate::error::Error::node_execution(
+            "inner",
+            crate::error::Error::InvalidNodeInput("boom".to_string()),
+        );
+        let wrapped = crate::error::Error::node_execution("outer", inner);
+        assert_eq!(wrapped.node_id(), Some("inner"));
+
+        let timeout = crate::error::Error::Timeout { node_id: "slow".to_string() };
+        let wrapped = crate::error::Error::node_execution("outer", timeout);
+        assert_eq!(wrapped.node_id(), Some("slow"));
+    }
+
+    /// Pure node that counts how often it actually runs
+    struct CountingPureNode {
+        runs: Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    impl Node for CountingPureNode {
+        fn id(&self) -> &str {
+            "counting_pure"
+        }
+
+        fn name(&self) -> &str {
+            "CountingPure"
+        }
+
+        fn is_pure(&self) -> bool {
+            true
+        }
+
+        node_input![
+            port! { name = "text", ty = String, desc = "text" },
+        ];
+
+        node_output![
+            port! { name = "length", ty = Integer, desc = "length" },
+        ];
+
+        fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+            self.runs.fetch_add(1, Ordering::SeqCst);
+            let length = match inputs.get("text") {
+                Some(DataValue::String(s)) => s.len() as i64,
+                _ => 0,
+            };
+            Ok(HashMap::from([("length".to_string(), DataValue::Integer(length))]))
+        }
+    }
+
+    #[test]
+    fn test_pure_node_outputs_are_memoized() {
+        let runs = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let mut graph = NodeGraph::new();
+        graph.add_node(Box::new(CountingPureNode { runs: runs.clone() })).unwrap();
+        let set_text = |graph: &mut NodeGraph, text: &str| {
+            graph.inline_values.insert(
+                "counting_pure".to_string(),
+                HashMap::from([("text".to_string(), DataValue::String(text.to_string()))]),
+            );
+        };
+        set_text(&mut graph, "hello");
+
+        // Caching is opt-in
+        graph.execute().unwrap();
+        graph.execute().unwrap();
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+
+        graph.set_caching_enabled(true);
+        graph.execute().unwrap();
+        let result = graph.execute_and_capture_results();
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+        assert!(matches!(result.node_results["counting_pure"].get("length"), Some(DataValue::Integer(5))));
+
+        // Changed inputs run the node again
+        set_text(&mut graph, "hi");
+        graph.execute().unwrap();
+        assert_eq!(runs.load(Ordering::SeqCst), 4);
+
+        graph.clear_cache();
+        graph.execute().unwrap();
+        assert_eq!(runs.load(Ordering::SeqCst), 5);
+    }
+
+    /// Outputs a fixed value on its `value` port
+    struct ConstNode {
+        id: String,
+        value: DataValue,
+    }
+
+    impl Node for ConstNode {
+        fn id(&self) -> &str {
+            &self.id
+        }
+
+        fn name(&self) -> &str {
+            "Const"
+        }
+
+        fn input_ports(&self) -> Vec<Port> {
+            Vec::new()
+        }
+
+        fn output_ports(&self) -> Vec<Port> {
+            vec![Port::new("value", self.value.data_type())]
+        }
+
+        fn execute(&mut self, _inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+            Ok(HashMap::from([("value".to_string(), self.value.clone())]))
+        }
+    }
+
+    /// Joins its `items` list input
+    struct JoinNode;
+
+    impl Node for JoinNode {
+        fn id(&self) -> &str {
+            "join"
+        }
+
+        fn name(&self) -> &str {
+            "Join"
+        }
+
+        node_input![
+            port! { name = "items", ty = List(String), desc = "items" },
+        ];
+
+        node_output![
+            port! { name = "joined", ty = String, desc = "joined" },
+        ];
+
+        fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+            self.validate_inputs(&inputs)?;
+            let joined = match inputs.get("items") {
+                Some(DataValue::List(items)) => items
+                    .iter()
+                    .map(|item| match item {
+                        DataValue::String(s) => s.clone(),
+                        other => format!("{:?}", other),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(","),
+                _ => String::new(),
+            };
+            Ok(HashMap::from([("joined".to_string(), DataValue::String(joined))]))
+        }
+    }
+
+    fn edge(from: &str, to_port: &str) -> EdgeDefinition {
+        EdgeDefinition {
+            from_node_id: from.to_string(),
+            from_port: "value".to_string(),
+            to_node_id: "join".to_string(),
+            to_port: to_port.to_string(),
+            condition: None,
+        }
+    }
+
+    #[test]
+    fn test_string_edges_merge_into_list_input() {
+        let mut graph = NodeGraph::new();
+        for (id, value) in [("c", "third"), ("a", "first"), ("b", "second")] {
+            graph
+                .add_node(Box::new(ConstNode {
+                    id: id.to_string(),
+                    value: DataValue::String(value.to_string()),
+                }))
+                .unwrap();
+        }
+        graph.add_node(Box::new(JoinNode)).unwrap();
+        graph.set_edges(vec![edge("c", "items"), edge("a", "items"), edge("b", "items")]);
+
+        let result = graph.execute_and_capture_results();
+        assert_eq!(result.error_message, None);
+        // Values are ordered by source node id, not by edge order
+        assert!(matches!(
+            result.node_results["join"].get("joined"),
+            Some(DataValue::String(s)) if s == "first,second,third"
+        ));
+    }
+
+    #[test]
+    fn test_merge_rejects_mismatched_element_type() {
+        let mut graph = NodeGraph::new();
+        graph
+            .add_node(Box::new(ConstNode {
+                id: "a".to_string(),
+                value: DataValue::String("text".to_string()),
+            }))
+            .unwrap();
+        graph
+            .add_node(Box::new(ConstNode {
+                id: "n".to_string(),
+                value: DataValue::Integer(1),
+            }))
+            .unwrap();
+        graph.add_node(Box::new(JoinNode)).unwrap();
+        graph.set_edges(vec![edge("a", "items"), edge("n", "items")]);
+
+        let err = graph.execute().unwrap_err().to_string();
+        assert!(err.contains("Port type mismatch"), "{}", err);
+    }
+
+    /// Run one StringDataNode feeding three PreviewStringNodes and return the
+    /// text each preview received through the execution callback
+    fn run_fan_out(source_id: &str, with_edges: bool) -> Vec<(String, DataValue)> {
+        use crate::node::util_nodes::{PreviewStringNode, StringDataNode};
+        use std::sync::Mutex;
+
+        let mut graph = NodeGraph::new();
+        graph.add_node(Box::new(StringDataNode::new(source_id, "Source"))).unwrap();
+        graph.inline_values.insert(
+            source_id.to_string(),
+            HashMap::from([("value".to_string(), DataValue::String("hello".to_string()))]),
+        );
+        let preview_ids = ["preview_1", "preview_2", "preview_3"];
+        for id in preview_ids {
+            graph.add_node(Box::new(PreviewStringNode::new(id, "Preview"))).unwrap();
+        }
+        if with_edges {
+            graph.set_edges(
+                preview_ids
+                    .iter()
+                    .map(|id| EdgeDefinition {
+                        from_node_id: source_id.to_string(),
+                        from_port: "text".to_string(),
+                        to_node_id: id.to_string(),
+                        to_port: "text".to_string(),
+                        condition: None,
+                    })
+                    .collect(),
+            );
+        }
+
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let sink = received.clone();
+        graph.set_execution_callback(move |_phase, node_id, inputs, _outputs| {
+            match inputs.get("text") {
+                Some(text) if node_id.starts_with("preview") => {
+                    sink.lock().unwrap().push((node_id.to_string(), text.clone()))
+                }
+                _ => {}
+            }
+        });
+
+        graph.execute().unwrap();
+        let mut received = received.lock().unwrap().clone();
+        received.sort_by(|a, b| a.0.cmp(&b.0));
+        received
+    }
+
+    #[test]
+    fn test_output_fans_out_to_many_inputs() {
+        for with_edges in [false, true] {
+            let source_id = format!("fan_out_source_{}", with_edges);
+            let received = run_fan_out(&source_id, with_edges);
+            let ids: Vec<&str> = received.iter().map(|(id, _)| id.as_str()).collect();
+            assert_eq!(ids, ["preview_1", "preview_2", "preview_3"], "with_edges = {}", with_edges);
+            assert!(received
+                .iter()
+                .all(|(_, text)| matches!(text, DataValue::String(s) if s == "hello")));
+        }
+    }
+
+    #[test]
+    fn test_validate_reports_unbound_required_ports() {
+        let mut graph = NodeGraph::new();
+        graph.add_node(Box::new(JoinNode)).unwrap();
+        assert_eq!(
+            graph.validate(),
+            [("join".to_string(), "items".to_string(), "Required input is not bound".to_string())]
+        );
+
+        graph.inline_values.insert(
+            "join".to_string(),
+            HashMap::from([("items".to_string(), DataValue::List(Vec::new()))]),
+        );
+        assert!(graph.validate().is_empty());
+    }
+
+    #[test]
+    fn test_validate_reports_edge_type_mismatch() {
+        let mut graph = NodeGraph::new();
+        graph
+            .add_node(Box::new(ConstNode {
+                id: "number".to_string(),
+                value: DataValue::Integer(3),
+            }))
+            .unwrap();
+        graph
+            .add_node(Box::new(ConstNode {
+                id: "text".to_string(),
+                value: DataValue::String("a".to_string()),
+            }))
+            .unwrap();
+        graph.add_node(Box::new(JoinNode)).unwrap();
+        graph.set_edges(vec![edge("number", "items"), edge("text", "items")]);
+
+        let problems = graph.validate();
+        assert_eq!(problems.len(), 1);
+        let (node_id, port, reason) = &problems[0];
+        assert_eq!((node_id.as_str(), port.as_str()), ("join", "items"));
+        assert!(reason.contains("type mismatch"), "{}", reason);
+    }
+
+    /// Passes its required `text` input through
+    struct EchoNode {
+        id: String,
+    }
+
+    impl Node for EchoNode {
+        fn id(&self) -> &str {
+            &self.id
+        }
+
+        fn name(&self) -> &str {
+            "Echo"
+        }
+
+        node_input![
+            port! { name = "text", ty = String, desc = "text" },
+        ];
+
+        node_output![
+            port! { name = "text", ty = String, desc = "text" },
+        ];
+
+        fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+            self.validate_inputs(&inputs)?;
+            Ok(inputs)
+        }
+    }
+
+    fn run_gated_chain(open: bool) -> ExecutionResult {
+        let mut graph = NodeGraph::new();
+        graph
+            .add_node(Box::new(ConstNode {
+                id: "flag".to_string(),
+                value: DataValue::Boolean(open),
+            }))
+            .unwrap();
+        graph
+            .add_node(Box::new(ConstNode {
+                id: "source".to_string(),
+                value: DataValue::String("hi".to_string()),
+            }))
+            .unwrap();
+        for id in ["echo", "echo_after"] {
+            graph.add_node(Box::new(EchoNode { id: id.to_string() })).unwrap();
+        }
+        graph.set_edges(vec![
+            EdgeDefinition {
+                from_node_id: "source".to_string(),
+                from_port: "value".to_string(),
+                to_node_id: "echo".to_string(),
+                to_port: "text".to_string(),
+                condition: Some(EdgeCondition {
+                    node_id: "flag".to_string(),
+                    port: "value".to_string(),
+                }),
+            },
+            EdgeDefinition {
+                from_node_id: "echo".to_string(),
+                from_port: "text".to_string(),
+                to_node_id: "echo_after".to_string(),
+                to_port: "text".to_string(),
+                condition: None,
+            },
+        ]);
+        graph.execute_and_capture_results()
+    }
+
+    #[test]
+    fn test_false_edge_condition_skips_downstream_nodes() {
+        let result = run_gated_chain(false);
+        assert_eq!(result.error_message, None);
+        assert!(result.node_results.contains_key("source"));
+        assert!(!result.node_results.contains_key("echo"));
+        assert!(!result.node_results.contains_key("echo_after"));
+
+        let result = run_gated_chain(true);
+        assert_eq!(result.error_message, None);
+        assert!(matches!(
+            result.node_results["echo_after"].get("text"),
+            Some(DataValue::String(s)) if s == "hi"
+        ));
+    }
+
+    #[test]
+    fn test_edge_condition_defaults_to_none_in_json() {
+        let edge: EdgeDefinition = serde_json::from_value(serde_json::json!({
+            "from_node_id": "a",
+            "from_port": "out",
+            "to_node_id": "b",
+            "to_port": "in",
+        }))
+        .unwrap();
+        assert!(edge.condition.is_none());
+        assert!(serde_json::to_value(&edge).unwrap().get("condition").is_none());
+    }
+
+    /// Emits `text` forever, counting updates and cleanups
+    struct TickProducer {
+        updates: Arc<std::sync::atomic::AtomicUsize>,
+        cleaned_up: Arc<AtomicBool>,
+    }
+
+    impl Node for TickProducer {
+        fn node_type(&self) -> NodeType {
+            NodeType::EventProducer
+        }
+
+        fn id(&self) -> &str {
+            "ticker"
+        }
+
+        fn name(&self) -> &str {
+            "Ticker"
+        }
+
+        node_input![];
+
+        node_output![
+            port! { name = "text", ty = String, desc = "tick" },
+        ];
+
+        fn execute(&mut self, _inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+            Ok(HashMap::new())
+        }
+
+        fn on_update(&mut self) -> Result<Option<HashMap<String, DataValue>>> {
+            self.updates.fetch_add(1, Ordering::SeqCst);
+            Ok(Some(HashMap::from([("text".to_string(), DataValue::String("tick".to_string()))])))
+        }
+
+        fn on_cleanup(&mut self) -> Result<()> {
+            self.cleaned_up.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_event_producer_stops_at_max_iterations() {
+        for with_edges in [false, true] {
+            let updates = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+            let cleaned_up = Arc::new(AtomicBool::new(false));
+            let mut graph = NodeGraph::new();
+            graph
+                .add_node(Box::new(TickProducer {
+                    updates: updates.clone(),
+                    cleaned_up: cleaned_up.clone(),
+                }))
+                .unwrap();
+            graph
+                .add_node(Box::new(crate::node::util_nodes::PreviewStringNode::new("echo", "Preview")))
+                .unwrap();
+            if with_edges {
+                graph.set_edges(vec![EdgeDefinition {
+                    from_node_id: "ticker".to_string(),
+                    from_port: "text".to_string(),
+                    to_node_id: "echo".to_string(),
+                    to_port: "text".to_string(),
+                    condition: None,
+                }]);
+            }
+            let echoes = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+            let counter = echoes.clone();
+            graph.set_execution_callback(move |_phase, node_id, _inputs, _outputs| {
+                if node_id == "echo" {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                }
+            });
+            graph.set_event_producer_max_iterations(Some(3));
+
+            graph.execute().unwrap();
+            assert_eq!(updates.load(Ordering::SeqCst), 3, "with_edges = {}", with_edges);
+            assert_eq!(echoes.load(Ordering::SeqCst), 3, "with_edges = {}", with_edges);
+            assert!(cleaned_up.load(Ordering::SeqCst));
+        }
+    }
+
+    /// Poll `condition` until it holds, failing after a few seconds
+    fn wait_for(condition: &dyn Fn() -> bool) {
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while !condition() {
+            assert!(std::time::Instant::now() < deadline, "timed out");
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_paused_event_producer_waits_without_cleanup() {
+        let updates = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let cleaned_up = Arc::new(AtomicBool::new(false));
+        let mut graph = NodeGraph::new();
+        graph
+            .add_node(Box::new(TickProducer {
+                updates: updates.clone(),
+                cleaned_up: cleaned_up.clone(),
+            }))
+            .unwrap();
+        let (pause_flag, stop_flag) = (graph.get_pause_flag(), graph.get_stop_flag());
+
+        graph.request_pause();
+        let runner = std::thread::spawn(move || {
+            graph.execute().unwrap();
+            graph
+        });
+        std::thread::sleep(PAUSE_POLL_INTERVAL * 3);
+        assert_eq!(updates.load(Ordering::SeqCst), 0);
+        assert!(!cleaned_up.load(Ordering::SeqCst));
+
+        pause_flag.store(false, Ordering::Relaxed);
+        wait_for(&|| updates.load(Ordering::SeqCst) >= 3);
+        pause_flag.store(true, Ordering::Relaxed);
+        std::thread::sleep(PAUSE_POLL_INTERVAL);
+        let paused_at = updates.load(Ordering::SeqCst);
+        std::thread::sleep(PAUSE_POLL_INTERVAL * 3);
+        assert_eq!(updates.load(Ordering::SeqCst), paused_at);
+        assert!(!cleaned_up.load(Ordering::SeqCst));
+
+        // Stopping a paused graph ends it without another update
+        stop_flag.store(true, Ordering::Relaxed);
+        let graph = runner.join().unwrap();
+        assert_eq!(updates.load(Ordering::SeqCst), paused_at);
+        assert!(cleaned_up.load(Ordering::SeqCst));
+        graph.resume();
+        assert!(!graph.get_pause_flag().load(Ordering::Relaxed));
+    }
+
+    /// `source` -> `first` -> `second`, passing on "hi"
+    fn linear_chain() -> NodeGraph {
+        let mut graph = NodeGraph::new();
+        graph
+            .add_node(Box::new(ConstNode {
+                id: "source".to_string(),
+                value: DataValue::String("hi".to_string()),
+            }))
+            .unwrap();
+        for id in ["first", "second"] {
+            graph.add_node(Box::new(EchoNode { id: id.to_string() })).unwrap();
+        }
+        graph.set_edges(
+            [("source", "value", "first"), ("first", "text", "second")]
+                .into_iter()
+                .map(|(from, from_port, to)| EdgeDefinition {
+                    from_node_id: from.to_string(),
+                    from_port: from_port.to_string(),
+                    to_node_id: to.to_string(),
+                    to_port: "text".to_string(),
+                    condition: None,
+                })
+                .collect(),
+        );
+        graph
+    }
+
+    #[test]
+    fn test_step_mode_runs_one_node_per_step() {
+        let mut graph = linear_chain();
+        graph.set_step_mode(true);
+        let stepper = graph.get_stepper().unwrap();
+
+        let runner = std::thread::spawn(move || graph.execute().is_ok());
+        let hi = DataValue::String("hi".to_string());
+        let source = stepper.step().unwrap();
+        assert_eq!(source.node_id, "source");
+        assert!(source.inputs.is_empty());
+        assert_eq!(source.outputs.get("value"), Some(&hi));
+
+        // The graph waits for the next step instead of running on
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        assert!(!runner.is_finished());
+
+        for id in ["first", "second"] {
+            let record = stepper.step().unwrap();
+            assert_eq!(record.node_id, id);
+            assert_eq!(record.inputs.get("text"), Some(&hi));
+            assert_eq!(record.outputs.get("text"), Some(&hi));
+        }
+        assert!(stepper.step().is_none());
+        assert!(runner.join().unwrap());
+    }
+
+    #[test]
+    fn test_breakpoint_halts_before_node_until_continued() {
+        let mut edgeless = NodeGraph::new();
+        edgeless
+            .add_node(Box::new(ConstNode {
+                id: "first".to_string(),
+                value: DataValue::String("hi".to_string()),
+            }))
+            .unwrap();
+
+        for mut graph in [linear_chain(), edgeless] {
+            let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+            let sink = events.clone();
+            graph.set_execution_callback(move |phase, node_id, inputs, _outputs| {
+                sink.lock().unwrap().push((phase, node_id.to_string(), inputs.get("text").cloned()));
+            });
+            graph.add_breakpoint("first");
+            let control = graph.get_breakpoint_control();
+
+            let runner = std::thread::spawn(move || graph.execute().is_ok());
+            wait_for(&|| control.halted_at().is_some());
+            assert_eq!(control.halted_at().as_deref(), Some("first"));
+            std::thread::sleep(std::time::Duration::from_millis(100));
+            assert!(!runner.is_finished());
+            let (phase, node_id, _) = events.lock().unwrap().last().cloned().unwrap();
+            assert_eq!((phase, node_id.as_str()), (ExecutionPhase::AboutToExecute, "first"));
+
+            control.continue_execution();
+            assert!(runner.join().unwrap());
+            assert_eq!(control.halted_at(), None);
+            let events = events.lock().unwrap();
+            let executed: Vec<&str> = events
+                .iter()
+                .filter(|(phase, _, _)| *phase == ExecutionPhase::Executed)
+                .map(|(_, node_id, _)| node_id.as_str())
+                .collect();
+            assert!(executed.contains(&"first"));
+        }
+
+        // Prepared inputs are visible at the breakpoint
+        let mut graph = linear_chain();
+        let prepared = Arc::new(std::sync::Mutex::new(None));
+        let sink = prepared.clone();
+        graph.set_execution_callback(move |phase, _node_id, inputs, _outputs| {
+            if phase == ExecutionPhase::AboutToExecute {
+                *sink.lock().unwrap() = inputs.get("text").cloned();
+            }
+        });
+        graph.add_breakpoint("second");
+        let control = graph.get_breakpoint_control();
+        let runner = std::thread::spawn(move || graph.execute().is_ok());
+        wait_for(&|| control.halted_at().is_some());
+        assert_eq!(*prepared.lock().unwrap(), Some(DataValue::String("hi".to_string())));
+        control.continue_execution();
+        assert!(runner.join().unwrap());
+    }
+
+    /// Event producer emitting either a `message` or a `notice`, alternately
+    struct AlternatingProducer {
+        updates: usize,
+    }
+
+    impl Node for AlternatingProducer {
+        fn node_type(&self) -> NodeType {
+            NodeType::EventProducer
+        }
+
+        fn id(&self) -> &str {
+            "events"
+        }
+
+        fn name(&self) -> &str {
+            "Events"
+        }
+
+        node_input![];
+
+        node_output![
+            port! { name = "message", ty = String, desc = "message" },
+            port! { name = "notice", ty = String, desc = "notice" },
+        ];
+
+        fn execute(&mut self, _inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+            Ok(HashMap::new())
+        }
+
+        fn on_update(&mut self) -> Result<Option<HashMap<String, DataValue>>> {
+            self.updates += 1;
+            let port = if self.updates % 2 == 1 { "message" } else { "notice" };
+            Ok(Some(HashMap::from([(port.to_string(), DataValue::String(port.to_string()))])))
+        }
+    }
+
+    #[test]
+    fn test_unset_output_port_skips_its_branch() {
+        let mut graph = NodeGraph::new();
+        graph.add_node(Box::new(AlternatingProducer { updates: 0 })).unwrap();
+        for port in ["message", "notice"] {
+            graph.add_node(Box::new(EchoNode { id: port.to_string() })).unwrap();
+        }
+        graph.set_edges(
+            ["message", "notice"]
+                .into_iter()
+                .map(|port| EdgeDefinition {
+                    from_node_id: "events".to_string(),
+                    from_port: port.to_string(),
+                    to_node_id: port.to_string(),
+                    to_port: "text".to_string(),
+                    condition: None,
+                })
+                .collect(),
+        );
+        let runs = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = runs.clone();
+        graph.set_execution_callback(move |_phase, node_id, _inputs, _outputs| {
+            if node_id != "events" {
+                recorded.lock().unwrap().push(node_id.to_string());
+            }
+        });
+        graph.set_event_producer_max_iterations(Some(4));
+
+        graph.execute().unwrap();
+        assert_eq!(*runs.lock().unwrap(), vec!["message", "notice", "message", "notice"]);
+    }
+
+    /// Event producer emitting the given updates in order, then stopping
+    struct ScriptedProducer {
+        updates: std::collections::VecDeque<HashMap<String, DataValue>>,
+    }
+
+    impl ScriptedProducer {
+        fn new(updates: Vec<Vec<(&str, &str)>>) -> Self {
+            let updates = updates
+                .into_iter()
+                .map(|ports| {
+                    ports
+                        .into_iter()
+                        .map(|(port, value)| (port.to_string(), DataValue::String(value.to_string())))
+                        .collect()
+                })
+                .collect();
+            Self { updates }
+        }
+    }
+
+    impl Node for ScriptedProducer {
+        fn node_type(&self) -> NodeType {
+            NodeType::EventProducer
+        }
+
+        fn id(&self) -> &str {
+            "adapter"
+        }
+
+        fn name(&self) -> &str {
+            "Adapter"
+        }
+
+        node_input![];
+
+        node_output![
+            port! { name = "message_event", ty = String, desc = "message", optional },
+            port! { name = "notice_event", ty = String, desc = "notice", optional },
+            port! { name = "connection_status", ty = String, desc = "status", optional },
+        ];
+
+        fn execute(&mut self, _inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+            Ok(HashMap::new())
+        }
+
+        fn on_update(&mut self) -> Result<Option<HashMap<String, DataValue>>> {
+            Ok(self.updates.pop_front())
+        }
+    }
+
+    /// Requires a `message_event`, like the nodes behind a bot adapter's message port
+    struct MessageReader;
+
+    impl Node for MessageReader {
+        fn id(&self) -> &str {
+            "reader"
+        }
+
+        fn name(&self) -> &str {
+            "Reader"
+        }
+
+        node_input![
+            port! { name = "message_event", ty = String, desc = "message" },
+        ];
+
+        node_output![
+            port! { name = "reply", ty = String, desc = "reply" },
+        ];
+
+        fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+            self.validate_inputs(&inputs)?;
+            Ok(HashMap::from([("reply".to_string(), inputs["message_event"].clone())]))
+        }
+    }
+
+    /// Run an edgeless graph of a scripted producer and a message reader, returning the replies
+    fn run_edgeless_reader(updates: Vec<Vec<(&str, &str)>>) -> Vec<DataValue> {
+        let mut graph = NodeGraph::new();
+        graph.add_node(Box::new(ScriptedProducer::new(updates))).unwrap();
+        graph.add_node(Box::new(MessageReader)).unwrap();
+        let replies = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = replies.clone();
+        graph.set_execution_callback(move |_phase, node_id, _inputs, outputs| {
+            if node_id == "reader" {
+                recorded.lock().unwrap().push(outputs["reply"].clone());
+            }
+        });
+
+        let result = graph.execute_and_capture_results();
+        assert!(result.error_message.is_none(), "{:?}", result.error_message);
+        let replies = replies.lock().unwrap().clone();
+        replies
+    }
+
+    #[test]
+    fn test_edgeless_notice_skips_message_consumers() {
+        let replies = run_edgeless_reader(vec![
+            vec![("notice_event", "poke")],
+            vec![("message_event", "hello")],
+        ]);
+        assert_eq!(replies, vec![DataValue::String("hello".to_string())]);
+    }
+
+    #[test]
+    fn test_edgeless_health_update_skips_message_consumers() {
+        let replies = run_edgeless_reader(vec![
+            vec![("connection_status", "Reconnecting")],
+            vec![("message_event", "hello"), ("connection_status", "Connected")],
+            vec![("connection_status", "Disconnected")],
+        ]);
+        assert_eq!(replies, vec![DataValue::String("hello".to_string())]);
+    }
+
+    /// Event producer whose `handle` output is known as soon as it starts
+    struct HandleProducer;
+
+    impl Node for HandleProducer {
+        fn node_type(&self) -> NodeType {
+            NodeType::EventProducer
+        }
+
+        fn id(&self) -> &str {
+            "producer"
+        }
+
+        fn name(&self) -> &str {
+            "Producer"
+        }
+
+        node_input![];
+
+        node_output![
+            port! { name = "handle", ty = String, desc = "handle" },
+            port! { name = "message", ty = String, desc = "message" },
+        ];
+
+        fn execute(&mut self, _inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+            Ok(HashMap::new())
+        }
+
+        fn start_outputs(&self) -> HashMap<String, DataValue> {
+            HashMap::from([("handle".to_string(), DataValue::String("h".to_string()))])
+        }
+
+        fn on_update(&mut self) -> Result<Option<HashMap<String, DataValue>>> {
+            let mut outputs = self.start_outputs();
+            outputs.insert("message".to_string(), DataValue::String("m".to_string()));
+            Ok(Some(outputs))
+        }
+    }
+
+    /// Logs `on_graph_start` and `execute` calls
+    struct LifecycleLog {
+        id: String,
+        input: &'static str,
+        log: Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl Node for LifecycleLog {
+        fn id(&self) -> &str {
+            &self.id
+        }
+
+        fn name(&self) -> &str {
+            "Log"
+        }
+
+        fn input_ports(&self) -> Vec<Port> {
+            vec![Port::new(self.input, DataType::String)]
+        }
+
+        node_output![];
+
+        fn execute(&mut self, _inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+            self.log.lock().unwrap().push(format!("{} execute", self.id));
+            Ok(HashMap::new())
+        }
+
+        fn on_graph_start(&mut self, inputs: HashMap<String, DataValue>) -> Result<()> {
+            assert!(inputs.contains_key(self.input));
+            self.log.lock().unwrap().push(format!("{} start", self.id));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_nodes_fed_by_start_outputs_get_graph_start() {
+        for with_edges in [false, true] {
+            let log = Arc::new(std::sync::Mutex::new(Vec::new()));
+            let mut graph = NodeGraph::new();
+            graph.add_node(Box::new(HandleProducer)).unwrap();
+            for (id, input) in [("on_handle", "handle"), ("on_message", "message")] {
+                graph
+                    .add_node(Box::new(LifecycleLog { id: id.to_string(), input, log: log.clone() }))
+                    .unwrap();
+            }
+            if with_edges {
+                graph.set_edges(
+                    [("handle", "on_handle"), ("message", "on_message")]
+                        .into_iter()
+                        .map(|(port, to)| EdgeDefinition {
+                            from_node_id: "producer".to_string(),
+                            from_port: port.to_string(),
+                            to_node_id: to.to_string(),
+                            to_port: port.to_string(),
+                            condition: None,
+                        })
+                        .collect(),
+                );
+            }
+            graph.set_event_producer_max_iterations(Some(1));
+
+            graph.execute().unwrap();
+            let mut log = log.lock().unwrap().clone();
+            // Only the node whose inputs exist before the first event is started, and first
+            assert_eq!(log.remove(0), "on_handle start", "with_edges = {}", with_edges);
+            log.sort();
+            assert_eq!(log, ["on_handle execute", "on_message execute"], "with_edges = {}", with_edges);
+        }
+    }
+
+    #[test]
+    fn test_execution_result_records_node_timings() {
+        let result = run_gated_chain(false);
+        let mut timed: Vec<&str> = result.timings.keys().map(String::as_str).collect();
+        timed.sort();
+        // Skipped nodes never ran, so they have no timing
+        assert_eq!(timed, ["flag", "source"]);
+
+        let mut graph = NodeGraph::new();
+        graph.add_node(Box::new(SleepNode { id: "slow".to_string(), millis: 30 })).unwrap();
+        graph.add_node(Box::new(SleepNode { id: "fast".to_string(), millis: 0 })).unwrap();
+        let result = graph.execute_and_capture_results();
+        let slow = result.timings["slow"];
+        assert!(slow >= std::time::Duration::from_millis(30), "{:?}", slow);
+        assert!(result.timings["fast"] < slow);
+        assert!(result.total_duration() >= slow);
+        assert!(result.total_duration() <= result.duration.unwrap());
+    }
+
+    /// Sleeps for a fixed time when executed
+    struct SleepNode {
+        id: String,
+        millis: u64,
+    }
+
+    impl Node for SleepNode {
+        fn id(&self) -> &str {
+            &self.id
+        }
+
+        fn name(&self) -> &str {
+            "Sleep"
+        }
+
+        node_input![];
+
+        node_output![];
+
+        fn execute(&mut self, _inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+            std::thread::sleep(std::time::Duration::from_millis(self.millis));
+            Ok(HashMap::new())
+        }
+    }
+
+    #[test]
+    fn test_execution_order_without_running() {
+        let updates = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let mut graph = NodeGraph::new();
+        graph
+            .add_node(Box::new(TickProducer {
+                updates: updates.clone(),
+                cleaned_up: Arc::new(AtomicBool::new(false)),
+            }))
+            .unwrap();
+        graph.add_node(Box::new(EchoNode { id: "echo".to_string() })).unwrap();
+        graph.add_node(Box::new(EchoNode { id: "after".to_string() })).unwrap();
+        graph.set_edges(vec![
+            EdgeDefinition {
+                from_node_id: "echo".to_string(),
+                from_port: "text".to_string(),
+                to_node_id: "after".to_string(),
+                to_port: "text".to_string(),
+                condition: None,
+            },
+            EdgeDefinition {
+                from_node_id: "ticker".to_string(),
+                from_port: "text".to_string(),
+                to_node_id: "echo".to_string(),
+                to_port: "text".to_string(),
+                condition: None,
+            },
+        ]);
+
+        assert_eq!(graph.execution_order().unwrap(), ["ticker", "echo", "after"]);
+        assert_eq!(updates.load(Ordering::SeqCst), 0);
+
+        graph.set_edges(vec![
+            EdgeDefinition {
+                from_node_id: "echo".to_string(),
+                from_port: "text".to_string(),
+                to_node_id: "after".to_string(),
+                to_port: "text".to_string(),
+                condition: None,
+            },
+            EdgeDefinition {
+                from_node_id: "after".to_string(),
+                from_port: "text".to_string(),
+                to_node_id: "echo".to_string(),
+                to_port: "text".to_string(),
+                condition: None,
+            },
+        ]);
+        let err = graph.execution_order().unwrap_err().to_string();
+        assert!(err.contains("Cycle detected"), "{}", err);
+    }
+
+    #[test]
+    fn test_auto_coerce_converts_edge_values() {
+        let build = |auto_coerce: bool| {
+            let mut graph = NodeGraph::new();
+            graph
+                .add_node(Box::new(ConstNode {
+                    id: "number".to_string(),
+                    value: DataValue::Integer(42),
+                }))
+                .unwrap();
+            graph.add_node(Box::new(EchoNode { id: "echo".to_string() })).unwrap();
+            graph.add_node(Box::new(JoinNode)).unwrap();
+            graph.set_edges(vec![
+                EdgeDefinition {
+                    from_node_id: "number".to_string(),
+                    from_port: "value".to_string(),
+                    to_node_id: "echo".to_string(),
+                    to_port: "text".to_string(),
+                    condition: None,
+                },
+                edge("number", "items"),
+            ]);
+            graph.set_auto_coerce(auto_coerce);
+            graph
+        };
+
+        let err = build(false).execute().unwrap_err().to_string();
+        assert!(err.contains("Port type mismatch"), "{}", err);
+
+        let result = build(true).execute_and_capture_results();
+        assert_eq!(result.error_message, None);
+        assert!(matches!(result.node_results["echo"].get("text"), Some(DataValue::String(s)) if s == "42"));
+        assert!(matches!(result.node_results["join"].get("joined"), Some(DataValue::String(s)) if s == "42"));
+    }
+
+    /// Greets its `name` input, which defaults to "world"
+    struct GreetNode;
+
+    impl Node for GreetNode {
+        fn id(&self) -> &str {
+            "greet"
+        }
+
+        fn name(&self) -> &str {
+            "Greet"
+        }
+
+        node_input![
+            port! { name = "name", ty = String, desc = "name", optional, default = DataValue::String("world".to_string()) },
+        ];
+
+        node_output![
+            port! { name = "greeting", ty = String, desc = "greeting" },
+        ];
+
+        fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+            self.validate_inputs(&inputs)?;
+            let name = match inputs.get("name") {
+                Some(DataValue::String(name)) => name.clone(),
+                _ => String::new(),
+            };
+            Ok(HashMap::from([("greeting".to_string(), DataValue::String(format!("hello {}", name)))]))
+        }
+    }
+
+    #[test]
+    fn test_port_default_fills_missing_input() {
+        let mut graph = NodeGraph::new();
+        graph.add_node(Box::new(GreetNode)).unwrap();
+        let result = graph.execute_and_capture_results();
+        assert_eq!(
+            result.node_results["greet"].get("greeting"),
+            Some(&DataValue::String("hello world".to_string()))
+        );
+
+        // An inline value wins over the default
+        graph.inline_values.insert(
+            "greet".to_string(),
+            HashMap::from([("name".to_string(), DataValue::String("bob".to_string()))]),
+        );
+        let result = graph.execute_and_capture_results();
+        assert_eq!(
+            result.node_results["greet"].get("greeting"),
+            Some(&DataValue::String("hello bob".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_port_default_survives_serialization() {
+        let port = Port::new("threshold", DataType::Integer).with_default(DataValue::Integer(3));
+        let json = serde_json::to_value(&port).unwrap();
+        assert_eq!(json["default"], serde_json::json!(3));
+        let restored: Port = serde_json::from_value(json).unwrap();
+        assert_eq!(restored.default, Some(DataValue::Integer(3)));
+
+        // Ports saved before defaults existed still load
+        let legacy = serde_json::json!({
+            "name": "text",
+            "data_type": "String",
+            "description": null,
+            "required": true,
+        });
+        let restored: Port = serde_json::from_value(legacy).unwrap();
+        assert_eq!(restored.default, None);
+    }
+
+    /// Only declares a `mode` input restricted to two choices
+    struct CaseNode;
+
+    impl Node for CaseNode {
+        fn id(&self) -> &str {
+            "case"
+        }
+
+        fn name(&self) -> &str {
+            "Case"
+        }
+
+        node_input![
+            port! { name = "mode", ty = String, desc = "mode", choices = ["upper", "lower"] },
+        ];
+
+        node_output![];
+
+        fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+            self.validate_inputs(&inputs)?;
+            Ok(HashMap::new())
+        }
+    }
+
+    #[test]
+    fn test_port_description_survives_save_and_load() {
+        let mut graph = NodeGraph::new();
+        graph.add_node(Box::new(CaseNode)).unwrap();
+        let definition = NodeGraphDefinition::from_node_graph(&graph);
+
+        let path = std::env::temp_dir().join(format!("port_description_{}.json", std::process::id()));
+        graph_io::save_graph_definition_to_json(&path, &definition).unwrap();
+        let loaded = graph_io::load_graph_definition_from_json(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let port = &loaded.unwrap().nodes[0].input_ports[0];
+        assert_eq!(port.name, "mode");
+        assert_eq!(port.description.as_deref(), Some("mode"));
+        assert_eq!(port.data_type, DataType::String);
+    }
+
+    #[test]
+    fn test_choice_port_rejects_unknown_value() {
+        let node = CaseNode;
+        assert_eq!(node.input_ports()[0].allowed, Some(vec!["upper".to_string(), "lower".to_string()]));
+
+        let inputs = |mode: &str| HashMap::from([("mode".to_string(), DataValue::String(mode.to_string()))]);
+        assert!(node.validate_inputs(&inputs("lower")).is_ok());
+        let err = node.validate_inputs(&inputs("title")).unwrap_err().to_string();
+        assert!(err.contains("Input port 'mode' expects one of [upper, lower], got 'title'"), "{}", err);
+    }
+
+    /// Declares bounded numeric inputs
+    struct BoundedNode;
+
+    impl Node for BoundedNode {
+        fn id(&self) -> &str {
+            "bounded"
+        }
+
+        fn name(&self) -> &str {
+            "Bounded"
+        }
+
+        node_input![
+            port! { name = "port", ty = Integer, desc = "port", min = 1, max = 65535 },
+            port! { name = "ratio", ty = Float, desc = "ratio", optional, min = -1 },
+        ];
+
+        node_output![];
+
+        fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+            self.validate_inputs(&inputs)?;
+            Ok(HashMap::new())
+        }
+    }
+
+    #[test]
+    fn test_macro_emits_numeric_bounds() {
+        let ports = BoundedNode.input_ports();
+        assert_eq!((ports[0].min, ports[0].max), (Some(1.0), Some(65535.0)));
+        assert_eq!((ports[1].min, ports[1].max), (Some(-1.0), None));
+    }
+
+    #[test]
+    fn test_numeric_bounds_are_inclusive_and_named_in_errors() {
+        let validate = |port: i64, ratio: f64| {
+            let inputs = HashMap::from([
+                ("port".to_string(), DataValue::Integer(port)),
+                ("ratio".to_string(), DataValue::Float(ratio)),
+            ]);
+            BoundedNode.validate_inputs(&inputs).map_err(|e| e.to_string())
+        };
+        assert!(validate(1, -1.0).is_ok());
+        assert!(validate(65535, 100.0).is_ok());
+
+        let err = validate(0, 0.0).unwrap_err();
+        assert!(err.contains("Input port 'port' must be >= 1, got 0"), "{}", err);
+        let err = validate(65536, 0.0).unwrap_err();
+        assert!(err.contains("Input port 'port' must be <= 65535, got 65536"), "{}", err);
+        let err = validate(80, -1.5).unwrap_err();
+        assert!(err.contains("Input port 'ratio' must be >= -1, got -1.5"), "{}", err);
+    }
+}
@@ -1,8 +1,11 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, RwLock};
 use once_cell::sync::Lazy;
 use serde_json::Value;
 use crate::node::{Node, DataValue, DataType};
+use crate::node::graph_interface_nodes::{
+    parse_interface_data_type, GraphInputNode, GraphOutputNode, DATA_TYPE_PORT, INTERFACE_DATA_TYPES,
+};
 use crate::error::Result;
 
 /// Node factory function type
@@ -20,6 +23,8 @@ pub struct NodeTypeMetadata {
     pub display_name: String,
     pub category: String,
     pub description: String,
+    /// Extra groups besides the category, e.g. "AI" or "消息"
+    pub tags: Vec<String>,
 }
 
 impl NodeRegistry {
@@ -30,28 +35,95 @@ impl NodeRegistry {
         }
     }
 
-    /// Register a node type with its factory function
+    /// Register a node type with its factory function.
+    /// Fails if the type id is already registered; see `register_or_replace`.
     pub fn register(
         &self,
         type_id: impl Into<String>,
         display_name: impl Into<String>,
         category: impl Into<String>,
         description: impl Into<String>,
+        tags: Vec<String>,
         factory: NodeFactory,
     ) -> Result<()> {
-        let type_id = type_id.into();
         let metadata = NodeTypeMetadata {
-            type_id: type_id.clone(),
+            type_id: type_id.into(),
             display_name: display_name.into(),
             category: category.into(),
             description: description.into(),
+            tags,
         };
+        self.insert(metadata, factory, false)
+    }
+
+    /// Register a node type, replacing any existing registration of the same type id
+    pub fn register_or_replace(
+        &self,
+        type_id: impl Into<String>,
+        display_name: impl Into<String>,
+        category: impl Into<String>,
+        description: impl Into<String>,
+        tags: Vec<String>,
+        factory: NodeFactory,
+    ) -> Result<()> {
+        let metadata = NodeTypeMetadata {
+            type_id: type_id.into(),
+            display_name: display_name.into(),
+            category: category.into(),
+            description: description.into(),
+            tags,
+        };
+        self.insert(metadata, factory, true)
+    }
+
+    /// Like `register`, but first builds a throwaway instance and rejects the type if
+    /// its port names repeat within inputs or outputs, or an input shares a name with an output
+    pub fn register_validated(
+        &self,
+        type_id: impl Into<String>,
+        display_name: impl Into<String>,
+        category: impl Into<String>,
+        description: impl Into<String>,
+        tags: Vec<String>,
+        factory: NodeFactory,
+    ) -> Result<()> {
+        let metadata = NodeTypeMetadata {
+            type_id: type_id.into(),
+            display_name: display_name.into(),
+            category: category.into(),
+            description: description.into(),
+            tags,
+        };
+        let node = factory(format!("{}_validation", metadata.type_id), metadata.display_name.clone());
+        check_port_schema(node.as_ref()).map_err(|reason| {
+            crate::error::Error::ValidationError(format!("Node type '{}' {}", metadata.type_id, reason))
+        })?;
+        self.insert(metadata, factory, false)
+    }
 
-        self.factories.write().unwrap().insert(type_id.clone(), factory);
-        self.metadata.write().unwrap().insert(type_id, metadata);
+    fn insert(&self, metadata: NodeTypeMetadata, factory: NodeFactory, replace: bool) -> Result<()> {
+        // Both maps are locked together so readers never see a half-registered type
+        let mut factories = self.factories.write().unwrap();
+        let mut all_metadata = self.metadata.write().unwrap();
+        if !replace && factories.contains_key(&metadata.type_id) {
+            return Err(crate::error::Error::ValidationError(format!(
+                "Node type '{}' is already registered",
+                metadata.type_id
+            )));
+        }
+        factories.insert(metadata.type_id.clone(), factory);
+        all_metadata.insert(metadata.type_id.clone(), metadata);
         Ok(())
     }
 
+    /// Remove a node type. Returns whether it was registered.
+    pub fn unregister(&self, type_id: &str) -> bool {
+        let mut factories = self.factories.write().unwrap();
+        let mut all_metadata = self.metadata.write().unwrap();
+        all_metadata.remove(type_id);
+        factories.remove(type_id).is_some()
+    }
+
     /// Create a new node instance by type ID
     pub fn create_node(
         &self,
@@ -67,6 +139,15 @@ impl NodeRegistry {
         Ok(factory(id.into(), name.into()))
     }
 
+    /// Get the metadata of a single node type
+    pub fn get_metadata(&self, type_id: &str) -> Option<NodeTypeMetadata> {
+        self.metadata.read().unwrap().get(type_id).cloned()
+    }
+
+    pub fn is_registered(&self, type_id: &str) -> bool {
+        self.factories.read().unwrap().contains_key(type_id)
+    }
+
     /// Get all registered node types
     pub fn get_all_types(&self) -> Vec<NodeTypeMetadata> {
         self.metadata.read().unwrap().values().cloned().collect()
@@ -83,6 +164,31 @@ impl NodeRegistry {
             .collect()
     }
 
+    /// Get node types carrying `tag`
+    pub fn get_types_by_tag(&self, tag: &str) -> Vec<NodeTypeMetadata> {
+        self.metadata
+            .read()
+            .unwrap()
+            .values()
+            .filter(|meta| meta.tags.iter().any(|t| t == tag))
+            .cloned()
+            .collect()
+    }
+
+    /// Get all tags used by registered node types
+    pub fn get_all_tags(&self) -> Vec<String> {
+        let mut tags: Vec<_> = self
+            .metadata
+            .read()
+            .unwrap()
+            .values()
+            .flat_map(|meta| meta.tags.iter().cloned())
+            .collect();
+        tags.sort();
+        tags.dedup();
+        tags
+    }
+
     /// Get all categories
     pub fn get_categories(&self) -> Vec<String> {
         let mut categories: Vec<_> = self
@@ -98,19 +204,45 @@ impl NodeRegistry {
     }
 }
 
+/// Port names must be unique within inputs and within outputs, and no input may share
+/// its name with an output
+fn check_port_schema(node: &dyn Node) -> std::result::Result<(), String> {
+    let mut input_names = HashSet::new();
+    for port in node.input_ports() {
+        if !input_names.insert(port.name.clone()) {
+            return Err(format!("has duplicate input port '{}'", port.name));
+        }
+    }
+    let mut output_names = HashSet::new();
+    for port in node.output_ports() {
+        if !output_names.insert(port.name.clone()) {
+            return Err(format!("has duplicate output port '{}'", port.name));
+        }
+        if input_names.contains(&port.name) {
+            return Err(format!("uses '{}' as both an input and an output port", port.name));
+        }
+    }
+    Ok(())
+}
+
 /// Global singleton registry
 pub static NODE_REGISTRY: Lazy<NodeRegistry> = Lazy::new(NodeRegistry::new);
 
-/// Helper macro to register a node type
+/// Helper macro to register a node type, replacing an earlier registration
+/// so `init_node_registry` can run more than once
 #[macro_export]
 macro_rules! register_node {
     ($type_id:expr, $display_name:expr, $category:expr, $description:expr, $node_struct:ty) => {
+        $crate::register_node!($type_id, $display_name, $category, $description, $node_struct, []);
+    };
+    ($type_id:expr, $display_name:expr, $category:expr, $description:expr, $node_struct:ty, [$($tag:expr),* $(,)?]) => {
         $crate::node::registry::NODE_REGISTRY
-            .register(
+            .register_or_replace(
                 $type_id,
                 $display_name,
                 $category,
                 $description,
+                vec![$(String::from($tag)),*],
                 std::sync::Arc::new(|id: String, name: String| {
                     Box::new(<$node_struct>::new(id, name))
                 }),
@@ -121,12 +253,26 @@ macro_rules! register_node {
 
 /// Initialize all node types in the registry
 pub fn init_node_registry() -> Result<()> {
-    use crate::node::util_nodes::{ConditionalNode, JsonParserNode, PreviewStringNode, StringDataNode, PreviewMessageListNode, MessageListDataNode};
+    use crate::node::util_nodes::{ConditionalNode, JsonParserNode, PreviewStringNode, StringDataNode, PreviewMessageListNode, MessageListDataNode, Base64EncodeNode, Base64DecodeNode, MapGetNode, MapInsertNode};
     use crate::llm::llm_api::LLMAPINode;
     use crate::bot_adapter::node_impl::{BotAdapterNode, MessageSenderNode};
     use crate::bot_adapter::extract_message_from_event::ExtractMessageFromEventNode;
+    use crate::bot_adapter::message_coalesce::MessageCoalesceNode;
+    use crate::bot_adapter::request_handler::RequestHandlerNode;
+    use crate::bot_adapter::humanize_send::HumanizeSendNode;
+    use crate::bot_adapter::group_member::GroupMemberNode;
+    use crate::bot_adapter::send_file::SendFileNode;
+    use crate::bot_adapter::message_builder::MessageBuilderNode;
+    use crate::bot_adapter::notice::ExtractNoticeNode;
+    use crate::bot_adapter::recall::MessageRecallNode;
+    use crate::node::http_nodes::WebhookNode;
     use crate::node::database_nodes::{RedisNode, MySqlNode};
-    use crate::node::message_nodes::{MessageMySQLPersistenceNode, MessageCacheNode};
+    use crate::node::message_nodes::{MessageMySQLPersistenceNode, MessageCacheNode, ConditionalPersistNode, TurnCounterNode, FloodDetectNode};
+    use crate::node::runtime_nodes::{DateTimeNode, LatencyGateNode, RuntimeInfoNode};
+    use crate::node::text_nodes::{TextPipelineNode, VaryReplyNode, WordFilterNode, GreetingNode, MarkdownTableToTextNode, TopicTrackerNode};
+    use crate::node::file_nodes::CsvExportNode;
+    use crate::node::image_nodes::QrCodeNode;
+    use crate::node::subgraph_nodes::SubgraphNode;
 
     // Utility nodes
     register_node!(
@@ -134,7 +280,8 @@ pub fn init_node_registry() -> Result<()> {
         "条件分支",
         "工具",
         "根据条件选择不同的输出分支",
-        ConditionalNode
+        ConditionalNode,
+        ["控制流"]
     );
 
     register_node!(
@@ -142,7 +289,44 @@ pub fn init_node_registry() -> Result<()> {
         "JSON解析器",
         "工具",
         "将JSON字符串解析为结构化数据",
-        JsonParserNode
+        JsonParserNode,
+        ["JSON"]
+    );
+
+    register_node!(
+        "base64_encode",
+        "Base64编码",
+        "工具",
+        "将二进制数据编码为Base64字符串",
+        Base64EncodeNode,
+        ["编码", "二进制"]
+    );
+
+    register_node!(
+        "base64_decode",
+        "Base64解码",
+        "工具",
+        "将Base64字符串解码为二进制数据",
+        Base64DecodeNode,
+        ["编码", "二进制"]
+    );
+
+    register_node!(
+        "map_get",
+        "Map取值",
+        "工具",
+        "按键读取Map中的值",
+        MapGetNode,
+        ["Map"]
+    );
+
+    register_node!(
+        "map_insert",
+        "Map写入",
+        "工具",
+        "向Map写入一个键值对，未连接Map时从空Map开始",
+        MapInsertNode,
+        ["Map"]
     );
 
     register_node!(
@@ -150,7 +334,8 @@ pub fn init_node_registry() -> Result<()> {
         "Preview String",
         "工具",
         "在节点卡片内预览输入字符串",
-        PreviewStringNode
+        PreviewStringNode,
+        ["预览"]
     );
 
     register_node!(
@@ -158,7 +343,8 @@ pub fn init_node_registry() -> Result<()> {
         "String Data",
         "数据",
         "字符串数据源，通过UI输入框提供字符串",
-        StringDataNode
+        StringDataNode,
+        ["数据源"]
     );
 
     register_node!(
@@ -166,7 +352,8 @@ pub fn init_node_registry() -> Result<()> {
         "Preview MessageList",
         "工具",
         "在节点卡片内预览消息列表",
-        PreviewMessageListNode
+        PreviewMessageListNode,
+        ["预览", "消息"]
     );
 
     register_node!(
@@ -174,7 +361,135 @@ pub fn init_node_registry() -> Result<()> {
         "MessageList Data",
         "数据",
         "消息列表数据源，通过UI容器编辑器提供MessageList",
-        MessageListDataNode
+        MessageListDataNode,
+        ["数据源", "消息"]
+    );
+
+    register_node!(
+        "runtime_info",
+        "运行信息",
+        "工具",
+        "输出节点图运行次数、进程运行时长和版本号",
+        RuntimeInfoNode,
+        ["运行时"]
+    );
+
+    register_node!(
+        "datetime_now",
+        "当前时间",
+        "工具",
+        "输出当前本地时间",
+        DateTimeNode,
+        ["时间"]
+    );
+
+    register_node!(
+        "latency_gate",
+        "延迟检查",
+        "工具",
+        "统计 start 到 stop 触发之间的耗时并与预算比较",
+        LatencyGateNode,
+        ["运行时", "时间"]
+    );
+
+    register_node!(
+        "text_pipeline",
+        "文本处理流水线",
+        "工具",
+        "按顺序对文本执行多个处理步骤（trim、lowercase、strip_markdown、truncate:N 等）",
+        TextPipelineNode,
+        ["文本"]
+    );
+
+    register_node!(
+        "vary_reply",
+        "回复变化",
+        "工具",
+        "随机为回复添加前后缀（可设种子），或由LLM改写以减少重复",
+        VaryReplyNode,
+        ["文本", "AI"]
+    );
+
+    register_node!(
+        "word_filter",
+        "敏感词过滤",
+        "工具",
+        "替换文本中的违禁词（不区分大小写，按整词匹配），或命中时拦截消息",
+        WordFilterNode,
+        ["文本", "安全"]
+    );
+
+    register_node!(
+        "greeting",
+        "问候语",
+        "工具",
+        "根据当前时段选择问候语，可自定义各时段文本，适合配合定时触发发送早安",
+        GreetingNode,
+        ["文本", "时间"]
+    );
+
+    register_node!(
+        "md_table_to_text",
+        "Markdown表格转文本",
+        "工具",
+        "将LLM输出中的Markdown表格改写为键值列表或对齐文本，便于在QQ中阅读",
+        MarkdownTableToTextNode,
+        ["文本"]
+    );
+
+    register_node!(
+        "topic_tracker",
+        "话题追踪",
+        "AI",
+        "按会话维护当前话题摘要，每隔N条消息调用LLM更新一次",
+        TopicTrackerNode,
+        ["AI", "消息"]
+    );
+
+    register_node!(
+        "csv_export",
+        "CSV导出",
+        "工具",
+        "将JSON对象列表写入output目录下的CSV文件",
+        CsvExportNode,
+        ["文件", "JSON"]
+    );
+
+    register_node!(
+        "qr_code",
+        "二维码生成",
+        "工具",
+        "将文本编码为二维码PNG图片",
+        QrCodeNode,
+        ["图片"]
+    );
+
+    register_node!(
+        "subgraph",
+        "子图",
+        "工具",
+        "加载已保存的节点图，作为单个可复用节点运行",
+        SubgraphNode,
+        ["控制流"]
+    );
+
+    // Graph interface nodes
+    register_node!(
+        "graph_input",
+        "图输入",
+        "接口",
+        "声明节点图的输入参数，运行时由调用方按参数名提供",
+        GraphInputNode,
+        ["子图", "测试"]
+    );
+
+    register_node!(
+        "graph_output",
+        "图输出",
+        "接口",
+        "将值按参数名记录为节点图的结果",
+        GraphOutputNode,
+        ["子图", "测试"]
     );
 
     // LLM nodes
@@ -183,7 +498,8 @@ pub fn init_node_registry() -> Result<()> {
         "LLM API调用",
         "AI",
         "调用语言模型API进行推理",
-        LLMAPINode
+        LLMAPINode,
+        ["AI"]
     );
 
     // Bot adapter nodes
@@ -192,7 +508,8 @@ pub fn init_node_registry() -> Result<()> {
         "QQ机器人适配器",
         "Bot适配器",
         "接收来自QQ服务器的消息事件",
-        BotAdapterNode
+        BotAdapterNode,
+        ["事件源", "消息"]
     );
 
     register_node!(
@@ -200,7 +517,35 @@ pub fn init_node_registry() -> Result<()> {
         "消息发送器",
         "Bot适配器",
         "向QQ服务器发送消息",
-        MessageSenderNode
+        MessageSenderNode,
+        ["发送", "消息"]
+    );
+
+    register_node!(
+        "message_builder",
+        "消息构建器",
+        "Bot适配器",
+        "由文本、图片、@和回复组成消息段",
+        MessageBuilderNode,
+        ["消息", "图片", "回复"]
+    );
+
+    register_node!(
+        "message_recall",
+        "撤回消息",
+        "Bot适配器",
+        "撤回机器人自己或他人的消息",
+        MessageRecallNode,
+        ["撤回", "消息", "管理"]
+    );
+
+    register_node!(
+        "extract_notice",
+        "解析通知",
+        "Bot适配器",
+        "从好友申请、群邀请、成员进出群等通知事件中取出类型、用户和群号",
+        ExtractNoticeNode,
+        ["通知", "好友申请", "入群"]
     );
 
     register_node!(
@@ -208,7 +553,53 @@ pub fn init_node_registry() -> Result<()> {
         "事件提取message列表",
         "Bot适配器",
         "从消息事件中提取openai的message列表",
-        ExtractMessageFromEventNode
+        ExtractMessageFromEventNode,
+        ["消息", "AI"]
+    );
+
+    register_node!(
+        "message_coalesce",
+        "消息合并",
+        "Bot适配器",
+        "等待同一用户停止输入后，将连续发送的多条消息合并为一条事件",
+        MessageCoalesceNode,
+        ["消息"]
+    );
+
+    register_node!(
+        "request_handler",
+        "请求处理",
+        "Bot适配器",
+        "根据配置自动同意或拒绝好友申请和群邀请",
+        RequestHandlerNode,
+        ["群管理"]
+    );
+
+    register_node!(
+        "humanize_send",
+        "拟人发送",
+        "Bot适配器",
+        "将回复拆分成多条消息，并按打字速度延迟发送",
+        HumanizeSendNode,
+        ["发送", "文本"]
+    );
+
+    register_node!(
+        "group_member",
+        "群成员信息",
+        "Bot适配器",
+        "查询群成员的群名片、角色和头衔，并按TTL缓存",
+        GroupMemberNode,
+        ["群管理", "缓存"]
+    );
+
+    register_node!(
+        "send_file",
+        "发送文件",
+        "Bot适配器",
+        "向私聊或群聊发送图片或文件，超出大小限制时报错",
+        SendFileNode,
+        ["发送", "图片", "文件"]
     );
 
     // Database nodes
@@ -217,7 +608,8 @@ pub fn init_node_registry() -> Result<()> {
         "Redis连接",
         "数据库",
         "构建Redis连接配置",
-        RedisNode
+        RedisNode,
+        ["Redis", "缓存"]
     );
 
     register_node!(
@@ -225,7 +617,8 @@ pub fn init_node_registry() -> Result<()> {
         "MySQL连接",
         "数据库",
         "构建MySQL连接配置",
-        MySqlNode
+        MySqlNode,
+        ["MySQL"]
     );
 
     // Message storage nodes
@@ -234,7 +627,17 @@ pub fn init_node_registry() -> Result<()> {
         "消息MySQL持久化",
         "消息存储",
         "将消息事件持久化到MySQL数据库",
-        MessageMySQLPersistenceNode
+        MessageMySQLPersistenceNode,
+        ["MySQL", "持久化", "消息"]
+    );
+
+    register_node!(
+        "conditional_persist",
+        "条件持久化",
+        "消息存储",
+        "仅当条件为真时将消息事件持久化到MySQL数据库",
+        ConditionalPersistNode,
+        ["MySQL", "持久化", "控制流"]
     );
 
     register_node!(
@@ -242,7 +645,35 @@ pub fn init_node_registry() -> Result<()> {
         "消息缓存",
         "消息存储",
         "缓存消息事件到内存或Redis",
-        MessageCacheNode
+        MessageCacheNode,
+        ["Redis", "缓存", "消息"]
+    );
+
+    register_node!(
+        "turn_counter",
+        "对话轮数统计",
+        "消息存储",
+        "按会话统计消息轮数，达到阈值时输出需要总结的信号并重新计数",
+        TurnCounterNode,
+        ["消息", "计数"]
+    );
+
+    register_node!(
+        "flood_detect",
+        "刷屏检测",
+        "消息存储",
+        "按用户统计时间窗口内相同或相近消息的重复次数，超过阈值时判定为刷屏",
+        FloodDetectNode,
+        ["消息", "安全"]
+    );
+
+    register_node!(
+        "webhook",
+        "Webhook",
+        "工具",
+        "将JSON数据POST到外部URL，非2xx状态作为输出返回，可选HMAC签名",
+        WebhookNode,
+        ["网络", "JSON"]
     );
 
     Ok(())
@@ -260,11 +691,31 @@ pub fn build_node_graph_from_definition(
 
     // Create all nodes
     for node_def in &definition.nodes {
-        let node = NODE_REGISTRY.create_node(
-            &node_def.node_type,
-            node_def.id.clone(),
-            node_def.name.clone(),
-        )?;
+        let node: Box<dyn Node> = match subgraph_path(node_def) {
+            // Subgraph ports depend on the embedded graph, so load it before reading them
+            Some(path) => Box::new(crate::node::subgraph_nodes::SubgraphNode::load(
+                node_def.id.clone(),
+                node_def.name.clone(),
+                path,
+            )
+            .map_err(crate::error::Error::ValidationError)?),
+            // Interface ports take the type chosen in the node's data_type inline value
+            None => match interface_data_type(node_def)? {
+                Some(data_type) if node_def.node_type == "graph_input" => Box::new(
+                    GraphInputNode::with_data_type(node_def.id.clone(), node_def.name.clone(), data_type),
+                ),
+                Some(data_type) => Box::new(GraphOutputNode::with_data_type(
+                    node_def.id.clone(),
+                    node_def.name.clone(),
+                    data_type,
+                )),
+                None => NODE_REGISTRY.create_node(
+                    &node_def.node_type,
+                    node_def.id.clone(),
+                    node_def.name.clone(),
+                )?,
+            },
+        };
 
         // Parse inline values
         if !node_def.inline_values.is_empty() {
@@ -292,7 +743,40 @@ pub fn build_node_graph_from_definition(
     Ok(graph)
 }
 
-fn json_to_data_value(json: &Value, target_type: &DataType) -> Option<DataValue> {
+/// Value type of a graph input or output node, `None` for other nodes
+fn interface_data_type(node_def: &crate::node::graph_io::NodeDefinition) -> Result<Option<DataType>> {
+    if node_def.node_type != "graph_input" && node_def.node_type != "graph_output" {
+        return Ok(None);
+    }
+    match node_def.inline_values.get(DATA_TYPE_PORT) {
+        None => Ok(Some(DataType::String)),
+        Some(Value::String(name)) if name.trim().is_empty() => Ok(Some(DataType::String)),
+        Some(Value::String(name)) => parse_interface_data_type(name).map(Some).ok_or_else(|| {
+            crate::error::Error::ValidationError(format!(
+                "Unsupported data type '{}' on node '{}', expected one of {}",
+                name,
+                node_def.id,
+                INTERFACE_DATA_TYPES.join(", ")
+            ))
+        }),
+        Some(other) => Err(crate::error::Error::ValidationError(format!(
+            "Data type of node '{}' must be a string, got {}",
+            node_def.id, other
+        ))),
+    }
+}
+
+fn subgraph_path(node_def: &crate::node::graph_io::NodeDefinition) -> Option<&str> {
+    if node_def.node_type != "subgraph" {
+        return None;
+    }
+    match node_def.inline_values.get(crate::node::subgraph_nodes::GRAPH_PATH_PORT) {
+        Some(Value::String(path)) if !path.trim().is_empty() => Some(path.trim()),
+        _ => None,
+    }
+}
+
+pub(crate) fn json_to_data_value(json: &Value, target_type: &DataType) -> Option<DataValue> {
     match (json, target_type) {
         (Value::String(s), DataType::String) => Some(DataValue::String(s.clone())),
         (Value::String(s), DataType::Password) => Some(DataValue::Password(s.clone())),
@@ -303,6 +787,13 @@ fn json_to_data_value(json: &Value, target_type: &DataType) -> Option<DataValue>
         },
         (Value::String(s), DataType::Integer) => s.parse().ok().map(DataValue::Integer),
         (Value::String(s), DataType::Float) => s.parse().ok().map(DataValue::Float),
+        (Value::String(s), DataType::Binary) => {
+            use base64::Engine;
+            base64::engine::general_purpose::STANDARD.decode(s.trim()).ok().map(DataValue::Binary)
+        }
+        (Value::String(s), DataType::DateTime) => {
+            crate::node::data_value::parse_iso8601(s).map(DataValue::DateTime)
+        }
         (Value::String(s), DataType::Json) => match serde_json::from_str(s) {
             Ok(v) => Some(DataValue::Json(v)),
             Err(_) => Some(DataValue::String(s.clone())), // Fallback? or Error? Or maybe just create Json string
@@ -315,6 +806,19 @@ fn json_to_data_value(json: &Value, target_type: &DataType) -> Option<DataValue>
         
         (v, DataType::Json) => Some(DataValue::Json(v.clone())),
 
+        (Value::Object(map), DataType::Map(inner)) => map
+            .iter()
+            .map(|(key, value)| json_to_data_value(value, inner).map(|value| (key.clone(), value)))
+            .collect::<Option<HashMap<_, _>>>()
+            .map(DataValue::Map),
+
+        (Value::Array(items), DataType::Tuple(types)) if items.len() == types.len() => items
+            .iter()
+            .zip(types)
+            .map(|(item, item_type)| json_to_data_value(item, item_type))
+            .collect::<Option<Vec<_>>>()
+            .map(DataValue::Tuple),
+
         // MessageList inline value is stored as a JSON array:
         // [ {"role": "user", "content": "..."}, ... ]
         (Value::Array(items), DataType::MessageList) => {
@@ -345,6 +849,7 @@ fn json_to_data_value(json: &Value, target_type: &DataType) -> Option<DataValue>
                         role,
                         content,
                         tool_calls: Vec::new(),
+                        usage: None,
                     });
                 }
             }
@@ -357,8 +862,119 @@ fn json_to_data_value(json: &Value, target_type: &DataType) -> Option<DataValue>
 
 #[cfg(test)]
 mod tests {
-    use super::json_to_data_value;
-    use crate::node::{DataType, DataValue};
+    use super::{init_node_registry, json_to_data_value, NodeRegistry, NODE_REGISTRY};
+    use crate::node::data_value::datetime_to_rfc3339;
+    use crate::error::Result;
+    use crate::node::util_nodes::{Base64EncodeNode, StringDataNode};
+    use crate::node::{DataType, DataValue, Node, Port};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_register_unregister_and_replace() {
+        let registry = NodeRegistry::new();
+        let factory = || -> super::NodeFactory {
+            Arc::new(|id: String, name: String| Box::new(StringDataNode::new(id, name)))
+        };
+
+        assert!(!registry.is_registered("text"));
+        registry.register("text", "Text", "Test", "", Vec::new(), factory()).unwrap();
+        assert!(registry.is_registered("text"));
+        assert_eq!(registry.get_metadata("text").map(|m| m.display_name), Some("Text".to_string()));
+        assert!(registry.create_node("text", "a", "A").is_ok());
+        assert!(registry.register("text", "Text", "Test", "", Vec::new(), factory()).is_err());
+        registry.register_or_replace("text", "Text 2", "Test", "", Vec::new(), factory()).unwrap();
+        assert_eq!(registry.get_all_types()[0].display_name, "Text 2");
+
+        assert!(registry.unregister("text"));
+        assert!(!registry.unregister("text"));
+        assert!(!registry.is_registered("text"));
+        assert!(registry.get_metadata("text").is_none());
+        assert!(registry.create_node("text", "a", "A").is_err());
+        assert!(registry.get_all_types().is_empty());
+    }
+
+    #[test]
+    fn test_types_by_tag() {
+        let registry = NodeRegistry::new();
+        let factory = || -> super::NodeFactory {
+            Arc::new(|id: String, name: String| Box::new(StringDataNode::new(id, name)))
+        };
+        let tags = |tags: &[&str]| tags.iter().map(|t| t.to_string()).collect();
+        registry.register("llm", "LLM", "AI", "", tags(&["AI", "streaming"]), factory()).unwrap();
+        registry.register("topic", "Topic", "AI", "", tags(&["AI"]), factory()).unwrap();
+        registry.register("plain", "Plain", "工具", "", Vec::new(), factory()).unwrap();
+
+        let mut ai: Vec<String> = registry.get_types_by_tag("AI").into_iter().map(|m| m.type_id).collect();
+        ai.sort();
+        assert_eq!(ai, ["llm", "topic"]);
+        assert_eq!(registry.get_types_by_tag("streaming").len(), 1);
+        assert!(registry.get_types_by_tag("工具").is_empty());
+        assert_eq!(registry.get_all_tags(), ["AI", "streaming"]);
+    }
+
+    /// Declares `text` as both an input and an output
+    struct OverlappingPortsNode;
+
+    impl Node for OverlappingPortsNode {
+        fn id(&self) -> &str {
+            "overlap"
+        }
+
+        fn name(&self) -> &str {
+            "Overlap"
+        }
+
+        fn input_ports(&self) -> Vec<Port> {
+            vec![Port::new("text", DataType::String)]
+        }
+
+        fn output_ports(&self) -> Vec<Port> {
+            vec![Port::new("text", DataType::String), Port::new("length", DataType::Integer)]
+        }
+
+        fn execute(&mut self, _inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+            Ok(HashMap::new())
+        }
+    }
+
+    #[test]
+    fn test_register_validated_rejects_broken_port_schema() {
+        let registry = NodeRegistry::new();
+        let err = registry
+            .register_validated(
+                "overlap",
+                "Overlap",
+                "Test",
+                "",
+                Vec::new(),
+                Arc::new(|_id: String, _name: String| Box::new(OverlappingPortsNode)),
+            )
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Node type 'overlap' uses 'text' as both an input and an output port"), "{}", err);
+        assert!(registry.create_node("overlap", "a", "A").is_err());
+
+        registry
+            .register_validated(
+                "base64_encode",
+                "Base64",
+                "Test",
+                "",
+                Vec::new(),
+                Arc::new(|id: String, name: String| Box::new(Base64EncodeNode::new(id, name))),
+            )
+            .unwrap();
+        assert!(registry.create_node("base64_encode", "a", "A").is_ok());
+    }
+
+    #[test]
+    fn test_init_node_registry_is_idempotent() {
+        init_node_registry().unwrap();
+        let count = NODE_REGISTRY.get_all_types().len();
+        init_node_registry().unwrap();
+        assert_eq!(NODE_REGISTRY.get_all_types().len(), count);
+    }
 
     #[test]
     fn parse_message_list_inline_value() {
@@ -385,4 +1001,37 @@ mod tests {
             _ => panic!("unexpected DataValue variant"),
         }
     }
+
+    #[test]
+    fn parse_datetime_inline_value_round_trips_rfc3339() {
+        let expected = chrono::NaiveDate::from_ymd_opt(2024, 5, 1)
+            .unwrap()
+            .and_hms_opt(12, 30, 15)
+            .unwrap();
+
+        let naive = serde_json::json!("2024-05-01T12:30:15");
+        assert!(matches!(
+            json_to_data_value(&naive, &DataType::DateTime),
+            Some(DataValue::DateTime(value)) if value == expected
+        ));
+
+        let rfc3339 = serde_json::json!(datetime_to_rfc3339(&expected));
+        assert!(matches!(
+            json_to_data_value(&rfc3339, &DataType::DateTime),
+            Some(DataValue::DateTime(value)) if value == expected
+        ));
+        assert!(json_to_data_value(&serde_json::json!("yesterday"), &DataType::DateTime).is_none());
+    }
+
+    #[test]
+    fn parse_map_inline_value_and_port_type_round_trip() {
+        let json = serde_json::json!({"greeting": "hi", "farewell": "bye"});
+        let val = json_to_data_value(&json, &DataType::Map(Box::new(DataType::String)))
+            .expect("should parse Map");
+        assert_eq!(val.to_json(), json);
+
+        let port_type = DataType::Map(Box::new(DataType::List(Box::new(DataType::Integer))));
+        let encoded = serde_json::to_string(&port_type).unwrap();
+        assert_eq!(serde_json::from_str::<DataType>(&encoded).unwrap(), port_type);
+    }
 }
@@ -0,0 +1,284 @@
+use crate::error::Result;
+use crate::node::data_value::datetime_to_rfc3339;
+use crate::node::state_store::{get_node_state, set_persisted_node_state};
+use crate::node::{node_input, node_output, DataType, DataValue, Node, Port};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// Process start time, forced in main() so uptime is measured from launch
+pub static PROCESS_START: Lazy<Instant> = Lazy::new(Instant::now);
+
+const RUN_COUNT_KEY: &str = "run_count";
+
+/// Runtime info node - outputs graph run count, process uptime and crate version
+pub struct RuntimeInfoNode {
+    id: String,
+    name: String,
+    graph_path: Option<PathBuf>,
+}
+
+impl RuntimeInfoNode {
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            graph_path: None,
+        }
+    }
+}
+
+impl Node for RuntimeInfoNode {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> Option<&str> {
+        Some("运行信息 - 输出节点图运行次数、进程运行时长和版本号")
+    }
+
+    node_input![];
+
+    node_output![
+        port! { name = "run_count", ty = Integer, desc = "节点执行次数（跨运行持久化）" },
+        port! { name = "uptime_secs", ty = Integer, desc = "进程启动以来的秒数" },
+        port! { name = "version", ty = String, desc = "程序版本号" },
+    ];
+
+    fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+        self.validate_inputs(&inputs)?;
+
+        let graph_path = self.graph_path.as_deref();
+        let run_count = get_node_state(graph_path, &self.id, RUN_COUNT_KEY)
+            .and_then(|v| v.as_i64())
+            .unwrap_or(0)
+            + 1;
+        set_persisted_node_state(graph_path, &self.id, RUN_COUNT_KEY, serde_json::json!(run_count));
+
+        let uptime_secs = PROCESS_START.elapsed().as_secs() as i64;
+
+        let mut outputs = HashMap::new();
+        outputs.insert("run_count".to_string(), DataValue::Integer(run_count));
+        outputs.insert("uptime_secs".to_string(), DataValue::Integer(uptime_secs));
+        outputs.insert(
+            "version".to_string(),
+            DataValue::String(env!("CARGO_PKG_VERSION").to_string()),
+        );
+
+        self.validate_outputs(&outputs)?;
+        Ok(outputs)
+    }
+
+    fn set_graph_path(&mut self, graph_path: Option<&Path>) {
+        self.graph_path = graph_path.map(Path::to_path_buf);
+    }
+}
+
+/// DateTime node - outputs the current local time
+pub struct DateTimeNode {
+    id: String,
+    name: String,
+}
+
+impl DateTimeNode {
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+        }
+    }
+}
+
+impl Node for DateTimeNode {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> Option<&str> {
+        Some("当前时间 - 输出当前本地时间")
+    }
+
+    node_input![];
+
+    node_output![
+        port! { name = "datetime", ty = DateTime, desc = "当前本地时间" },
+        port! { name = "iso_string", ty = String, desc = "RFC3339格式的当前时间" },
+    ];
+
+    fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+        self.validate_inputs(&inputs)?;
+
+        let now = chrono::Local::now().naive_local();
+
+        let mut outputs = HashMap::new();
+        outputs.insert("iso_string".to_string(), DataValue::String(datetime_to_rfc3339(&now)));
+        outputs.insert("datetime".to_string(), DataValue::DateTime(now));
+
+        self.validate_outputs(&outputs)?;
+        Ok(outputs)
+    }
+}
+
+/// Latency gate node - measures time between a `start` and a `stop` trigger
+/// and checks it against a budget.
+///
+/// The start time lives in the node itself, so both triggers must reach the same
+/// node instance (e.g. inside one event producer loop).
+pub struct LatencyGateNode {
+    id: String,
+    name: String,
+    started_at: Option<Instant>,
+}
+
+impl LatencyGateNode {
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            started_at: None,
+        }
+    }
+
+    /// Apply the triggers at `now`, returning `(elapsed_ms, within_budget)`.
+    /// Before `stop` arrives the elapsed time so far is reported.
+    fn measure(&mut self, start: bool, stop: bool, budget_ms: i64, now: Instant) -> (i64, bool) {
+        if start {
+            self.started_at = Some(now);
+        }
+        let elapsed_ms = self
+            .started_at
+            .map(|started_at| now.saturating_duration_since(started_at).as_millis() as i64)
+            .unwrap_or(0);
+        if stop {
+            self.started_at = None;
+        }
+        (elapsed_ms, elapsed_ms <= budget_ms)
+    }
+}
+
+impl Node for LatencyGateNode {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> Option<&str> {
+        Some("延迟检查 - 统计 start 到 stop 触发之间的耗时，并判断是否在预算内")
+    }
+
+    node_input![
+        port! { name = "start", ty = Boolean, desc = "为 true 时开始计时", optional },
+        port! { name = "stop", ty = Boolean, desc = "为 true 时结束计时", optional },
+        port! { name = "budget_ms", ty = Integer, desc = "允许的最大耗时（毫秒）" },
+    ];
+
+    node_output![
+        port! { name = "elapsed_ms", ty = Integer, desc = "从 start 开始经过的毫秒数" },
+        port! { name = "within_budget", ty = Boolean, desc = "耗时是否未超过预算" },
+    ];
+
+    fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+        self.validate_inputs(&inputs)?;
+
+        let trigger = |name: &str| matches!(inputs.get(name), Some(DataValue::Boolean(true)));
+        let budget_ms = match inputs.get("budget_ms") {
+            Some(DataValue::Integer(budget)) => *budget,
+            _ => {
+                return Err(crate::error::Error::InvalidNodeInput(
+                    "budget_ms is required".to_string(),
+                ))
+            }
+        };
+
+        let (elapsed_ms, within_budget) =
+            self.measure(trigger("start"), trigger("stop"), budget_ms, Instant::now());
+
+        let mut outputs = HashMap::new();
+        outputs.insert("elapsed_ms".to_string(), DataValue::Integer(elapsed_ms));
+        outputs.insert("within_budget".to_string(), DataValue::Boolean(within_budget));
+
+        self.validate_outputs(&outputs)?;
+        Ok(outputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_runtime_info_run_count_and_version() {
+        let mut node = RuntimeInfoNode::new("runtime_info_test", "RuntimeInfo");
+
+        let first = node.execute(HashMap::new()).unwrap();
+        let second = node.execute(HashMap::new()).unwrap();
+
+        match (first.get("run_count"), second.get("run_count")) {
+            (Some(DataValue::Integer(a)), Some(DataValue::Integer(b))) => assert_eq!(*b, *a + 1),
+            _ => panic!("run_count should be an Integer"),
+        }
+
+        match second.get("version") {
+            Some(DataValue::String(v)) => assert_eq!(v, env!("CARGO_PKG_VERSION")),
+            _ => panic!("version should be a String"),
+        }
+    }
+
+    #[test]
+    fn test_runtime_info_run_count_is_persisted_per_graph() {
+        use crate::node::state_store::state_file_path;
+
+        let dir = std::env::temp_dir().join(format!("zihuan_run_count_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let graph_a = dir.join("a.json");
+        let graph_b = dir.join("b.json");
+        // A count left by an earlier process
+        std::fs::write(state_file_path(&graph_a), r#"{"info": {"run_count": 41}}"#).unwrap();
+
+        let run_count = |graph_path: &Path| {
+            let mut node = RuntimeInfoNode::new("info", "RuntimeInfo");
+            node.set_graph_path(Some(graph_path));
+            match node.execute(HashMap::new()).unwrap().remove("run_count") {
+                Some(DataValue::Integer(count)) => count,
+                other => panic!("run_count should be an Integer, got {:?}", other),
+            }
+        };
+        assert_eq!(run_count(&graph_a), 42);
+        // The same node id in another graph counts on its own
+        assert_eq!(run_count(&graph_b), 1);
+
+        let saved: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(state_file_path(&graph_a)).unwrap()).unwrap();
+        assert_eq!(saved["info"]["run_count"], 42);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_latency_gate_budget() {
+        use std::time::Duration;
+
+        let mut node = LatencyGateNode::new("latency_gate_test", "LatencyGate");
+        let t0 = Instant::now();
+
+        node.measure(true, false, 100, t0);
+        assert_eq!(node.measure(false, true, 100, t0 + Duration::from_millis(80)), (80, true));
+
+        node.measure(true, false, 100, t0 + Duration::from_millis(200));
+        assert_eq!(node.measure(false, true, 100, t0 + Duration::from_millis(350)), (150, false));
+
+        // stop resets the gate
+        assert_eq!(node.measure(false, false, 100, t0 + Duration::from_millis(400)), (0, true));
+    }
+}
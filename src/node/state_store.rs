@@ -0,0 +1,101 @@
+use log::warn;
+use once_cell::sync::Lazy;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+/// Node id -> state key -> value
+type NodeValues = HashMap<String, HashMap<String, Value>>;
+
+#[derive(Default)]
+struct GraphState {
+    values: NodeValues,
+    /// The part of `values` written to the graph's state file
+    persisted: NodeValues,
+}
+
+// Process-wide state for nodes that need to remember values across executions.
+// The UI rebuilds the NodeGraph (and therefore every node instance) on each run,
+// so state kept in `&mut self` does not survive; state kept here does.
+// Keyed by the file the graph was loaded from (`None` for an unsaved graph), then by
+// node id, then by a node-defined state key, so equal node ids in two graphs never share state.
+static NODE_STATE_STORE: Lazy<RwLock<HashMap<Option<PathBuf>, GraphState>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// File next to the graph holding its persisted state, `bot.json` -> `bot.state.json`
+pub fn state_file_path(graph_path: &Path) -> PathBuf {
+    graph_path.with_extension("state.json")
+}
+
+fn load_state_file(graph_path: Option<&Path>) -> GraphState {
+    let Some(path) = graph_path.map(state_file_path) else {
+        return GraphState::default();
+    };
+    let persisted: NodeValues = match fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+            warn!("Ignoring unreadable node state file {}: {}", path.display(), e);
+            NodeValues::new()
+        }),
+        Err(_) => NodeValues::new(),
+    };
+    GraphState {
+        values: persisted.clone(),
+        persisted,
+    }
+}
+
+/// Run `f` on the state of the graph, reading its state file the first time
+fn with_graph_state<R>(graph_path: Option<&Path>, f: impl FnOnce(&mut GraphState) -> R) -> R {
+    let mut store = NODE_STATE_STORE.write().unwrap();
+    let state = store
+        .entry(graph_path.map(Path::to_path_buf))
+        .or_insert_with(|| load_state_file(graph_path));
+    f(state)
+}
+
+/// Read a state value previously stored by the node
+pub fn get_node_state(graph_path: Option<&Path>, node_id: &str, key: &str) -> Option<Value> {
+    with_graph_state(graph_path, |state| {
+        state.values.get(node_id).and_then(|values| values.get(key)).cloned()
+    })
+}
+
+/// Store a state value for the node, replacing any previous value. It lasts until the process exits.
+pub fn set_node_state(graph_path: Option<&Path>, node_id: &str, key: &str, value: Value) {
+    with_graph_state(graph_path, |state| {
+        state
+            .values
+            .entry(node_id.to_string())
+            .or_default()
+            .insert(key.to_string(), value);
+    })
+}
+
+/// Like `set_node_state`, but also write the value to the graph's state file so it is
+/// restored when the graph is loaded again. Unsaved graphs only keep it in memory.
+pub fn set_persisted_node_state(graph_path: Option<&Path>, node_id: &str, key: &str, value: Value) {
+    with_graph_state(graph_path, |state| {
+        state
+            .values
+            .entry(node_id.to_string())
+            .or_default()
+            .insert(key.to_string(), value.clone());
+        state
+            .persisted
+            .entry(node_id.to_string())
+            .or_default()
+            .insert(key.to_string(), value);
+
+        let Some(path) = graph_path.map(state_file_path) else {
+            return;
+        };
+        let written = serde_json::to_string_pretty(&state.persisted)
+            .map_err(|e| e.to_string())
+            .and_then(|content| fs::write(&path, content).map_err(|e| e.to_string()));
+        if let Err(e) = written {
+            warn!("Failed to save node state to {}: {}", path.display(), e);
+        }
+    })
+}
@@ -0,0 +1,166 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+use crate::node::DataValue;
+
+/// How often a graph waiting for the next step or at a breakpoint checks whether it should stop
+const STEP_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// One node run by a graph in step mode
+#[derive(Debug, Clone)]
+pub struct StepRecord {
+    pub node_id: String,
+    pub inputs: HashMap<String, DataValue>,
+    pub outputs: HashMap<String, DataValue>,
+}
+
+#[derive(Default)]
+struct StepState {
+    /// Nodes the graph may still run before waiting again
+    permits: usize,
+    /// Nodes run but not yet handed out by `step`
+    records: VecDeque<StepRecord>,
+    finished: bool,
+}
+
+/// Handle to a graph in step mode, see `NodeGraph::set_step_mode`. The graph runs on
+/// one thread and waits before each node; `step` is called from another.
+#[derive(Clone)]
+pub struct GraphStepper {
+    shared: Arc<(Mutex<StepState>, Condvar)>,
+    stop_flag: Arc<AtomicBool>,
+}
+
+impl GraphStepper {
+    pub(crate) fn new(stop_flag: Arc<AtomicBool>) -> Self {
+        Self {
+            shared: Arc::new((Mutex::new(StepState::default()), Condvar::new())),
+            stop_flag,
+        }
+    }
+
+    /// Let the graph run exactly one node and wait until it has. Returns `None` once
+    /// the run is over, whether it completed, failed or was stopped.
+    pub fn step(&self) -> Option<StepRecord> {
+        let (state, changed) = &*self.shared;
+        let mut state = state.lock().unwrap();
+        if state.finished {
+            return state.records.pop_front();
+        }
+        state.permits += 1;
+        changed.notify_all();
+        loop {
+            if let Some(record) = state.records.pop_front() {
+                return Some(record);
+            }
+            if state.finished {
+                return None;
+            }
+            state = changed.wait(state).unwrap();
+        }
+    }
+
+    /// Mark the start of a run, so `step` waits for its nodes. Steps requested before
+    /// the first run starts are kept.
+    pub(crate) fn start(&self) {
+        let (state, _) = &*self.shared;
+        let mut state = state.lock().unwrap();
+        state.finished = false;
+        state.records.clear();
+    }
+
+    /// Mark the end of a run and wake a pending `step`
+    pub(crate) fn finish(&self) {
+        let (state, changed) = &*self.shared;
+        let mut state = state.lock().unwrap();
+        state.finished = true;
+        state.permits = 0;
+        changed.notify_all();
+    }
+
+    /// Block until `step` allows the next node to run. Returns `false` if a stop is
+    /// requested first.
+    pub(crate) fn wait_for_step(&self) -> bool {
+        let (state, changed) = &*self.shared;
+        let mut state = state.lock().unwrap();
+        while state.permits == 0 {
+            if self.stop_flag.load(Ordering::Relaxed) {
+                return false;
+            }
+            state = changed.wait_timeout(state, STEP_POLL_INTERVAL).unwrap().0;
+        }
+        state.permits -= 1;
+        true
+    }
+
+    /// Hand a finished node to the `step` call waiting for it
+    pub(crate) fn record(&self, record: StepRecord) {
+        let (state, changed) = &*self.shared;
+        state.lock().unwrap().records.push_back(record);
+        changed.notify_all();
+    }
+}
+
+/// Handle to a graph that may halt at a breakpoint, see `NodeGraph::add_breakpoint`
+#[derive(Clone)]
+pub struct BreakpointControl {
+    /// Node the graph is halted before, if any
+    halted_at: Arc<(Mutex<Option<String>>, Condvar)>,
+    stop_flag: Arc<AtomicBool>,
+}
+
+impl BreakpointControl {
+    pub(crate) fn new(stop_flag: Arc<AtomicBool>) -> Self {
+        Self {
+            halted_at: Arc::new((Mutex::new(None), Condvar::new())),
+            stop_flag,
+        }
+    }
+
+    /// Node the graph is waiting at, or `None` while it runs
+    pub fn halted_at(&self) -> Option<String> {
+        self.halted_at.0.lock().unwrap().clone()
+    }
+
+    /// Let a halted graph run on until the next breakpoint. Does nothing while it runs.
+    pub fn continue_execution(&self) {
+        let (halted_at, changed) = &*self.halted_at;
+        *halted_at.lock().unwrap() = None;
+        changed.notify_all();
+    }
+
+    /// Block before `node_id` until `continue_execution`. Returns `false` if a stop is
+    /// requested first.
+    pub(crate) fn halt(&self, node_id: &str) -> bool {
+        let (halted_at, changed) = &*self.halted_at;
+        let mut halted_at = halted_at.lock().unwrap();
+        *halted_at = Some(node_id.to_string());
+        while halted_at.is_some() {
+            if self.stop_flag.load(Ordering::Relaxed) {
+                *halted_at = None;
+                return false;
+            }
+            halted_at = changed.wait_timeout(halted_at, STEP_POLL_INTERVAL).unwrap().0;
+        }
+        true
+    }
+}
+
+/// Keeps a stepped run marked as started, finishing it when dropped so a pending `step`
+/// returns even if the run ends early
+pub(crate) struct StepSession(GraphStepper);
+
+impl StepSession {
+    pub(crate) fn start(stepper: &GraphStepper) -> Self {
+        stepper.start();
+        Self(stepper.clone())
+    }
+}
+
+impl Drop for StepSession {
+    fn drop(&mut self) {
+        self.0.finish();
+    }
+}
@@ -0,0 +1,256 @@
+use crate::error::Result;
+use crate::node::graph_io::{load_graph_definition_from_json, NodeGraphDefinition};
+use crate::node::registry::build_node_graph_from_definition;
+use crate::node::{DataType, DataValue, Node, Port};
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Inline input holding the path of the embedded graph definition
+pub const GRAPH_PATH_PORT: &str = "graph_path";
+/// Guards against a graph that (indirectly) embeds itself
+const MAX_SUBGRAPH_DEPTH: usize = 8;
+
+thread_local! {
+    static SUBGRAPH_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Name of the outer port exposing `port` of the inner node `node_id`
+fn exposed_port_name(node_id: &str, port: &str) -> String {
+    format!("{}.{}", node_id, port)
+}
+
+/// Subgraph node - runs a saved NodeGraphDefinition as a single node
+///
+/// Inputs:
+///   - graph_path: Path of the graph JSON (inline value)
+///   - `<inner_node_id>.<port>`: One input per unbound required input of the inner graph
+///
+/// Outputs:
+///   - `<inner_node_id>.<port>`: One output per inner output no inner node consumes
+pub struct SubgraphNode {
+    id: String,
+    name: String,
+    definition: Option<NodeGraphDefinition>,
+    exposed_inputs: Vec<(String, Port)>,
+    exposed_outputs: Vec<(String, Port)>,
+    graph_path: Option<PathBuf>,
+}
+
+impl SubgraphNode {
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            definition: None,
+            exposed_inputs: Vec::new(),
+            exposed_outputs: Vec::new(),
+            graph_path: None,
+        }
+    }
+
+    /// Embed an already loaded definition
+    pub fn with_definition(id: impl Into<String>, name: impl Into<String>, definition: NodeGraphDefinition) -> Self {
+        let mut node = Self::new(id, name);
+        node.set_definition(definition);
+        node
+    }
+
+    /// Embed the definition stored at `path`
+    pub fn load(
+        id: impl Into<String>,
+        name: impl Into<String>,
+        path: impl AsRef<Path>,
+    ) -> std::result::Result<Self, String> {
+        let definition = Self::load_definition(path.as_ref())?;
+        Ok(Self::with_definition(id, name, definition))
+    }
+
+    fn load_definition(path: &Path) -> std::result::Result<NodeGraphDefinition, String> {
+        load_graph_definition_from_json(path)
+            .map_err(|e| format!("Failed to load subgraph '{}': {}", path.display(), e))
+    }
+
+    fn set_definition(&mut self, definition: NodeGraphDefinition) {
+        self.exposed_inputs = definition.unbound_inputs();
+        self.exposed_outputs = definition.unconsumed_outputs();
+        self.definition = Some(definition);
+    }
+
+    /// Prefix for inner node ids, so they never clash with the outer graph
+    fn namespace(&self) -> String {
+        format!("{}/", self.id)
+    }
+
+    fn run_inner(
+        &self,
+        definition: &NodeGraphDefinition,
+        inputs: &HashMap<String, DataValue>,
+    ) -> std::result::Result<HashMap<String, DataValue>, String> {
+        let namespace = self.namespace();
+        let mut graph =
+            build_node_graph_from_definition(&definition.namespaced(&namespace)).map_err(|e| e.to_string())?;
+        // Inner node ids are namespaced, so their state can live with the outer graph's
+        graph.set_graph_path(self.graph_path.clone());
+
+        for (node_id, port) in &self.exposed_inputs {
+            if let Some(value) = inputs.get(&exposed_port_name(node_id, &port.name)) {
+                graph
+                    .inline_values
+                    .entry(format!("{}{}", namespace, node_id))
+                    .or_default()
+                    .insert(port.name.clone(), value.clone());
+            }
+        }
+
+        let result = graph.execute_and_capture_results();
+        if let Some(message) = result.error_message {
+            return Err(format!("Subgraph '{}' failed: {}", self.id, message));
+        }
+
+        let mut outputs = HashMap::new();
+        for (node_id, port) in &self.exposed_outputs {
+            let value = result
+                .node_results
+                .get(&format!("{}{}", namespace, node_id))
+                .and_then(|values| values.get(&port.name));
+            if let Some(value) = value {
+                outputs.insert(exposed_port_name(node_id, &port.name), value.clone());
+            }
+        }
+        Ok(outputs)
+    }
+}
+
+impl Node for SubgraphNode {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> Option<&str> {
+        Some("子图 - 将已保存的节点图作为单个节点运行")
+    }
+
+    fn input_ports(&self) -> Vec<Port> {
+        let mut ports = vec![Port::new(GRAPH_PATH_PORT, DataType::String)
+            .with_description("子图JSON文件路径")
+            .optional()];
+        ports.extend(self.exposed_inputs.iter().map(|(node_id, port)| Port {
+            name: exposed_port_name(node_id, &port.name),
+            ..port.clone()
+        }));
+        ports
+    }
+
+    fn output_ports(&self) -> Vec<Port> {
+        self.exposed_outputs
+            .iter()
+            .map(|(node_id, port)| Port {
+                name: exposed_port_name(node_id, &port.name),
+                ..port.clone()
+            })
+            .collect()
+    }
+
+    fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+        if self.definition.is_none() {
+            match inputs.get(GRAPH_PATH_PORT) {
+                Some(DataValue::String(path)) if !path.trim().is_empty() => {
+                    let definition =
+                        Self::load_definition(Path::new(path.trim())).map_err(crate::error::Error::ValidationError)?;
+                    self.set_definition(definition);
+                }
+                _ => return Err(crate::error::Error::InvalidNodeInput("graph_path is required".to_string())),
+            }
+        }
+        self.validate_inputs(&inputs)?;
+
+        let depth = SUBGRAPH_DEPTH.with(|depth| depth.get());
+        if depth >= MAX_SUBGRAPH_DEPTH {
+            return Err(crate::error::Error::ValidationError(format!(
+                "Subgraphs nested more than {} levels deep, does the graph embed itself?",
+                MAX_SUBGRAPH_DEPTH
+            )));
+        }
+
+        let definition = self.definition.clone().unwrap_or_default();
+        SUBGRAPH_DEPTH.with(|d| d.set(depth + 1));
+        let outputs = self.run_inner(&definition, &inputs);
+        SUBGRAPH_DEPTH.with(|d| d.set(depth));
+        let outputs = outputs.map_err(crate::error::Error::ValidationError)?;
+
+        self.validate_outputs(&outputs)?;
+        Ok(outputs)
+    }
+    fn set_graph_path(&mut self, graph_path: Option<&Path>) {
+        self.graph_path = graph_path.map(Path::to_path_buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::graph_io::{EdgeDefinition, NodeDefinition};
+    use crate::node::registry::{init_node_registry, NODE_REGISTRY};
+
+    fn node_definition(id: &str, node_type: &str) -> NodeDefinition {
+        let node = NODE_REGISTRY.create_node(node_type, id, id).unwrap();
+        NodeDefinition {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: None,
+            node_type: node_type.to_string(),
+            input_ports: node.input_ports(),
+            output_ports: node.output_ports(),
+            position: None,
+            size: None,
+            inline_values: HashMap::new(),
+            has_error: false,
+        }
+    }
+
+    #[test]
+    fn test_subgraph_exposes_unbound_ports_and_runs_inner_graph() {
+        init_node_registry().unwrap();
+        // parser.parsed feeds the conditional, parser.success is left for the outer graph
+        let definition = NodeGraphDefinition {
+            nodes: vec![node_definition("parser", "json_parser"), node_definition("branch", "conditional")],
+            edges: vec![EdgeDefinition {
+                from_node_id: "parser".to_string(),
+                from_port: "parsed".to_string(),
+                to_node_id: "branch".to_string(),
+                to_port: "true_value".to_string(),
+                condition: None,
+            }],
+            ..Default::default()
+        };
+
+        let mut node = SubgraphNode::with_definition("sub", "Subgraph", definition);
+        let input_names: Vec<String> = node.input_ports().into_iter().map(|p| p.name).collect();
+        assert_eq!(
+            input_names,
+            ["graph_path", "branch.condition", "branch.false_value", "parser.json_string"]
+        );
+        let output_names: Vec<String> = node.output_ports().into_iter().map(|p| p.name).collect();
+        assert_eq!(
+            output_names,
+            ["branch.result", "branch.branch_taken", "parser.success", "parser.parsed_datetime"]
+        );
+
+        let inputs = HashMap::from([
+            ("parser.json_string".to_string(), DataValue::String(r#"{"a": 1}"#.to_string())),
+            ("branch.condition".to_string(), DataValue::Boolean(true)),
+            ("branch.false_value".to_string(), DataValue::Json(serde_json::Value::Null)),
+        ]);
+        let outputs = node.execute(inputs).unwrap();
+        assert!(matches!(outputs.get("parser.success"), Some(DataValue::Boolean(true))));
+        assert!(matches!(
+            outputs.get("branch.result"),
+            Some(DataValue::Json(value)) if value == &serde_json::json!({"a": 1})
+        ));
+    }
+}
@@ -0,0 +1,1254 @@
+use crate::error::Result;
+use crate::llm::llm_api::LLMAPI;
+use crate::llm::{InferenceParam, LLMBase};
+use crate::node::state_store::{get_node_state, set_persisted_node_state};
+use crate::node::{node_input, node_output, DataType, DataValue, Node, Port};
+use chrono::Timelike;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// A single text transform usable in a TextPipelineNode step list
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TextStep {
+    Trim,
+    Lowercase,
+    Uppercase,
+    CollapseWhitespace,
+    StripMarkdown,
+    /// Keep at most N characters
+    Truncate(usize),
+}
+
+impl TextStep {
+    /// Parse a step name such as `trim` or `truncate:100`
+    pub fn parse(step: &str) -> std::result::Result<Self, String> {
+        let step = step.trim();
+        let (name, arg) = match step.split_once(':') {
+            Some((name, arg)) => (name.trim(), Some(arg.trim())),
+            None => (step, None),
+        };
+
+        let parsed = match (name, arg) {
+            ("trim", None) => TextStep::Trim,
+            ("lowercase", None) => TextStep::Lowercase,
+            ("uppercase", None) => TextStep::Uppercase,
+            ("collapse_whitespace", None) => TextStep::CollapseWhitespace,
+            ("strip_markdown", None) => TextStep::StripMarkdown,
+            ("truncate", Some(n)) => TextStep::Truncate(n.parse().map_err(|_| {
+                format!("Invalid text step '{}': truncate expects a non-negative integer", step)
+            })?),
+            _ => return Err(format!("Unknown text step '{}'", step)),
+        };
+        Ok(parsed)
+    }
+
+    pub fn apply(&self, text: &str) -> String {
+        match self {
+            TextStep::Trim => text.trim().to_string(),
+            TextStep::Lowercase => text.to_lowercase(),
+            TextStep::Uppercase => text.to_uppercase(),
+            TextStep::CollapseWhitespace => text.split_whitespace().collect::<Vec<_>>().join(" "),
+            TextStep::StripMarkdown => strip_markdown(text),
+            TextStep::Truncate(max_chars) => text.chars().take(*max_chars).collect(),
+        }
+    }
+}
+
+/// Remove common markdown syntax, keeping the readable text.
+/// LLM replies often contain markdown that QQ renders literally.
+pub fn strip_markdown(text: &str) -> String {
+    let mut lines = Vec::new();
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        // Drop code fences but keep the code itself
+        if trimmed.starts_with("```") {
+            continue;
+        }
+        let trimmed = trimmed.trim_start_matches('#').trim_start_matches('>');
+        let trimmed = trimmed
+            .strip_prefix("- ")
+            .or_else(|| trimmed.strip_prefix("* "))
+            .unwrap_or(trimmed);
+        lines.push(strip_inline_markdown(trimmed.trim_start()));
+    }
+    lines.join("\n")
+}
+
+/// Index of the run of `run` markers closing the one at `open`, if that one opens at a
+/// word start. The closing run must end a word and match the opening run's length.
+/// Words are judged by `is_word_char`, so markers next to CJK text still pair up.
+fn find_closing_marker(chars: &[char], open: usize, run: usize) -> Option<usize> {
+    let marker = chars[open];
+    let opens_word = open == 0 || !is_word_char(chars[open - 1]);
+    if !opens_word || chars.get(open + run).is_none_or(|c| c.is_whitespace()) {
+        return None;
+    }
+    let mut i = open + run + 1;
+    while i + run <= chars.len() {
+        let is_run = chars[i..i + run].iter().all(|&c| c == marker)
+            && chars[i - 1] != marker
+            && chars.get(i + run) != Some(&marker);
+        let closes_word = !chars[i - 1].is_whitespace() && chars.get(i + run).is_none_or(|&c| !is_word_char(c));
+        if is_run && closes_word {
+            return Some(i);
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Remove paired emphasis, strikethrough and inline code markers and link syntax. Markers
+/// without a partner, or inside a word like `snake_case` or `2*3*4`, are kept.
+fn strip_inline_markdown(line: &str) -> String {
+    let chars: Vec<char> = line.chars().collect();
+    let mut out = String::with_capacity(line.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            '*' | '_' | '`' | '~' => {
+                let run = chars[i..].iter().take_while(|&&m| m == c).count();
+                match find_closing_marker(&chars, i, run) {
+                    Some(close) => {
+                        let inner: String = chars[i + run..close].iter().collect();
+                        // Code is shown as written
+                        if c == '`' {
+                            out.push_str(&inner);
+                        } else {
+                            out.push_str(&strip_inline_markdown(&inner));
+                        }
+                        i = close + run;
+                    }
+                    None => {
+                        out.extend(&chars[i..i + run]);
+                        i += run;
+                    }
+                }
+            }
+            // [text](url) -> text
+            '[' => {
+                let rest: String = chars[i + 1..].iter().collect();
+                if let Some((label, after)) = rest.split_once("](") {
+                    if let Some(close) = after.find(')') {
+                        out.push_str(&strip_inline_markdown(label));
+                        i += 1 + label.chars().count() + 2 + after[..close].chars().count() + 1;
+                        continue;
+                    }
+                }
+                out.push(c);
+                i += 1;
+            }
+            _ => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+/// Text pipeline node - applies an ordered list of text transforms
+pub struct TextPipelineNode {
+    id: String,
+    name: String,
+}
+
+impl TextPipelineNode {
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+        }
+    }
+}
+
+impl Node for TextPipelineNode {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> Option<&str> {
+        Some("文本处理流水线 - 按顺序执行 trim/lowercase/uppercase/collapse_whitespace/strip_markdown/truncate:N")
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    node_input![
+        port! { name = "text", ty = String, desc = "输入文本" },
+        port! { name = "steps", ty = List(String), desc = "按顺序执行的处理步骤，例如 trim、strip_markdown、truncate:100" },
+    ];
+
+    node_output![
+        port! { name = "result", ty = String, desc = "处理后的文本" },
+    ];
+
+    fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+        self.validate_inputs(&inputs)?;
+
+        let text = match inputs.get("text") {
+            Some(DataValue::String(s)) => s.clone(),
+            _ => return Err(crate::error::Error::InvalidNodeInput("text is required".to_string())),
+        };
+        let steps = match inputs.get("steps") {
+            Some(DataValue::List(items)) => items
+                .iter()
+                .map(|item| match item {
+                    DataValue::String(s) => TextStep::parse(s),
+                    other => Err(format!("steps must contain strings, got {}", other.data_type())),
+                })
+                .collect::<std::result::Result<Vec<_>, String>>()
+                .map_err(crate::error::Error::ValidationError)?,
+            _ => return Err(crate::error::Error::InvalidNodeInput("steps is required".to_string())),
+        };
+
+        let result = steps.iter().fold(text, |acc, step| step.apply(&acc));
+
+        let mut outputs = HashMap::new();
+        outputs.insert("result".to_string(), DataValue::String(result));
+
+        self.validate_outputs(&outputs)?;
+        Ok(outputs)
+    }
+}
+
+/// Affixes used by VaryReplyNode when no variations are given. `{}` is replaced by the reply.
+const DEFAULT_VARIATIONS: &[&str] = &["{}~", "嗯，{}", "{}！", "唔，{}"];
+
+const PARAPHRASE_PROMPT: &str = "请在不改变意思的前提下，用略微不同的说法改写下面这句话。只输出改写后的句子。";
+
+/// Apply a variation to `text`: templates containing `{}` wrap the text,
+/// anything else is appended as a suffix. The original text is always kept whole.
+fn apply_variation(text: &str, variation: &str) -> String {
+    if variation.contains("{}") {
+        variation.replacen("{}", text, 1)
+    } else {
+        format!("{}{}", text, variation)
+    }
+}
+
+/// Pick either the unchanged text or one of the variations uniformly at random
+pub fn vary_with_templates<R: Rng>(text: &str, variations: &[String], rng: &mut R) -> String {
+    let choice = rng.gen_range(0..=variations.len());
+    match variations.get(choice) {
+        Some(variation) => apply_variation(text, variation),
+        None => text.to_string(),
+    }
+}
+
+/// Vary reply node - lightly decorates or paraphrases a reply to reduce repetitiveness
+pub struct VaryReplyNode {
+    id: String,
+    name: String,
+}
+
+impl VaryReplyNode {
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+        }
+    }
+}
+
+impl Node for VaryReplyNode {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> Option<&str> {
+        Some("回复变化 - 随机为回复添加前后缀，或在提供模型配置时由LLM改写")
+    }
+
+    node_input![
+        port! { name = "text", ty = String, desc = "原始回复" },
+        port! { name = "variations", ty = List(String), desc = "前后缀模板，{} 代表原文，不含 {} 时作为后缀", optional },
+        port! { name = "seed", ty = Integer, desc = "随机种子，用于复现结果", optional },
+        port! { name = "model_name", ty = String, desc = "改写所用模型名称（与api_endpoint同时提供时启用LLM改写）", optional },
+        port! { name = "api_endpoint", ty = String, desc = "改写所用API端点URL", optional },
+        port! { name = "api_key", ty = Password, desc = "API密钥", optional },
+    ];
+
+    node_output![
+        port! { name = "result", ty = String, desc = "变化后的回复" },
+    ];
+
+    fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+        self.validate_inputs(&inputs)?;
+
+        let text = match inputs.get("text") {
+            Some(DataValue::String(s)) => s.clone(),
+            _ => return Err(crate::error::Error::InvalidNodeInput("text is required".to_string())),
+        };
+        let string_input = |name: &str| match inputs.get(name) {
+            Some(DataValue::String(s)) | Some(DataValue::Password(s)) if !s.is_empty() => Some(s.clone()),
+            _ => None,
+        };
+
+        let result = match (string_input("model_name"), string_input("api_endpoint")) {
+            (Some(model_name), Some(api_endpoint)) => {
+                let llm = LLMAPI::new(
+                    model_name,
+                    api_endpoint,
+                    string_input("api_key"),
+                    Duration::from_secs(60),
+                );
+                let messages = vec![LLMAPI::system_message(PARAPHRASE_PROMPT), LLMAPI::user_message(&text)];
+                let response = llm.inference(&InferenceParam::new(&messages));
+                // Keep the original reply if the model fails or returns nothing
+                match response.content {
+                    Some(content) if !content.trim().is_empty() && !content.starts_with("Error:") => {
+                        content.trim().to_string()
+                    }
+                    _ => text,
+                }
+            }
+            _ => {
+                let variations: Vec<String> = match inputs.get("variations") {
+                    Some(DataValue::List(items)) if !items.is_empty() => items
+                        .iter()
+                        .filter_map(|item| match item {
+                            DataValue::String(s) => Some(s.clone()),
+                            _ => None,
+                        })
+                        .collect(),
+                    _ => DEFAULT_VARIATIONS.iter().map(|s| s.to_string()).collect(),
+                };
+                let mut rng = match inputs.get("seed") {
+                    Some(DataValue::Integer(seed)) => StdRng::seed_from_u64(*seed as u64),
+                    _ => StdRng::from_entropy(),
+                };
+                vary_with_templates(&text, &variations, &mut rng)
+            }
+        };
+
+        let mut outputs = HashMap::new();
+        outputs.insert("result".to_string(), DataValue::String(result));
+
+        self.validate_outputs(&outputs)?;
+        Ok(outputs)
+    }
+}
+
+/// Characters that continue a word; a banned term must not be surrounded by them.
+/// CJK text has no word separators, so only ASCII letters and digits count.
+fn is_word_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+fn chars_eq_ignore_case(a: char, b: char) -> bool {
+    a == b || a.to_lowercase().eq(b.to_lowercase())
+}
+
+/// Whether `term` occurs at `start` in `text` as a whole word
+fn term_matches_at(text: &[char], start: usize, term: &[char]) -> bool {
+    let end = start + term.len();
+    if term.is_empty() || end > text.len() {
+        return false;
+    }
+    if !text[start..end]
+        .iter()
+        .zip(term)
+        .all(|(a, b)| chars_eq_ignore_case(*a, *b))
+    {
+        return false;
+    }
+    // Boundaries only matter where the term itself starts or ends with a word character
+    let left_ok = !is_word_char(term[0]) || start == 0 || !is_word_char(text[start - 1]);
+    let right_ok = !is_word_char(term[term.len() - 1]) || end == text.len() || !is_word_char(text[end]);
+    left_ok && right_ok
+}
+
+/// Replace banned terms (case-insensitive, whole words) and count the hits.
+/// With no `replacement` each hit is masked with one `*` per character.
+pub fn filter_words(text: &str, banned: &[String], replacement: Option<&str>) -> (String, usize) {
+    let text: Vec<char> = text.chars().collect();
+    let mut terms: Vec<Vec<char>> = banned
+        .iter()
+        .map(|term| term.trim())
+        .filter(|term| !term.is_empty())
+        .map(|term| term.chars().collect())
+        .collect();
+    // Prefer the longest term when several match at the same position
+    terms.sort_by_key(|term| std::cmp::Reverse(term.len()));
+
+    let mut result = String::with_capacity(text.len());
+    let mut hits = 0;
+    let mut i = 0;
+    while i < text.len() {
+        match terms.iter().find(|term| term_matches_at(&text, i, term)) {
+            Some(term) => {
+                match replacement {
+                    Some(replacement) => result.push_str(replacement),
+                    None => result.push_str(&"*".repeat(term.len())),
+                }
+                hits += 1;
+                i += term.len();
+            }
+            None => {
+                result.push(text[i]);
+                i += 1;
+            }
+        }
+    }
+    (result, hits)
+}
+
+/// Word filter node - masks banned terms in outgoing text, or blocks the message entirely
+pub struct WordFilterNode {
+    id: String,
+    name: String,
+}
+
+impl WordFilterNode {
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+        }
+    }
+}
+
+impl Node for WordFilterNode {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> Option<&str> {
+        Some("敏感词过滤 - 替换文本中的违禁词（不区分大小写，按整词匹配），或直接拦截消息")
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    node_input![
+        port! { name = "text", ty = String, desc = "待过滤的文本" },
+        port! { name = "banned", ty = List(String), desc = "违禁词列表" },
+        port! { name = "replacement", ty = String, desc = "替换文本 (默认: 按字数替换为*)", optional },
+        port! { name = "block_on_hit", ty = Boolean, desc = "命中时拦截整条消息而不是替换 (默认: false)", optional },
+    ];
+
+    node_output![
+        port! { name = "result", ty = String, desc = "过滤后的文本，被拦截时为空" },
+        port! { name = "hits", ty = Integer, desc = "命中的违禁词次数" },
+        port! { name = "allowed", ty = Boolean, desc = "消息是否允许发送" },
+    ];
+
+    fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+        self.validate_inputs(&inputs)?;
+
+        let text = match inputs.get("text") {
+            Some(DataValue::String(s)) => s.clone(),
+            _ => return Err(crate::error::Error::InvalidNodeInput("text is required".to_string())),
+        };
+        let banned: Vec<String> = match inputs.get("banned") {
+            Some(DataValue::List(items)) => items
+                .iter()
+                .filter_map(|item| match item {
+                    DataValue::String(s) => Some(s.clone()),
+                    _ => None,
+                })
+                .collect(),
+            _ => return Err(crate::error::Error::InvalidNodeInput("banned is required".to_string())),
+        };
+        let replacement = match inputs.get("replacement") {
+            Some(DataValue::String(s)) if !s.is_empty() => Some(s.as_str()),
+            _ => None,
+        };
+        let block_on_hit = matches!(inputs.get("block_on_hit"), Some(DataValue::Boolean(true)));
+
+        let (filtered, hits) = filter_words(&text, &banned, replacement);
+        let allowed = !(block_on_hit && hits > 0);
+        // A blocked message must not leak downstream, filtered or not
+        let result = if allowed { filtered } else { String::new() };
+
+        let mut outputs = HashMap::new();
+        outputs.insert("result".to_string(), DataValue::String(result));
+        outputs.insert("hits".to_string(), DataValue::Integer(hits as i64));
+        outputs.insert("allowed".to_string(), DataValue::Boolean(allowed));
+
+        self.validate_outputs(&outputs)?;
+        Ok(outputs)
+    }
+}
+
+/// Part of the day used to pick a greeting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DayPeriod {
+    Morning,
+    Afternoon,
+    Evening,
+    Night,
+}
+
+impl DayPeriod {
+    /// Morning 5-11, afternoon 12-17, evening 18-22, night 23-4
+    pub fn from_hour(hour: u32) -> Self {
+        match hour {
+            5..=11 => DayPeriod::Morning,
+            12..=17 => DayPeriod::Afternoon,
+            18..=22 => DayPeriod::Evening,
+            _ => DayPeriod::Night,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DayPeriod::Morning => "morning",
+            DayPeriod::Afternoon => "afternoon",
+            DayPeriod::Evening => "evening",
+            DayPeriod::Night => "night",
+        }
+    }
+
+    fn default_greeting(&self) -> &'static str {
+        match self {
+            DayPeriod::Morning => "早上好！新的一天也要元气满满哦~",
+            DayPeriod::Afternoon => "下午好！记得休息一下，喝杯水~",
+            DayPeriod::Evening => "晚上好！今天辛苦啦~",
+            DayPeriod::Night => "夜深了，早点休息，晚安~",
+        }
+    }
+}
+
+/// Greeting for `period`, taken from `overrides` (keyed by period name) when present
+pub fn greeting_for(period: DayPeriod, overrides: Option<&serde_json::Value>) -> String {
+    overrides
+        .and_then(|map| map.get(period.as_str()))
+        .and_then(|value| value.as_str())
+        .filter(|greeting| !greeting.trim().is_empty())
+        .unwrap_or_else(|| period.default_greeting())
+        .to_string()
+}
+
+/// Greeting node - picks a greeting matching the current time of day
+pub struct GreetingNode {
+    id: String,
+    name: String,
+}
+
+impl GreetingNode {
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+        }
+    }
+}
+
+impl Node for GreetingNode {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> Option<&str> {
+        Some("问候语 - 根据当前时段（早上/下午/晚上/深夜）选择问候语")
+    }
+
+    node_input![
+        port! { name = "overrides", ty = Json, desc = "自定义问候语，键为 morning/afternoon/evening/night", optional },
+        port! { name = "hour", ty = Integer, desc = "指定小时 0-23 (默认: 当前本地时间)", optional, min = 0, max = 23 },
+    ];
+
+    node_output![
+        port! { name = "greeting", ty = String, desc = "问候语" },
+        port! { name = "period", ty = String, desc = "时段: morning/afternoon/evening/night" },
+    ];
+
+    fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+        self.validate_inputs(&inputs)?;
+
+        let hour = match inputs.get("hour") {
+            // Bounds are checked by validate_inputs
+            Some(DataValue::Integer(hour)) => *hour as u32,
+            _ => chrono::Local::now().hour(),
+        };
+        let overrides = match inputs.get("overrides") {
+            Some(DataValue::Json(value)) => Some(value),
+            _ => None,
+        };
+
+        let period = DayPeriod::from_hour(hour);
+        let mut outputs = HashMap::new();
+        outputs.insert("greeting".to_string(), DataValue::String(greeting_for(period, overrides)));
+        outputs.insert("period".to_string(), DataValue::String(period.as_str().to_string()));
+
+        self.validate_outputs(&outputs)?;
+        Ok(outputs)
+    }
+}
+
+/// Normalized edit-distance similarity in [0, 1]; 1.0 means identical.
+/// Compares characters, so it works the same for CJK and latin text.
+pub fn text_similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+
+    // Levenshtein distance with a single rolling row
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    1.0 - row[b.len()] as f64 / longest as f64
+}
+
+/// How MarkdownTableToTextNode rewrites a table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableStyle {
+    /// One block of `header: value` lines per row
+    KeyValue,
+    /// Space-padded columns, for clients using a monospace font
+    Aligned,
+}
+
+impl TableStyle {
+    pub fn parse(style: &str) -> std::result::Result<Self, String> {
+        match style.trim() {
+            "kv" | "key_value" => Ok(TableStyle::KeyValue),
+            "aligned" => Ok(TableStyle::Aligned),
+            other => Err(format!("Unknown table style '{}', expected 'kv' or 'aligned'", other)),
+        }
+    }
+}
+
+/// Split a table line into trimmed cells; `\|` is kept as a literal pipe
+fn table_cells(line: &str) -> Option<Vec<String>> {
+    let line = line.trim();
+    if !line.contains('|') {
+        return None;
+    }
+    let line = line.strip_prefix('|').unwrap_or(line);
+    let line = line.strip_suffix('|').unwrap_or(line);
+    let mut cells = Vec::new();
+    let mut current = String::new();
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if chars.peek() == Some(&'|') => {
+                current.push('|');
+                chars.next();
+            }
+            '|' => cells.push(std::mem::take(&mut current).trim().to_string()),
+            _ => current.push(c),
+        }
+    }
+    cells.push(current.trim().to_string());
+    Some(cells)
+}
+
+fn is_separator_row(cells: &[String]) -> bool {
+    cells.iter().all(|cell| {
+        let dashes = cell.trim_start_matches(':').trim_end_matches(':');
+        !dashes.is_empty() && dashes.chars().all(|c| c == '-')
+    })
+}
+
+/// Display width, counting wide (CJK, fullwidth) characters as two columns
+fn display_width(text: &str) -> usize {
+    text.chars().map(|c| if c >= '\u{1100}' { 2 } else { 1 }).sum()
+}
+
+fn render_table(header: &[String], rows: &[Vec<String>], style: TableStyle) -> Vec<String> {
+    match style {
+        TableStyle::KeyValue => {
+            let mut lines = Vec::new();
+            for (index, row) in rows.iter().enumerate() {
+                if index > 0 {
+                    lines.push(String::new());
+                }
+                for (key, value) in header.iter().zip(row) {
+                    lines.push(format!("{}: {}", key, value));
+                }
+            }
+            lines
+        }
+        TableStyle::Aligned => {
+            let widths: Vec<usize> = (0..header.len())
+                .map(|col| {
+                    std::iter::once(&header[col])
+                        .chain(rows.iter().map(|row| &row[col]))
+                        .map(|cell| display_width(cell))
+                        .max()
+                        .unwrap_or(0)
+                })
+                .collect();
+            std::iter::once(header)
+                .chain(rows.iter().map(|row| row.as_slice()))
+                .map(|row| {
+                    let padded: Vec<String> = row
+                        .iter()
+                        .zip(&widths)
+                        .map(|(cell, width)| format!("{}{}", cell, " ".repeat(width - display_width(cell))))
+                        .collect();
+                    padded.join("  ").trim_end().to_string()
+                })
+                .collect()
+        }
+    }
+}
+
+/// Rewrite Markdown tables in `text`, leaving everything else untouched.
+/// A table whose separator or rows do not match the header's column count is left as is.
+pub fn convert_markdown_tables(text: &str, style: TableStyle) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut output: Vec<String> = Vec::with_capacity(lines.len());
+    let mut i = 0;
+    while i < lines.len() {
+        let header = table_cells(lines[i]);
+        let separator = lines.get(i + 1).and_then(|line| table_cells(line));
+        let (header, separator) = match (header, separator) {
+            (Some(header), Some(separator)) if is_separator_row(&separator) => (header, separator),
+            _ => {
+                output.push(lines[i].to_string());
+                i += 1;
+                continue;
+            }
+        };
+
+        let mut end = i + 2;
+        let mut rows = Vec::new();
+        while let Some(cells) = lines.get(end).and_then(|line| table_cells(line)) {
+            rows.push(cells);
+            end += 1;
+        }
+
+        let well_formed = separator.len() == header.len() && rows.iter().all(|row| row.len() <= header.len());
+        if well_formed {
+            // Rows missing trailing cells are common in LLM output; treat them as empty
+            for row in &mut rows {
+                row.resize(header.len(), String::new());
+            }
+            output.extend(render_table(&header, &rows, style));
+        } else {
+            output.extend(lines[i..end].iter().map(|line| line.to_string()));
+        }
+        i = end;
+    }
+
+    let mut result = output.join("\n");
+    if text.ends_with('\n') {
+        result.push('\n');
+    }
+    result
+}
+
+/// Markdown table to text node - rewrites tables in LLM output into text that reads well in QQ
+pub struct MarkdownTableToTextNode {
+    id: String,
+    name: String,
+}
+
+impl MarkdownTableToTextNode {
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+        }
+    }
+}
+
+impl Node for MarkdownTableToTextNode {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> Option<&str> {
+        Some("Markdown表格转文本 - 将文本中的Markdown表格改写为键值列表或对齐文本，其余内容保持不变")
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    node_input![
+        port! { name = "text", ty = String, desc = "包含Markdown表格的文本" },
+        port! { name = "style", ty = String, desc = "输出样式: kv（键值列表）或 aligned（对齐列） (默认: kv)", optional },
+    ];
+
+    node_output![
+        port! { name = "result", ty = String, desc = "转换后的文本" },
+    ];
+
+    fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+        self.validate_inputs(&inputs)?;
+
+        let text = match inputs.get("text") {
+            Some(DataValue::String(s)) => s.clone(),
+            _ => return Err(crate::error::Error::InvalidNodeInput("text is required".to_string())),
+        };
+        let style = match inputs.get("style") {
+            Some(DataValue::String(s)) if !s.trim().is_empty() => {
+                TableStyle::parse(s).map_err(crate::error::Error::InvalidNodeInput)?
+            }
+            _ => TableStyle::KeyValue,
+        };
+
+        let mut outputs = HashMap::new();
+        outputs.insert("result".to_string(), DataValue::String(convert_markdown_tables(&text, style)));
+
+        self.validate_outputs(&outputs)?;
+        Ok(outputs)
+    }
+}
+
+const DEFAULT_TOPIC_UPDATE_EVERY: i64 = 5;
+/// Messages kept between topic updates; older ones are dropped
+const MAX_PENDING_TOPIC_MESSAGES: usize = 20;
+
+const TOPIC_PROMPT: &str = "你负责追踪一段群聊或私聊的当前话题。根据之前的话题和最新的消息，用一句不超过20字的话概括当前话题。只输出话题本身。";
+
+/// Topic tracker node - keeps a short, LLM-maintained summary of each conversation's topic
+pub struct TopicTrackerNode {
+    id: String,
+    name: String,
+    /// Used instead of building an LLMAPI from the inputs, e.g. in tests
+    llm: Option<Box<dyn LLMBase + Send + Sync>>,
+    graph_path: Option<PathBuf>,
+}
+
+impl TopicTrackerNode {
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            llm: None,
+            graph_path: None,
+        }
+    }
+
+    /// Use a fixed LLM instead of the one configured through the inputs
+    pub fn with_llm(id: impl Into<String>, name: impl Into<String>, llm: Box<dyn LLMBase + Send + Sync>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            llm: Some(llm),
+            graph_path: None,
+        }
+    }
+
+    /// Ask the LLM for the new topic, `None` if it fails or answers with nothing
+    fn summarize(llm: &dyn LLMBase, previous_topic: &str, messages: &[String]) -> Option<String> {
+        let previous = if previous_topic.is_empty() { "（无）" } else { previous_topic };
+        let prompt = format!("之前的话题：{}\n最新的消息：\n{}", previous, messages.join("\n"));
+        let request = vec![LLMAPI::system_message(TOPIC_PROMPT), LLMAPI::user_message(&prompt)];
+        let response = llm.inference(&InferenceParam::new(&request));
+        match response.content {
+            Some(content) if !content.trim().is_empty() && !content.starts_with("Error:") => {
+                Some(content.trim().to_string())
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Node for TopicTrackerNode {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> Option<&str> {
+        Some("话题追踪 - 按会话维护当前话题摘要，每隔N条消息调用LLM更新一次")
+    }
+
+    node_input![
+        port! { name = "conversation_id", ty = String, desc = "会话标识，例如 group:123456" },
+        port! { name = "message", ty = String, desc = "最新的消息内容" },
+        port! { name = "update_every", ty = Integer, desc = "每收到多少条消息更新一次话题 (默认: 5)", optional },
+        port! { name = "model_name", ty = String, desc = "模型名称", optional },
+        port! { name = "api_endpoint", ty = String, desc = "API端点URL", optional },
+        port! { name = "api_key", ty = Password, desc = "API密钥", optional },
+    ];
+
+    node_output![
+        port! { name = "topic", ty = String, desc = "当前话题，尚未生成时为空" },
+        port! { name = "updated", ty = Boolean, desc = "本次是否更新了话题" },
+    ];
+
+    fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+        self.validate_inputs(&inputs)?;
+
+        let string_input = |name: &str| match inputs.get(name) {
+            Some(DataValue::String(s)) | Some(DataValue::Password(s)) if !s.is_empty() => Some(s.clone()),
+            _ => None,
+        };
+        let conversation = string_input("conversation_id")
+            .ok_or_else(|| crate::error::Error::InvalidNodeInput("conversation_id is required".to_string()))?;
+        let message = string_input("message")
+            .ok_or_else(|| crate::error::Error::InvalidNodeInput("message is required".to_string()))?;
+        let update_every = match inputs.get("update_every") {
+            Some(DataValue::Integer(i)) => (*i).max(1) as usize,
+            _ => DEFAULT_TOPIC_UPDATE_EVERY as usize,
+        };
+
+        let state = get_node_state(self.graph_path.as_deref(), &self.id, &conversation).unwrap_or(serde_json::Value::Null);
+        let mut topic = state["topic"].as_str().unwrap_or_default().to_string();
+        let mut pending: Vec<String> = serde_json::from_value(state["pending"].clone()).unwrap_or_default();
+        pending.push(message);
+        if pending.len() > MAX_PENDING_TOPIC_MESSAGES {
+            pending.drain(..pending.len() - MAX_PENDING_TOPIC_MESSAGES);
+        }
+
+        // The first message sets a topic right away; after that only every `update_every` messages
+        let mut updated = false;
+        if topic.is_empty() || pending.len() >= update_every {
+            let summary = match &self.llm {
+                Some(llm) => Self::summarize(llm.as_ref(), &topic, &pending),
+                None => {
+                    let (model_name, api_endpoint) = match (string_input("model_name"), string_input("api_endpoint")) {
+                        (Some(model_name), Some(api_endpoint)) => (model_name, api_endpoint),
+                        _ => {
+                            return Err(crate::error::Error::InvalidNodeInput(
+                                "model_name and api_endpoint are required".to_string(),
+                            ))
+                        }
+                    };
+                    let llm = LLMAPI::new(model_name, api_endpoint, string_input("api_key"), Duration::from_secs(60));
+                    Self::summarize(&llm, &topic, &pending)
+                }
+            };
+            // On failure the messages stay pending and are retried with the next one
+            if let Some(summary) = summary {
+                topic = summary;
+                pending.clear();
+                updated = true;
+            }
+        }
+        set_persisted_node_state(
+            self.graph_path.as_deref(),
+            &self.id,
+            &conversation,
+            serde_json::json!({ "topic": topic, "pending": pending }),
+        );
+
+        let mut outputs = HashMap::new();
+        outputs.insert("topic".to_string(), DataValue::String(topic));
+        outputs.insert("updated".to_string(), DataValue::Boolean(updated));
+
+        self.validate_outputs(&outputs)?;
+        Ok(outputs)
+    }
+    fn set_graph_path(&mut self, graph_path: Option<&Path>) {
+        self.graph_path = graph_path.map(Path::to_path_buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(text: &str, steps: &[&str]) -> std::result::Result<String, String> {
+        let mut node = TextPipelineNode::new("text_pipeline_test", "TextPipeline");
+        let mut inputs = HashMap::new();
+        inputs.insert("text".to_string(), DataValue::String(text.to_string()));
+        inputs.insert(
+            "steps".to_string(),
+            DataValue::List(
+                steps
+                    .iter()
+                    .map(|s| DataValue::String(s.to_string()))
+                    .collect(),
+            ),
+        );
+        match node.execute(inputs) {
+            Ok(outputs) => match outputs.get("result") {
+                Some(DataValue::String(s)) => Ok(s.clone()),
+                _ => panic!("result should be a String"),
+            },
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_multi_step_pipeline() {
+        let result = run(
+            "  ## **Hello** [World](https://example.com)  ",
+            &["trim", "strip_markdown", "lowercase", "truncate:8"],
+        )
+        .unwrap();
+        assert_eq!(result, "hello wo");
+    }
+
+    #[test]
+    fn test_strip_markdown_keeps_markers_inside_words() {
+        assert_eq!(strip_markdown("snake_case_name"), "snake_case_name");
+        assert_eq!(strip_markdown("2*3*4"), "2*3*4");
+        assert_eq!(strip_markdown("a * b * c"), "a * b * c");
+        assert_eq!(strip_markdown("use *this* and __that__, ~~not~~ `co_de`"), "use this and that, not co_de");
+        assert_eq!(strip_markdown("**加粗**文本"), "加粗文本");
+        assert_eq!(strip_markdown("[**docs**](https://example.com/a_b) end"), "docs end");
+    }
+
+    #[test]
+    fn test_unknown_step_is_named_in_error() {
+        let err = run("text", &["trim", "reverse"]).unwrap_err();
+        assert!(err.contains("reverse"));
+    }
+
+    fn vary(text: &str, variations: &[&str], seed: i64) -> String {
+        let mut node = VaryReplyNode::new("vary_reply_test", "VaryReply");
+        let inputs = HashMap::from([
+            ("text".to_string(), DataValue::String(text.to_string())),
+            (
+                "variations".to_string(),
+                DataValue::List(variations.iter().map(|v| DataValue::String(v.to_string())).collect()),
+            ),
+            ("seed".to_string(), DataValue::Integer(seed)),
+        ]);
+        match node.execute(inputs).unwrap().remove("result") {
+            Some(DataValue::String(s)) => s,
+            _ => panic!("result should be a String"),
+        }
+    }
+
+    #[test]
+    fn test_vary_reply_is_deterministic_with_seed() {
+        let variations = ["{}~", "Well, {}", " :)"];
+        let first = vary("see you tomorrow", &variations, 42);
+        assert_eq!(first, vary("see you tomorrow", &variations, 42));
+
+        let mut seen = std::collections::HashSet::new();
+        for seed in 0..50 {
+            let result = vary("see you tomorrow", &variations, seed);
+            // Template mode only adds affixes, the original text stays intact
+            assert!(result.contains("see you tomorrow"));
+            seen.insert(result);
+        }
+        // Unchanged text plus each of the three variations
+        assert_eq!(seen.len(), 4);
+    }
+
+    #[test]
+    fn test_apply_variation_prefix_and_suffix() {
+        assert_eq!(apply_variation("ok", "嗯，{}"), "嗯，ok");
+        assert_eq!(apply_variation("ok", "!"), "ok!");
+    }
+
+    fn banned(terms: &[&str]) -> Vec<String> {
+        terms.iter().map(|t| t.to_string()).collect()
+    }
+
+    #[test]
+    fn test_word_filter_replaces_case_insensitively() {
+        let (result, hits) = filter_words("Spam and SPAM, 广告链接", &banned(&["spam", "广告"]), Some("[x]"));
+        assert_eq!(result, "[x] and [x], [x]链接");
+        assert_eq!(hits, 3);
+
+        let (masked, _) = filter_words("no spam", &banned(&["spam"]), None);
+        assert_eq!(masked, "no ****");
+    }
+
+    #[test]
+    fn test_word_filter_respects_word_boundaries() {
+        let (result, hits) = filter_words("class assignment, ass", &banned(&["ass"]), Some("*"));
+        assert_eq!(result, "class assignment, *");
+        assert_eq!(hits, 1);
+    }
+
+    #[test]
+    fn test_word_filter_block_mode() {
+        let mut node = WordFilterNode::new("word_filter_test", "WordFilter");
+        let inputs = HashMap::from([
+            ("text".to_string(), DataValue::String("buy spam now".to_string())),
+            (
+                "banned".to_string(),
+                DataValue::List(vec![DataValue::String("spam".to_string())]),
+            ),
+            ("block_on_hit".to_string(), DataValue::Boolean(true)),
+        ]);
+        let outputs = node.execute(inputs).unwrap();
+        assert!(matches!(outputs.get("allowed"), Some(DataValue::Boolean(false))));
+        assert!(matches!(outputs.get("hits"), Some(DataValue::Integer(1))));
+        assert!(matches!(outputs.get("result"), Some(DataValue::String(s)) if s.is_empty()));
+    }
+
+    #[test]
+    fn test_hours_map_to_periods() {
+        assert_eq!(DayPeriod::from_hour(5), DayPeriod::Morning);
+        assert_eq!(DayPeriod::from_hour(11), DayPeriod::Morning);
+        assert_eq!(DayPeriod::from_hour(12), DayPeriod::Afternoon);
+        assert_eq!(DayPeriod::from_hour(18), DayPeriod::Evening);
+        assert_eq!(DayPeriod::from_hour(23), DayPeriod::Night);
+        assert_eq!(DayPeriod::from_hour(0), DayPeriod::Night);
+        assert_eq!(DayPeriod::from_hour(4), DayPeriod::Night);
+    }
+
+    #[test]
+    fn test_greeting_uses_overrides_for_injected_hour() {
+        let mut node = GreetingNode::new("greeting_test", "Greeting");
+        let inputs = HashMap::from([
+            ("hour".to_string(), DataValue::Integer(8)),
+            (
+                "overrides".to_string(),
+                DataValue::Json(serde_json::json!({"morning": "Good morning!"})),
+            ),
+        ]);
+        let outputs = node.execute(inputs).unwrap();
+        assert!(matches!(outputs.get("greeting"), Some(DataValue::String(s)) if s == "Good morning!"));
+        assert!(matches!(outputs.get("period"), Some(DataValue::String(s)) if s == "morning"));
+
+        // Periods without an override fall back to the zh default
+        assert_eq!(greeting_for(DayPeriod::Night, Some(&serde_json::json!({"morning": "hi"}))), "夜深了，早点休息，晚安~");
+    }
+
+    #[test]
+    fn test_greeting_rejects_hour_out_of_range() {
+        let mut node = GreetingNode::new("greeting_test", "Greeting");
+        let inputs = HashMap::from([("hour".to_string(), DataValue::Integer(24))]);
+        let err = node.execute(inputs).unwrap_err().to_string();
+        assert!(err.contains("Input port 'hour' must be <= 23, got 24"), "{}", err);
+    }
+
+    #[test]
+    fn test_text_similarity() {
+        assert_eq!(text_similarity("hello", "hello"), 1.0);
+        assert_eq!(text_similarity("", ""), 1.0);
+        assert!((text_similarity("kitten", "sitting") - (1.0 - 3.0 / 7.0)).abs() < 1e-9);
+        assert_eq!(text_similarity("abc", "xyz"), 0.0);
+    }
+
+    #[test]
+    fn test_markdown_table_to_key_value() {
+        let text = "Prices:\n| Item | Price |\n|:-----|------:|\n| Apple | 3 |\n| Pear |\nDone";
+        assert_eq!(
+            convert_markdown_tables(text, TableStyle::KeyValue),
+            "Prices:\nItem: Apple\nPrice: 3\n\nItem: Pear\nPrice: \nDone"
+        );
+        assert_eq!(
+            convert_markdown_tables(text, TableStyle::Aligned),
+            "Prices:\nItem   Price\nApple  3\nPear\nDone"
+        );
+    }
+
+    #[test]
+    fn test_markdown_without_table_is_untouched() {
+        let text = "a | b is not a table\n\n- list item\n";
+        assert_eq!(convert_markdown_tables(text, TableStyle::KeyValue), text);
+    }
+
+    #[test]
+    fn test_malformed_markdown_table_passes_through() {
+        // Separator has fewer columns than the header
+        let text = "| a | b | c |\n|---|---|\n| 1 | 2 | 3 |";
+        assert_eq!(convert_markdown_tables(text, TableStyle::KeyValue), text);
+        // A row has more cells than the header
+        let text = "| a | b |\n|---|---|\n| 1 | 2 | 3 |";
+        assert_eq!(convert_markdown_tables(text, TableStyle::KeyValue), text);
+    }
+
+    /// Returns "topic N" on the N-th call
+    #[derive(Debug)]
+    struct CountingLlm {
+        calls: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    impl LLMBase for CountingLlm {
+        fn get_model_name(&self) -> &str {
+            "mock"
+        }
+
+        fn inference(&self, _param: &InferenceParam) -> crate::llm::Message {
+            let n = self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+            crate::llm::Message::system(format!("topic {}", n))
+        }
+    }
+
+    #[test]
+    fn test_topic_tracker_throttles_and_persists() {
+        let calls = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let track = |message: &str| {
+            // A fresh node per message, like the UI rebuilding the graph on every run
+            let llm = Box::new(CountingLlm { calls: calls.clone() });
+            let mut node = TopicTrackerNode::with_llm("topic_tracker_test", "TopicTracker", llm);
+            let inputs = HashMap::from([
+                ("conversation_id".to_string(), DataValue::String("group:1".to_string())),
+                ("message".to_string(), DataValue::String(message.to_string())),
+                ("update_every".to_string(), DataValue::Integer(3)),
+            ]);
+            match node.execute(inputs).unwrap().remove("topic") {
+                Some(DataValue::String(topic)) => topic,
+                _ => panic!("topic should be a String"),
+            }
+        };
+
+        // The first message sets the topic immediately
+        assert_eq!(track("anyone playing tonight?"), "topic 1");
+        assert_eq!(track("I'm in"), "topic 1");
+        assert_eq!(track("me too"), "topic 1");
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+        // The third message since the last update triggers the next one
+        assert_eq!(track("8pm?"), "topic 2");
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_topic_tracker_restores_topic_from_state_file() {
+        use crate::node::state_store::state_file_path;
+
+        let dir = std::env::temp_dir().join(format!("zihuan_topic_tracker_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let calls = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let track = |graph_path: &Path, message: &str| {
+            let llm = Box::new(CountingLlm { calls: calls.clone() });
+            let mut node = TopicTrackerNode::with_llm("topic", "TopicTracker", llm);
+            node.set_graph_path(Some(graph_path));
+            let inputs = HashMap::from([
+                ("conversation_id".to_string(), DataValue::String("group:1".to_string())),
+                ("message".to_string(), DataValue::String(message.to_string())),
+                ("update_every".to_string(), DataValue::Integer(3)),
+            ]);
+            match node.execute(inputs).unwrap().remove("topic") {
+                Some(DataValue::String(topic)) => topic,
+                _ => panic!("topic should be a String"),
+            }
+        };
+
+        let graph = dir.join("bot.json");
+        assert_eq!(track(&graph, "anyone playing tonight?"), "topic 1");
+
+        // A graph this process has not loaded yet reads the saved file, like one loaded after a restart
+        let restarted = dir.join("restarted.json");
+        std::fs::copy(state_file_path(&graph), state_file_path(&restarted)).unwrap();
+        assert_eq!(track(&restarted, "I'm in"), "topic 1");
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
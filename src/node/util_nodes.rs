@@ -1,12 +1,22 @@
 use crate::error::Result;
+use crate::node::data_value::parse_iso8601;
 use crate::node::{node_input, node_output, DataType, DataValue, Node, Port};
+use base64::Engine;
 use std::collections::HashMap;
-use std::sync::RwLock;
-use once_cell::sync::Lazy;
 
-// Global context for string_data nodes to access UI input values
-pub static STRING_DATA_CONTEXT: Lazy<RwLock<HashMap<String, String>>> = 
-    Lazy::new(|| RwLock::new(HashMap::new()));
+/// Walk a JSON value along a dotted path such as `data.items.0.name` or `data.items[0].name`.
+/// Numeric segments index into arrays; an empty path returns the value itself.
+pub fn resolve_json_path<'a>(value: &'a serde_json::Value, path: &str) -> Option<&'a serde_json::Value> {
+    let normalized = path.replace('[', ".").replace(']', "");
+    normalized
+        .split('.')
+        .filter(|segment| !segment.is_empty())
+        .try_fold(value, |current, segment| match current {
+            serde_json::Value::Object(map) => map.get(segment),
+            serde_json::Value::Array(items) => segment.parse::<usize>().ok().and_then(|i| items.get(i)),
+            _ => None,
+        })
+}
 
 pub struct ConditionalNode {
     id: String,
@@ -156,6 +166,10 @@ impl Node for JsonParserNode {
         Some("Parse JSON string to structured data")
     }
 
+    fn is_pure(&self) -> bool {
+        true
+    }
+
     node_input![
         port! { name = "json_string", ty = String, desc = "JSON string to parse" },
     ];
@@ -163,6 +177,7 @@ impl Node for JsonParserNode {
     node_output![
         port! { name = "parsed", ty = Json, desc = "Parsed JSON object" },
         port! { name = "success", ty = Boolean, desc = "Whether parsing was successful" },
+        port! { name = "parsed_datetime", ty = DateTime, desc = "Set when the input is an ISO-8601 timestamp, quoted or bare" },
     ];
 
     fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
@@ -173,10 +188,17 @@ impl Node for JsonParserNode {
         if let Some(DataValue::String(json_str)) = inputs.get("json_string") {
             match serde_json::from_str::<serde_json::Value>(json_str) {
                 Ok(parsed) => {
+                    if let Some(datetime) = parsed.as_str().and_then(parse_iso8601) {
+                        outputs.insert("parsed_datetime".to_string(), DataValue::DateTime(datetime));
+                    }
                     outputs.insert("parsed".to_string(), DataValue::Json(parsed));
                     outputs.insert("success".to_string(), DataValue::Boolean(true));
                 }
                 Err(_) => {
+                    // A bare timestamp is still not valid JSON, so only the datetime is set
+                    if let Some(datetime) = parse_iso8601(json_str) {
+                        outputs.insert("parsed_datetime".to_string(), DataValue::DateTime(datetime));
+                    }
                     outputs.insert("parsed".to_string(), DataValue::Json(serde_json::json!(null)));
                     outputs.insert("success".to_string(), DataValue::Boolean(false));
                 }
@@ -233,7 +255,15 @@ impl Node for StringDataNode {
         Some("String data source with UI input field")
     }
 
-    node_input![];
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    // Like MessageListDataNode, the UI value arrives as an inline value on an input port,
+    // so each graph carries its own value instead of sharing global state.
+    node_input![
+        port! { name = "value", ty = String, desc = "String provided by UI input field", optional },
+    ];
 
     node_output![
         port! { name = "text", ty = String, desc = "Output string from UI input" },
@@ -242,14 +272,13 @@ impl Node for StringDataNode {
     fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
         self.validate_inputs(&inputs)?;
 
-        // StringDataNode gets its value from the global context (set by UI layer before execution)
         let mut outputs = HashMap::new();
-        let value = {
-            let context = STRING_DATA_CONTEXT.read().unwrap();
-            context.get(&self.id).cloned().unwrap_or_default()
+        let value = match inputs.get("value") {
+            Some(DataValue::String(s)) => s.clone(),
+            _ => String::new(),
         };
         outputs.insert("text".to_string(), DataValue::String(value));
-        
+
         self.validate_outputs(&outputs)?;
         Ok(outputs)
     }
@@ -270,6 +299,7 @@ impl Node for PreviewMessageListNode {
 
     node_input![
         port! { name = "messages", ty = MessageList, desc = "MessageList to preview inside the node", optional },
+        port! { name = "preview_path", ty = String, desc = "JSON path shown instead of the full content when a message's content is JSON", optional },
     ];
 
     node_output![];
@@ -325,3 +355,352 @@ impl Node for MessageListDataNode {
         Ok(outputs)
     }
 }
+
+/// Base64 encode node - turns binary data into a base64 string
+pub struct Base64EncodeNode {
+    id: String,
+    name: String,
+}
+
+/// Base64 decode node - turns a base64 string back into binary data
+pub struct Base64DecodeNode {
+    id: String,
+    name: String,
+}
+
+impl Base64EncodeNode {
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+        }
+    }
+}
+
+impl Base64DecodeNode {
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+        }
+    }
+}
+
+impl Node for Base64EncodeNode {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> Option<&str> {
+        Some("Encode binary data as a base64 string")
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    node_input![
+        port! { name = "bytes", ty = Binary, desc = "Binary data to encode" },
+    ];
+
+    node_output![
+        port! { name = "base64", ty = String, desc = "Base64 encoded string" },
+    ];
+
+    fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+        self.validate_inputs(&inputs)?;
+
+        let encoded = match inputs.get("bytes") {
+            Some(DataValue::Binary(bytes)) => base64::engine::general_purpose::STANDARD.encode(bytes),
+            _ => return Err(crate::error::Error::InvalidNodeInput("bytes is required".to_string())),
+        };
+
+        let mut outputs = HashMap::new();
+        outputs.insert("base64".to_string(), DataValue::String(encoded));
+
+        self.validate_outputs(&outputs)?;
+        Ok(outputs)
+    }
+}
+
+impl Node for Base64DecodeNode {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> Option<&str> {
+        Some("Decode a base64 string into binary data")
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    node_input![
+        port! { name = "base64", ty = String, desc = "Base64 encoded string" },
+    ];
+
+    node_output![
+        port! { name = "bytes", ty = Binary, desc = "Decoded binary data" },
+    ];
+
+    fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+        self.validate_inputs(&inputs)?;
+
+        let decoded = match inputs.get("base64") {
+            Some(DataValue::String(text)) => base64::engine::general_purpose::STANDARD
+                .decode(text.trim())
+                .map_err(|e| crate::error::Error::InvalidNodeInput(format!("base64 is not valid: {}", e)))?,
+            _ => return Err(crate::error::Error::InvalidNodeInput("base64 is required".to_string())),
+        };
+
+        let mut outputs = HashMap::new();
+        outputs.insert("bytes".to_string(), DataValue::Binary(decoded));
+
+        self.validate_outputs(&outputs)?;
+        Ok(outputs)
+    }
+}
+
+/// Map get node - looks up a key in a string map
+pub struct MapGetNode {
+    id: String,
+    name: String,
+}
+
+/// Map insert node - returns a copy of a string map with one key set
+pub struct MapInsertNode {
+    id: String,
+    name: String,
+}
+
+impl MapGetNode {
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+        }
+    }
+}
+
+impl MapInsertNode {
+    pub fn new(id: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+        }
+    }
+}
+
+impl Node for MapGetNode {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> Option<&str> {
+        Some("Look up the value stored under a key")
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    node_input![
+        port! { name = "map", ty = Map(String), desc = "Map to read from" },
+        port! { name = "key", ty = String, desc = "Key to look up" },
+    ];
+
+    node_output![
+        port! { name = "value", ty = String, desc = "Value under the key, empty when missing" },
+        port! { name = "found", ty = Boolean, desc = "Whether the key exists" },
+    ];
+
+    fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+        self.validate_inputs(&inputs)?;
+
+        let value = match (inputs.get("map"), inputs.get("key")) {
+            (Some(DataValue::Map(entries)), Some(DataValue::String(key))) => entries.get(key).cloned(),
+            _ => return Err(crate::error::Error::InvalidNodeInput("map and key are required".to_string())),
+        };
+
+        let mut outputs = HashMap::new();
+        outputs.insert("found".to_string(), DataValue::Boolean(value.is_some()));
+        outputs.insert("value".to_string(), value.unwrap_or_else(|| DataValue::String(String::new())));
+
+        self.validate_outputs(&outputs)?;
+        Ok(outputs)
+    }
+}
+
+impl Node for MapInsertNode {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> Option<&str> {
+        Some("Set a key in a map, creating the map when none is given")
+    }
+
+    fn is_pure(&self) -> bool {
+        true
+    }
+
+    node_input![
+        port! { name = "map", ty = Map(String), desc = "Map to update, starts empty when not connected", optional },
+        port! { name = "key", ty = String, desc = "Key to set" },
+        port! { name = "value", ty = String, desc = "Value to store" },
+    ];
+
+    node_output![
+        port! { name = "map", ty = Map(String), desc = "Updated map" },
+    ];
+
+    fn execute(&mut self, inputs: HashMap<String, DataValue>) -> Result<HashMap<String, DataValue>> {
+        self.validate_inputs(&inputs)?;
+
+        let mut entries = match inputs.get("map") {
+            Some(DataValue::Map(entries)) => entries.clone(),
+            _ => HashMap::new(),
+        };
+        let key = match inputs.get("key") {
+            Some(DataValue::String(key)) => key.clone(),
+            _ => return Err(crate::error::Error::InvalidNodeInput("key is required".to_string())),
+        };
+        let value = inputs
+            .get("value")
+            .cloned()
+            .ok_or_else(|| crate::error::Error::InvalidNodeInput("value is required".to_string()))?;
+        entries.insert(key, value);
+
+        let mut outputs = HashMap::new();
+        outputs.insert("map".to_string(), DataValue::Map(entries));
+
+        self.validate_outputs(&outputs)?;
+        Ok(outputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64_nodes_round_trip() {
+        let bytes = vec![0u8, 159, 146, 150, 255];
+        let mut encoder = Base64EncodeNode::new("encode", "Encode");
+        let encoded = encoder
+            .execute(HashMap::from([("bytes".to_string(), DataValue::Binary(bytes.clone()))]))
+            .unwrap()
+            .remove("base64")
+            .unwrap();
+
+        let mut decoder = Base64DecodeNode::new("decode", "Decode");
+        let decoded = decoder.execute(HashMap::from([("base64".to_string(), encoded)])).unwrap();
+        assert!(matches!(decoded.get("bytes"), Some(DataValue::Binary(out)) if *out == bytes));
+
+        // Binary values serialize as the same base64 string
+        assert_eq!(DataValue::Binary(bytes).to_json(), serde_json::json!("AJ+Slv8="));
+    }
+
+    #[test]
+    fn test_base64_decode_rejects_invalid_input() {
+        let mut decoder = Base64DecodeNode::new("decode", "Decode");
+        let result = decoder.execute(HashMap::from([(
+            "base64".to_string(),
+            DataValue::String("not base64!".to_string()),
+        )]));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_json_parser_reports_timestamps() {
+        let mut parser = JsonParserNode::new("parser", "Parser");
+        let parse = |parser: &mut JsonParserNode, input: &str| {
+            parser
+                .execute(HashMap::from([("json_string".to_string(), DataValue::String(input.to_string()))]))
+                .unwrap()
+        };
+
+        let outputs = parse(&mut parser, "\"2024-05-01T08:30:00Z\"");
+        assert!(matches!(outputs.get("success"), Some(DataValue::Boolean(true))));
+        assert!(matches!(outputs.get("parsed_datetime"), Some(DataValue::DateTime(_))));
+
+        // A bare timestamp yields a datetime but is not JSON
+        let outputs = parse(&mut parser, "2024-05-01T08:30:00Z");
+        assert!(matches!(outputs.get("success"), Some(DataValue::Boolean(false))));
+        assert!(matches!(outputs.get("parsed_datetime"), Some(DataValue::DateTime(_))));
+
+        let outputs = parse(&mut parser, "{\"a\": 1}");
+        assert!(matches!(outputs.get("success"), Some(DataValue::Boolean(true))));
+        assert!(!outputs.contains_key("parsed_datetime"));
+    }
+
+    #[test]
+    fn test_map_insert_then_get() {
+        let mut insert = MapInsertNode::new("insert", "Insert");
+        let map = insert
+            .execute(HashMap::from([
+                ("key".to_string(), DataValue::String("city".to_string())),
+                ("value".to_string(), DataValue::String("Hangzhou".to_string())),
+            ]))
+            .unwrap()
+            .remove("map")
+            .unwrap();
+
+        let mut get = MapGetNode::new("get", "Get");
+        let outputs = get
+            .execute(HashMap::from([
+                ("map".to_string(), map.clone()),
+                ("key".to_string(), DataValue::String("city".to_string())),
+            ]))
+            .unwrap();
+        assert!(matches!(outputs.get("found"), Some(DataValue::Boolean(true))));
+        assert!(matches!(outputs.get("value"), Some(DataValue::String(s)) if s == "Hangzhou"));
+
+        let outputs = get
+            .execute(HashMap::from([
+                ("map".to_string(), map),
+                ("key".to_string(), DataValue::String("country".to_string())),
+            ]))
+            .unwrap();
+        assert!(matches!(outputs.get("found"), Some(DataValue::Boolean(false))));
+    }
+
+    #[test]
+    fn test_map_port_rejects_mismatched_value() {
+        let mut get = MapGetNode::new("get", "Get");
+        let map = DataValue::Map(HashMap::from([
+            ("a".to_string(), DataValue::String("x".to_string())),
+            ("b".to_string(), DataValue::Integer(1)),
+        ]));
+        let err = get
+            .execute(HashMap::from([
+                ("map".to_string(), map),
+                ("key".to_string(), DataValue::String("a".to_string())),
+            ]))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("at key 'b'"), "{}", err);
+
+        // An empty map matches any value type
+        assert!(DataValue::Map(HashMap::new())
+            .check_type(&DataType::Map(Box::new(DataType::Integer)))
+            .is_ok());
+    }
+}
@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+
+/// How edges between ports are drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeStyle {
+    /// Horizontal and vertical segments, see `route_edge`
+    #[default]
+    Orthogonal,
+    /// One cubic bezier curve per edge
+    Bezier,
+}
+
+/// Control points of a cubic bezier from an output port at `from` to an input port at `to`.
+/// The curve leaves and enters horizontally; the handles reach half the horizontal distance,
+/// but at least `min_offset`, so edges running backwards loop around the nodes.
+pub fn bezier_control_points(from: (f32, f32), to: (f32, f32), min_offset: f32) -> ((f32, f32), (f32, f32)) {
+    let offset = ((to.0 - from.0).abs() / 2.0).max(min_offset);
+    ((from.0 + offset, from.1), (to.0 - offset, to.1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Point at `t` (0 to 1) on the cubic bezier from `from` to `to`
+    fn bezier_point(from: (f32, f32), c1: (f32, f32), c2: (f32, f32), to: (f32, f32), t: f32) -> (f32, f32) {
+        let u = 1.0 - t;
+        let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+        (
+            a * from.0 + b * c1.0 + c * c2.0 + d * to.0,
+            a * from.1 + b * c1.1 + c * c2.1 + d * to.1,
+        )
+    }
+
+    #[test]
+    fn test_forward_edge_control_points() {
+        let (c1, c2) = bezier_control_points((100.0, 40.0), (300.0, 120.0), 40.0);
+        assert_eq!(c1, (200.0, 40.0));
+        assert_eq!(c2, (200.0, 120.0));
+    }
+
+    #[test]
+    fn test_short_and_backward_edges_use_min_offset() {
+        let (c1, c2) = bezier_control_points((100.0, 40.0), (120.0, 40.0), 40.0);
+        assert_eq!((c1, c2), ((140.0, 40.0), (80.0, 40.0)));
+
+        // Backwards, the handles point away from each other
+        let (c1, c2) = bezier_control_points((300.0, 40.0), (100.0, 200.0), 40.0);
+        assert_eq!((c1, c2), ((400.0, 40.0), (0.0, 200.0)));
+    }
+
+    #[test]
+    fn test_curve_midpoint_is_label_position() {
+        // Edge labels sit halfway between the ports for both edge styles
+        let (from, to) = ((100.0, 40.0), (300.0, 120.0));
+        let (c1, c2) = bezier_control_points(from, to, 40.0);
+        assert_eq!(bezier_point(from, c1, c2, to, 0.0), from);
+        assert_eq!(bezier_point(from, c1, c2, to, 1.0), to);
+        assert_eq!(bezier_point(from, c1, c2, to, 0.5), (200.0, 80.0));
+    }
+}
@@ -0,0 +1,241 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::node::graph_io::{EdgeDefinition, NodeDefinition, NodeGraphDefinition};
+use crate::ui::node_render::{inline_port_key, remove_inline_inputs, InlinePortValue};
+
+/// Nodes copied from a graph, with the edges between them and their inline inputs
+#[derive(Debug, Clone)]
+pub struct GraphClipboard {
+    nodes: Vec<NodeDefinition>,
+    edges: Vec<EdgeDefinition>,
+    inline_inputs: HashMap<String, InlinePortValue>,
+    /// Pastes so far, so repeated pastes don't stack on top of each other
+    pastes: usize,
+}
+
+impl GraphClipboard {
+    /// Copy the nodes in `node_ids` and the edges running entirely between them.
+    /// Returns `None` if none of the nodes are in the graph.
+    pub fn copy(
+        graph: &NodeGraphDefinition,
+        inline_inputs: &HashMap<String, InlinePortValue>,
+        node_ids: &HashSet<String>,
+    ) -> Option<Self> {
+        let nodes: Vec<NodeDefinition> = graph
+            .nodes
+            .iter()
+            .filter(|n| node_ids.contains(&n.id))
+            .cloned()
+            .collect();
+        if nodes.is_empty() {
+            return None;
+        }
+
+        let edges = graph
+            .edges
+            .iter()
+            .filter(|e| node_ids.contains(&e.from_node_id) && node_ids.contains(&e.to_node_id))
+            .cloned()
+            .collect();
+        let inline_inputs = inline_inputs
+            .iter()
+            .filter(|(key, _)| nodes.iter().any(|n| key.starts_with(&inline_port_key(&n.id, ""))))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+
+        Some(Self {
+            nodes,
+            edges,
+            inline_inputs,
+            pastes: 0,
+        })
+    }
+
+    /// Add the copied nodes to `graph` under fresh ids, shifted by `offset` more on every paste.
+    /// Internal edges and inline inputs move over to the new ids. Returns the new node ids.
+    pub fn paste(
+        &mut self,
+        graph: &mut NodeGraphDefinition,
+        inline_inputs: &mut HashMap<String, InlinePortValue>,
+        offset: f32,
+    ) -> Vec<String> {
+        self.pastes += 1;
+        let shift = offset * self.pastes as f32;
+
+        let mut id_map = HashMap::new();
+        for node in &self.nodes {
+            let mut pasted = node.clone();
+            pasted.id = next_node_id(graph);
+            if let Some(pos) = &mut pasted.position {
+                pos.x += shift;
+                pos.y += shift;
+            }
+            pasted.has_error = false;
+
+            // Inputs left behind by a deleted node that had the same id
+            remove_inline_inputs(inline_inputs, &pasted.id);
+            let old_prefix = inline_port_key(&node.id, "");
+            for (key, value) in &self.inline_inputs {
+                if let Some(port_name) = key.strip_prefix(&old_prefix) {
+                    inline_inputs.insert(inline_port_key(&pasted.id, port_name), value.clone());
+                }
+            }
+
+            id_map.insert(node.id.clone(), pasted.id.clone());
+            graph.nodes.push(pasted);
+        }
+
+        for edge in &self.edges {
+            let mut pasted = edge.clone();
+            pasted.from_node_id = id_map[&edge.from_node_id].clone();
+            pasted.to_node_id = id_map[&edge.to_node_id].clone();
+            // A condition on a copied node follows the copy, one outside the selection stays
+            if let Some(condition) = &mut pasted.condition {
+                if let Some(new_id) = id_map.get(&condition.node_id) {
+                    condition.node_id = new_id.clone();
+                }
+            }
+            graph.edges.push(pasted);
+        }
+
+        self.nodes.iter().map(|n| id_map[&n.id].clone()).collect()
+    }
+}
+
+/// First `node_{n}` id not used in `graph`
+pub fn next_node_id(graph: &NodeGraphDefinition) -> String {
+    let mut index = 1usize;
+    loop {
+        let candidate = format!("node_{index}");
+        if !graph.nodes.iter().any(|node| node.id == candidate) {
+            return candidate;
+        }
+        index += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::graph_io::{EdgeCondition, GraphPosition};
+
+    fn node(id: &str, x: f32) -> NodeDefinition {
+        NodeDefinition {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: None,
+            node_type: "string_data".to_string(),
+            input_ports: Vec::new(),
+            output_ports: Vec::new(),
+            position: Some(GraphPosition { x, y: 0.0 }),
+            size: None,
+            inline_values: HashMap::from([("text".to_string(), serde_json::json!("hi"))]),
+            has_error: false,
+        }
+    }
+
+    fn edge(from: &str, to: &str) -> EdgeDefinition {
+        EdgeDefinition {
+            from_node_id: from.to_string(),
+            from_port: "out".to_string(),
+            to_node_id: to.to_string(),
+            to_port: "in".to_string(),
+            condition: None,
+        }
+    }
+
+    fn sample_graph() -> NodeGraphDefinition {
+        NodeGraphDefinition {
+            nodes: vec![node("node_1", 0.0), node("node_2", 200.0), node("node_3", 400.0)],
+            edges: vec![edge("node_1", "node_2"), edge("node_2", "node_3")],
+            ..Default::default()
+        }
+    }
+
+    fn selected(ids: &[&str]) -> HashSet<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn test_paste_remaps_ids_and_internal_edges() {
+        let mut graph = sample_graph();
+        graph.edges[0].condition = Some(EdgeCondition {
+            node_id: "node_1".to_string(),
+            port: "ok".to_string(),
+        });
+        let mut inline_inputs = HashMap::from([
+            (inline_port_key("node_1", "text"), InlinePortValue::Text("copied".to_string())),
+            (inline_port_key("node_3", "text"), InlinePortValue::Text("not copied".to_string())),
+        ]);
+
+        let mut clipboard = GraphClipboard::copy(&graph, &inline_inputs, &selected(&["node_1", "node_2"])).unwrap();
+        let pasted = clipboard.paste(&mut graph, &mut inline_inputs, 20.0);
+
+        assert_eq!(pasted, ["node_4", "node_5"]);
+        assert_eq!(graph.nodes.len(), 5);
+        // Only the edge between the copied nodes comes along
+        assert_eq!(graph.edges.len(), 3);
+        let new_edge = &graph.edges[2];
+        assert_eq!((new_edge.from_node_id.as_str(), new_edge.to_node_id.as_str()), ("node_4", "node_5"));
+        assert_eq!(new_edge.condition.as_ref().unwrap().node_id, "node_4");
+
+        let node_4 = graph.nodes.iter().find(|n| n.id == "node_4").unwrap();
+        assert_eq!(node_4.position.as_ref().map(|p| (p.x, p.y)), Some((20.0, 20.0)));
+        assert_eq!(node_4.inline_values["text"], serde_json::json!("hi"));
+        assert!(matches!(
+            inline_inputs.get(&inline_port_key("node_4", "text")),
+            Some(InlinePortValue::Text(text)) if text == "copied"
+        ));
+        assert!(!inline_inputs.contains_key(&inline_port_key("node_5", "text")));
+    }
+
+    #[test]
+    fn test_repeated_pastes_are_offset() {
+        let mut graph = sample_graph();
+        let mut inline_inputs = HashMap::new();
+        let mut clipboard = GraphClipboard::copy(&graph, &inline_inputs, &selected(&["node_3"])).unwrap();
+
+        let first = clipboard.paste(&mut graph, &mut inline_inputs, 20.0);
+        let second = clipboard.paste(&mut graph, &mut inline_inputs, 20.0);
+        let x_of = |id: &str| graph.nodes.iter().find(|n| n.id == id).unwrap().position.as_ref().unwrap().x;
+        assert_eq!(x_of(&first[0]), 420.0);
+        assert_eq!(x_of(&second[0]), 440.0);
+    }
+
+    #[test]
+    fn test_paste_drops_stale_inputs_of_reused_id() {
+        let mut graph = sample_graph();
+        graph.nodes.retain(|n| n.id != "node_2");
+        // Left over from the deleted node_2
+        let mut inline_inputs = HashMap::from([(inline_port_key("node_2", "old"), InlinePortValue::Bool(true))]);
+
+        let mut clipboard = GraphClipboard::copy(&graph, &inline_inputs, &selected(&["node_1"])).unwrap();
+        assert_eq!(clipboard.paste(&mut graph, &mut inline_inputs, 20.0), ["node_2"]);
+        assert!(inline_inputs.is_empty());
+    }
+
+    #[test]
+    fn test_copy_without_nodes() {
+        let graph = sample_graph();
+        assert!(GraphClipboard::copy(&graph, &HashMap::new(), &selected(&[])).is_none());
+        assert!(GraphClipboard::copy(&graph, &HashMap::new(), &selected(&["missing"])).is_none());
+    }
+
+    #[test]
+    fn test_node_added_after_rename_starts_without_inline_inputs() {
+        let mut graph = sample_graph();
+        let mut inline_inputs = HashMap::from([(
+            inline_port_key("node_1", "text"),
+            InlinePortValue::Text("hello".to_string()),
+        )]);
+
+        graph.rename_node("node_1", "greeting").unwrap();
+        crate::ui::node_render::rename_inline_inputs(&mut inline_inputs, "node_1", "greeting");
+
+        let added = next_node_id(&graph);
+        assert_eq!(added, "node_1");
+        let prefix = inline_port_key(&added, "");
+        assert!(!inline_inputs.keys().any(|key| key.starts_with(&prefix)));
+        assert!(inline_inputs.contains_key(&inline_port_key("greeting", "text")));
+    }
+}
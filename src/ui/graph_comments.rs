@@ -0,0 +1,244 @@
+use std::collections::HashSet;
+
+use crate::node::graph_io::{CommentBox, GraphPosition, GraphSize, NodeDefinition, NodeGraphDefinition};
+
+/// Colors a comment cycles through; new comments take the first
+pub const COMMENT_COLORS: [&str; 5] = ["#4a90d9", "#50a060", "#d9a040", "#c05050", "#9060c0"];
+
+/// Size of a comment added while no node is selected
+const DEFAULT_COMMENT_SIZE: (f32, f32) = (400.0, 240.0);
+
+pub fn next_comment_id(graph: &NodeGraphDefinition) -> String {
+    let mut index = 1usize;
+    loop {
+        let candidate = format!("comment_{index}");
+        if !graph.comments.iter().any(|comment| comment.id == candidate) {
+            return candidate;
+        }
+        index += 1;
+    }
+}
+
+/// Add a comment enclosing `node_ids`, `margin` away from them on every side and another
+/// `margin` higher for its title. Without positioned nodes it gets a default size at
+/// `fallback`. Returns the new comment's id.
+pub fn add_comment(
+    graph: &mut NodeGraphDefinition,
+    node_ids: &HashSet<String>,
+    dimensions: impl Fn(&NodeDefinition) -> (f32, f32),
+    margin: f32,
+    fallback: (f32, f32),
+) -> String {
+    let bounds = graph
+        .nodes
+        .iter()
+        .filter(|n| node_ids.contains(&n.id))
+        .filter_map(|n| n.position.as_ref().map(|pos| (pos, dimensions(n))))
+        .fold(None, |bounds: Option<(f32, f32, f32, f32)>, (pos, (width, height))| {
+            let (right, bottom) = (pos.x + width, pos.y + height);
+            Some(match bounds {
+                Some((left, top, max_x, max_y)) => {
+                    (left.min(pos.x), top.min(pos.y), max_x.max(right), max_y.max(bottom))
+                }
+                None => (pos.x, pos.y, right, bottom),
+            })
+        });
+    let (position, size) = match bounds {
+        Some((left, top, right, bottom)) => (
+            GraphPosition {
+                x: (left - margin).max(0.0),
+                y: (top - margin * 2.0).max(0.0),
+            },
+            GraphSize {
+                width: right - left + margin * 2.0,
+                height: bottom - top + margin * 3.0,
+            },
+        ),
+        None => (
+            GraphPosition { x: fallback.0, y: fallback.1 },
+            GraphSize {
+                width: DEFAULT_COMMENT_SIZE.0,
+                height: DEFAULT_COMMENT_SIZE.1,
+            },
+        ),
+    };
+
+    let id = next_comment_id(graph);
+    graph.comments.push(CommentBox {
+        id: id.clone(),
+        position,
+        size,
+        text: "注释".to_string(),
+        color: COMMENT_COLORS[0].to_string(),
+    });
+    id
+}
+
+/// Ids of the nodes lying completely inside `comment`
+pub fn nodes_inside(
+    graph: &NodeGraphDefinition,
+    comment: &CommentBox,
+    dimensions: impl Fn(&NodeDefinition) -> (f32, f32),
+) -> HashSet<String> {
+    let (left, top) = (comment.position.x, comment.position.y);
+    let (right, bottom) = (left + comment.size.width, top + comment.size.height);
+    graph
+        .nodes
+        .iter()
+        .filter(|node| {
+            let Some(pos) = node.position.as_ref() else {
+                return false;
+            };
+            let (width, height) = dimensions(node);
+            pos.x >= left && pos.y >= top && pos.x + width <= right && pos.y + height <= bottom
+        })
+        .map(|node| node.id.clone())
+        .collect()
+}
+
+/// Move a comment to `(x, y)`. With `carry_nodes`, the nodes inside it move along by the same
+/// amount. Returns false if there is no such comment or it stays where it is.
+pub fn move_comment(
+    graph: &mut NodeGraphDefinition,
+    comment_id: &str,
+    x: f32,
+    y: f32,
+    carry_nodes: bool,
+    dimensions: impl Fn(&NodeDefinition) -> (f32, f32),
+) -> bool {
+    let Some(comment) = graph.comments.iter().find(|c| c.id == comment_id) else {
+        return false;
+    };
+    let (dx, dy) = (x - comment.position.x, y - comment.position.y);
+    if dx == 0.0 && dy == 0.0 {
+        return false;
+    }
+
+    if carry_nodes {
+        let inside = nodes_inside(graph, comment, dimensions);
+        for node in graph.nodes.iter_mut().filter(|n| inside.contains(&n.id)) {
+            if let Some(pos) = &mut node.position {
+                pos.x += dx;
+                pos.y += dy;
+            }
+        }
+    }
+    if let Some(comment) = graph.comments.iter_mut().find(|c| c.id == comment_id) {
+        comment.position = GraphPosition { x, y };
+    }
+    true
+}
+
+/// The color after `color` in `COMMENT_COLORS`, or the first one for a color not in the list
+pub fn next_comment_color(color: &str) -> &'static str {
+    let index = COMMENT_COLORS
+        .iter()
+        .position(|c| c.eq_ignore_ascii_case(color))
+        .map_or(0, |index| (index + 1) % COMMENT_COLORS.len());
+    COMMENT_COLORS[index]
+}
+
+/// `#rrggbb` as red, green and blue, `None` if malformed
+pub fn parse_hex_color(color: &str) -> Option<(u8, u8, u8)> {
+    let hex = color.strip_prefix('#')?;
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None;
+    }
+    let channel = |range: std::ops::Range<usize>| u8::from_str_radix(&hex[range], 16).ok();
+    Some((channel(0..2)?, channel(2..4)?, channel(4..6)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn node_at(id: &str, x: f32, y: f32) -> NodeDefinition {
+        NodeDefinition {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: None,
+            node_type: "string_data".to_string(),
+            input_ports: Vec::new(),
+            output_ports: Vec::new(),
+            position: Some(GraphPosition { x, y }),
+            size: None,
+            inline_values: HashMap::new(),
+            has_error: false,
+        }
+    }
+
+    fn position_of(graph: &NodeGraphDefinition, id: &str) -> (f32, f32) {
+        let pos = graph.nodes.iter().find(|n| n.id == id).unwrap().position.clone().unwrap();
+        (pos.x, pos.y)
+    }
+
+    #[test]
+    fn test_comments_round_trip_and_old_graphs_load() {
+        let mut graph = NodeGraphDefinition {
+            nodes: vec![node_at("a", 100.0, 100.0)],
+            ..Default::default()
+        };
+        add_comment(&mut graph, &HashSet::new(), |_| (200.0, 100.0), 20.0, (40.0, 40.0));
+
+        let json = serde_json::to_string(&graph).unwrap();
+        let restored: NodeGraphDefinition = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.comments, graph.comments);
+        assert_eq!(restored.comments[0].color, COMMENT_COLORS[0]);
+
+        // Graphs saved before comments existed have no `comments` key
+        let legacy: NodeGraphDefinition = serde_json::from_str(r#"{"nodes": [], "edges": []}"#).unwrap();
+        assert!(legacy.comments.is_empty());
+        let json = serde_json::to_value(&legacy).unwrap();
+        assert!(json.get("comments").is_none());
+    }
+
+    #[test]
+    fn test_add_comment_encloses_selected_nodes() {
+        let mut graph = NodeGraphDefinition {
+            nodes: vec![node_at("a", 100.0, 100.0), node_at("b", 400.0, 200.0), node_at("c", 0.0, 600.0)],
+            ..Default::default()
+        };
+        let selected: HashSet<String> = ["a", "b"].iter().map(|id| id.to_string()).collect();
+
+        let id = add_comment(&mut graph, &selected, |_| (200.0, 100.0), 20.0, (40.0, 40.0));
+        assert_eq!(id, "comment_1");
+        let comment = &graph.comments[0];
+        assert_eq!((comment.position.x, comment.position.y), (80.0, 60.0));
+        assert_eq!((comment.size.width, comment.size.height), (540.0, 260.0));
+
+        let inside = nodes_inside(&graph, comment, |_| (200.0, 100.0));
+        assert_eq!(inside, selected);
+        assert_eq!(next_comment_id(&graph), "comment_2");
+    }
+
+    #[test]
+    fn test_move_comment_carries_nodes_inside() {
+        let mut graph = NodeGraphDefinition {
+            nodes: vec![node_at("inside", 100.0, 100.0), node_at("outside", 600.0, 100.0)],
+            ..Default::default()
+        };
+        add_comment(&mut graph, &HashSet::from(["inside".to_string()]), |_| (200.0, 100.0), 20.0, (0.0, 0.0));
+        let (x, y) = (graph.comments[0].position.x, graph.comments[0].position.y);
+
+        assert!(move_comment(&mut graph, "comment_1", x + 40.0, y + 20.0, true, |_| (200.0, 100.0)));
+        assert_eq!(position_of(&graph, "inside"), (140.0, 120.0));
+        assert_eq!(position_of(&graph, "outside"), (600.0, 100.0));
+
+        // Without carrying, the comment moves alone
+        assert!(move_comment(&mut graph, "comment_1", x, y, false, |_| (200.0, 100.0)));
+        assert_eq!(position_of(&graph, "inside"), (140.0, 120.0));
+        assert!(!move_comment(&mut graph, "comment_1", x, y, true, |_| (200.0, 100.0)));
+        assert!(!move_comment(&mut graph, "missing", 0.0, 0.0, true, |_| (200.0, 100.0)));
+    }
+
+    #[test]
+    fn test_comment_colors() {
+        assert_eq!(next_comment_color(COMMENT_COLORS[0]), COMMENT_COLORS[1]);
+        assert_eq!(next_comment_color(COMMENT_COLORS[4]), COMMENT_COLORS[0]);
+        assert_eq!(next_comment_color("#000000"), COMMENT_COLORS[0]);
+        assert_eq!(parse_hex_color("#4a90d9"), Some((0x4a, 0x90, 0xd9)));
+        assert_eq!(parse_hex_color("4a90d9"), None);
+        assert_eq!(parse_hex_color("#4a90"), None);
+    }
+}
@@ -0,0 +1,154 @@
+use crate::node::graph_io::{NodeDefinition, NodeGraphDefinition};
+
+/// Rectangle of an image in physical pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PixelRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Canvas area covered by the nodes, grown by `margin` on every side, as
+/// `(x, y, width, height)`. Edges run between ports and loop at most a couple of grid cells
+/// around nodes, so a margin of a few cells keeps them in. `None` for a graph without
+/// positioned nodes.
+pub fn content_bounds(
+    graph: &NodeGraphDefinition,
+    dimensions: impl Fn(&NodeDefinition) -> (f32, f32),
+    margin: f32,
+) -> Option<(f32, f32, f32, f32)> {
+    let mut bounds: Option<(f32, f32, f32, f32)> = None;
+    for node in &graph.nodes {
+        let Some(pos) = node.position.as_ref() else {
+            continue;
+        };
+        let (width, height) = dimensions(node);
+        let (right, bottom) = (pos.x + width, pos.y + height);
+        bounds = Some(match bounds {
+            Some((left, top, max_x, max_y)) => (left.min(pos.x), top.min(pos.y), max_x.max(right), max_y.max(bottom)),
+            None => (pos.x, pos.y, right, bottom),
+        });
+    }
+    bounds.map(|(left, top, right, bottom)| {
+        let (left, top) = ((left - margin).max(0.0), (top - margin).max(0.0));
+        (left, top, right + margin - left, bottom + margin - top)
+    })
+}
+
+/// Pixels of a window snapshot showing `bounds` of the canvas, whose top-left corner is at
+/// `canvas_origin` in the window (both in logical pixels). Parts outside the image are cut
+/// off; `None` if nothing of `bounds` is visible.
+pub fn snapshot_rect(
+    bounds: (f32, f32, f32, f32),
+    canvas_origin: (f32, f32),
+    scale_factor: f32,
+    image_width: u32,
+    image_height: u32,
+) -> Option<PixelRect> {
+    let (x, y, width, height) = bounds;
+    let to_pixels = |value: f32| (value * scale_factor).round().max(0.0) as u32;
+    let left = to_pixels(canvas_origin.0 + x).min(image_width);
+    let top = to_pixels(canvas_origin.1 + y).min(image_height);
+    let right = to_pixels(canvas_origin.0 + x + width).min(image_width);
+    let bottom = to_pixels(canvas_origin.1 + y + height).min(image_height);
+    if right <= left || bottom <= top {
+        return None;
+    }
+    Some(PixelRect {
+        x: left,
+        y: top,
+        width: right - left,
+        height: bottom - top,
+    })
+}
+
+/// Encode `rect` of an RGBA8 image that is `image_width` pixels wide as PNG
+pub fn encode_png(rgba: &[u8], image_width: u32, rect: PixelRect) -> std::result::Result<Vec<u8>, String> {
+    let stride = image_width as usize * 4;
+    let mut cropped = Vec::with_capacity(rect.width as usize * rect.height as usize * 4);
+    for row in rect.y..rect.y + rect.height {
+        let start = row as usize * stride + rect.x as usize * 4;
+        let end = start + rect.width as usize * 4;
+        let line = rgba
+            .get(start..end)
+            .ok_or_else(|| format!("Crop {:?} is outside the image", rect))?;
+        cropped.extend_from_slice(line);
+    }
+
+    let mut png_bytes = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut png_bytes, rect.width, rect.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+        writer.write_image_data(&cropped).map_err(|e| e.to_string())?;
+    }
+    Ok(png_bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::graph_io::GraphPosition;
+    use std::collections::HashMap;
+
+    fn node_at(id: &str, x: f32, y: f32) -> NodeDefinition {
+        NodeDefinition {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: None,
+            node_type: "string_data".to_string(),
+            input_ports: Vec::new(),
+            output_ports: Vec::new(),
+            position: Some(GraphPosition { x, y }),
+            size: None,
+            inline_values: HashMap::new(),
+            has_error: false,
+        }
+    }
+
+    #[test]
+    fn test_content_bounds() {
+        let mut graph = NodeGraphDefinition::default();
+        assert_eq!(content_bounds(&graph, |_| (200.0, 100.0), 60.0), None);
+
+        graph.nodes = vec![node_at("a", 100.0, 80.0), node_at("b", 400.0, 300.0)];
+        assert_eq!(
+            content_bounds(&graph, |_| (200.0, 100.0), 60.0),
+            Some((40.0, 20.0, 620.0, 440.0))
+        );
+        // The margin doesn't reach past the canvas origin
+        assert_eq!(
+            content_bounds(&graph, |_| (200.0, 100.0), 200.0),
+            Some((0.0, 0.0, 800.0, 600.0))
+        );
+    }
+
+    #[test]
+    fn test_snapshot_rect_scales_and_clips() {
+        let rect = snapshot_rect((40.0, 20.0, 100.0, 50.0), (0.0, 56.0), 2.0, 2400, 1600);
+        assert_eq!(rect, Some(PixelRect { x: 80, y: 152, width: 200, height: 100 }));
+
+        let rect = snapshot_rect((1100.0, 20.0, 300.0, 50.0), (0.0, 0.0), 1.0, 1200, 800);
+        assert_eq!(rect, Some(PixelRect { x: 1100, y: 20, width: 100, height: 50 }));
+        assert_eq!(snapshot_rect((1300.0, 20.0, 100.0, 50.0), (0.0, 0.0), 1.0, 1200, 800), None);
+    }
+
+    #[test]
+    fn test_encode_png_crops() {
+        // 4x2 image, every pixel's red channel is its index
+        let rgba: Vec<u8> = (0..8u8).flat_map(|i| [i, 0, 0, 255]).collect();
+        let rect = PixelRect { x: 1, y: 0, width: 2, height: 2 };
+        let png_bytes = encode_png(&rgba, 4, rect).unwrap();
+
+        let mut reader = png::Decoder::new(png_bytes.as_slice()).read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        assert_eq!((info.width, info.height), (2, 2));
+        let reds: Vec<u8> = pixels.chunks(4).map(|p| p[0]).collect();
+        assert_eq!(reds, [1, 2, 5, 6]);
+
+        assert!(encode_png(&rgba, 4, PixelRect { x: 3, y: 1, width: 2, height: 2 }).is_err());
+    }
+}
@@ -0,0 +1,159 @@
+use crate::node::graph_io::NodeGraphDefinition;
+
+/// Maximum number of undo steps kept per tab
+pub const MAX_UNDO_DEPTH: usize = 100;
+
+/// Undo/redo history of one graph tab, kept as whole-graph snapshots
+#[derive(Default)]
+pub struct GraphHistory {
+    undo_stack: Vec<NodeGraphDefinition>,
+    redo_stack: Vec<NodeGraphDefinition>,
+    /// Graph before the drag or resize in progress, taken on its first update
+    gesture_start: Option<NodeGraphDefinition>,
+}
+
+/// Copy of `graph` for the history. Execution results belong to the last run, not the edit,
+/// so they are left out.
+pub fn snapshot(graph: &NodeGraphDefinition) -> NodeGraphDefinition {
+    let mut snapshot = graph.clone();
+    snapshot.execution_results.clear();
+    snapshot
+}
+
+impl GraphHistory {
+    /// Record an edit, given the graph from before it. Drops the oldest step past
+    /// `MAX_UNDO_DEPTH` and clears the redo stack.
+    pub fn record(&mut self, before: NodeGraphDefinition) {
+        self.undo_stack.push(before);
+        if self.undo_stack.len() > MAX_UNDO_DEPTH {
+            self.undo_stack.remove(0);
+        }
+        self.redo_stack.clear();
+    }
+
+    /// Remember the graph at the start of a drag or resize. Later calls during the same
+    /// gesture keep the first snapshot.
+    pub fn begin_gesture(&mut self, graph: &NodeGraphDefinition) {
+        if self.gesture_start.is_none() {
+            self.gesture_start = Some(snapshot(graph));
+        }
+    }
+
+    /// The graph from before the current gesture, or `graph` itself if none was started
+    pub fn take_gesture(&mut self, graph: &NodeGraphDefinition) -> NodeGraphDefinition {
+        self.gesture_start.take().unwrap_or_else(|| snapshot(graph))
+    }
+
+    /// Restore the graph before the last edit. Returns false if there is nothing to undo.
+    pub fn undo(&mut self, graph: &mut NodeGraphDefinition) -> bool {
+        let Some(previous) = self.undo_stack.pop() else {
+            return false;
+        };
+        self.redo_stack.push(snapshot(graph));
+        restore(graph, previous);
+        true
+    }
+
+    /// Reapply the last undone edit. Returns false if there is nothing to redo.
+    pub fn redo(&mut self, graph: &mut NodeGraphDefinition) -> bool {
+        let Some(next) = self.redo_stack.pop() else {
+            return false;
+        };
+        self.undo_stack.push(snapshot(graph));
+        restore(graph, next);
+        true
+    }
+
+    /// Forget all steps, e.g. when another file is loaded into the tab
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.gesture_start = None;
+    }
+}
+
+/// Replace `graph` with `snapshot`, keeping the results of the last run on screen
+fn restore(graph: &mut NodeGraphDefinition, snapshot: NodeGraphDefinition) {
+    let execution_results = std::mem::take(&mut graph.execution_results);
+    *graph = snapshot;
+    graph.execution_results = execution_results;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::graph_io::EdgeDefinition;
+
+    fn graph_with_edges(count: usize) -> NodeGraphDefinition {
+        let mut graph = NodeGraphDefinition::default();
+        for i in 0..count {
+            graph.edges.push(EdgeDefinition {
+                from_node_id: format!("node_{i}"),
+                from_port: "out".to_string(),
+                to_node_id: format!("node_{}", i + 1),
+                to_port: "in".to_string(),
+                condition: None,
+            });
+        }
+        graph
+    }
+
+    #[test]
+    fn test_undo_redo_round_trip() {
+        let mut history = GraphHistory::default();
+        let mut graph = graph_with_edges(1);
+
+        history.record(snapshot(&graph));
+        graph = graph_with_edges(2);
+
+        assert!(history.undo(&mut graph));
+        assert_eq!(graph.edges.len(), 1);
+        assert!(!history.undo(&mut graph));
+        assert!(history.redo(&mut graph));
+        assert_eq!(graph.edges.len(), 2);
+        assert!(!history.redo(&mut graph));
+    }
+
+    #[test]
+    fn test_new_edit_clears_redo() {
+        let mut history = GraphHistory::default();
+        let mut graph = graph_with_edges(1);
+        history.record(snapshot(&graph));
+        graph = graph_with_edges(2);
+        assert!(history.undo(&mut graph));
+
+        history.record(snapshot(&graph));
+        assert!(!history.redo(&mut graph));
+    }
+
+    #[test]
+    fn test_depth_is_capped() {
+        let mut history = GraphHistory::default();
+        let mut graph = graph_with_edges(0);
+        for i in 1..=MAX_UNDO_DEPTH + 5 {
+            history.record(snapshot(&graph));
+            graph = graph_with_edges(i);
+        }
+
+        let mut steps = 0;
+        while history.undo(&mut graph) {
+            steps += 1;
+        }
+        assert_eq!(steps, MAX_UNDO_DEPTH);
+        // The oldest steps were dropped
+        assert_eq!(graph.edges.len(), 5);
+    }
+
+    #[test]
+    fn test_gesture_keeps_first_snapshot() {
+        let mut history = GraphHistory::default();
+        let mut graph = graph_with_edges(1);
+        history.begin_gesture(&graph);
+        graph = graph_with_edges(2);
+        history.begin_gesture(&graph);
+
+        assert_eq!(history.take_gesture(&graph).edges.len(), 1);
+        // Without a gesture in progress the current graph is used
+        assert_eq!(history.take_gesture(&graph).edges.len(), 2);
+    }
+}
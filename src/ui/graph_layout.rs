@@ -0,0 +1,302 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::node::graph_io::{GraphPosition, NodeDefinition, NodeGraphDefinition};
+
+/// Columns of the grid that nodes without edges are packed into
+const UNCONNECTED_COLUMNS: usize = 4;
+
+/// Assign every connected node a layer, its longest dependency path from a node without
+/// inputs. Nodes without any edge are left out. Nodes on a cycle, which have no such
+/// depth, go into one layer after all others.
+pub fn assign_layers(graph: &NodeGraphDefinition) -> HashMap<String, usize> {
+    let node_ids: HashSet<&str> = graph.nodes.iter().map(|n| n.id.as_str()).collect();
+    let mut in_degree: HashMap<&str, usize> = HashMap::new();
+    let mut dependents: HashMap<&str, Vec<&str>> = HashMap::new();
+    for edge in &graph.edges {
+        let (from, to) = (edge.from_node_id.as_str(), edge.to_node_id.as_str());
+        if from == to || !node_ids.contains(from) || !node_ids.contains(to) {
+            continue;
+        }
+        in_degree.entry(from).or_insert(0);
+        *in_degree.entry(to).or_insert(0) += 1;
+        dependents.entry(from).or_default().push(to);
+    }
+
+    // Walk in graph order so the result doesn't depend on hash order
+    let mut ready: Vec<&str> = graph
+        .nodes
+        .iter()
+        .map(|n| n.id.as_str())
+        .filter(|id| in_degree.get(id) == Some(&0))
+        .collect();
+    let mut layers: HashMap<String, usize> = HashMap::new();
+    let mut next = 0;
+    while next < ready.len() {
+        let node_id = ready[next];
+        next += 1;
+        let layer = *layers.entry(node_id.to_string()).or_insert(0);
+        for &dependent in dependents.get(node_id).into_iter().flatten() {
+            let entry = layers.entry(dependent.to_string()).or_insert(0);
+            *entry = (*entry).max(layer + 1);
+            let count = in_degree.get_mut(dependent).unwrap();
+            *count -= 1;
+            if *count == 0 {
+                ready.push(dependent);
+            }
+        }
+    }
+
+    let cycle_layer = layers.values().max().map_or(0, |max| max + 1);
+    for node in &graph.nodes {
+        if in_degree.get(node.id.as_str()).is_some_and(|count| *count > 0) {
+            layers.insert(node.id.clone(), cycle_layer);
+        }
+    }
+    layers
+}
+
+/// Lay out the graph left to right: one column per layer from `assign_layers`, nodes of a
+/// column stacked top to bottom in the order of their inputs. Nodes without edges are packed
+/// into a grid below. `dimensions` gives a node's width and height; all positions are
+/// multiples of `grid_size`.
+pub fn auto_layout(
+    graph: &mut NodeGraphDefinition,
+    grid_size: f32,
+    dimensions: impl Fn(&NodeDefinition) -> (f32, f32),
+) {
+    let margin = grid_size * 2.0;
+    let column_gap = grid_size * 4.0;
+    let row_gap = grid_size * 2.0;
+    let snap = |value: f32| (value / grid_size).round() * grid_size;
+
+    let layers = assign_layers(graph);
+    let layer_count = layers.values().max().map_or(0, |max| max + 1);
+    let mut columns: Vec<Vec<usize>> = vec![Vec::new(); layer_count];
+    let mut unconnected = Vec::new();
+    for (index, node) in graph.nodes.iter().enumerate() {
+        match layers.get(&node.id) {
+            Some(layer) => columns[*layer].push(index),
+            None => unconnected.push(index),
+        }
+    }
+
+    // Order each column by the mean row of its inputs in earlier columns, to keep
+    // edges from crossing where possible
+    let mut row_of: HashMap<&str, usize> = HashMap::new();
+    for column in &mut columns {
+        let barycenter = |index: &usize| {
+            let rows: Vec<usize> = graph
+                .edges
+                .iter()
+                .filter(|e| e.to_node_id == graph.nodes[*index].id)
+                .filter_map(|e| row_of.get(e.from_node_id.as_str()).copied())
+                .collect();
+            if rows.is_empty() {
+                f32::MAX
+            } else {
+                rows.iter().sum::<usize>() as f32 / rows.len() as f32
+            }
+        };
+        let mut keyed: Vec<(f32, usize)> = column.iter().map(|index| (barycenter(index), *index)).collect();
+        keyed.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+        *column = keyed.into_iter().map(|(_, index)| index).collect();
+        for (row, index) in column.iter().enumerate() {
+            row_of.insert(graph.nodes[*index].id.as_str(), row);
+        }
+    }
+
+    let mut positions: Vec<(usize, GraphPosition)> = Vec::with_capacity(graph.nodes.len());
+    let mut x = margin;
+    let mut bottom = margin;
+    for column in &columns {
+        let mut y = margin;
+        let mut column_width: f32 = 0.0;
+        for index in column {
+            let (width, height) = dimensions(&graph.nodes[*index]);
+            positions.push((*index, GraphPosition { x: snap(x), y: snap(y) }));
+            y += height + row_gap;
+            column_width = column_width.max(width);
+        }
+        bottom = bottom.max(y);
+        x += column_width + column_gap;
+    }
+
+    let mut y = if columns.is_empty() { margin } else { bottom + row_gap };
+    for row in unconnected.chunks(UNCONNECTED_COLUMNS) {
+        let mut x = margin;
+        let mut row_height: f32 = 0.0;
+        for index in row {
+            let (width, height) = dimensions(&graph.nodes[*index]);
+            positions.push((*index, GraphPosition { x: snap(x), y: snap(y) }));
+            x += width + column_gap;
+            row_height = row_height.max(height);
+        }
+        y += row_height + row_gap;
+    }
+
+    for (index, position) in positions {
+        graph.nodes[index].position = Some(position);
+    }
+}
+
+/// Move the nodes in `node_ids` together by `(dx, dy)`. With a `grid_size`, the shift is
+/// adjusted so the group's top-left corner lands on the grid, and every node keeps its offset
+/// to the others. Returns false if none of the nodes has a position to move.
+pub fn nudge_nodes(
+    graph: &mut NodeGraphDefinition,
+    node_ids: &HashSet<String>,
+    dx: f32,
+    dy: f32,
+    grid_size: Option<f32>,
+) -> bool {
+    let corner = graph
+        .nodes
+        .iter()
+        .filter(|n| node_ids.contains(&n.id))
+        .filter_map(|n| n.position.as_ref())
+        .fold(None, |corner: Option<(f32, f32)>, pos| match corner {
+            Some((x, y)) => Some((x.min(pos.x), y.min(pos.y))),
+            None => Some((pos.x, pos.y)),
+        });
+    let Some((left, top)) = corner else {
+        return false;
+    };
+
+    let (dx, dy) = match grid_size {
+        Some(grid) => {
+            let snap = |value: f32| (value / grid).round() * grid;
+            (snap(left + dx) - left, snap(top + dy) - top)
+        }
+        None => (dx, dy),
+    };
+    for node in graph.nodes.iter_mut().filter(|n| node_ids.contains(&n.id)) {
+        if let Some(pos) = &mut node.position {
+            pos.x += dx;
+            pos.y += dy;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::graph_io::EdgeDefinition;
+
+    fn node(id: &str) -> NodeDefinition {
+        NodeDefinition {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: None,
+            node_type: "string_data".to_string(),
+            input_ports: Vec::new(),
+            output_ports: Vec::new(),
+            position: None,
+            size: None,
+            inline_values: HashMap::new(),
+            has_error: false,
+        }
+    }
+
+    fn graph(ids: &[&str], edges: &[(&str, &str)]) -> NodeGraphDefinition {
+        NodeGraphDefinition {
+            nodes: ids.iter().map(|id| node(id)).collect(),
+            edges: edges
+                .iter()
+                .map(|(from, to)| EdgeDefinition {
+                    from_node_id: from.to_string(),
+                    from_port: "out".to_string(),
+                    to_node_id: to.to_string(),
+                    to_port: "in".to_string(),
+                    condition: None,
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_layers_follow_longest_path() {
+        // a -> b -> d, a -> d, c -> d; e has no edges
+        let graph = graph(&["a", "b", "c", "d", "e"], &[("a", "b"), ("b", "d"), ("a", "d"), ("c", "d")]);
+        let layers = assign_layers(&graph);
+
+        assert_eq!(layers["a"], 0);
+        assert_eq!(layers["b"], 1);
+        assert_eq!(layers["c"], 0);
+        assert_eq!(layers["d"], 2);
+        assert!(!layers.contains_key("e"));
+    }
+
+    #[test]
+    fn test_cycle_goes_into_last_layer() {
+        let graph = graph(&["a", "b", "c"], &[("a", "b"), ("b", "c"), ("c", "b")]);
+        let layers = assign_layers(&graph);
+
+        assert_eq!(layers["a"], 0);
+        assert_eq!(layers["b"], 2);
+        assert_eq!(layers["c"], 2);
+    }
+
+    #[test]
+    fn test_auto_layout_columns_and_grid() {
+        let mut graph = graph(&["a", "b", "c", "lonely"], &[("a", "b"), ("a", "c")]);
+        auto_layout(&mut graph, 20.0, |_| (200.0, 100.0));
+
+        let position = |id: &str| {
+            let pos = graph.nodes.iter().find(|n| n.id == id).unwrap().position.clone().unwrap();
+            (pos.x, pos.y)
+        };
+        assert_eq!(position("a"), (40.0, 40.0));
+        // Second column starts after the first node's width and the column gap
+        assert_eq!(position("b"), (320.0, 40.0));
+        assert_eq!(position("c"), (320.0, 180.0));
+        // Below the tallest column
+        assert_eq!(position("lonely"), (40.0, 360.0));
+        for node in &graph.nodes {
+            let pos = node.position.as_ref().unwrap();
+            assert_eq!((pos.x % 20.0, pos.y % 20.0), (0.0, 0.0));
+        }
+    }
+
+    fn positioned(id: &str, x: f32, y: f32) -> NodeDefinition {
+        let mut node = node(id);
+        node.position = Some(GraphPosition { x, y });
+        node
+    }
+
+    fn position_of(graph: &NodeGraphDefinition, id: &str) -> (f32, f32) {
+        let pos = graph.nodes.iter().find(|n| n.id == id).unwrap().position.clone().unwrap();
+        (pos.x, pos.y)
+    }
+
+    #[test]
+    fn test_nudge_moves_group_together() {
+        let mut graph = graph(&[], &[]);
+        graph.nodes = vec![positioned("a", 40.0, 60.0), positioned("b", 200.0, 20.0), positioned("c", 0.0, 0.0)];
+        let selected: HashSet<String> = ["a", "b"].iter().map(|id| id.to_string()).collect();
+
+        assert!(nudge_nodes(&mut graph, &selected, 20.0, 0.0, Some(20.0)));
+        assert_eq!(position_of(&graph, "a"), (60.0, 60.0));
+        assert_eq!(position_of(&graph, "b"), (220.0, 20.0));
+        assert_eq!(position_of(&graph, "c"), (0.0, 0.0));
+
+        assert!(nudge_nodes(&mut graph, &selected, 0.0, -1.0, None));
+        assert_eq!(position_of(&graph, "a"), (60.0, 59.0));
+        assert_eq!(position_of(&graph, "b"), (220.0, 19.0));
+    }
+
+    #[test]
+    fn test_grid_nudge_snaps_group_corner() {
+        let mut graph = graph(&[], &[]);
+        // Off the grid after a fine nudge
+        graph.nodes = vec![positioned("a", 43.0, 61.0), positioned("b", 103.0, 21.0)];
+        let selected: HashSet<String> = ["a", "b"].iter().map(|id| id.to_string()).collect();
+
+        assert!(nudge_nodes(&mut graph, &selected, 20.0, 0.0, Some(20.0)));
+        // The corner (43, 21) lands on (60, 20); both nodes shift by the same amount
+        assert_eq!(position_of(&graph, "a"), (60.0, 60.0));
+        assert_eq!(position_of(&graph, "b"), (120.0, 20.0));
+        assert!(!nudge_nodes(&mut graph, &HashSet::new(), 20.0, 0.0, Some(20.0)));
+    }
+}
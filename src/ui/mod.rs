@@ -1,5 +1,11 @@
 pub mod node_graph_view;
 pub mod graph_window;
+pub mod edge_curve;
+pub mod graph_clipboard;
+pub mod graph_comments;
+pub mod graph_export;
+pub mod graph_history;
+pub mod graph_layout;
 pub mod selection;
 pub mod window_state;
 pub mod node_render;
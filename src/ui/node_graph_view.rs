@@ -1,6 +1,6 @@
 use log::{error, info};
-use slint::{ModelRc, VecModel, SharedString, ComponentHandle};
-use std::collections::HashMap;
+use slint::{Model, ModelRc, VecModel, SharedString, ComponentHandle};
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::sync::atomic::AtomicBool;
@@ -8,15 +8,21 @@ use std::sync::atomic::AtomicBool;
 use crate::error::Result;
 use crate::node::graph_io::{
     ensure_positions,
-    load_graph_definition_from_json,
+    load_graph_definition_from_json_lenient,
     NodeGraphDefinition,
 };
 use crate::node::registry::NODE_REGISTRY;
 
 use crate::ui::graph_window::{
-    EdgeCornerVm, EdgeLabelVm, EdgeSegmentVm, EdgeVm, GridLineVm, NodeGraphWindow, NodeTypeVm,
-    NodeVm, PortVm, MessageItemVm,
+    CommentVm, EdgeCornerVm, EdgeCurveVm, EdgeLabelVm, EdgeSegmentVm, EdgeVm, GraphProblemVm, GridLineVm,
+    NodeGraphWindow, NodeTypeVm, NodeVm, PortVm, MessageItemVm,
 };
+use crate::ui::edge_curve::{bezier_control_points, EdgeStyle};
+use crate::ui::graph_clipboard::{next_node_id, GraphClipboard};
+use crate::ui::graph_comments::{add_comment, move_comment, next_comment_color, parse_hex_color};
+use crate::ui::graph_export::{content_bounds, encode_png, snapshot_rect};
+use crate::ui::graph_history::{snapshot, GraphHistory};
+use crate::ui::graph_layout::{auto_layout, nudge_nodes};
 use crate::ui::selection::{BoxSelection, SelectionState};
 use crate::ui::window_state::{apply_window_state, load_window_state, save_window_state, WindowState};
 #[cfg(target_os = "macos")]
@@ -31,7 +37,7 @@ const CANVAS_WIDTH: f32 = 1200.0;
 const CANVAS_HEIGHT: f32 = 800.0;
 const EDGE_THICKNESS_RATIO: f32 = 0.3;
 
-use crate::ui::node_render::{InlinePortValue, inline_port_key, get_node_preview_text};
+use crate::ui::node_render::{InlinePortValue, inline_port_key, get_node_preview_text, rename_inline_inputs, remove_inline_inputs, edge_value_preview};
 
 struct GraphTabState {
     id: u64,
@@ -40,32 +46,67 @@ struct GraphTabState {
     graph: NodeGraphDefinition,
     selection: SelectionState,
     inline_inputs: HashMap<String, InlinePortValue>,
+    history: GraphHistory,
     is_dirty: bool,
     is_running: bool,
     stop_flag: Option<Arc<AtomicBool>>,
+    pause_flag: Option<Arc<AtomicBool>>,
+    last_run_report: Option<serde_json::Value>,
+    /// `(node_id, port_name, reason)` from the last validation
+    validation_problems: Vec<(String, String, String)>,
+}
+
+fn inline_port_value_from_json(val: &serde_json::Value) -> Option<InlinePortValue> {
+    match val {
+        serde_json::Value::String(s) => Some(InlinePortValue::Text(s.clone())),
+        serde_json::Value::Bool(b) => Some(InlinePortValue::Bool(*b)),
+        serde_json::Value::Number(n) => Some(InlinePortValue::Text(n.to_string())),
+        serde_json::Value::Array(_) | serde_json::Value::Object(_) => Some(InlinePortValue::Json(val.clone())),
+        serde_json::Value::Null => None,
+    }
+}
+
+/// Prefill inline inputs of `node` from its port defaults, keeping values already set
+fn insert_port_defaults(node: &crate::node::graph_io::NodeDefinition, map: &mut HashMap<String, InlinePortValue>) {
+    for port in &node.input_ports {
+        let Some(default) = port.default.as_ref() else {
+            continue;
+        };
+        let key = inline_port_key(&node.id, &port.name);
+        if map.contains_key(&key) {
+            continue;
+        }
+        if let Some(value) = inline_port_value_from_json(&default.to_json()) {
+            map.insert(key, value);
+        }
+    }
+}
+
+/// Bring back inline inputs stored in the nodes of a restored undo step, keeping values already
+/// set. Renaming a node moves its inputs away from the old id, so undoing it finds them here.
+fn restore_inline_inputs(graph: &NodeGraphDefinition, map: &mut HashMap<String, InlinePortValue>) {
+    for node in &graph.nodes {
+        for (port_name, val) in &node.inline_values {
+            let key = inline_port_key(&node.id, port_name);
+            if map.contains_key(&key) {
+                continue;
+            }
+            if let Some(value) = inline_port_value_from_json(val) {
+                map.insert(key, value);
+            }
+        }
+    }
 }
 
 fn build_inline_inputs_from_graph(graph: &NodeGraphDefinition) -> HashMap<String, InlinePortValue> {
     let mut map = HashMap::new();
     for node in &graph.nodes {
         for (port_name, val) in &node.inline_values {
-            let key = inline_port_key(&node.id, port_name);
-            match val {
-                serde_json::Value::String(s) => {
-                    map.insert(key, InlinePortValue::Text(s.clone()));
-                }
-                serde_json::Value::Bool(b) => {
-                    map.insert(key, InlinePortValue::Bool(*b));
-                }
-                serde_json::Value::Number(n) => {
-                    map.insert(key, InlinePortValue::Text(n.to_string()));
-                }
-                serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
-                    map.insert(key, InlinePortValue::Json(val.clone()));
-                }
-                _ => {}
+            if let Some(value) = inline_port_value_from_json(val) {
+                map.insert(inline_port_key(&node.id, port_name), value);
             }
         }
+        insert_port_defaults(node, &mut map);
     }
     map
 }
@@ -91,9 +132,13 @@ fn new_blank_tab(next_untitled: &mut usize, next_id: &mut u64) -> GraphTabState
         graph: NodeGraphDefinition::default(),
         selection: SelectionState::default(),
         inline_inputs: HashMap::new(),
+        history: GraphHistory::default(),
         is_dirty: false,
         is_running: false,
         stop_flag: None,
+        pause_flag: None,
+        last_run_report: None,
+        validation_problems: Vec::new(),
     }
 }
 
@@ -105,6 +150,16 @@ fn update_tabs_ui(ui: &NodeGraphWindow, tabs: &[GraphTabState], active_index: us
 
 fn refresh_active_tab_ui(ui: &NodeGraphWindow, tabs: &[GraphTabState], active_index: usize) {
     if let Some(tab) = tabs.get(active_index) {
+        let problems: Vec<GraphProblemVm> = tab
+            .validation_problems
+            .iter()
+            .map(|(node_id, port, reason)| GraphProblemVm {
+                node_id: node_id.as_str().into(),
+                port: port.as_str().into(),
+                reason: reason.as_str().into(),
+            })
+            .collect();
+        ui.set_graph_problems(ModelRc::new(VecModel::from(problems)));
         apply_graph_to_ui(
             ui,
             &tab.graph,
@@ -114,10 +169,37 @@ fn refresh_active_tab_ui(ui: &NodeGraphWindow, tabs: &[GraphTabState], active_in
         );
         tab.selection.apply_to_ui(ui);
         ui.set_is_graph_running(tab.is_running);
+        ui.set_is_graph_paused(is_paused(tab));
     }
     update_tabs_ui(ui, tabs, active_index);
 }
 
+fn is_paused(tab: &GraphTabState) -> bool {
+    tab.pause_flag
+        .as_ref()
+        .is_some_and(|flag| flag.load(std::sync::atomic::Ordering::Relaxed))
+}
+
+fn set_graph_paused(
+    ui_handle: &slint::Weak<NodeGraphWindow>,
+    tabs: &Arc<Mutex<Vec<GraphTabState>>>,
+    active_tab_index: &Arc<Mutex<usize>>,
+    paused: bool,
+) {
+    let tabs_guard = tabs.lock().unwrap();
+    let active_index = *active_tab_index.lock().unwrap();
+    let Some(pause_flag) = tabs_guard.get(active_index).and_then(|tab| tab.pause_flag.as_ref()) else {
+        return;
+    };
+    pause_flag.store(paused, std::sync::atomic::Ordering::Relaxed);
+    info!("{}", if paused { "暂停节点图执行" } else { "继续节点图执行" });
+
+    if let Some(ui) = ui_handle.upgrade() {
+        ui.set_is_graph_paused(paused);
+        ui.set_connection_status(if paused { "⏸ 节点图已暂停" } else { "⏳ 节点图运行中..." }.into());
+    }
+}
+
 pub fn show_graph(initial_graph: Option<NodeGraphDefinition>) -> Result<()> {
     register_cjk_fonts();
 
@@ -129,6 +211,7 @@ pub fn show_graph(initial_graph: Option<NodeGraphDefinition>) -> Result<()> {
 
     if let Some(state) = load_window_state() {
         apply_window_state(&ui.window(), &state);
+        ui.set_curved_edges(state.edge_style == EdgeStyle::Bezier);
     }
 
     let mut next_untitled_index = 1usize;
@@ -156,6 +239,7 @@ pub fn show_graph(initial_graph: Option<NodeGraphDefinition>) -> Result<()> {
             display_name: meta.display_name.clone().into(),
             category: meta.category.clone().into(),
             description: meta.description.clone().into(),
+            tags: meta.tags.join(", ").into(),
         })
         .collect();
 
@@ -191,24 +275,34 @@ pub fn show_graph(initial_graph: Option<NodeGraphDefinition>) -> Result<()> {
             None => return,
         };
 
-        if let Ok(graph) = load_graph_definition_from_json(&selected_path) {
-            let mut tabs_guard = tabs_clone.lock().unwrap();
-            let active_index = *active_tab_clone.lock().unwrap();
-            if let Some(tab) = tabs_guard.get_mut(active_index) {
-                tab.graph = graph.clone();
-                tab.inline_inputs = build_inline_inputs_from_graph(&graph);
-                tab.selection.clear();
-                tab.file_path = Some(selected_path.clone());
-                tab.title = selected_path
-                    .file_name()
-                    .map(|name| name.to_string_lossy().to_string())
-                    .unwrap_or_else(|| selected_path.display().to_string());
-                tab.is_dirty = false;
+        let graph = match load_graph_definition_from_json_lenient(&selected_path) {
+            Ok(graph) => graph,
+            Err(e) => {
+                if let Some(ui) = ui_handle.upgrade() {
+                    ui.invoke_show_error(format!("打开节点图失败：{}", e).into());
+                }
+                return;
             }
+        };
 
-            if let Some(ui) = ui_handle.upgrade() {
-                refresh_active_tab_ui(&ui, &tabs_guard, active_index);
-            }
+        let mut tabs_guard = tabs_clone.lock().unwrap();
+        let active_index = *active_tab_clone.lock().unwrap();
+        if let Some(tab) = tabs_guard.get_mut(active_index) {
+            tab.graph = graph.clone();
+            tab.inline_inputs = build_inline_inputs_from_graph(&graph);
+            tab.selection.clear();
+            tab.history.clear();
+            tab.validation_problems.clear();
+            tab.file_path = Some(selected_path.clone());
+            tab.title = selected_path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| selected_path.display().to_string());
+            tab.is_dirty = false;
+        }
+
+        if let Some(ui) = ui_handle.upgrade() {
+            refresh_active_tab_ui(&ui, &tabs_guard, active_index);
         }
     });
 
@@ -542,10 +636,17 @@ pub fn show_graph(initial_graph: Option<NodeGraphDefinition>) -> Result<()> {
         let mut tabs_guard = tabs_clone.lock().unwrap();
         let active_index = *active_tab_clone.lock().unwrap();
         if let Some(tab) = tabs_guard.get_mut(active_index) {
+            let before = snapshot(&tab.graph);
             if let Err(e) = add_node_to_graph(&mut tab.graph, type_id_str) {
                 eprintln!("Failed to add node: {}", e);
                 return;
             }
+            if let Some(node) = tab.graph.nodes.last() {
+                // Inputs left behind by a deleted node that had the same id
+                remove_inline_inputs(&mut tab.inline_inputs, &node.id);
+                insert_port_defaults(node, &mut tab.inline_inputs);
+            }
+            tab.history.record(before);
             tab.is_dirty = true;
         }
 
@@ -554,11 +655,42 @@ pub fn show_graph(initial_graph: Option<NodeGraphDefinition>) -> Result<()> {
         }
     });
 
+    let ui_handle = ui.as_weak();
+    let tabs_clone = Arc::clone(&tabs);
+    let active_tab_clone = Arc::clone(&active_tab_index);
+    ui.on_validate_graph(move || {
+        let mut tabs_guard = tabs_clone.lock().unwrap();
+        let active_index = *active_tab_clone.lock().unwrap();
+        let Some(tab) = tabs_guard.get_mut(active_index) else {
+            return false;
+        };
+
+        let graph_def = runnable_definition(&tab.graph, &tab.inline_inputs);
+        tab.validation_problems = validate_definition(&graph_def);
+        let problem_count = tab.validation_problems.len();
+
+        if let Some(ui) = ui_handle.upgrade() {
+            refresh_active_tab_ui(&ui, &tabs_guard, active_index);
+            if problem_count == 0 {
+                ui.set_connection_status("节点图检查通过".into());
+            } else {
+                ui.set_connection_status(format!("节点图有 {} 个问题", problem_count).into());
+            }
+        }
+        problem_count == 0
+    });
+
     let ui_handle = ui.as_weak();
     let tabs_clone = Arc::clone(&tabs);
     let active_tab_clone = Arc::clone(&active_tab_index);
     ui.on_run_graph(move || {
-        let (tab_id, graph_def, inline_inputs_map) = {
+        if let Some(ui) = ui_handle.upgrade() {
+            if !ui.invoke_validate_graph() {
+                return;
+            }
+        }
+
+        let (tab_id, graph_def, inline_inputs_map, file_path) = {
             let tabs_guard = tabs_clone.lock().unwrap();
             let active_index = *active_tab_clone.lock().unwrap();
             let tab = match tabs_guard.get(active_index) {
@@ -571,32 +703,15 @@ pub fn show_graph(initial_graph: Option<NodeGraphDefinition>) -> Result<()> {
                 return;
             }
 
-            (tab.id, tab.graph.clone(), tab.inline_inputs.clone())
+            (tab.id, tab.graph.clone(), tab.inline_inputs.clone(), tab.file_path.clone())
         };
 
-        let mut graph_def = graph_def;
-
-        // Set up inline values in global context for string_data nodes
-        {
-            use crate::node::util_nodes::STRING_DATA_CONTEXT;
-            let mut context = STRING_DATA_CONTEXT.write().unwrap();
-            context.clear();
-
-            for node in &graph_def.nodes {
-                if node.node_type == "string_data" {
-                    let key = inline_port_key(&node.id, "text");
-                    if let Some(InlinePortValue::Text(value)) = inline_inputs_map.get(&key) {
-                        context.insert(node.id.clone(), value.clone());
-                    }
-                }
-            }
-        }
-
-        apply_inline_inputs_to_graph(&mut graph_def, &inline_inputs_map);
+        let graph_def = runnable_definition(&graph_def, &inline_inputs_map);
 
         match crate::node::registry::build_node_graph_from_definition(&graph_def) {
             Ok(mut node_graph) => {
                 info!("开始执行节点图...");
+                node_graph.set_graph_path(file_path);
 
                 let has_event_producer = node_graph
                     .nodes
@@ -611,6 +726,7 @@ pub fn show_graph(initial_graph: Option<NodeGraphDefinition>) -> Result<()> {
                         if let Some(tab) = tabs_guard.iter_mut().find(|t| t.id == tab_id) {
                             tab.is_running = true;
                             tab.stop_flag = Some(stop_flag.clone());
+                            tab.pause_flag = Some(node_graph.get_pause_flag());
                         }
                     }
 
@@ -630,7 +746,7 @@ pub fn show_graph(initial_graph: Option<NodeGraphDefinition>) -> Result<()> {
                     let active_tab_cb = Arc::clone(&active_tab_clone);
                     let inline_inputs_cb = inline_inputs_map.clone();
 
-                    node_graph.set_execution_callback(move |node_id, inputs, outputs| {
+                    node_graph.set_execution_callback(move |_phase, node_id, inputs, outputs| {
                         let node_id = node_id.to_string();
                         let mut result = inputs.clone();
                         for (k, v) in outputs {
@@ -680,6 +796,7 @@ pub fn show_graph(initial_graph: Option<NodeGraphDefinition>) -> Result<()> {
                                 None => return,
                             };
 
+                            tab.last_run_report = Some(execution_result.run_report());
                             tab.graph.execution_results = execution_result.node_results;
 
                             if let (Some(error_node_id), Some(error_msg)) =
@@ -734,10 +851,12 @@ pub fn show_graph(initial_graph: Option<NodeGraphDefinition>) -> Result<()> {
 
                             tab.is_running = false;
                             tab.stop_flag = None;
+                            tab.pause_flag = None;
 
                             if let Some(ui) = ui_weak.upgrade() {
                                 if active_tab_id == Some(tab_id) {
                                     ui.set_is_graph_running(false);
+                                    ui.set_is_graph_paused(false);
                                 }
                             }
                         });
@@ -753,6 +872,7 @@ pub fn show_graph(initial_graph: Option<NodeGraphDefinition>) -> Result<()> {
                         None => return,
                     };
 
+                    tab.last_run_report = Some(execution_result.run_report());
                     tab.graph.execution_results = execution_result.node_results;
 
                     if let (Some(error_node_id), Some(error_msg)) =
@@ -819,6 +939,116 @@ pub fn show_graph(initial_graph: Option<NodeGraphDefinition>) -> Result<()> {
         }
     });
 
+    // Pausing holds event producers between events; unlike stopping, nothing is cleaned up
+    let ui_handle = ui.as_weak();
+    let tabs_clone = Arc::clone(&tabs);
+    let active_tab_clone = Arc::clone(&active_tab_index);
+    ui.on_pause_graph(move || {
+        set_graph_paused(&ui_handle, &tabs_clone, &active_tab_clone, true);
+    });
+
+    let ui_handle = ui.as_weak();
+    let tabs_clone = Arc::clone(&tabs);
+    let active_tab_clone = Arc::clone(&active_tab_index);
+    ui.on_resume_graph(move || {
+        set_graph_paused(&ui_handle, &tabs_clone, &active_tab_clone, false);
+    });
+
+    let ui_handle = ui.as_weak();
+    let tabs_clone = Arc::clone(&tabs);
+    let active_tab_clone = Arc::clone(&active_tab_index);
+    ui.on_copy_run_report(move || {
+        let report = {
+            let tabs_guard = tabs_clone.lock().unwrap();
+            let active_index = *active_tab_clone.lock().unwrap();
+            tabs_guard.get(active_index).and_then(|tab| tab.last_run_report.clone())
+        };
+        let Some(ui) = ui_handle.upgrade() else {
+            return;
+        };
+        let Some(report) = report else {
+            ui.set_connection_status("尚无运行报告，请先运行节点图".into());
+            return;
+        };
+
+        let text = serde_json::to_string_pretty(&report).unwrap_or_else(|_| report.to_string());
+        match arboard::Clipboard::new().and_then(|mut clipboard| clipboard.set_text(text)) {
+            Ok(()) => {
+                info!("运行报告已复制到剪贴板");
+                ui.set_connection_status("运行报告已复制到剪贴板".into());
+            }
+            Err(e) => {
+                error!("复制运行报告失败: {}", e);
+                ui.invoke_show_error(format!("复制运行报告失败：{}", e).into());
+            }
+        }
+    });
+
+    let ui_handle = ui.as_weak();
+    let tabs_clone = Arc::clone(&tabs);
+    let active_tab_clone = Arc::clone(&active_tab_index);
+    ui.on_export_png(move || {
+        let Some(ui) = ui_handle.upgrade() else {
+            return;
+        };
+        let bounds = {
+            let tabs_guard = tabs_clone.lock().unwrap();
+            let active_index = *active_tab_clone.lock().unwrap();
+            tabs_guard.get(active_index).and_then(|tab| {
+                let mut graph = tab.graph.clone();
+                ensure_positions(&mut graph);
+                content_bounds(&graph, node_dimensions, GRID_SIZE * 3.0)
+            })
+        };
+        let Some(bounds) = bounds else {
+            ui.set_connection_status("节点图为空，没有可导出的内容".into());
+            return;
+        };
+
+        // Capture before the file dialog opens, without the buttons on top of the graph
+        ui.set_exporting(true);
+        let snapshot = ui.window().take_snapshot();
+        ui.set_exporting(false);
+        let snapshot = match snapshot {
+            Ok(snapshot) => snapshot,
+            Err(e) => {
+                error!("导出图片失败: {}", e);
+                ui.invoke_show_error(format!("导出图片失败：{}", e).into());
+                return;
+            }
+        };
+        let Some(rect) = snapshot_rect(
+            bounds,
+            (ui.get_canvas_x(), ui.get_canvas_y()),
+            ui.window().scale_factor(),
+            snapshot.width(),
+            snapshot.height(),
+        ) else {
+            ui.set_connection_status("节点图不在可见区域内，没有可导出的内容".into());
+            return;
+        };
+
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("PNG", &["png"])
+            .set_file_name("node_graph.png")
+            .save_file()
+        else {
+            return;
+        };
+        let result = encode_png(snapshot.as_bytes(), snapshot.width(), rect)
+            .and_then(|png_bytes| std::fs::write(&path, png_bytes).map_err(|e| e.to_string()));
+        match result {
+            Ok(()) => {
+                info!("节点图已导出到 {}", path.display());
+                ui.set_connection_status(format!("节点图已导出到 {}", path.display()).into());
+            }
+            Err(e) => {
+                error!("导出图片失败: {}", e);
+                ui.invoke_show_error(format!("导出图片失败：{}", e).into());
+            }
+        }
+    });
+
     let ui_handle = ui.as_weak();
     let all_node_types_clone = Arc::clone(&all_node_types);
     ui.on_filter_nodes(move |search_text: SharedString, category: SharedString| {
@@ -831,7 +1061,8 @@ pub fn show_graph(initial_graph: Option<NodeGraphDefinition>) -> Result<()> {
                 .filter(|n| {
                     let name_match = search_text.is_empty() 
                         || n.display_name.to_lowercase().contains(&search_text) 
-                        || n.description.to_lowercase().contains(&search_text);
+                        || n.description.to_lowercase().contains(&search_text)
+                        || n.tags.to_lowercase().contains(&search_text);
                     let cat_match = category.is_empty() || n.category == category;
                     name_match && cat_match
                 })
@@ -880,6 +1111,7 @@ pub fn show_graph(initial_graph: Option<NodeGraphDefinition>) -> Result<()> {
         let mut tabs_guard = tabs_clone.lock().unwrap();
         let active_index = *active_tab_clone.lock().unwrap();
         if let Some(tab) = tabs_guard.get_mut(active_index) {
+            tab.history.begin_gesture(&tab.graph);
             if let Some(node) = tab.graph.nodes.iter_mut().find(|n| n.id == node_id.as_str()) {
                 if let Some(pos) = &mut node.position {
                     pos.x = x;
@@ -890,14 +1122,7 @@ pub fn show_graph(initial_graph: Option<NodeGraphDefinition>) -> Result<()> {
             }
 
             if let Some(ui) = ui_handle.upgrade() {
-                let edges = build_edges(&tab.graph, &tab.selection, false);
-                let (edge_segments, edge_corners, edge_labels) =
-                    build_edge_segments(&tab.graph, false);
-
-                ui.set_edges(ModelRc::new(VecModel::from(edges)));
-                ui.set_edge_segments(ModelRc::new(VecModel::from(edge_segments)));
-                ui.set_edge_corners(ModelRc::new(VecModel::from(edge_corners)));
-                ui.set_edge_labels(ModelRc::new(VecModel::from(edge_labels)));
+                apply_edges_to_ui(&ui, &tab.graph, &tab.selection, false);
             }
         }
     });
@@ -909,19 +1134,13 @@ pub fn show_graph(initial_graph: Option<NodeGraphDefinition>) -> Result<()> {
         let mut tabs_guard = tabs_clone.lock().unwrap();
         let active_index = *active_tab_clone.lock().unwrap();
         if let Some(tab) = tabs_guard.get_mut(active_index) {
+            tab.history.begin_gesture(&tab.graph);
             if let Some(node) = tab.graph.nodes.iter_mut().find(|n| n.id == node_id.as_str()) {
                 node.size = Some(crate::node::graph_io::GraphSize { width, height });
             }
 
             if let Some(ui) = ui_handle.upgrade() {
-                let edges = build_edges(&tab.graph, &tab.selection, false);
-                let (edge_segments, edge_corners, edge_labels) =
-                    build_edge_segments(&tab.graph, false);
-
-                ui.set_edges(ModelRc::new(VecModel::from(edges)));
-                ui.set_edge_segments(ModelRc::new(VecModel::from(edge_segments)));
-                ui.set_edge_corners(ModelRc::new(VecModel::from(edge_corners)));
-                ui.set_edge_labels(ModelRc::new(VecModel::from(edge_labels)));
+                apply_edges_to_ui(&ui, &tab.graph, &tab.selection, false);
             }
         }
     });
@@ -933,6 +1152,7 @@ pub fn show_graph(initial_graph: Option<NodeGraphDefinition>) -> Result<()> {
         let mut tabs_guard = tabs_clone.lock().unwrap();
         let active_index = *active_tab_clone.lock().unwrap();
         if let Some(tab) = tabs_guard.get_mut(active_index) {
+            let before = tab.history.take_gesture(&tab.graph);
             let snapped_x = snap_to_grid(x);
             let snapped_y = snap_to_grid(y);
             if let Some(node) = tab.graph.nodes.iter_mut().find(|n| n.id == node_id.as_str()) {
@@ -947,7 +1167,11 @@ pub fn show_graph(initial_graph: Option<NodeGraphDefinition>) -> Result<()> {
                 }
             }
 
-            tab.is_dirty = true;
+            // A click without dragging also ends a move, and isn't worth an undo step
+            if node_layout_changed(&before, &tab.graph, node_id.as_str()) {
+                tab.history.record(before);
+                tab.is_dirty = true;
+            }
 
             if let Some(ui) = ui_handle.upgrade() {
                 refresh_active_tab_ui(&ui, &tabs_guard, active_index);
@@ -962,6 +1186,7 @@ pub fn show_graph(initial_graph: Option<NodeGraphDefinition>) -> Result<()> {
         let mut tabs_guard = tabs_clone.lock().unwrap();
         let active_index = *active_tab_clone.lock().unwrap();
         if let Some(tab) = tabs_guard.get_mut(active_index) {
+            let before = tab.history.take_gesture(&tab.graph);
             let snapped_width = snap_to_grid(width).max(GRID_SIZE * NODE_WIDTH_CELLS);
             if let Some(node) = tab.graph.nodes.iter_mut().find(|n| n.id == node_id.as_str()) {
                 let min_height = GRID_SIZE
@@ -978,7 +1203,10 @@ pub fn show_graph(initial_graph: Option<NodeGraphDefinition>) -> Result<()> {
                 });
             }
 
-            tab.is_dirty = true;
+            if node_layout_changed(&before, &tab.graph, node_id.as_str()) {
+                tab.history.record(before);
+                tab.is_dirty = true;
+            }
 
             if let Some(ui) = ui_handle.upgrade() {
                 refresh_active_tab_ui(&ui, &tabs_guard, active_index);
@@ -986,6 +1214,49 @@ pub fn show_graph(initial_graph: Option<NodeGraphDefinition>) -> Result<()> {
         }
     });
 
+    let ui_handle = ui.as_weak();
+    let tabs_clone = Arc::clone(&tabs);
+    let active_tab_clone = Arc::clone(&active_tab_index);
+    ui.on_rename_node(move |old_id: SharedString, new_id: SharedString| {
+        let Some(ui) = ui_handle.upgrade() else {
+            return;
+        };
+        let mut tabs_guard = tabs_clone.lock().unwrap();
+        let active_index = *active_tab_clone.lock().unwrap();
+        if let Some(tab) = tabs_guard.get_mut(active_index) {
+            let (old_id, new_id) = (old_id.as_str(), new_id.trim());
+            if old_id == new_id {
+                return;
+            }
+            // The inline inputs move to the new id, so the undo step carries them for the old one
+            let mut before = snapshot(&tab.graph);
+            apply_inline_inputs_to_graph(&mut before, &tab.inline_inputs);
+            if let Err(e) = tab.graph.rename_node(old_id, new_id) {
+                ui.invoke_show_error(format!("重命名节点失败：{}", e).into());
+                return;
+            }
+            rename_inline_inputs(&mut tab.inline_inputs, old_id, new_id);
+
+            if tab.selection.selected_node_ids.remove(old_id) {
+                tab.selection.selected_node_ids.insert(new_id.to_string());
+            }
+            for id in [&mut tab.selection.selected_edge_from_node, &mut tab.selection.selected_edge_to_node] {
+                if id == old_id {
+                    *id = new_id.to_string();
+                }
+            }
+            for (node_id, _, _) in &mut tab.validation_problems {
+                if node_id == old_id {
+                    *node_id = new_id.to_string();
+                }
+            }
+            tab.history.record(before);
+            tab.is_dirty = true;
+        }
+
+        refresh_active_tab_ui(&ui, &tabs_guard, active_index);
+    });
+
     let port_selection = Arc::new(Mutex::new(None::<(String, String, bool)>));
     let port_selection_for_click = Arc::clone(&port_selection);
     let port_selection_for_move = Arc::clone(&port_selection);
@@ -1007,6 +1278,7 @@ pub fn show_graph(initial_graph: Option<NodeGraphDefinition>) -> Result<()> {
                 let mut tabs_guard = tabs_clone.lock().unwrap();
                 let active_index = *active_tab_clone.lock().unwrap();
                 if let Some(tab) = tabs_guard.get_mut(active_index) {
+                    let before = snapshot(&tab.graph);
                     ensure_positions(&mut tab.graph);
 
                     let (from_node, from_port, to_node, to_port) = if is_input {
@@ -1020,8 +1292,10 @@ pub fn show_graph(initial_graph: Option<NodeGraphDefinition>) -> Result<()> {
                         from_port,
                         to_node_id: to_node,
                         to_port,
+                        condition: None,
                     });
 
+                    tab.history.record(before);
                     tab.is_dirty = true;
 
                     if let Some(ui) = ui_handle_for_click.upgrade() {
@@ -1162,6 +1436,7 @@ pub fn show_graph(initial_graph: Option<NodeGraphDefinition>) -> Result<()> {
             let mut tabs_guard = tabs_clone.lock().unwrap();
             let active_index = *active_tab_clone.lock().unwrap();
             if let Some(tab) = tabs_guard.get_mut(active_index) {
+                let before = snapshot(&tab.graph);
                 if !tab.selection.selected_node_ids.is_empty() {
                     tab.graph.nodes.retain(|n| !tab.selection.selected_node_ids.contains(&n.id));
                     tab.graph.edges.retain(|e| {
@@ -1177,9 +1452,12 @@ pub fn show_graph(initial_graph: Option<NodeGraphDefinition>) -> Result<()> {
                     });
                 }
 
+                if tab.graph.nodes.len() != before.nodes.len() || tab.graph.edges.len() != before.edges.len() {
+                    tab.history.record(before);
+                    tab.is_dirty = true;
+                }
                 tab.selection.clear();
                 tab.selection.apply_to_ui(&ui);
-                tab.is_dirty = true;
 
                 apply_graph_to_ui(
                     &ui,
@@ -1193,6 +1471,313 @@ pub fn show_graph(initial_graph: Option<NodeGraphDefinition>) -> Result<()> {
         }
     });
     
+    let ui_handle = ui.as_weak();
+    let tabs_clone = Arc::clone(&tabs);
+    let active_tab_clone = Arc::clone(&active_tab_index);
+    ui.on_undo(move || {
+        let mut tabs_guard = tabs_clone.lock().unwrap();
+        let active_index = *active_tab_clone.lock().unwrap();
+        if let Some(tab) = tabs_guard.get_mut(active_index) {
+            if !tab.history.undo(&mut tab.graph) {
+                return;
+            }
+            restore_inline_inputs(&tab.graph, &mut tab.inline_inputs);
+            // Selected nodes or edges may not exist in the restored graph
+            tab.selection.clear();
+            tab.is_dirty = true;
+        }
+
+        if let Some(ui) = ui_handle.upgrade() {
+            refresh_active_tab_ui(&ui, &tabs_guard, active_index);
+        }
+    });
+
+    let ui_handle = ui.as_weak();
+    let tabs_clone = Arc::clone(&tabs);
+    let active_tab_clone = Arc::clone(&active_tab_index);
+    ui.on_redo(move || {
+        let mut tabs_guard = tabs_clone.lock().unwrap();
+        let active_index = *active_tab_clone.lock().unwrap();
+        if let Some(tab) = tabs_guard.get_mut(active_index) {
+            if !tab.history.redo(&mut tab.graph) {
+                return;
+            }
+            restore_inline_inputs(&tab.graph, &mut tab.inline_inputs);
+            tab.selection.clear();
+            tab.is_dirty = true;
+        }
+
+        if let Some(ui) = ui_handle.upgrade() {
+            refresh_active_tab_ui(&ui, &tabs_guard, active_index);
+        }
+    });
+
+    let ui_handle = ui.as_weak();
+    let tabs_clone = Arc::clone(&tabs);
+    let active_tab_clone = Arc::clone(&active_tab_index);
+    ui.on_auto_layout(move || {
+        let mut tabs_guard = tabs_clone.lock().unwrap();
+        let active_index = *active_tab_clone.lock().unwrap();
+        if let Some(tab) = tabs_guard.get_mut(active_index) {
+            if tab.graph.nodes.is_empty() {
+                return;
+            }
+            let before = snapshot(&tab.graph);
+            auto_layout(&mut tab.graph, GRID_SIZE, node_dimensions);
+            tab.history.record(before);
+            tab.is_dirty = true;
+        }
+
+        if let Some(ui) = ui_handle.upgrade() {
+            refresh_active_tab_ui(&ui, &tabs_guard, active_index);
+        }
+    });
+
+    let ui_handle = ui.as_weak();
+    let tabs_clone = Arc::clone(&tabs);
+    let active_tab_clone = Arc::clone(&active_tab_index);
+    ui.on_add_comment(move || {
+        let mut tabs_guard = tabs_clone.lock().unwrap();
+        let active_index = *active_tab_clone.lock().unwrap();
+        if let Some(tab) = tabs_guard.get_mut(active_index) {
+            let before = snapshot(&tab.graph);
+            ensure_positions(&mut tab.graph);
+            // Encloses the selected nodes, if any
+            add_comment(
+                &mut tab.graph,
+                &tab.selection.selected_node_ids,
+                node_dimensions,
+                GRID_SIZE,
+                (GRID_SIZE * 2.0, GRID_SIZE * 2.0),
+            );
+            tab.history.record(before);
+            tab.is_dirty = true;
+        }
+
+        if let Some(ui) = ui_handle.upgrade() {
+            refresh_active_tab_ui(&ui, &tabs_guard, active_index);
+        }
+    });
+
+    let ui_handle = ui.as_weak();
+    let tabs_clone = Arc::clone(&tabs);
+    let active_tab_clone = Arc::clone(&active_tab_index);
+    ui.on_comment_move_finished(move |comment_id: SharedString, x: f32, y: f32, carry_nodes: bool| {
+        let mut tabs_guard = tabs_clone.lock().unwrap();
+        let active_index = *active_tab_clone.lock().unwrap();
+        if let Some(tab) = tabs_guard.get_mut(active_index) {
+            let before = snapshot(&tab.graph);
+            ensure_positions(&mut tab.graph);
+            let (x, y) = (snap_to_grid(x).max(0.0), snap_to_grid(y).max(0.0));
+            if move_comment(&mut tab.graph, comment_id.as_str(), x, y, carry_nodes, node_dimensions) {
+                tab.history.record(before);
+                tab.is_dirty = true;
+            }
+        }
+
+        if let Some(ui) = ui_handle.upgrade() {
+            refresh_active_tab_ui(&ui, &tabs_guard, active_index);
+        }
+    });
+
+    let ui_handle = ui.as_weak();
+    let tabs_clone = Arc::clone(&tabs);
+    let active_tab_clone = Arc::clone(&active_tab_index);
+    ui.on_comment_resize_finished(move |comment_id: SharedString, width: f32, height: f32| {
+        let mut tabs_guard = tabs_clone.lock().unwrap();
+        let active_index = *active_tab_clone.lock().unwrap();
+        if let Some(tab) = tabs_guard.get_mut(active_index) {
+            let before = snapshot(&tab.graph);
+            let size = crate::node::graph_io::GraphSize {
+                width: snap_to_grid(width).max(GRID_SIZE * 6.0),
+                height: snap_to_grid(height).max(GRID_SIZE * 3.0),
+            };
+            if let Some(comment) = tab.graph.comments.iter_mut().find(|c| c.id == comment_id.as_str()) {
+                if comment.size != size {
+                    comment.size = size;
+                    tab.history.record(before);
+                    tab.is_dirty = true;
+                }
+            }
+        }
+
+        if let Some(ui) = ui_handle.upgrade() {
+            refresh_active_tab_ui(&ui, &tabs_guard, active_index);
+        }
+    });
+
+    let ui_handle = ui.as_weak();
+    let tabs_clone = Arc::clone(&tabs);
+    let active_tab_clone = Arc::clone(&active_tab_index);
+    ui.on_comment_text_changed(move |comment_id: SharedString, text: SharedString| {
+        let mut tabs_guard = tabs_clone.lock().unwrap();
+        let active_index = *active_tab_clone.lock().unwrap();
+        if let Some(tab) = tabs_guard.get_mut(active_index) {
+            if let Some(comment) = tab.graph.comments.iter_mut().find(|c| c.id == comment_id.as_str()) {
+                comment.text = text.to_string();
+                tab.is_dirty = true;
+            }
+            if let Some(ui) = ui_handle.upgrade() {
+                update_tabs_ui(&ui, &tabs_guard, active_index);
+            }
+        }
+    });
+
+    let ui_handle = ui.as_weak();
+    let tabs_clone = Arc::clone(&tabs);
+    let active_tab_clone = Arc::clone(&active_tab_index);
+    ui.on_comment_color_cycled(move |comment_id: SharedString| {
+        let mut tabs_guard = tabs_clone.lock().unwrap();
+        let active_index = *active_tab_clone.lock().unwrap();
+        if let Some(tab) = tabs_guard.get_mut(active_index) {
+            let before = snapshot(&tab.graph);
+            if let Some(comment) = tab.graph.comments.iter_mut().find(|c| c.id == comment_id.as_str()) {
+                comment.color = next_comment_color(&comment.color).to_string();
+                tab.history.record(before);
+                tab.is_dirty = true;
+            }
+        }
+
+        if let Some(ui) = ui_handle.upgrade() {
+            refresh_active_tab_ui(&ui, &tabs_guard, active_index);
+        }
+    });
+
+    let ui_handle = ui.as_weak();
+    let tabs_clone = Arc::clone(&tabs);
+    let active_tab_clone = Arc::clone(&active_tab_index);
+    ui.on_comment_deleted(move |comment_id: SharedString| {
+        let mut tabs_guard = tabs_clone.lock().unwrap();
+        let active_index = *active_tab_clone.lock().unwrap();
+        if let Some(tab) = tabs_guard.get_mut(active_index) {
+            let before = snapshot(&tab.graph);
+            tab.graph.comments.retain(|c| c.id != comment_id.as_str());
+            if tab.graph.comments.len() != before.comments.len() {
+                tab.history.record(before);
+                tab.is_dirty = true;
+            }
+        }
+
+        if let Some(ui) = ui_handle.upgrade() {
+            refresh_active_tab_ui(&ui, &tabs_guard, active_index);
+        }
+    });
+
+    let ui_handle = ui.as_weak();
+    let tabs_clone = Arc::clone(&tabs);
+    let active_tab_clone = Arc::clone(&active_tab_index);
+    ui.on_nudge_selected(move |dx: f32, dy: f32| {
+        let mut tabs_guard = tabs_clone.lock().unwrap();
+        let active_index = *active_tab_clone.lock().unwrap();
+        if let Some(tab) = tabs_guard.get_mut(active_index) {
+            let before = snapshot(&tab.graph);
+            ensure_positions(&mut tab.graph);
+            // Whole-cell steps keep the nodes on the grid; single-pixel steps are fine adjustments
+            let grid = (dx % GRID_SIZE == 0.0 && dy % GRID_SIZE == 0.0).then_some(GRID_SIZE);
+            if !nudge_nodes(&mut tab.graph, &tab.selection.selected_node_ids, dx, dy, grid) {
+                return;
+            }
+            tab.history.record(before);
+            tab.is_dirty = true;
+        }
+
+        if let Some(ui) = ui_handle.upgrade() {
+            refresh_active_tab_ui(&ui, &tabs_guard, active_index);
+        }
+    });
+
+    let ui_handle = ui.as_weak();
+    let tabs_clone = Arc::clone(&tabs);
+    let active_tab_clone = Arc::clone(&active_tab_index);
+    ui.on_toggle_edge_style(move || {
+        if let Some(ui) = ui_handle.upgrade() {
+            ui.set_curved_edges(!ui.get_curved_edges());
+            let tabs_guard = tabs_clone.lock().unwrap();
+            let active_index = *active_tab_clone.lock().unwrap();
+            refresh_active_tab_ui(&ui, &tabs_guard, active_index);
+        }
+    });
+
+    let ui_handle = ui.as_weak();
+    let tabs_clone = Arc::clone(&tabs);
+    let active_tab_clone = Arc::clone(&active_tab_index);
+    ui.on_toggle_edge_values(move || {
+        if let Some(ui) = ui_handle.upgrade() {
+            ui.set_show_edge_values(!ui.get_show_edge_values());
+            let tabs_guard = tabs_clone.lock().unwrap();
+            let active_index = *active_tab_clone.lock().unwrap();
+            refresh_active_tab_ui(&ui, &tabs_guard, active_index);
+        }
+    });
+
+    // Shared by all tabs, so nodes can be copied from one graph into another
+    let clipboard: Arc<Mutex<Option<GraphClipboard>>> = Arc::new(Mutex::new(None));
+
+    // Returns whether any nodes were selected to copy
+    let copy_selection = Arc::new({
+        let tabs_clone = Arc::clone(&tabs);
+        let active_tab_clone = Arc::clone(&active_tab_index);
+        let clipboard_clone = Arc::clone(&clipboard);
+        move || -> bool {
+            let mut tabs_guard = tabs_clone.lock().unwrap();
+            let active_index = *active_tab_clone.lock().unwrap();
+            let Some(tab) = tabs_guard.get_mut(active_index) else {
+                return false;
+            };
+            ensure_positions(&mut tab.graph);
+            match GraphClipboard::copy(&tab.graph, &tab.inline_inputs, &tab.selection.selected_node_ids) {
+                Some(copied) => {
+                    *clipboard_clone.lock().unwrap() = Some(copied);
+                    true
+                }
+                None => false,
+            }
+        }
+    });
+
+    let copy_selection_clone = Arc::clone(&copy_selection);
+    ui.on_copy_selected(move || {
+        copy_selection_clone();
+    });
+
+    let paste_clipboard = Arc::new({
+        let tabs_clone = Arc::clone(&tabs);
+        let active_tab_clone = Arc::clone(&active_tab_index);
+        let clipboard_clone = Arc::clone(&clipboard);
+        let ui_handle = ui.as_weak();
+        move || {
+            let mut clipboard_guard = clipboard_clone.lock().unwrap();
+            let Some(copied) = clipboard_guard.as_mut() else {
+                return;
+            };
+            let mut tabs_guard = tabs_clone.lock().unwrap();
+            let active_index = *active_tab_clone.lock().unwrap();
+            if let Some(tab) = tabs_guard.get_mut(active_index) {
+                let before = snapshot(&tab.graph);
+                ensure_positions(&mut tab.graph);
+                let pasted = copied.paste(&mut tab.graph, &mut tab.inline_inputs, GRID_SIZE);
+                tab.history.record(before);
+                tab.selection.select_nodes(pasted);
+                tab.is_dirty = true;
+            }
+
+            if let Some(ui) = ui_handle.upgrade() {
+                refresh_active_tab_ui(&ui, &tabs_guard, active_index);
+            }
+        }
+    });
+
+    let paste_clipboard_clone = Arc::clone(&paste_clipboard);
+    ui.on_paste(move || paste_clipboard_clone());
+
+    let copy_selection_clone = Arc::clone(&copy_selection);
+    let paste_clipboard_clone = Arc::clone(&paste_clipboard);
+    ui.on_duplicate_selected(move || {
+        if copy_selection_clone() {
+            paste_clipboard_clone();
+        }
+    });
+
     // Setup box selection
     let box_selection = Arc::new(Mutex::new(BoxSelection::new()));
     
@@ -1503,7 +2088,12 @@ pub fn show_graph(initial_graph: Option<NodeGraphDefinition>) -> Result<()> {
 
     let run_result = ui.run();
     if run_result.is_ok() {
-        let state = WindowState::from_window(&ui.window());
+        let edge_style = if ui.get_curved_edges() {
+            EdgeStyle::Bezier
+        } else {
+            EdgeStyle::Orthogonal
+        };
+        let state = WindowState::from_window(&ui.window(), edge_style);
         if let Err(e) = save_window_state(&state) {
             eprintln!("Failed to save window state: {e}");
         }
@@ -1572,6 +2162,12 @@ fn apply_graph_to_ui(
         }
     }
     
+    let problem_ports: HashSet<(String, String)> = ui
+        .get_graph_problems()
+        .iter()
+        .map(|problem| (problem.node_id.to_string(), problem.port.to_string()))
+        .collect();
+
     let nodes: Vec<NodeVm> = graph
         .nodes
         .iter()
@@ -1609,6 +2205,7 @@ fn apply_graph_to_ui(
                         crate::node::DataType::String
                         | crate::node::DataType::Integer
                         | crate::node::DataType::Float
+                        | crate::node::DataType::DateTime
                         | crate::node::DataType::Password => {
                             let value = match inline_inputs.get(&key) {
                                 Some(InlinePortValue::Text(v)) => v.clone(),
@@ -1628,6 +2225,12 @@ fn apply_graph_to_ui(
                         }
                         _ => (String::new(), false, false),
                     };
+                    let choices: Vec<SharedString> = p
+                        .allowed
+                        .iter()
+                        .flatten()
+                        .map(|choice| choice.as_str().into())
+                        .collect();
                     PortVm {
                         name: p.name.clone().into(),
                         is_input: true,
@@ -1637,6 +2240,9 @@ fn apply_graph_to_ui(
                         data_type: p.data_type.to_string().into(),
                         inline_text: inline_text.into(),
                         inline_bool,
+                        choices: ModelRc::new(VecModel::from(choices)),
+                        has_error: problem_ports.contains(&(node.id.clone(), p.name.clone())),
+                        description: p.description.clone().unwrap_or_default().into(),
                     }
                 })
                 .collect();
@@ -1657,17 +2263,20 @@ fn apply_graph_to_ui(
                         data_type: p.data_type.to_string().into(),
                         inline_text: "".into(),
                         inline_bool: false,
+                        choices: ModelRc::default(),
+                        has_error: false,
+                        description: p.description.clone().unwrap_or_default().into(),
                     }
                 })
                 .collect();
 
             // Get string_data text value from inline inputs
             let string_data_text = if node.node_type == "string_data" {
-                let key = inline_port_key(&node.id, "text");
-                match inline_inputs.get(&key) {
-                    Some(InlinePortValue::Text(value)) => value.clone(),
-                    _ => String::new(),
-                }
+                let key = inline_port_key(&node.id, "value");
+                inline_inputs
+                    .get(&key)
+                    .map(|value| format!("{:#}", value.to_data_value()))
+                    .unwrap_or_default()
             } else {
                 String::new()
             };
@@ -1676,7 +2285,7 @@ fn apply_graph_to_ui(
             // and for message_list_data nodes (from inline JSON editor state)
             let message_list: Vec<MessageItemVm> = if node.node_type == "preview_message_list" {
                 use crate::ui::node_render::preview_message_list::get_message_list_data;
-                get_message_list_data(&node.id, &graph)
+                get_message_list_data(&node.id, &graph, inline_inputs)
                     .into_iter()
                     .map(|msg| MessageItemVm {
                         role: msg.role.into(),
@@ -1731,20 +2340,91 @@ fn apply_graph_to_ui(
         })
         .collect();
 
-    // Calculate edge visual positions based on node positions
-    let edges = build_edges(&graph, selection_state, true);
-    let (edge_segments, edge_corners, edge_labels) = build_edge_segments(&graph, true);
-
     let label = current_file.unwrap_or_else(|| "已加载 JSON".to_string());
     let grid_lines = build_grid_lines(CANVAS_WIDTH, CANVAS_HEIGHT, GRID_SIZE);
 
     ui.set_nodes(ModelRc::new(VecModel::from(nodes)));
+    ui.set_comments(ModelRc::new(VecModel::from(build_comments(&graph))));
+    // Calculate edge visual positions based on node positions
+    apply_edges_to_ui(ui, &graph, selection_state, true);
+    ui.set_grid_lines(ModelRc::new(VecModel::from(grid_lines)));
+    ui.set_current_file(label.into());
+}
+
+fn build_comments(graph: &NodeGraphDefinition) -> Vec<CommentVm> {
+    graph
+        .comments
+        .iter()
+        .map(|comment| {
+            let (red, green, blue) = parse_hex_color(&comment.color).unwrap_or((0x80, 0x80, 0x80));
+            CommentVm {
+                id: comment.id.as_str().into(),
+                text: comment.text.as_str().into(),
+                color: slint::Color::from_rgb_u8(red, green, blue),
+                x: comment.position.x,
+                y: comment.position.y,
+                width: comment.size.width,
+                height: comment.size.height,
+            }
+        })
+        .collect()
+}
+
+/// Update the edges on the canvas, drawn in the edge style selected in the window and
+/// labelled with the values of the last run if the window shows them
+fn apply_edges_to_ui(
+    ui: &NodeGraphWindow,
+    graph: &NodeGraphDefinition,
+    selection_state: &crate::ui::selection::SelectionState,
+    snap: bool,
+) {
+    let edges = build_edges(graph, selection_state, snap);
+    let (mut edge_segments, mut edge_corners, edge_labels) = build_edge_segments(graph, snap, ui.get_show_edge_values());
+    // Labels sit halfway between the ports, which is also the middle of the curve
+    let edge_curves = if ui.get_curved_edges() {
+        edge_segments.clear();
+        edge_corners.clear();
+        build_edge_curves(graph, snap)
+    } else {
+        Vec::new()
+    };
+
     ui.set_edges(ModelRc::new(VecModel::from(edges)));
     ui.set_edge_segments(ModelRc::new(VecModel::from(edge_segments)));
     ui.set_edge_corners(ModelRc::new(VecModel::from(edge_corners)));
+    ui.set_edge_curves(ModelRc::new(VecModel::from(edge_curves)));
     ui.set_edge_labels(ModelRc::new(VecModel::from(edge_labels)));
-    ui.set_grid_lines(ModelRc::new(VecModel::from(grid_lines)));
-    ui.set_current_file(label.into());
+}
+
+/// The tab's graph with its inline inputs, as it is built for a run
+fn runnable_definition(
+    graph: &NodeGraphDefinition,
+    inline_inputs: &HashMap<String, InlinePortValue>,
+) -> NodeGraphDefinition {
+    let mut graph_def = graph.clone();
+
+    // string_data values travel as inline values of this graph, so tabs never share them.
+    // Graphs saved before the node had a `value` input lack the port in their definition.
+    for node in &mut graph_def.nodes {
+        if node.node_type == "string_data" {
+            let key = inline_port_key(&node.id, "value");
+            if let Some(InlinePortValue::Text(value)) = inline_inputs.get(&key) {
+                node.inline_values
+                    .insert("value".to_string(), serde_json::Value::String(value.clone()));
+            }
+        }
+    }
+
+    apply_inline_inputs_to_graph(&mut graph_def, inline_inputs);
+    graph_def
+}
+
+/// Problems of a graph as `(node_id, port_name, reason)`, including a graph that can't be built
+fn validate_definition(graph_def: &NodeGraphDefinition) -> Vec<(String, String, String)> {
+    match crate::node::registry::build_node_graph_from_definition(graph_def) {
+        Ok(node_graph) => node_graph.validate(),
+        Err(e) => vec![(String::new(), String::new(), format!("构建节点图失败：{}", e))],
+    }
 }
 
 fn apply_inline_inputs_to_graph(
@@ -1817,11 +2497,9 @@ fn add_node_to_graph(graph: &mut NodeGraphDefinition, type_id: &str) -> Result<(
     let id = next_node_id(graph);
     
     // Get metadata from registry
-    let all_types = NODE_REGISTRY.get_all_types();
-    let metadata = all_types.iter().find(|meta| meta.type_id == type_id);
-    
-    let display_name = metadata
-        .map(|m| m.display_name.clone())
+    let display_name = NODE_REGISTRY
+        .get_metadata(type_id)
+        .map(|m| m.display_name)
         .unwrap_or_else(|| "NewNode".to_string());
 
     // Create a dummy node instance to get port information
@@ -1843,15 +2521,17 @@ fn add_node_to_graph(graph: &mut NodeGraphDefinition, type_id: &str) -> Result<(
     Ok(())
 }
 
-fn next_node_id(graph: &NodeGraphDefinition) -> String {
-    let mut index = 1usize;
-    loop {
-        let candidate = format!("node_{index}");
-        if !graph.nodes.iter().any(|node| node.id == candidate) {
-            return candidate;
-        }
-        index += 1;
-    }
+/// Whether the position or size of `node_id` differs between two versions of a graph
+fn node_layout_changed(before: &NodeGraphDefinition, after: &NodeGraphDefinition, node_id: &str) -> bool {
+    let layout = |graph: &NodeGraphDefinition| {
+        graph.nodes.iter().find(|n| n.id == node_id).map(|n| {
+            (
+                n.position.as_ref().map(|p| (p.x, p.y)),
+                n.size.as_ref().map(|s| (s.width, s.height)),
+            )
+        })
+    };
+    layout(before) != layout(after)
 }
 
 fn find_port_at(
@@ -2010,9 +2690,12 @@ fn route_edge(
     }
 }
 
+/// Routed segments, corners and labels of every edge. With `show_values`, the label of an
+/// edge whose source node ran also shows the value it passed on.
 fn build_edge_segments(
     graph: &NodeGraphDefinition,
     snap: bool,
+    show_values: bool,
 ) -> (Vec<EdgeSegmentVm>, Vec<EdgeCornerVm>, Vec<EdgeLabelVm>) {
     let mut segments = Vec::new();
     let mut corners = Vec::new();
@@ -2056,9 +2739,16 @@ fn build_edge_segments(
             &mut segments, &mut corners
         );
 
-        let label_text = get_edge_data_type_label(from_node, &edge.from_port)
+        let mut label_text = get_edge_data_type_label(from_node, &edge.from_port)
             .unwrap_or_else(|| "Unknown".to_string());
-        let label_width = (label_text.len() as f32 * 7.0).max(GRID_SIZE * 2.0);
+        let value = graph
+            .execution_results
+            .get(&edge.from_node_id)
+            .and_then(|results| results.get(&edge.from_port));
+        if let Some(value) = value.filter(|_| show_values) {
+            label_text = format!("{label_text} = {}", edge_value_preview(value));
+        }
+        let label_width = (label_text.chars().count() as f32 * 7.0).max(GRID_SIZE * 2.0);
         let label_height = GRID_SIZE * 0.8;
        
         labels.push(EdgeLabelVm {
@@ -2067,6 +2757,7 @@ fn build_edge_segments(
             y: label_y,
             width: label_width,
             height: label_height,
+            edge_index,
         });
         
         edge_index += 1;
@@ -2075,6 +2766,55 @@ fn build_edge_segments(
     (segments, corners, labels)
 }
 
+/// One cubic bezier per edge, between the same port centers as `build_edge_segments`
+fn build_edge_curves(graph: &NodeGraphDefinition, snap: bool) -> Vec<EdgeCurveVm> {
+    let mut curves = Vec::new();
+    let mut edge_index: i32 = 0;
+
+    for edge in &graph.edges {
+        let Some(from_node) = graph.nodes.iter().find(|n| n.id == edge.from_node_id) else {
+            continue;
+        };
+        let Some(to_node) = graph.nodes.iter().find(|n| n.id == edge.to_node_id) else {
+            continue;
+        };
+        let Some((from_x, from_y)) = get_port_center_for_node(from_node, &edge.from_port, false) else {
+            continue;
+        };
+        let Some((to_x, to_y)) = get_port_center_for_node(to_node, &edge.to_port, true) else {
+            continue;
+        };
+
+        let (from_x, from_y, to_x, to_y) = if snap {
+            (
+                snap_to_grid_center(from_x),
+                snap_to_grid_center(from_y),
+                snap_to_grid_center(to_x),
+                snap_to_grid_center(to_y),
+            )
+        } else {
+            (from_x, from_y, to_x, to_y)
+        };
+
+        let (c1, c2) = bezier_control_points((from_x, from_y), (to_x, to_y), GRID_SIZE * 2.0);
+        curves.push(EdgeCurveVm {
+            from_x,
+            from_y,
+            c1_x: c1.0,
+            c1_y: c1.1,
+            c2_x: c2.0,
+            c2_y: c2.1,
+            to_x,
+            to_y,
+            edge_index,
+        });
+
+        edge_index += 1;
+    }
+
+    curves
+}
+
 fn build_edges(
     graph: &NodeGraphDefinition,
     selection_state: &crate::ui::selection::SelectionState,
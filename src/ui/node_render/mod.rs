@@ -4,6 +4,7 @@ pub mod preview_message_list;
 pub mod message_list_data;
 
 use crate::node::graph_io::NodeGraphDefinition;
+use crate::node::DataValue;
 use std::collections::HashMap;
 use serde_json::Value;
 
@@ -27,6 +28,16 @@ pub enum InlinePortValue {
     Json(Value),
 }
 
+impl InlinePortValue {
+    pub fn to_data_value(&self) -> DataValue {
+        match self {
+            InlinePortValue::Text(value) => DataValue::String(value.clone()),
+            InlinePortValue::Bool(value) => DataValue::Boolean(*value),
+            InlinePortValue::Json(value) => DataValue::Json(value.clone()),
+        }
+    }
+}
+
 /// Get preview text for any node with custom rendering
 pub fn get_node_preview_text(
     node_id: &str,
@@ -64,3 +75,79 @@ pub fn has_custom_rendering(node_type: &str) -> bool {
 pub fn inline_port_key(node_id: &str, port_name: &str) -> String {
     format!("{node_id}::{port_name}")
 }
+
+/// Move the inline inputs of node `old_id` to `new_id`, replacing any left behind by an earlier
+/// node called `new_id`. Nothing stays under `old_id`, so a node that later gets that id starts
+/// without them.
+pub fn rename_inline_inputs(inline_inputs: &mut HashMap<String, InlinePortValue>, old_id: &str, new_id: &str) {
+    remove_inline_inputs(inline_inputs, new_id);
+    let old_prefix = inline_port_key(old_id, "");
+    let old_keys: Vec<String> = inline_inputs
+        .keys()
+        .filter(|key| key.starts_with(&old_prefix))
+        .cloned()
+        .collect();
+    for key in old_keys {
+        if let Some(value) = inline_inputs.remove(&key) {
+            inline_inputs.insert(inline_port_key(new_id, &key[old_prefix.len()..]), value);
+        }
+    }
+}
+
+/// Drop the inline inputs of `node_id`, e.g. ones left behind by a deleted node before its id is reused
+pub fn remove_inline_inputs(inline_inputs: &mut HashMap<String, InlinePortValue>, node_id: &str) {
+    let prefix = inline_port_key(node_id, "");
+    inline_inputs.retain(|key, _| !key.starts_with(&prefix));
+}
+
+/// Longest value shown on an edge label, in characters
+const EDGE_VALUE_PREVIEW_CHARS: usize = 32;
+
+/// Preview of a value that passed along an edge, cut off after `EDGE_VALUE_PREVIEW_CHARS`
+/// characters. `Display` escapes line breaks in strings, so it stays on one line.
+pub fn edge_value_preview(value: &DataValue) -> String {
+    let text = value.to_string();
+    if text.chars().count() <= EDGE_VALUE_PREVIEW_CHARS {
+        return text;
+    }
+    let mut preview: String = text.chars().take(EDGE_VALUE_PREVIEW_CHARS - 1).collect();
+    preview.push('…');
+    preview
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rename_inline_inputs_moves_keys_to_new_id() {
+        let mut inline_inputs = HashMap::from([
+            (inline_port_key("node_1", "text"), InlinePortValue::Text("hello".to_string())),
+            (inline_port_key("node_10", "text"), InlinePortValue::Text("other node".to_string())),
+            (inline_port_key("greeting", "stale"), InlinePortValue::Bool(true)),
+        ]);
+
+        rename_inline_inputs(&mut inline_inputs, "node_1", "greeting");
+        assert!(matches!(
+            inline_inputs.get(&inline_port_key("greeting", "text")),
+            Some(InlinePortValue::Text(text)) if text == "hello"
+        ));
+        assert!(!inline_inputs.contains_key(&inline_port_key("greeting", "stale")));
+        assert!(matches!(
+            inline_inputs.get(&inline_port_key("node_10", "text")),
+            Some(InlinePortValue::Text(text)) if text == "other node"
+        ));
+        assert!(!inline_inputs.contains_key(&inline_port_key("node_1", "text")));
+    }
+
+    #[test]
+    fn test_edge_value_preview_truncates_to_one_short_line() {
+        assert_eq!(edge_value_preview(&DataValue::Integer(42)), "42");
+        assert_eq!(edge_value_preview(&DataValue::String("a\nb".to_string())), "\"a\\nb\"");
+
+        let long = edge_value_preview(&DataValue::String("你好".repeat(40)));
+        assert_eq!(long.chars().count(), EDGE_VALUE_PREVIEW_CHARS);
+        assert!(long.starts_with("\"你好"));
+        assert!(long.ends_with('…'));
+    }
+}
@@ -1,7 +1,8 @@
 use crate::node::graph_io::NodeGraphDefinition;
 use crate::node::DataValue;
+use crate::node::util_nodes::resolve_json_path;
 use crate::llm::{Message, MessageRole};
-use super::{NodeRenderer, InlinePortValue};
+use super::{NodeRenderer, InlinePortValue, inline_port_key};
 use std::collections::HashMap;
 
 pub struct PreviewMessageListRenderer;
@@ -10,12 +11,12 @@ impl NodeRenderer for PreviewMessageListRenderer {
     fn get_preview_text(
         node_id: &str,
         graph: &NodeGraphDefinition,
-        _inline_inputs: &HashMap<String, InlinePortValue>,
+        inline_inputs: &HashMap<String, InlinePortValue>,
     ) -> String {
         // Get messages from execution results
         if let Some(results) = graph.execution_results.get(node_id) {
             if let Some(DataValue::MessageList(messages)) = results.get("messages") {
-                return format_message_list(messages);
+                return format_message_list(messages, preview_path(node_id, inline_inputs).as_deref());
             }
         }
 
@@ -27,8 +28,29 @@ impl NodeRenderer for PreviewMessageListRenderer {
     }
 }
 
+/// The inline `preview_path` of a preview node, if one is set
+fn preview_path(node_id: &str, inline_inputs: &HashMap<String, InlinePortValue>) -> Option<String> {
+    match inline_inputs.get(&inline_port_key(node_id, "preview_path")) {
+        Some(InlinePortValue::Text(path)) if !path.trim().is_empty() => Some(path.trim().to_string()),
+        _ => None,
+    }
+}
+
+/// Content shown for a message: the value at `path` when the content is JSON and the
+/// path resolves, otherwise the full content
+fn display_content(content: &str, path: Option<&str>) -> String {
+    let resolved = path.and_then(|path| {
+        let parsed: serde_json::Value = serde_json::from_str(content).ok()?;
+        match resolve_json_path(&parsed, path)? {
+            serde_json::Value::String(s) => Some(s.clone()),
+            other => Some(other.to_string()),
+        }
+    });
+    resolved.unwrap_or_else(|| content.to_string())
+}
+
 /// Format a list of messages as a preview text
-fn format_message_list(messages: &[Message]) -> String {
+fn format_message_list(messages: &[Message], path: Option<&str>) -> String {
     messages.iter()
         .map(|msg| {
             let role_str = match msg.role {
@@ -38,7 +60,7 @@ fn format_message_list(messages: &[Message]) -> String {
                 MessageRole::Tool => "Tool",
             };
             
-            let content = msg.content.as_deref().unwrap_or("");
+            let content = display_content(msg.content.as_deref().unwrap_or(""), path);
             
             format!("[{}] {}", role_str, content)
         })
@@ -50,9 +72,11 @@ fn format_message_list(messages: &[Message]) -> String {
 pub fn get_message_list_data(
     node_id: &str,
     graph: &NodeGraphDefinition,
+    inline_inputs: &HashMap<String, InlinePortValue>,
 ) -> Vec<MessageItem> {
     if let Some(results) = graph.execution_results.get(node_id) {
         if let Some(DataValue::MessageList(messages)) = results.get("messages") {
+            let path = preview_path(node_id, inline_inputs);
             return messages.iter().map(|msg| {
                 let role_str = match msg.role {
                     MessageRole::System => "system",
@@ -63,7 +87,7 @@ pub fn get_message_list_data(
                 
                 MessageItem {
                     role: role_str.to_string(),
-                    content: msg.content.clone().unwrap_or_default(),
+                    content: display_content(msg.content.as_deref().unwrap_or(""), path.as_deref()),
                 }
             }).collect();
         }
@@ -76,3 +100,31 @@ pub struct MessageItem {
     pub role: String,
     pub content: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preview_text_resolves_path_in_json_content() {
+        let mut graph = NodeGraphDefinition::default();
+        graph.execution_results.insert(
+            "preview".to_string(),
+            HashMap::from([(
+                "messages".to_string(),
+                DataValue::MessageList(vec![
+                    Message::user(r#"{"reply": {"items": [{"text": "hello"}]}}"#),
+                    Message::system("plain text"),
+                ]),
+            )]),
+        );
+        let inline_inputs = HashMap::from([(
+            inline_port_key("preview", "preview_path"),
+            InlinePortValue::Text("reply.items[0].text".to_string()),
+        )]);
+
+        let text = PreviewMessageListRenderer::get_preview_text("preview", &graph, &inline_inputs);
+        // Non-JSON content falls back to the full text
+        assert_eq!(text, "[User] hello\n[System] plain text");
+    }
+}
@@ -1,5 +1,4 @@
 use crate::node::graph_io::NodeGraphDefinition;
-use crate::node::DataValue;
 use super::{NodeRenderer, InlinePortValue};
 use std::collections::HashMap;
 
@@ -13,22 +12,17 @@ impl NodeRenderer for PreviewStringRenderer {
     ) -> String {
         // Get preview text from execution results
         if let Some(results) = graph.execution_results.get(node_id) {
-            if let Some(DataValue::String(s)) = results.get("text") {
-                return s.clone();
+            if let Some(value) = results.get("text") {
+                return format!("{:#}", value);
             }
         }
 
         // Fallback to inline input if no execution result
         let key = super::inline_port_key(node_id, "text");
-        if let Some(InlinePortValue::Text(s)) = inline_inputs.get(&key) {
-            return s.clone();
-        }
-
-        if let Some(InlinePortValue::Json(_)) = inline_inputs.get(&key) {
-            return "(json)".to_string();
-        }
-
-        String::new()
+        inline_inputs
+            .get(&key)
+            .map(|value| format!("{:#}", value.to_data_value()))
+            .unwrap_or_default()
     }
     
     fn handles_node_type(node_type: &str) -> bool {
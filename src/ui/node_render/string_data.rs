@@ -11,11 +11,9 @@ impl NodeRenderer for StringDataRenderer {
         inline_inputs: &HashMap<String, InlinePortValue>,
     ) -> String {
         // Get preview text from inline input (the UI text field)
-        let key = inline_port_key(node_id, "text");
+        let key = inline_port_key(node_id, "value");
         match inline_inputs.get(&key) {
-            Some(InlinePortValue::Text(value)) => format!("{}", value),
-            Some(InlinePortValue::Bool(value)) => format!("{}", value),
-            Some(InlinePortValue::Json(_)) => "(json)".to_string(),
+            Some(value) => format!("{:#}", value.to_data_value()),
             None => "(empty...)".to_string(),
         }
     }
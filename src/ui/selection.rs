@@ -35,6 +35,12 @@ impl SelectionState {
         self.selected_node_ids.insert(node_id);
     }
     
+    /// Select exactly `node_ids`, e.g. nodes that were just pasted
+    pub fn select_nodes(&mut self, node_ids: impl IntoIterator<Item = String>) {
+        self.clear();
+        self.selected_node_ids.extend(node_ids);
+    }
+
     pub fn toggle_node_selection(&mut self, node_id: String) {
         if self.selected_node_ids.contains(&node_id) {
             self.selected_node_ids.remove(&node_id);
@@ -2,16 +2,20 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
 
+use crate::ui::edge_curve::EdgeStyle;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WindowState {
     pub width: f32,
     pub height: f32,
     pub x: i32,
     pub y: i32,
+    #[serde(default)]
+    pub edge_style: EdgeStyle,
 }
 
 impl WindowState {
-    pub fn from_window(window: &slint::Window) -> Self {
+    pub fn from_window(window: &slint::Window, edge_style: EdgeStyle) -> Self {
         let size = window.size();
         let position = window.position();
         WindowState {
@@ -19,6 +23,7 @@ impl WindowState {
             height: size.height as f32,
             x: position.x,
             y: position.y,
+            edge_style,
         }
     }
 }
@@ -0,0 +1,123 @@
+use log::warn;
+use reqwest::blocking::{Client, RequestBuilder};
+use reqwest::Method;
+use std::time::Duration;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_MAX_RETRIES: u32 = 2;
+const RETRY_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Status and body of an HTTP response
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+/// Minimal HTTP client used by nodes, so tests can swap in a mock
+pub trait HttpPost: Send + Sync {
+    /// POST `body` to `url`. Any response, including non-2xx, is `Ok`;
+    /// `Err` means no response was received.
+    fn post(&self, url: &str, headers: &[(String, String)], body: &[u8]) -> Result<HttpResponse, String>;
+}
+
+/// GET counterpart of `HttpPost`, with the same `Ok`/`Err` meaning
+pub trait HttpGet: Send + Sync {
+    fn get(&self, url: &str, headers: &[(String, String)]) -> Result<HttpResponse, String>;
+}
+
+/// Request with any method, with the same `Ok`/`Err` meaning as `HttpPost`
+pub trait HttpRequest: Send + Sync {
+    fn request(&self, method: &str, url: &str, headers: &[(String, String)], body: &[u8]) -> Result<HttpResponse, String>;
+}
+
+/// Blocking reqwest client that retries requests which never got a response.
+/// Proxies are taken from the standard HTTP(S)_PROXY / NO_PROXY environment variables.
+pub struct RetryingHttpClient {
+    client: Client,
+    max_retries: u32,
+}
+
+impl RetryingHttpClient {
+    pub fn new() -> Self {
+        Self::with_options(DEFAULT_TIMEOUT, DEFAULT_MAX_RETRIES)
+    }
+
+    pub fn with_options(timeout: Duration, max_retries: u32) -> Self {
+        let client = Client::builder()
+            .timeout(timeout)
+            .build()
+            .expect("Failed to create HTTP client");
+        Self { client, max_retries }
+    }
+
+    /// Default options, but redirects are returned instead of followed, so callers that
+    /// restrict which hosts are reachable can't be bounced elsewhere
+    pub fn without_redirects() -> Self {
+        let client = Client::builder()
+            .timeout(DEFAULT_TIMEOUT)
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .expect("Failed to create HTTP client");
+        Self {
+            client,
+            max_retries: DEFAULT_MAX_RETRIES,
+        }
+    }
+
+    fn send(&self, method: &str, url: &str, make_request: impl Fn() -> RequestBuilder) -> Result<HttpResponse, String> {
+        let mut attempt = 0;
+        loop {
+            match make_request().send() {
+                Ok(response) => {
+                    let status = response.status().as_u16();
+                    let body = response.text().map_err(|e| e.to_string())?;
+                    return Ok(HttpResponse { status, body });
+                }
+                Err(e) if attempt < self.max_retries && (e.is_connect() || e.is_timeout()) => {
+                    attempt += 1;
+                    warn!("[HTTP] {} {} failed ({}), retrying {}/{}", method, url, e, attempt, self.max_retries);
+                    std::thread::sleep(RETRY_BACKOFF * attempt);
+                }
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+    }
+}
+
+fn with_headers(mut request: RequestBuilder, headers: &[(String, String)]) -> RequestBuilder {
+    for (name, value) in headers {
+        request = request.header(name.as_str(), value.as_str());
+    }
+    request
+}
+
+impl Default for RetryingHttpClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HttpPost for RetryingHttpClient {
+    fn post(&self, url: &str, headers: &[(String, String)], body: &[u8]) -> Result<HttpResponse, String> {
+        self.send("POST", url, || with_headers(self.client.post(url).body(body.to_vec()), headers))
+    }
+}
+
+impl HttpGet for RetryingHttpClient {
+    fn get(&self, url: &str, headers: &[(String, String)]) -> Result<HttpResponse, String> {
+        self.send("GET", url, || with_headers(self.client.get(url), headers))
+    }
+}
+
+impl HttpRequest for RetryingHttpClient {
+    fn request(&self, method: &str, url: &str, headers: &[(String, String)], body: &[u8]) -> Result<HttpResponse, String> {
+        let method = Method::from_bytes(method.to_ascii_uppercase().as_bytes())
+            .map_err(|_| format!("invalid HTTP method '{}'", method))?;
+        self.send(method.as_str(), url, || {
+            let request = self.client.request(method.clone(), url);
+            let request = if body.is_empty() { request } else { request.body(body.to_vec()) };
+            with_headers(request, headers)
+        })
+    }
+}
@@ -1,32 +1,217 @@
 use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::sync::Mutex;
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
+use tokio::sync::{mpsc, Mutex};
 use tokio::time::{sleep, Duration};
-use redis::aio::Connection;
+use redis::aio::ConnectionManager;
 use redis::{AsyncCommands};
+use futures_util::StreamExt;
 use log::{info, warn, error, debug};
-use sqlx::mysql::MySqlPool;
-use sqlx::Row;
+use sqlx::mysql::{MySql, MySqlPool, MySqlRow};
+use sqlx::{QueryBuilder, Row};
 use chrono::NaiveDateTime;
 use crate::util::mask_url_credentials;
 use crate::error::Result;
 
 
-struct RedisState {
-    conn: Option<Connection>,
-    use_memory: bool,
-    reconnect_in_progress: bool,
+/// Redis connection shared by all calls, `None` while Redis is unconfigured or hasn't been reached yet
+type RedisSlot = Arc<RwLock<Option<ConnectionManager>>>;
+
+/// Connect to Redis and clear the previous run's messages
+async fn connect_redis(url: &str) -> std::result::Result<ConnectionManager, String> {
+    let client = redis::Client::open(url).map_err(|e| format!("Invalid Redis URL: {}", e))?;
+    let mut conn = client
+        .get_connection_manager()
+        .await
+        .map_err(|e| format!("Failed to connect to Redis {}: {}", mask_url_credentials(url), e))?;
+
+    // Clear all existing messages in Redis on startup
+    match redis::cmd("FLUSHDB").query_async::<_, ()>(&mut conn).await {
+        Ok(_) => info!("[MessageStore] Cleared all existing messages from Redis on startup"),
+        Err(e) => warn!("[MessageStore] Failed to clear Redis on startup: {}", e),
+    }
+    Ok(conn)
+}
+
+/// Keep trying to reach Redis after the first connection attempt failed. Once connected the
+/// connection manager re-establishes dropped connections by itself, so this only runs at startup.
+async fn retry_redis_connect(slot: RedisSlot, url: String, max_attempts: u32, interval_secs: u64) {
+    for attempt in 1..=max_attempts {
+        sleep(Duration::from_secs(interval_secs)).await;
+        match connect_redis(&url).await {
+            Ok(conn) => {
+                info!("[MessageStore] Redis connection succeeded on attempt {}", attempt);
+                *slot.write().unwrap() = Some(conn);
+                return;
+            }
+            Err(e) => error!("[MessageStore] Redis connection attempt {} failed: {}", attempt, e),
+        }
+    }
+    warn!(
+        "[MessageStore] Exhausted Redis connection attempts ({} tries). Continuing with in-memory storage.",
+        max_attempts
+    );
+}
+
+/// Cached message in the in-memory fallback, dropped on read once `expires_at` has passed
+#[derive(Debug, Clone)]
+struct MemoryEntry {
+    value: String,
+    expires_at: Option<Instant>,
+}
+
+impl MemoryEntry {
+    fn new(value: &str, ttl: Option<Duration>) -> Self {
+        Self {
+            value: value.to_string(),
+            expires_at: ttl.map(|ttl| Instant::now() + ttl),
+        }
+    }
+
+    fn is_expired(&self, now: Instant) -> bool {
+        self.expires_at.is_some_and(|at| at <= now)
+    }
+}
+
+/// `SETEX key seconds value` when a TTL is given, plain `SET key value` otherwise.
+/// Redis expiry has one second resolution, so shorter TTLs are rounded up.
+fn set_command(key: &str, value: &str, ttl: Option<Duration>) -> redis::Cmd {
+    match ttl {
+        Some(ttl) => {
+            let secs = (ttl.as_secs() + u64::from(ttl.subsec_nanos() > 0)).max(1);
+            let mut cmd = redis::cmd("SETEX");
+            cmd.arg(key).arg(secs).arg(value);
+            cmd
+        }
+        None => {
+            let mut cmd = redis::cmd("SET");
+            cmd.arg(key).arg(value);
+            cmd
+        }
+    }
+}
+
+/// Schema of the message_record table, matching the alembic migrations plus lookup indexes
+const CREATE_MESSAGE_RECORD_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS message_record (
+    id INT NOT NULL AUTO_INCREMENT,
+    message_id VARCHAR(64) NOT NULL,
+    sender_id VARCHAR(64) NOT NULL,
+    sender_name VARCHAR(128) NOT NULL,
+    send_time DATETIME NOT NULL,
+    group_id VARCHAR(64) NULL,
+    group_name VARCHAR(128) NULL,
+    content VARCHAR(2048) NOT NULL,
+    at_target_list VARCHAR(512) NULL,
+    PRIMARY KEY (id),
+    INDEX idx_message_record_sender_id (sender_id),
+    INDEX idx_message_record_group_id (group_id),
+    INDEX idx_message_record_send_time (send_time)
+)
+"#;
+
+/// Create the message_record table unless it exists. Returns whether it was created.
+async fn ensure_message_record_table(pool: &MySqlPool) -> std::result::Result<bool, String> {
+    let (existing,): (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM information_schema.tables WHERE table_schema = DATABASE() AND table_name = 'message_record'",
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(|e| format!("Failed to look up message_record table: {}", e))?;
+
+    sqlx::query(CREATE_MESSAGE_RECORD_TABLE)
+        .execute(pool)
+        .await
+        .map_err(|e| format!("Failed to create message_record table: {}", e))?;
+    Ok(existing == 0)
+}
+
+const FULLTEXT_INDEX_NAME: &str = "ft_message_record_content";
+
+/// Add the FULLTEXT index on content used by `search_content`, if missing. The ngram parser
+/// is needed for Chinese text, which has no spaces between words; servers without it (e.g.
+/// MariaDB) keep searching with LIKE. Returns whether the index is available.
+async fn ensure_fulltext_index(pool: &MySqlPool) -> bool {
+    let existing = sqlx::query_as::<_, (i64,)>(
+        "SELECT COUNT(*) FROM information_schema.statistics \
+         WHERE table_schema = DATABASE() AND table_name = 'message_record' AND index_name = ?",
+    )
+    .bind(FULLTEXT_INDEX_NAME)
+    .fetch_one(pool)
+    .await;
+    match existing {
+        Ok((count,)) if count > 0 => return true,
+        Ok(_) => {}
+        Err(e) => {
+            warn!("[MessageStore] Failed to look up the message content FULLTEXT index: {}", e);
+            return false;
+        }
+    }
+
+    let alter = format!(
+        "ALTER TABLE message_record ADD FULLTEXT INDEX {} (content) WITH PARSER ngram",
+        FULLTEXT_INDEX_NAME
+    );
+    match sqlx::query(&alter).execute(pool).await {
+        Ok(_) => {
+            info!("[MessageStore] Created FULLTEXT index on message_record.content");
+            true
+        }
+        Err(e) => {
+            warn!("[MessageStore] FULLTEXT index unavailable, content search falls back to LIKE: {}", e);
+            false
+        }
+    }
+}
+
+/// Escape LIKE wildcards so the query matches literally
+fn escape_like(query: &str) -> String {
+    let mut escaped = String::with_capacity(query.len());
+    for c in query.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Buffered payloads per pub/sub subscription before the subscriber task waits for the consumer
+const SUBSCRIPTION_BUFFER: usize = 256;
+
+/// Rows per multi-row INSERT, keeps batches well below MySQL's placeholder limit
+const MYSQL_INSERT_BATCH_SIZE: usize = 500;
+
+/// Insert records with a single multi-row INSERT
+async fn insert_records(pool: &MySqlPool, records: &[MessageRecord]) -> std::result::Result<(), sqlx::Error> {
+    let mut builder = QueryBuilder::<MySql>::new(
+        "INSERT INTO message_record \
+         (message_id, sender_id, sender_name, send_time, group_id, group_name, content, at_target_list) ",
+    );
+    builder.push_values(records, |mut row, record| {
+        row.push_bind(&record.message_id)
+            .push_bind(&record.sender_id)
+            .push_bind(&record.sender_name)
+            .push_bind(record.send_time)
+            .push_bind(&record.group_id)
+            .push_bind(&record.group_name)
+            .push_bind(&record.content)
+            .push_bind(&record.at_target_list);
+    });
+    builder.build().execute(pool).await.map(|_| ())
 }
 
 struct MySqlState {
     pool: Option<MySqlPool>,
     use_memory: bool,
     reconnect_in_progress: bool,
+    /// Whether message_record has the FULLTEXT index on content that `search_content` can use
+    fulltext_index: bool,
 }
 
 /// MessageStore provides Redis-backed message storage with MySQL persistence and in-memory fallback
 pub struct MessageStore {
-    redis_state: Arc<Mutex<RedisState>>,
+    redis: RedisSlot,
     redis_url: Option<String>,
     reconnect_max_attempts: u32,
     reconnect_interval_secs: u64,
@@ -34,8 +219,10 @@ pub struct MessageStore {
     mysql_url: Option<String>,
     mysql_reconnect_max_attempts: u32,
     mysql_reconnect_interval_secs: u64,
-    memory_store: Arc<Mutex<HashMap<String, String>>>,
+    memory_store: Arc<Mutex<HashMap<String, MemoryEntry>>>,
     mysql_memory_store: Arc<Mutex<HashMap<String, MessageRecord>>>,
+    /// Expiry of cached messages stored without an explicit TTL, `None` keeps them forever
+    default_ttl: Option<Duration>,
 }
 
 impl std::fmt::Debug for MessageStore {
@@ -47,6 +234,7 @@ impl std::fmt::Debug for MessageStore {
             .field("mysql_url", &self.mysql_url)
             .field("mysql_reconnect_max_attempts", &self.mysql_reconnect_max_attempts)
             .field("mysql_reconnect_interval_secs", &self.mysql_reconnect_interval_secs)
+            .field("default_ttl", &self.default_ttl)
             .finish_non_exhaustive()
     }
 }
@@ -63,8 +251,70 @@ pub struct MessageRecord {
     pub at_target_list: Option<String>,
 }
 
+impl MessageRecord {
+    fn from_row(row: &MySqlRow) -> Self {
+        Self {
+            message_id: row.get("message_id"),
+            sender_id: row.get("sender_id"),
+            sender_name: row.get("sender_name"),
+            send_time: row.get("send_time"),
+            group_id: row.get("group_id"),
+            group_name: row.get("group_name"),
+            content: row.get("content"),
+            at_target_list: row.get("at_target_list"),
+        }
+    }
+}
+
+/// Filter for `MessageStore::query_records`. Unset fields don't filter; `after` and `before`
+/// are exclusive bounds on `send_time`.
+#[derive(Debug, Clone, Default)]
+pub struct MessageQuery {
+    pub sender_id: Option<String>,
+    pub group_id: Option<String>,
+    pub after: Option<NaiveDateTime>,
+    pub before: Option<NaiveDateTime>,
+    pub limit: Option<u32>,
+}
+
+impl MessageQuery {
+    fn matches(&self, record: &MessageRecord) -> bool {
+        self.sender_id.as_ref().is_none_or(|id| &record.sender_id == id)
+            && self.group_id.as_ref().is_none_or(|id| record.group_id.as_ref() == Some(id))
+            && self.after.is_none_or(|after| record.send_time > after)
+            && self.before.is_none_or(|before| record.send_time < before)
+    }
+
+    /// SELECT for this filter; values are bound, never spliced into the SQL
+    fn build_sql(&self) -> QueryBuilder<'_, MySql> {
+        let mut builder = QueryBuilder::<MySql>::new(
+            "SELECT message_id, sender_id, sender_name, send_time, group_id, group_name, content, at_target_list \
+             FROM message_record WHERE 1 = 1",
+        );
+        if let Some(sender_id) = &self.sender_id {
+            builder.push(" AND sender_id = ").push_bind(sender_id);
+        }
+        if let Some(group_id) = &self.group_id {
+            builder.push(" AND group_id = ").push_bind(group_id);
+        }
+        if let Some(after) = self.after {
+            builder.push(" AND send_time > ").push_bind(after);
+        }
+        if let Some(before) = self.before {
+            builder.push(" AND send_time < ").push_bind(before);
+        }
+        builder.push(" ORDER BY send_time DESC");
+        if let Some(limit) = self.limit {
+            builder.push(" LIMIT ").push_bind(limit);
+        }
+        builder
+    }
+}
+
 impl MessageStore {
-    /// Initialize the message store, try Redis first, MySQL for persistence, fallback to memory
+    /// Initialize the message store, try Redis first, MySQL for persistence, fallback to memory.
+    /// `default_ttl` is the expiry of cached messages (Redis and in-memory) stored by `store_message`;
+    /// MySQL records never expire.
     pub async fn new(
         redis_url: Option<&str>,
         mysql_url: Option<&str>,
@@ -72,6 +322,7 @@ impl MessageStore {
         reconnect_interval_secs: Option<u64>,
         mysql_max_reconnect_attempts: Option<u32>,
         mysql_reconnect_interval_secs: Option<u64>,
+        default_ttl: Option<Duration>,
     ) -> Self {
         let memory_store = Arc::new(Mutex::new(HashMap::new()));
         let mysql_memory_store = Arc::new(Mutex::new(HashMap::new()));
@@ -81,15 +332,12 @@ impl MessageStore {
         let mysql_reconnect_interval_secs = mysql_reconnect_interval_secs.unwrap_or(60);
         let redis_url_owned = redis_url.map(|u| u.to_string());
         let mysql_url_owned = mysql_url.map(|u| u.to_string());
-        let mut redis_state = RedisState {
-            conn: None,
-            use_memory: true,
-            reconnect_in_progress: false,
-        };
+        let redis: RedisSlot = Arc::new(RwLock::new(None));
         let mut mysql_state = MySqlState {
             pool: None,
             use_memory: true,
             reconnect_in_progress: false,
+            fulltext_index: false,
         };
         
         if let Some(url) = mysql_url {
@@ -98,6 +346,12 @@ impl MessageStore {
             match MySqlPool::connect(url).await {
                 Ok(pool) => {
                     info!("[MessageStore] Connected to MySQL at {}", safe_url);
+                    match ensure_message_record_table(&pool).await {
+                        Ok(true) => info!("[MessageStore] Created message_record table"),
+                        Ok(false) => info!("[MessageStore] Found existing message_record table"),
+                        Err(e) => error!("[MessageStore] {}", e),
+                    }
+                    mysql_state.fulltext_index = ensure_fulltext_index(&pool).await;
                     mysql_state.pool = Some(pool);
                     mysql_state.use_memory = false;
                 }
@@ -111,29 +365,20 @@ impl MessageStore {
         }
 
         if let Some(url) = redis_url {
-            let safe_url = mask_url_credentials(url);
-            match redis::Client::open(url) {
-                Ok(client) => match client.get_tokio_connection().await {
-                    Ok(mut conn) => {
-                        info!("[MessageStore] Connected to Redis at {}", safe_url);
-                        
-                        // Clear all existing messages in Redis on startup
-                        match redis::cmd("FLUSHDB").query_async::<_, ()>(&mut conn).await {
-                            Ok(_) => info!("[MessageStore] Cleared all existing messages from Redis on startup"),
-                            Err(e) => warn!("[MessageStore] Failed to clear Redis on startup: {}", e),
-                        }
-                        
-                        redis_state.conn = Some(conn);
-                        redis_state.use_memory = false;
-                    }
-                    Err(e) => {
-                        error!("[MessageStore] Failed to connect to Redis {}: {}", safe_url, e);
-                        warn!("[MessageStore] Falling back to in-memory message store due to Redis connection error.");
-                    }
-                },
+            match connect_redis(url).await {
+                Ok(conn) => {
+                    info!("[MessageStore] Connected to Redis at {}", mask_url_credentials(url));
+                    *redis.write().unwrap() = Some(conn);
+                }
                 Err(e) => {
-                    error!("[MessageStore] Invalid Redis URL: {}", e);
-                    warn!("[MessageStore] Falling back to in-memory message store due to invalid Redis URL.");
+                    error!("[MessageStore] {}", e);
+                    warn!("[MessageStore] Falling back to in-memory message store until Redis is reachable.");
+                    tokio::spawn(retry_redis_connect(
+                        redis.clone(),
+                        url.to_string(),
+                        reconnect_max_attempts,
+                        reconnect_interval_secs,
+                    ));
                 }
             }
         } else {
@@ -141,7 +386,7 @@ impl MessageStore {
         }
 
         Self {
-            redis_state: Arc::new(Mutex::new(redis_state)),
+            redis,
             redis_url: redis_url_owned,
             reconnect_max_attempts,
             reconnect_interval_secs,
@@ -151,9 +396,26 @@ impl MessageStore {
             mysql_reconnect_interval_secs,
             memory_store,
             mysql_memory_store,
+            default_ttl,
         }
     }
 
+    /// Create the message_record table if it doesn't exist yet. Runs automatically when
+    /// `new` connects to MySQL; a no-op without a MySQL connection.
+    pub async fn ensure_schema(&self) -> std::result::Result<(), String> {
+        let mut state = self.mysql_state.lock().await;
+        let Some(pool) = state.pool.as_ref() else {
+            return Ok(());
+        };
+        if ensure_message_record_table(pool).await? {
+            info!("[MessageStore] Created message_record table");
+        } else {
+            debug!("[MessageStore] Found existing message_record table");
+        }
+        state.fulltext_index = ensure_fulltext_index(pool).await;
+        Ok(())
+    }
+
     /// Load recent messages from MySQL into Redis or memory cache on startup
     /// This populates the cache with historical messages for faster access
     pub async fn load_messages_from_mysql(&self, limit: u32) -> Result<u32> {
@@ -183,39 +445,33 @@ impl MessageStore {
         info!("[MessageStore] Loaded {} message records from MySQL", records.len());
         
         let mut loaded_count = 0;
-        let mut redis_state = self.redis_state.lock().await;
-        
+        let mut redis = self.redis();
+
         for row in records {
             let message_id: String = row.get("message_id");
             let content: String = row.get("content");
-            
+
             // Try to store in Redis first, fallback to memory
-            if !redis_state.use_memory {
-                if let Some(conn) = redis_state.conn.as_mut() {
-                    match conn.set::<_, _, ()>(&message_id, &content).await {
-                        Ok(_) => {
-                            loaded_count += 1;
-                            debug!("[MessageStore] Loaded message {} into Redis from MySQL", message_id);
-                        }
-                        Err(e) => {
-                            error!("[MessageStore] Failed to load message {} into Redis: {}", message_id, e);
-                            // Switch to memory and store there
-                            redis_state.use_memory = true;
-                            let mut mem = self.memory_store.lock().await;
-                            mem.insert(message_id.clone(), content.clone());
-                            loaded_count += 1;
-                        }
+            if let Some(conn) = redis.as_mut() {
+                match set_command(&message_id, &content, self.default_ttl).query_async::<_, ()>(conn).await {
+                    Ok(_) => {
+                        loaded_count += 1;
+                        debug!("[MessageStore] Loaded message {} into Redis from MySQL", message_id);
+                        continue;
+                    }
+                    Err(e) => {
+                        error!("[MessageStore] Failed to load message {} into Redis: {}", message_id, e);
+                        // Load the rest into memory
+                        redis = None;
                     }
                 }
-            } else {
-                // Store directly to memory
-                let mut mem = self.memory_store.lock().await;
-                mem.insert(message_id.clone(), content.clone());
-                loaded_count += 1;
-                debug!("[MessageStore] Loaded message {} into memory from MySQL", message_id);
             }
+            let mut mem = self.memory_store.lock().await;
+            mem.insert(message_id.clone(), MemoryEntry::new(&content, self.default_ttl));
+            loaded_count += 1;
+            debug!("[MessageStore] Loaded message {} into memory from MySQL", message_id);
         }
-        
+
         info!("[MessageStore] Successfully loaded {} messages from MySQL into cache", loaded_count);
         Ok(loaded_count)
     }
@@ -228,180 +484,116 @@ impl MessageStore {
         group_id: Option<&str>,
         limit: u32,
     ) -> Result<Vec<MessageRecord>> {
+        let filter = MessageQuery {
+            sender_id: Some(sender_id.to_string()),
+            group_id: group_id.map(str::to_string),
+            limit: Some(limit),
+            ..Default::default()
+        };
+        self.query_records(filter).await.map_err(crate::error::Error::StringError)
+    }
+
+    /// Query message records by sender, group and send time window from MySQL, or the memory
+    /// buffer while MySQL is unavailable. Returns records ordered by send_time DESC.
+    pub async fn query_records(&self, filter: MessageQuery) -> std::result::Result<Vec<MessageRecord>, String> {
         let state = self.mysql_state.lock().await;
-        
-        if state.pool.is_none() {
+
+        let Some(pool) = state.pool.as_ref() else {
             warn!("[MessageStore] No MySQL pool available, checking memory buffer");
-            // Fallback to memory buffer
             let mem = self.mysql_memory_store.lock().await;
-            let mut records: Vec<MessageRecord> = mem.values()
-                .filter(|r| {
-                    r.sender_id == sender_id && 
-                    (group_id.is_none() || r.group_id.as_deref() == group_id)
-                })
-                .cloned()
-                .collect();
-            
+            let mut records: Vec<MessageRecord> = mem.values().filter(|r| filter.matches(r)).cloned().collect();
+
             // Sort by send_time DESC
             records.sort_by(|a, b| b.send_time.cmp(&a.send_time));
-            records.truncate(limit as usize);
-            
+            if let Some(limit) = filter.limit {
+                records.truncate(limit as usize);
+            }
             return Ok(records);
-        }
-        
-        let pool = state.pool.as_ref().unwrap();
-        
-        let records = if let Some(gid) = group_id {
-            // Query with both sender_id and group_id
-            sqlx::query(
-                r#"
-                SELECT message_id, sender_id, sender_name, send_time, group_id, group_name, content, at_target_list
-                FROM message_record
-                WHERE sender_id = ? AND group_id = ?
-                ORDER BY send_time DESC
-                LIMIT ?
-                "#
-            )
-            .bind(sender_id)
-            .bind(gid)
-            .bind(limit)
-            .fetch_all(pool)
-            .await
-            .map_err(|e| crate::string_error!("Failed to query messages by sender and group: {}", e))?
-        } else {
-            // Query by sender_id only
-            sqlx::query(
-                r#"
-                SELECT message_id, sender_id, sender_name, send_time, group_id, group_name, content, at_target_list
-                FROM message_record
-                WHERE sender_id = ?
-                ORDER BY send_time DESC
-                LIMIT ?
-                "#
-            )
-            .bind(sender_id)
-            .bind(limit)
+        };
+
+        let rows = filter
+            .build_sql()
+            .build()
             .fetch_all(pool)
             .await
-            .map_err(|e| crate::string_error!("Failed to query messages by sender: {}", e))?
-        };
-        
-        let mut result = Vec::new();
-        for row in records {
-            result.push(MessageRecord {
-                message_id: row.get("message_id"),
-                sender_id: row.get("sender_id"),
-                sender_name: row.get("sender_name"),
-                send_time: row.get("send_time"),
-                group_id: row.get("group_id"),
-                group_name: row.get("group_name"),
-                content: row.get("content"),
-                at_target_list: row.get("at_target_list"),
-            });
-        }
-        
-        debug!("[MessageStore] Retrieved {} messages for sender {} (group: {:?})", 
-               result.len(), sender_id, group_id);
-        Ok(result)
+            .map_err(|e| format!("Failed to query message records: {}", e))?;
+        let records: Vec<MessageRecord> = rows.iter().map(MessageRecord::from_row).collect();
+
+        debug!("[MessageStore] Retrieved {} message records for {:?}", records.len(), filter);
+        Ok(records)
     }
 
-    async fn schedule_reconnect(&self) {
-        let redis_url = match &self.redis_url {
-            Some(url) => url.clone(),
-            None => {
-                warn!("[MessageStore] No Redis URL available for reconnection attempts.");
-                return;
-            }
+    /// Search stored message content for `query`, optionally within one group, newest first.
+    /// Uses the FULLTEXT index when there is one, and a literal substring match (LIKE) otherwise
+    /// or when the index finds nothing, e.g. for queries shorter than the ngram size.
+    pub async fn search_content(
+        &self,
+        group_id: Option<&str>,
+        query: &str,
+        limit: usize,
+    ) -> std::result::Result<Vec<MessageRecord>, String> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Err("Search query is empty".to_string());
+        }
+
+        let state = self.mysql_state.lock().await;
+        let Some(pool) = state.pool.as_ref() else {
+            let needle = query.to_lowercase();
+            let mem = self.mysql_memory_store.lock().await;
+            let mut records: Vec<MessageRecord> = mem
+                .values()
+                .filter(|r| group_id.is_none_or(|id| r.group_id.as_deref() == Some(id)))
+                .filter(|r| r.content.to_lowercase().contains(&needle))
+                .cloned()
+                .collect();
+            records.sort_by_key(|r| std::cmp::Reverse(r.send_time));
+            records.truncate(limit);
+            return Ok(records);
         };
 
-        {
-            let mut state = self.redis_state.lock().await;
-            if state.reconnect_in_progress {
-                debug!("[MessageStore] Redis reconnection already in progress, skipping new attempt.");
-                return;
+        let limit = limit as u64;
+        if state.fulltext_index {
+            let mut builder = QueryBuilder::<MySql>::new(
+                "SELECT message_id, sender_id, sender_name, send_time, group_id, group_name, content, at_target_list \
+                 FROM message_record WHERE MATCH(content) AGAINST (",
+            );
+            builder.push_bind(query).push(" IN NATURAL LANGUAGE MODE)");
+            if let Some(group_id) = group_id {
+                builder.push(" AND group_id = ").push_bind(group_id);
+            }
+            builder.push(" ORDER BY send_time DESC LIMIT ").push_bind(limit);
+            let rows = builder
+                .build()
+                .fetch_all(pool)
+                .await
+                .map_err(|e| format!("Failed to search message content: {}", e))?;
+            if !rows.is_empty() {
+                return Ok(rows.iter().map(MessageRecord::from_row).collect());
             }
-            state.reconnect_in_progress = true;
         }
 
-        let state = self.redis_state.clone();
-        let max_attempts = self.reconnect_max_attempts;
-        let interval_secs = self.reconnect_interval_secs;
-        let memory_store = self.memory_store.clone();
-
-        tokio::spawn(async move {
-            for attempt in 1..=max_attempts {
-                match redis::Client::open(redis_url.as_str()) {
-                    Ok(client) => match client.get_tokio_connection().await {
-                        Ok(mut conn) => {
-                            info!("[MessageStore] Redis reconnection succeeded on attempt {}", attempt);
-                            
-                            // Migrate in-memory data to Redis
-                            let memory_data = {
-                                let store = memory_store.lock().await;
-                                store.clone()
-                            };
-                            
-                            let mut migrated_count = 0;
-                            let mut failed_count = 0;
-                            for (key, value) in memory_data.iter() {
-                                match conn.set::<_, _, ()>(key, value).await {
-                                    Ok(_) => {
-                                        migrated_count += 1;
-                                        debug!("[MessageStore] Migrated message {} from memory to Redis", key);
-                                    }
-                                    Err(e) => {
-                                        failed_count += 1;
-                                        error!("[MessageStore] Failed to migrate message {} to Redis: {}", key, e);
-                                    }
-                                }
-                            }
-                            
-                            if migrated_count > 0 {
-                                info!("[MessageStore] Successfully migrated {} messages from memory to Redis", migrated_count);
-                            }
-                            if failed_count > 0 {
-                                warn!("[MessageStore] Failed to migrate {} messages to Redis", failed_count);
-                            }
-                            
-                            // Clear memory cache after successful migration
-                            {
-                                let mut store = memory_store.lock().await;
-                                store.clear();
-                                info!("[MessageStore] Cleared in-memory cache after Redis reconnection");
-                            }
-                            
-                            // Update state
-                            let mut guard = state.lock().await;
-                            guard.conn = Some(conn);
-                            guard.use_memory = false;
-                            guard.reconnect_in_progress = false;
-                            return;
-                        }
-                        Err(e) => {
-                            error!(
-                                "[MessageStore] Redis reconnection attempt {} failed: {}",
-                                attempt, e
-                            );
-                        }
-                    },
-                    Err(e) => {
-                        error!("[MessageStore] Invalid Redis URL during reconnection: {}", e);
-                        break;
-                    }
-                }
-
-                if attempt < max_attempts {
-                    sleep(Duration::from_secs(interval_secs)).await;
-                }
-            }
+        let mut builder = QueryBuilder::<MySql>::new(
+            "SELECT message_id, sender_id, sender_name, send_time, group_id, group_name, content, at_target_list \
+             FROM message_record WHERE content LIKE ",
+        );
+        builder.push_bind(format!("%{}%", escape_like(query))).push(" ESCAPE '\\\\'");
+        if let Some(group_id) = group_id {
+            builder.push(" AND group_id = ").push_bind(group_id);
+        }
+        builder.push(" ORDER BY send_time DESC LIMIT ").push_bind(limit);
+        let rows = builder
+            .build()
+            .fetch_all(pool)
+            .await
+            .map_err(|e| format!("Failed to search message content: {}", e))?;
+        Ok(rows.iter().map(MessageRecord::from_row).collect())
+    }
 
-            let mut guard = state.lock().await;
-            guard.reconnect_in_progress = false;
-            warn!(
-                "[MessageStore] Exhausted Redis reconnection attempts ({} tries). Continuing with in-memory storage.",
-                max_attempts
-            );
-        });
+    /// Redis connection for one call. The manager multiplexes concurrent calls over one
+    /// connection, so callers don't wait on each other.
+    fn redis(&self) -> Option<ConnectionManager> {
+        self.redis.read().unwrap().clone()
     }
 
     async fn schedule_mysql_reconnect(&self) {
@@ -516,78 +708,78 @@ impl MessageStore {
         });
     }
 
-    /// Store a message by ID
+    /// Store a message by ID, expiring after the default TTL if one is configured
     pub async fn store_message(&self, message_id: &str, message: &str) {
-        let mut need_reconnect = false;
+        self.store_message_with_ttl(message_id, message, self.default_ttl).await;
+    }
 
-        {
-            let mut state = self.redis_state.lock().await;
-            if !state.use_memory {
-                if let Some(conn) = state.conn.as_mut() {
-                    match conn.set::<_, _, ()>(message_id, message).await {
-                        Ok(_) => {
-                            debug!("[MessageStore] Message stored in Redis: {}", message_id);
-                            return;
-                        }
-                        Err(e) => {
-                            error!("[MessageStore] Failed to store message in Redis: {}", e);
-                            state.use_memory = true;
-                            state.conn = None;
-                            need_reconnect = true;
-                            warn!("[MessageStore] Switching to in-memory message store due to Redis error.");
-                        }
-                    }
-                } else {
-                    state.use_memory = true;
-                    need_reconnect = true;
-                    warn!("[MessageStore] Redis connection missing, switching to in-memory store.");
+    /// Store a message by ID that expires after `ttl`
+    pub async fn store_message_ex(&self, message_id: &str, message: &str, ttl: Duration) {
+        self.store_message_with_ttl(message_id, message, Some(ttl)).await;
+    }
+
+    async fn store_message_with_ttl(&self, message_id: &str, message: &str, ttl: Option<Duration>) {
+        if let Some(mut conn) = self.redis() {
+            match set_command(message_id, message, ttl).query_async::<_, ()>(&mut conn).await {
+                Ok(_) => {
+                    debug!("[MessageStore] Message stored in Redis: {}", message_id);
+                    return;
+                }
+                Err(e) => {
+                    error!("[MessageStore] Failed to store message in Redis: {}", e);
+                    warn!("[MessageStore] Storing message in memory while Redis is unavailable.");
                 }
-            } else if self.redis_url.is_some() && !state.reconnect_in_progress {
-                // Redis is configured but currently unavailable; trigger a reconnection attempt in the background.
-                need_reconnect = true;
             }
         }
-
-        if need_reconnect {
-            self.schedule_reconnect().await;
-        }
         // Fallback to memory
         let mut store = self.memory_store.lock().await;
-        store.insert(message_id.to_string(), message.to_string());
+        store.insert(message_id.to_string(), MemoryEntry::new(message, ttl));
         debug!("[MessageStore] Message stored in memory: {}", message_id);
     }
 
     /// Store a full message record to MySQL
     pub async fn store_message_record(&self, record: &MessageRecord) -> Result<()> {
+        self.persist_records(std::slice::from_ref(record)).await;
+        Ok(())
+    }
+
+    /// Store a batch of message records: multi-row INSERTs into MySQL, and the id → content cache
+    /// filled through one Redis pipeline. Falls back to memory like the single-item methods.
+    /// Returns the number of records stored.
+    pub async fn store_messages(&self, records: &[MessageRecord]) -> std::result::Result<usize, String> {
+        if records.is_empty() {
+            return Ok(0);
+        }
+        self.persist_records(records).await;
+        self.cache_messages(records).await;
+        Ok(records.len())
+    }
+
+    /// Write records to MySQL, buffering whatever couldn't be written in memory
+    async fn persist_records(&self, records: &[MessageRecord]) {
         let mut need_reconnect = false;
+        let mut persisted = 0;
         {
             let mut state = self.mysql_state.lock().await;
             if !state.use_memory {
                 if let Some(pool) = &state.pool {
-                    let result = sqlx::query(
-                r#"
-                INSERT INTO message_record 
-                (message_id, sender_id, sender_name, send_time, group_id, group_name, content, at_target_list)
-                VALUES (?, ?, ?, ?, ?, ?, ?, ?)
-                "#
-            )
-            .bind(&record.message_id)
-            .bind(&record.sender_id)
-            .bind(&record.sender_name)
-            .bind(record.send_time)
-            .bind(&record.group_id)
-            .bind(&record.group_name)
-            .bind(&record.content)
-            .bind(&record.at_target_list)
-            .execute(pool)
-            .await;
-                    match result {
-                        Ok(_) => {
-                            debug!("[MessageStore] Message record persisted to MySQL: {}", record.message_id);
-                            return Ok(());
+                    let mut failure = None;
+                    for chunk in records.chunks(MYSQL_INSERT_BATCH_SIZE) {
+                        match insert_records(pool, chunk).await {
+                            Ok(()) => persisted += chunk.len(),
+                            Err(e) => {
+                                failure = Some(e);
+                                break;
+                            }
+                        }
+                    }
+                    match failure {
+                        None => {
+                            debug!("[MessageStore] {} message record(s) persisted to MySQL", persisted);
+                            return;
                         }
-                        Err(e) => {
-                            error!("[MessageStore] Failed to store message record in MySQL: {}", e);
+                        Some(e) => {
+                            error!("[MessageStore] Failed to store message records in MySQL: {}", e);
                             state.use_memory = true;
                             state.pool = None;
                             need_reconnect = true;
@@ -606,11 +798,38 @@ impl MessageStore {
             self.schedule_mysql_reconnect().await;
         }
 
-        // Store record in memory buffer
+        // Store the records that didn't make it in the memory buffer
         let mut mem = self.mysql_memory_store.lock().await;
-        mem.insert(record.message_id.clone(), record.clone());
-        debug!("[MessageStore] Message record stored in memory buffer: {}", record.message_id);
-        Ok(())
+        for record in &records[persisted..] {
+            mem.insert(record.message_id.clone(), record.clone());
+        }
+        debug!("[MessageStore] {} message record(s) stored in memory buffer", records.len() - persisted);
+    }
+
+    /// Cache the content of records by message id with a single Redis pipeline
+    async fn cache_messages(&self, records: &[MessageRecord]) {
+        if let Some(mut conn) = self.redis() {
+            let mut pipe = redis::pipe();
+            for record in records {
+                pipe.add_command(set_command(&record.message_id, &record.content, self.default_ttl))
+                    .ignore();
+            }
+            match pipe.query_async::<_, ()>(&mut conn).await {
+                Ok(_) => {
+                    debug!("[MessageStore] {} message(s) stored in Redis", records.len());
+                    return;
+                }
+                Err(e) => {
+                    error!("[MessageStore] Failed to store messages in Redis: {}", e);
+                    warn!("[MessageStore] Storing messages in memory while Redis is unavailable.");
+                }
+            }
+        }
+        // Fallback to memory
+        let mut store = self.memory_store.lock().await;
+        for record in records {
+            store.insert(record.message_id.clone(), MemoryEntry::new(&record.content, self.default_ttl));
+        }
     }
 
     /// Retrieve a message record from MySQL by message_id
@@ -630,16 +849,7 @@ impl MessageStore {
             .await;
                 match result {
                     Ok(Some(row)) => {
-                        let record = MessageRecord {
-                            message_id: row.get("message_id"),
-                            sender_id: row.get("sender_id"),
-                            sender_name: row.get("sender_name"),
-                            send_time: row.get("send_time"),
-                            group_id: row.get("group_id"),
-                            group_name: row.get("group_name"),
-                            content: row.get("content"),
-                            at_target_list: row.get("at_target_list"),
-                        };
+                        let record = MessageRecord::from_row(&row);
                         debug!("[MessageStore] Message record retrieved from MySQL: {}", message_id);
                         return Ok(Some(record))
                     }
@@ -662,114 +872,221 @@ impl MessageStore {
 
     /// Get a message by ID
     pub async fn get_message(&self, message_id: &str) -> Option<String> {
-        let mut need_reconnect = false;
+        if let Some(mut conn) = self.redis() {
+            match conn.get::<_, Option<String>>(message_id).await {
+                Ok(Some(val)) => return Some(val),
+                // Might have been stored in memory while Redis was unavailable
+                Ok(None) => {}
+                Err(e) => error!("[MessageStore] Failed to get message from Redis: {}", e),
+            }
+        }
+        // Fallback to memory
+        self.get_memory_message(message_id).await
+    }
+
+    /// Delete a message from the Redis cache, the memory stores and MySQL.
+    /// Returns whether any copy of it existed; fails only if the MySQL delete fails.
+    pub async fn delete_message(&self, message_id: &str) -> std::result::Result<bool, String> {
+        let mut deleted = false;
+
+        if let Some(mut conn) = self.redis() {
+            match conn.del::<_, u64>(message_id).await {
+                Ok(removed) => deleted |= removed > 0,
+                Err(e) => error!("[MessageStore] Failed to delete message from Redis: {}", e),
+            }
+        }
+
+        // The memory stores may hold copies from before a fallback, so always clear them
+        deleted |= self.memory_store.lock().await.remove(message_id).is_some();
+        deleted |= self.mysql_memory_store.lock().await.remove(message_id).is_some();
 
         {
-            let mut state = self.redis_state.lock().await;
-            if !state.use_memory {
-                if let Some(conn) = state.conn.as_mut() {
-                    match conn.get::<_, Option<String>>(message_id).await {
-                        Ok(val) => return val,
-                        Err(e) => {
-                            error!("[MessageStore] Failed to get message from Redis: {}", e);
-                            state.use_memory = true;
-                            state.conn = None;
-                            need_reconnect = true;
-                            warn!("[MessageStore] Switching to in-memory message store due to Redis error.");
+            let state = self.mysql_state.lock().await;
+            if let Some(pool) = state.pool.as_ref() {
+                let result = sqlx::query("DELETE FROM message_record WHERE message_id = ?")
+                    .bind(message_id)
+                    .execute(pool)
+                    .await
+                    .map_err(|e| format!("Failed to delete message record from MySQL: {}", e))?;
+                deleted |= result.rows_affected() > 0;
+            }
+        }
+
+        debug!("[MessageStore] Deleted message {} (found: {})", message_id, deleted);
+        Ok(deleted)
+    }
+
+    /// Count stored message records, optionally of one group. Counts the in-memory record
+    /// buffer while MySQL is unavailable.
+    pub async fn count_messages(&self, group_id: Option<&str>) -> std::result::Result<u64, String> {
+        let state = self.mysql_state.lock().await;
+        let Some(pool) = state.pool.as_ref() else {
+            let mem = self.mysql_memory_store.lock().await;
+            let count = mem
+                .values()
+                .filter(|r| group_id.is_none_or(|id| r.group_id.as_deref() == Some(id)))
+                .count();
+            return Ok(count as u64);
+        };
+
+        let mut builder = QueryBuilder::<MySql>::new("SELECT COUNT(*) FROM message_record");
+        if let Some(group_id) = group_id {
+            builder.push(" WHERE group_id = ").push_bind(group_id);
+        }
+        let (count,): (i64,) = builder
+            .build_query_as()
+            .fetch_one(pool)
+            .await
+            .map_err(|e| format!("Failed to count message records: {}", e))?;
+        Ok(count.max(0) as u64)
+    }
+
+    /// Publish `payload` on a Redis pub/sub channel, e.g. to tell other bot instances a message
+    /// was handled. Returns how many subscribers received it. Pub/sub only exists in Redis, so
+    /// this fails when Redis is not configured or not connected.
+    pub async fn publish(&self, channel: &str, payload: &str) -> std::result::Result<u64, String> {
+        let mut conn = self
+            .redis()
+            .ok_or_else(|| "Redis is not connected, pub/sub is unavailable".to_string())?;
+        conn.publish::<_, _, u64>(channel, payload)
+            .await
+            .map_err(|e| format!("Failed to publish to Redis channel {}: {}", channel, e))
+    }
+
+    /// Subscribe to a Redis pub/sub channel and receive its payloads.
+    ///
+    /// Threading model: a connection in subscribe mode can't run other commands, so each
+    /// subscription opens a dedicated connection instead of using the shared manager. A tokio
+    /// task spawned on the current runtime reads that connection and forwards payloads into the
+    /// returned bounded channel; when the consumer falls behind, the task waits rather than
+    /// buffering without limit. The task, and with it the connection, ends when the receiver is
+    /// dropped. If the connection is lost the receiver yields `None`; subscribe again to resume.
+    pub async fn subscribe(&self, channel: &str) -> std::result::Result<mpsc::Receiver<String>, String> {
+        let url = self
+            .redis_url
+            .as_deref()
+            .ok_or_else(|| "Redis is not configured, pub/sub is unavailable".to_string())?;
+        let client = redis::Client::open(url).map_err(|e| format!("Invalid Redis URL: {}", e))?;
+        let mut pubsub = client
+            .get_tokio_connection()
+            .await
+            .map_err(|e| format!("Failed to open Redis pub/sub connection: {}", e))?
+            .into_pubsub();
+        pubsub
+            .subscribe(channel)
+            .await
+            .map_err(|e| format!("Failed to subscribe to Redis channel {}: {}", channel, e))?;
+
+        let (tx, rx) = mpsc::channel(SUBSCRIPTION_BUFFER);
+        let channel = channel.to_string();
+        tokio::spawn(async move {
+            let mut messages = pubsub.on_message();
+            loop {
+                let msg = tokio::select! {
+                    msg = messages.next() => msg,
+                    _ = tx.closed() => break,
+                };
+                let Some(msg) = msg else {
+                    warn!("[MessageStore] Redis pub/sub connection for channel {} closed", channel);
+                    break;
+                };
+                match msg.get_payload::<String>() {
+                    Ok(payload) => {
+                        if tx.send(payload).await.is_err() {
+                            break;
                         }
                     }
-                } else {
-                    state.use_memory = true;
-                    need_reconnect = true;
-                    warn!("[MessageStore] Redis connection missing, switching to in-memory store.");
+                    Err(e) => error!("[MessageStore] Invalid payload on Redis channel {}: {}", channel, e),
                 }
-            } else if self.redis_url.is_some() && !state.reconnect_in_progress {
-                need_reconnect = true;
             }
-        }
+            debug!("[MessageStore] Subscriber task for channel {} finished", channel);
+        });
+        Ok(rx)
+    }
 
-        if need_reconnect {
-            self.schedule_reconnect().await;
+    /// Read a message from the in-memory fallback, evicting it if it has expired
+    async fn get_memory_message(&self, message_id: &str) -> Option<String> {
+        let mut store = self.memory_store.lock().await;
+        match store.get(message_id) {
+            Some(entry) if entry.is_expired(Instant::now()) => {
+                store.remove(message_id);
+                debug!("[MessageStore] Message expired in memory: {}", message_id);
+                None
+            }
+            Some(entry) => Some(entry.value.clone()),
+            None => None,
         }
-        // Fallback to memory
-        let store = self.memory_store.lock().await;
-        store.get(message_id).cloned()
     }
 
     /// Get a message by ID from Redis, fallback to MySQL, then memory
     pub async fn get_message_with_mysql(&self, message_id: &str) -> Option<String> {
-        let mut need_reconnect = false;
+        self.get_messages(&[message_id]).await.pop().flatten()
+    }
+
+    /// Get several messages at once: one Redis MGET, then MySQL and memory for the ids the
+    /// cache doesn't have. Results are in the order of `ids`, `None` for unknown ids.
+    pub async fn get_messages(&self, ids: &[&str]) -> Vec<Option<String>> {
+        let mut found: Vec<Option<String>> = vec![None; ids.len()];
+        if ids.is_empty() {
+            return found;
+        }
         // Try Redis first
-        {
-            let mut state = self.redis_state.lock().await;
-            if !state.use_memory {
-                if let Some(conn) = state.conn.as_mut() {
-                    match conn.get::<_, Option<String>>(message_id).await {
-                        Ok(val) => {
-                            if val.is_some() {
-                                return val;
-                            }
-                        }
-                        Err(e) => {
-                            error!("[MessageStore] Failed to get message from Redis: {}", e);
-                            state.use_memory = true;
-                            state.conn = None;
-                            need_reconnect = true;
-                            warn!("[MessageStore] Switching to in-memory message store due to Redis error.");
-                        }
-                    }
-                } else {
-                    state.use_memory = true;
-                    need_reconnect = true;
-                    warn!("[MessageStore] Redis connection missing, switching to in-memory store.");
+        if let Some(mut conn) = self.redis() {
+            match redis::cmd("MGET").arg(ids).query_async::<_, Vec<Option<String>>>(&mut conn).await {
+                Ok(values) if values.len() == ids.len() => found = values,
+                Ok(values) => {
+                    warn!("[MessageStore] Redis MGET returned {} values for {} ids", values.len(), ids.len());
                 }
-            } else if self.redis_url.is_some() && !state.reconnect_in_progress {
-                need_reconnect = true;
+                Err(e) => error!("[MessageStore] Failed to get messages from Redis: {}", e),
             }
         }
 
-        if need_reconnect {
-            self.schedule_reconnect().await;
-        }
-
-        // Try MySQL as fallback
+        // Try MySQL for the ids the cache missed
         {
             let state = self.mysql_state.lock().await;
             if let Some(pool) = state.pool.as_ref() {
-                if let Ok(Some(record)) = sqlx::query_as::<_, (String,)>(
-                    "SELECT content FROM message_record WHERE message_id = ? LIMIT 1"
-                )
-                .bind(message_id)
-                .fetch_optional(pool)
-                .await
-                {
-                    debug!("[MessageStore] Message retrieved from MySQL: {}", message_id);
-                    return Some(record.0);
+                for (id, slot) in ids.iter().zip(found.iter_mut()).filter(|(_, slot)| slot.is_none()) {
+                    if let Ok(Some(record)) = sqlx::query_as::<_, (String,)>(
+                        "SELECT content FROM message_record WHERE message_id = ? LIMIT 1"
+                    )
+                    .bind(id)
+                    .fetch_optional(pool)
+                    .await
+                    {
+                        debug!("[MessageStore] Message retrieved from MySQL: {}", id);
+                        *slot = Some(record.0);
+                    }
                 }
             }
         }
 
         // Try memory buffer for MySQL records
-        let mem = self.mysql_memory_store.lock().await;
-        if let Some(rec) = mem.get(message_id) {
-            return Some(rec.content.clone());
+        {
+            let mem = self.mysql_memory_store.lock().await;
+            for (id, slot) in ids.iter().zip(found.iter_mut()).filter(|(_, slot)| slot.is_none()) {
+                *slot = mem.get(*id).map(|rec| rec.content.clone());
+            }
         }
 
         // Fallback to memory
-        let store = self.memory_store.lock().await;
-        store.get(message_id).cloned()
+        for (id, slot) in ids.iter().zip(found.iter_mut()).filter(|(_, slot)| slot.is_none()) {
+            *slot = self.get_memory_message(id).await;
+        }
+        found
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{MessageStore, MessageRecord};
+    use super::{escape_like, set_command, MessageQuery, MessageStore, MessageRecord};
+    use std::sync::Arc;
     use tokio;
+    use tokio::time::Duration;
     use chrono::Local;
 
     #[tokio::test]
     async fn test_memory_store() {
-        let store = MessageStore::new(None, None, None, None, None, None).await;
+        let store = MessageStore::new(None, None, None, None, None, None, None).await;
         store.store_message("id1", "hello").await;
         let val = store.get_message("id1").await;
         assert_eq!(val, Some("hello".to_string()));
@@ -777,13 +1094,237 @@ mod tests {
 
     #[tokio::test]
     async fn test_memory_store_overwrite() {
-        let store = MessageStore::new(None, None, None, None, None, None).await;
+        let store = MessageStore::new(None, None, None, None, None, None, None).await;
         store.store_message("id2", "foo").await;
         store.store_message("id2", "bar").await;
         let val = store.get_message("id2").await;
         assert_eq!(val, Some("bar".to_string()));
     }
 
+    #[tokio::test]
+    async fn test_memory_store_expiry() {
+        let store = MessageStore::new(None, None, None, None, None, None, None).await;
+        store.store_message_ex("id4", "short lived", Duration::from_millis(50)).await;
+        store.store_message("id5", "kept").await;
+        assert_eq!(store.get_message("id4").await, Some("short lived".to_string()));
+
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        assert_eq!(store.get_message("id4").await, None);
+        assert_eq!(store.get_message_with_mysql("id4").await, None);
+        assert_eq!(store.get_message("id5").await, Some("kept".to_string()));
+        // Expired entries are evicted when read
+        assert!(!store.memory_store.lock().await.contains_key("id4"));
+    }
+
+    #[tokio::test]
+    async fn test_default_ttl_applies_to_store_message() {
+        let store = MessageStore::new(None, None, None, None, None, None, Some(Duration::from_millis(50))).await;
+        store.store_message("id6", "hello").await;
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        assert_eq!(store.get_message("id6").await, None);
+    }
+
+    fn command_args(cmd: &redis::Cmd) -> Vec<String> {
+        cmd.args_iter()
+            .map(|arg| match arg {
+                redis::Arg::Simple(bytes) => String::from_utf8_lossy(bytes).to_string(),
+                redis::Arg::Cursor => "<cursor>".to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_redis_uses_setex_for_ttl() {
+        assert_eq!(
+            command_args(&set_command("id7", "hi", Some(Duration::from_secs(3600)))),
+            ["SETEX", "id7", "3600", "hi"]
+        );
+        // Sub-second TTLs round up, Redis can't express them
+        assert_eq!(
+            command_args(&set_command("id7", "hi", Some(Duration::from_millis(1500)))),
+            ["SETEX", "id7", "2", "hi"]
+        );
+        assert_eq!(command_args(&set_command("id7", "hi", None)), ["SET", "id7", "hi"]);
+    }
+
+    fn record(message_id: &str, content: &str) -> MessageRecord {
+        MessageRecord {
+            message_id: message_id.to_string(),
+            sender_id: "user_123".to_string(),
+            sender_name: "Test User".to_string(),
+            send_time: Local::now().naive_local(),
+            group_id: Some("group_456".to_string()),
+            group_name: Some("Test Group".to_string()),
+            content: content.to_string(),
+            at_target_list: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_memory_store_batch() {
+        let store = MessageStore::new(None, None, None, None, None, None, None).await;
+        let records = vec![record("b1", "first"), record("b2", "second"), record("b3", "third")];
+        assert_eq!(store.store_messages(&records).await, Ok(3));
+        assert_eq!(store.store_messages(&[]).await, Ok(0));
+
+        let found = store.get_messages(&["b3", "missing", "b1"]).await;
+        assert_eq!(found, vec![Some("third".to_string()), None, Some("first".to_string())]);
+        assert_eq!(store.get_message_with_mysql("b2").await, Some("second".to_string()));
+        let buffered = store.get_message_record("b2").await.unwrap().unwrap();
+        assert_eq!(buffered.content, "second");
+        assert!(store.get_messages(&[]).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_memory_query_records() {
+        let store = MessageStore::new(None, None, None, None, None, None, None).await;
+        let now = Local::now().naive_local();
+        let mut records = Vec::new();
+        for (i, group) in ["g1", "g1", "g2", "g1"].into_iter().enumerate() {
+            let mut r = record(&format!("q{}", i), &format!("message {}", i));
+            r.group_id = Some(group.to_string());
+            r.send_time = now - chrono::Duration::minutes(10 * i as i64);
+            records.push(r);
+        }
+        store.store_messages(&records).await.unwrap();
+
+        let filter = MessageQuery {
+            group_id: Some("g1".to_string()),
+            after: Some(now - chrono::Duration::minutes(35)),
+            before: Some(now),
+            ..Default::default()
+        };
+        let ids: Vec<String> = store.query_records(filter).await.unwrap().into_iter().map(|r| r.message_id).collect();
+        assert_eq!(ids, ["q1", "q3"]);
+
+        let latest = MessageQuery {
+            limit: Some(2),
+            ..Default::default()
+        };
+        let ids: Vec<String> = store.query_records(latest).await.unwrap().into_iter().map(|r| r.message_id).collect();
+        assert_eq!(ids, ["q0", "q1"]);
+    }
+
+    #[test]
+    fn test_query_sql_binds_values() {
+        let filter = MessageQuery {
+            sender_id: Some("1' OR '1'='1".to_string()),
+            after: Some(Local::now().naive_local()),
+            limit: Some(10),
+            ..Default::default()
+        };
+        let mut builder = filter.build_sql();
+        let sql = builder.sql().to_string();
+        assert!(sql.ends_with("WHERE 1 = 1 AND sender_id = ? AND send_time > ? ORDER BY send_time DESC LIMIT ?"), "{}", sql);
+        assert!(!sql.contains("OR '1'"));
+        let _ = builder.build();
+    }
+
+    #[tokio::test]
+    async fn test_memory_search_content() {
+        let store = MessageStore::new(None, None, None, None, None, None, None).await;
+        let mut other_group = record("s3", "明天去爬山吗");
+        other_group.group_id = Some("group_789".to_string());
+        store
+            .store_messages(&[record("s1", "明天去爬山吗"), record("s2", "今天好热"), other_group])
+            .await
+            .unwrap();
+
+        let found = store.search_content(Some("group_456"), "爬山", 10).await.unwrap();
+        assert_eq!(found.iter().map(|r| r.message_id.as_str()).collect::<Vec<_>>(), ["s1"]);
+        assert_eq!(store.search_content(None, "爬山", 10).await.unwrap().len(), 2);
+        assert!(store.search_content(None, "  ", 10).await.is_err());
+    }
+
+    #[test]
+    fn test_escape_like_wildcards() {
+        assert_eq!(escape_like("100%_done\\"), "100\\%\\_done\\\\");
+        assert_eq!(escape_like("爬山"), "爬山");
+    }
+
+    #[tokio::test]
+    async fn test_memory_delete_message() {
+        let store = MessageStore::new(None, None, None, None, None, None, None).await;
+        store.store_messages(&[record("d1", "to delete"), record("d2", "to keep")]).await.unwrap();
+        store.store_message("d3", "cache only").await;
+
+        assert_eq!(store.delete_message("d1").await, Ok(true));
+        assert_eq!(store.get_message_with_mysql("d1").await, None);
+        assert!(store.get_message_record("d1").await.unwrap().is_none());
+        assert_eq!(store.delete_message("d3").await, Ok(true));
+        assert_eq!(store.get_message("d3").await, None);
+        // Deleting again finds nothing
+        assert_eq!(store.delete_message("d1").await, Ok(false));
+        assert_eq!(store.get_message_with_mysql("d2").await, Some("to keep".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_memory_count_messages() {
+        let store = MessageStore::new(None, None, None, None, None, None, None).await;
+        let mut other_group = record("c3", "c");
+        other_group.group_id = Some("group_789".to_string());
+        store.store_messages(&[record("c1", "a"), record("c2", "b"), other_group]).await.unwrap();
+
+        assert_eq!(store.count_messages(None).await, Ok(3));
+        assert_eq!(store.count_messages(Some("group_456")).await, Ok(2));
+        assert_eq!(store.count_messages(Some("nobody")).await, Ok(0));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_get_message() {
+        // Runs against Redis when ZIHUAN_TEST_REDIS_URL is set, the memory store otherwise.
+        // Connecting flushes the database, so point it at a throwaway instance.
+        let redis_url = std::env::var("ZIHUAN_TEST_REDIS_URL").ok();
+        let store = Arc::new(MessageStore::new(redis_url.as_deref(), None, Some(1), Some(1), None, None, None).await);
+        for i in 0..50 {
+            store.store_message(&format!("p{}", i), &format!("parallel {}", i)).await;
+        }
+
+        let tasks: Vec<_> = (0..500)
+            .map(|i| {
+                let store = store.clone();
+                tokio::spawn(async move {
+                    let id = i % 50;
+                    assert_eq!(store.get_message(&format!("p{}", id)).await, Some(format!("parallel {}", id)));
+                })
+            })
+            .collect();
+        for task in tasks {
+            tokio::time::timeout(Duration::from_secs(10), task).await.unwrap().unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pubsub_needs_redis() {
+        let store = MessageStore::new(None, None, None, None, None, None, None).await;
+        assert!(store.publish("zihuan:events", "handled:1").await.is_err());
+        assert!(store.subscribe("zihuan:events").await.is_err());
+    }
+
+    // To test Redis pub/sub, set ZIHUAN_TEST_REDIS_URL to a throwaway Redis instance;
+    // connecting flushes its database
+    #[tokio::test]
+    async fn test_redis_pubsub() {
+        let redis_url = std::env::var("ZIHUAN_TEST_REDIS_URL").ok();
+        if redis_url.is_none() {
+            // Skip if no Redis URL
+            return;
+        }
+        // Two stores stand in for two bot instances
+        let publisher = MessageStore::new(redis_url.as_deref(), None, Some(3), Some(1), None, None, None).await;
+        let subscriber = MessageStore::new(redis_url.as_deref(), None, Some(3), Some(1), None, None, None).await;
+        let mut rx = subscriber.subscribe("zihuan:test:events").await.unwrap();
+
+        assert_eq!(publisher.publish("zihuan:test:events", "handled:42").await, Ok(1));
+        let payload = tokio::time::timeout(Duration::from_secs(5), rx.recv()).await.unwrap();
+        assert_eq!(payload, Some("handled:42".to_string()));
+
+        // Dropping the receiver ends the subscription
+        drop(rx);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(publisher.publish("zihuan:test:events", "handled:43").await, Ok(0));
+    }
+
     // To test Redis, set REDIS_URL env var to a running Redis instance
     #[tokio::test]
     async fn test_redis_store() {
@@ -792,7 +1333,7 @@ mod tests {
             // Skip if no Redis URL
             return;
         }
-        let store = MessageStore::new(redis_url.as_deref(), None, Some(3), Some(1), None, None).await;
+        let store = MessageStore::new(redis_url.as_deref(), None, Some(3), Some(1), None, None, None).await;
         store.store_message("id3", "redis_test").await;
         let val = store.get_message("id3").await;
         assert_eq!(val, Some("redis_test".to_string()));
@@ -806,7 +1347,7 @@ mod tests {
             // Skip if no MySQL URL
             return;
         }
-        let store = MessageStore::new(None, mysql_url.as_deref(), None, None, Some(3), Some(1)).await;
+        let store = MessageStore::new(None, mysql_url.as_deref(), None, None, Some(3), Some(1), None).await;
         let record = MessageRecord {
             message_id: "test_msg_001".to_string(),
             sender_id: "user_123".to_string(),
@@ -825,4 +1366,148 @@ mod tests {
         assert!(retrieved.is_ok());
         assert!(retrieved.unwrap().is_some());
     }
+
+    // To test MySQL, set DATABASE_URL env var to a running MySQL instance
+    #[tokio::test]
+    async fn test_mysql_store_batch() {
+        let mysql_url = std::env::var("DATABASE_URL").ok();
+        if mysql_url.is_none() {
+            // Skip if no MySQL URL
+            return;
+        }
+        let store = MessageStore::new(None, mysql_url.as_deref(), None, None, Some(3), Some(1), None).await;
+        let records = vec![
+            record("test_batch_001", "First batch message"),
+            record("test_batch_002", "Second batch message"),
+        ];
+
+        let result = store.store_messages(&records).await;
+        assert_eq!(result, Ok(2));
+
+        let retrieved = store.get_message_record("test_batch_002").await;
+        assert!(retrieved.is_ok());
+        assert!(retrieved.unwrap().is_some());
+        let contents = store.get_messages(&["test_batch_001", "test_batch_002"]).await;
+        assert_eq!(
+            contents,
+            vec![Some("First batch message".to_string()), Some("Second batch message".to_string())]
+        );
+    }
+
+    // To test MySQL, set DATABASE_URL env var to a running MySQL instance
+    #[tokio::test]
+    async fn test_mysql_query_records() {
+        let mysql_url = std::env::var("DATABASE_URL").ok();
+        if mysql_url.is_none() {
+            // Skip if no MySQL URL
+            return;
+        }
+        let store = MessageStore::new(None, mysql_url.as_deref(), None, None, Some(3), Some(1), None).await;
+        // A group id of its own keeps rows from other runs out of the result
+        let group_id = format!("query_test_{}", Local::now().timestamp_millis());
+        let now = Local::now().naive_local();
+        let mut records = Vec::new();
+        for i in 0..4 {
+            let mut r = record(&format!("{}_{}", group_id, i), &format!("message {}", i));
+            r.group_id = Some(group_id.clone());
+            r.send_time = now - chrono::Duration::minutes(10 * i);
+            records.push(r);
+        }
+        store.store_messages(&records).await.unwrap();
+
+        let filter = MessageQuery {
+            group_id: Some(group_id.clone()),
+            after: Some(now - chrono::Duration::minutes(25)),
+            before: Some(now - chrono::Duration::minutes(5)),
+            ..Default::default()
+        };
+        let result = store.query_records(filter).await.unwrap();
+        let ids: Vec<String> = result.into_iter().map(|r| r.message_id).collect();
+        assert_eq!(ids, [format!("{}_1", group_id), format!("{}_2", group_id)]);
+    }
+
+    // To test MySQL, set DATABASE_URL env var to a running MySQL instance.
+    // Drops message_record, so only point it at a scratch database.
+    #[tokio::test]
+    async fn test_mysql_ensure_schema_recreates_table() {
+        let mysql_url = std::env::var("DATABASE_URL").ok();
+        if mysql_url.is_none() {
+            // Skip if no MySQL URL
+            return;
+        }
+        let store = MessageStore::new(None, mysql_url.as_deref(), None, None, Some(3), Some(1), None).await;
+        {
+            let state = store.mysql_state.lock().await;
+            let pool = state.pool.as_ref().unwrap();
+            sqlx::query("DROP TABLE IF EXISTS message_record").execute(pool).await.unwrap();
+        }
+
+        assert_eq!(store.ensure_schema().await, Ok(()));
+        // Running it again on an existing table is fine
+        assert_eq!(store.ensure_schema().await, Ok(()));
+
+        let result = store.store_message_record(&record("schema_test_001", "after recreate")).await;
+        assert!(result.is_ok());
+        let retrieved = store.get_message_record("schema_test_001").await.unwrap();
+        assert_eq!(retrieved.map(|r| r.content), Some("after recreate".to_string()));
+    }
+
+    // To test MySQL, set DATABASE_URL env var to a running MySQL instance
+    #[tokio::test]
+    async fn test_mysql_search_content() {
+        let mysql_url = std::env::var("DATABASE_URL").ok();
+        if mysql_url.is_none() {
+            // Skip if no MySQL URL
+            return;
+        }
+        let store = MessageStore::new(None, mysql_url.as_deref(), None, None, Some(3), Some(1), None).await;
+        // A group id of its own keeps rows from other runs out of the result
+        let group_id = format!("search_test_{}", Local::now().timestamp_millis());
+        let mut records = Vec::new();
+        for (i, content) in ["周末一起去爬山吧", "今天的会议改到下午", "rust async is great"].into_iter().enumerate() {
+            let mut r = record(&format!("{}_{}", group_id, i), content);
+            r.group_id = Some(group_id.clone());
+            records.push(r);
+        }
+        store.store_messages(&records).await.unwrap();
+
+        let found = store.search_content(Some(&group_id), "爬山", 10).await.unwrap();
+        assert_eq!(found.iter().map(|r| r.content.as_str()).collect::<Vec<_>>(), ["周末一起去爬山吧"]);
+        let found = store.search_content(Some(&group_id), "async", 10).await.unwrap();
+        assert_eq!(found.len(), 1);
+
+        // Injection attempts and wildcards are matched literally
+        for query in ["' OR '1'='1", "%", "_", "\\", "'); DROP TABLE message_record; --"] {
+            let found = store.search_content(Some(&group_id), query, 10).await.unwrap();
+            assert!(found.is_empty(), "{} matched {:?}", query, found);
+        }
+        assert!(store.get_message_record(&format!("{}_0", group_id)).await.unwrap().is_some());
+    }
+
+    // To test MySQL, set DATABASE_URL env var to a running MySQL instance
+    #[tokio::test]
+    async fn test_mysql_delete_and_count() {
+        let mysql_url = std::env::var("DATABASE_URL").ok();
+        if mysql_url.is_none() {
+            // Skip if no MySQL URL
+            return;
+        }
+        let store = MessageStore::new(None, mysql_url.as_deref(), None, None, Some(3), Some(1), None).await;
+        // A group id of its own keeps rows from other runs out of the count
+        let group_id = format!("count_test_{}", Local::now().timestamp_millis());
+        let mut records = Vec::new();
+        for i in 0..3 {
+            let mut r = record(&format!("{}_{}", group_id, i), "count me");
+            r.group_id = Some(group_id.clone());
+            records.push(r);
+        }
+        store.store_messages(&records).await.unwrap();
+        assert_eq!(store.count_messages(Some(&group_id)).await, Ok(3));
+        assert!(store.count_messages(None).await.unwrap() >= 3);
+
+        assert_eq!(store.delete_message(&format!("{}_0", group_id)).await, Ok(true));
+        assert_eq!(store.delete_message(&format!("{}_0", group_id)).await, Ok(false));
+        assert_eq!(store.count_messages(Some(&group_id)).await, Ok(2));
+        assert!(store.get_message_record(&format!("{}_0", group_id)).await.unwrap().is_none());
+    }
 }